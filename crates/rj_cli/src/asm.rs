@@ -0,0 +1,152 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use rj_core::asm::class_text::assemble;
+
+const USAGE: &str = "\
+Usage: rj asm <options> <file>
+where possible options include:
+  -h  -help  --help  -?    Print this help message
+  -o <file>                Write the class to <file>
+
+Assembles a class from the text `rj dis` prints: the constant pool entry by
+entry, then the class, its fields and its methods, whose code is written as
+instructions and labels. The class is written to <file>, or else to its
+name under the current directory, like com/example/Foo.class
+";
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    input: String,
+    output: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Assemble(Args),
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut output = None;
+    let mut inputs = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "-help" | "--help" | "-?" => return Ok(Command::Help),
+            "-o" => match args.next() {
+                Some(path) => output = Some(path.clone()),
+                None => return Err("-o requires a file".to_string()),
+            },
+            flag if flag.starts_with('-') => return Err(format!("unknown option: {flag}")),
+            input => inputs.push(input.to_string()),
+        }
+    }
+    match <[String; 1]>::try_from(inputs) {
+        Ok([input]) => Ok(Command::Assemble(Args { input, output })),
+        Err(_) => Err("expected one file to assemble".to_string()),
+    }
+}
+
+// the class file's bytes, with the path they go to by default
+fn assemble_file(input: &str) -> Result<(PathBuf, Vec<u8>), String> {
+    let text = fs::read_to_string(input).map_err(|error| format!("{input}: {error}"))?;
+    let classfile = assemble(&text).map_err(|error| format!("{input}: {error}"))?;
+    let name = classfile
+        .constant_pool
+        .class_name(classfile.this_class)
+        .map_err(|error| format!("{input}: {error}"))?;
+    let path = PathBuf::from(format!("{name}.class"));
+    let data = classfile
+        .to_bytes()
+        .map_err(|error| format!("{input}: {error}"))?;
+    Ok((path, data))
+}
+
+fn write_class(path: &Path, data: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+    }
+    fs::write(path, data).map_err(|error| error.to_string())
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let args = match parse_args(args) {
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Assemble(args)) => args,
+        Err(error) => {
+            eprint!("Error: {error}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let (path, data) = match assemble_file(&args.input) {
+        Ok(assembled) => assembled,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let path = args.output.map(PathBuf::from).unwrap_or(path);
+    match write_class(&path, &data) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {}: {error}", path.display());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use rj_core::asm::class_text::disassemble;
+
+    use super::*;
+    use crate::test_support::{args, fixture};
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args(&["Foo.j", "-o", "out/Foo.class"])),
+            Ok(Command::Assemble(Args {
+                input: "Foo.j".to_string(),
+                output: Some("out/Foo.class".to_string()),
+            }))
+        );
+        assert_eq!(parse_args(&args(&["Foo.j", "-?"])), Ok(Command::Help));
+        assert_eq!(
+            parse_args(&args(&["Foo.j", "-o"])),
+            Err("-o requires a file".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["Foo.j", "Bar.j"])),
+            Err("expected one file to assemble".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["-x", "Foo.j"])),
+            Err("unknown option: -x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_assemble_file() {
+        let data = fs::read(fixture("Point.class")).unwrap();
+        let input = env::temp_dir().join(format!("rj-asm-test-{}.j", std::process::id()));
+        fs::write(&input, disassemble(&data).unwrap()).unwrap();
+        let assembled = assemble_file(input.to_str().unwrap());
+        fs::remove_file(&input).unwrap();
+        assert_eq!(assembled, Ok((PathBuf::from("Point.class"), data)));
+
+        let missing = fixture("Missing.j");
+        let error = fs::read(&missing).unwrap_err();
+        assert_eq!(assemble_file(&missing), Err(format!("{missing}: {error}")));
+    }
+}
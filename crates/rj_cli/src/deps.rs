@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::process::ExitCode;
+
+use rj_core::analysis::{is_jdk_internal_package, is_jdk_package, package_name};
+use rj_core::archive::{read_class, Jmod, ZipArchive};
+use rj_core::class::parse_classfile;
+
+const USAGE: &str = "\
+Usage: rj deps <options> <classes>
+where possible options include:
+  -h  -help  --help  -?    Print this help message
+  -v  -verbose             Print the classes that use each dependency
+  --exclude-jdk            Leave out classes in the JDK's packages
+  --jdk-internals          Print only classes in the JDK's internal
+                           packages, like sun.misc and jdk.internal
+
+Lists the classes that the given classes refer to in their constant pools,
+descriptors, signatures and annotations, grouped by package. Each class
+may be a class file, a class file in a jar like app.jar!com/example/Foo.class,
+or a whole jar or jmod. Classes among the given ones aren't listed. The
+exit code is 1 if any class couldn't be read
+";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Filter {
+    All,
+    ExcludeJdk,
+    JdkInternals,
+}
+
+impl Filter {
+    fn accepts(self, package: &str) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::ExcludeJdk => !is_jdk_package(package),
+            Filter::JdkInternals => is_jdk_internal_package(package),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    verbose: bool,
+    filter: Filter,
+    classes: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Deps(Args),
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut verbose = false;
+    let mut filter = Filter::All;
+    let mut classes = Vec::new();
+    for arg in args {
+        let next = match arg.as_str() {
+            "-h" | "-help" | "--help" | "-?" => return Ok(Command::Help),
+            "-v" | "-verbose" => {
+                verbose = true;
+                continue;
+            }
+            "--exclude-jdk" => Filter::ExcludeJdk,
+            "--jdk-internals" => Filter::JdkInternals,
+            flag if flag.starts_with('-') => return Err(format!("unknown option: {flag}")),
+            class => {
+                classes.push(class.to_string());
+                continue;
+            }
+        };
+        if filter != Filter::All && filter != next {
+            return Err("--exclude-jdk and --jdk-internals can't be used together".to_string());
+        }
+        filter = next;
+    }
+    if classes.is_empty() {
+        return Err("no classes were specified".to_string());
+    }
+    Ok(Command::Deps(Args {
+        verbose,
+        filter,
+        classes,
+    }))
+}
+
+// the classes read so far, by internal name, with what each depends on
+#[derive(Debug, Default)]
+struct Dependencies {
+    classes: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Dependencies {
+    fn add(&mut self, data: &[u8]) -> Result<(), String> {
+        let (_, classfile) = parse_classfile(data).map_err(|error| error.to_string())?;
+        let name = classfile
+            .constant_pool
+            .class_name(classfile.this_class)
+            .map_err(|error| error.to_string())?;
+        self.classes
+            .insert(name.into_owned(), classfile.dependencies());
+        Ok(())
+    }
+
+    // the dependencies outside the classes read that the filter lets
+    // through, with the classes that use each
+    fn external(&self, filter: Filter) -> BTreeMap<&str, BTreeSet<&str>> {
+        let mut external = BTreeMap::<_, BTreeSet<_>>::new();
+        for (class, dependencies) in &self.classes {
+            for dependency in dependencies {
+                if !self.classes.contains_key(dependency)
+                    && filter.accepts(package_name(dependency))
+                {
+                    external
+                        .entry(dependency.as_str())
+                        .or_default()
+                        .insert(class.as_str());
+                }
+            }
+        }
+        external
+    }
+}
+
+fn java_name(name: &str) -> String {
+    name.replace('/', ".")
+}
+
+// each package on a line of its own, followed by its classes and, when
+// verbose, the classes that use them
+fn format_dependencies(external: &BTreeMap<&str, BTreeSet<&str>>, verbose: bool) -> String {
+    let mut output = String::new();
+    let mut last_package = None;
+    for (dependency, users) in external {
+        let package = package_name(dependency);
+        if last_package != Some(package) {
+            match package {
+                "" => output.push_str("<unnamed package>\n"),
+                package => output.push_str(&format!("{}\n", java_name(package))),
+            }
+            last_package = Some(package);
+        }
+        output.push_str(&format!("  {}\n", java_name(dependency)));
+        if verbose {
+            for user in users {
+                output.push_str(&format!("    <- {}\n", java_name(user)));
+            }
+        }
+    }
+    output
+}
+
+// a class's name for error messages, and its bytes if they could be read
+pub(crate) type ClassData = (String, Result<Vec<u8>, String>);
+
+// the classes in a class file, an `archive!entry`, or a whole jar or jmod
+pub(crate) fn read_classes(path: &str) -> Result<Vec<ClassData>, String> {
+    if path.contains('!') {
+        let (_, data) = read_class(path).map_err(|error| error.to_string())?;
+        return Ok(vec![(path.to_string(), Ok(data))]);
+    }
+    let data = fs::read(path).map_err(|error| format!("{path}: {error}"))?;
+    if data.starts_with(&[0xca, 0xfe, 0xba, 0xbe]) {
+        return Ok(vec![(path.to_string(), Ok(data))]);
+    }
+    let archive = match data.starts_with(b"JM\x01\x00") {
+        true => Jmod::new(data).map(Jmod::into_archive),
+        false => ZipArchive::new(data),
+    }
+    .map_err(|error| format!("{path}: {error}"))?;
+    Ok(archive
+        .classes()
+        .map(|(entry, data)| {
+            let data = data.map_err(|error| error.to_string());
+            (format!("{path}!{}", entry.name), data)
+        })
+        .collect())
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let args = match parse_args(args) {
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Deps(args)) => args,
+        Err(error) => {
+            eprint!("Error: {error}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut dependencies = Dependencies::default();
+    let mut failed = false;
+    for path in &args.classes {
+        let classes = match read_classes(path) {
+            Ok(classes) => classes,
+            Err(error) => {
+                failed = true;
+                eprintln!("Error: {error}");
+                continue;
+            }
+        };
+        for (name, data) in classes {
+            if let Err(error) = data.and_then(|data| dependencies.add(&data)) {
+                failed = true;
+                eprintln!("Error: {name}: {error}");
+            }
+        }
+    }
+    let external = dependencies.external(args.filter);
+    print!("{}", format_dependencies(&external, args.verbose));
+    match failed {
+        true => ExitCode::FAILURE,
+        false => ExitCode::SUCCESS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{args, fixture};
+
+    fn read_dependencies(paths: &[&str]) -> Dependencies {
+        let mut dependencies = Dependencies::default();
+        for path in paths {
+            for (_, data) in read_classes(&fixture(path)).unwrap() {
+                dependencies.add(&data.unwrap()).unwrap();
+            }
+        }
+        dependencies
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args(&["-v", "--exclude-jdk", "a.jar", "Foo.class"])),
+            Ok(Command::Deps(Args {
+                verbose: true,
+                filter: Filter::ExcludeJdk,
+                classes: args(&["a.jar", "Foo.class"]),
+            }))
+        );
+        assert_eq!(parse_args(&args(&["a.jar", "-?"])), Ok(Command::Help));
+        assert_eq!(
+            parse_args(&args(&["--exclude-jdk", "--jdk-internals", "a.jar"])),
+            Err("--exclude-jdk and --jdk-internals can't be used together".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["-v"])),
+            Err("no classes were specified".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["-x", "a.jar"])),
+            Err("unknown option: -x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_dependencies() {
+        let dependencies = read_dependencies(&["HelloWorld.class"]);
+        let external = dependencies.external(Filter::All);
+        assert_eq!(
+            format_dependencies(&external, false),
+            "java.io\n  java.io.PrintStream\n\
+             java.lang\n  java.lang.Object\n  java.lang.String\n  java.lang.System\n"
+        );
+        assert_eq!(dependencies.external(Filter::ExcludeJdk), BTreeMap::new());
+        assert_eq!(dependencies.external(Filter::JdkInternals), BTreeMap::new());
+
+        let dependencies = read_dependencies(&["HelloWorld.class", "Point.class"]);
+        let output = format_dependencies(&dependencies.external(Filter::All), true);
+        assert!(output.contains("  java.lang.Object\n    <- HelloWorld\n    <- Point\n"));
+    }
+
+    #[test]
+    fn test_read_classes() {
+        // the jar's own classes aren't listed, Outer is only in the
+        // unnamed package because it isn't in the jar
+        let dependencies = read_dependencies(&["classes.jar"]);
+        let external = dependencies.external(Filter::ExcludeJdk);
+        assert_eq!(
+            format_dependencies(&external, true),
+            "<unnamed package>\n  Outer\n    <- Outer$Inner\n"
+        );
+
+        let classes = read_classes(&format!("{}!Point.class", fixture("classes.jar"))).unwrap();
+        assert_eq!(classes.len(), 1);
+        let classes = read_classes(&fixture("demo.jmod")).unwrap();
+        assert_eq!(classes.len(), 2);
+        assert!(read_classes(&fixture("missing.jar")).is_err());
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::process::ExitCode;
+
+use rj_core::archive::{read_class, Jmod, ZipArchive};
+use rj_core::class::{parse_classfile, ClassFile};
+use rj_core::print::{ArchiveDiff, ClassDiff};
+
+const USAGE: &str = "\
+Usage: rj diff <options> <old> <new>
+where possible options include:
+  -h  -help  --help  -?    Print this help message
+  --json                   Print the differences as JSON
+
+Compares two class files, or every class in two jars or jmods, by their
+members, code and constants rather than their bytes. Each class may be a
+class file or a class file in a jar like app.jar!com/example/Foo.class.
+The exit code is 0 if they match, 1 if they differ and 2 on errors
+";
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    json: bool,
+    old: String,
+    new: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Diff(Args),
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut json = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "-help" | "--help" | "-?" => return Ok(Command::Help),
+            "--json" => json = true,
+            flag if flag.starts_with('-') => return Err(format!("unknown option: {flag}")),
+            path => paths.push(path.to_string()),
+        }
+    }
+    match <[String; 2]>::try_from(paths) {
+        Ok([old, new]) => Ok(Command::Diff(Args { json, old, new })),
+        Err(_) => Err("expected an old and a new class or archive".to_string()),
+    }
+}
+
+fn parse_class<'a>(name: &str, data: &'a [u8]) -> Result<ClassFile<'a>, String> {
+    let (_, class) = parse_classfile(data).map_err(|error| format!("{name}: {error}"))?;
+    Ok(class)
+}
+
+// the classes `old` and `new`, each with the name its errors are reported by
+fn diff_classes(old: (&str, &[u8]), new: (&str, &[u8])) -> Result<ClassDiff, String> {
+    let (old, new) = (parse_class(old.0, old.1)?, parse_class(new.0, new.1)?);
+    old.diff(&new).map_err(|error| error.to_string())
+}
+
+// the classes in a jar or jmod, by internal name like `com/example/Foo`
+fn archive_classes(data: Vec<u8>) -> Result<BTreeMap<String, Vec<u8>>, String> {
+    let mut classes = BTreeMap::new();
+    let mut insert = |name: &str, data| {
+        let name = name.strip_suffix(".class").unwrap_or(name).to_string();
+        classes.insert(name, data);
+    };
+    if data.starts_with(b"JM\x01\x00") {
+        let jmod = Jmod::new(data).map_err(|error| error.to_string())?;
+        for (name, data) in jmod.classes() {
+            insert(name, data.map_err(|error| format!("{name}: {error}"))?);
+        }
+    } else {
+        let archive = ZipArchive::new(data).map_err(|error| error.to_string())?;
+        for (entry, data) in archive.classes() {
+            insert(
+                &entry.name,
+                data.map_err(|error| format!("{}: {error}", entry.name))?,
+            );
+        }
+    }
+    Ok(classes)
+}
+
+// classes that are byte for byte the same are taken to match without being
+// parsed
+fn diff_archives(old: Vec<u8>, new: Vec<u8>) -> Result<ArchiveDiff, String> {
+    let old = archive_classes(old)?;
+    let new = archive_classes(new)?;
+    let mut diff = ArchiveDiff::default();
+    for (name, old_data) in &old {
+        match new.get(name) {
+            None => diff.removed.push(name.clone()),
+            Some(new_data) if new_data == old_data => {}
+            Some(new_data) => {
+                let class_diff = diff_classes((name, old_data), (name, new_data))?;
+                if !class_diff.is_empty() {
+                    diff.changed.push(class_diff);
+                }
+            }
+        }
+    }
+    diff.added = new
+        .keys()
+        .filter(|name| !old.contains_key(*name))
+        .cloned()
+        .collect();
+    Ok(diff)
+}
+
+// a class file, an `archive!entry`, or a whole jar or jmod
+enum Input {
+    Class(Vec<u8>),
+    Archive(Vec<u8>),
+}
+
+// a `.class` path is a class even when it doesn't parse, so the parse error
+// is what's reported; anything else is told apart by its magic number
+fn read_input(path: &str) -> Result<Input, String> {
+    if path.contains('!') {
+        let (_, data) = read_class(path).map_err(|error| error.to_string())?;
+        return Ok(Input::Class(data));
+    }
+    let data = fs::read(path).map_err(|error| format!("{path}: {error}"))?;
+    match data.get(..4) {
+        _ if path.ends_with(".class") => Ok(Input::Class(data)),
+        Some([0xca, 0xfe, 0xba, 0xbe]) => Ok(Input::Class(data)),
+        // a jmod, or a zip's first local header or, when empty, its end of
+        // central directory
+        Some([b'J', b'M', 1, 0] | [b'P', b'K', 3, 4] | [b'P', b'K', 5, 6]) => {
+            Ok(Input::Archive(data))
+        }
+        _ => Err(format!("{path}: not a class file, jar or jmod")),
+    }
+}
+
+// the differences as they're printed, and whether there were any
+fn diff(args: &Args) -> Result<(String, bool), String> {
+    let (old, new) = (read_input(&args.old)?, read_input(&args.new)?);
+    match (old, new) {
+        (Input::Class(old), Input::Class(new)) => {
+            let diff = diff_classes((&args.old, &old), (&args.new, &new))?;
+            let output = match args.json {
+                true => diff.to_json(),
+                false => diff.print(),
+            };
+            Ok((output, !diff.is_empty()))
+        }
+        (Input::Archive(old), Input::Archive(new)) => {
+            let diff = diff_archives(old, new)?;
+            let output = match args.json {
+                true => diff.to_json(),
+                false => diff.print(),
+            };
+            Ok((output, !diff.is_empty()))
+        }
+        _ => Err("can't compare a class with an archive".to_string()),
+    }
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let args = match parse_args(args) {
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Diff(args)) => args,
+        Err(error) => {
+            eprint!("Error: {error}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match diff(&args) {
+        Ok((output, differs)) => {
+            print!("{output}");
+            ExitCode::from(u8::from(differs))
+        }
+        Err(error) => {
+            eprintln!("Error: {error}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process;
+
+    use super::*;
+    use crate::test_support::{args, fixture};
+
+    fn diff_args(old: &str, new: &str, json: bool) -> Args {
+        Args {
+            json,
+            old: fixture(old),
+            new: fixture(new),
+        }
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args(&["--json", "a.jar", "b.jar"])),
+            Ok(Command::Diff(Args {
+                json: true,
+                old: "a.jar".into(),
+                new: "b.jar".into(),
+            }))
+        );
+        assert_eq!(parse_args(&args(&["a.jar", "-?"])), Ok(Command::Help));
+        assert_eq!(
+            parse_args(&args(&["a.jar"])),
+            Err("expected an old and a new class or archive".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["-c", "a.jar", "b.jar"])),
+            Err("unknown option: -c".to_string())
+        );
+    }
+
+    #[test]
+    fn test_diff_classes() {
+        let args = diff_args("diff/old/Counter.class", "diff/new/Counter.class", false);
+        let (output, differs) = diff(&args).unwrap();
+        assert!(differs);
+        assert!(output.starts_with("~ class Counter\n+ field step:I\n"));
+
+        let args = diff_args("diff/old/Counter.class", "diff/old/Counter.class", false);
+        assert_eq!(diff(&args), Ok((String::new(), false)));
+
+        // a truncated class reports why it doesn't parse
+        let data = fs::read(fixture("diff/old/Counter.class")).unwrap();
+        let truncated = env::temp_dir().join(format!("rj-diff-test-{}.class", process::id()));
+        fs::write(&truncated, &data[..data.len() / 2]).unwrap();
+        let args = Args {
+            json: false,
+            old: fixture("diff/old/Counter.class"),
+            new: truncated.to_str().unwrap().to_string(),
+        };
+        let result = diff(&args);
+        fs::remove_file(&truncated).unwrap();
+        assert_eq!(
+            result,
+            Err(format!("{}: unexpected end of input", args.new))
+        );
+
+        // an entry in a jar compares with the same class on disk
+        let args = Args {
+            json: false,
+            old: format!("{}!Counter.class", fixture("diff/old.jar")),
+            new: fixture("diff/old/Counter.class"),
+        };
+        assert_eq!(diff(&args), Ok((String::new(), false)));
+    }
+
+    #[test]
+    fn test_diff_archives() {
+        let args = diff_args("diff/old.jar", "diff/new.jar", false);
+        let (output, differs) = diff(&args).unwrap();
+        assert!(differs);
+        assert!(output.starts_with("- class Point\n+ class Shape\n~ class Counter\n"));
+
+        let args = diff_args("diff/old.jar", "diff/new.jar", true);
+        let (output, _) = diff(&args).unwrap();
+        assert!(output.starts_with(
+            "{\n  \"removed\": [\n    \"Point\"\n  ],\n  \"added\": [\n    \"Shape\"\n  ],\n"
+        ));
+
+        let args = diff_args("classes.jar", "classes.jar", false);
+        assert_eq!(diff(&args), Ok((String::new(), false)));
+        let args = diff_args("classes.jar", "HelloWorld.class", false);
+        assert_eq!(
+            diff(&args),
+            Err("can't compare a class with an archive".to_string())
+        );
+        let args = diff_args("classes.jar", "HelloWorld.java", false);
+        assert_eq!(
+            diff(&args),
+            Err(format!("{}: not a class file, jar or jmod", args.new))
+        );
+    }
+}
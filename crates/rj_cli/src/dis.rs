@@ -0,0 +1,145 @@
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+
+use rj_core::archive::ClassPath;
+use rj_core::asm::class_text::disassemble;
+
+use crate::javap::find_class;
+
+const USAGE: &str = "\
+Usage: rj dis <options> <class>
+where possible options include:
+  -h  -help  --help  -?    Print this help message
+  -o <file>                Write the text to <file>
+
+Prints a class as text that `rj asm` assembles back to the same class file:
+the constant pool entry by entry, then the class, its fields and its
+methods, whose code is written as instructions and labels. Attributes other
+than Code are kept in hex. The class may be a class file, a class file in a
+jar like app.jar!com/example/Foo.class, or a class name like
+com.example.Foo that is looked up in CLASSPATH, or the current directory,
+after JAVA_HOME's system classes
+";
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    class: String,
+    output: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Disassemble(Args),
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut output = None;
+    let mut classes = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "-help" | "--help" | "-?" => return Ok(Command::Help),
+            "-o" => match args.next() {
+                Some(path) => output = Some(path.clone()),
+                None => return Err("-o requires a file".to_string()),
+            },
+            flag if flag.starts_with('-') => return Err(format!("unknown option: {flag}")),
+            class => classes.push(class.to_string()),
+        }
+    }
+    match <[String; 1]>::try_from(classes) {
+        Ok([class]) => Ok(Command::Disassemble(Args { class, output })),
+        Err(_) => Err("expected one class to disassemble".to_string()),
+    }
+}
+
+fn disassemble_class(class: &str, class_path: &ClassPath) -> Result<String, String> {
+    let (_, data) = find_class(class, class_path)?;
+    disassemble(&data).map_err(|error| format!("{class}: {error}"))
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let args = match parse_args(args) {
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Disassemble(args)) => args,
+        Err(error) => {
+            eprint!("Error: {error}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut class_path = ClassPath::new();
+    if let Some(java_home) = env::var_os("JAVA_HOME") {
+        let _ = class_path.push_system(java_home);
+    }
+    let search_path = env::var("CLASSPATH").unwrap_or_else(|_| ".".to_string());
+    if let Err(error) = class_path.push_search_path(&search_path) {
+        eprintln!("Error: {error}");
+        return ExitCode::FAILURE;
+    }
+    let text = match disassemble_class(&args.class, &class_path) {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match args.output {
+        Some(path) => match fs::write(&path, text) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(error) => {
+                eprintln!("Error: {path}: {error}");
+                ExitCode::FAILURE
+            }
+        },
+        None => {
+            print!("{text}");
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{args, fixture};
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args(&["-o", "Foo.j", "Foo.class"])),
+            Ok(Command::Disassemble(Args {
+                class: "Foo.class".to_string(),
+                output: Some("Foo.j".to_string()),
+            }))
+        );
+        assert_eq!(parse_args(&args(&["-?"])), Ok(Command::Help));
+        assert_eq!(
+            parse_args(&args(&[])),
+            Err("expected one class to disassemble".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["-x", "Foo.class"])),
+            Err("unknown option: -x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_disassemble_class() {
+        let class_path = ClassPath::parse(&fixture("classes.jar")).unwrap();
+        let text = disassemble_class("Point", &class_path).unwrap();
+        assert!(text.starts_with(".version "));
+        assert!(text.contains("\n.class 0x"));
+
+        let text = disassemble_class(&fixture("HelloWorld.class"), &ClassPath::new()).unwrap();
+        assert!(text.contains("    ldc #7                              // \"Hello, World!\"\n"));
+        assert_eq!(
+            disassemble_class("Missing", &class_path),
+            Err("class not found: Missing".to_string())
+        );
+    }
+}
@@ -0,0 +1,401 @@
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::UNIX_EPOCH;
+
+use rj_core::archive::{read_class, ClassLocation, ClassPath};
+use rj_core::class::parse_classfile;
+use rj_core::print::{print_system_info, PrintOptions, Visibility};
+
+const USAGE: &str = "\
+Usage: rj javap <options> <classes>
+where possible options include:
+  -h  -help  --help  -?    Print this help message
+  -v  -verbose             Print additional information
+  -l                       Print line number and local variable tables
+  -public                  Show only public classes and members
+  -protected               Show protected/public classes and members
+  -package                 Show package/protected/public classes
+                           and members (default)
+  -p  -private             Show all classes and members
+  -c                       Disassemble the code
+  -sysinfo                 Show system info (path, size, date, SHA-256 hash)
+                           of class being processed
+  -constants               Show final constants
+  -cp <path>               Specify where to find user class files
+  -classpath <path>        Specify where to find user class files
+  --class-path <path>      Specify where to find user class files
+  --system <jdk>           Specify where to find system modules
+                           (default JAVA_HOME)
+
+Each class may be a class file, a class file in a jar like
+app.jar!com/example/Foo.class, or a class name like com.example.Foo
+";
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    options: PrintOptions,
+    // -cp, or CLASSPATH when it's not given
+    class_path: Option<String>,
+    // --system, or JAVA_HOME when it's not given
+    system: Option<String>,
+    // files, `archive!entry` locations or class names
+    classes: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Print(Args),
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut options = PrintOptions::JAVAP;
+    let mut class_path = None;
+    let mut system = None;
+    let mut classes = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "-help" | "--help" | "-?" => return Ok(Command::Help),
+            "-v" | "-verbose" => {
+                options.verbose = true;
+                options.show_code = true;
+                options.show_line_numbers = true;
+                options.show_system_info = true;
+            }
+            "-l" => options.show_line_numbers = true,
+            "-c" => options.show_code = true,
+            "-public" => options.visibility = Visibility::Public,
+            "-protected" => options.visibility = Visibility::Protected,
+            "-package" => options.visibility = Visibility::Package,
+            "-p" | "-private" => options.visibility = Visibility::Private,
+            "-sysinfo" => options.show_system_info = true,
+            "-constants" => options.show_constants = true,
+            "-cp" | "-classpath" | "--class-path" => match args.next() {
+                Some(path) => class_path = Some(path.clone()),
+                None => return Err(format!("{arg} requires an argument")),
+            },
+            flag if flag.starts_with("--class-path=") => {
+                class_path = Some(flag["--class-path=".len()..].to_string());
+            }
+            "--system" => match args.next() {
+                Some(path) => system = Some(path.clone()),
+                None => return Err(format!("{arg} requires an argument")),
+            },
+            flag if flag.starts_with("--system=") => {
+                system = Some(flag["--system=".len()..].to_string());
+            }
+            flag if flag.starts_with('-') => return Err(format!("unknown option: {flag}")),
+            class => classes.push(class.to_string()),
+        }
+    }
+    if classes.is_empty() {
+        return Err("no classes were specified".to_string());
+    }
+    Ok(Command::Print(Args {
+        options,
+        class_path,
+        system,
+        classes,
+    }))
+}
+
+// a date as javap prints it, like `Oct 17, 2026`, from seconds since the
+// epoch. always in UTC
+fn format_date(seconds: u64) -> String {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    // days to a civil date, counting years from March so the leap day is last
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{} {day}, {year}", MONTHS[month as usize - 1])
+}
+
+// the lines above the listing under -v and -sysinfo. classes in an archive
+// are shown with the archive's modification time
+fn system_info(location: &ClassLocation, data: &[u8]) -> String {
+    let file = match location {
+        ClassLocation::File(path) | ClassLocation::Archive(path, _) => path,
+    };
+    let canonical = fs::canonicalize(file).unwrap_or_else(|_| file.clone());
+    let modified = fs::metadata(&canonical)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|modified| format!("Last modified {}; ", format_date(modified.as_secs())))
+        .unwrap_or_default();
+    let mut lines = print_system_info(data)
+        .lines()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    lines[0] = format!("{modified}{}", lines[0]);
+    let mut output = match location {
+        ClassLocation::File(_) => format!("Classfile {}\n", canonical.display()),
+        // a full name in a JDK's lib/modules
+        ClassLocation::Archive(_, name) if name.starts_with('/') => {
+            format!("Classfile jrt:{name}\n")
+        }
+        ClassLocation::Archive(_, name) => {
+            format!("Classfile jar:file:{}!/{name}\n", canonical.display())
+        }
+    };
+    for line in lines {
+        output.push_str(&format!("  {line}\n"));
+    }
+    output
+}
+
+// reads a class given by path, as `archive!entry` or by name from the class
+// path, the way javap tells them apart
+pub(crate) fn find_class(
+    class: &str,
+    class_path: &ClassPath,
+) -> Result<(ClassLocation, Vec<u8>), String> {
+    if class.contains('!') || class.ends_with(".class") || Path::new(class).is_file() {
+        return read_class(class).map_err(|error| error.to_string());
+    }
+    match class_path.find(class) {
+        Ok(Some(found)) => Ok(found),
+        Ok(None) => Err(format!("class not found: {class}")),
+        Err(error) => Err(format!("{class}: {error}")),
+    }
+}
+
+fn listing(class: &str, class_path: &ClassPath, options: &PrintOptions) -> Result<String, String> {
+    let (location, data) = find_class(class, class_path)?;
+    let (_, classfile) = parse_classfile(&data).map_err(|error| format!("{class}: {error}"))?;
+    let listing = classfile
+        .print_with(options)
+        .map_err(|error| format!("{class}: {error}"))?;
+    match options.show_system_info {
+        true => Ok(system_info(&location, &data) + &listing),
+        false => Ok(listing),
+    }
+}
+
+// prints each class in turn, carrying on past the ones that fail. the exit
+// code is 1 if any did
+pub fn run(args: &[String]) -> ExitCode {
+    let args = match parse_args(args) {
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Print(args)) => args,
+        Err(error) => {
+            eprint!("Error: {error}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut class_path = ClassPath::new();
+    // system classes come first, as they do for java. a JAVA_HOME without
+    // any is passed over, but a --system without any is an error
+    match &args.system {
+        Some(system) => {
+            if let Err(error) = class_path.push_system(system) {
+                eprintln!("Error: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+        None => {
+            if let Some(java_home) = env::var_os("JAVA_HOME") {
+                let _ = class_path.push_system(java_home);
+            }
+        }
+    }
+    let search_path = args
+        .class_path
+        .clone()
+        .or_else(|| env::var("CLASSPATH").ok())
+        .unwrap_or_else(|| ".".to_string());
+    if let Err(error) = class_path.push_search_path(&search_path) {
+        eprintln!("Error: {error}");
+        return ExitCode::FAILURE;
+    }
+    let mut stdout = io::stdout().lock();
+    let mut failed = false;
+    for class in &args.classes {
+        match listing(class, &class_path, &args.options) {
+            Ok(listing) => {
+                if stdout.write_all(listing.as_bytes()).is_err() {
+                    return ExitCode::FAILURE;
+                }
+            }
+            Err(error) => {
+                failed = true;
+                eprintln!("Error: {error}");
+            }
+        }
+    }
+    match failed {
+        true => ExitCode::FAILURE,
+        false => ExitCode::SUCCESS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{args, fixture};
+
+    fn print_args(arguments: &[&str]) -> Args {
+        match parse_args(&args(arguments)) {
+            Ok(Command::Print(args)) => args,
+            result => panic!("expected options, got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = print_args(&["-c", "A.class", "B.class"]);
+        assert_eq!(
+            parsed,
+            Args {
+                options: PrintOptions {
+                    show_code: true,
+                    ..PrintOptions::JAVAP
+                },
+                class_path: None,
+                system: None,
+                classes: args(&["A.class", "B.class"]),
+            }
+        );
+        // like javap's, -v alone doesn't show private members
+        let parsed = print_args(&["-v", "A.class"]);
+        assert_eq!(parsed.options.visibility, Visibility::Package);
+        assert_eq!(
+            print_args(&["-v", "-p", "A.class"]).options,
+            PrintOptions::VERBOSE
+        );
+        let parsed = print_args(&["-public", "-v", "A.class"]);
+        assert_eq!(parsed.options.visibility, Visibility::Public);
+
+        let parsed = print_args(&["-cp", "lib/a.jar:classes", "com.example.A"]);
+        assert_eq!(parsed.class_path.as_deref(), Some("lib/a.jar:classes"));
+        assert_eq!(parsed.classes, ["com.example.A"]);
+        let parsed = print_args(&["--class-path=lib/*", "A"]);
+        assert_eq!(parsed.class_path.as_deref(), Some("lib/*"));
+        let parsed = print_args(&["--system", "/opt/jdk", "java.lang.Object"]);
+        assert_eq!(parsed.system.as_deref(), Some("/opt/jdk"));
+        let parsed = print_args(&["--system=/opt/jdk", "java.lang.Object"]);
+        assert_eq!(parsed.system.as_deref(), Some("/opt/jdk"));
+
+        assert_eq!(
+            parse_args(&args(&["-c", "-?", "A.class"])),
+            Ok(Command::Help)
+        );
+        assert_eq!(
+            parse_args(&args(&["-s", "A.class"])),
+            Err("unknown option: -s".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["-c"])),
+            Err("no classes were specified".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["A", "-cp"])),
+            Err("-cp requires an argument".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!(format_date(0), "Jan 1, 1970");
+        assert_eq!(format_date(951782400), "Feb 29, 2000");
+        assert_eq!(format_date(1792238400), "Oct 17, 2026");
+    }
+
+    #[test]
+    fn test_listing() {
+        let class_path = ClassPath::new();
+        let output = listing(
+            &fixture("HelloWorld.class"),
+            &class_path,
+            &PrintOptions::JAVAP,
+        );
+        let expected = r#"
+Compiled from "HelloWorld.java"
+public class HelloWorld {
+  public HelloWorld();
+  public static void main(java.lang.String[]);
+}
+"#;
+        assert_eq!(output.unwrap(), expected[1..]);
+
+        let options = PrintOptions {
+            show_system_info: true,
+            ..PrintOptions::JAVAP
+        };
+        let output = listing(&fixture("HelloWorld.class"), &class_path, &options).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("Classfile /") && lines[0].ends_with("/HelloWorld.class"));
+        assert!(lines[1].starts_with("  Last modified ") && lines[1].ends_with("; size 567 bytes"));
+        assert!(lines[2].starts_with("  SHA-256 checksum "));
+        assert_eq!(lines[3], "  Compiled from \"HelloWorld.java\"");
+
+        let error = listing(
+            &fixture("HelloWorld.java"),
+            &class_path,
+            &PrintOptions::JAVAP,
+        );
+        assert!(error
+            .unwrap_err()
+            .ends_with("HelloWorld.java: not a class file: the magic number is 0x7075626c"));
+        assert!(listing(&fixture("Missing.class"), &class_path, &PrintOptions::JAVAP).is_err());
+    }
+
+    #[test]
+    fn test_listing_class_path() {
+        let jar = fixture("classes.jar");
+        let class_path = ClassPath::parse(&jar).unwrap();
+        let expected = listing(&fixture("Point.class"), &class_path, &PrintOptions::JAVAP);
+        let output = listing("Point", &class_path, &PrintOptions::JAVAP);
+        assert_eq!(output, expected);
+        let output = listing(
+            &format!("{jar}!Point.class"),
+            &class_path,
+            &PrintOptions::JAVAP,
+        );
+        assert_eq!(output, expected);
+
+        let options = PrintOptions {
+            show_system_info: true,
+            ..PrintOptions::JAVAP
+        };
+        let output = listing("Outer$Inner", &class_path, &options).unwrap();
+        let first = output.lines().next().unwrap();
+        assert!(first.starts_with("Classfile jar:file:/"));
+        assert!(first.ends_with("/classes.jar!/Outer$Inner.class"));
+
+        assert_eq!(
+            listing("Frames", &class_path, &PrintOptions::JAVAP),
+            Err("class not found: Frames".to_string())
+        );
+    }
+
+    #[test]
+    fn test_listing_modules() {
+        let mut class_path = ClassPath::new();
+        class_path.push(fixture("demo.jimage")).unwrap();
+        let options = PrintOptions {
+            show_system_info: true,
+            ..PrintOptions::JAVAP
+        };
+        let output = listing("demo.Greeter", &class_path, &options).unwrap();
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("Classfile jrt:/demo/demo/Greeter.class"));
+        assert!(output.contains("public class demo.Greeter {"));
+    }
+}
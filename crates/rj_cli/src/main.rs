@@ -0,0 +1,52 @@
+mod asm;
+mod deps;
+mod diff;
+mod dis;
+mod javap;
+mod method;
+mod scan;
+#[cfg(test)]
+mod test_support;
+mod verify;
+
+use std::env;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: rj <command> <options> <files>
+commands:
+  asm      Assemble a class from the text dis prints
+  deps     List the classes that classes depend on, by package
+  diff     Compare two classes, or the classes in two jars
+  dis      Print a class as text that asm assembles back
+  javap    Disassemble class files, like the JDK's javap
+  method   Disassemble one method of a class
+  scan     Parse every class in jars, reporting the ones that fail
+  verify   Check the structure and bytecode of classes
+";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("asm") => asm::run(&args[1..]),
+        Some("deps") => deps::run(&args[1..]),
+        Some("diff") => diff::run(&args[1..]),
+        Some("dis") => dis::run(&args[1..]),
+        Some("javap") => javap::run(&args[1..]),
+        Some("method") => method::run(&args[1..]),
+        Some("scan") => scan::run(&args[1..]),
+        Some("verify") => verify::run(&args[1..]),
+        Some("-h" | "-help" | "--help") => {
+            print!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Some(command) => {
+            eprint!("Error: unknown command: {command}\n{USAGE}");
+            ExitCode::from(2)
+        }
+        None => {
+            eprint!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::env;
+use std::process::ExitCode;
+
+use rj_core::archive::ClassPath;
+use rj_core::class::parse_classfile;
+use rj_core::print::PrintOptions;
+
+use crate::javap::find_class;
+
+const USAGE: &str = "\
+Usage: rj method <options> [<class path>] <class>#<method>[<descriptor>]
+where possible options include:
+  -h  -help  --help  -?    Print this help message
+  -c                       Print only the declaration and the code
+  --stable                 Leave out constant pool indices
+
+Prints one method's declaration, descriptor, flags and disassembled code,
+or every overload of it unless a descriptor like bar(I)V picks one. The
+class may be a class file, a class file in a jar like
+app.jar!com/example/Foo.class, or a class name like com.example.Foo that
+is looked up in the class path (default CLASSPATH, or the current
+directory) after JAVA_HOME's system classes, as in
+  rj method app.jar com.example.Foo#bar
+";
+
+// the method named by `class#name` or `class#name(descriptor)`
+#[derive(Debug, PartialEq)]
+struct Target {
+    class: String,
+    name: String,
+    descriptor: Option<String>,
+}
+
+impl Target {
+    fn parse(target: &str) -> Result<Target, String> {
+        let Some((class, method)) = target.rsplit_once('#') else {
+            return Err(format!("expected <class>#<method>: {target}"));
+        };
+        let (name, descriptor) = match method.find('(') {
+            Some(start) => (&method[..start], Some(method[start..].to_string())),
+            None => (method, None),
+        };
+        if class.is_empty() || name.is_empty() {
+            return Err(format!("expected <class>#<method>: {target}"));
+        }
+        Ok(Target {
+            class: class.to_string(),
+            name: name.to_string(),
+            descriptor,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    options: PrintOptions,
+    class_path: Option<String>,
+    target: Target,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Print(Args),
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut options = PrintOptions::VERBOSE;
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "-help" | "--help" | "-?" => return Ok(Command::Help),
+            "-c" => {
+                options.verbose = false;
+                options.show_line_numbers = false;
+            }
+            "--stable" => options.stable = true,
+            flag if flag.starts_with('-') => return Err(format!("unknown option: {flag}")),
+            path => paths.push(path.to_string()),
+        }
+    }
+    let (class_path, target) = match <[String; 2]>::try_from(paths) {
+        Ok([class_path, target]) => (Some(class_path), target),
+        Err(paths) => match <[String; 1]>::try_from(paths) {
+            Ok([target]) => (None, target),
+            Err(_) => return Err("expected a class path and a method".to_string()),
+        },
+    };
+    Ok(Command::Print(Args {
+        options,
+        class_path,
+        target: Target::parse(&target)?,
+    }))
+}
+
+// each method the target names, a blank line apart
+fn print_methods(
+    target: &Target,
+    class_path: &ClassPath,
+    options: &PrintOptions,
+) -> Result<String, String> {
+    let class = &target.class;
+    let (_, data) = find_class(class, class_path)?;
+    let (_, classfile) = parse_classfile(&data).map_err(|error| format!("{class}: {error}"))?;
+    let methods = classfile.find_methods(&target.name, target.descriptor.as_deref());
+    if methods.is_empty() {
+        let descriptor = target.descriptor.as_deref().unwrap_or_default();
+        return Err(format!("{class}: no method {}{descriptor}", target.name));
+    }
+    let mut output = Vec::new();
+    for method in methods {
+        let listing = classfile
+            .print_method(method, options)
+            .map_err(|error| format!("{class}: {error}"))?;
+        output.push(listing);
+    }
+    Ok(output.join("\n"))
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let args = match parse_args(args) {
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Print(args)) => args,
+        Err(error) => {
+            eprint!("Error: {error}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut class_path = ClassPath::new();
+    if let Some(java_home) = env::var_os("JAVA_HOME") {
+        let _ = class_path.push_system(java_home);
+    }
+    let search_path = args
+        .class_path
+        .clone()
+        .or_else(|| env::var("CLASSPATH").ok())
+        .unwrap_or_else(|| ".".to_string());
+    if let Err(error) = class_path.push_search_path(&search_path) {
+        eprintln!("Error: {error}");
+        return ExitCode::FAILURE;
+    }
+    match print_methods(&args.target, &class_path, &args.options) {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("Error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{args, fixture};
+
+    fn target(class: &str, name: &str, descriptor: Option<&str>) -> Target {
+        Target {
+            class: class.to_string(),
+            name: name.to_string(),
+            descriptor: descriptor.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args(&["app.jar", "com.example.Foo#bar"])),
+            Ok(Command::Print(Args {
+                options: PrintOptions::VERBOSE,
+                class_path: Some("app.jar".to_string()),
+                target: target("com.example.Foo", "bar", None),
+            }))
+        );
+        match parse_args(&args(&["-c", "--stable", "Foo.class#bar(I)V"])) {
+            Ok(Command::Print(args)) => {
+                assert!(!args.options.verbose && !args.options.show_line_numbers);
+                assert!(args.options.stable);
+                assert_eq!(args.class_path, None);
+                assert_eq!(args.target, target("Foo.class", "bar", Some("(I)V")));
+            }
+            result => panic!("expected arguments, got {result:?}"),
+        }
+        assert_eq!(parse_args(&args(&["-?"])), Ok(Command::Help));
+        assert_eq!(
+            parse_args(&args(&["app.jar"])),
+            Err("expected <class>#<method>: app.jar".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["Foo#"])),
+            Err("expected <class>#<method>: Foo#".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["a", "b", "c"])),
+            Err("expected a class path and a method".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["-x", "Foo#bar"])),
+            Err("unknown option: -x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_print_methods() {
+        let class_path = ClassPath::parse(&fixture("classes.jar")).unwrap();
+        let output = print_methods(
+            &target("Point", "x", None),
+            &class_path,
+            &PrintOptions::VERBOSE,
+        )
+        .unwrap();
+        assert!(output.starts_with(
+            "public int x();\n  descriptor: ()I\n  flags: (0x0001) ACC_PUBLIC\n  Code:\n"
+        ));
+
+        // a class file by path, and a method by its descriptor
+        let class = fixture("HelloWorld.class");
+        let options = PrintOptions {
+            verbose: false,
+            show_line_numbers: false,
+            ..PrintOptions::VERBOSE
+        };
+        let output = print_methods(
+            &target(&class, "main", Some("([Ljava/lang/String;)V")),
+            &ClassPath::new(),
+            &options,
+        )
+        .unwrap();
+        assert!(output.starts_with("public static void main(java.lang.String[]);\n  Code:\n"));
+
+        assert_eq!(
+            print_methods(
+                &target(&class, "main", Some("()V")),
+                &ClassPath::new(),
+                &options
+            ),
+            Err(format!("{class}: no method main()V"))
+        );
+        assert_eq!(
+            print_methods(&target("Missing", "x", None), &class_path, &options),
+            Err("class not found: Missing".to_string())
+        );
+    }
+
+    #[test]
+    fn test_print_overloads() {
+        let class = fixture("Overloads.class");
+        let options = PrintOptions {
+            verbose: false,
+            show_line_numbers: false,
+            ..PrintOptions::VERBOSE
+        };
+        let output = print_methods(&target(&class, "twice", None), &ClassPath::new(), &options);
+        let expected = r#"
+static int twice(int);
+  Code:
+     0: iload_0
+     1: iconst_2
+     2: imul
+     3: ireturn
+
+static long twice(long);
+  Code:
+     0: lload_0
+     1: ldc2_w        #7                  // long 2l
+     4: lmul
+     5: lreturn
+"#;
+        assert_eq!(output, Ok(expected[1..].to_string()));
+
+        let output = print_methods(
+            &target(&class, "twice", Some("(J)J")),
+            &ClassPath::new(),
+            &options,
+        )
+        .unwrap();
+        assert!(output.starts_with("static long twice(long);\n"));
+    }
+}
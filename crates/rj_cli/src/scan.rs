@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use rj_core::archive::{Archive, ArchiveError};
+use rj_core::asm::disassemble;
+use rj_core::class::{parse_classfile, ClassVersion};
+
+const USAGE: &str = "\
+Usage: rj scan <options> <archives>
+where possible options include:
+  -h  -help  --help  -?    Print this help message
+  -v  -verbose             Print every class as it is scanned
+  -j <threads>             Parse with this many threads (default: one
+                           per CPU)
+
+Parses every class in each jar, jmod or JDK lib/modules image, reporting
+the ones that fail and a summary of the rest. The exit code is 1 if any
+class failed
+";
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    verbose: bool,
+    threads: Option<usize>,
+    archives: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Scan(Args),
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut verbose = false;
+    let mut threads = None;
+    let mut archives = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "-help" | "--help" | "-?" => return Ok(Command::Help),
+            "-v" | "-verbose" => verbose = true,
+            "-j" => match args.next().map(|count| count.parse::<usize>()) {
+                Some(Ok(count)) if count > 0 => threads = Some(count),
+                Some(_) => return Err(format!("{arg} requires a positive number")),
+                None => return Err(format!("{arg} requires an argument")),
+            },
+            flag if flag.starts_with('-') => return Err(format!("unknown option: {flag}")),
+            archive => archives.push(archive.to_string()),
+        }
+    }
+    if archives.is_empty() {
+        return Err("no archives were specified".to_string());
+    }
+    Ok(Command::Scan(Args {
+        verbose,
+        threads,
+        archives,
+    }))
+}
+
+// what a class that parsed contributes to the summary
+#[derive(Debug, PartialEq)]
+struct ClassStats {
+    version: ClassVersion,
+    // the length of every method's code
+    code_size: u64,
+}
+
+// parses a class and disassembles its methods, as far as anything rj does
+// with a class will go
+fn scan_class(data: &[u8]) -> Result<ClassStats, String> {
+    let (rest, classfile) = parse_classfile(data).map_err(|error| error.to_string())?;
+    if !rest.is_empty() {
+        return Err(format!("{} bytes after the end of the class", rest.len()));
+    }
+    let mut code_size = 0;
+    for method in &classfile.methods {
+        let Some(code) = method.code() else {
+            continue;
+        };
+        if let Some(Err(error)) = disassemble(&code.code).find(Result::is_err) {
+            let name = classfile
+                .constant_pool
+                .utf8(method.name_index)
+                .unwrap_or_default();
+            return Err(format!("{name}: {error}"));
+        }
+        code_size += code.code.len() as u64;
+    }
+    Ok(ClassStats {
+        version: classfile.version(),
+        code_size,
+    })
+}
+
+// an entry's name and what scanning it found
+type ScanResult = (String, Result<ClassStats, String>);
+
+// reads and scans the archive's classes on `threads` threads, each taking
+// the next class until there are none left. results are by entry name, in
+// archive order
+fn scan_classes(archive: &Archive, threads: usize) -> Result<Vec<ScanResult>, ArchiveError> {
+    let entries = archive.class_names()?;
+    let next = AtomicUsize::new(0);
+    let scan = || {
+        let mut results = Vec::new();
+        loop {
+            let index = next.fetch_add(1, Ordering::Relaxed);
+            let Some(entry) = entries.get(index) else {
+                return results;
+            };
+            let result = match archive.read(entry) {
+                Ok(Some(data)) => scan_class(&data),
+                Ok(None) => Err("not found".to_string()),
+                Err(error) => Err(error.to_string()),
+            };
+            results.push((index, result));
+        }
+    };
+    let mut results = thread::scope(|scope| {
+        let workers = (0..threads.min(entries.len()).max(1))
+            .map(|_| scope.spawn(scan))
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    Ok(results
+        .into_iter()
+        .map(|(index, result)| (entries[index].clone(), result))
+        .collect())
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Summary {
+    classes: usize,
+    failed: usize,
+    code_size: u64,
+    versions: BTreeMap<ClassVersion, usize>,
+}
+
+impl Summary {
+    fn add(&mut self, result: &Result<ClassStats, String>) {
+        self.classes += 1;
+        match result {
+            Ok(stats) => {
+                self.code_size += stats.code_size;
+                *self.versions.entry(stats.version).or_default() += 1;
+            }
+            Err(_) => self.failed += 1,
+        }
+    }
+}
+
+fn format_summary(name: &str, summary: &Summary) -> String {
+    let mut output = format!(
+        "{name}: {} classes, {} failed\n  bytecode: {} bytes\n",
+        summary.classes, summary.failed, summary.code_size
+    );
+    for (version, count) in &summary.versions {
+        output.push_str(&format!(
+            "  version {}.{} (Java {}): {count}\n",
+            version.major,
+            version.minor,
+            version.java_release()
+        ));
+    }
+    output
+}
+
+// scans each archive in turn, printing failures as they're found and a
+// summary of each archive. the exit code is 1 if any archive or class
+// failed
+pub fn run(args: &[String]) -> ExitCode {
+    let args = match parse_args(args) {
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Scan(args)) => args,
+        Err(error) => {
+            eprint!("Error: {error}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let threads = args.threads.unwrap_or_else(|| {
+        thread::available_parallelism()
+            .map(usize::from)
+            .unwrap_or(1)
+    });
+    let mut stdout = io::stdout().lock();
+    let mut failed = false;
+    for path in &args.archives {
+        // jars, jmods and jimages are told apart the way the class path does
+        let results = Archive::open(Path::new(path))
+            .map_err(|error| error.to_string())
+            .and_then(|archive| {
+                scan_classes(&archive, threads).map_err(|error| format!("{path}: {error}"))
+            });
+        let results = match results {
+            Ok(results) => results,
+            Err(error) => {
+                failed = true;
+                eprintln!("Error: {error}");
+                continue;
+            }
+        };
+        let mut summary = Summary::default();
+        let mut output = String::new();
+        for (entry, result) in results {
+            summary.add(&result);
+            match result {
+                Ok(stats) if args.verbose => output.push_str(&format!(
+                    "{entry}: version {}.{}, {} bytes of bytecode\n",
+                    stats.version.major, stats.version.minor, stats.code_size
+                )),
+                Ok(_) => {}
+                Err(error) => eprintln!("Error: {path}!{entry}: {error}"),
+            }
+        }
+        failed |= summary.failed > 0;
+        output.push_str(&format_summary(path, &summary));
+        if stdout.write_all(output.as_bytes()).is_err() {
+            return ExitCode::FAILURE;
+        }
+    }
+    match failed {
+        true => ExitCode::FAILURE,
+        false => ExitCode::SUCCESS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{args, fixture};
+
+    fn open_fixture(name: &str) -> Archive {
+        Archive::open(Path::new(&fixture(name))).unwrap()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args(&["-v", "-j", "4", "a.jar", "b.jar"])),
+            Ok(Command::Scan(Args {
+                verbose: true,
+                threads: Some(4),
+                archives: args(&["a.jar", "b.jar"]),
+            }))
+        );
+        assert_eq!(parse_args(&args(&["-?", "a.jar"])), Ok(Command::Help));
+        assert_eq!(
+            parse_args(&args(&["a.jar", "-j", "0"])),
+            Err("-j requires a positive number".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["a.jar", "-j"])),
+            Err("-j requires an argument".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["-v"])),
+            Err("no archives were specified".to_string())
+        );
+    }
+
+    #[test]
+    fn test_scan_class() {
+        let data = include_bytes!("../../../java/HelloWorld.class");
+        let stats = scan_class(data).unwrap();
+        assert_eq!(stats.version, ClassVersion::new(65, 0));
+        // 11 bytes in each of the constructor, sayHello and main
+        assert_eq!(stats.code_size, 33);
+
+        assert!(scan_class(&data[..data.len() - 1]).is_err());
+        let mut trailing = data.to_vec();
+        trailing.push(0);
+        assert_eq!(
+            scan_class(&trailing),
+            Err("1 bytes after the end of the class".to_string())
+        );
+    }
+
+    #[test]
+    fn test_scan_classes() {
+        let archive = open_fixture("classes.jar");
+        for threads in [1, 2, 8] {
+            let results = scan_classes(&archive, threads).unwrap();
+            let names = results
+                .iter()
+                .map(|(entry, _)| entry.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                names,
+                ["HelloWorld.class", "Point.class", "Outer$Inner.class"]
+            );
+            assert!(results.iter().all(|(_, result)| result.is_ok()));
+        }
+
+        let mut summary = Summary::default();
+        for (_, result) in scan_classes(&archive, 2).unwrap() {
+            summary.add(&result);
+        }
+        summary.add(&Err("truncated".to_string()));
+        assert_eq!(summary.classes, 4);
+        assert_eq!(summary.failed, 1);
+        let output = format_summary("classes.jar", &summary);
+        let mut lines = output.lines();
+        assert_eq!(lines.next(), Some("classes.jar: 4 classes, 1 failed"));
+        assert!(lines.next().unwrap().starts_with("  bytecode: "));
+        assert_eq!(
+            lines.collect::<Vec<_>>(),
+            ["  version 61.0 (Java 17): 2", "  version 65.0 (Java 21): 1"]
+        );
+    }
+
+    #[test]
+    fn test_scan_jmod() {
+        let archive = open_fixture("demo.jmod");
+        let results = scan_classes(&archive, 4).unwrap();
+        let names = results
+            .iter()
+            .map(|(entry, _)| entry.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["classes/module-info.class", "classes/demo/Greeter.class"]
+        );
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+        assert!(Archive::open(Path::new(&fixture("HelloWorld.class"))).is_err());
+    }
+
+    #[test]
+    fn test_scan_jimage() {
+        // a JDK's lib/modules, as jlink writes it
+        let archive = open_fixture("demo.jimage");
+        let mut results = scan_classes(&archive, 2).unwrap();
+        results.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let (names, results): (Vec<_>, Vec<_>) = results.into_iter().unzip();
+        assert_eq!(
+            names,
+            ["/demo/demo/Greeter.class", "/demo/module-info.class"]
+        );
+        assert!(results.iter().all(Result::is_ok));
+    }
+}
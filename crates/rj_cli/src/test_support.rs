@@ -0,0 +1,9 @@
+// command lines as `run` and `parse_args` take them
+pub(crate) fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+// the path of a file in the repository's java directory
+pub(crate) fn fixture(name: &str) -> String {
+    format!("{}/../../java/{name}", env!("CARGO_MANIFEST_DIR"))
+}
@@ -0,0 +1,193 @@
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+use rj_core::analysis::{verify_class, Violation};
+
+use crate::deps::read_classes;
+
+const USAGE: &str = "\
+Usage: rj verify <options> <classes>
+where possible options include:
+  -h  -help  --help  -?    Print this help message
+  -q  -quiet               Print only the summary
+
+Checks the structure of each class: the constant pool, the class references
+and descriptors of the class and its members, that every attribute decodes
+within its length, and that each method's instructions decode, refer to the
+right kinds of constants, keep the stack consistent within max_stack and
+max_locals, and are what the code's tables point at. Each class may be a
+class file, a class file in a jar like app.jar!com/example/Foo.class, or a
+whole jar or jmod. Violations are printed with the offset into the class
+file they're at. The exit code is 1 if there were any
+";
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    quiet: bool,
+    classes: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,
+    Verify(Args),
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut quiet = false;
+    let mut classes = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "-help" | "--help" | "-?" => return Ok(Command::Help),
+            "-q" | "-quiet" => quiet = true,
+            flag if flag.starts_with('-') => return Err(format!("unknown option: {flag}")),
+            class => classes.push(class.to_string()),
+        }
+    }
+    if classes.is_empty() {
+        return Err("no classes were specified".to_string());
+    }
+    Ok(Command::Verify(Args { quiet, classes }))
+}
+
+// `Foo.class: 0x01e3: method bar:()V, pc 10: invalid stack: code falls off the end at pc 10`
+fn format_violation(name: &str, violation: &Violation) -> String {
+    format!(
+        "{name}: {:#06x}: {}: {}\n",
+        violation.offset, violation.location, violation.kind
+    )
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Summary {
+    classes: usize,
+    // classes with violations, or that couldn't be read
+    failed: usize,
+    violations: usize,
+}
+
+// verifies each class in `path`, writing the violations to `output`
+fn verify_path(path: &str, summary: &mut Summary, output: &mut String) -> Result<(), String> {
+    for (name, data) in read_classes(path)? {
+        summary.classes += 1;
+        let violations = match data {
+            Ok(data) => verify_class(&data),
+            Err(error) => {
+                summary.failed += 1;
+                output.push_str(&format!("{name}: {error}\n"));
+                continue;
+            }
+        };
+        if !violations.is_empty() {
+            summary.failed += 1;
+            summary.violations += violations.len();
+        }
+        for violation in &violations {
+            output.push_str(&format_violation(&name, violation));
+        }
+    }
+    Ok(())
+}
+
+pub fn run(args: &[String]) -> ExitCode {
+    let args = match parse_args(args) {
+        Ok(Command::Help) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Verify(args)) => args,
+        Err(error) => {
+            eprint!("Error: {error}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    let mut stdout = io::stdout().lock();
+    let mut summary = Summary::default();
+    let mut failed = false;
+    for path in &args.classes {
+        let mut output = String::new();
+        if let Err(error) = verify_path(path, &mut summary, &mut output) {
+            failed = true;
+            eprintln!("Error: {error}");
+        }
+        if !args.quiet && stdout.write_all(output.as_bytes()).is_err() {
+            return ExitCode::FAILURE;
+        }
+    }
+    let _ = writeln!(
+        stdout,
+        "{} classes, {} with violations, {} violations",
+        summary.classes, summary.failed, summary.violations
+    );
+    match failed || summary.failed > 0 {
+        true => ExitCode::FAILURE,
+        false => ExitCode::SUCCESS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rj_core::analysis::{Location, ViolationKind};
+
+    use super::*;
+    use crate::test_support::{args, fixture};
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(&args(&["-q", "a.jar", "Foo.class"])),
+            Ok(Command::Verify(Args {
+                quiet: true,
+                classes: args(&["a.jar", "Foo.class"]),
+            }))
+        );
+        assert_eq!(parse_args(&args(&["a.jar", "-?"])), Ok(Command::Help));
+        assert_eq!(
+            parse_args(&args(&["-q"])),
+            Err("no classes were specified".to_string())
+        );
+        assert_eq!(
+            parse_args(&args(&["-x", "a.jar"])),
+            Err("unknown option: -x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_violation() {
+        let violation = Violation {
+            offset: 483,
+            location: Location::Code {
+                method: "sayHello:()V".to_string(),
+                pc: 10,
+            },
+            kind: ViolationKind::MaxStackTooSmall {
+                declared: 1,
+                computed: 2,
+            },
+        };
+        assert_eq!(
+            format_violation("HelloWorld.class", &violation),
+            "HelloWorld.class: 0x01e3: method sayHello:()V, pc 10: \
+             max_stack is 1, but the stack reaches 2\n"
+        );
+    }
+
+    #[test]
+    fn test_verify_path() {
+        let mut summary = Summary::default();
+        let mut output = String::new();
+        verify_path(&fixture("classes.jar"), &mut summary, &mut output).unwrap();
+        verify_path(&fixture("demo.jmod"), &mut summary, &mut output).unwrap();
+        verify_path(&fixture("HelloWorld.class"), &mut summary, &mut output).unwrap();
+        assert_eq!(output, "");
+        assert_eq!(
+            summary,
+            Summary {
+                classes: 6,
+                failed: 0,
+                violations: 0,
+            }
+        );
+        assert!(verify_path(&fixture("missing.jar"), &mut summary, &mut output).is_err());
+    }
+}
@@ -0,0 +1,8 @@
+mod dependencies;
+mod format_check;
+pub mod locals;
+mod verify;
+
+pub use dependencies::*;
+pub use format_check::*;
+pub use verify::*;
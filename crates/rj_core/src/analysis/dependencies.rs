@@ -0,0 +1,238 @@
+use std::collections::BTreeSet;
+
+use crate::class::{
+    class_names, decode_modified_utf8, Annotation, Attribute, ClassFile, Constant, ConstantPool,
+    ElementValue, Text,
+};
+
+// packages that ship with the JDK, going by name: the java SE APIs and the
+// JDK's own. javax is only partly the JDK's, but is counted whole
+const JDK_PACKAGES: [&str; 8] = [
+    "java/",
+    "javax/",
+    "jdk/",
+    "sun/",
+    "com/sun/",
+    "org/ietf/jgss/",
+    "org/w3c/dom/",
+    "org/xml/sax/",
+];
+
+// the package of an internal class name, like `java/lang` for
+// `java/lang/String`; empty for the unnamed package
+pub fn package_name(class_name: &str) -> &str {
+    class_name
+        .rsplit_once('/')
+        .map_or("", |(package, _)| package)
+}
+
+pub fn is_jdk_package(package: &str) -> bool {
+    let package = format!("{package}/");
+    JDK_PACKAGES
+        .iter()
+        .any(|prefix| package.starts_with(prefix))
+}
+
+// JDK packages no module exports for general use: sun.*, jdk.internal.* and
+// the `internal` packages under com.sun, which break between releases
+pub fn is_jdk_internal_package(package: &str) -> bool {
+    let package = format!("{package}/");
+    package.starts_with("sun/")
+        || package.starts_with("jdk/internal/")
+        || (package.starts_with("com/sun/") && package.contains("/internal/"))
+}
+
+struct Dependencies<'p, 'a> {
+    constant_pool: &'p ConstantPool<'a>,
+    names: BTreeSet<String>,
+}
+
+impl Dependencies<'_, '_> {
+    // the classes named in the Utf8 at `index`; anything that isn't a Utf8
+    // or doesn't parse as `text` is passed over
+    fn text(&mut self, index: u16, text: Text) {
+        let Some(Constant::Utf8 { value }) = self.constant_pool.get(index) else {
+            return;
+        };
+        for name in class_names(value, text).unwrap_or_default() {
+            if let Ok(name) = decode_modified_utf8(name) {
+                self.names.insert(name.into_owned());
+            }
+        }
+    }
+
+    fn element_value(&mut self, value: &ElementValue) {
+        match value {
+            ElementValue::Const { .. } => {}
+            ElementValue::Enum {
+                type_name_index, ..
+            } => self.text(*type_name_index, Text::FieldDescriptor),
+            ElementValue::Class { class_info_index } => {
+                self.text(*class_info_index, Text::ReturnDescriptor)
+            }
+            ElementValue::Annotation(annotation) => self.annotation(annotation),
+            ElementValue::Array(values) => {
+                for value in values {
+                    self.element_value(value);
+                }
+            }
+        }
+    }
+
+    fn annotation(&mut self, annotation: &Annotation) {
+        self.text(annotation.type_index, Text::FieldDescriptor);
+        for pair in &annotation.element_value_pairs {
+            self.element_value(&pair.value);
+        }
+    }
+
+    // `signature` is what a Signature attribute among these holds
+    fn attributes(&mut self, attributes: &[Attribute], signature: Text) {
+        for attribute in attributes {
+            match attribute {
+                Attribute::Signature(attribute) => self.text(attribute.signature_index, signature),
+                Attribute::Code(code) => self.attributes(&code.attributes, signature),
+                Attribute::LocalVariableTable(attribute) => {
+                    for entry in &attribute.local_variable_table {
+                        self.text(entry.descriptor_index, Text::FieldDescriptor);
+                    }
+                }
+                Attribute::LocalVariableTypeTable(attribute) => {
+                    for entry in &attribute.local_variable_type_table {
+                        self.text(entry.signature_index, Text::FieldSignature);
+                    }
+                }
+                Attribute::Record(record) => {
+                    for component in &record.components {
+                        self.text(component.descriptor_index, Text::FieldDescriptor);
+                        self.attributes(&component.attributes, Text::FieldSignature);
+                    }
+                }
+                Attribute::AnnotationDefault(attribute) => {
+                    self.element_value(&attribute.default_value)
+                }
+                Attribute::RuntimeVisibleAnnotations(attribute) => attribute
+                    .annotations
+                    .iter()
+                    .for_each(|a| self.annotation(a)),
+                Attribute::RuntimeInvisibleAnnotations(attribute) => attribute
+                    .annotations
+                    .iter()
+                    .for_each(|a| self.annotation(a)),
+                Attribute::RuntimeVisibleParameterAnnotations(attribute) => attribute
+                    .parameter_annotations
+                    .iter()
+                    .flatten()
+                    .for_each(|a| self.annotation(a)),
+                Attribute::RuntimeInvisibleParameterAnnotations(attribute) => attribute
+                    .parameter_annotations
+                    .iter()
+                    .flatten()
+                    .for_each(|a| self.annotation(a)),
+                Attribute::RuntimeVisibleTypeAnnotations(attribute) => attribute
+                    .annotations
+                    .iter()
+                    .for_each(|a| self.annotation(&a.annotation)),
+                Attribute::RuntimeInvisibleTypeAnnotations(attribute) => attribute
+                    .annotations
+                    .iter()
+                    .for_each(|a| self.annotation(&a.annotation)),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl ClassFile<'_> {
+    // the internal names of the classes this class refers to, other than
+    // itself: by Class entries and in the descriptors, generic signatures
+    // and annotations of its constants, members and attributes, as jdeps
+    // finds them. attributes left raw by a lazy parse aren't looked into
+    pub fn dependencies(&self) -> BTreeSet<String> {
+        let mut dependencies = Dependencies {
+            constant_pool: &self.constant_pool,
+            names: BTreeSet::new(),
+        };
+        for (_, constant) in self.constant_pool.iter() {
+            match constant {
+                Constant::Class { name_index } => dependencies.text(*name_index, Text::ClassName),
+                Constant::NameAndType {
+                    descriptor_index, ..
+                } => dependencies.text(*descriptor_index, Text::Descriptor),
+                Constant::MethodType { descriptor_index } => {
+                    dependencies.text(*descriptor_index, Text::MethodDescriptor)
+                }
+                _ => {}
+            }
+        }
+        for field in &self.fields {
+            dependencies.text(field.descriptor_index, Text::FieldDescriptor);
+            dependencies.attributes(&field.attributes, Text::FieldSignature);
+        }
+        for method in &self.methods {
+            dependencies.text(method.descriptor_index, Text::MethodDescriptor);
+            dependencies.attributes(&method.attributes, Text::MethodSignature);
+        }
+        dependencies.attributes(&self.attributes, Text::ClassSignature);
+
+        let mut names = dependencies.names;
+        if let Ok(this_class) = self.constant_pool.class_name(self.this_class) {
+            names.remove(this_class.as_ref());
+        }
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::parse_classfile;
+
+    #[test]
+    fn test_dependencies() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        assert_eq!(
+            classfile.dependencies().into_iter().collect::<Vec<_>>(),
+            [
+                "java/io/PrintStream",
+                "java/lang/Object",
+                "java/lang/String",
+                "java/lang/System"
+            ]
+        );
+
+        // the bounds and type arguments of generic signatures, which erase
+        // to Object in descriptors
+        let data = include_bytes!("../../../../java/Generics.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let dependencies = classfile.dependencies();
+        assert!(dependencies.contains("java/lang/Comparable"));
+        assert!(!dependencies.contains("Generics"));
+
+        let data = include_bytes!("../../../../java/Annotated.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let dependencies = classfile.dependencies();
+        // annotation types and the enums and classes in their values
+        assert!(dependencies.contains("Annotated$Info"));
+        assert!(dependencies.contains("java/lang/annotation/ElementType"));
+        assert!(dependencies.contains("java/lang/Deprecated"));
+    }
+
+    #[test]
+    fn test_packages() {
+        assert_eq!(package_name("java/lang/String"), "java/lang");
+        assert_eq!(package_name("HelloWorld"), "");
+        assert!(is_jdk_package("java/lang"));
+        assert!(is_jdk_package("com/sun/net/httpserver"));
+        assert!(!is_jdk_package("javafx/scene"));
+        assert!(!is_jdk_package(""));
+        assert!(is_jdk_internal_package("sun/misc"));
+        assert!(is_jdk_internal_package("jdk/internal/misc"));
+        assert!(is_jdk_internal_package(
+            "com/sun/org/apache/xml/internal/utils"
+        ));
+        assert!(!is_jdk_internal_package("com/sun/net/httpserver"));
+        assert!(!is_jdk_internal_package("java/lang"));
+    }
+}
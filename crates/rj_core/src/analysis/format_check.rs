@@ -0,0 +1,563 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::asm::InstructionParseError;
+use crate::class::{
+    parse_field_type, Attribute, AttributeName, ClassFile, ClassVersion, Constant, ConstantPool,
+    MethodAccessFlags, MethodDescriptor,
+};
+
+use super::locals::{verify_max_locals, MaxLocalsMismatch};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FormatIssue {
+    PreviewClassFile {
+        version: ClassVersion,
+        severity: Severity,
+    },
+    // an attribute defined only for later class file versions
+    AttributeNotAllowed {
+        name: AttributeName,
+        version: ClassVersion,
+        severity: Severity,
+    },
+    InvalidFieldDescriptor {
+        field_index: usize,
+    },
+    DuplicateField {
+        name: String,
+        descriptor: String,
+        first_index: usize,
+        second_index: usize,
+    },
+    DuplicateMethod {
+        name: String,
+        descriptor: String,
+        first_index: usize,
+        second_index: usize,
+    },
+    DuplicateConstant {
+        first_index: u16,
+        second_index: u16,
+    },
+    InvalidMethodDescriptor {
+        method_index: usize,
+    },
+    InvalidCode {
+        method_index: usize,
+        error: InstructionParseError,
+    },
+    MaxLocals {
+        method_index: usize,
+        mismatch: MaxLocalsMismatch,
+    },
+}
+
+impl FormatIssue {
+    pub fn severity(&self) -> Severity {
+        match self {
+            FormatIssue::PreviewClassFile { severity, .. } => *severity,
+            FormatIssue::AttributeNotAllowed { severity, .. } => *severity,
+            FormatIssue::InvalidFieldDescriptor { .. } => Severity::Error,
+            FormatIssue::DuplicateField { .. } => Severity::Error,
+            FormatIssue::DuplicateMethod { .. } => Severity::Error,
+            FormatIssue::DuplicateConstant { .. } => Severity::Info,
+            FormatIssue::InvalidMethodDescriptor { .. } => Severity::Error,
+            FormatIssue::InvalidCode { .. } => Severity::Error,
+            FormatIssue::MaxLocals {
+                mismatch: MaxLocalsMismatch::Under { .. },
+                ..
+            } => Severity::Error,
+            FormatIssue::MaxLocals {
+                mismatch: MaxLocalsMismatch::Over { .. },
+                ..
+            } => Severity::Info,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreviewPolicy {
+    #[default]
+    Accept,
+    Warn,
+    Reject,
+}
+
+// what to do with attributes that are newer than the class file, which the
+// JVM ignores
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AttributeVersionPolicy {
+    #[default]
+    Accept,
+    Warn,
+    Reject,
+}
+
+#[derive(Debug, Default)]
+pub struct FormatCheckOptions {
+    // decode every Code attribute and verify max_locals
+    pub deep: bool,
+    pub preview: PreviewPolicy,
+    pub attribute_versions: AttributeVersionPolicy,
+}
+
+fn constant_key<'a>(constant: &'a Constant) -> (u8, u64, &'a [u8]) {
+    fn pair(first: u16, second: u16) -> u64 {
+        ((first as u64) << 16) | second as u64
+    }
+    match constant {
+        Constant::Utf8 { value } => (1, 0, value),
+        Constant::Integer { value } => (3, *value as u32 as u64, &[]),
+        Constant::Float { value } => (4, value.to_bits() as u64, &[]),
+        Constant::Long { value } => (5, *value as u64, &[]),
+        Constant::Double { value } => (6, value.to_bits(), &[]),
+        Constant::Class { name_index } => (7, *name_index as u64, &[]),
+        Constant::String { string_index } => (8, *string_index as u64, &[]),
+        Constant::Fieldref {
+            class_index,
+            name_and_type_index,
+        } => (9, pair(*class_index, *name_and_type_index), &[]),
+        Constant::Methodref {
+            class_index,
+            name_and_type_index,
+        } => (10, pair(*class_index, *name_and_type_index), &[]),
+        Constant::InterfaceMethodref {
+            class_index,
+            name_and_type_index,
+        } => (11, pair(*class_index, *name_and_type_index), &[]),
+        Constant::NameAndType {
+            name_index,
+            descriptor_index,
+        } => (12, pair(*name_index, *descriptor_index), &[]),
+        Constant::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => (15, pair(*reference_kind as u16, *reference_index), &[]),
+        Constant::MethodType { descriptor_index } => (16, *descriptor_index as u64, &[]),
+        Constant::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => (
+            17,
+            pair(*bootstrap_method_attr_index, *name_and_type_index),
+            &[],
+        ),
+        Constant::InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => (
+            18,
+            pair(*bootstrap_method_attr_index, *name_and_type_index),
+            &[],
+        ),
+        Constant::Module { name_index } => (19, *name_index as u64, &[]),
+        Constant::Package { name_index } => (20, *name_index as u64, &[]),
+    }
+}
+
+fn check_duplicate_constants(classfile: &ClassFile, issues: &mut Vec<FormatIssue>) {
+    let mut seen = HashMap::new();
+    for (index, constant) in classfile.constant_pool.iter() {
+        let first_index = *seen.entry(constant_key(constant)).or_insert(index);
+        if first_index != index {
+            issues.push(FormatIssue::DuplicateConstant {
+                first_index,
+                second_index: index,
+            });
+        }
+    }
+}
+
+// the attributes of the class, its members, Code attributes and record
+// components
+fn all_attributes<'a, 'b>(classfile: &'b ClassFile<'a>) -> Vec<&'b Attribute<'a>> {
+    fn collect<'a, 'b>(attributes: &'b [Attribute<'a>], all: &mut Vec<&'b Attribute<'a>>) {
+        for attribute in attributes {
+            all.push(attribute);
+            match attribute {
+                Attribute::Code(code) => collect(&code.attributes, all),
+                Attribute::Record(record) => {
+                    for component in &record.components {
+                        collect(&component.attributes, all);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut all = vec![];
+    collect(&classfile.attributes, &mut all);
+    for field in &classfile.fields {
+        collect(&field.attributes, &mut all);
+    }
+    for method in &classfile.methods {
+        collect(&method.attributes, &mut all);
+    }
+    all
+}
+
+fn check_attribute_versions(
+    classfile: &ClassFile,
+    options: &FormatCheckOptions,
+    issues: &mut Vec<FormatIssue>,
+) {
+    let severity = match options.attribute_versions {
+        AttributeVersionPolicy::Accept => return,
+        AttributeVersionPolicy::Warn => Severity::Warning,
+        AttributeVersionPolicy::Reject => Severity::Error,
+    };
+    let version = classfile.version();
+    for attribute in all_attributes(classfile) {
+        match attribute.attribute_name() {
+            Some(name) if !version.allows(name) => {
+                issues.push(FormatIssue::AttributeNotAllowed {
+                    name,
+                    version,
+                    severity,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_duplicate_members<'a>(
+    members: impl Iterator<Item = (u16, u16)>,
+    constant_pool: &'a ConstantPool,
+) -> Vec<(Cow<'a, str>, Cow<'a, str>, usize, usize)> {
+    let mut seen = HashMap::new();
+    let mut duplicates = Vec::new();
+    for (index, (name_index, descriptor_index)) in members.enumerate() {
+        let name = constant_pool.utf8(name_index);
+        let descriptor = constant_pool.utf8(descriptor_index);
+        if let (Ok(name), Ok(descriptor)) = (name, descriptor) {
+            let key = (name, descriptor);
+            if let Some(&first_index) = seen.get(&key) {
+                duplicates.push((key.0, key.1, first_index, index));
+            } else {
+                seen.insert(key, index);
+            }
+        }
+    }
+    duplicates
+}
+
+pub fn check_format(classfile: &ClassFile, options: &FormatCheckOptions) -> Vec<FormatIssue> {
+    let mut issues = Vec::new();
+
+    let version = classfile.version();
+    if version.is_preview() {
+        match options.preview {
+            PreviewPolicy::Accept => {}
+            PreviewPolicy::Warn => issues.push(FormatIssue::PreviewClassFile {
+                version,
+                severity: Severity::Warning,
+            }),
+            PreviewPolicy::Reject => issues.push(FormatIssue::PreviewClassFile {
+                version,
+                severity: Severity::Error,
+            }),
+        }
+    }
+
+    for (field_index, field) in classfile.fields.iter().enumerate() {
+        let valid = classfile
+            .constant_pool
+            .utf8(field.descriptor_index)
+            .is_ok_and(|value| matches!(parse_field_type(value.as_bytes()), Ok((b"", _))));
+        if !valid {
+            issues.push(FormatIssue::InvalidFieldDescriptor { field_index });
+        }
+    }
+
+    let fields = classfile
+        .fields
+        .iter()
+        .map(|field| (field.name_index, field.descriptor_index));
+    for (name, descriptor, first_index, second_index) in
+        check_duplicate_members(fields, &classfile.constant_pool)
+    {
+        issues.push(FormatIssue::DuplicateField {
+            name: name.into_owned(),
+            descriptor: descriptor.into_owned(),
+            first_index,
+            second_index,
+        });
+    }
+
+    let methods = classfile
+        .methods
+        .iter()
+        .map(|method| (method.name_index, method.descriptor_index));
+    for (name, descriptor, first_index, second_index) in
+        check_duplicate_members(methods, &classfile.constant_pool)
+    {
+        issues.push(FormatIssue::DuplicateMethod {
+            name: name.into_owned(),
+            descriptor: descriptor.into_owned(),
+            first_index,
+            second_index,
+        });
+    }
+
+    check_attribute_versions(classfile, options, &mut issues);
+    check_duplicate_constants(classfile, &mut issues);
+
+    for (method_index, method) in classfile.methods.iter().enumerate() {
+        let descriptor = classfile.constant_pool.utf8(method.descriptor_index);
+        let descriptor = match descriptor
+            .as_deref()
+            .ok()
+            .and_then(|value| MethodDescriptor::parse(value.as_bytes()).ok())
+        {
+            Some(descriptor) => descriptor,
+            None => {
+                issues.push(FormatIssue::InvalidMethodDescriptor { method_index });
+                continue;
+            }
+        };
+        if !options.deep {
+            continue;
+        }
+        let is_static = method.access_flags.contains(MethodAccessFlags::STATIC);
+        for attribute in &method.attributes {
+            if let Attribute::Code(code) = attribute {
+                match verify_max_locals(code, &descriptor, is_static) {
+                    Ok(Some(mismatch)) => issues.push(FormatIssue::MaxLocals {
+                        method_index,
+                        mismatch,
+                    }),
+                    Ok(None) => {}
+                    Err(error) => issues.push(FormatIssue::InvalidCode {
+                        method_index,
+                        error,
+                    }),
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::{parse_classfile, ClassAccessFlags, Method};
+
+    fn method(name_index: u16, descriptor_index: u16) -> Method<'static> {
+        Method {
+            access_flags: MethodAccessFlags::ABSTRACT,
+            name_index,
+            descriptor_index,
+            attributes: vec![],
+        }
+    }
+
+    fn duplicate_methods_class(methods: Vec<Method<'static>>) -> ClassFile<'static> {
+        ClassFile {
+            magic: 0xCAFEBABE,
+            minor_version: 0,
+            major_version: 65,
+            constant_pool: ConstantPool::from(vec![
+                Constant::Class { name_index: 2 },
+                Constant::Utf8 {
+                    value: b"Foo".into(),
+                },
+                Constant::Utf8 {
+                    value: b"foo".into(),
+                },
+                Constant::Utf8 {
+                    value: b"()V".into(),
+                },
+                Constant::Utf8 {
+                    value: b"(I)V".into(),
+                },
+            ]),
+            access_flags: ClassAccessFlags::ABSTRACT,
+            this_class: 1,
+            super_class: 0,
+            interfaces: vec![],
+            fields: vec![],
+            methods,
+            attributes: vec![],
+        }
+    }
+
+    #[test]
+    fn test_check_format() {
+        let data = include_bytes!("../../../../java/Locals.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let options = FormatCheckOptions {
+            deep: true,
+            ..Default::default()
+        };
+        assert_eq!(check_format(&classfile, &options), vec![]);
+    }
+
+    #[test]
+    fn test_check_format_max_locals() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        for attribute in &mut classfile.methods[2].attributes {
+            if let Attribute::Code(code) = attribute {
+                code.max_locals = 0;
+            }
+        }
+
+        let issues = check_format(&classfile, &FormatCheckOptions::default());
+        assert_eq!(issues, vec![]);
+
+        let issues = check_format(
+            &classfile,
+            &FormatCheckOptions {
+                deep: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            issues,
+            vec![FormatIssue::MaxLocals {
+                method_index: 2,
+                mismatch: MaxLocalsMismatch::Under {
+                    declared: 0,
+                    computed: 1
+                }
+            }]
+        );
+        assert_eq!(issues[0].severity(), Severity::Error);
+    }
+
+    #[test]
+    fn test_check_format_preview() {
+        let mut data = include_bytes!("../../../../java/HelloWorld.class").to_vec();
+        data[4] = 0xFF;
+        data[5] = 0xFF;
+        let (_, classfile) = parse_classfile(&data).unwrap();
+        let version = ClassVersion::new(65, 0xFFFF);
+
+        let options = FormatCheckOptions::default();
+        assert_eq!(check_format(&classfile, &options), vec![]);
+
+        let options = FormatCheckOptions {
+            preview: PreviewPolicy::Warn,
+            ..Default::default()
+        };
+        assert_eq!(
+            check_format(&classfile, &options),
+            vec![FormatIssue::PreviewClassFile {
+                version,
+                severity: Severity::Warning
+            }]
+        );
+
+        let options = FormatCheckOptions {
+            preview: PreviewPolicy::Reject,
+            ..Default::default()
+        };
+        let issues = check_format(&classfile, &options);
+        assert_eq!(
+            issues,
+            vec![FormatIssue::PreviewClassFile {
+                version,
+                severity: Severity::Error
+            }]
+        );
+        assert_eq!(issues[0].severity(), Severity::Error);
+    }
+
+    #[test]
+    fn test_check_format_attribute_versions() {
+        // Point is a record, whose Record attribute needs Java 16
+        let mut data = include_bytes!("../../../../java/Point.class").to_vec();
+        data[7] = 52;
+        let (_, classfile) = parse_classfile(&data).unwrap();
+        let version = ClassVersion::for_release(8);
+
+        let options = FormatCheckOptions::default();
+        assert_eq!(check_format(&classfile, &options), vec![]);
+
+        let options = FormatCheckOptions {
+            attribute_versions: AttributeVersionPolicy::Warn,
+            ..Default::default()
+        };
+        assert_eq!(
+            check_format(&classfile, &options),
+            vec![FormatIssue::AttributeNotAllowed {
+                name: AttributeName::Record,
+                version,
+                severity: Severity::Warning
+            }]
+        );
+
+        let options = FormatCheckOptions {
+            attribute_versions: AttributeVersionPolicy::Reject,
+            ..Default::default()
+        };
+        let issues = check_format(&classfile, &options);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity(), Severity::Error);
+
+        let data = include_bytes!("../../../../java/Point.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        assert_eq!(check_format(&classfile, &options), vec![]);
+    }
+
+    #[test]
+    fn test_check_format_duplicate_methods() {
+        let classfile = duplicate_methods_class(vec![method(3, 4), method(3, 5), method(3, 4)]);
+        let issues = check_format(&classfile, &FormatCheckOptions::default());
+        assert_eq!(
+            issues,
+            vec![FormatIssue::DuplicateMethod {
+                name: "foo".to_string(),
+                descriptor: "()V".to_string(),
+                first_index: 0,
+                second_index: 2,
+            }]
+        );
+        assert_eq!(issues[0].severity(), Severity::Error);
+    }
+
+    #[test]
+    fn test_check_format_overloaded_methods() {
+        let classfile = duplicate_methods_class(vec![method(3, 4), method(3, 5)]);
+        let issues = check_format(&classfile, &FormatCheckOptions::default());
+        assert_eq!(issues, vec![]);
+    }
+
+    #[test]
+    fn test_check_format_duplicate_constants() {
+        let mut classfile = duplicate_methods_class(vec![]);
+        classfile.constant_pool.push(Constant::Utf8 {
+            value: b"foo".into(),
+        });
+        classfile.constant_pool.push(Constant::Utf8 {
+            value: b"foo".into(),
+        });
+        let issues = check_format(&classfile, &FormatCheckOptions::default());
+        assert_eq!(
+            issues,
+            vec![
+                FormatIssue::DuplicateConstant {
+                    first_index: 3,
+                    second_index: 6,
+                },
+                FormatIssue::DuplicateConstant {
+                    first_index: 3,
+                    second_index: 7,
+                },
+            ]
+        );
+        assert_eq!(issues[0].severity(), Severity::Info);
+    }
+}
@@ -0,0 +1,181 @@
+use std::cmp::Ordering;
+
+use crate::asm::{disassemble, Instruction, InstructionParseError};
+use crate::class::{Code, MethodDescriptor};
+
+#[derive(Debug, PartialEq)]
+pub enum MaxLocalsMismatch {
+    Under { declared: u16, computed: u16 },
+    Over { declared: u16, computed: u16 },
+}
+
+fn touched_slots(instruction: &Instruction) -> Option<u32> {
+    let (index, size) = match instruction {
+        Instruction::Iload(index)
+        | Instruction::Fload(index)
+        | Instruction::Aload(index)
+        | Instruction::Istore(index)
+        | Instruction::Fstore(index)
+        | Instruction::Astore(index)
+        | Instruction::Iinc(index, _)
+        | Instruction::Ret(index) => (*index as u32, 1),
+        Instruction::Lload(index)
+        | Instruction::Dload(index)
+        | Instruction::Lstore(index)
+        | Instruction::Dstore(index) => (*index as u32, 2),
+        Instruction::WideIload(index)
+        | Instruction::WideFload(index)
+        | Instruction::WideAload(index)
+        | Instruction::WideIstore(index)
+        | Instruction::WideFstore(index)
+        | Instruction::WideAstore(index)
+        | Instruction::WideIinc(index, _)
+        | Instruction::WideRet(index) => (*index as u32, 1),
+        Instruction::WideLload(index)
+        | Instruction::WideDload(index)
+        | Instruction::WideLstore(index)
+        | Instruction::WideDstore(index) => (*index as u32, 2),
+        Instruction::Iload0
+        | Instruction::Fload0
+        | Instruction::Aload0
+        | Instruction::Istore0
+        | Instruction::Fstore0
+        | Instruction::Astore0 => (0, 1),
+        Instruction::Iload1
+        | Instruction::Fload1
+        | Instruction::Aload1
+        | Instruction::Istore1
+        | Instruction::Fstore1
+        | Instruction::Astore1 => (1, 1),
+        Instruction::Iload2
+        | Instruction::Fload2
+        | Instruction::Aload2
+        | Instruction::Istore2
+        | Instruction::Fstore2
+        | Instruction::Astore2 => (2, 1),
+        Instruction::Iload3
+        | Instruction::Fload3
+        | Instruction::Aload3
+        | Instruction::Istore3
+        | Instruction::Fstore3
+        | Instruction::Astore3 => (3, 1),
+        Instruction::Lload0 | Instruction::Dload0 | Instruction::Lstore0 | Instruction::Dstore0 => {
+            (0, 2)
+        }
+        Instruction::Lload1 | Instruction::Dload1 | Instruction::Lstore1 | Instruction::Dstore1 => {
+            (1, 2)
+        }
+        Instruction::Lload2 | Instruction::Dload2 | Instruction::Lstore2 | Instruction::Dstore2 => {
+            (2, 2)
+        }
+        Instruction::Lload3 | Instruction::Dload3 | Instruction::Lstore3 | Instruction::Dstore3 => {
+            (3, 2)
+        }
+        _ => return None,
+    };
+    Some(index + size)
+}
+
+pub fn compute_max_locals(
+    code: &[(u32, Instruction)],
+    descriptor: &MethodDescriptor,
+    is_static: bool,
+) -> u16 {
+    let mut max_locals = descriptor.parameter_slots() as u32;
+    if !is_static {
+        max_locals += 1;
+    }
+    for (_, instruction) in code {
+        if let Some(slots) = touched_slots(instruction) {
+            max_locals = max_locals.max(slots);
+        }
+    }
+    max_locals.min(u16::MAX as u32) as u16
+}
+
+pub fn verify_max_locals<A>(
+    code: &Code<A>,
+    descriptor: &MethodDescriptor,
+    is_static: bool,
+) -> Result<Option<MaxLocalsMismatch>, InstructionParseError> {
+    let instructions = disassemble(&code.code).collect::<Result<Vec<_>, _>>()?;
+    let computed = compute_max_locals(&instructions, descriptor, is_static);
+    let declared = code.max_locals;
+    match declared.cmp(&computed) {
+        Ordering::Less => Ok(Some(MaxLocalsMismatch::Under { declared, computed })),
+        Ordering::Greater => Ok(Some(MaxLocalsMismatch::Over { declared, computed })),
+        Ordering::Equal => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::{
+        parse_classfile, parse_method_descriptor, Attribute, Constant, MethodAccessFlags,
+    };
+
+    #[test]
+    fn test_compute_max_locals() {
+        let data = include_bytes!("../../../../java/Locals.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let mut checked = 0;
+        for method in &classfile.methods {
+            let descriptor = match classfile
+                .constant_pool
+                .get(method.descriptor_index)
+                .unwrap()
+            {
+                Constant::Utf8 { value } => parse_method_descriptor(value).unwrap().1,
+                _ => unreachable!(),
+            };
+            let is_static = method.access_flags.contains(MethodAccessFlags::STATIC);
+            for attribute in &method.attributes {
+                if let Attribute::Code(code) = attribute {
+                    let instructions = disassemble(&code.code)
+                        .collect::<Result<Vec<_>, _>>()
+                        .unwrap();
+                    assert_eq!(
+                        compute_max_locals(&instructions, &descriptor, is_static),
+                        code.max_locals
+                    );
+                    assert_eq!(verify_max_locals(code, &descriptor, is_static), Ok(None));
+                    checked += 1;
+                }
+            }
+        }
+        assert_eq!(checked, 5);
+    }
+
+    #[test]
+    fn test_verify_max_locals_too_small() {
+        let code: Code<Attribute> = Code {
+            max_stack: 4,
+            max_locals: 2,
+            code: vec![
+                0x1e, // lload_0
+                0x40, // lstore_1
+                0x15, 0x03, // iload 3
+                0xac, // ireturn
+            ]
+            .into(),
+            exception_table: vec![],
+            attributes: vec![],
+        };
+        let (_, descriptor) = parse_method_descriptor(b"(J)I").unwrap();
+        assert_eq!(
+            verify_max_locals(&code, &descriptor, true),
+            Ok(Some(MaxLocalsMismatch::Under {
+                declared: 2,
+                computed: 4
+            }))
+        );
+        assert_eq!(
+            verify_max_locals(&code, &descriptor, false),
+            Ok(Some(MaxLocalsMismatch::Under {
+                declared: 2,
+                computed: 4
+            }))
+        );
+    }
+}
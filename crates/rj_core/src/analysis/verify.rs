@@ -0,0 +1,703 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::asm::{
+    compute_max_stack, disassemble, InstructionParseError, MaxStackError, ResolveError,
+};
+use crate::class::{
+    parse_constant, parse_field_type, parse_field_with, parse_method_with, parse_raw_attribute,
+    Attribute, ClassParseError, Code, Constant, ConstantPool, ConstantPoolError, MethodAccessFlags,
+    MethodDescriptor, CLASS_FILE_MAGIC,
+};
+use crate::parser::{be_u16, be_u32};
+
+use super::locals::compute_max_locals;
+
+// where in a class a violation is
+#[derive(Debug, Clone, PartialEq)]
+pub enum Location {
+    // the header, this_class, super_class, interfaces and class attributes
+    Class,
+    Constant(u16),
+    // members by `name:descriptor`, or by `#index` when those can't be read
+    Field(String),
+    Method(String),
+    // an instruction, or a pc that the Code's tables refer to
+    Code { method: String, pc: u32 },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ViolationKind {
+    // a structure that doesn't parse; nothing after it can be checked
+    Malformed(ClassParseError),
+    // bytes after the end of the class
+    TrailingBytes(usize),
+    InvalidConstant(ConstantPoolError),
+    // this_class, super_class, an interface or a catch type that isn't a
+    // Class constant
+    InvalidClassIndex(u16),
+    // a member name that isn't a Utf8 constant
+    InvalidName(u16),
+    InvalidFieldDescriptor,
+    InvalidMethodDescriptor,
+    // an attribute whose contents don't parse within its length
+    InvalidAttribute {
+        name: String,
+        error: ClassParseError,
+    },
+    // code that's empty or longer than 65535 bytes
+    InvalidCodeLength(usize),
+    InvalidInstruction(InstructionParseError),
+    // a constant pool operand of the wrong kind
+    InvalidOperand(ResolveError),
+    // control flow the operand stack can't follow
+    InvalidStack(MaxStackError),
+    MaxStackTooSmall {
+        declared: u16,
+        computed: u16,
+    },
+    MaxLocalsTooSmall {
+        declared: u16,
+        computed: u16,
+    },
+    // a pc in the exception table or a table attribute of the code that's
+    // past the end of the code or not at an instruction
+    InvalidPc(&'static str),
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Location::Class => write!(f, "class"),
+            Location::Constant(index) => write!(f, "constant #{index}"),
+            Location::Field(name) => write!(f, "field {name}"),
+            Location::Method(name) => write!(f, "method {name}"),
+            Location::Code { method, pc } => write!(f, "method {method}, pc {pc}"),
+        }
+    }
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ViolationKind::Malformed(error) => write!(f, "malformed: {error}"),
+            ViolationKind::TrailingBytes(count) => {
+                write!(f, "{count} bytes after the end of the class")
+            }
+            ViolationKind::InvalidConstant(error) => write!(f, "invalid constant: {error}"),
+            ViolationKind::InvalidClassIndex(index) => {
+                write!(f, "#{index} is not a Class constant")
+            }
+            ViolationKind::InvalidName(index) => write!(f, "name #{index} is not a Utf8 constant"),
+            ViolationKind::InvalidFieldDescriptor => write!(f, "invalid field descriptor"),
+            ViolationKind::InvalidMethodDescriptor => write!(f, "invalid method descriptor"),
+            ViolationKind::InvalidAttribute { name, error } => {
+                write!(f, "invalid {name} attribute: {error}")
+            }
+            ViolationKind::InvalidCodeLength(length) => write!(f, "invalid code length {length}"),
+            // the location already has the pc
+            ViolationKind::InvalidInstruction(error) => {
+                write!(f, "invalid instruction: {}", error.root())
+            }
+            ViolationKind::InvalidOperand(error) => write!(f, "invalid operand: {error}"),
+            ViolationKind::InvalidStack(error) => write!(f, "invalid stack: {error}"),
+            ViolationKind::MaxStackTooSmall { declared, computed } => {
+                write!(
+                    f,
+                    "max_stack is {declared}, but the stack reaches {computed}"
+                )
+            }
+            ViolationKind::MaxLocalsTooSmall { declared, computed } => {
+                write!(f, "max_locals is {declared}, but the code uses {computed}")
+            }
+            ViolationKind::InvalidPc(table) => {
+                write!(f, "{table} refers to a pc between instructions")
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Violation {
+    // bytes into the class file of the structure at fault
+    pub offset: usize,
+    pub location: Location,
+    pub kind: ViolationKind,
+}
+
+// a structure that doesn't parse, with where it starts
+type Malformed = (usize, Location, ClassParseError);
+
+fn malformed<E: Into<ClassParseError>>(
+    offset: usize,
+    location: Location,
+) -> impl FnOnce(E) -> Malformed {
+    move |error| (offset, location, error.into())
+}
+
+// `name:descriptor`, or `#index` for a member whose name or descriptor
+// can't be read
+fn member_name(constant_pool: &ConstantPool, name: u16, descriptor: u16, index: u16) -> String {
+    match (constant_pool.utf8(name), constant_pool.utf8(descriptor)) {
+        (Ok(name), Ok(descriptor)) => format!("{name}:{descriptor}"),
+        _ => format!("#{index}"),
+    }
+}
+
+fn stack_error_pc(error: &MaxStackError) -> u32 {
+    match error {
+        MaxStackError::StackEffectError(_) => 0,
+        MaxStackError::StackUnderflow(pc)
+        | MaxStackError::InconsistentStackHeight(pc)
+        | MaxStackError::InvalidBranchTarget(pc)
+        | MaxStackError::FallsOffEnd(pc) => *pc,
+    }
+}
+
+struct Verifier<'a> {
+    data: &'a [u8],
+    violations: Vec<Violation>,
+}
+
+impl<'a> Verifier<'a> {
+    fn offset(&self, rest: &[u8]) -> usize {
+        self.data.len() - rest.len()
+    }
+
+    fn report(&mut self, offset: usize, location: Location, kind: ViolationKind) {
+        self.violations.push(Violation {
+            offset,
+            location,
+            kind,
+        });
+    }
+
+    fn class_index(&mut self, constant_pool: &ConstantPool, index: u16, offset: usize) {
+        if !matches!(constant_pool.get(index), Some(Constant::Class { .. })) {
+            self.report(
+                offset,
+                Location::Class,
+                ViolationKind::InvalidClassIndex(index),
+            );
+        }
+    }
+
+    // the whole class, stopping at the first structure that doesn't parse
+    fn class(&mut self) -> Result<(), Malformed> {
+        let input = self.data;
+        let (input, magic) = be_u32(input).map_err(malformed(0, Location::Class))?;
+        if magic != CLASS_FILE_MAGIC {
+            return Err((0, Location::Class, ClassParseError::NotAClassFile(magic)));
+        }
+        let (input, _minor_version) = be_u16(input).map_err(malformed(4, Location::Class))?;
+        let (input, _major_version) = be_u16(input).map_err(malformed(6, Location::Class))?;
+        let (input, constant_pool) = self.constant_pool(input)?;
+
+        let offset = self.offset(input);
+        let (input, _access_flags) = be_u16(input).map_err(malformed(offset, Location::Class))?;
+        let (input, this_class) = be_u16(input).map_err(malformed(offset, Location::Class))?;
+        let (input, super_class) = be_u16(input).map_err(malformed(offset, Location::Class))?;
+        self.class_index(&constant_pool, this_class, offset + 2);
+        // only java.lang.Object and module-info have no superclass
+        if super_class != 0 {
+            self.class_index(&constant_pool, super_class, offset + 4);
+        }
+        let (mut input, interfaces_count) =
+            be_u16(input).map_err(malformed(offset, Location::Class))?;
+        for _ in 0..interfaces_count {
+            let offset = self.offset(input);
+            let (rest, interface) = be_u16(input).map_err(malformed(offset, Location::Class))?;
+            self.class_index(&constant_pool, interface, offset);
+            input = rest;
+        }
+
+        let offset = self.offset(input);
+        let (mut input, fields_count) =
+            be_u16(input).map_err(malformed(offset, Location::Class))?;
+        for index in 0..fields_count {
+            let offset = self.offset(input);
+            let location = Location::Field(format!("#{index}"));
+            let (rest, field) = parse_field_with(input, &constant_pool, parse_raw_attribute)
+                .map_err(malformed(offset, location))?;
+            let name = member_name(
+                &constant_pool,
+                field.name_index,
+                field.descriptor_index,
+                index,
+            );
+            let location = Location::Field(name);
+            if constant_pool.utf8(field.name_index).is_err() {
+                self.report(
+                    offset,
+                    location.clone(),
+                    ViolationKind::InvalidName(field.name_index),
+                );
+            }
+            let valid = match constant_pool.get(field.descriptor_index) {
+                Some(Constant::Utf8 { value }) => {
+                    matches!(parse_field_type(value), Ok((rest, _)) if rest.is_empty())
+                }
+                _ => false,
+            };
+            if !valid {
+                self.report(
+                    offset,
+                    location.clone(),
+                    ViolationKind::InvalidFieldDescriptor,
+                );
+            }
+            self.attributes(&field.attributes, offset + 8, &constant_pool, &location);
+            input = rest;
+        }
+
+        let offset = self.offset(input);
+        let (mut input, methods_count) =
+            be_u16(input).map_err(malformed(offset, Location::Class))?;
+        for index in 0..methods_count {
+            let offset = self.offset(input);
+            let location = Location::Method(format!("#{index}"));
+            let (rest, method) = parse_method_with(input, &constant_pool, parse_raw_attribute)
+                .map_err(malformed(offset, location))?;
+            let name = member_name(
+                &constant_pool,
+                method.name_index,
+                method.descriptor_index,
+                index,
+            );
+            let location = Location::Method(name.clone());
+            if constant_pool.utf8(method.name_index).is_err() {
+                self.report(
+                    offset,
+                    location.clone(),
+                    ViolationKind::InvalidName(method.name_index),
+                );
+            }
+            let descriptor = match constant_pool.get(method.descriptor_index) {
+                Some(Constant::Utf8 { value }) => MethodDescriptor::parse(value).ok(),
+                _ => None,
+            };
+            if descriptor.is_none() {
+                self.report(
+                    offset,
+                    location.clone(),
+                    ViolationKind::InvalidMethodDescriptor,
+                );
+            }
+            let is_static = method.access_flags.contains(MethodAccessFlags::STATIC);
+            let attributes =
+                self.attributes(&method.attributes, offset + 8, &constant_pool, &location);
+            for (attribute_offset, attribute) in attributes {
+                if let Attribute::Code(code) = attribute {
+                    let descriptor = descriptor
+                        .as_ref()
+                        .map(|descriptor| (descriptor, is_static));
+                    self.code(&name, &code, attribute_offset, &constant_pool, descriptor);
+                }
+            }
+            input = rest;
+        }
+
+        let offset = self.offset(input);
+        let (mut input, attributes_count) =
+            be_u16(input).map_err(malformed(offset, Location::Class))?;
+        let mut attributes = Vec::new();
+        for _ in 0..attributes_count {
+            let offset = self.offset(input);
+            let (rest, attribute) = parse_raw_attribute(input, &constant_pool)
+                .map_err(malformed(offset, Location::Class))?;
+            attributes.push(attribute);
+            input = rest;
+        }
+        self.attributes(&attributes, offset + 2, &constant_pool, &Location::Class);
+
+        if !input.is_empty() {
+            let offset = self.offset(input);
+            self.report(
+                offset,
+                Location::Class,
+                ViolationKind::TrailingBytes(input.len()),
+            );
+        }
+        Ok(())
+    }
+
+    // parses the constant pool an entry at a time, to know where each one is
+    fn constant_pool(
+        &mut self,
+        input: &'a [u8],
+    ) -> Result<(&'a [u8], ConstantPool<'a>), Malformed> {
+        let offset = self.offset(input);
+        let (mut input, count) = be_u16(input).map_err(malformed(offset, Location::Class))?;
+        let mut constant_pool = ConstantPool::new();
+        let mut offsets = BTreeMap::new();
+        while constant_pool.len() + 1 < count as usize {
+            let offset = self.offset(input);
+            let index = constant_pool.len() as u16 + 1;
+            let (rest, constant) =
+                parse_constant(input).map_err(malformed(offset, Location::Constant(index)))?;
+            constant_pool.push(constant);
+            offsets.insert(index, offset);
+            input = rest;
+        }
+        for violation in constant_pool.validate() {
+            self.report(
+                offsets[&violation.index],
+                Location::Constant(violation.index),
+                ViolationKind::InvalidConstant(violation.error),
+            );
+        }
+        Ok((input, constant_pool))
+    }
+
+    // decodes the raw attributes starting at `offset`, returning the ones
+    // that decode with where each starts
+    fn attributes<'b>(
+        &mut self,
+        attributes: &'b [Attribute<'b>],
+        mut offset: usize,
+        constant_pool: &ConstantPool<'b>,
+        location: &Location,
+    ) -> Vec<(usize, Attribute<'b>)> {
+        let mut decoded = Vec::new();
+        for attribute in attributes {
+            let Attribute::Raw { name, data, .. } = attribute else {
+                continue;
+            };
+            match attribute.decode(constant_pool) {
+                Some(Ok(attribute)) => decoded.push((offset, attribute)),
+                Some(Err(error)) => {
+                    let name = String::from_utf8_lossy(name).into_owned();
+                    self.report(
+                        offset,
+                        location.clone(),
+                        ViolationKind::InvalidAttribute { name, error },
+                    );
+                }
+                None => {}
+            }
+            offset += 6 + data.len();
+        }
+        decoded
+    }
+
+    // the code of a method whose Code attribute is at `offset`, given its
+    // descriptor and whether it's static when the descriptor is valid
+    fn code(
+        &mut self,
+        method: &str,
+        code: &Code<Attribute>,
+        offset: usize,
+        constant_pool: &ConstantPool,
+        descriptor: Option<(&MethodDescriptor, bool)>,
+    ) {
+        let at = |pc: u32| Location::Code {
+            method: method.to_string(),
+            pc,
+        };
+        // past the attribute header, max_stack, max_locals and code_length
+        let code_offset = offset + 14;
+        let length = code.code.len();
+        if length == 0 || length > u16::MAX as usize {
+            self.report(offset, at(0), ViolationKind::InvalidCodeLength(length));
+            return;
+        }
+        let instructions = match disassemble(&code.code).collect::<Result<Vec<_>, _>>() {
+            Ok(instructions) => instructions,
+            Err(error) => {
+                let pc = error.pc().unwrap_or(0);
+                let offset = code_offset + pc as usize;
+                self.report(offset, at(pc), ViolationKind::InvalidInstruction(error));
+                return;
+            }
+        };
+
+        let mut operands_valid = true;
+        for (pc, instruction) in &instructions {
+            if let Err(error) = instruction.resolve(constant_pool) {
+                operands_valid = false;
+                let offset = code_offset + *pc as usize;
+                self.report(offset, at(*pc), ViolationKind::InvalidOperand(error));
+            }
+        }
+        // the stack effects of instructions come from the operands
+        if operands_valid {
+            match compute_max_stack(&instructions, &code.exception_table, constant_pool) {
+                Ok(computed) if computed > code.max_stack => self.report(
+                    offset,
+                    at(0),
+                    ViolationKind::MaxStackTooSmall {
+                        declared: code.max_stack,
+                        computed,
+                    },
+                ),
+                Ok(_) => {}
+                Err(error) => {
+                    let pc = stack_error_pc(&error);
+                    let offset = code_offset + pc as usize;
+                    self.report(offset, at(pc), ViolationKind::InvalidStack(error));
+                }
+            }
+        }
+        if let Some((descriptor, is_static)) = descriptor {
+            let computed = compute_max_locals(&instructions, descriptor, is_static);
+            if computed > code.max_locals {
+                self.report(
+                    offset,
+                    at(0),
+                    ViolationKind::MaxLocalsTooSmall {
+                        declared: code.max_locals,
+                        computed,
+                    },
+                );
+            }
+        }
+
+        // (table, start, end) of each pc range the code's tables hold, where
+        // handlers and line numbers only have a start
+        let mut ranges = Vec::new();
+        for entry in &code.exception_table {
+            ranges.push(("exception_table", entry.start_pc, Some(entry.end_pc as u32)));
+            ranges.push(("exception_table", entry.handler_pc, None));
+            if entry.catch_type != 0
+                && !matches!(
+                    constant_pool.get(entry.catch_type),
+                    Some(Constant::Class { .. })
+                )
+            {
+                self.report(
+                    offset,
+                    at(entry.handler_pc as u32),
+                    ViolationKind::InvalidClassIndex(entry.catch_type),
+                );
+            }
+        }
+        for attribute in &code.attributes {
+            match attribute {
+                Attribute::LineNumberTable(table) => {
+                    for entry in &table.line_number_table {
+                        ranges.push(("LineNumberTable", entry.start_pc, None));
+                    }
+                }
+                Attribute::LocalVariableTable(table) => {
+                    for entry in &table.local_variable_table {
+                        let end = entry.start_pc as u32 + entry.length as u32;
+                        ranges.push(("LocalVariableTable", entry.start_pc, Some(end)));
+                    }
+                }
+                Attribute::LocalVariableTypeTable(table) => {
+                    for entry in &table.local_variable_type_table {
+                        let end = entry.start_pc as u32 + entry.length as u32;
+                        ranges.push(("LocalVariableTypeTable", entry.start_pc, Some(end)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        // a range starts at an instruction and ends after its start, at an
+        // instruction or the end of the code
+        let starts = instructions
+            .iter()
+            .map(|(pc, _)| *pc)
+            .collect::<BTreeSet<_>>();
+        for (table, start, end) in ranges {
+            let start = start as u32;
+            let bad_pc = match end {
+                _ if !starts.contains(&start) => Some(start),
+                Some(end) if end <= start => Some(end),
+                Some(end) if end as usize != length && !starts.contains(&end) => Some(end),
+                _ => None,
+            };
+            if let Some(pc) = bad_pc {
+                self.report(offset, at(pc), ViolationKind::InvalidPc(table));
+            }
+        }
+    }
+}
+
+// checks a class file's structure, reporting everything wrong with it
+// rather than stopping at the first problem as parsing does: the constant
+// pool's references, the class references in the header, member
+// descriptors, that each attribute decodes within its length, and each
+// method's code: that its instructions decode, their operands refer to
+// constants of the right kind, the stack works out on every path within
+// max_stack and max_locals, and the pcs in its tables are at instructions.
+// empty for a well-formed class
+pub fn verify_class(data: &[u8]) -> Vec<Violation> {
+    let mut verifier = Verifier {
+        data,
+        violations: Vec::new(),
+    };
+    if let Err((offset, location, error)) = verifier.class() {
+        verifier.report(offset, location, ViolationKind::Malformed(error));
+    }
+    verifier.violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::{ConstantPoolError, ConstantTag};
+
+    fn find(data: &[u8], bytes: &[u8]) -> usize {
+        data.windows(bytes.len())
+            .position(|window| window == bytes)
+            .unwrap()
+    }
+
+    fn say_hello(pc: u32) -> Location {
+        Location::Code {
+            method: "sayHello:()V".to_string(),
+            pc,
+        }
+    }
+
+    #[test]
+    fn test_verify_class() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        assert_eq!(verify_class(data), vec![]);
+        let data = include_bytes!("../../../../java/Switches.class");
+        assert_eq!(verify_class(data), vec![]);
+    }
+
+    #[test]
+    fn test_verify_code() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        // getstatic, aload_0, getfield, invokevirtual, return, with the Code
+        // attribute's header and max_stack, max_locals and code_length before
+        let code = find(data, &[0xb2, 0x00, 0x0f, 0x2a, 0xb4, 0x00, 0x09]);
+        let attribute = code - 14;
+
+        // return becomes nop
+        let mut corrupt = data.to_vec();
+        corrupt[code + 10] = 0x00;
+        assert_eq!(
+            verify_class(&corrupt),
+            vec![Violation {
+                offset: code + 10,
+                location: say_hello(10),
+                kind: ViolationKind::InvalidStack(MaxStackError::FallsOffEnd(10)),
+            }]
+        );
+
+        // getfield of a Methodref
+        let mut corrupt = data.to_vec();
+        corrupt[code + 6] = 0x01;
+        assert_eq!(
+            verify_class(&corrupt),
+            vec![Violation {
+                offset: code + 4,
+                location: say_hello(4),
+                kind: ViolationKind::InvalidOperand(ResolveError::ConstantPoolError(
+                    ConstantPoolError::UnexpectedConstant {
+                        index: 1,
+                        expected: ConstantTag::Fieldref,
+                        found: ConstantTag::Methodref,
+                    }
+                )),
+            }]
+        );
+
+        let mut corrupt = data.to_vec();
+        corrupt[code - 7] = 1;
+        corrupt[code - 5] = 0;
+        assert_eq!(
+            verify_class(&corrupt),
+            vec![
+                Violation {
+                    offset: attribute,
+                    location: say_hello(0),
+                    kind: ViolationKind::MaxStackTooSmall {
+                        declared: 1,
+                        computed: 2
+                    },
+                },
+                Violation {
+                    offset: attribute,
+                    location: say_hello(0),
+                    kind: ViolationKind::MaxLocalsTooSmall {
+                        declared: 0,
+                        computed: 1
+                    },
+                },
+            ]
+        );
+
+        // the second line number starts past the end of the code
+        let mut corrupt = data.to_vec();
+        corrupt[code + 28] = 11;
+        assert_eq!(
+            verify_class(&corrupt),
+            vec![Violation {
+                offset: attribute,
+                location: say_hello(11),
+                kind: ViolationKind::InvalidPc("LineNumberTable"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_verify_structure() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+
+        // the first constant, a Methodref, names itself as its class
+        let mut corrupt = data.to_vec();
+        corrupt[12] = 0x01;
+        let violations = verify_class(&corrupt);
+        assert_eq!(
+            violations[0],
+            Violation {
+                offset: 10,
+                location: Location::Constant(1),
+                kind: ViolationKind::InvalidConstant(ConstantPoolError::UnexpectedConstant {
+                    index: 1,
+                    expected: ConstantTag::Class,
+                    found: ConstantTag::Methodref,
+                }),
+            }
+        );
+
+        let mut corrupt = data.to_vec();
+        let descriptor = find(data, b"([Ljava/lang/String;)V");
+        corrupt[descriptor + 21] = b'Q';
+        let violations = verify_class(&corrupt);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(
+            violations[0].location,
+            Location::Method("main:([Ljava/lang/String;)Q".to_string())
+        );
+        assert_eq!(violations[0].kind, ViolationKind::InvalidMethodDescriptor);
+
+        // cut off in the middle of the SourceFile attribute, which starts
+        // 8 bytes from the end
+        let violations = verify_class(&data[..data.len() - 3]);
+        assert_eq!(
+            violations,
+            vec![Violation {
+                offset: data.len() - 8,
+                location: Location::Class,
+                kind: ViolationKind::Malformed(ClassParseError::ParseError(
+                    crate::parser::ParseError::Eof
+                )),
+            }]
+        );
+
+        let mut corrupt = data.to_vec();
+        corrupt.push(0);
+        assert_eq!(
+            verify_class(&corrupt),
+            vec![Violation {
+                offset: data.len(),
+                location: Location::Class,
+                kind: ViolationKind::TrailingBytes(1),
+            }]
+        );
+        assert_eq!(
+            verify_class(b"\xca\xfe\xba\xbf"),
+            vec![Violation {
+                offset: 0,
+                location: Location::Class,
+                kind: ViolationKind::Malformed(ClassParseError::NotAClassFile(0xcafebabf)),
+            }]
+        );
+    }
+}
@@ -0,0 +1,15 @@
+mod class_path;
+mod error;
+mod inflate;
+mod jimage;
+mod jmod;
+mod manifest;
+mod zip;
+
+pub use class_path::*;
+pub use error::*;
+pub use inflate::*;
+pub use jimage::*;
+pub use jmod::*;
+pub use manifest::*;
+pub use zip::*;
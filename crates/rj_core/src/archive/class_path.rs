@@ -0,0 +1,383 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::error::{ArchiveError, ClassPathError};
+use super::jimage::JImage;
+use super::jmod::Jmod;
+use super::zip::ZipArchive;
+
+// where a class was read from
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClassLocation {
+    File(PathBuf),
+    // an archive and the name of the entry in it
+    Archive(PathBuf, String),
+}
+
+impl fmt::Display for ClassLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClassLocation::File(path) => write!(f, "{}", path.display()),
+            ClassLocation::Archive(path, name) => write!(f, "{}!{name}", path.display()),
+        }
+    }
+}
+
+// the kinds of file a class path entry can be, told apart by their first
+// bytes
+pub enum Archive {
+    Zip(ZipArchive),
+    Jmod(Jmod),
+    Image(JImage),
+}
+
+impl Archive {
+    pub fn open(path: &Path) -> Result<Self, ClassPathError> {
+        let data = fs::read(path)
+            .map_err(|error| ClassPathError::IoError(path.to_path_buf(), error.kind()))?;
+        let archive = match data.get(..4) {
+            Some([b'J', b'M', 1, 0]) => Jmod::new(data).map(Archive::Jmod),
+            Some([0xda, 0xda, 0xfe, 0xca] | [0xca, 0xfe, 0xda, 0xda]) => {
+                JImage::new(data).map(Archive::Image)
+            }
+            _ => ZipArchive::new(data).map(Archive::Zip),
+        };
+        archive.map_err(|error| ClassPathError::ArchiveError(path.to_path_buf(), error))
+    }
+
+    // the entry `name`: a path in a jar or jmod, or a full name in an image
+    // like `/java.base/java/lang/Object.class`
+    pub fn read(&self, name: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        match self {
+            Archive::Zip(archive) => archive.read(name.trim_start_matches('/')),
+            Archive::Jmod(jmod) => jmod.archive().read(name.trim_start_matches('/')),
+            Archive::Image(image) => image.read(name),
+        }
+    }
+
+    // the names of the class files, in the order the archive lists them
+    pub fn class_names(&self) -> Result<Vec<String>, ArchiveError> {
+        let archive = match self {
+            Archive::Zip(archive) => archive,
+            Archive::Jmod(jmod) => jmod.archive(),
+            Archive::Image(image) => {
+                let mut names = image.names()?;
+                names.retain(|name| name.ends_with(".class"));
+                return Ok(names);
+            }
+        };
+        Ok(archive
+            .entries()
+            .iter()
+            .filter(|entry| entry.is_class())
+            .map(|entry| entry.name.clone())
+            .collect())
+    }
+
+    // the class in `file_name`, like `java/lang/Object.class`, and the name
+    // of the entry it was found in
+    fn find(&self, file_name: &str) -> Result<Option<(String, Vec<u8>)>, ArchiveError> {
+        let entry = match self {
+            Archive::Zip(_) => file_name.to_string(),
+            Archive::Jmod(_) => format!("classes/{file_name}"),
+            Archive::Image(image) => {
+                let name = file_name.strip_suffix(".class").unwrap_or(file_name);
+                let found = image.find_class(name)?;
+                let found = found.map(|(module, data)| (format!("/{module}/{file_name}"), data));
+                return Ok(found);
+            }
+        };
+        Ok(self.read(&entry)?.map(|data| (entry, data)))
+    }
+}
+
+enum ClassPathEntry {
+    Directory(PathBuf),
+    Archive(PathBuf, Archive),
+}
+
+// `com.example.Foo` or `com/example/Foo` as the path of its class file
+fn class_file_name(name: &str) -> String {
+    let name = name.strip_suffix(".class").unwrap_or(name);
+    format!("{}.class", name.replace('.', "/"))
+}
+
+fn read_file(path: &Path) -> Result<Option<Vec<u8>>, ClassPathError> {
+    match fs::read(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(ClassPathError::IoError(path.to_path_buf(), error.kind())),
+    }
+}
+
+// the directories and archives classes are looked up in, in order, as
+// given to `java -cp`. archives can be jars, jmods or a JDK's lib/modules
+#[derive(Default)]
+pub struct ClassPath {
+    entries: Vec<ClassPathEntry>,
+}
+
+impl ClassPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // a search path in the platform's format, like `lib/*:build/classes`,
+    // where `dir/*` stands for the jars in `dir`. like the JVM, entries that
+    // don't exist are skipped
+    pub fn parse(search_path: &str) -> Result<Self, ClassPathError> {
+        let mut class_path = Self::new();
+        class_path.push_search_path(search_path)?;
+        Ok(class_path)
+    }
+
+    // adds the entries of a search path, as `parse` reads it
+    pub fn push_search_path(&mut self, search_path: &str) -> Result<&mut Self, ClassPathError> {
+        for path in env::split_paths(search_path) {
+            if path.file_name().is_some_and(|name| name == "*") {
+                let directory = path.parent().unwrap_or(Path::new(""));
+                let directory = match directory.as_os_str().is_empty() {
+                    true => Path::new("."),
+                    false => directory,
+                };
+                let Ok(listing) = fs::read_dir(directory) else {
+                    continue;
+                };
+                let mut jars = listing
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| {
+                        path.extension()
+                            .is_some_and(|extension| extension.eq_ignore_ascii_case("jar"))
+                    })
+                    .collect::<Vec<_>>();
+                jars.sort();
+                for jar in jars {
+                    self.push(jar)?;
+                }
+            } else if path.exists() {
+                self.push(path)?;
+            }
+        }
+        Ok(self)
+    }
+
+    // adds a directory, or a jar, jmod or jimage file, to the end of the
+    // path
+    pub fn push<P: AsRef<Path>>(&mut self, path: P) -> Result<&mut Self, ClassPathError> {
+        let path = path.as_ref();
+        let entry = match path.is_file() {
+            true => ClassPathEntry::Archive(path.to_path_buf(), Archive::open(path)?),
+            false => ClassPathEntry::Directory(path.to_path_buf()),
+        };
+        self.entries.push(entry);
+        Ok(self)
+    }
+
+    // adds the classes of the JDK or JRE installed at `java_home`: its
+    // lib/modules from Java 9 on, its rt.jar before
+    pub fn push_system<P: AsRef<Path>>(
+        &mut self,
+        java_home: P,
+    ) -> Result<&mut Self, ClassPathError> {
+        let java_home = java_home.as_ref();
+        let candidates = [
+            java_home.join("lib/modules"),
+            java_home.join("jre/lib/rt.jar"),
+            java_home.join("lib/rt.jar"),
+        ];
+        match candidates.iter().find(|path| path.is_file()) {
+            Some(path) => self.push(path),
+            None => Err(ClassPathError::IoError(
+                candidates[0].clone(),
+                io::ErrorKind::NotFound,
+            )),
+        }
+    }
+
+    // the first class named `name`, a binary name like `com.example.Foo` or
+    // an internal one like `com/example/Foo`
+    pub fn find(&self, name: &str) -> Result<Option<(ClassLocation, Vec<u8>)>, ClassPathError> {
+        let file_name = class_file_name(name);
+        for entry in &self.entries {
+            match entry {
+                ClassPathEntry::Directory(directory) => {
+                    let path = directory.join(&file_name);
+                    if let Some(data) = read_file(&path)? {
+                        return Ok(Some((ClassLocation::File(path), data)));
+                    }
+                }
+                ClassPathEntry::Archive(path, archive) => {
+                    let found = archive
+                        .find(&file_name)
+                        .map_err(|error| ClassPathError::ArchiveError(path.clone(), error))?;
+                    if let Some((entry, data)) = found {
+                        let location = ClassLocation::Archive(path.clone(), entry);
+                        return Ok(Some((location, data)));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+// reads a class file by path, or from an archive as `app.jar!com/example/
+// Foo.class`, `java.base.jmod!classes/java/lang/Object.class` or
+// `lib/modules!/java.base/java/lang/Object.class`
+pub fn read_class(location: &str) -> Result<(ClassLocation, Vec<u8>), ClassPathError> {
+    let Some((archive, name)) = location.split_once('!') else {
+        let path = PathBuf::from(location);
+        return match fs::read(&path) {
+            Ok(data) => Ok((ClassLocation::File(path), data)),
+            Err(error) => Err(ClassPathError::IoError(path, error.kind())),
+        };
+    };
+    let path = PathBuf::from(archive);
+    let archive = Archive::open(&path)?;
+    let name = match archive {
+        Archive::Image(_) => format!("/{}", name.trim_start_matches('/')),
+        _ => name.trim_start_matches('/').to_string(),
+    };
+    let data = archive
+        .read(&name)
+        .map_err(|error| ClassPathError::ArchiveError(path.clone(), error))?;
+    match data {
+        Some(data) => Ok((ClassLocation::Archive(path, name), data)),
+        None => Err(ClassPathError::EntryNotFound(path, name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../../java")
+    }
+
+    #[test]
+    fn test_class_path_find() {
+        let jar = fixtures().join("classes.jar");
+        let mut class_path = ClassPath::new();
+        class_path.push(&jar).unwrap().push(fixtures()).unwrap();
+
+        // the jar comes first, then the directory
+        let (location, data) = class_path.find("HelloWorld").unwrap().unwrap();
+        assert_eq!(
+            location,
+            ClassLocation::Archive(jar.clone(), "HelloWorld.class".into())
+        );
+        assert_eq!(data, include_bytes!("../../../../java/HelloWorld.class"));
+        let (location, _) = class_path.find("Outer$Inner").unwrap().unwrap();
+        assert_eq!(
+            location.to_string(),
+            format!("{}!Outer$Inner.class", jar.display())
+        );
+        let (location, data) = class_path.find("Frames").unwrap().unwrap();
+        assert_eq!(
+            location,
+            ClassLocation::File(fixtures().join("Frames.class"))
+        );
+        assert_eq!(data, include_bytes!("../../../../java/Frames.class"));
+        assert_eq!(class_path.find("java.lang.Object"), Ok(None));
+
+        assert_eq!(class_file_name("com.example.Foo"), "com/example/Foo.class");
+        assert_eq!(class_file_name("com/example/Foo"), "com/example/Foo.class");
+        assert_eq!(class_file_name("Foo.class"), "Foo.class");
+    }
+
+    #[test]
+    fn test_class_path_modules() {
+        let jmod = fixtures().join("demo.jmod");
+        let image = fixtures().join("demo.jimage");
+        let greeter = include_bytes!("../../../../java/demo/demo/Greeter.class");
+        for (path, entry) in [
+            (&jmod, "classes/demo/Greeter.class"),
+            (&image, "/demo/demo/Greeter.class"),
+        ] {
+            let mut class_path = ClassPath::new();
+            class_path.push(path).unwrap();
+            let (location, data) = class_path.find("demo.Greeter").unwrap().unwrap();
+            assert_eq!(location, ClassLocation::Archive(path.clone(), entry.into()));
+            assert_eq!(data, greeter);
+            assert_eq!(class_path.find("demo.Missing"), Ok(None));
+            assert_eq!(read_class(&location.to_string()).unwrap().1, greeter);
+        }
+
+        let missing = fixtures().join("missing");
+        assert!(matches!(
+            ClassPath::new().push_system(&missing),
+            Err(ClassPathError::IoError(path, io::ErrorKind::NotFound))
+                if path == missing.join("lib/modules")
+        ));
+    }
+
+    #[test]
+    fn test_archive_class_names() {
+        let archive = Archive::open(&fixtures().join("classes.jar")).unwrap();
+        assert_eq!(
+            archive.class_names().unwrap(),
+            ["HelloWorld.class", "Point.class", "Outer$Inner.class"]
+        );
+        let archive = Archive::open(&fixtures().join("demo.jmod")).unwrap();
+        assert_eq!(
+            archive.class_names().unwrap(),
+            ["classes/module-info.class", "classes/demo/Greeter.class"]
+        );
+        let archive = Archive::open(&fixtures().join("demo.jimage")).unwrap();
+        let mut names = archive.class_names().unwrap();
+        names.sort();
+        assert_eq!(
+            names,
+            ["/demo/demo/Greeter.class", "/demo/module-info.class"]
+        );
+    }
+
+    #[test]
+    fn test_class_path_parse() {
+        let missing = fixtures().join("missing");
+        let search_path = env::join_paths([missing, fixtures().join("*")]).unwrap();
+        let class_path = ClassPath::parse(search_path.to_str().unwrap()).unwrap();
+        // just the jar, so nothing from the directory itself
+        assert_eq!(class_path.entries.len(), 1);
+        assert!(class_path.find("Point").unwrap().is_some());
+        assert_eq!(class_path.find("Frames"), Ok(None));
+
+        let search_path = fixtures().join("HelloWorld.class");
+        assert!(matches!(
+            ClassPath::parse(search_path.to_str().unwrap()),
+            Err(ClassPathError::ArchiveError(_, ArchiveError::NotAnArchive))
+        ));
+    }
+
+    #[test]
+    fn test_read_class() {
+        let jar = fixtures().join("classes.jar");
+        let (location, data) = read_class(&format!("{}!Point.class", jar.display())).unwrap();
+        assert_eq!(
+            location,
+            ClassLocation::Archive(jar.clone(), "Point.class".into())
+        );
+        assert_eq!(data, include_bytes!("../../../../java/Point.class"));
+        assert_eq!(
+            read_class(&format!("{}!/Point.class", jar.display())).unwrap(),
+            (location, data)
+        );
+        assert_eq!(
+            read_class(&format!("{}!Frames.class", jar.display())),
+            Err(ClassPathError::EntryNotFound(jar, "Frames.class".into()))
+        );
+
+        let path = fixtures().join("Frames.class");
+        let (location, _) = read_class(path.to_str().unwrap()).unwrap();
+        assert_eq!(location, ClassLocation::File(path));
+        let path = fixtures().join("Missing.class");
+        assert_eq!(
+            read_class(path.to_str().unwrap()),
+            Err(ClassPathError::IoError(path, io::ErrorKind::NotFound))
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, PartialEq)]
+pub enum InflateError {
+    // the stream stopped before its last block did
+    UnexpectedEnd,
+    // a block type of 3, which is reserved
+    InvalidBlockType,
+    // a stored block whose length doesn't match its complement
+    InvalidStoredLength,
+    // a malformed Huffman code, or a symbol it can't decode
+    InvalidCode,
+    // a match reaching back before the start of the output
+    InvalidDistance(u32),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ArchiveError {
+    IoError(io::ErrorKind),
+    // no end of central directory record was found
+    NotAnArchive,
+    // a jmod or jimage file that doesn't start with its magic number
+    InvalidMagic(u32),
+    // a jimage format other than 1.x
+    UnsupportedVersion(u32),
+    // a header past the end of the data, or without its signature
+    InvalidHeader(u32),
+    // zip64 archives, over 4GB or 65535 entries, aren't read
+    Zip64,
+    // an entry compressed with something other than store or deflate
+    UnsupportedMethod(String, u16),
+    // a jimage resource compressed by a jlink plugin other than zip
+    UnsupportedCompression(String, String),
+    InflateError(String, InflateError),
+    // the entry's data doesn't match its CRC-32 or size
+    ChecksumMismatch(String),
+}
+
+impl From<io::Error> for ArchiveError {
+    fn from(error: io::Error) -> Self {
+        ArchiveError::IoError(error.kind())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ClassPathError {
+    IoError(PathBuf, io::ErrorKind),
+    ArchiveError(PathBuf, ArchiveError),
+    // an `archive!entry` location whose entry isn't in the archive
+    EntryNotFound(PathBuf, String),
+}
+
+impl fmt::Display for InflateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InflateError::UnexpectedEnd => write!(f, "unexpected end of compressed data"),
+            InflateError::InvalidBlockType => write!(f, "invalid deflate block type"),
+            InflateError::InvalidStoredLength => {
+                write!(f, "a stored block length that doesn't match its complement")
+            }
+            InflateError::InvalidCode => write!(f, "invalid Huffman code"),
+            InflateError::InvalidDistance(distance) => {
+                write!(f, "a distance of {distance} before the start of the output")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArchiveError::IoError(kind) => write!(f, "{kind}"),
+            ArchiveError::NotAnArchive => write!(f, "not a jar, jmod or jimage"),
+            ArchiveError::InvalidMagic(magic) => write!(f, "invalid magic number {magic:#010x}"),
+            ArchiveError::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported jimage version {}.{}",
+                version >> 16,
+                version & 0xffff
+            ),
+            ArchiveError::InvalidHeader(offset) => write!(f, "invalid header at offset {offset}"),
+            ArchiveError::Zip64 => write!(f, "zip64 archives aren't supported"),
+            ArchiveError::UnsupportedMethod(name, method) => {
+                write!(f, "{name}: unsupported compression method {method}")
+            }
+            ArchiveError::UnsupportedCompression(name, compression) => {
+                write!(f, "{name}: unsupported compression {compression}")
+            }
+            ArchiveError::InflateError(name, error) => write!(f, "{name}: {error}"),
+            ArchiveError::ChecksumMismatch(name) => {
+                write!(f, "{name}: the data doesn't match its checksum")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ClassPathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClassPathError::IoError(path, kind) => write!(f, "{}: {kind}", path.display()),
+            ClassPathError::ArchiveError(path, error) => {
+                write!(f, "{}: {error}", path.display())
+            }
+            ClassPathError::EntryNotFound(path, name) => {
+                write!(f, "{}!{name}: not found", path.display())
+            }
+        }
+    }
+}
@@ -0,0 +1,379 @@
+use std::fs;
+use std::path::Path;
+
+use super::error::ArchiveError;
+use super::inflate::inflate;
+
+const MAGIC: u32 = 0xcafedada;
+const HEADER_SIZE: usize = 28;
+const HASH_MULTIPLIER: u32 = 0x01000193;
+// a resource compressed by a jlink plugin starts with this
+const COMPRESSED_MAGIC: u32 = 0xcafefafa;
+const COMPRESSED_HEADER_SIZE: usize = 29;
+
+const ATTRIBUTE_MODULE: usize = 1;
+const ATTRIBUTE_PARENT: usize = 2;
+const ATTRIBUTE_BASE: usize = 3;
+const ATTRIBUTE_EXTENSION: usize = 4;
+const ATTRIBUTE_OFFSET: usize = 5;
+const ATTRIBUTE_COMPRESSED: usize = 6;
+const ATTRIBUTE_UNCOMPRESSED: usize = 7;
+
+// the FNV-1a style hash names are placed in the lookup table with. names are
+// ASCII in practice, where modified UTF-8 and UTF-8 agree
+fn hash(name: &str, seed: u32) -> u32 {
+    let mut hash = seed;
+    for byte in name.bytes() {
+        hash = hash.wrapping_mul(HASH_MULTIPLIER) ^ byte as u32;
+    }
+    hash & 0x7fffffff
+}
+
+// the attributes of a resource, indexed by ATTRIBUTE_*
+type Location = [u64; 8];
+
+// the `lib/modules` file of a JDK or jlink image: the classes and resources
+// of every module in the runtime, found through a perfect hash of their
+// names, like `/java.base/java/lang/Object.class`
+#[derive(Debug)]
+pub struct JImage {
+    data: Vec<u8>,
+    big_endian: bool,
+    table_length: u32,
+    locations_size: u32,
+    strings_size: u32,
+}
+
+impl JImage {
+    pub fn new(data: Vec<u8>) -> Result<Self, ArchiveError> {
+        let magic = data.get(..4).ok_or(ArchiveError::InvalidMagic(0))?;
+        let magic = [magic[0], magic[1], magic[2], magic[3]];
+        // written in the byte order of the platform it was built for
+        let big_endian = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+            (MAGIC, _) => false,
+            (_, MAGIC) => true,
+            (_, magic) => return Err(ArchiveError::InvalidMagic(magic)),
+        };
+        let mut image = JImage {
+            data,
+            big_endian,
+            table_length: 0,
+            locations_size: 0,
+            strings_size: 0,
+        };
+        let header = |index: usize| image.u32_at(index * 4);
+        let (Some(version), Some(table_length), Some(locations_size), Some(strings_size)) =
+            (header(1), header(4), header(5), header(6))
+        else {
+            return Err(ArchiveError::InvalidHeader(0));
+        };
+        if version >> 16 != 1 {
+            return Err(ArchiveError::UnsupportedVersion(version));
+        }
+        image.table_length = table_length;
+        image.locations_size = locations_size;
+        image.strings_size = strings_size;
+        if image.data.len() < image.index_size() {
+            return Err(ArchiveError::InvalidHeader(0));
+        }
+        Ok(image)
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ArchiveError> {
+        Self::new(fs::read(path)?)
+    }
+
+    // a u32 of `data` in the image's byte order
+    fn u32_in(&self, data: &[u8], offset: usize) -> Option<u32> {
+        let bytes = data.get(offset..offset + 4)?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Some(match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+
+    fn u64_in(&self, data: &[u8], offset: usize) -> Option<u64> {
+        let (first, second) = (self.u32_in(data, offset)?, self.u32_in(data, offset + 4)?);
+        Some(match self.big_endian {
+            true => (first as u64) << 32 | second as u64,
+            false => (second as u64) << 32 | first as u64,
+        })
+    }
+
+    fn u32_at(&self, offset: usize) -> Option<u32> {
+        self.u32_in(&self.data, offset)
+    }
+
+    fn redirect_start(&self) -> usize {
+        HEADER_SIZE
+    }
+
+    fn offsets_start(&self) -> usize {
+        self.redirect_start() + self.table_length as usize * 4
+    }
+
+    fn locations_start(&self) -> usize {
+        self.offsets_start() + self.table_length as usize * 4
+    }
+
+    fn strings_start(&self) -> usize {
+        self.locations_start() + self.locations_size as usize
+    }
+
+    // where resource contents start
+    fn index_size(&self) -> usize {
+        self.strings_start() + self.strings_size as usize
+    }
+
+    fn string(&self, offset: u64) -> Result<&str, ArchiveError> {
+        let invalid = || ArchiveError::InvalidHeader(offset as u32);
+        let start = self.strings_start() + offset as usize;
+        let strings = self
+            .data
+            .get(start..self.index_size())
+            .ok_or_else(invalid)?;
+        let end = strings
+            .iter()
+            .position(|&byte| byte == 0)
+            .ok_or_else(invalid)?;
+        std::str::from_utf8(&strings[..end]).map_err(|_| invalid())
+    }
+
+    fn location(&self, index: u32) -> Result<Location, ArchiveError> {
+        let offset = self
+            .u32_at(self.offsets_start() + index as usize * 4)
+            .ok_or(ArchiveError::InvalidHeader(index))?;
+        let invalid = || ArchiveError::InvalidHeader(offset);
+        let start = self.locations_start() + offset as usize;
+        let bytes = self
+            .data
+            .get(start..self.strings_start())
+            .ok_or_else(invalid)?;
+        let mut location = [0; 8];
+        let mut position = 0;
+        // each attribute is a byte of kind << 3 | length - 1, then the value
+        // in that many big endian bytes, up to a kind of 0
+        while let Some(&byte) = bytes.get(position) {
+            let (kind, length) = ((byte >> 3) as usize, (byte & 7) as usize + 1);
+            if kind == 0 {
+                break;
+            }
+            let value = bytes
+                .get(position + 1..position + 1 + length)
+                .ok_or_else(invalid)?;
+            location[kind] = value
+                .iter()
+                .fold(0u64, |value, &byte| value << 8 | byte as u64);
+            position += 1 + length;
+        }
+        Ok(location)
+    }
+
+    // `/module/parent/base.extension`, leaving out the parts that are empty
+    fn location_name(&self, location: &Location) -> Result<String, ArchiveError> {
+        let mut name = String::new();
+        let module = self.string(location[ATTRIBUTE_MODULE])?;
+        if !module.is_empty() {
+            name.push_str(&format!("/{module}/"));
+        }
+        let parent = self.string(location[ATTRIBUTE_PARENT])?;
+        if !parent.is_empty() {
+            name.push_str(&format!("{parent}/"));
+        }
+        name.push_str(self.string(location[ATTRIBUTE_BASE])?);
+        let extension = self.string(location[ATTRIBUTE_EXTENSION])?;
+        if !extension.is_empty() {
+            name.push_str(&format!(".{extension}"));
+        }
+        Ok(name)
+    }
+
+    fn find(&self, name: &str) -> Result<Option<Location>, ArchiveError> {
+        if self.table_length == 0 {
+            return Ok(None);
+        }
+        let slot = hash(name, HASH_MULTIPLIER) % self.table_length;
+        let redirect = self
+            .u32_at(self.redirect_start() + slot as usize * 4)
+            .ok_or(ArchiveError::InvalidHeader(slot))? as i32;
+        // negative for the only name in its slot, otherwise the seed that
+        // spreads the names sharing it
+        let index = match redirect {
+            0 => return Ok(None),
+            redirect if redirect < 0 => (-1 - redirect) as u32,
+            seed => hash(name, seed as u32) % self.table_length,
+        };
+        if index >= self.table_length {
+            return Ok(None);
+        }
+        // a name that isn't there can still hash to a slot that's used
+        let location = self.location(index)?;
+        match self.location_name(&location)? == name {
+            true => Ok(Some(location)),
+            false => Ok(None),
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> Result<bool, ArchiveError> {
+        Ok(self.find(name)?.is_some())
+    }
+
+    // every resource name, in table order
+    pub fn names(&self) -> Result<Vec<String>, ArchiveError> {
+        (0..self.table_length)
+            .map(|index| self.location_name(&self.location(index)?))
+            .collect()
+    }
+
+    fn content(&self, name: &str, location: &Location) -> Result<Vec<u8>, ArchiveError> {
+        let (compressed, uncompressed) = (
+            location[ATTRIBUTE_COMPRESSED],
+            location[ATTRIBUTE_UNCOMPRESSED],
+        );
+        let start = self.index_size() + location[ATTRIBUTE_OFFSET] as usize;
+        let length = match compressed {
+            0 => uncompressed,
+            compressed => compressed,
+        };
+        let mut data = self
+            .data
+            .get(start..start + length as usize)
+            .ok_or_else(|| ArchiveError::InvalidHeader(location[ATTRIBUTE_OFFSET] as u32))?
+            .to_vec();
+        // plugins can be stacked, each adding a header
+        while compressed != 0 && self.u32_in(&data, 0) == Some(COMPRESSED_MAGIC) {
+            data = self.decompress(name, &data)?;
+        }
+        if data.len() as u64 != uncompressed {
+            return Err(ArchiveError::ChecksumMismatch(name.to_string()));
+        }
+        Ok(data)
+    }
+
+    fn decompress(&self, name: &str, data: &[u8]) -> Result<Vec<u8>, ArchiveError> {
+        let invalid = || ArchiveError::InvalidHeader(0);
+        let header = data.get(..COMPRESSED_HEADER_SIZE).ok_or_else(invalid)?;
+        // the magic, the compressed and uncompressed sizes, then the offset
+        // of the decompressor's name
+        let compressed_size = self.u64_in(header, 4).ok_or_else(invalid)?;
+        let decompressor = self.u32_in(header, 20).ok_or_else(invalid)?;
+        let decompressor = self.string(decompressor as u64)?;
+        let content = data
+            .get(COMPRESSED_HEADER_SIZE..COMPRESSED_HEADER_SIZE + compressed_size as usize)
+            .ok_or_else(invalid)?;
+        match decompressor {
+            // zlib: a two byte header before the deflate stream
+            "zip" => inflate(content.get(2..).ok_or_else(invalid)?)
+                .map_err(|error| ArchiveError::InflateError(name.to_string(), error)),
+            decompressor => Err(ArchiveError::UnsupportedCompression(
+                name.to_string(),
+                decompressor.to_string(),
+            )),
+        }
+    }
+
+    // the contents of the resource `name`, a full name like
+    // `/java.base/java/lang/Object.class`
+    pub fn read(&self, name: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        match self.find(name)? {
+            Some(location) => self.content(name, &location).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // the modules with classes in `package`, like `java/lang`, from the
+    // image's `/packages` directory
+    pub fn package_modules(&self, package: &str) -> Result<Vec<String>, ArchiveError> {
+        let name = format!("/packages/{}", package.replace('/', "."));
+        let Some(data) = self.read(&name)? else {
+            return Ok(Vec::new());
+        };
+        // pairs of whether the package is empty in the module, and the
+        // module's name
+        let mut modules = Vec::new();
+        for pair in data.chunks_exact(8) {
+            let empty = self.u32_in(pair, 0).unwrap();
+            let module = self.u32_in(pair, 4).unwrap();
+            if empty == 0 {
+                modules.push(self.string(module as u64)?.to_string());
+            }
+        }
+        Ok(modules)
+    }
+
+    // the class with the internal name `name`, like `java/lang/Object`, and
+    // the module it's in
+    pub fn find_class(&self, name: &str) -> Result<Option<(String, Vec<u8>)>, ArchiveError> {
+        let package = name.rsplit_once('/').map_or("", |(package, _)| package);
+        for module in self.package_modules(package)? {
+            if let Some(data) = self.read(&format!("/{module}/{name}.class"))? {
+                return Ok(Some((module, data)));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::parse_classfile;
+
+    #[test]
+    fn test_jimage_read() {
+        let data = include_bytes!("../../../../java/demo.jimage");
+        let image = JImage::new(data.to_vec()).unwrap();
+        let mut names = image.names().unwrap();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "/demo/demo/Greeter.class",
+                "/demo/module-info.class",
+                "/packages/demo"
+            ]
+        );
+        // Greeter is compressed, module-info isn't, and was rewritten by
+        // jlink to list its packages
+        assert_eq!(
+            image.read("/demo/demo/Greeter.class").unwrap().unwrap(),
+            include_bytes!("../../../../java/demo/demo/Greeter.class")
+        );
+        let data = image.read("/demo/module-info.class").unwrap().unwrap();
+        let (_, class) = parse_classfile(&data).unwrap();
+        assert_eq!(
+            class.constant_pool.class_name(class.this_class).unwrap(),
+            "module-info"
+        );
+        assert_eq!(image.read("/demo/demo/Missing.class"), Ok(None));
+        assert!(!image.contains("/java.base/java/lang/Object.class").unwrap());
+    }
+
+    #[test]
+    fn test_jimage_find_class() {
+        let data = include_bytes!("../../../../java/demo.jimage");
+        let image = JImage::new(data.to_vec()).unwrap();
+        assert_eq!(image.package_modules("demo").unwrap(), ["demo"]);
+        assert_eq!(image.package_modules("java/lang").unwrap(), [] as [&str; 0]);
+        let (module, data) = image.find_class("demo/Greeter").unwrap().unwrap();
+        assert_eq!(module, "demo");
+        assert_eq!(
+            data,
+            include_bytes!("../../../../java/demo/demo/Greeter.class")
+        );
+        assert_eq!(image.find_class("demo/Missing"), Ok(None));
+        assert_eq!(image.find_class("java/lang/Object"), Ok(None));
+    }
+
+    #[test]
+    fn test_jimage_errors() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        assert_eq!(
+            JImage::new(data.to_vec()).err(),
+            Some(ArchiveError::InvalidMagic(0xcafebabe))
+        );
+        let data = include_bytes!("../../../../java/demo.jimage");
+        assert!(JImage::new(data[..20].to_vec()).is_err());
+    }
+}
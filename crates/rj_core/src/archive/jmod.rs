@@ -0,0 +1,94 @@
+use std::fs;
+use std::path::Path;
+
+use super::error::ArchiveError;
+use super::zip::{ZipArchive, ZipEntry};
+
+const MAGIC: [u8; 4] = [b'J', b'M', 1, 0];
+
+// a module packaged for jlink, from a JDK's `jmods` directory: a zip behind
+// a four byte header, with classes under `classes/` and native libraries,
+// commands and configuration in sections of their own
+#[derive(Debug)]
+pub struct Jmod {
+    archive: ZipArchive,
+}
+
+impl Jmod {
+    pub fn new(mut data: Vec<u8>) -> Result<Self, ArchiveError> {
+        match data.get(..4) {
+            Some(magic) if magic == MAGIC => {}
+            Some(magic) => {
+                let magic = u32::from_be_bytes([magic[0], magic[1], magic[2], magic[3]]);
+                return Err(ArchiveError::InvalidMagic(magic));
+            }
+            None => return Err(ArchiveError::InvalidMagic(0)),
+        }
+        // offsets in the zip are from the end of the header
+        data.drain(..4);
+        Ok(Jmod {
+            archive: ZipArchive::new(data)?,
+        })
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ArchiveError> {
+        Self::new(fs::read(path)?)
+    }
+
+    // every section, with entries named like `classes/java/lang/Object.class`
+    // or `lib/libjava.so`
+    pub fn archive(&self) -> &ZipArchive {
+        &self.archive
+    }
+
+    pub fn into_archive(self) -> ZipArchive {
+        self.archive
+    }
+
+    // the class with the internal name `name`, like `java/lang/Object`
+    pub fn read_class(&self, name: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        self.archive.read(&format!("classes/{name}.class"))
+    }
+
+    // the module's classes, named without the `classes/` prefix
+    pub fn classes(&self) -> impl Iterator<Item = (&str, Result<Vec<u8>, ArchiveError>)> {
+        self.archive
+            .entries()
+            .iter()
+            .filter(|entry| entry.is_class())
+            .filter_map(|entry: &ZipEntry| {
+                let name = entry.name.strip_prefix("classes/")?;
+                Some((name, self.archive.read_entry(entry)))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jmod_read() {
+        let data = include_bytes!("../../../../java/demo.jmod");
+        let jmod = Jmod::new(data.to_vec()).unwrap();
+        assert_eq!(
+            jmod.read_class("demo/Greeter").unwrap().unwrap(),
+            include_bytes!("../../../../java/demo/demo/Greeter.class")
+        );
+        assert_eq!(jmod.read_class("demo/Missing"), Ok(None));
+        assert!(jmod.archive().contains("classes/module-info.class"));
+        let names = jmod.classes().map(|(name, _)| name).collect::<Vec<_>>();
+        assert_eq!(names, ["module-info.class", "demo/Greeter.class"]);
+        assert!(jmod.classes().all(|(_, data)| data.is_ok()));
+    }
+
+    #[test]
+    fn test_jmod_errors() {
+        let data = include_bytes!("../../../../java/classes.jar");
+        assert_eq!(
+            Jmod::new(data.to_vec()).err(),
+            Some(ArchiveError::InvalidMagic(0x504b0304))
+        );
+        assert_eq!(Jmod::new(vec![]).err(), Some(ArchiveError::InvalidMagic(0)));
+    }
+}
@@ -0,0 +1,135 @@
+// the attributes of a section, in the order they were written
+pub type ManifestAttributes = Vec<(String, String)>;
+
+// a jar's META-INF/MANIFEST.MF: a main section, then one section per entry
+// that has attributes of its own. attribute names are compared ignoring
+// case, as the jar specification asks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Manifest {
+    pub main: ManifestAttributes,
+    // by the entry in their `Name` attribute
+    pub sections: Vec<(String, ManifestAttributes)>,
+}
+
+fn find<'m>(attributes: &'m ManifestAttributes, name: &str) -> Option<&'m str> {
+    attributes
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+impl Manifest {
+    // lines wrapped at 72 bytes carry on after a single space. lines that
+    // aren't `Name: value` are skipped rather than rejected
+    pub fn parse(data: &[u8]) -> Manifest {
+        let text = String::from_utf8_lossy(data);
+        let mut lines: Vec<String> = Vec::new();
+        for line in text.split('\n') {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            match (line.strip_prefix(' '), lines.last_mut()) {
+                (Some(rest), Some(last)) if !last.is_empty() => last.push_str(rest),
+                _ => lines.push(line.to_string()),
+            }
+        }
+
+        let mut manifest = Manifest::default();
+        let mut section: Option<ManifestAttributes> = None;
+        for line in lines {
+            if line.is_empty() {
+                if let Some(attributes) = section.take() {
+                    manifest.push_section(attributes);
+                }
+                continue;
+            }
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let attribute = (key.trim().to_string(), value.trim_start().to_string());
+            section.get_or_insert_with(Vec::new).push(attribute);
+        }
+        if let Some(attributes) = section {
+            manifest.push_section(attributes);
+        }
+        manifest
+    }
+
+    fn push_section(&mut self, attributes: ManifestAttributes) {
+        // the first section is the main one, whether or not it's named
+        if self.main.is_empty() && self.sections.is_empty() {
+            self.main = attributes;
+            return;
+        }
+        if let Some(name) = find(&attributes, "Name") {
+            self.sections.push((name.to_string(), attributes));
+        }
+    }
+
+    // an attribute of the main section
+    pub fn get(&self, name: &str) -> Option<&str> {
+        find(&self.main, name)
+    }
+
+    // the attributes for the entry `name`, like `com/example/Foo.class`
+    pub fn section(&self, name: &str) -> Option<&ManifestAttributes> {
+        self.sections
+            .iter()
+            .find(|(section, _)| section == name)
+            .map(|(_, attributes)| attributes)
+    }
+
+    pub fn main_class(&self) -> Option<&str> {
+        self.get("Main-Class")
+    }
+
+    // the jars and directories `Class-Path` adds, relative to the jar
+    pub fn class_path(&self) -> Vec<&str> {
+        self.get("Class-Path")
+            .map(|value| value.split_ascii_whitespace().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn is_multi_release(&self) -> bool {
+        self.get("Multi-Release")
+            .is_some_and(|value| value.eq_ignore_ascii_case("true"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_parse() {
+        let data = b"Manifest-Version: 1.0\r\n\
+            Main-Class: com.example.Main\r\n\
+            Class-Path: lib/first.jar lib/sec\r\n ond.jar\r\n\
+            multi-release: true\r\n\
+            \r\n\
+            Name: com/example/Main.class\r\n\
+            SHA-256-Digest: abc=\r\n\
+            \r\n\
+            Name: com/example/\r\n\
+            Sealed: true\r\n";
+        let manifest = Manifest::parse(data);
+        assert_eq!(manifest.get("manifest-version"), Some("1.0"));
+        assert_eq!(manifest.main_class(), Some("com.example.Main"));
+        assert_eq!(manifest.class_path(), ["lib/first.jar", "lib/second.jar"]);
+        assert!(manifest.is_multi_release());
+        assert_eq!(manifest.get("Name"), None);
+        assert_eq!(
+            manifest.section("com/example/Main.class"),
+            Some(&vec![
+                ("Name".to_string(), "com/example/Main.class".to_string()),
+                ("SHA-256-Digest".to_string(), "abc=".to_string()),
+            ])
+        );
+        assert_eq!(manifest.sections[1].0, "com/example/");
+        assert_eq!(manifest.section("com/example/Other.class"), None);
+
+        let manifest = Manifest::parse(b"");
+        assert_eq!(manifest, Manifest::default());
+        assert_eq!(manifest.main_class(), None);
+        assert!(manifest.class_path().is_empty());
+        assert!(!manifest.is_multi_release());
+    }
+}
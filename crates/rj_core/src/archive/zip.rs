@@ -0,0 +1,320 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::error::ArchiveError;
+use super::inflate::inflate;
+use super::manifest::Manifest;
+
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const STORED: u16 = 0;
+const DEFLATED: u16 = 8;
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 1 {
+                1 => 0xedb88320 ^ (crc >> 1),
+                _ => crc >> 1,
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc = CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+// an entry as the central directory describes it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZipEntry {
+    // the path in the archive, like `com/example/Foo.class`; directories
+    // end in `/`
+    pub name: String,
+    // 0 for stored, 8 for deflated
+    pub method: u16,
+    pub crc32: u32,
+    pub compressed_size: u32,
+    pub size: u32,
+    // of the entry's local header
+    offset: u32,
+}
+
+impl ZipEntry {
+    pub fn is_directory(&self) -> bool {
+        self.name.ends_with('/')
+    }
+
+    pub fn is_class(&self) -> bool {
+        self.name.ends_with(".class") && !self.is_directory()
+    }
+}
+
+// a zip file, such as a jar, read whole into memory. entries are found
+// through the central directory at the end and decompressed when read
+#[derive(Debug)]
+pub struct ZipArchive {
+    data: Vec<u8>,
+    entries: Vec<ZipEntry>,
+    by_name: HashMap<String, usize>,
+}
+
+impl ZipArchive {
+    pub fn new(data: Vec<u8>) -> Result<Self, ArchiveError> {
+        // the record is 22 bytes, followed by a comment of up to 65535
+        let end = (0..=data.len().saturating_sub(22))
+            .rev()
+            .take(65536)
+            .find(|&offset| u32_at(&data, offset) == Some(END_OF_CENTRAL_DIRECTORY))
+            .ok_or(ArchiveError::NotAnArchive)?;
+        let count = u16_at(&data, end + 10).unwrap();
+        let directory = u32_at(&data, end + 16).unwrap();
+        if count == u16::MAX || directory == u32::MAX {
+            return Err(ArchiveError::Zip64);
+        }
+
+        let mut entries = Vec::with_capacity(count as usize);
+        let mut offset = directory as usize;
+        for _ in 0..count {
+            let invalid = ArchiveError::InvalidHeader(offset as u32);
+            if u32_at(&data, offset) != Some(CENTRAL_HEADER) {
+                return Err(invalid);
+            }
+            let field = |at: usize| u32_at(&data, offset + at);
+            let (Some(method), Some(crc32), Some(compressed_size), Some(size)) =
+                (u16_at(&data, offset + 10), field(16), field(20), field(24))
+            else {
+                return Err(invalid);
+            };
+            let (Some(name_length), Some(extra_length), Some(comment_length), Some(local)) = (
+                u16_at(&data, offset + 28),
+                u16_at(&data, offset + 30),
+                u16_at(&data, offset + 32),
+                field(42),
+            ) else {
+                return Err(invalid);
+            };
+            if compressed_size == u32::MAX || size == u32::MAX || local == u32::MAX {
+                return Err(ArchiveError::Zip64);
+            }
+            let name_start = offset + 46;
+            let name = data
+                .get(name_start..name_start + name_length as usize)
+                .ok_or(invalid)?;
+            entries.push(ZipEntry {
+                name: String::from_utf8_lossy(name).into_owned(),
+                method,
+                crc32,
+                compressed_size,
+                size,
+                offset: local,
+            });
+            offset = name_start + name_length as usize + extra_length as usize;
+            offset += comment_length as usize;
+        }
+        let by_name = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (entry.name.clone(), index))
+            .collect();
+        Ok(ZipArchive {
+            data,
+            entries,
+            by_name,
+        })
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ArchiveError> {
+        Self::new(fs::read(path)?)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.by_name.contains_key(name)
+    }
+
+    // every entry, in central directory order
+    pub fn entries(&self) -> &[ZipEntry] {
+        &self.entries
+    }
+
+    pub fn entry(&self, name: &str) -> Option<&ZipEntry> {
+        self.by_name.get(name).map(|&index| &self.entries[index])
+    }
+
+    // the decompressed contents of the entry `name`, a path like
+    // `com/example/Foo.class`, if there is one
+    pub fn read(&self, name: &str) -> Result<Option<Vec<u8>>, ArchiveError> {
+        match self.entry(name) {
+            Some(entry) => self.read_entry(entry).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // the parsed META-INF/MANIFEST.MF, if the archive has one
+    pub fn manifest(&self) -> Result<Option<Manifest>, ArchiveError> {
+        let data = self.read("META-INF/MANIFEST.MF")?;
+        Ok(data.map(|data| Manifest::parse(&data)))
+    }
+
+    // the class files in the archive, each decompressed only when the
+    // iterator reaches it, so a scan holds one class at a time
+    pub fn classes(&self) -> impl Iterator<Item = (&ZipEntry, Result<Vec<u8>, ArchiveError>)> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.is_class())
+            .map(|entry| (entry, self.read_entry(entry)))
+    }
+
+    pub fn read_entry(&self, entry: &ZipEntry) -> Result<Vec<u8>, ArchiveError> {
+        let offset = entry.offset as usize;
+        let invalid = || ArchiveError::InvalidHeader(entry.offset);
+        if u32_at(&self.data, offset) != Some(LOCAL_HEADER) {
+            return Err(invalid());
+        }
+        // the local header repeats the name, but its extra field can differ
+        let name_length = u16_at(&self.data, offset + 26).ok_or_else(invalid)?;
+        let extra_length = u16_at(&self.data, offset + 28).ok_or_else(invalid)?;
+        let start = offset + 30 + name_length as usize + extra_length as usize;
+        let compressed = self
+            .data
+            .get(start..start + entry.compressed_size as usize)
+            .ok_or_else(invalid)?;
+        let data = match entry.method {
+            STORED => Cow::Borrowed(compressed),
+            DEFLATED => inflate(compressed)
+                .map(Cow::Owned)
+                .map_err(|error| ArchiveError::InflateError(entry.name.clone(), error))?,
+            method => {
+                return Err(ArchiveError::UnsupportedMethod(entry.name.clone(), method));
+            }
+        };
+        if data.len() != entry.size as usize || crc32(&data) != entry.crc32 {
+            return Err(ArchiveError::ChecksumMismatch(entry.name.clone()));
+        }
+        Ok(data.into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+    }
+
+    #[test]
+    fn test_zip_read() {
+        let data = include_bytes!("../../../../java/classes.jar");
+        let archive = ZipArchive::new(data.to_vec()).unwrap();
+        // the classes are deflated with dynamic codes, the manifest with
+        // fixed ones
+        assert_eq!(
+            archive.read("HelloWorld.class").unwrap().unwrap(),
+            include_bytes!("../../../../java/HelloWorld.class")
+        );
+        assert_eq!(
+            archive.read("Outer$Inner.class").unwrap().unwrap(),
+            include_bytes!("../../../../java/Outer$Inner.class")
+        );
+        assert!(archive.contains("META-INF/MANIFEST.MF"));
+        assert_eq!(archive.read("Frames.class"), Ok(None));
+        assert_eq!(archive.read("META-INF/").unwrap().unwrap(), b"");
+    }
+
+    #[test]
+    fn test_zip_entries() {
+        let data = include_bytes!("../../../../java/classes.jar");
+        let archive = ZipArchive::new(data.to_vec()).unwrap();
+        let names = archive
+            .entries()
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "META-INF/",
+                "META-INF/MANIFEST.MF",
+                "HelloWorld.class",
+                "Point.class",
+                "Outer$Inner.class"
+            ]
+        );
+        assert!(archive.entries()[0].is_directory());
+        let entry = archive.entry("Point.class").unwrap();
+        assert_eq!((entry.method, entry.size), (DEFLATED, 959));
+
+        let manifest = archive.manifest().unwrap().unwrap();
+        assert_eq!(manifest.main_class(), Some("HelloWorld"));
+        assert_eq!(manifest.get("Manifest-Version"), Some("1.0"));
+
+        let classes = archive
+            .classes()
+            .map(|(entry, data)| {
+                let data = data.unwrap();
+                let (_, classfile) = crate::class::parse_classfile(&data).unwrap();
+                let name = classfile.constant_pool.class_name(classfile.this_class);
+                (entry.name.clone(), name.unwrap().into_owned())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            classes,
+            [
+                ("HelloWorld.class".into(), "HelloWorld".into()),
+                ("Point.class".into(), "Point".into()),
+                ("Outer$Inner.class".into(), "Outer$Inner".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_zip_errors() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        assert_eq!(
+            ZipArchive::new(data.to_vec()).unwrap_err(),
+            ArchiveError::NotAnArchive
+        );
+        assert_eq!(
+            ZipArchive::new(Vec::new()).unwrap_err(),
+            ArchiveError::NotAnArchive
+        );
+
+        let mut data = include_bytes!("../../../../java/classes.jar").to_vec();
+        let archive = ZipArchive::new(data.clone()).unwrap();
+        // flip a byte of the first class's compressed data
+        let entry = &archive.entries[archive.by_name["HelloWorld.class"]];
+        let start = entry.offset as usize + 30 + entry.name.len() + 10;
+        data[start] ^= 0x55;
+        let archive = ZipArchive::new(data).unwrap();
+        assert!(archive.read("HelloWorld.class").is_err());
+        assert!(archive.read("Point.class").is_ok());
+    }
+}
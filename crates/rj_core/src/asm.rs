@@ -1,5 +1,23 @@
+mod builder;
+mod class_builder;
+pub mod class_text;
 mod error;
+pub mod frames;
 mod instruction;
+mod kind;
+mod layout;
+mod normalize;
+mod operands;
+mod relocate;
+mod resolve;
+mod stack;
+pub mod text;
 
+pub use builder::*;
+pub use class_builder::*;
 pub use error::*;
 pub use instruction::*;
+pub use kind::*;
+pub use relocate::*;
+pub use resolve::*;
+pub use stack::*;
@@ -0,0 +1,278 @@
+use super::error::CodeBuildError;
+use super::instruction::Instruction;
+use super::layout::{layout, Item};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Label(usize);
+
+// branch instructions are recorded with label targets and their offsets are
+// filled in by `build`, widening them if the target ends up out of i16 range
+#[derive(Default)]
+pub struct CodeBuilder {
+    instructions: Vec<(Instruction, Vec<Label>)>,
+    // index of the instruction each label is bound before
+    labels: Vec<Option<usize>>,
+    error: Option<CodeBuildError>,
+}
+
+pub struct AssembledCode {
+    pub code: Vec<u8>,
+    pub instructions: Vec<(u32, Instruction)>,
+    label_pcs: Vec<u32>,
+}
+
+impl AssembledCode {
+    pub fn code_length(&self) -> u32 {
+        self.code.len() as u32
+    }
+
+    pub fn label_pc(&self, label: Label) -> u32 {
+        self.label_pcs[label.0]
+    }
+}
+
+impl CodeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    pub fn bind(&mut self, label: Label) -> &mut Self {
+        match self.labels[label.0] {
+            Some(_) => self.fail(CodeBuildError::LabelAlreadyBound(label)),
+            None => self.labels[label.0] = Some(self.instructions.len()),
+        }
+        self
+    }
+
+    // appends an instruction as is; branch offsets of instructions added this
+    // way are not adjusted
+    pub fn emit(&mut self, instruction: Instruction) -> &mut Self {
+        self.instructions.push((instruction, Vec::new()));
+        self
+    }
+
+    // appends a goto, jsr or if* instruction targeting `label`; the offset in
+    // `instruction` is ignored
+    pub fn branch(&mut self, instruction: Instruction, label: Label) -> &mut Self {
+        if instruction.branch_target(0).is_none() {
+            self.fail(CodeBuildError::NotABranch(instruction));
+            return self;
+        }
+        self.instructions.push((instruction, vec![label]));
+        self
+    }
+
+    pub fn goto(&mut self, label: Label) -> &mut Self {
+        self.branch(Instruction::Goto(0), label)
+    }
+
+    pub fn jsr(&mut self, label: Label) -> &mut Self {
+        self.branch(Instruction::Jsr(0), label)
+    }
+
+    pub fn tableswitch(&mut self, low: i32, default: Label, targets: &[Label]) -> &mut Self {
+        let high = low.wrapping_add(targets.len() as i32).wrapping_sub(1);
+        if targets.is_empty() || high < low {
+            self.fail(CodeBuildError::InvalidSwitch);
+            return self;
+        }
+        let offsets = vec![0; targets.len()];
+        let mut labels = vec![default];
+        labels.extend_from_slice(targets);
+        self.instructions
+            .push((Instruction::Tableswitch(0, low, high, offsets), labels));
+        self
+    }
+
+    pub fn lookupswitch(&mut self, default: Label, pairs: &[(i32, Label)]) -> &mut Self {
+        let mut keys = pairs.iter().map(|(key, _)| *key).collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+        if keys.len() != pairs.len() {
+            self.fail(CodeBuildError::InvalidSwitch);
+            return self;
+        }
+        let mut pairs = pairs.to_vec();
+        pairs.sort_by_key(|(key, _)| *key);
+        let mut labels = vec![default];
+        labels.extend(pairs.iter().map(|(_, label)| *label));
+        let pairs = pairs.iter().map(|(key, _)| (*key, 0)).collect();
+        self.instructions
+            .push((Instruction::Lookupswitch(0, pairs), labels));
+        self
+    }
+
+    fn fail(&mut self, error: CodeBuildError) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+
+    pub fn build(self) -> Result<AssembledCode, CodeBuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let mut label_indices = Vec::new();
+        for (i, label) in self.labels.iter().enumerate() {
+            label_indices.push(label.ok_or(CodeBuildError::UnboundLabel(Label(i)))?);
+        }
+        let items = self
+            .instructions
+            .into_iter()
+            .map(|(instruction, labels)| Item {
+                instruction,
+                targets: labels.iter().map(|label| label_indices[label.0]).collect(),
+            })
+            .collect();
+        let layout = layout(items);
+        let code_length = *layout.pcs.last().unwrap();
+        if code_length > u16::MAX as u32 {
+            return Err(CodeBuildError::CodeTooLarge(code_length));
+        }
+        let mut code = Vec::with_capacity(code_length as usize);
+        for (pc, instruction) in &layout.instructions {
+            instruction.encode(*pc, &mut code);
+        }
+        Ok(AssembledCode {
+            code,
+            instructions: layout.instructions,
+            label_pcs: label_indices
+                .iter()
+                .map(|index| layout.pcs[*index])
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::disassemble;
+
+    #[test]
+    fn test_build_loop() {
+        // for (int i = 0; i < 10; i++) {}
+        let mut builder = CodeBuilder::new();
+        let condition = builder.label();
+        let body = builder.label();
+        builder
+            .emit(Instruction::Iconst0)
+            .emit(Instruction::Istore1)
+            .goto(condition)
+            .bind(body)
+            .emit(Instruction::Iinc(1, 1))
+            .bind(condition)
+            .emit(Instruction::Iload1)
+            .emit(Instruction::Bipush(10))
+            .branch(Instruction::IfIcmplt(0), body)
+            .emit(Instruction::Return);
+        let code = builder.build().unwrap();
+        assert_eq!(
+            code.code,
+            vec![
+                0x03, // iconst_0
+                0x3c, // istore_1
+                0xa7, 0x00, 0x06, // goto 8
+                0x84, 0x01, 0x01, // iinc 1, 1
+                0x1b, // iload_1
+                0x10, 0x0a, // bipush 10
+                0xa1, 0xff, 0xfa, // if_icmplt 5
+                0xb1, // return
+            ]
+        );
+        assert_eq!(code.code_length(), 15);
+        assert_eq!(code.label_pc(body), 5);
+        assert_eq!(code.label_pc(condition), 8);
+    }
+
+    #[test]
+    fn test_build_switches() {
+        let mut builder = CodeBuilder::new();
+        let one = builder.label();
+        let two = builder.label();
+        let other = builder.label();
+        builder
+            .emit(Instruction::Iload0)
+            .tableswitch(1, other, &[one, two])
+            .bind(one)
+            .emit(Instruction::Iload0)
+            .lookupswitch(other, &[(100, two), (-5, one)])
+            .bind(two)
+            .emit(Instruction::Iconst1)
+            .emit(Instruction::Ireturn)
+            .bind(other)
+            .emit(Instruction::Iconst0)
+            .emit(Instruction::Ireturn);
+        let code = builder.build().unwrap();
+        let instructions = disassemble(&code.code)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(instructions, code.instructions);
+        assert_eq!(
+            instructions[1],
+            (1, Instruction::Tableswitch(53, 1, 2, vec![23, 51]))
+        );
+        assert_eq!(
+            instructions[3],
+            (25, Instruction::Lookupswitch(29, vec![(-5, -1), (100, 27)]))
+        );
+        assert_eq!(code.label_pc(other), 54);
+    }
+
+    #[test]
+    fn test_build_widens_branches() {
+        let mut builder = CodeBuilder::new();
+        let end = builder.label();
+        builder.emit(Instruction::Iload0);
+        builder.branch(Instruction::Ifeq(0), end);
+        for _ in 0..40000 {
+            builder.emit(Instruction::Nop);
+        }
+        builder.bind(end).emit(Instruction::Return);
+        let code = builder.build().unwrap();
+        assert_eq!(code.instructions[1], (1, Instruction::Ifne(8)));
+        assert_eq!(code.instructions[2], (4, Instruction::GotoW(40005)));
+        assert_eq!(code.label_pc(end), 40009);
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let mut builder = CodeBuilder::new();
+        let label = builder.label();
+        builder.goto(label);
+        assert_eq!(
+            builder.build().err(),
+            Some(CodeBuildError::UnboundLabel(label))
+        );
+
+        let mut builder = CodeBuilder::new();
+        let label = builder.label();
+        builder.bind(label).bind(label);
+        assert_eq!(
+            builder.build().err(),
+            Some(CodeBuildError::LabelAlreadyBound(label))
+        );
+
+        let mut builder = CodeBuilder::new();
+        let label = builder.label();
+        builder.bind(label).branch(Instruction::Nop, label);
+        assert_eq!(
+            builder.build().err(),
+            Some(CodeBuildError::NotABranch(Instruction::Nop))
+        );
+
+        let mut builder = CodeBuilder::new();
+        for _ in 0..0x10000 {
+            builder.emit(Instruction::Nop);
+        }
+        assert_eq!(
+            builder.build().err(),
+            Some(CodeBuildError::CodeTooLarge(0x10000))
+        );
+    }
+}
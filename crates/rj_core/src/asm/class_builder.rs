@@ -0,0 +1,750 @@
+use std::borrow::Cow;
+
+use super::builder::{CodeBuilder, Label};
+use super::error::ClassBuildError;
+use super::frames::{compute_stack_map_tables, SimpleClassHierarchy};
+use super::instruction::Instruction;
+use super::stack::compute_max_stack;
+use crate::analysis::locals::compute_max_locals;
+use crate::class::{
+    Attribute, ClassAccessFlags, ClassFile, ClassVersion, Code, ConstantPoolBuildError,
+    ConstantPoolBuilder, ExceptionTableEntry, Field, FieldAccessFlags, Method, MethodAccessFlags,
+    MethodDescriptor, SourceFile, CLASS_FILE_MAGIC,
+};
+
+// an exception handler recorded by label, resolved once the code is laid out
+struct Handler {
+    start: Label,
+    end: Label,
+    handler: Label,
+    catch_type: u16,
+}
+
+// the code of one method, handed to the closure passed to
+// `ClassBuilder::method`. constants for instruction operands are added
+// through `constant_pool`. max_stack and max_locals are computed from the
+// code unless set
+pub struct MethodBody<'p> {
+    pub code: CodeBuilder,
+    pub constant_pool: &'p mut ConstantPoolBuilder,
+    pub max_stack: Option<u16>,
+    pub max_locals: Option<u16>,
+    handlers: Vec<Handler>,
+}
+
+impl<'p> MethodBody<'p> {
+    // catches exceptions of `catch_type`, an internal class name, thrown
+    // between `start` and `end`; None catches anything, as for `finally`
+    pub fn exception_handler(
+        &mut self,
+        start: Label,
+        end: Label,
+        handler: Label,
+        catch_type: Option<&str>,
+    ) -> Result<&mut Self, ClassBuildError> {
+        let catch_type = match catch_type {
+            Some(name) => self.constant_pool.class(name)?,
+            None => 0,
+        };
+        self.handlers.push(Handler {
+            start,
+            end,
+            handler,
+            catch_type,
+        });
+        Ok(self)
+    }
+
+    fn build(
+        self,
+        descriptor: &MethodDescriptor,
+        is_static: bool,
+    ) -> Result<Code<'static, Attribute<'static>>, ClassBuildError> {
+        let assembled = self.code.build()?;
+        let exception_table = self
+            .handlers
+            .iter()
+            .map(|handler| ExceptionTableEntry {
+                start_pc: assembled.label_pc(handler.start) as u16,
+                end_pc: assembled.label_pc(handler.end) as u16,
+                handler_pc: assembled.label_pc(handler.handler) as u16,
+                catch_type: handler.catch_type,
+            })
+            .collect::<Vec<_>>();
+        let max_stack = match self.max_stack {
+            Some(max_stack) => max_stack,
+            None => {
+                let constant_pool = self.constant_pool.to_constant_pool();
+                compute_max_stack(&assembled.instructions, &exception_table, &constant_pool)?
+            }
+        };
+        let max_locals = self
+            .max_locals
+            .unwrap_or_else(|| compute_max_locals(&assembled.instructions, descriptor, is_static));
+        Ok(Code {
+            max_stack,
+            max_locals,
+            code: Cow::Owned(assembled.code),
+            exception_table,
+            attributes: Vec::new(),
+        })
+    }
+}
+
+// the instructions that load a local of type `descriptor` and return a value
+// of that type
+fn load_and_return(descriptor: &str, slot: u8) -> (Instruction, Instruction) {
+    match (descriptor.as_bytes().first(), slot) {
+        (Some(b'J'), 0) => (Instruction::Lload0, Instruction::Lreturn),
+        (Some(b'J'), 1) => (Instruction::Lload1, Instruction::Lreturn),
+        (Some(b'J'), _) => (Instruction::Lload(slot), Instruction::Lreturn),
+        (Some(b'F'), 0) => (Instruction::Fload0, Instruction::Freturn),
+        (Some(b'F'), 1) => (Instruction::Fload1, Instruction::Freturn),
+        (Some(b'F'), _) => (Instruction::Fload(slot), Instruction::Freturn),
+        (Some(b'D'), 0) => (Instruction::Dload0, Instruction::Dreturn),
+        (Some(b'D'), 1) => (Instruction::Dload1, Instruction::Dreturn),
+        (Some(b'D'), _) => (Instruction::Dload(slot), Instruction::Dreturn),
+        (Some(b'L' | b'['), 0) => (Instruction::Aload0, Instruction::Areturn),
+        (Some(b'L' | b'['), 1) => (Instruction::Aload1, Instruction::Areturn),
+        (Some(b'L' | b'['), _) => (Instruction::Aload(slot), Instruction::Areturn),
+        (_, 0) => (Instruction::Iload0, Instruction::Ireturn),
+        (_, 1) => (Instruction::Iload1, Instruction::Ireturn),
+        (_, _) => (Instruction::Iload(slot), Instruction::Ireturn),
+    }
+}
+
+// `name` with its first letter in upper case, after `prefix`
+fn accessor_name(prefix: &str, name: &str) -> String {
+    let mut chars = name.chars();
+    let first = chars.next().map(|c| c.to_uppercase().to_string());
+    format!("{prefix}{}{}", first.unwrap_or_default(), chars.as_str())
+}
+
+// generates a class without index arithmetic: names and descriptors go in
+// as strings and the constant pool, access flags and Code attributes are
+// filled in. like CodeBuilder, the first error is kept and returned by
+// `build`
+pub struct ClassBuilder {
+    name: String,
+    super_name: Option<String>,
+    hierarchy: SimpleClassHierarchy,
+    constant_pool: ConstantPoolBuilder,
+    version: ClassVersion,
+    access_flags: ClassAccessFlags,
+    this_class: u16,
+    super_class: u16,
+    interfaces: Vec<u16>,
+    fields: Vec<Field<'static>>,
+    // the names and descriptors of `fields`, for the accessors
+    field_names: Vec<(String, String)>,
+    methods: Vec<Method<'static>>,
+    attributes: Vec<Attribute<'static>>,
+    error: Option<ClassBuildError>,
+}
+
+impl ClassBuilder {
+    // a public class named `name`, an internal name like `com/example/Foo`,
+    // extending java/lang/Object. the version defaults to 49.0, the last
+    // that verifies without a StackMapTable; from 50.0 on the tables are
+    // computed by `build`
+    pub fn new(name: &str) -> Self {
+        let mut builder = ClassBuilder {
+            name: name.to_string(),
+            super_name: None,
+            hierarchy: SimpleClassHierarchy::new(),
+            constant_pool: ConstantPoolBuilder::new(),
+            version: ClassVersion::new(49, 0),
+            access_flags: ClassAccessFlags::PUBLIC | ClassAccessFlags::SUPER,
+            this_class: 0,
+            super_class: 0,
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            field_names: Vec::new(),
+            methods: Vec::new(),
+            attributes: Vec::new(),
+            error: None,
+        };
+        builder.this_class = builder.intern(|constant_pool| constant_pool.class(name));
+        builder.super_class("java/lang/Object");
+        builder
+    }
+
+    fn fail(&mut self, error: ClassBuildError) {
+        if self.error.is_none() {
+            self.error = Some(error);
+        }
+    }
+
+    // the index `f` adds to the pool, or 0 after recording its error
+    fn intern<F>(&mut self, f: F) -> u16
+    where
+        F: FnOnce(&mut ConstantPoolBuilder) -> Result<u16, ConstantPoolBuildError>,
+    {
+        match f(&mut self.constant_pool) {
+            Ok(index) => index,
+            Err(error) => {
+                self.fail(error.into());
+                0
+            }
+        }
+    }
+
+    pub fn version(&mut self, version: ClassVersion) -> &mut Self {
+        self.version = version;
+        self
+    }
+
+    pub fn access_flags(&mut self, access_flags: ClassAccessFlags) -> &mut Self {
+        self.access_flags = access_flags;
+        self
+    }
+
+    pub fn super_class(&mut self, name: &str) -> &mut Self {
+        self.super_name = Some(name.to_string());
+        self.super_class = self.intern(|constant_pool| constant_pool.class(name));
+        self
+    }
+
+    // the classes the code refers to, for merging the types of values that
+    // meet at a branch target. classes it doesn't know extend Object
+    pub fn class_hierarchy(&mut self, hierarchy: SimpleClassHierarchy) -> &mut Self {
+        self.hierarchy = hierarchy;
+        self
+    }
+
+    pub fn interface(&mut self, name: &str) -> &mut Self {
+        let index = self.intern(|constant_pool| constant_pool.class(name));
+        self.interfaces.push(index);
+        self
+    }
+
+    pub fn source_file(&mut self, name: &str) -> &mut Self {
+        self.intern(|constant_pool| constant_pool.utf8("SourceFile"));
+        let sourcefile_index = self.intern(|constant_pool| constant_pool.utf8(name));
+        self.attributes
+            .push(Attribute::SourceFile(SourceFile { sourcefile_index }));
+        self
+    }
+
+    pub fn field(
+        &mut self,
+        access_flags: FieldAccessFlags,
+        name: &str,
+        descriptor: &str,
+    ) -> &mut Self {
+        let name_index = self.intern(|constant_pool| constant_pool.utf8(name));
+        let descriptor_index = self.intern(|constant_pool| constant_pool.utf8(descriptor));
+        self.fields.push(Field {
+            access_flags,
+            name_index,
+            descriptor_index,
+            attributes: Vec::new(),
+        });
+        self.field_names
+            .push((name.to_string(), descriptor.to_string()));
+        self
+    }
+
+    // a method without code, e.g. abstract or native
+    pub fn declare_method(
+        &mut self,
+        access_flags: MethodAccessFlags,
+        name: &str,
+        descriptor: &str,
+    ) -> &mut Self {
+        let name_index = self.intern(|constant_pool| constant_pool.utf8(name));
+        let descriptor_index = self.intern(|constant_pool| constant_pool.utf8(descriptor));
+        self.methods.push(Method {
+            access_flags,
+            name_index,
+            descriptor_index,
+            attributes: Vec::new(),
+        });
+        self
+    }
+
+    // a method whose code `body` emits
+    pub fn method<F>(
+        &mut self,
+        access_flags: MethodAccessFlags,
+        name: &str,
+        descriptor: &str,
+        body: F,
+    ) -> &mut Self
+    where
+        F: FnOnce(&mut MethodBody) -> Result<(), ClassBuildError>,
+    {
+        self.declare_method(access_flags, name, descriptor);
+        self.intern(|constant_pool| constant_pool.utf8("Code"));
+        let Ok(descriptor) = MethodDescriptor::parse(descriptor.as_bytes()) else {
+            let error = ClassBuildError::InvalidMethodDescriptor(descriptor.to_string());
+            self.fail(error);
+            return self;
+        };
+        let is_static = access_flags.contains(MethodAccessFlags::STATIC);
+        let mut method_body = MethodBody {
+            code: CodeBuilder::new(),
+            constant_pool: &mut self.constant_pool,
+            max_stack: None,
+            max_locals: None,
+            handlers: Vec::new(),
+        };
+        let code = body(&mut method_body).and_then(|()| method_body.build(&descriptor, is_static));
+        match code {
+            Ok(code) => {
+                if let Some(method) = self.methods.last_mut() {
+                    method.attributes.push(Attribute::Code(code));
+                }
+            }
+            Err(error) => self.fail(error),
+        }
+        self
+    }
+
+    pub fn public_method<F>(&mut self, name: &str, descriptor: &str, body: F) -> &mut Self
+    where
+        F: FnOnce(&mut MethodBody) -> Result<(), ClassBuildError>,
+    {
+        self.method(MethodAccessFlags::PUBLIC, name, descriptor, body)
+    }
+
+    pub fn public_static_method<F>(&mut self, name: &str, descriptor: &str, body: F) -> &mut Self
+    where
+        F: FnOnce(&mut MethodBody) -> Result<(), ClassBuildError>,
+    {
+        let access_flags = MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC;
+        self.method(access_flags, name, descriptor, body)
+    }
+
+    // a public no-argument constructor that calls the super class's
+    pub fn default_constructor(&mut self) -> &mut Self {
+        let super_name = self.super_name.clone().unwrap_or_default();
+        self.public_method("<init>", "()V", |body| {
+            let init = body
+                .constant_pool
+                .method_ref(&super_name, "<init>", "()V")?;
+            body.code
+                .emit(Instruction::Aload0)
+                .emit(Instruction::Invokespecial(init))
+                .emit(Instruction::Return);
+            Ok(())
+        })
+    }
+
+    // whether a field added with `field` is static, with the class name and
+    // the field's descriptor
+    fn declared_field(&mut self, name: &str) -> Option<(bool, String, String)> {
+        let position = self
+            .field_names
+            .iter()
+            .position(|(field_name, _)| field_name == name);
+        let Some(position) = position else {
+            self.fail(ClassBuildError::UnknownField(name.to_string()));
+            return None;
+        };
+        let is_static = self.fields[position]
+            .access_flags
+            .contains(FieldAccessFlags::STATIC);
+        let descriptor = self.field_names[position].1.clone();
+        Some((is_static, self.name.clone(), descriptor))
+    }
+
+    // a public method returning a field added with `field`: `getName`, or
+    // `isName` for a boolean. static for a static field
+    pub fn getter(&mut self, name: &str) -> &mut Self {
+        let Some((is_static, class, descriptor)) = self.declared_field(name) else {
+            return self;
+        };
+        let prefix = if descriptor == "Z" { "is" } else { "get" };
+        let access_flags = match is_static {
+            true => MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC,
+            false => MethodAccessFlags::PUBLIC,
+        };
+        let method_descriptor = format!("(){descriptor}");
+        let (_, ret) = load_and_return(&descriptor, 0);
+        self.method(
+            access_flags,
+            &accessor_name(prefix, name),
+            &method_descriptor,
+            |body| {
+                let field = body.constant_pool.field_ref(&class, name, &descriptor)?;
+                if is_static {
+                    body.code.emit(Instruction::Getstatic(field));
+                } else {
+                    body.code
+                        .emit(Instruction::Aload0)
+                        .emit(Instruction::Getfield(field));
+                }
+                body.code.emit(ret);
+                Ok(())
+            },
+        )
+    }
+
+    // a public method `setName` storing its argument in a field added with
+    // `field`. static for a static field
+    pub fn setter(&mut self, name: &str) -> &mut Self {
+        let Some((is_static, class, descriptor)) = self.declared_field(name) else {
+            return self;
+        };
+        let access_flags = match is_static {
+            true => MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC,
+            false => MethodAccessFlags::PUBLIC,
+        };
+        let method_descriptor = format!("({descriptor})V");
+        let (load, _) = load_and_return(&descriptor, if is_static { 0 } else { 1 });
+        self.method(
+            access_flags,
+            &accessor_name("set", name),
+            &method_descriptor,
+            |body| {
+                let field = body.constant_pool.field_ref(&class, name, &descriptor)?;
+                if is_static {
+                    body.code.emit(load).emit(Instruction::Putstatic(field));
+                } else {
+                    body.code
+                        .emit(Instruction::Aload0)
+                        .emit(load)
+                        .emit(Instruction::Putfield(field));
+                }
+                body.code.emit(Instruction::Return);
+                Ok(())
+            },
+        )
+    }
+
+    // `public static void main(String[])` that returns straight away, to be
+    // filled in later or just to make the class runnable
+    pub fn main_stub(&mut self) -> &mut Self {
+        self.public_static_method("main", "([Ljava/lang/String;)V", |body| {
+            body.code.emit(Instruction::Return);
+            Ok(())
+        })
+    }
+
+    pub fn build(mut self) -> Result<ClassFile<'static>, ClassBuildError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let is_interface = self.access_flags.contains(ClassAccessFlags::INTERFACE);
+        self.hierarchy
+            .add_class(&self.name, self.super_name.as_deref(), is_interface);
+        let mut classfile = ClassFile {
+            magic: CLASS_FILE_MAGIC,
+            minor_version: self.version.minor,
+            major_version: self.version.major,
+            constant_pool: self.constant_pool.to_constant_pool(),
+            access_flags: self.access_flags,
+            this_class: self.this_class,
+            super_class: self.super_class,
+            interfaces: self.interfaces,
+            fields: self.fields,
+            methods: self.methods,
+            attributes: self.attributes,
+        };
+        compute_stack_map_tables(&mut classfile, &self.hierarchy)?;
+        Ok(classfile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::{Instruction, MaxStackError};
+    use crate::class::{parse_classfile, StackMapFrame, VerificationTypeInfo};
+    use crate::print::PrintOptions;
+
+    fn hello_world() -> ClassBuilder {
+        let mut class = ClassBuilder::new("Hello");
+        class
+            .source_file("Hello.java")
+            .public_method("<init>", "()V", |body| {
+                let init = body
+                    .constant_pool
+                    .method_ref("java/lang/Object", "<init>", "()V")?;
+                body.code
+                    .emit(Instruction::Aload0)
+                    .emit(Instruction::Invokespecial(init))
+                    .emit(Instruction::Return);
+                Ok(())
+            })
+            .public_static_method("main", "([Ljava/lang/String;)V", |body| {
+                let out = body.constant_pool.field_ref(
+                    "java/lang/System",
+                    "out",
+                    "Ljava/io/PrintStream;",
+                )?;
+                let hello = body.constant_pool.string("Hello, World!")?;
+                let println = body.constant_pool.method_ref(
+                    "java/io/PrintStream",
+                    "println",
+                    "(Ljava/lang/String;)V",
+                )?;
+                body.code
+                    .emit(Instruction::Getstatic(out))
+                    .emit(Instruction::LdcW(hello))
+                    .emit(Instruction::Invokevirtual(println))
+                    .emit(Instruction::Return);
+                Ok(())
+            });
+        class
+    }
+
+    #[test]
+    fn test_build_class() {
+        let classfile = hello_world().build().unwrap();
+        let bytes = classfile.to_bytes().unwrap();
+        let (rest, parsed) = parse_classfile(&bytes).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(parsed, classfile);
+        assert_eq!(classfile.constant_pool.validate(), vec![]);
+        let limits = classfile
+            .methods
+            .iter()
+            .map(|method| {
+                let code = method.code().unwrap();
+                (code.max_stack, code.max_locals)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(limits, vec![(1, 1), (2, 1)]);
+
+        let output = classfile.print_with(&PrintOptions::JAVAP).unwrap();
+        let expected = r#"
+Compiled from "Hello.java"
+public class Hello {
+  public Hello();
+  public static void main(java.lang.String[]);
+}
+"#;
+        assert_eq!(output, expected[1..]);
+        let output = classfile.print().unwrap();
+        assert!(output
+            .contains("       3: ldc_w         #21                 // String Hello, World!\n"));
+    }
+
+    #[test]
+    fn test_build_exception_handler() {
+        let mut class = ClassBuilder::new("Catch");
+        class.public_static_method("run", "()V", |body| {
+            let start = body.code.label();
+            let end = body.code.label();
+            let handler = body.code.label();
+            body.code
+                .bind(start)
+                .emit(Instruction::Nop)
+                .bind(end)
+                .emit(Instruction::Return)
+                .bind(handler)
+                .emit(Instruction::Athrow);
+            body.exception_handler(start, end, handler, Some("java/lang/Exception"))?
+                .exception_handler(start, end, handler, None)?;
+            body.max_locals = Some(3);
+            Ok(())
+        });
+        let classfile = class.build().unwrap();
+        let code = classfile.methods[0].code().unwrap();
+        assert_eq!((code.max_stack, code.max_locals), (1, 3));
+        assert_eq!(
+            code.exception_table,
+            vec![
+                ExceptionTableEntry {
+                    start_pc: 0,
+                    end_pc: 1,
+                    handler_pc: 2,
+                    catch_type: classfile.constant_pool.classes().last().unwrap().0,
+                },
+                ExceptionTableEntry {
+                    start_pc: 0,
+                    end_pc: 1,
+                    handler_pc: 2,
+                    catch_type: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_stack_map_table() {
+        let mut class = ClassBuilder::new("Count");
+        class
+            .version(ClassVersion::new(52, 0))
+            .public_static_method("count", "(I)I", |body| {
+                let head = body.code.label();
+                let end = body.code.label();
+                body.code
+                    .emit(Instruction::Iconst0)
+                    .emit(Instruction::Istore1)
+                    .bind(head)
+                    .emit(Instruction::Iload0)
+                    .branch(Instruction::Ifle(0), end)
+                    .emit(Instruction::Iinc(1, 1))
+                    .emit(Instruction::Iinc(0, -1))
+                    .goto(head)
+                    .bind(end)
+                    .emit(Instruction::Iload1)
+                    .emit(Instruction::Ireturn);
+                Ok(())
+            })
+            .public_static_method("fail", "()V", |body| {
+                let start = body.code.label();
+                let end = body.code.label();
+                let handler = body.code.label();
+                body.code
+                    .bind(start)
+                    .emit(Instruction::Return)
+                    .bind(end)
+                    .bind(handler)
+                    .emit(Instruction::Athrow);
+                body.exception_handler(start, end, handler, Some("java/lang/Exception"))?;
+                Ok(())
+            });
+        let classfile = class.build().unwrap();
+        let bytes = classfile.to_bytes().unwrap();
+        assert_eq!(parse_classfile(&bytes).unwrap().1, classfile);
+
+        let tables = classfile
+            .methods
+            .iter()
+            .map(|method| match &method.code().unwrap().attributes[..] {
+                [Attribute::StackMapTable(table)] => table.entries.clone(),
+                _ => panic!("expected a StackMapTable"),
+            })
+            .collect::<Vec<_>>();
+        let exception = classfile
+            .constant_pool
+            .classes()
+            .find(|(_, name)| name == "java/lang/Exception")
+            .unwrap()
+            .0;
+        assert_eq!(
+            tables,
+            vec![
+                vec![
+                    StackMapFrame::Append {
+                        offset_delta: 2,
+                        locals: vec![VerificationTypeInfo::Integer],
+                    },
+                    StackMapFrame::Same { offset_delta: 12 },
+                ],
+                vec![StackMapFrame::SameLocals1StackItem {
+                    offset_delta: 1,
+                    stack: VerificationTypeInfo::Object {
+                        cpool_index: exception,
+                    },
+                }],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let mut class = ClassBuilder::new("Broken");
+        class.public_method("run", "()V", |body| {
+            let label = body.code.label();
+            body.code.goto(label);
+            Ok(())
+        });
+        assert!(matches!(
+            class.build(),
+            Err(ClassBuildError::CodeBuildError(_))
+        ));
+
+        let mut class = ClassBuilder::new("Underflow");
+        class.public_static_method("run", "()V", |body| {
+            body.code.emit(Instruction::Pop).emit(Instruction::Return);
+            Ok(())
+        });
+        assert_eq!(
+            class.build().err(),
+            Some(ClassBuildError::MaxStackError(
+                MaxStackError::StackUnderflow(0)
+            ))
+        );
+
+        let mut class = ClassBuilder::new("Descriptor");
+        class.public_method("run", "(V", |_| Ok(()));
+        assert_eq!(
+            class.build().err(),
+            Some(ClassBuildError::InvalidMethodDescriptor("(V".to_string()))
+        );
+
+        let mut class = ClassBuilder::new("Long");
+        class.field(FieldAccessFlags::EMPTY, &"x".repeat(70000), "I");
+        assert_eq!(
+            class.build().err(),
+            Some(ClassBuildError::ConstantPoolBuildError(
+                ConstantPoolBuildError::Utf8TooLong(70000)
+            ))
+        );
+
+        let mut class = ClassBuilder::new("Missing");
+        class.getter("name");
+        assert_eq!(
+            class.build().err(),
+            Some(ClassBuildError::UnknownField("name".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_build_accessors() {
+        let mut class = ClassBuilder::new("Bean");
+        class
+            .version(ClassVersion::new(52, 0))
+            .field(FieldAccessFlags::PRIVATE, "name", "Ljava/lang/String;")
+            .field(FieldAccessFlags::PRIVATE, "size", "J")
+            .field(FieldAccessFlags::PRIVATE, "ready", "Z")
+            .field(FieldAccessFlags::STATIC, "count", "I")
+            .default_constructor()
+            .getter("name")
+            .setter("name")
+            .getter("size")
+            .setter("size")
+            .getter("ready")
+            .getter("count")
+            .setter("count")
+            .main_stub();
+        let classfile = class.build().unwrap();
+        let output = classfile.print_with(&PrintOptions::JAVAP).unwrap();
+        let expected = r#"
+public class Bean {
+  static int count;
+  public Bean();
+  public java.lang.String getName();
+  public void setName(java.lang.String);
+  public long getSize();
+  public void setSize(long);
+  public boolean isReady();
+  public static int getCount();
+  public static void setCount(int);
+  public static void main(java.lang.String[]);
+}
+"#;
+        assert_eq!(output, expected[1..]);
+
+        let code = classfile
+            .methods
+            .iter()
+            .map(|method| {
+                let code = method.code().unwrap();
+                (code.code.to_vec(), code.max_stack, code.max_locals)
+            })
+            .collect::<Vec<_>>();
+        let field = |name: &str| {
+            let (index, _) = classfile
+                .constant_pool
+                .field_refs()
+                .find(|(_, field_ref)| field_ref.name == name)
+                .unwrap();
+            index.to_be_bytes()
+        };
+        let [name, size, count] = [field("name"), field("size"), field("count")];
+        assert_eq!(code[1], (vec![0x2a, 0xb4, name[0], name[1], 0xb0], 1, 1));
+        assert_eq!(
+            code[4],
+            (vec![0x2a, 0x1f, 0xb5, size[0], size[1], 0xb1], 3, 3)
+        );
+        assert_eq!(code[6], (vec![0xb2, count[0], count[1], 0xac], 1, 0));
+        assert_eq!(code[7], (vec![0x1a, 0xb3, count[0], count[1], 0xb1], 1, 1));
+        assert_eq!(code[8], (vec![0xb1], 0, 1));
+    }
+}
@@ -0,0 +1,1278 @@
+// A textual form of a whole class, which assembles back to the bytes it was
+// disassembled from:
+//
+//     .version 52 0
+//     .const #1 = Methodref #2.#3           // java/lang/Object.<init>:()V
+//     .const #2 = Class #4                  // java/lang/Object
+//     .const #3 = NameAndType #5:#6         // <init>:()V
+//     .const #4 = Utf8 "java/lang/Object"
+//     ...
+//     .class 0x0021 #7                      // Hello
+//     .super #2                             // java/lang/Object
+//     .field 0x0002 #8 #9                   // count:I
+//     .end field
+//     .method 0x0001 #5 #6                  // <init>:()V
+//     .code 1 1
+//         aload_0
+//         invokespecial #1
+//         return
+//     .attribute #10 0001000000010000       // LineNumberTable
+//     .end code
+//     .end method
+//
+// The constant pool is listed entry by entry, in order, and everything else
+// refers to it by index. The body of a `.code` block uses the syntax of
+// `text`, and `.catch <start> <end> <handler> #<type>` adds an exception
+// handler by label or pc. `.code` without max_stack and max_locals computes
+// them, from the constants declared before it. Attributes other than Code
+// are kept as their name and payload in hex, and belong to the innermost
+// open `.field`, `.method` or `.code` block, or else to the class.
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt;
+
+use super::error::MaxStackError;
+use super::instruction::{disassemble as disassemble_code, Instruction};
+use super::stack::compute_max_stack;
+use super::text::{assemble_with_labels, parse_number, TextError};
+use crate::analysis::locals::compute_max_locals;
+use crate::class::{
+    decode_modified_utf8, encode_modified_utf8, parse_classfile_lazy, parse_code,
+    parse_raw_attribute, Attribute, ClassAccessFlags, ClassFile, ClassParseError, Code, Constant,
+    ConstantPool, ExceptionTableEntry, Field, FieldAccessFlags, Method, MethodAccessFlags,
+    MethodDescriptor, ReferenceKind, CLASS_FILE_MAGIC,
+};
+
+#[derive(Debug, PartialEq)]
+pub enum ClassTextError {
+    UnknownDirective { line: usize, directive: String },
+    InvalidOperand { line: usize, operand: String },
+    MissingOperand { line: usize },
+    UnexpectedOperand { line: usize, operand: String },
+    // a directive outside the block it belongs in, like `.catch` outside
+    // `.code`, one given twice, or an instruction outside `.code`
+    Misplaced { line: usize, directive: String },
+    // a block still open at the end of the text, by the line it starts on
+    Unclosed { line: usize },
+    // a `.const` whose index isn't the next free slot
+    ConstantOutOfOrder { line: usize, index: u16 },
+    UndefinedLabel { line: usize, label: String },
+    // computing max_stack and max_locals for the `.code` on `line`
+    InvalidMethodDescriptor { line: usize },
+    MaxStack { line: usize, error: MaxStackError },
+    // a `.code` body, with line numbers counted from the start of the text
+    Code(TextError),
+    MissingClass,
+}
+
+impl fmt::Display for ClassTextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClassTextError::UnknownDirective { line, directive } => {
+                write!(f, "line {line}: unknown directive {directive}")
+            }
+            ClassTextError::InvalidOperand { line, operand } => {
+                write!(f, "line {line}: invalid operand {operand}")
+            }
+            ClassTextError::MissingOperand { line } => write!(f, "line {line}: missing operand"),
+            ClassTextError::UnexpectedOperand { line, operand } => {
+                write!(f, "line {line}: unexpected operand {operand}")
+            }
+            ClassTextError::Misplaced { line, directive } => {
+                write!(f, "line {line}: misplaced {directive}")
+            }
+            ClassTextError::Unclosed { line } => {
+                write!(f, "line {line}: block is never closed")
+            }
+            ClassTextError::ConstantOutOfOrder { line, index } => {
+                write!(f, "line {line}: #{index} isn't the next free constant")
+            }
+            ClassTextError::UndefinedLabel { line, label } => {
+                write!(f, "line {line}: undefined label {label}")
+            }
+            ClassTextError::InvalidMethodDescriptor { line } => {
+                write!(f, "line {line}: invalid method descriptor")
+            }
+            ClassTextError::MaxStack { line, error } => write!(f, "line {line}: {error}"),
+            ClassTextError::Code(error) => write!(f, "{error}"),
+            ClassTextError::MissingClass => write!(f, "no .class directive"),
+        }
+    }
+}
+
+// the whitespace-separated tokens of a directive up to any `//` comment; a
+// quoted string is one token, quotes included. None for an unterminated string
+fn tokenize(line: &str) -> Option<Vec<&str>> {
+    let mut tokens = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() && !rest.starts_with("//") {
+        let end = match rest.strip_prefix('"') {
+            Some(string) => {
+                let mut escaped = false;
+                let (close, _) = string.char_indices().find(|(_, c)| {
+                    let close = *c == '"' && !escaped;
+                    escaped = *c == '\\' && !escaped;
+                    close
+                })?;
+                close + 2
+            }
+            None => rest.find(char::is_whitespace).unwrap_or(rest.len()),
+        };
+        tokens.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+    Some(tokens)
+}
+
+// control characters, quotes and backslashes escaped as in Java
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unquote(token: &str) -> Option<String> {
+    let string = token.strip_prefix('"')?.strip_suffix('"')?;
+    let mut value = String::with_capacity(string.len());
+    let mut chars = string.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        let c = match chars.next()? {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                let hex = chars.as_str().get(..4)?;
+                chars.nth(3);
+                char::from_u32(u32::from_str_radix(hex, 16).ok()?)?
+            }
+            c @ ('"' | '\\') => c,
+            _ => return None,
+        };
+        value.push(c);
+    }
+    Some(value)
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn to_hex(data: &[u8]) -> String {
+    let mut hex = String::with_capacity(data.len() * 2);
+    for byte in data {
+        hex.push_str(&format!("{byte:02x}"));
+    }
+    hex
+}
+
+fn parse_index(token: &str) -> Option<u16> {
+    let value = parse_number(token.strip_prefix('#').unwrap_or(token))?;
+    u16::try_from(value).ok()
+}
+
+// `#1.#2` or `#1:#2`
+fn parse_index_pair(token: &str, separator: char) -> Option<(u16, u16)> {
+    let (first, second) = token.split_once(separator)?;
+    Some((parse_index(first)?, parse_index(second)?))
+}
+
+struct Operands<'a> {
+    line: usize,
+    tokens: std::slice::Iter<'a, &'a str>,
+}
+
+impl<'a> Operands<'a> {
+    fn next(&mut self) -> Result<&'a str, ClassTextError> {
+        self.tokens
+            .next()
+            .copied()
+            .ok_or(ClassTextError::MissingOperand { line: self.line })
+    }
+
+    fn invalid(&self, operand: &str) -> ClassTextError {
+        ClassTextError::InvalidOperand {
+            line: self.line,
+            operand: operand.to_string(),
+        }
+    }
+
+    fn parse<T>(&mut self, parse: impl FnOnce(&str) -> Option<T>) -> Result<T, ClassTextError> {
+        let token = self.next()?;
+        parse(token).ok_or_else(|| self.invalid(token))
+    }
+
+    fn number<T: TryFrom<i64>>(&mut self) -> Result<T, ClassTextError> {
+        self.parse(|token| T::try_from(parse_number(token)?).ok())
+    }
+
+    fn index(&mut self) -> Result<u16, ClassTextError> {
+        self.parse(parse_index)
+    }
+
+    fn finish(mut self) -> Result<(), ClassTextError> {
+        match self.tokens.next() {
+            Some(token) => Err(ClassTextError::UnexpectedOperand {
+                line: self.line,
+                operand: token.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_constant(operands: &mut Operands) -> Result<Constant<'static>, ClassTextError> {
+    let tag = operands.next()?;
+    let constant = match tag {
+        "Utf8" => {
+            let value = operands.parse(|token| match token.strip_prefix("0x") {
+                Some(hex) => parse_hex(hex),
+                None => Some(encode_modified_utf8(&unquote(token)?).into_owned()),
+            })?;
+            Constant::Utf8 {
+                value: Cow::Owned(value),
+            }
+        }
+        "Integer" => Constant::Integer {
+            value: operands.number()?,
+        },
+        // NaNs are written as their bits, to keep the payload
+        "Float" => Constant::Float {
+            value: operands.parse(|token| match token.strip_prefix("0x") {
+                Some(bits) => Some(f32::from_bits(u32::from_str_radix(bits, 16).ok()?)),
+                None => token.parse().ok(),
+            })?,
+        },
+        "Long" => Constant::Long {
+            value: operands.parse(|token| token.parse().ok())?,
+        },
+        "Double" => Constant::Double {
+            value: operands.parse(|token| match token.strip_prefix("0x") {
+                Some(bits) => Some(f64::from_bits(u64::from_str_radix(bits, 16).ok()?)),
+                None => token.parse().ok(),
+            })?,
+        },
+        "Class" => Constant::Class {
+            name_index: operands.index()?,
+        },
+        "String" => Constant::String {
+            string_index: operands.index()?,
+        },
+        "Fieldref" | "Methodref" | "InterfaceMethodref" => {
+            let (class_index, name_and_type_index) =
+                operands.parse(|token| parse_index_pair(token, '.'))?;
+            match tag {
+                "Fieldref" => Constant::Fieldref {
+                    class_index,
+                    name_and_type_index,
+                },
+                "Methodref" => Constant::Methodref {
+                    class_index,
+                    name_and_type_index,
+                },
+                _ => Constant::InterfaceMethodref {
+                    class_index,
+                    name_and_type_index,
+                },
+            }
+        }
+        "NameAndType" => {
+            let (name_index, descriptor_index) =
+                operands.parse(|token| parse_index_pair(token, ':'))?;
+            Constant::NameAndType {
+                name_index,
+                descriptor_index,
+            }
+        }
+        "MethodHandle" => {
+            let (reference_kind, reference_index) = operands.parse(|token| {
+                let (kind, index) = token.split_once(':')?;
+                Some((
+                    ReferenceKind::from_u8(kind.parse().ok()?)?,
+                    parse_index(index)?,
+                ))
+            })?;
+            Constant::MethodHandle {
+                reference_kind,
+                reference_index,
+            }
+        }
+        "MethodType" => Constant::MethodType {
+            descriptor_index: operands.index()?,
+        },
+        "Dynamic" | "InvokeDynamic" => {
+            let (bootstrap_method_attr_index, name_and_type_index) =
+                operands.parse(|token| parse_index_pair(token, ':'))?;
+            match tag {
+                "Dynamic" => Constant::Dynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                },
+                _ => Constant::InvokeDynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                },
+            }
+        }
+        "Module" => Constant::Module {
+            name_index: operands.index()?,
+        },
+        "Package" => Constant::Package {
+            name_index: operands.index()?,
+        },
+        _ => return Err(operands.invalid(tag)),
+    };
+    Ok(constant)
+}
+
+// a `.catch`, resolved once the code's labels are
+struct Catch {
+    line: usize,
+    pcs: [String; 3],
+    catch_type: u16,
+}
+
+struct CodeBlock {
+    line: usize,
+    max_stack_and_locals: Option<(u16, u16)>,
+    // the lines of the block, with directives blanked out so that line
+    // numbers in errors can be offset by `line`
+    body: String,
+    catches: Vec<Catch>,
+    attributes: Vec<Attribute<'static>>,
+}
+
+// the innermost open block, with the line it starts on
+enum Block {
+    Class,
+    Field(usize, Field<'static>),
+    Method(usize, Method<'static>),
+    Code(usize, Method<'static>, CodeBlock),
+}
+
+struct ClassAssembler {
+    version: Option<(u16, u16)>,
+    constant_pool: ConstantPool<'static>,
+    class: Option<(ClassAccessFlags, u16)>,
+    super_class: Option<u16>,
+    interfaces: Vec<u16>,
+    fields: Vec<Field<'static>>,
+    methods: Vec<Method<'static>>,
+    attributes: Vec<Attribute<'static>>,
+    block: Block,
+}
+
+fn misplaced(line: usize, directive: &str) -> ClassTextError {
+    ClassTextError::Misplaced {
+        line,
+        directive: directive.to_string(),
+    }
+}
+
+// text's errors, with line numbers `offset` lines further on
+fn offset_lines(error: TextError, offset: usize) -> TextError {
+    match error {
+        TextError::UnknownMnemonic { line, mnemonic } => TextError::UnknownMnemonic {
+            line: line + offset,
+            mnemonic,
+        },
+        TextError::InvalidOperand { line, operand } => TextError::InvalidOperand {
+            line: line + offset,
+            operand,
+        },
+        TextError::MissingOperand { line } => TextError::MissingOperand {
+            line: line + offset,
+        },
+        TextError::UnexpectedOperand { line, operand } => TextError::UnexpectedOperand {
+            line: line + offset,
+            operand,
+        },
+        TextError::DuplicateLabel { line, label } => TextError::DuplicateLabel {
+            line: line + offset,
+            label,
+        },
+        error => error,
+    }
+}
+
+impl ClassAssembler {
+    fn new() -> Self {
+        ClassAssembler {
+            version: None,
+            constant_pool: ConstantPool::new(),
+            class: None,
+            super_class: None,
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: Vec::new(),
+            attributes: Vec::new(),
+            block: Block::Class,
+        }
+    }
+
+    fn line(&mut self, line: usize, text: &str) -> Result<(), ClassTextError> {
+        let is_directive = text.trim_start().starts_with('.');
+        if let Block::Code(_, _, code) = &mut self.block {
+            if !is_directive {
+                code.body.push_str(text);
+            }
+            code.body.push('\n');
+            if !is_directive {
+                return Ok(());
+            }
+        }
+        let tokens = tokenize(text).ok_or_else(|| ClassTextError::InvalidOperand {
+            line,
+            operand: text.trim().to_string(),
+        })?;
+        let Some((directive, tokens)) = tokens.split_first() else {
+            return Ok(());
+        };
+        let mut operands = Operands {
+            line,
+            tokens: tokens.iter(),
+        };
+        let at_class = matches!(self.block, Block::Class);
+        match *directive {
+            ".version" if at_class && self.version.is_none() => {
+                self.version = Some((operands.number()?, operands.number()?));
+            }
+            ".const" if at_class => {
+                let index = operands.index()?;
+                if index as usize != self.constant_pool.len() + 1 {
+                    return Err(ClassTextError::ConstantOutOfOrder { line, index });
+                }
+                match operands.next()? {
+                    "=" => {}
+                    token => return Err(operands.invalid(token)),
+                }
+                let constant = parse_constant(&mut operands)?;
+                self.constant_pool.push(constant);
+            }
+            ".class" if at_class && self.class.is_none() => {
+                let access_flags = ClassAccessFlags::from_bits(operands.number()?);
+                self.class = Some((access_flags, operands.index()?));
+            }
+            ".super" if at_class && self.super_class.is_none() => {
+                self.super_class = Some(operands.index()?);
+            }
+            ".implements" if at_class => self.interfaces.push(operands.index()?),
+            ".field" if at_class => {
+                let field = Field {
+                    access_flags: FieldAccessFlags::from_bits(operands.number()?),
+                    name_index: operands.index()?,
+                    descriptor_index: operands.index()?,
+                    attributes: Vec::new(),
+                };
+                self.block = Block::Field(line, field);
+            }
+            ".method" if at_class => {
+                let method = Method {
+                    access_flags: MethodAccessFlags::from_bits(operands.number()?),
+                    name_index: operands.index()?,
+                    descriptor_index: operands.index()?,
+                    attributes: Vec::new(),
+                };
+                self.block = Block::Method(line, method);
+            }
+            ".code" if matches!(self.block, Block::Method(..)) => {
+                let max_stack_and_locals = match operands.tokens.len() {
+                    0 => None,
+                    _ => Some((operands.number()?, operands.number()?)),
+                };
+                let Block::Method(start, method) = std::mem::replace(&mut self.block, Block::Class)
+                else {
+                    unreachable!()
+                };
+                let code = CodeBlock {
+                    line,
+                    max_stack_and_locals,
+                    body: String::new(),
+                    catches: Vec::new(),
+                    attributes: Vec::new(),
+                };
+                self.block = Block::Code(start, method, code);
+            }
+            ".catch" => {
+                let Block::Code(_, _, code) = &mut self.block else {
+                    return Err(misplaced(line, directive));
+                };
+                let pcs = [
+                    operands.next()?.to_string(),
+                    operands.next()?.to_string(),
+                    operands.next()?.to_string(),
+                ];
+                let catch_type = operands.index()?;
+                code.catches.push(Catch {
+                    line,
+                    pcs,
+                    catch_type,
+                });
+            }
+            ".attribute" => {
+                let attribute = self.attribute(&mut operands)?;
+                match &mut self.block {
+                    Block::Class => self.attributes.push(attribute),
+                    Block::Field(_, field) => field.attributes.push(attribute),
+                    Block::Method(_, method) => method.attributes.push(attribute),
+                    Block::Code(_, _, code) => code.attributes.push(attribute),
+                }
+            }
+            ".end" => self.end(line, operands.next()?)?,
+            ".version" | ".const" | ".class" | ".super" | ".implements" | ".field" | ".method"
+            | ".code" => return Err(misplaced(line, directive)),
+            directive if directive.starts_with('.') => {
+                return Err(ClassTextError::UnknownDirective {
+                    line,
+                    directive: directive.to_string(),
+                })
+            }
+            // an instruction outside `.code`
+            instruction => return Err(misplaced(line, instruction)),
+        }
+        operands.finish()
+    }
+
+    // `.attribute #<name> <hex>...`, whose name must already be in the pool
+    fn attribute(&self, operands: &mut Operands) -> Result<Attribute<'static>, ClassTextError> {
+        let token = operands.tokens.as_slice().first().copied().unwrap_or("");
+        let attribute_name_index = operands.index()?;
+        let name = match self.constant_pool.get(attribute_name_index) {
+            Some(Constant::Utf8 { value }) => value.clone(),
+            _ => return Err(operands.invalid(token)),
+        };
+        let mut data = Vec::new();
+        while !operands.tokens.as_slice().is_empty() {
+            data.extend(operands.parse(parse_hex)?);
+        }
+        Ok(Attribute::Unknown {
+            attribute_name_index,
+            name,
+            data: Cow::Owned(data),
+        })
+    }
+
+    fn end(&mut self, line: usize, kind: &str) -> Result<(), ClassTextError> {
+        match (std::mem::replace(&mut self.block, Block::Class), kind) {
+            (Block::Field(_, field), "field") => self.fields.push(field),
+            (Block::Method(_, method), "method") => self.methods.push(method),
+            (Block::Code(start, mut method, code), "code") => {
+                method
+                    .attributes
+                    .push(Attribute::Code(self.code(code, &method)?));
+                self.block = Block::Method(start, method);
+            }
+            (block, _) => {
+                self.block = block;
+                return Err(misplaced(line, &format!(".end {kind}")));
+            }
+        }
+        Ok(())
+    }
+
+    fn code(
+        &self,
+        code: CodeBlock,
+        method: &Method,
+    ) -> Result<Code<'static, Attribute<'static>>, ClassTextError> {
+        let (assembled, labels) = assemble_with_labels(&code.body)
+            .map_err(|error| ClassTextError::Code(offset_lines(error, code.line)))?;
+        let mut exception_table = Vec::new();
+        for catch in &code.catches {
+            let mut pcs = [0; 3];
+            for (pc, token) in pcs.iter_mut().zip(&catch.pcs) {
+                *pc = match parse_number(token) {
+                    Some(number) => {
+                        u16::try_from(number).map_err(|_| ClassTextError::InvalidOperand {
+                            line: catch.line,
+                            operand: token.clone(),
+                        })?
+                    }
+                    None => match labels.get(token) {
+                        Some(pc) => *pc as u16,
+                        None => {
+                            return Err(ClassTextError::UndefinedLabel {
+                                line: catch.line,
+                                label: token.clone(),
+                            })
+                        }
+                    },
+                };
+            }
+            exception_table.push(ExceptionTableEntry {
+                start_pc: pcs[0],
+                end_pc: pcs[1],
+                handler_pc: pcs[2],
+                catch_type: catch.catch_type,
+            });
+        }
+        let (max_stack, max_locals) = match code.max_stack_and_locals {
+            Some(max_stack_and_locals) => max_stack_and_locals,
+            None => {
+                let line = code.line;
+                let max_stack = compute_max_stack(
+                    &assembled.instructions,
+                    &exception_table,
+                    &self.constant_pool,
+                )
+                .map_err(|error| ClassTextError::MaxStack { line, error })?;
+                let descriptor = match self.constant_pool.get(method.descriptor_index) {
+                    Some(Constant::Utf8 { value }) => MethodDescriptor::parse(value).ok(),
+                    _ => None,
+                }
+                .ok_or(ClassTextError::InvalidMethodDescriptor { line })?;
+                let is_static = method.access_flags.contains(MethodAccessFlags::STATIC);
+                let max_locals =
+                    compute_max_locals(&assembled.instructions, &descriptor, is_static);
+                (max_stack, max_locals)
+            }
+        };
+        Ok(Code {
+            max_stack,
+            max_locals,
+            code: Cow::Owned(assembled.code),
+            exception_table,
+            attributes: code.attributes,
+        })
+    }
+
+    fn finish(self) -> Result<ClassFile<'static>, ClassTextError> {
+        match self.block {
+            Block::Class => {}
+            Block::Field(line, _)
+            | Block::Method(line, _)
+            | Block::Code(_, _, CodeBlock { line, .. }) => {
+                return Err(ClassTextError::Unclosed { line })
+            }
+        }
+        let (access_flags, this_class) = self.class.ok_or(ClassTextError::MissingClass)?;
+        // the version ClassBuilder defaults to as well
+        let (major_version, minor_version) = self.version.unwrap_or((49, 0));
+        Ok(ClassFile {
+            magic: CLASS_FILE_MAGIC,
+            minor_version,
+            major_version,
+            constant_pool: self.constant_pool,
+            access_flags,
+            this_class,
+            super_class: self.super_class.unwrap_or(0),
+            interfaces: self.interfaces,
+            fields: self.fields,
+            methods: self.methods,
+            attributes: self.attributes,
+        })
+    }
+}
+
+pub fn assemble(text: &str) -> Result<ClassFile<'static>, ClassTextError> {
+    let mut assembler = ClassAssembler::new();
+    for (i, line) in text.lines().enumerate() {
+        assembler.line(i + 1, line)?;
+    }
+    assembler.finish()
+}
+
+// `line`, followed by `comment` at column 40 unless it's empty
+fn with_comment(line: String, comment: Option<String>) -> String {
+    match comment {
+        Some(comment) if !comment.is_empty() => format!("{line:<39} // {comment}\n"),
+        _ => format!("{line}\n"),
+    }
+}
+
+fn format_constant(constant: &Constant) -> String {
+    match constant {
+        Constant::Utf8 { value } => match decode_modified_utf8(value) {
+            // modified UTF-8 that doesn't encode back the same is kept as hex
+            Ok(string) if *encode_modified_utf8(&string) == **value => {
+                format!("Utf8 \"{}\"", escape(&string))
+            }
+            _ => format!("Utf8 0x{}", to_hex(value)),
+        },
+        Constant::Integer { value } => format!("Integer {value}"),
+        Constant::Float { value } => match value.is_nan() {
+            true => format!("Float {:#x}", value.to_bits()),
+            false => format!("Float {value:?}"),
+        },
+        Constant::Long { value } => format!("Long {value}"),
+        Constant::Double { value } => match value.is_nan() {
+            true => format!("Double {:#x}", value.to_bits()),
+            false => format!("Double {value:?}"),
+        },
+        Constant::Class { name_index } => format!("Class #{name_index}"),
+        Constant::String { string_index } => format!("String #{string_index}"),
+        Constant::Fieldref {
+            class_index,
+            name_and_type_index,
+        } => format!("Fieldref #{class_index}.#{name_and_type_index}"),
+        Constant::Methodref {
+            class_index,
+            name_and_type_index,
+        } => format!("Methodref #{class_index}.#{name_and_type_index}"),
+        Constant::InterfaceMethodref {
+            class_index,
+            name_and_type_index,
+        } => format!("InterfaceMethodref #{class_index}.#{name_and_type_index}"),
+        Constant::NameAndType {
+            name_index,
+            descriptor_index,
+        } => format!("NameAndType #{name_index}:#{descriptor_index}"),
+        Constant::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => format!("MethodHandle {}:#{reference_index}", *reference_kind as u8),
+        Constant::MethodType { descriptor_index } => format!("MethodType #{descriptor_index}"),
+        Constant::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => format!("Dynamic #{bootstrap_method_attr_index}:#{name_and_type_index}"),
+        Constant::InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => format!("InvokeDynamic #{bootstrap_method_attr_index}:#{name_and_type_index}"),
+        Constant::Module { name_index } => format!("Module #{name_index}"),
+        Constant::Package { name_index } => format!("Package #{name_index}"),
+    }
+}
+
+// what constant `index` stands for, for comments; None for Utf8 entries and
+// references that don't resolve
+fn describe(constant_pool: &ConstantPool, index: u16) -> Option<String> {
+    let description = match constant_pool.get(index)? {
+        Constant::Utf8 { .. } => return None,
+        Constant::Integer { value } => value.to_string(),
+        Constant::Float { value } => format!("{value:?}"),
+        Constant::Long { value } => value.to_string(),
+        Constant::Double { value } => format!("{value:?}"),
+        Constant::Class { .. } => constant_pool.class_name(index).ok()?.into_owned(),
+        Constant::String { string_index } => {
+            let value = constant_pool.utf8(*string_index).ok()?;
+            return Some(format!("\"{}\"", escape(&value)));
+        }
+        Constant::Fieldref { .. } => {
+            let field = constant_pool.field_ref(index).ok()?;
+            format!("{}.{}:{}", field.class_name, field.name, field.descriptor)
+        }
+        Constant::Methodref { .. } => {
+            let method = constant_pool.method_ref(index).ok()?;
+            format!(
+                "{}.{}:{}",
+                method.class_name, method.name, method.descriptor
+            )
+        }
+        Constant::InterfaceMethodref { .. } => {
+            let method = constant_pool.interface_method_ref(index).ok()?;
+            format!(
+                "{}.{}:{}",
+                method.class_name, method.name, method.descriptor
+            )
+        }
+        Constant::NameAndType { .. } => {
+            let name_and_type = constant_pool.name_and_type(index).ok()?;
+            format!("{}:{}", name_and_type.name, name_and_type.descriptor)
+        }
+        Constant::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => format!(
+            "{reference_kind:?} {}",
+            describe(constant_pool, *reference_index)?
+        ),
+        Constant::MethodType { descriptor_index }
+        | Constant::Module {
+            name_index: descriptor_index,
+        }
+        | Constant::Package {
+            name_index: descriptor_index,
+        } => constant_pool.utf8(*descriptor_index).ok()?.into_owned(),
+        Constant::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        }
+        | Constant::InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => {
+            let name_and_type = constant_pool.name_and_type(*name_and_type_index).ok()?;
+            format!(
+                "#{bootstrap_method_attr_index}:{}:{}",
+                name_and_type.name, name_and_type.descriptor
+            )
+        }
+    };
+    Some(escape(&description))
+}
+
+// `name:descriptor` of a field or method, for comments
+fn describe_member(constant_pool: &ConstantPool, name_index: u16, descriptor_index: u16) -> String {
+    let name = constant_pool.utf8(name_index).unwrap_or_default();
+    let descriptor = constant_pool.utf8(descriptor_index).unwrap_or_default();
+    escape(&format!("{name}:{descriptor}"))
+}
+
+fn array_type(atype: u8) -> Option<&'static str> {
+    let name = match atype {
+        4 => "boolean",
+        5 => "char",
+        6 => "float",
+        7 => "double",
+        8 => "byte",
+        9 => "short",
+        10 => "int",
+        11 => "long",
+        _ => return None,
+    };
+    Some(name)
+}
+
+// an instruction in text's syntax, with branch targets in `labels` written
+// as `L<pc>`; None for a switch to a pc that isn't labelled
+fn format_instruction(
+    pc: u32,
+    instruction: &Instruction,
+    labels: &BTreeSet<u32>,
+) -> Option<String> {
+    let label = |offset: i32| {
+        let target = u32::try_from(pc as i64 + offset as i64).ok()?;
+        labels.contains(&target).then(|| format!("L{target}"))
+    };
+    let mnemonic = instruction.mnemonic();
+    let operands = match instruction {
+        _ if instruction.byte_len(pc) == 1 => return Some(mnemonic.to_string()),
+        Instruction::Bipush(value) => value.to_string(),
+        Instruction::Sipush(value) => value.to_string(),
+        Instruction::Iinc(index, value) => format!("{index} {value}"),
+        Instruction::WideIinc(index, value) => format!("{index} {value}"),
+        Instruction::Invokeinterface(index, count, _) => format!("#{index} {count}"),
+        Instruction::Multianewarray(index, dimensions) => format!("#{index} {dimensions}"),
+        Instruction::Newarray(atype) => match array_type(*atype) {
+            Some(name) => name.to_string(),
+            None => atype.to_string(),
+        },
+        Instruction::Tableswitch(default, low, _, offsets) => {
+            let mut operands = format!("{low} {}", label(*default)?);
+            for offset in offsets {
+                operands.push(' ');
+                operands.push_str(&label(*offset)?);
+            }
+            operands
+        }
+        Instruction::Lookupswitch(default, pairs) => {
+            let mut operands = label(*default)?;
+            for (key, offset) in pairs {
+                operands.push_str(&format!(" {key}:{}", label(*offset)?));
+            }
+            operands
+        }
+        _ => match (
+            instruction.constant_pool_index(),
+            instruction.branch_target(pc),
+            instruction.local_index(),
+        ) {
+            (Some(index), _, _) => format!("#{index}"),
+            // a branch to the middle of an instruction keeps its offset
+            (_, Some(target), _) => {
+                let offset = target - pc as i64;
+                label(offset as i32).unwrap_or_else(|| format!("{offset:+}"))
+            }
+            (_, _, Some(index)) => index.to_string(),
+            _ => return None,
+        },
+    };
+    Some(format!("{mnemonic} {operands}"))
+}
+
+fn format_pc(pc: u16, labels: &BTreeSet<u32>) -> String {
+    match labels.contains(&(pc as u32)) {
+        true => format!("L{pc}"),
+        false => pc.to_string(),
+    }
+}
+
+// a Code attribute as a `.code` block; None when assembling the block
+// wouldn't give back the same bytes, for the caller to keep it as hex
+fn format_code(data: &[u8], constant_pool: &ConstantPool) -> Option<String> {
+    let (rest, Attribute::Code(code)) =
+        parse_code(data, constant_pool, parse_raw_attribute).ok()?
+    else {
+        return None;
+    };
+    if !rest.is_empty() {
+        return None;
+    }
+    let instructions = disassemble_code(&code.code)
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    let mut encoded = Vec::with_capacity(code.code.len());
+    for (pc, instruction) in &instructions {
+        instruction.encode(*pc, &mut encoded);
+    }
+    if encoded != *code.code {
+        return None;
+    }
+
+    let mut starts = instructions
+        .iter()
+        .map(|(pc, _)| *pc)
+        .collect::<BTreeSet<_>>();
+    starts.insert(code.code.len() as u32);
+    let targets = instructions
+        .iter()
+        .flat_map(|(pc, instruction)| instruction.branch_targets(*pc))
+        .filter_map(|target| u32::try_from(target).ok());
+    let handlers = code
+        .exception_table
+        .iter()
+        .flat_map(|entry| [entry.start_pc, entry.end_pc, entry.handler_pc].map(|pc| pc as u32));
+    let labels = targets
+        .chain(handlers)
+        .filter(|pc| starts.contains(pc))
+        .collect::<BTreeSet<_>>();
+
+    let mut output = format!(".code {} {}\n", code.max_stack, code.max_locals);
+    for (pc, instruction) in &instructions {
+        if labels.contains(pc) {
+            output.push_str(&format!("L{pc}:\n"));
+        }
+        let line = format!("    {}", format_instruction(*pc, instruction, &labels)?);
+        let comment = instruction
+            .constant_pool_index()
+            .and_then(|index| describe(constant_pool, index));
+        output.push_str(&with_comment(line, comment));
+    }
+    if labels.contains(&(code.code.len() as u32)) {
+        output.push_str(&format!("L{}:\n", code.code.len()));
+    }
+    for entry in &code.exception_table {
+        let line = format!(
+            ".catch {} {} {} #{}",
+            format_pc(entry.start_pc, &labels),
+            format_pc(entry.end_pc, &labels),
+            format_pc(entry.handler_pc, &labels),
+            entry.catch_type
+        );
+        let comment = match entry.catch_type {
+            0 => Some("any".to_string()),
+            catch_type => describe(constant_pool, catch_type),
+        };
+        output.push_str(&with_comment(line, comment));
+    }
+    for attribute in &code.attributes {
+        output.push_str(&format_attribute(attribute, constant_pool));
+    }
+    output.push_str(".end code\n");
+    Some(output)
+}
+
+fn format_attribute(attribute: &Attribute, constant_pool: &ConstantPool) -> String {
+    let Attribute::Raw {
+        attribute_name_index,
+        name,
+        data,
+    } = attribute
+    else {
+        unreachable!("lazily parsed attributes are raw")
+    };
+    // the writer names a decoded Code attribute by the first Code in the pool
+    if **name == *b"Code" && constant_pool.find_utf8("Code") == Some(*attribute_name_index) {
+        if let Some(code) = format_code(data, constant_pool) {
+            return code;
+        }
+    }
+    let line = match data.is_empty() {
+        true => format!(".attribute #{attribute_name_index}"),
+        false => format!(".attribute #{attribute_name_index} {}", to_hex(data)),
+    };
+    let name = decode_modified_utf8(name).map(|name| escape(&name));
+    with_comment(line, name.ok())
+}
+
+// the class in the syntax `assemble` reads, which assembles back to `data`
+pub fn disassemble(data: &[u8]) -> Result<String, ClassParseError> {
+    let (_, classfile) = parse_classfile_lazy(data)?;
+    let constant_pool = &classfile.constant_pool;
+    let mut output = format!(
+        ".version {} {}\n",
+        classfile.major_version, classfile.minor_version
+    );
+    for (index, constant) in constant_pool.iter() {
+        let line = format!(".const #{index} = {}", format_constant(constant));
+        output.push_str(&with_comment(line, describe(constant_pool, index)));
+    }
+
+    output.push('\n');
+    let line = format!(
+        ".class {:#06x} #{}",
+        classfile.access_flags.bits(),
+        classfile.this_class
+    );
+    output.push_str(&with_comment(
+        line,
+        describe(constant_pool, classfile.this_class),
+    ));
+    let line = format!(".super #{}", classfile.super_class);
+    output.push_str(&with_comment(
+        line,
+        describe(constant_pool, classfile.super_class),
+    ));
+    for interface in &classfile.interfaces {
+        let line = format!(".implements #{interface}");
+        output.push_str(&with_comment(line, describe(constant_pool, *interface)));
+    }
+
+    for field in &classfile.fields {
+        output.push('\n');
+        let line = format!(
+            ".field {:#06x} #{} #{}",
+            field.access_flags.bits(),
+            field.name_index,
+            field.descriptor_index
+        );
+        let comment = describe_member(constant_pool, field.name_index, field.descriptor_index);
+        output.push_str(&with_comment(line, Some(comment)));
+        for attribute in &field.attributes {
+            output.push_str(&format_attribute(attribute, constant_pool));
+        }
+        output.push_str(".end field\n");
+    }
+    for method in &classfile.methods {
+        output.push('\n');
+        let line = format!(
+            ".method {:#06x} #{} #{}",
+            method.access_flags.bits(),
+            method.name_index,
+            method.descriptor_index
+        );
+        let comment = describe_member(constant_pool, method.name_index, method.descriptor_index);
+        output.push_str(&with_comment(line, Some(comment)));
+        for attribute in &method.attributes {
+            output.push_str(&format_attribute(attribute, constant_pool));
+        }
+        output.push_str(".end method\n");
+    }
+
+    if !classfile.attributes.is_empty() {
+        output.push('\n');
+    }
+    for attribute in &classfile.attributes {
+        output.push_str(&format_attribute(attribute, constant_pool));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::verify_class;
+
+    fn round_trip(data: &[u8]) {
+        let text = disassemble(data).unwrap();
+        assert_eq!(assemble(&text).unwrap().to_bytes().unwrap(), data);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(include_bytes!("../../../../java/HelloWorld.class"));
+        round_trip(include_bytes!("../../../../java/Frames.class"));
+        round_trip(include_bytes!("../../../../java/Switches.class"));
+        round_trip(include_bytes!("../../../../java/Constants.class"));
+        round_trip(include_bytes!("../../../../java/Annotated.class"));
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let text = disassemble(include_bytes!("../../../../java/HelloWorld.class")).unwrap();
+        assert!(text.starts_with(".version 65 0\n"));
+        assert!(text.contains(
+            ".const #7 = String #8                   // \"Hello, World!\"\n\
+             .const #8 = Utf8 \"Hello, World!\"\n"
+        ));
+        assert!(text.contains(
+            "\n.method 0x0009 #33 #34                  // main:([Ljava/lang/String;)V\n\
+             .code 2 1\n\
+             \x20   new #10                             // HelloWorld\n"
+        ));
+        assert!(text.ends_with(".attribute #35 0024                     // SourceFile\n"));
+
+        let text = disassemble(include_bytes!("../../../../java/Frames.class")).unwrap();
+        assert!(text.contains(
+            ".catch L0 L4 L5 #19                     // java/lang/NumberFormatException\n"
+        ));
+    }
+
+    #[test]
+    fn test_assemble() {
+        let text = r#"
+            .const #1 = Utf8 "Loop"
+            .const #2 = Class #1
+            .const #3 = Utf8 "java/lang/Object"
+            .const #4 = Class #3
+            .const #5 = Utf8 "count"
+            .const #6 = Utf8 "(I)I"
+            .const #7 = Utf8 "Code"
+            .const #8 = Long -1
+            .const #10 = Float 0x7fc00001
+            .const #11 = Utf8 "tab\there \"quoted\" \u0000 // not a comment"
+            .const #12 = Utf8 0xeda080
+            .const #13 = Utf8 "java/lang/Exception"
+            .const #14 = Class #13
+
+            .class 0x0021 #2                    // Loop
+            .super #4
+            .method 0x0009 #5 #6
+            .code                               // max_stack and max_locals computed
+                iconst_0
+                istore_1
+            start:
+                goto check
+            loop:
+                iinc 1, 1
+            check: iload_1
+                iload_0
+                if_icmplt loop
+                iload_1
+            end:
+                ireturn
+            handler:
+                pop
+                iconst_m1
+                ireturn
+            .catch start end handler #14
+            .end code
+            .end method
+        "#;
+        let classfile = assemble(text).unwrap();
+        // without a .version, before StackMapTables
+        assert_eq!((classfile.major_version, classfile.minor_version), (49, 0));
+        match classfile.constant_pool.get(10) {
+            Some(Constant::Float { value }) => assert_eq!(value.to_bits(), 0x7fc00001),
+            constant => panic!("expected a Float, got {constant:?}"),
+        }
+        assert_eq!(
+            classfile.constant_pool.utf8(11).unwrap(),
+            "tab\there \"quoted\" \0 // not a comment"
+        );
+        assert_eq!(
+            classfile.constant_pool.get(12),
+            Some(&Constant::Utf8 {
+                value: Cow::Borrowed(&[0xed, 0xa0, 0x80])
+            })
+        );
+        let code = classfile.methods[0].code().unwrap();
+        assert_eq!((code.max_stack, code.max_locals), (2, 2));
+        assert_eq!(
+            code.exception_table,
+            vec![ExceptionTableEntry {
+                start_pc: 2,
+                end_pc: 14,
+                handler_pc: 15,
+                catch_type: 14,
+            }]
+        );
+        let data = classfile.to_bytes().unwrap();
+        assert_eq!(verify_class(&data), vec![]);
+        round_trip(&data);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            assemble(".class 0x0021 #1\n.frobnicate"),
+            Err(ClassTextError::UnknownDirective {
+                line: 2,
+                directive: ".frobnicate".to_string()
+            })
+        );
+        assert_eq!(
+            assemble(".const #2 = Integer 1"),
+            Err(ClassTextError::ConstantOutOfOrder { line: 1, index: 2 })
+        );
+        assert_eq!(
+            assemble(".const #1 = Integer x"),
+            Err(ClassTextError::InvalidOperand {
+                line: 1,
+                operand: "x".to_string()
+            })
+        );
+        assert_eq!(
+            assemble(".const #1 = Utf8 \"open"),
+            Err(ClassTextError::InvalidOperand {
+                line: 1,
+                operand: ".const #1 = Utf8 \"open".to_string()
+            })
+        );
+        assert_eq!(
+            assemble(".class 0x0021"),
+            Err(ClassTextError::MissingOperand { line: 1 })
+        );
+        assert_eq!(
+            assemble(".super #1 #2"),
+            Err(ClassTextError::UnexpectedOperand {
+                line: 1,
+                operand: "#2".to_string()
+            })
+        );
+        assert_eq!(
+            assemble(".method 1 #1 #2\n.end field"),
+            Err(ClassTextError::Misplaced {
+                line: 2,
+                directive: ".end field".to_string()
+            })
+        );
+        assert_eq!(
+            assemble("return"),
+            Err(ClassTextError::Misplaced {
+                line: 1,
+                directive: "return".to_string()
+            })
+        );
+        assert_eq!(
+            assemble(".method 1 #1 #2\n.code 1 1\nreturn"),
+            Err(ClassTextError::Unclosed { line: 2 })
+        );
+        assert_eq!(
+            assemble(".method 1 #1 #2\n.code 1 1\nnop\nfrobnicate\n.end code"),
+            Err(ClassTextError::Code(TextError::UnknownMnemonic {
+                line: 4,
+                mnemonic: "frobnicate".to_string()
+            }))
+        );
+        assert_eq!(
+            assemble(".method 1 #1 #2\n.code 1 1\nreturn\n.catch a b c #0\n.end code"),
+            Err(ClassTextError::UndefinedLabel {
+                line: 4,
+                label: "a".to_string()
+            })
+        );
+        assert_eq!(
+            assemble(".attribute #1"),
+            Err(ClassTextError::InvalidOperand {
+                line: 1,
+                operand: "#1".to_string()
+            })
+        );
+        assert_eq!(assemble(""), Err(ClassTextError::MissingClass));
+    }
+}
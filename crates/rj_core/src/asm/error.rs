@@ -1,9 +1,49 @@
+use std::fmt;
+
+use super::builder::Label;
+use super::frames::FrameError;
+use super::instruction::Instruction;
+use crate::class::{ConstantPoolBuildError, ConstantPoolError};
 use crate::parser;
 
 #[derive(Debug, PartialEq)]
 pub enum InstructionParseError {
     ParseError(parser::ParseError),
     UnknownInstruction(u8),
+    InvalidSwitchBounds(i32, i32),
+    InvalidLookupswitchPairs(i32),
+    // a reserved operand byte of invokeinterface or invokedynamic, by the
+    // opcode, that isn't zero
+    NonZeroPadding(u8),
+    // a switch with more entries than `ParseLimits::max_switch_entries`
+    SwitchTooLarge(u64),
+    // a code array with more instructions than `ParseLimits::max_instructions`
+    TooManyInstructions(u32),
+    // an error raised while walking a code array, with the offset it occurred at,
+    // the opcode found there and the (pc, opcode) of the instruction before it
+    AtOffset {
+        pc: u32,
+        opcode: u8,
+        previous: Option<(u32, u8)>,
+        error: Box<InstructionParseError>,
+    },
+}
+
+impl InstructionParseError {
+    pub fn pc(&self) -> Option<u32> {
+        match self {
+            InstructionParseError::AtOffset { pc, .. } => Some(*pc),
+            _ => None,
+        }
+    }
+
+    // the underlying error without any offset context
+    pub fn root(&self) -> &InstructionParseError {
+        match self {
+            InstructionParseError::AtOffset { error, .. } => error.root(),
+            error => error,
+        }
+    }
 }
 
 impl From<parser::ParseError> for InstructionParseError {
@@ -11,3 +51,218 @@ impl From<parser::ParseError> for InstructionParseError {
         InstructionParseError::ParseError(error)
     }
 }
+
+#[derive(Debug, PartialEq)]
+pub enum StackEffectError {
+    InvalidConstantPoolIndex(u16),
+    InvalidDescriptor(u16),
+    ConstantPoolError(ConstantPoolError),
+}
+
+impl From<ConstantPoolError> for StackEffectError {
+    fn from(error: ConstantPoolError) -> Self {
+        StackEffectError::ConstantPoolError(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MaxStackError {
+    StackEffectError(StackEffectError),
+    // an instruction popping more than the stack holds, by pc
+    StackUnderflow(u32),
+    // two paths reaching the same pc with different stack heights
+    InconsistentStackHeight(u32),
+    InvalidBranchTarget(u32),
+    // the last instruction continues past the end of the code
+    FallsOffEnd(u32),
+}
+
+impl From<StackEffectError> for MaxStackError {
+    fn from(error: StackEffectError) -> Self {
+        MaxStackError::StackEffectError(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ResolveError {
+    InvalidConstantPoolIndex(u16),
+    // a constant of a kind the instruction can't take, like a Utf8 for an ldc
+    UnexpectedConstant(u16),
+    ConstantPoolError(ConstantPoolError),
+}
+
+impl From<ConstantPoolError> for ResolveError {
+    fn from(error: ConstantPoolError) -> Self {
+        ResolveError::ConstantPoolError(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RelocationError {
+    InvalidBranchTarget(u32),
+    InvalidEditRange(u32, u32),
+    BranchIntoDeletedCode(u32),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CodeBuildError {
+    UnboundLabel(Label),
+    LabelAlreadyBound(Label),
+    NotABranch(Instruction),
+    InvalidSwitch,
+    CodeTooLarge(u32),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ClassBuildError {
+    ConstantPoolBuildError(ConstantPoolBuildError),
+    CodeBuildError(CodeBuildError),
+    MaxStackError(MaxStackError),
+    FrameError(FrameError),
+    InvalidMethodDescriptor(String),
+    // an accessor for a field the builder wasn't given
+    UnknownField(String),
+}
+
+impl From<ConstantPoolBuildError> for ClassBuildError {
+    fn from(error: ConstantPoolBuildError) -> Self {
+        ClassBuildError::ConstantPoolBuildError(error)
+    }
+}
+
+impl From<CodeBuildError> for ClassBuildError {
+    fn from(error: CodeBuildError) -> Self {
+        ClassBuildError::CodeBuildError(error)
+    }
+}
+
+impl From<MaxStackError> for ClassBuildError {
+    fn from(error: MaxStackError) -> Self {
+        ClassBuildError::MaxStackError(error)
+    }
+}
+
+impl From<FrameError> for ClassBuildError {
+    fn from(error: FrameError) -> Self {
+        ClassBuildError::FrameError(error)
+    }
+}
+
+impl fmt::Display for InstructionParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InstructionParseError::ParseError(error) => write!(f, "{error}"),
+            InstructionParseError::UnknownInstruction(opcode) => {
+                write!(f, "unknown opcode {opcode:#04x}")
+            }
+            InstructionParseError::InvalidSwitchBounds(low, high) => {
+                write!(f, "tableswitch low {low} is greater than high {high}")
+            }
+            InstructionParseError::InvalidLookupswitchPairs(count) => {
+                write!(f, "lookupswitch with {count} pairs")
+            }
+            InstructionParseError::NonZeroPadding(opcode) => write!(
+                f,
+                "non-zero reserved operand byte in {opcode:#04x} (invokeinterface/invokedynamic)"
+            ),
+            InstructionParseError::SwitchTooLarge(entries) => {
+                write!(f, "switch with {entries} entries, over the limit")
+            }
+            InstructionParseError::TooManyInstructions(count) => {
+                write!(f, "more than {count} instructions")
+            }
+            InstructionParseError::AtOffset {
+                pc, opcode, error, ..
+            } => write!(f, "pc {pc}, opcode {opcode:#04x}: {error}"),
+        }
+    }
+}
+
+impl fmt::Display for StackEffectError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StackEffectError::InvalidConstantPoolIndex(index) => {
+                write!(f, "invalid constant pool index #{index}")
+            }
+            StackEffectError::InvalidDescriptor(index) => {
+                write!(f, "invalid descriptor in #{index}")
+            }
+            StackEffectError::ConstantPoolError(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl fmt::Display for MaxStackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MaxStackError::StackEffectError(error) => write!(f, "{error}"),
+            MaxStackError::StackUnderflow(pc) => write!(f, "stack underflow at pc {pc}"),
+            MaxStackError::InconsistentStackHeight(pc) => {
+                write!(f, "inconsistent stack height at pc {pc}")
+            }
+            MaxStackError::InvalidBranchTarget(pc) => {
+                write!(f, "invalid branch target from pc {pc}")
+            }
+            MaxStackError::FallsOffEnd(pc) => write!(f, "code falls off the end at pc {pc}"),
+        }
+    }
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolveError::InvalidConstantPoolIndex(index) => {
+                write!(f, "invalid constant pool index #{index}")
+            }
+            ResolveError::UnexpectedConstant(index) => write!(f, "unexpected constant #{index}"),
+            ResolveError::ConstantPoolError(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl fmt::Display for RelocationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RelocationError::InvalidBranchTarget(pc) => {
+                write!(f, "invalid branch target from pc {pc}")
+            }
+            RelocationError::InvalidEditRange(start, end) => {
+                write!(f, "invalid edit range {start}..{end}")
+            }
+            RelocationError::BranchIntoDeletedCode(pc) => {
+                write!(f, "branch into deleted code at pc {pc}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for CodeBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodeBuildError::UnboundLabel(label) => write!(f, "{label:?} is never bound"),
+            CodeBuildError::LabelAlreadyBound(label) => write!(f, "{label:?} is bound twice"),
+            CodeBuildError::NotABranch(instruction) => {
+                write!(f, "{instruction:?} isn't a branch")
+            }
+            CodeBuildError::InvalidSwitch => write!(f, "invalid switch"),
+            CodeBuildError::CodeTooLarge(length) => {
+                write!(f, "{length} bytes of code, over 65535")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ClassBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClassBuildError::ConstantPoolBuildError(error) => write!(f, "{error}"),
+            ClassBuildError::CodeBuildError(error) => write!(f, "{error}"),
+            ClassBuildError::MaxStackError(error) => write!(f, "{error}"),
+            ClassBuildError::FrameError(error) => write!(f, "{error}"),
+            ClassBuildError::InvalidMethodDescriptor(descriptor) => {
+                write!(f, "invalid method descriptor {descriptor}")
+            }
+            ClassBuildError::UnknownField(name) => write!(f, "unknown field {name}"),
+        }
+    }
+}
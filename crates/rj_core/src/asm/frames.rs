@@ -0,0 +1,1429 @@
+// Stack map frame computation
+// https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-4.html#jvms-4.10.1
+
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use super::error::InstructionParseError;
+use super::instruction::{disassemble, Instruction};
+use crate::class::{
+    encode_modified_utf8, parse_field_type, Attribute, AttributeName, ClassFile, Code, Constant,
+    ConstantPool, ConstantPoolError, FieldType, MethodAccessFlags, MethodDescriptor, StackMapFrame,
+    StackMapTable, SymbolicRef, VerificationTypeInfo,
+};
+
+const OBJECT: &str = "java/lang/Object";
+const THROWABLE: &str = "java/lang/Throwable";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameType {
+    Top,
+    Integer,
+    Float,
+    Long,
+    Double,
+    Null,
+    UninitializedThis,
+    Object(String),
+    Uninitialized(u16),
+}
+
+impl FrameType {
+    fn object(name: &str) -> Self {
+        FrameType::Object(name.to_string())
+    }
+
+    fn is_category2(&self) -> bool {
+        matches!(self, FrameType::Long | FrameType::Double)
+    }
+
+    fn from_field_type(field_type: &FieldType) -> Option<Self> {
+        match field_type {
+            FieldType::Byte
+            | FieldType::Char
+            | FieldType::Int
+            | FieldType::Short
+            | FieldType::Boolean => Some(FrameType::Integer),
+            FieldType::Float => Some(FrameType::Float),
+            FieldType::Long => Some(FrameType::Long),
+            FieldType::Double => Some(FrameType::Double),
+            FieldType::Object(name) => Some(FrameType::Object(
+                String::from_utf8_lossy(name).into_owned(),
+            )),
+            FieldType::Array(_) => Some(FrameType::Object(field_type.write())),
+            FieldType::Void => None,
+        }
+    }
+
+    fn from_descriptor(value: &[u8]) -> Result<Self, FrameError> {
+        match parse_field_type(value) {
+            Ok((b"", field_type)) => {
+                FrameType::from_field_type(&field_type).ok_or(FrameError::InvalidDescriptor)
+            }
+            _ => Err(FrameError::InvalidDescriptor),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub pc: u32,
+    pub locals: Vec<FrameType>,
+    pub stack: Vec<FrameType>,
+}
+
+pub trait ClassHierarchy {
+    fn super_class(&self, name: &str) -> Option<String>;
+    fn is_interface(&self, name: &str) -> bool;
+}
+
+#[derive(Debug, Default)]
+pub struct SimpleClassHierarchy {
+    classes: HashMap<String, (Option<String>, bool)>,
+}
+
+impl SimpleClassHierarchy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_class(&mut self, name: &str, super_class: Option<&str>, is_interface: bool) {
+        self.classes.insert(
+            name.to_string(),
+            (super_class.map(|s| s.to_string()), is_interface),
+        );
+    }
+}
+
+impl ClassHierarchy for SimpleClassHierarchy {
+    fn super_class(&self, name: &str) -> Option<String> {
+        match self.classes.get(name) {
+            Some((super_class, _)) => super_class.clone(),
+            None if name == OBJECT => None,
+            None => Some(OBJECT.to_string()),
+        }
+    }
+
+    fn is_interface(&self, name: &str) -> bool {
+        matches!(self.classes.get(name), Some((_, true)))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FrameError {
+    InstructionParseError(InstructionParseError),
+    ConstantPoolError(ConstantPoolError),
+    InvalidConstantPoolIndex(u16),
+    InvalidDescriptor,
+    InvalidLocal(u32),
+    InvalidBranchTarget(u32),
+    StackUnderflow(u32),
+    StackHeightMismatch(u32),
+    IncompatibleStack(u32),
+    UnreachableCode(u32),
+    UnsupportedInstruction(u32),
+    MissingClass(String),
+}
+
+impl From<InstructionParseError> for FrameError {
+    fn from(error: InstructionParseError) -> Self {
+        FrameError::InstructionParseError(error)
+    }
+}
+
+impl From<ConstantPoolError> for FrameError {
+    fn from(error: ConstantPoolError) -> Self {
+        FrameError::ConstantPoolError(error)
+    }
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FrameError::InstructionParseError(error) => write!(f, "{error}"),
+            FrameError::ConstantPoolError(error) => write!(f, "{error}"),
+            FrameError::InvalidConstantPoolIndex(index) => {
+                write!(f, "invalid constant pool index #{index}")
+            }
+            FrameError::InvalidDescriptor => write!(f, "invalid descriptor"),
+            FrameError::InvalidLocal(pc) => write!(f, "invalid local at pc {pc}"),
+            FrameError::InvalidBranchTarget(pc) => {
+                write!(f, "invalid branch target from pc {pc}")
+            }
+            FrameError::StackUnderflow(pc) => write!(f, "stack underflow at pc {pc}"),
+            FrameError::StackHeightMismatch(pc) => {
+                write!(f, "inconsistent stack height at pc {pc}")
+            }
+            FrameError::IncompatibleStack(pc) => write!(f, "incompatible stack at pc {pc}"),
+            FrameError::UnreachableCode(pc) => write!(f, "unreachable code at pc {pc}"),
+            FrameError::UnsupportedInstruction(pc) => {
+                write!(f, "unsupported instruction at pc {pc}")
+            }
+            FrameError::MissingClass(name) => write!(f, "class {name} not found"),
+        }
+    }
+}
+
+pub struct FrameContext<'a> {
+    pub constant_pool: &'a ConstantPool<'a>,
+    pub this_class: &'a str,
+    pub method_name: &'a str,
+    pub descriptor: &'a MethodDescriptor<'a>,
+    pub is_static: bool,
+    pub hierarchy: &'a dyn ClassHierarchy,
+}
+
+// a CONSTANT_Class name is either an internal name or an array descriptor
+fn class_descriptor(name: &str) -> String {
+    if name.starts_with('[') {
+        name.to_string()
+    } else {
+        format!("L{name};")
+    }
+}
+
+fn component_type(name: &str) -> FrameType {
+    match name.strip_prefix('[') {
+        Some(component) => {
+            FrameType::from_descriptor(component.as_bytes()).unwrap_or(FrameType::object(OBJECT))
+        }
+        None => FrameType::object(OBJECT),
+    }
+}
+
+fn common_super_class(a: &str, b: &str, hierarchy: &dyn ClassHierarchy) -> String {
+    if a == b {
+        return a.to_string();
+    }
+    if a.starts_with('[') || b.starts_with('[') {
+        let reference_component = |name: &str| match name.strip_prefix('[') {
+            Some(component) => match FrameType::from_descriptor(component.as_bytes()) {
+                Ok(FrameType::Object(name)) => Some(name),
+                _ => None,
+            },
+            None => None,
+        };
+        return match (reference_component(a), reference_component(b)) {
+            (Some(a), Some(b)) => format!(
+                "[{}",
+                class_descriptor(&common_super_class(&a, &b, hierarchy))
+            ),
+            _ => OBJECT.to_string(),
+        };
+    }
+    if hierarchy.is_interface(a) || hierarchy.is_interface(b) {
+        return OBJECT.to_string();
+    }
+    let mut ancestors = HashSet::new();
+    let mut current = Some(a.to_string());
+    while let Some(name) = current {
+        if !ancestors.insert(name.clone()) {
+            break;
+        }
+        current = hierarchy.super_class(&name);
+    }
+    let mut visited = HashSet::new();
+    let mut current = Some(b.to_string());
+    while let Some(name) = current {
+        if ancestors.contains(&name) {
+            return name;
+        }
+        if !visited.insert(name.clone()) {
+            break;
+        }
+        current = hierarchy.super_class(&name);
+    }
+    OBJECT.to_string()
+}
+
+fn merge_type(a: &FrameType, b: &FrameType, hierarchy: &dyn ClassHierarchy) -> FrameType {
+    match (a, b) {
+        _ if a == b => a.clone(),
+        (FrameType::Null, FrameType::Object(_)) => b.clone(),
+        (FrameType::Object(_), FrameType::Null) => a.clone(),
+        (FrameType::Object(a), FrameType::Object(b)) => {
+            FrameType::Object(common_super_class(a, b, hierarchy))
+        }
+        _ => FrameType::Top,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    locals: Vec<FrameType>,
+    stack: Vec<FrameType>,
+}
+
+impl State {
+    fn push(&mut self, value: FrameType) {
+        self.stack.push(value);
+    }
+
+    fn pop(&mut self, pc: u32) -> Result<FrameType, FrameError> {
+        self.stack.pop().ok_or(FrameError::StackUnderflow(pc))
+    }
+
+    fn pop_n(&mut self, n: usize, pc: u32) -> Result<(), FrameError> {
+        for _ in 0..n {
+            self.pop(pc)?;
+        }
+        Ok(())
+    }
+
+    // pops values occupying exactly `words` stack words, returned bottom to top
+    fn pop_words(&mut self, words: usize, pc: u32) -> Result<Vec<FrameType>, FrameError> {
+        let mut values = Vec::new();
+        let mut count = 0;
+        while count < words {
+            let value = self.pop(pc)?;
+            count += if value.is_category2() { 2 } else { 1 };
+            values.insert(0, value);
+        }
+        if count != words {
+            return Err(FrameError::IncompatibleStack(pc));
+        }
+        Ok(values)
+    }
+
+    fn push_all(&mut self, values: &[FrameType]) {
+        self.stack.extend_from_slice(values);
+    }
+
+    fn load(&mut self, index: usize, pc: u32) -> Result<FrameType, FrameError> {
+        self.locals
+            .get(index)
+            .cloned()
+            .ok_or(FrameError::InvalidLocal(pc))
+    }
+
+    fn store(&mut self, index: usize, value: FrameType, pc: u32) -> Result<(), FrameError> {
+        let size = if value.is_category2() { 2 } else { 1 };
+        if index + size > self.locals.len() {
+            return Err(FrameError::InvalidLocal(pc));
+        }
+        if index > 0 && self.locals[index - 1].is_category2() {
+            self.locals[index - 1] = FrameType::Top;
+        }
+        self.locals[index] = value;
+        if size == 2 {
+            self.locals[index + 1] = FrameType::Top;
+        }
+        Ok(())
+    }
+
+    fn merge(
+        &mut self,
+        other: &State,
+        pc: u32,
+        hierarchy: &dyn ClassHierarchy,
+    ) -> Result<bool, FrameError> {
+        if self.stack.len() != other.stack.len() {
+            return Err(FrameError::StackHeightMismatch(pc));
+        }
+        let mut changed = false;
+        for (current, incoming) in self.locals.iter_mut().zip(&other.locals) {
+            let merged = merge_type(current, incoming, hierarchy);
+            if merged != *current {
+                *current = merged;
+                changed = true;
+            }
+        }
+        for (current, incoming) in self.stack.iter_mut().zip(&other.stack) {
+            let merged = merge_type(current, incoming, hierarchy);
+            if merged == FrameType::Top {
+                return Err(FrameError::IncompatibleStack(pc));
+            }
+            if merged != *current {
+                *current = merged;
+                changed = true;
+            }
+        }
+        Ok(changed)
+    }
+
+    fn to_frame(&self, pc: u32) -> Frame {
+        let mut locals = Vec::new();
+        let mut slots = self.locals.iter();
+        while let Some(local) = slots.next() {
+            if local.is_category2() {
+                slots.next();
+            }
+            locals.push(local.clone());
+        }
+        while locals.last() == Some(&FrameType::Top) {
+            locals.pop();
+        }
+        Frame {
+            pc,
+            locals,
+            stack: self.stack.clone(),
+        }
+    }
+}
+
+fn get_constant<'a, 'b>(
+    index: u16,
+    constant_pool: &'b ConstantPool<'a>,
+) -> Result<&'b Constant<'a>, FrameError> {
+    constant_pool
+        .get(index)
+        .ok_or(FrameError::InvalidConstantPoolIndex(index))
+}
+
+fn get_class_name(index: u16, constant_pool: &ConstantPool) -> Result<String, FrameError> {
+    Ok(constant_pool.class_name(index)?.into_owned())
+}
+
+// a Methodref, or an InterfaceMethodref for invokeinterface and interface
+// methods named by invokestatic and invokespecial
+fn get_method_ref<'a>(
+    index: u16,
+    constant_pool: &'a ConstantPool,
+) -> Result<SymbolicRef<'a>, FrameError> {
+    match get_constant(index, constant_pool)? {
+        Constant::InterfaceMethodref { .. } => Ok(constant_pool.interface_method_ref(index)?),
+        _ => Ok(constant_pool.method_ref(index)?),
+    }
+}
+
+fn get_dynamic_descriptor<'a>(
+    index: u16,
+    constant_pool: &'a ConstantPool,
+) -> Result<Cow<'a, str>, FrameError> {
+    match get_constant(index, constant_pool)? {
+        Constant::Dynamic {
+            name_and_type_index,
+            ..
+        }
+        | Constant::InvokeDynamic {
+            name_and_type_index,
+            ..
+        } => Ok(constant_pool
+            .name_and_type(*name_and_type_index)?
+            .descriptor),
+        _ => Err(FrameError::InvalidConstantPoolIndex(index)),
+    }
+}
+
+fn ldc_type(index: u16, constant_pool: &ConstantPool) -> Result<FrameType, FrameError> {
+    match get_constant(index, constant_pool)? {
+        Constant::Integer { .. } => Ok(FrameType::Integer),
+        Constant::Float { .. } => Ok(FrameType::Float),
+        Constant::Long { .. } => Ok(FrameType::Long),
+        Constant::Double { .. } => Ok(FrameType::Double),
+        Constant::String { .. } => Ok(FrameType::object("java/lang/String")),
+        Constant::Class { .. } => Ok(FrameType::object("java/lang/Class")),
+        Constant::MethodType { .. } => Ok(FrameType::object("java/lang/invoke/MethodType")),
+        Constant::MethodHandle { .. } => Ok(FrameType::object("java/lang/invoke/MethodHandle")),
+        Constant::Dynamic { .. } => {
+            FrameType::from_descriptor(get_dynamic_descriptor(index, constant_pool)?.as_bytes())
+        }
+        _ => Err(FrameError::InvalidConstantPoolIndex(index)),
+    }
+}
+
+fn parse_descriptor(value: &[u8]) -> Result<MethodDescriptor, FrameError> {
+    MethodDescriptor::parse(value).map_err(|_| FrameError::InvalidDescriptor)
+}
+
+fn invoke(
+    state: &mut State,
+    pc: u32,
+    index: u16,
+    has_receiver: bool,
+    context: &FrameContext,
+) -> Result<(), FrameError> {
+    let reference = get_method_ref(index, context.constant_pool)?;
+    let descriptor = parse_descriptor(reference.descriptor.as_bytes())?;
+    state.pop_n(descriptor.parameters.len(), pc)?;
+    if has_receiver {
+        let receiver = state.pop(pc)?;
+        if reference.name == "<init>" {
+            let initialized = match receiver {
+                FrameType::UninitializedThis => FrameType::object(context.this_class),
+                FrameType::Uninitialized(_) => FrameType::Object(reference.class_name.into_owned()),
+                _ => return Err(FrameError::IncompatibleStack(pc)),
+            };
+            for value in state.locals.iter_mut().chain(state.stack.iter_mut()) {
+                if *value == receiver {
+                    *value = initialized.clone();
+                }
+            }
+        }
+    }
+    if let Some(return_type) = FrameType::from_field_type(&descriptor.return_type) {
+        state.push(return_type);
+    }
+    Ok(())
+}
+
+fn execute(
+    state: &mut State,
+    pc: u32,
+    instruction: &Instruction,
+    context: &FrameContext,
+) -> Result<(), FrameError> {
+    use FrameType::*;
+    use Instruction as I;
+
+    let constant_pool = context.constant_pool;
+    match instruction {
+        I::Nop => {}
+        I::AconstNull => state.push(Null),
+        I::IconstM1
+        | I::Iconst0
+        | I::Iconst1
+        | I::Iconst2
+        | I::Iconst3
+        | I::Iconst4
+        | I::Iconst5
+        | I::Bipush(_)
+        | I::Sipush(_) => state.push(Integer),
+        I::Lconst0 | I::Lconst1 => state.push(Long),
+        I::Fconst0 | I::Fconst1 | I::Fconst2 => state.push(Float),
+        I::Dconst0 | I::Dconst1 => state.push(Double),
+        I::Ldc(index) => state.push(ldc_type(*index as u16, constant_pool)?),
+        I::LdcW(index) | I::Ldc2W(index) => state.push(ldc_type(*index, constant_pool)?),
+
+        I::Iload(_) | I::Iload0 | I::Iload1 | I::Iload2 | I::Iload3 | I::WideIload(_) => {
+            state.push(Integer)
+        }
+        I::Lload(_) | I::Lload0 | I::Lload1 | I::Lload2 | I::Lload3 | I::WideLload(_) => {
+            state.push(Long)
+        }
+        I::Fload(_) | I::Fload0 | I::Fload1 | I::Fload2 | I::Fload3 | I::WideFload(_) => {
+            state.push(Float)
+        }
+        I::Dload(_) | I::Dload0 | I::Dload1 | I::Dload2 | I::Dload3 | I::WideDload(_) => {
+            state.push(Double)
+        }
+        I::Aload(index) => {
+            let value = state.load(*index as usize, pc)?;
+            state.push(value);
+        }
+        I::WideAload(index) => {
+            let value = state.load(*index as usize, pc)?;
+            state.push(value);
+        }
+        I::Aload0 | I::Aload1 | I::Aload2 | I::Aload3 => {
+            let index = match instruction {
+                I::Aload0 => 0,
+                I::Aload1 => 1,
+                I::Aload2 => 2,
+                _ => 3,
+            };
+            let value = state.load(index, pc)?;
+            state.push(value);
+        }
+
+        I::Istore(_)
+        | I::Istore0
+        | I::Istore1
+        | I::Istore2
+        | I::Istore3
+        | I::WideIstore(_)
+        | I::Lstore(_)
+        | I::Lstore0
+        | I::Lstore1
+        | I::Lstore2
+        | I::Lstore3
+        | I::WideLstore(_)
+        | I::Fstore(_)
+        | I::Fstore0
+        | I::Fstore1
+        | I::Fstore2
+        | I::Fstore3
+        | I::WideFstore(_)
+        | I::Dstore(_)
+        | I::Dstore0
+        | I::Dstore1
+        | I::Dstore2
+        | I::Dstore3
+        | I::WideDstore(_)
+        | I::Astore(_)
+        | I::Astore0
+        | I::Astore1
+        | I::Astore2
+        | I::Astore3
+        | I::WideAstore(_) => {
+            let index = match instruction {
+                I::Istore(index)
+                | I::Lstore(index)
+                | I::Fstore(index)
+                | I::Dstore(index)
+                | I::Astore(index) => *index as usize,
+                I::WideIstore(index)
+                | I::WideLstore(index)
+                | I::WideFstore(index)
+                | I::WideDstore(index)
+                | I::WideAstore(index) => *index as usize,
+                I::Istore0 | I::Lstore0 | I::Fstore0 | I::Dstore0 | I::Astore0 => 0,
+                I::Istore1 | I::Lstore1 | I::Fstore1 | I::Dstore1 | I::Astore1 => 1,
+                I::Istore2 | I::Lstore2 | I::Fstore2 | I::Dstore2 | I::Astore2 => 2,
+                _ => 3,
+            };
+            let value = state.pop(pc)?;
+            state.store(index, value, pc)?;
+        }
+        I::Iinc(_, _) | I::WideIinc(_, _) => {}
+
+        I::Iaload | I::Baload | I::Caload | I::Saload => {
+            state.pop_n(2, pc)?;
+            state.push(Integer);
+        }
+        I::Laload => {
+            state.pop_n(2, pc)?;
+            state.push(Long);
+        }
+        I::Faload => {
+            state.pop_n(2, pc)?;
+            state.push(Float);
+        }
+        I::Daload => {
+            state.pop_n(2, pc)?;
+            state.push(Double);
+        }
+        I::Aaload => {
+            state.pop(pc)?;
+            let value = match state.pop(pc)? {
+                Object(name) => component_type(&name),
+                _ => Null,
+            };
+            state.push(value);
+        }
+        I::Iastore
+        | I::Lastore
+        | I::Fastore
+        | I::Dastore
+        | I::Aastore
+        | I::Bastore
+        | I::Castore
+        | I::Sastore => state.pop_n(3, pc)?,
+
+        I::Pop => {
+            state.pop_words(1, pc)?;
+        }
+        I::Pop2 => {
+            state.pop_words(2, pc)?;
+        }
+        I::Dup => {
+            let a = state.pop_words(1, pc)?;
+            state.push_all(&a);
+            state.push_all(&a);
+        }
+        I::DupX1 | I::DupX2 | I::Dup2X1 | I::Dup2X2 => {
+            let (top, below) = match instruction {
+                I::DupX1 => (1, 1),
+                I::DupX2 => (1, 2),
+                I::Dup2X1 => (2, 1),
+                _ => (2, 2),
+            };
+            let a = state.pop_words(top, pc)?;
+            let b = state.pop_words(below, pc)?;
+            state.push_all(&a);
+            state.push_all(&b);
+            state.push_all(&a);
+        }
+        I::Dup2 => {
+            let a = state.pop_words(2, pc)?;
+            state.push_all(&a);
+            state.push_all(&a);
+        }
+        I::Swap => {
+            let a = state.pop_words(1, pc)?;
+            let b = state.pop_words(1, pc)?;
+            state.push_all(&a);
+            state.push_all(&b);
+        }
+
+        I::Iadd
+        | I::Isub
+        | I::Imul
+        | I::Idiv
+        | I::Irem
+        | I::Iand
+        | I::Ior
+        | I::Ixor
+        | I::Ishl
+        | I::Ishr
+        | I::Iushr
+        | I::Lcmp
+        | I::Fcmpl
+        | I::Fcmpg
+        | I::Dcmpl
+        | I::Dcmpg => {
+            state.pop_n(2, pc)?;
+            state.push(Integer);
+        }
+        I::Ladd
+        | I::Lsub
+        | I::Lmul
+        | I::Ldiv
+        | I::Lrem
+        | I::Land
+        | I::Lor
+        | I::Lxor
+        | I::Lshl
+        | I::Lshr
+        | I::Lushr => {
+            state.pop_n(2, pc)?;
+            state.push(Long);
+        }
+        I::Fadd | I::Fsub | I::Fmul | I::Fdiv | I::Frem => {
+            state.pop_n(2, pc)?;
+            state.push(Float);
+        }
+        I::Dadd | I::Dsub | I::Dmul | I::Ddiv | I::Drem => {
+            state.pop_n(2, pc)?;
+            state.push(Double);
+        }
+        I::Ineg
+        | I::L2i
+        | I::F2i
+        | I::D2i
+        | I::I2b
+        | I::I2c
+        | I::I2s
+        | I::Arraylength
+        | I::Instanceof(_) => {
+            state.pop(pc)?;
+            state.push(Integer);
+        }
+        I::Lneg | I::I2l | I::F2l | I::D2l => {
+            state.pop(pc)?;
+            state.push(Long);
+        }
+        I::Fneg | I::I2f | I::L2f | I::D2f => {
+            state.pop(pc)?;
+            state.push(Float);
+        }
+        I::Dneg | I::I2d | I::L2d | I::F2d => {
+            state.pop(pc)?;
+            state.push(Double);
+        }
+
+        I::Ifeq(_)
+        | I::Ifne(_)
+        | I::Iflt(_)
+        | I::Ifge(_)
+        | I::Ifgt(_)
+        | I::Ifle(_)
+        | I::Ifnull(_)
+        | I::Ifnonnull(_)
+        | I::Tableswitch(..)
+        | I::Lookupswitch(..)
+        | I::Ireturn
+        | I::Lreturn
+        | I::Freturn
+        | I::Dreturn
+        | I::Areturn
+        | I::Athrow
+        | I::Monitorenter
+        | I::Monitorexit
+        | I::Putstatic(_) => {
+            state.pop(pc)?;
+        }
+        I::IfIcmpeq(_)
+        | I::IfIcmpne(_)
+        | I::IfIcmplt(_)
+        | I::IfIcmpge(_)
+        | I::IfIcmpgt(_)
+        | I::IfIcmple(_)
+        | I::IfAcmpeq(_)
+        | I::IfAcmpne(_)
+        | I::Putfield(_) => state.pop_n(2, pc)?,
+        I::Goto(_) | I::GotoW(_) | I::Return => {}
+        I::Jsr(_) | I::JsrW(_) | I::Ret(_) | I::WideRet(_) => {
+            return Err(FrameError::UnsupportedInstruction(pc));
+        }
+
+        I::Getstatic(index) => {
+            let descriptor = constant_pool.field_ref(*index)?.descriptor;
+            state.push(FrameType::from_descriptor(descriptor.as_bytes())?);
+        }
+        I::Getfield(index) => {
+            let descriptor = constant_pool.field_ref(*index)?.descriptor;
+            state.pop(pc)?;
+            state.push(FrameType::from_descriptor(descriptor.as_bytes())?);
+        }
+        I::Invokevirtual(index) | I::Invokespecial(index) | I::Invokeinterface(index, _, _) => {
+            invoke(state, pc, *index, true, context)?;
+        }
+        I::Invokestatic(index) => invoke(state, pc, *index, false, context)?,
+        I::Invokedynamic(index, _, _) => {
+            let descriptor = get_dynamic_descriptor(*index, constant_pool)?;
+            let descriptor = parse_descriptor(descriptor.as_bytes())?;
+            state.pop_n(descriptor.parameters.len(), pc)?;
+            if let Some(return_type) = FrameType::from_field_type(&descriptor.return_type) {
+                state.push(return_type);
+            }
+        }
+
+        I::New(_) => state.push(Uninitialized(pc as u16)),
+        I::Newarray(atype) => {
+            state.pop(pc)?;
+            let name = match atype {
+                4 => "[Z",
+                5 => "[C",
+                6 => "[F",
+                7 => "[D",
+                8 => "[B",
+                9 => "[S",
+                10 => "[I",
+                11 => "[J",
+                _ => return Err(FrameError::IncompatibleStack(pc)),
+            };
+            state.push(FrameType::object(name));
+        }
+        I::Anewarray(index) => {
+            state.pop(pc)?;
+            let name = get_class_name(*index, constant_pool)?;
+            state.push(Object(format!("[{}", class_descriptor(&name))));
+        }
+        I::Multianewarray(index, dimensions) => {
+            state.pop_n(*dimensions as usize, pc)?;
+            state.push(Object(get_class_name(*index, constant_pool)?));
+        }
+        I::Checkcast(index) => {
+            state.pop(pc)?;
+            state.push(Object(get_class_name(*index, constant_pool)?));
+        }
+    }
+    Ok(())
+}
+
+fn is_unconditional(instruction: &Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::Goto(_)
+            | Instruction::GotoW(_)
+            | Instruction::Tableswitch(..)
+            | Instruction::Lookupswitch(..)
+            | Instruction::Ireturn
+            | Instruction::Lreturn
+            | Instruction::Freturn
+            | Instruction::Dreturn
+            | Instruction::Areturn
+            | Instruction::Return
+            | Instruction::Athrow
+    )
+}
+
+fn initial_state(max_locals: u16, context: &FrameContext) -> State {
+    let mut locals = Vec::new();
+    if !context.is_static {
+        if context.method_name == "<init>" && context.this_class != OBJECT {
+            locals.push(FrameType::UninitializedThis);
+        } else {
+            locals.push(FrameType::object(context.this_class));
+        }
+    }
+    for parameter in &context.descriptor.parameters {
+        if let Some(value) = FrameType::from_field_type(parameter) {
+            let category2 = value.is_category2();
+            locals.push(value);
+            if category2 {
+                locals.push(FrameType::Top);
+            }
+        }
+    }
+    while locals.len() < max_locals as usize {
+        locals.push(FrameType::Top);
+    }
+    State {
+        locals,
+        stack: vec![],
+    }
+}
+
+struct Worklist {
+    states: Vec<Option<State>>,
+    queue: VecDeque<usize>,
+    queued: Vec<bool>,
+}
+
+impl Worklist {
+    fn propagate(
+        &mut self,
+        target: usize,
+        state: &State,
+        pc: u32,
+        context: &FrameContext,
+    ) -> Result<(), FrameError> {
+        let changed = match &mut self.states[target] {
+            Some(current) => current.merge(state, pc, context.hierarchy)?,
+            None => {
+                self.states[target] = Some(state.clone());
+                true
+            }
+        };
+        if changed && !self.queued[target] {
+            self.queued[target] = true;
+            self.queue.push_back(target);
+        }
+        Ok(())
+    }
+}
+
+pub fn analyze<A>(code: &Code<A>, context: &FrameContext) -> Result<Vec<Frame>, FrameError> {
+    let instructions = disassemble(&code.code).collect::<Result<Vec<_>, _>>()?;
+    let index_of: HashMap<u32, usize> = instructions
+        .iter()
+        .enumerate()
+        .map(|(index, (pc, _))| (*pc, index))
+        .collect();
+    let target_index = |pc: u32, target: i64| -> Result<usize, FrameError> {
+        u32::try_from(target)
+            .ok()
+            .and_then(|target| index_of.get(&target).copied())
+            .ok_or(FrameError::InvalidBranchTarget(pc))
+    };
+
+    let mut frame_pcs = BTreeSet::new();
+    let mut handlers = Vec::new();
+    for entry in &code.exception_table {
+        let handler = target_index(entry.handler_pc as u32, entry.handler_pc as i64)?;
+        let catch_type = match entry.catch_type {
+            0 => THROWABLE.to_string(),
+            index => get_class_name(index, context.constant_pool)?,
+        };
+        handlers.push((
+            entry.start_pc as u32,
+            entry.end_pc as u32,
+            handler,
+            catch_type,
+        ));
+        frame_pcs.insert(entry.handler_pc as u32);
+    }
+    for (index, (pc, instruction)) in instructions.iter().enumerate() {
+        for target in instruction.branch_targets(*pc) {
+            let target = target_index(*pc, target)?;
+            frame_pcs.insert(instructions[target].0);
+        }
+        if is_unconditional(instruction) {
+            if let Some((next_pc, _)) = instructions.get(index + 1) {
+                frame_pcs.insert(*next_pc);
+            }
+        }
+    }
+
+    let mut worklist = Worklist {
+        states: vec![None; instructions.len()],
+        queue: VecDeque::new(),
+        queued: vec![false; instructions.len()],
+    };
+    if !instructions.is_empty() {
+        worklist.propagate(0, &initial_state(code.max_locals, context), 0, context)?;
+    }
+
+    while let Some(index) = worklist.queue.pop_front() {
+        worklist.queued[index] = false;
+        let (pc, instruction) = &instructions[index];
+        let input = worklist.states[index]
+            .clone()
+            .ok_or(FrameError::UnreachableCode(*pc))?;
+
+        for (start_pc, end_pc, handler, catch_type) in &handlers {
+            if *start_pc <= *pc && *pc < *end_pc {
+                let state = State {
+                    locals: input.locals.clone(),
+                    stack: vec![FrameType::Object(catch_type.clone())],
+                };
+                worklist.propagate(*handler, &state, instructions[*handler].0, context)?;
+            }
+        }
+
+        let mut output = input;
+        execute(&mut output, *pc, instruction, context)?;
+        for target in instruction.branch_targets(*pc) {
+            let target = target_index(*pc, target)?;
+            worklist.propagate(target, &output, instructions[target].0, context)?;
+        }
+        if !is_unconditional(instruction) {
+            let next_pc = instructions
+                .get(index + 1)
+                .ok_or(FrameError::InvalidBranchTarget(*pc))?
+                .0;
+            worklist.propagate(index + 1, &output, next_pc, context)?;
+        }
+    }
+
+    let states = worklist.states;
+    frame_pcs
+        .into_iter()
+        .map(|pc| {
+            states[index_of[&pc]]
+                .as_ref()
+                .map(|state| state.to_frame(pc))
+                .ok_or(FrameError::UnreachableCode(pc))
+        })
+        .collect()
+}
+
+fn to_verification_type<F>(
+    value: &FrameType,
+    class_index: &mut F,
+) -> Result<VerificationTypeInfo, FrameError>
+where
+    F: FnMut(&str) -> Option<u16>,
+{
+    Ok(match value {
+        FrameType::Top => VerificationTypeInfo::Top,
+        FrameType::Integer => VerificationTypeInfo::Integer,
+        FrameType::Float => VerificationTypeInfo::Float,
+        FrameType::Long => VerificationTypeInfo::Long,
+        FrameType::Double => VerificationTypeInfo::Double,
+        FrameType::Null => VerificationTypeInfo::Null,
+        FrameType::UninitializedThis => VerificationTypeInfo::UninitializedThis,
+        FrameType::Object(name) => VerificationTypeInfo::Object {
+            cpool_index: class_index(name).ok_or_else(|| FrameError::MissingClass(name.clone()))?,
+        },
+        FrameType::Uninitialized(offset) => VerificationTypeInfo::Uninitialized { offset: *offset },
+    })
+}
+
+fn to_verification_types<F>(
+    values: &[FrameType],
+    class_index: &mut F,
+) -> Result<Vec<VerificationTypeInfo>, FrameError>
+where
+    F: FnMut(&str) -> Option<u16>,
+{
+    values
+        .iter()
+        .map(|value| to_verification_type(value, class_index))
+        .collect()
+}
+
+pub fn encode<F>(
+    frames: &[Frame],
+    initial: &Frame,
+    mut class_index: F,
+) -> Result<StackMapTable, FrameError>
+where
+    F: FnMut(&str) -> Option<u16>,
+{
+    let mut entries = Vec::new();
+    let mut previous_locals = &initial.locals;
+    let mut previous_pc = None;
+    for frame in frames {
+        let offset_delta = match previous_pc {
+            Some(previous_pc) => frame.pc - previous_pc - 1,
+            None => frame.pc,
+        } as u16;
+        let same_locals = frame.locals == *previous_locals;
+        let is_prefix = |shorter: &[FrameType], longer: &[FrameType]| {
+            longer.len() > shorter.len()
+                && longer.len() - shorter.len() <= 3
+                && longer[..shorter.len()] == *shorter
+        };
+        let entry = if same_locals && frame.stack.is_empty() {
+            if offset_delta < 64 {
+                StackMapFrame::Same {
+                    offset_delta: offset_delta as u8,
+                }
+            } else {
+                StackMapFrame::SameExtended { offset_delta }
+            }
+        } else if same_locals && frame.stack.len() == 1 {
+            let stack = to_verification_type(&frame.stack[0], &mut class_index)?;
+            if offset_delta < 64 {
+                StackMapFrame::SameLocals1StackItem {
+                    offset_delta: offset_delta as u8,
+                    stack,
+                }
+            } else {
+                StackMapFrame::SameLocals1StackItemExtended {
+                    offset_delta,
+                    stack,
+                }
+            }
+        } else if frame.stack.is_empty() && is_prefix(previous_locals, &frame.locals) {
+            StackMapFrame::Append {
+                offset_delta,
+                locals: to_verification_types(
+                    &frame.locals[previous_locals.len()..],
+                    &mut class_index,
+                )?,
+            }
+        } else if frame.stack.is_empty() && is_prefix(&frame.locals, previous_locals) {
+            StackMapFrame::Chop {
+                k: (previous_locals.len() - frame.locals.len()) as u8,
+                offset_delta,
+            }
+        } else {
+            StackMapFrame::Full {
+                offset_delta,
+                locals: to_verification_types(&frame.locals, &mut class_index)?,
+                stack: to_verification_types(&frame.stack, &mut class_index)?,
+            }
+        };
+        entries.push(entry);
+        previous_locals = &frame.locals;
+        previous_pc = Some(frame.pc);
+    }
+    Ok(StackMapTable { entries })
+}
+
+pub fn compute<A, F>(
+    code: &Code<A>,
+    context: &FrameContext,
+    class_index: F,
+) -> Result<StackMapTable, FrameError>
+where
+    F: FnMut(&str) -> Option<u16>,
+{
+    let frames = analyze(code, context)?;
+    let initial = initial_state(code.max_locals, context).to_frame(0);
+    encode(&frames, &initial, class_index)
+}
+
+// the Class constant naming `name`, added to the end of the pool if missing
+fn class_constant(constant_pool: &mut ConstantPool, name: &str) -> u16 {
+    if let Some((index, _)) = constant_pool.classes().find(|(_, class)| class == name) {
+        return index;
+    }
+    let name_index = utf8_constant(constant_pool, name);
+    constant_pool.push(Constant::Class { name_index })
+}
+
+fn utf8_constant(constant_pool: &mut ConstantPool, value: &str) -> u16 {
+    match constant_pool.find_utf8(value) {
+        Some(index) => index,
+        None => constant_pool.push(Constant::Utf8 {
+            value: encode_modified_utf8(value).into_owned().into(),
+        }),
+    }
+}
+
+fn constant_utf8(constant_pool: &ConstantPool, index: u16) -> Result<String, FrameError> {
+    Ok(constant_pool.utf8(index)?.into_owned())
+}
+
+// replaces the StackMapTable of every decoded Code attribute with one
+// computed from the code, for classes generated or rewritten in place.
+// the Class and Utf8 constants the tables need are added to the pool.
+// classes older than version 50 verify by type inference and are left alone
+pub fn compute_stack_map_tables(
+    classfile: &mut ClassFile,
+    hierarchy: &dyn ClassHierarchy,
+) -> Result<(), FrameError> {
+    for method in 0..classfile.methods.len() {
+        compute_method_stack_map_table(classfile, method, hierarchy)?;
+    }
+    Ok(())
+}
+
+// the same, for just the method at `method` in `classfile.methods`
+pub fn compute_method_stack_map_table(
+    classfile: &mut ClassFile,
+    method: usize,
+    hierarchy: &dyn ClassHierarchy,
+) -> Result<(), FrameError> {
+    if !classfile.version().supports_stack_map_table() {
+        return Ok(());
+    }
+    let constant_pool = &mut classfile.constant_pool;
+    let this_class = constant_pool.class_name(classfile.this_class)?.into_owned();
+    let Some(method) = classfile.methods.get_mut(method) else {
+        return Ok(());
+    };
+    let method_name = constant_utf8(constant_pool, method.name_index)?;
+    let descriptor = constant_utf8(constant_pool, method.descriptor_index)?;
+    let descriptor = MethodDescriptor::parse(descriptor.as_bytes())
+        .map_err(|_| FrameError::InvalidDescriptor)?;
+    let Some(Attribute::Code(code)) = method
+        .attributes
+        .iter_mut()
+        .find(|attribute| matches!(attribute, Attribute::Code(_)))
+    else {
+        return Ok(());
+    };
+    let context = FrameContext {
+        constant_pool,
+        this_class: &this_class,
+        method_name: &method_name,
+        descriptor: &descriptor,
+        is_static: method.access_flags.contains(MethodAccessFlags::STATIC),
+        hierarchy,
+    };
+    let frames = analyze(code, &context)?;
+    let initial = initial_state(code.max_locals, &context).to_frame(0);
+    let table = encode(&frames, &initial, |name| {
+        Some(class_constant(constant_pool, name))
+    })?;
+
+    code.attributes
+        .retain(|attribute| attribute.attribute_name() != Some(AttributeName::StackMapTable));
+    if !table.entries.is_empty() {
+        utf8_constant(constant_pool, "StackMapTable");
+        code.attributes.push(Attribute::StackMapTable(table));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::{parse_classfile, Attribute, MethodAccessFlags};
+
+    #[test]
+    fn test_common_super_class() {
+        let mut hierarchy = SimpleClassHierarchy::new();
+        hierarchy.add_class("Animal", Some(OBJECT), false);
+        hierarchy.add_class("Dog", Some("Animal"), false);
+        hierarchy.add_class("Cat", Some("Animal"), false);
+        hierarchy.add_class("Pet", Some(OBJECT), true);
+
+        assert_eq!(common_super_class("Dog", "Cat", &hierarchy), "Animal");
+        assert_eq!(common_super_class("Dog", "Animal", &hierarchy), "Animal");
+        assert_eq!(common_super_class("Dog", "Pet", &hierarchy), OBJECT);
+        assert_eq!(common_super_class("Dog", "Unknown", &hierarchy), OBJECT);
+        assert_eq!(
+            common_super_class("[LDog;", "[LCat;", &hierarchy),
+            "[LAnimal;"
+        );
+        assert_eq!(
+            common_super_class("[[I", "[LCat;", &hierarchy),
+            "[Ljava/lang/Object;"
+        );
+        assert_eq!(common_super_class("[I", "[J", &hierarchy), OBJECT);
+        assert_eq!(common_super_class("[I", "Dog", &hierarchy), OBJECT);
+    }
+
+    #[test]
+    fn test_compute() {
+        let data = include_bytes!("../../../../java/Frames.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let constant_pool = &classfile.constant_pool;
+        let class_index = |name: &str| {
+            constant_pool
+                .iter()
+                .find(|(_, constant)| match constant {
+                    Constant::Class { name_index } => {
+                        constant_pool.utf8(*name_index).ok().as_deref() == Some(name)
+                    }
+                    _ => false,
+                })
+                .map(|(index, _)| index)
+        };
+        let hierarchy = SimpleClassHierarchy::new();
+
+        let mut checked = 0;
+        for method in &classfile.methods {
+            let name = constant_pool.utf8(method.name_index).unwrap();
+            let descriptor = constant_pool.utf8(method.descriptor_index).unwrap();
+            let descriptor = parse_descriptor(descriptor.as_bytes()).unwrap();
+            let context = FrameContext {
+                constant_pool,
+                this_class: "Frames",
+                method_name: &name,
+                descriptor: &descriptor,
+                is_static: method.access_flags.contains(MethodAccessFlags::STATIC),
+                hierarchy: &hierarchy,
+            };
+            for attribute in &method.attributes {
+                if let Attribute::Code(code) = attribute {
+                    let expected = code
+                        .attributes
+                        .iter()
+                        .find_map(|attribute| match attribute {
+                            Attribute::StackMapTable(table) => Some(table.clone()),
+                            _ => None,
+                        })
+                        .unwrap_or(StackMapTable { entries: vec![] });
+                    let table = compute(code, &context, class_index).unwrap();
+                    assert_eq!(table, expected);
+                    checked += 1;
+                }
+            }
+        }
+        assert_eq!(checked, 5);
+    }
+
+    #[test]
+    fn test_analyze_if_else_and_loop() {
+        let constant_pool = ConstantPool::from(vec![]);
+        let hierarchy = SimpleClassHierarchy::new();
+        let descriptor = MethodDescriptor::parse(b"(I)I").unwrap();
+        let context = FrameContext {
+            constant_pool: &constant_pool,
+            this_class: "Test",
+            method_name: "test",
+            descriptor: &descriptor,
+            is_static: true,
+            hierarchy: &hierarchy,
+        };
+        let code: Code<Attribute> = Code {
+            max_stack: 2,
+            max_locals: 2,
+            code: vec![
+                0x1a, // 0: iload_0
+                0x99, 0x00, 0x08, // 1: ifeq 9
+                0x01, // 4: aconst_null
+                0x4c, // 5: astore_1
+                0xa7, 0x00, 0x05, // 6: goto 11
+                0x03, // 9: iconst_0
+                0x3c, // 10: istore_1
+                0x84, 0x00, 0xff, // 11: iinc 0, -1
+                0x1a, // 14: iload_0
+                0x9a, 0xff, 0xfc, // 15: ifne 11
+                0x1a, // 18: iload_0
+                0xac, // 19: ireturn
+            ]
+            .into(),
+            exception_table: vec![],
+            attributes: vec![],
+        };
+        let frames = analyze(&code, &context).unwrap();
+        assert_eq!(
+            frames,
+            vec![
+                Frame {
+                    pc: 9,
+                    locals: vec![FrameType::Integer],
+                    stack: vec![],
+                },
+                Frame {
+                    pc: 11,
+                    locals: vec![FrameType::Integer],
+                    stack: vec![],
+                },
+            ]
+        );
+        let initial = initial_state(code.max_locals, &context).to_frame(0);
+        let table = encode(&frames, &initial, |_| None).unwrap();
+        assert_eq!(
+            table.entries,
+            vec![
+                StackMapFrame::Same { offset_delta: 9 },
+                StackMapFrame::Same { offset_delta: 1 },
+            ]
+        );
+    }
+
+    // assembles an if/else and a loop into a class for Java 8, which the
+    // JVM verifies by type checking its frames
+    #[test]
+    #[ignore = "runs java; cargo test -- --ignored"]
+    fn test_java_loads_frames() {
+        use crate::asm::{ClassBuilder, Instruction as I};
+        use crate::class::ClassVersion;
+        use std::{env, fs, process};
+
+        let mut class = ClassBuilder::new("Loops");
+        class
+            .version(ClassVersion::new(52, 0))
+            .public_static_method("main", "([Ljava/lang/String;)V", |body| {
+                let none = body.constant_pool.string("none")?;
+                let some = body.constant_pool.string("some")?;
+                let out = body.constant_pool.field_ref(
+                    "java/lang/System",
+                    "out",
+                    "Ljava/io/PrintStream;",
+                )?;
+                let print_string = body.constant_pool.method_ref(
+                    "java/io/PrintStream",
+                    "println",
+                    "(Ljava/lang/String;)V",
+                )?;
+                let print_int =
+                    body.constant_pool
+                        .method_ref("java/io/PrintStream", "println", "(I)V")?;
+                let (other, joined, test, done) = (
+                    body.code.label(),
+                    body.code.label(),
+                    body.code.label(),
+                    body.code.label(),
+                );
+                // String label = args.length == 0 ? "none" : "some";
+                body.code
+                    .emit(I::Aload0)
+                    .emit(I::Arraylength)
+                    .branch(I::Ifne(0), other)
+                    .emit(I::LdcW(none))
+                    .emit(I::Astore1)
+                    .goto(joined)
+                    .bind(other)
+                    .emit(I::LdcW(some))
+                    .emit(I::Astore1)
+                    .bind(joined);
+                // int sum = 0; for (int i = 0; i < 10; i++) sum += i;
+                body.code
+                    .emit(I::Iconst0)
+                    .emit(I::Istore2)
+                    .emit(I::Iconst0)
+                    .emit(I::Istore3)
+                    .bind(test)
+                    .emit(I::Iload3)
+                    .emit(I::Bipush(10))
+                    .branch(I::IfIcmpge(0), done)
+                    .emit(I::Iload2)
+                    .emit(I::Iload3)
+                    .emit(I::Iadd)
+                    .emit(I::Istore2)
+                    .emit(I::Iinc(3, 1))
+                    .goto(test)
+                    .bind(done)
+                    .emit(I::Getstatic(out))
+                    .emit(I::Aload1)
+                    .emit(I::Invokevirtual(print_string))
+                    .emit(I::Getstatic(out))
+                    .emit(I::Iload2)
+                    .emit(I::Invokevirtual(print_int))
+                    .emit(I::Return);
+                Ok(())
+            });
+        let classfile = class.build().unwrap();
+        let code = classfile.methods[0].code().unwrap();
+        assert!(code
+            .attributes
+            .iter()
+            .any(|attribute| matches!(attribute, Attribute::StackMapTable(_))));
+
+        let directory = env::temp_dir().join(format!("rj-frames-{}", process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("Loops.class"), classfile.to_bytes().unwrap()).unwrap();
+        let java = match crate::test_support::java_home() {
+            Some(java_home) => java_home.join("bin/java"),
+            None => "java".into(),
+        };
+        let output = process::Command::new(java)
+            .arg("-cp")
+            .arg(&directory)
+            .arg("Loops")
+            .output()
+            .expect("a JVM to run");
+        fs::remove_dir_all(&directory).unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "{stderr}");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "none\n45\n");
+    }
+
+    #[test]
+    fn test_compute_stack_map_tables() {
+        let data = include_bytes!("../../../../java/Frames.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let hierarchy = SimpleClassHierarchy::new();
+        compute_stack_map_tables(&mut classfile, &hierarchy).unwrap();
+        assert_eq!(classfile.to_bytes().unwrap(), data);
+
+        // tables dropped from the code are rebuilt, reusing the constants
+        let constants = classfile.constant_pool.len();
+        for method in &mut classfile.methods {
+            if let Some(Attribute::Code(code)) = method.attributes.first_mut() {
+                code.attributes.clear();
+            }
+        }
+        compute_stack_map_tables(&mut classfile, &hierarchy).unwrap();
+        let tables = classfile
+            .methods
+            .iter()
+            .filter_map(|method| method.code())
+            .filter(|code| !code.attributes.is_empty())
+            .count();
+        assert_eq!(tables, 4);
+        assert_eq!(classfile.constant_pool.len(), constants);
+    }
+}
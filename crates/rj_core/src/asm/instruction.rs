@@ -2,9 +2,9 @@
 // https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-6.html
 
 use super::error::InstructionParseError;
-use crate::parser::{be_i16, be_i32, be_i8, be_u16, be_u8};
+use crate::parser::{self, be_i16, be_i32, be_i8, be_u16, be_u8, bytes};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Instruction {
     Aaload,
     Aastore,
@@ -221,7 +221,812 @@ pub enum Instruction {
     WideIinc(u16, i16),
 }
 
+impl Instruction {
+    pub fn opcode(&self) -> u8 {
+        match self {
+            Instruction::Aaload => 0x32,
+            Instruction::Aastore => 0x53,
+            Instruction::AconstNull => 0x01,
+            Instruction::Aload(..) => 0x19,
+            Instruction::Aload0 => 0x2a,
+            Instruction::Aload1 => 0x2b,
+            Instruction::Aload2 => 0x2c,
+            Instruction::Aload3 => 0x2d,
+            Instruction::Anewarray(..) => 0xbd,
+            Instruction::Areturn => 0xb0,
+            Instruction::Arraylength => 0xbe,
+            Instruction::Astore(..) => 0x3a,
+            Instruction::Astore0 => 0x4b,
+            Instruction::Astore1 => 0x4c,
+            Instruction::Astore2 => 0x4d,
+            Instruction::Astore3 => 0x4e,
+            Instruction::Athrow => 0xbf,
+            Instruction::Baload => 0x33,
+            Instruction::Bastore => 0x54,
+            Instruction::Bipush(..) => 0x10,
+            Instruction::Caload => 0x34,
+            Instruction::Castore => 0x55,
+            Instruction::Checkcast(..) => 0xc0,
+            Instruction::D2f => 0x90,
+            Instruction::D2i => 0x8e,
+            Instruction::D2l => 0x8f,
+            Instruction::Dadd => 0x63,
+            Instruction::Daload => 0x31,
+            Instruction::Dastore => 0x52,
+            Instruction::Dcmpg => 0x98,
+            Instruction::Dcmpl => 0x97,
+            Instruction::Dconst0 => 0x0e,
+            Instruction::Dconst1 => 0x0f,
+            Instruction::Ddiv => 0x6f,
+            Instruction::Dload(..) => 0x18,
+            Instruction::Dload0 => 0x26,
+            Instruction::Dload1 => 0x27,
+            Instruction::Dload2 => 0x28,
+            Instruction::Dload3 => 0x29,
+            Instruction::Dmul => 0x6b,
+            Instruction::Dneg => 0x77,
+            Instruction::Drem => 0x73,
+            Instruction::Dreturn => 0xaf,
+            Instruction::Dstore(..) => 0x39,
+            Instruction::Dstore0 => 0x47,
+            Instruction::Dstore1 => 0x48,
+            Instruction::Dstore2 => 0x49,
+            Instruction::Dstore3 => 0x4a,
+            Instruction::Dsub => 0x67,
+            Instruction::Dup => 0x59,
+            Instruction::DupX1 => 0x5a,
+            Instruction::DupX2 => 0x5b,
+            Instruction::Dup2 => 0x5c,
+            Instruction::Dup2X1 => 0x5d,
+            Instruction::Dup2X2 => 0x5e,
+            Instruction::F2d => 0x8d,
+            Instruction::F2i => 0x8b,
+            Instruction::F2l => 0x8c,
+            Instruction::Fadd => 0x62,
+            Instruction::Faload => 0x30,
+            Instruction::Fastore => 0x51,
+            Instruction::Fcmpg => 0x96,
+            Instruction::Fcmpl => 0x95,
+            Instruction::Fconst0 => 0x0b,
+            Instruction::Fconst1 => 0x0c,
+            Instruction::Fconst2 => 0x0d,
+            Instruction::Fdiv => 0x6e,
+            Instruction::Fload(..) => 0x17,
+            Instruction::Fload0 => 0x22,
+            Instruction::Fload1 => 0x23,
+            Instruction::Fload2 => 0x24,
+            Instruction::Fload3 => 0x25,
+            Instruction::Fmul => 0x6a,
+            Instruction::Fneg => 0x76,
+            Instruction::Frem => 0x72,
+            Instruction::Freturn => 0xae,
+            Instruction::Fstore(..) => 0x38,
+            Instruction::Fstore0 => 0x43,
+            Instruction::Fstore1 => 0x44,
+            Instruction::Fstore2 => 0x45,
+            Instruction::Fstore3 => 0x46,
+            Instruction::Fsub => 0x66,
+            Instruction::Getfield(..) => 0xb4,
+            Instruction::Getstatic(..) => 0xb2,
+            Instruction::Goto(..) => 0xa7,
+            Instruction::GotoW(..) => 0xc8,
+            Instruction::I2b => 0x91,
+            Instruction::I2c => 0x92,
+            Instruction::I2d => 0x87,
+            Instruction::I2f => 0x86,
+            Instruction::I2l => 0x85,
+            Instruction::I2s => 0x93,
+            Instruction::Iadd => 0x60,
+            Instruction::Iaload => 0x2e,
+            Instruction::Iand => 0x7e,
+            Instruction::Iastore => 0x4f,
+            Instruction::IconstM1 => 0x02,
+            Instruction::Iconst0 => 0x03,
+            Instruction::Iconst1 => 0x04,
+            Instruction::Iconst2 => 0x05,
+            Instruction::Iconst3 => 0x06,
+            Instruction::Iconst4 => 0x07,
+            Instruction::Iconst5 => 0x08,
+            Instruction::Idiv => 0x6c,
+            Instruction::IfAcmpeq(..) => 0xa5,
+            Instruction::IfAcmpne(..) => 0xa6,
+            Instruction::IfIcmpeq(..) => 0x9f,
+            Instruction::IfIcmpne(..) => 0xa0,
+            Instruction::IfIcmplt(..) => 0xa1,
+            Instruction::IfIcmpge(..) => 0xa2,
+            Instruction::IfIcmpgt(..) => 0xa3,
+            Instruction::IfIcmple(..) => 0xa4,
+            Instruction::Ifeq(..) => 0x99,
+            Instruction::Ifne(..) => 0x9a,
+            Instruction::Iflt(..) => 0x9b,
+            Instruction::Ifge(..) => 0x9c,
+            Instruction::Ifgt(..) => 0x9d,
+            Instruction::Ifle(..) => 0x9e,
+            Instruction::Ifnonnull(..) => 0xc7,
+            Instruction::Ifnull(..) => 0xc6,
+            Instruction::Iinc(..) => 0x84,
+            Instruction::Iload(..) => 0x15,
+            Instruction::Iload0 => 0x1a,
+            Instruction::Iload1 => 0x1b,
+            Instruction::Iload2 => 0x1c,
+            Instruction::Iload3 => 0x1d,
+            Instruction::Imul => 0x68,
+            Instruction::Ineg => 0x74,
+            Instruction::Instanceof(..) => 0xc1,
+            Instruction::Invokedynamic(..) => 0xba,
+            Instruction::Invokeinterface(..) => 0xb9,
+            Instruction::Invokespecial(..) => 0xb7,
+            Instruction::Invokestatic(..) => 0xb8,
+            Instruction::Invokevirtual(..) => 0xb6,
+            Instruction::Ior => 0x80,
+            Instruction::Irem => 0x70,
+            Instruction::Ireturn => 0xac,
+            Instruction::Ishl => 0x78,
+            Instruction::Ishr => 0x7a,
+            Instruction::Istore(..) => 0x36,
+            Instruction::Istore0 => 0x3b,
+            Instruction::Istore1 => 0x3c,
+            Instruction::Istore2 => 0x3d,
+            Instruction::Istore3 => 0x3e,
+            Instruction::Isub => 0x64,
+            Instruction::Iushr => 0x7c,
+            Instruction::Ixor => 0x82,
+            Instruction::Jsr(..) => 0xa8,
+            Instruction::JsrW(..) => 0xc9,
+            Instruction::L2d => 0x8a,
+            Instruction::L2f => 0x89,
+            Instruction::L2i => 0x88,
+            Instruction::Ladd => 0x61,
+            Instruction::Laload => 0x2f,
+            Instruction::Land => 0x7f,
+            Instruction::Lastore => 0x50,
+            Instruction::Lcmp => 0x94,
+            Instruction::Lconst0 => 0x09,
+            Instruction::Lconst1 => 0x0a,
+            Instruction::Ldc(..) => 0x12,
+            Instruction::LdcW(..) => 0x13,
+            Instruction::Ldc2W(..) => 0x14,
+            Instruction::Ldiv => 0x6d,
+            Instruction::Lload(..) => 0x16,
+            Instruction::Lload0 => 0x1e,
+            Instruction::Lload1 => 0x1f,
+            Instruction::Lload2 => 0x20,
+            Instruction::Lload3 => 0x21,
+            Instruction::Lmul => 0x69,
+            Instruction::Lneg => 0x75,
+            Instruction::Lookupswitch(..) => 0xab,
+            Instruction::Lor => 0x81,
+            Instruction::Lrem => 0x71,
+            Instruction::Lreturn => 0xad,
+            Instruction::Lshl => 0x79,
+            Instruction::Lshr => 0x7b,
+            Instruction::Lstore(..) => 0x37,
+            Instruction::Lstore0 => 0x3f,
+            Instruction::Lstore1 => 0x40,
+            Instruction::Lstore2 => 0x41,
+            Instruction::Lstore3 => 0x42,
+            Instruction::Lsub => 0x65,
+            Instruction::Lushr => 0x7d,
+            Instruction::Lxor => 0x83,
+            Instruction::Monitorenter => 0xc2,
+            Instruction::Monitorexit => 0xc3,
+            Instruction::Multianewarray(..) => 0xc5,
+            Instruction::New(..) => 0xbb,
+            Instruction::Newarray(..) => 0xbc,
+            Instruction::Nop => 0x00,
+            Instruction::Pop => 0x57,
+            Instruction::Pop2 => 0x58,
+            Instruction::Putfield(..) => 0xb5,
+            Instruction::Putstatic(..) => 0xb3,
+            Instruction::Ret(..) => 0xa9,
+            Instruction::Return => 0xb1,
+            Instruction::Saload => 0x35,
+            Instruction::Sastore => 0x56,
+            Instruction::Sipush(..) => 0x11,
+            Instruction::Swap => 0x5f,
+            Instruction::Tableswitch(..) => 0xaa,
+            Instruction::WideIload(..) => 0xc4,
+            Instruction::WideFload(..) => 0xc4,
+            Instruction::WideAload(..) => 0xc4,
+            Instruction::WideLload(..) => 0xc4,
+            Instruction::WideDload(..) => 0xc4,
+            Instruction::WideIstore(..) => 0xc4,
+            Instruction::WideFstore(..) => 0xc4,
+            Instruction::WideAstore(..) => 0xc4,
+            Instruction::WideLstore(..) => 0xc4,
+            Instruction::WideDstore(..) => 0xc4,
+            Instruction::WideRet(..) => 0xc4,
+            Instruction::WideIinc(..) => 0xc4,
+        }
+    }
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::Aaload => "aaload",
+            Instruction::Aastore => "aastore",
+            Instruction::AconstNull => "aconst_null",
+            Instruction::Aload(..) => "aload",
+            Instruction::Aload0 => "aload_0",
+            Instruction::Aload1 => "aload_1",
+            Instruction::Aload2 => "aload_2",
+            Instruction::Aload3 => "aload_3",
+            Instruction::Anewarray(..) => "anewarray",
+            Instruction::Areturn => "areturn",
+            Instruction::Arraylength => "arraylength",
+            Instruction::Astore(..) => "astore",
+            Instruction::Astore0 => "astore_0",
+            Instruction::Astore1 => "astore_1",
+            Instruction::Astore2 => "astore_2",
+            Instruction::Astore3 => "astore_3",
+            Instruction::Athrow => "athrow",
+            Instruction::Baload => "baload",
+            Instruction::Bastore => "bastore",
+            Instruction::Bipush(..) => "bipush",
+            Instruction::Caload => "caload",
+            Instruction::Castore => "castore",
+            Instruction::Checkcast(..) => "checkcast",
+            Instruction::D2f => "d2f",
+            Instruction::D2i => "d2i",
+            Instruction::D2l => "d2l",
+            Instruction::Dadd => "dadd",
+            Instruction::Daload => "daload",
+            Instruction::Dastore => "dastore",
+            Instruction::Dcmpg => "dcmpg",
+            Instruction::Dcmpl => "dcmpl",
+            Instruction::Dconst0 => "dconst_0",
+            Instruction::Dconst1 => "dconst_1",
+            Instruction::Ddiv => "ddiv",
+            Instruction::Dload(..) => "dload",
+            Instruction::Dload0 => "dload_0",
+            Instruction::Dload1 => "dload_1",
+            Instruction::Dload2 => "dload_2",
+            Instruction::Dload3 => "dload_3",
+            Instruction::Dmul => "dmul",
+            Instruction::Dneg => "dneg",
+            Instruction::Drem => "drem",
+            Instruction::Dreturn => "dreturn",
+            Instruction::Dstore(..) => "dstore",
+            Instruction::Dstore0 => "dstore_0",
+            Instruction::Dstore1 => "dstore_1",
+            Instruction::Dstore2 => "dstore_2",
+            Instruction::Dstore3 => "dstore_3",
+            Instruction::Dsub => "dsub",
+            Instruction::Dup => "dup",
+            Instruction::DupX1 => "dup_x1",
+            Instruction::DupX2 => "dup_x2",
+            Instruction::Dup2 => "dup2",
+            Instruction::Dup2X1 => "dup2_x1",
+            Instruction::Dup2X2 => "dup2_x2",
+            Instruction::F2d => "f2d",
+            Instruction::F2i => "f2i",
+            Instruction::F2l => "f2l",
+            Instruction::Fadd => "fadd",
+            Instruction::Faload => "faload",
+            Instruction::Fastore => "fastore",
+            Instruction::Fcmpg => "fcmpg",
+            Instruction::Fcmpl => "fcmpl",
+            Instruction::Fconst0 => "fconst_0",
+            Instruction::Fconst1 => "fconst_1",
+            Instruction::Fconst2 => "fconst_2",
+            Instruction::Fdiv => "fdiv",
+            Instruction::Fload(..) => "fload",
+            Instruction::Fload0 => "fload_0",
+            Instruction::Fload1 => "fload_1",
+            Instruction::Fload2 => "fload_2",
+            Instruction::Fload3 => "fload_3",
+            Instruction::Fmul => "fmul",
+            Instruction::Fneg => "fneg",
+            Instruction::Frem => "frem",
+            Instruction::Freturn => "freturn",
+            Instruction::Fstore(..) => "fstore",
+            Instruction::Fstore0 => "fstore_0",
+            Instruction::Fstore1 => "fstore_1",
+            Instruction::Fstore2 => "fstore_2",
+            Instruction::Fstore3 => "fstore_3",
+            Instruction::Fsub => "fsub",
+            Instruction::Getfield(..) => "getfield",
+            Instruction::Getstatic(..) => "getstatic",
+            Instruction::Goto(..) => "goto",
+            Instruction::GotoW(..) => "goto_w",
+            Instruction::I2b => "i2b",
+            Instruction::I2c => "i2c",
+            Instruction::I2d => "i2d",
+            Instruction::I2f => "i2f",
+            Instruction::I2l => "i2l",
+            Instruction::I2s => "i2s",
+            Instruction::Iadd => "iadd",
+            Instruction::Iaload => "iaload",
+            Instruction::Iand => "iand",
+            Instruction::Iastore => "iastore",
+            Instruction::IconstM1 => "iconst_m1",
+            Instruction::Iconst0 => "iconst_0",
+            Instruction::Iconst1 => "iconst_1",
+            Instruction::Iconst2 => "iconst_2",
+            Instruction::Iconst3 => "iconst_3",
+            Instruction::Iconst4 => "iconst_4",
+            Instruction::Iconst5 => "iconst_5",
+            Instruction::Idiv => "idiv",
+            Instruction::IfAcmpeq(..) => "if_acmpeq",
+            Instruction::IfAcmpne(..) => "if_acmpne",
+            Instruction::IfIcmpeq(..) => "if_icmpeq",
+            Instruction::IfIcmpne(..) => "if_icmpne",
+            Instruction::IfIcmplt(..) => "if_icmplt",
+            Instruction::IfIcmpge(..) => "if_icmpge",
+            Instruction::IfIcmpgt(..) => "if_icmpgt",
+            Instruction::IfIcmple(..) => "if_icmple",
+            Instruction::Ifeq(..) => "ifeq",
+            Instruction::Ifne(..) => "ifne",
+            Instruction::Iflt(..) => "iflt",
+            Instruction::Ifge(..) => "ifge",
+            Instruction::Ifgt(..) => "ifgt",
+            Instruction::Ifle(..) => "ifle",
+            Instruction::Ifnonnull(..) => "ifnonnull",
+            Instruction::Ifnull(..) => "ifnull",
+            Instruction::Iinc(..) => "iinc",
+            Instruction::Iload(..) => "iload",
+            Instruction::Iload0 => "iload_0",
+            Instruction::Iload1 => "iload_1",
+            Instruction::Iload2 => "iload_2",
+            Instruction::Iload3 => "iload_3",
+            Instruction::Imul => "imul",
+            Instruction::Ineg => "ineg",
+            Instruction::Instanceof(..) => "instanceof",
+            Instruction::Invokedynamic(..) => "invokedynamic",
+            Instruction::Invokeinterface(..) => "invokeinterface",
+            Instruction::Invokespecial(..) => "invokespecial",
+            Instruction::Invokestatic(..) => "invokestatic",
+            Instruction::Invokevirtual(..) => "invokevirtual",
+            Instruction::Ior => "ior",
+            Instruction::Irem => "irem",
+            Instruction::Ireturn => "ireturn",
+            Instruction::Ishl => "ishl",
+            Instruction::Ishr => "ishr",
+            Instruction::Istore(..) => "istore",
+            Instruction::Istore0 => "istore_0",
+            Instruction::Istore1 => "istore_1",
+            Instruction::Istore2 => "istore_2",
+            Instruction::Istore3 => "istore_3",
+            Instruction::Isub => "isub",
+            Instruction::Iushr => "iushr",
+            Instruction::Ixor => "ixor",
+            Instruction::Jsr(..) => "jsr",
+            Instruction::JsrW(..) => "jsr_w",
+            Instruction::L2d => "l2d",
+            Instruction::L2f => "l2f",
+            Instruction::L2i => "l2i",
+            Instruction::Ladd => "ladd",
+            Instruction::Laload => "laload",
+            Instruction::Land => "land",
+            Instruction::Lastore => "lastore",
+            Instruction::Lcmp => "lcmp",
+            Instruction::Lconst0 => "lconst_0",
+            Instruction::Lconst1 => "lconst_1",
+            Instruction::Ldc(..) => "ldc",
+            Instruction::LdcW(..) => "ldc_w",
+            Instruction::Ldc2W(..) => "ldc2_w",
+            Instruction::Ldiv => "ldiv",
+            Instruction::Lload(..) => "lload",
+            Instruction::Lload0 => "lload_0",
+            Instruction::Lload1 => "lload_1",
+            Instruction::Lload2 => "lload_2",
+            Instruction::Lload3 => "lload_3",
+            Instruction::Lmul => "lmul",
+            Instruction::Lneg => "lneg",
+            Instruction::Lookupswitch(..) => "lookupswitch",
+            Instruction::Lor => "lor",
+            Instruction::Lrem => "lrem",
+            Instruction::Lreturn => "lreturn",
+            Instruction::Lshl => "lshl",
+            Instruction::Lshr => "lshr",
+            Instruction::Lstore(..) => "lstore",
+            Instruction::Lstore0 => "lstore_0",
+            Instruction::Lstore1 => "lstore_1",
+            Instruction::Lstore2 => "lstore_2",
+            Instruction::Lstore3 => "lstore_3",
+            Instruction::Lsub => "lsub",
+            Instruction::Lushr => "lushr",
+            Instruction::Lxor => "lxor",
+            Instruction::Monitorenter => "monitorenter",
+            Instruction::Monitorexit => "monitorexit",
+            Instruction::Multianewarray(..) => "multianewarray",
+            Instruction::New(..) => "new",
+            Instruction::Newarray(..) => "newarray",
+            Instruction::Nop => "nop",
+            Instruction::Pop => "pop",
+            Instruction::Pop2 => "pop2",
+            Instruction::Putfield(..) => "putfield",
+            Instruction::Putstatic(..) => "putstatic",
+            Instruction::Ret(..) => "ret",
+            Instruction::Return => "return",
+            Instruction::Saload => "saload",
+            Instruction::Sastore => "sastore",
+            Instruction::Sipush(..) => "sipush",
+            Instruction::Swap => "swap",
+            Instruction::Tableswitch(..) => "tableswitch",
+            Instruction::WideIload(..) => "iload_w",
+            Instruction::WideFload(..) => "fload_w",
+            Instruction::WideAload(..) => "aload_w",
+            Instruction::WideLload(..) => "lload_w",
+            Instruction::WideDload(..) => "dload_w",
+            Instruction::WideIstore(..) => "istore_w",
+            Instruction::WideFstore(..) => "fstore_w",
+            Instruction::WideAstore(..) => "astore_w",
+            Instruction::WideLstore(..) => "lstore_w",
+            Instruction::WideDstore(..) => "dstore_w",
+            Instruction::WideRet(..) => "ret_w",
+            Instruction::WideIinc(..) => "iinc_w",
+        }
+    }
+
+    pub fn byte_len(&self, pc: u32) -> usize {
+        match self {
+            Instruction::Lookupswitch(_, pairs) => {
+                1 + switch_padding(pc as usize) + 8 + pairs.len() * 8
+            }
+            Instruction::Tableswitch(_, _, _, offsets) => {
+                1 + switch_padding(pc as usize) + 12 + offsets.len() * 4
+            }
+            Instruction::WideIinc(..) => 6,
+            Instruction::GotoW(_)
+            | Instruction::JsrW(_)
+            | Instruction::Invokedynamic(..)
+            | Instruction::Invokeinterface(..) => 5,
+            Instruction::Multianewarray(..)
+            | Instruction::WideIload(_)
+            | Instruction::WideFload(_)
+            | Instruction::WideAload(_)
+            | Instruction::WideLload(_)
+            | Instruction::WideDload(_)
+            | Instruction::WideIstore(_)
+            | Instruction::WideFstore(_)
+            | Instruction::WideAstore(_)
+            | Instruction::WideLstore(_)
+            | Instruction::WideDstore(_)
+            | Instruction::WideRet(_) => 4,
+            Instruction::Anewarray(_)
+            | Instruction::Checkcast(_)
+            | Instruction::Getfield(_)
+            | Instruction::Getstatic(_)
+            | Instruction::Instanceof(_)
+            | Instruction::Invokespecial(_)
+            | Instruction::Invokestatic(_)
+            | Instruction::Invokevirtual(_)
+            | Instruction::LdcW(_)
+            | Instruction::Ldc2W(_)
+            | Instruction::New(_)
+            | Instruction::Putfield(_)
+            | Instruction::Putstatic(_)
+            | Instruction::Goto(_)
+            | Instruction::IfAcmpeq(_)
+            | Instruction::IfAcmpne(_)
+            | Instruction::IfIcmpeq(_)
+            | Instruction::IfIcmpne(_)
+            | Instruction::IfIcmplt(_)
+            | Instruction::IfIcmpge(_)
+            | Instruction::IfIcmpgt(_)
+            | Instruction::IfIcmple(_)
+            | Instruction::Ifeq(_)
+            | Instruction::Ifne(_)
+            | Instruction::Iflt(_)
+            | Instruction::Ifge(_)
+            | Instruction::Ifgt(_)
+            | Instruction::Ifle(_)
+            | Instruction::Ifnonnull(_)
+            | Instruction::Ifnull(_)
+            | Instruction::Jsr(_)
+            | Instruction::Sipush(_)
+            | Instruction::Iinc(..) => 3,
+            Instruction::Aload(_)
+            | Instruction::Astore(_)
+            | Instruction::Dload(_)
+            | Instruction::Dstore(_)
+            | Instruction::Fload(_)
+            | Instruction::Fstore(_)
+            | Instruction::Iload(_)
+            | Instruction::Istore(_)
+            | Instruction::Ldc(_)
+            | Instruction::Lload(_)
+            | Instruction::Lstore(_)
+            | Instruction::Newarray(_)
+            | Instruction::Ret(_)
+            | Instruction::Bipush(_) => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn encode(&self, pc: u32, out: &mut Vec<u8>) {
+        out.push(self.opcode());
+        match self {
+            Instruction::Aload(value)
+            | Instruction::Astore(value)
+            | Instruction::Dload(value)
+            | Instruction::Dstore(value)
+            | Instruction::Fload(value)
+            | Instruction::Fstore(value)
+            | Instruction::Iload(value)
+            | Instruction::Istore(value)
+            | Instruction::Ldc(value)
+            | Instruction::Lload(value)
+            | Instruction::Lstore(value)
+            | Instruction::Newarray(value)
+            | Instruction::Ret(value) => out.push(*value),
+            Instruction::Bipush(value) => out.push(*value as u8),
+            Instruction::Anewarray(value)
+            | Instruction::Checkcast(value)
+            | Instruction::Getfield(value)
+            | Instruction::Getstatic(value)
+            | Instruction::Instanceof(value)
+            | Instruction::Invokespecial(value)
+            | Instruction::Invokestatic(value)
+            | Instruction::Invokevirtual(value)
+            | Instruction::LdcW(value)
+            | Instruction::Ldc2W(value)
+            | Instruction::New(value)
+            | Instruction::Putfield(value)
+            | Instruction::Putstatic(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Instruction::Goto(value)
+            | Instruction::IfAcmpeq(value)
+            | Instruction::IfAcmpne(value)
+            | Instruction::IfIcmpeq(value)
+            | Instruction::IfIcmpne(value)
+            | Instruction::IfIcmplt(value)
+            | Instruction::IfIcmpge(value)
+            | Instruction::IfIcmpgt(value)
+            | Instruction::IfIcmple(value)
+            | Instruction::Ifeq(value)
+            | Instruction::Ifne(value)
+            | Instruction::Iflt(value)
+            | Instruction::Ifge(value)
+            | Instruction::Ifgt(value)
+            | Instruction::Ifle(value)
+            | Instruction::Ifnonnull(value)
+            | Instruction::Ifnull(value)
+            | Instruction::Jsr(value)
+            | Instruction::Sipush(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Instruction::GotoW(offset) | Instruction::JsrW(offset) => {
+                out.extend_from_slice(&offset.to_be_bytes())
+            }
+            Instruction::Iinc(index, value) => {
+                out.push(*index);
+                out.push(*value as u8);
+            }
+            Instruction::Invokedynamic(index, byte1, byte2)
+            | Instruction::Invokeinterface(index, byte1, byte2) => {
+                out.extend_from_slice(&index.to_be_bytes());
+                out.push(*byte1);
+                out.push(*byte2);
+            }
+            Instruction::Multianewarray(index, dimensions) => {
+                out.extend_from_slice(&index.to_be_bytes());
+                out.push(*dimensions);
+            }
+            Instruction::Lookupswitch(default, pairs) => {
+                out.resize(out.len() + switch_padding(pc as usize), 0);
+                out.extend_from_slice(&default.to_be_bytes());
+                out.extend_from_slice(&(pairs.len() as i32).to_be_bytes());
+                for (key, offset) in pairs {
+                    out.extend_from_slice(&key.to_be_bytes());
+                    out.extend_from_slice(&offset.to_be_bytes());
+                }
+            }
+            Instruction::Tableswitch(default, low, high, offsets) => {
+                out.resize(out.len() + switch_padding(pc as usize), 0);
+                out.extend_from_slice(&default.to_be_bytes());
+                out.extend_from_slice(&low.to_be_bytes());
+                out.extend_from_slice(&high.to_be_bytes());
+                for offset in offsets {
+                    out.extend_from_slice(&offset.to_be_bytes());
+                }
+            }
+            Instruction::WideIload(index) => {
+                out.push(0x15);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::WideFload(index) => {
+                out.push(0x17);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::WideAload(index) => {
+                out.push(0x19);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::WideLload(index) => {
+                out.push(0x16);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::WideDload(index) => {
+                out.push(0x18);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::WideIstore(index) => {
+                out.push(0x36);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::WideFstore(index) => {
+                out.push(0x38);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::WideAstore(index) => {
+                out.push(0x3a);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::WideLstore(index) => {
+                out.push(0x37);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::WideDstore(index) => {
+                out.push(0x39);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::WideRet(index) => {
+                out.push(0xa9);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+            Instruction::WideIinc(index, value) => {
+                out.push(0x84);
+                out.extend_from_slice(&index.to_be_bytes());
+                out.extend_from_slice(&value.to_be_bytes());
+            }
+            _ => {}
+        }
+    }
+}
+
+// switch padding is relative to the start of the code array, so `input` is
+// assumed to start at pc 0; use `parse_instruction_at` for other offsets
 pub fn parse_instruction(input: &[u8]) -> Result<(&[u8], Instruction), InstructionParseError> {
+    parse_instruction_with_pc(input, 0, ParseMode::Lenient, ParseLimits::UNLIMITED)
+}
+
+pub fn parse_instruction_at(
+    code: &[u8],
+    pc: usize,
+) -> Result<(&[u8], Instruction), InstructionParseError> {
+    parse_instruction_with_mode(code, pc, ParseMode::Lenient)
+}
+
+// Strict rejects operand bytes the spec requires to be zero; Lenient keeps
+// whatever values are present in the instruction so they round-trip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    Strict,
+    #[default]
+    Lenient,
+}
+
+pub fn parse_instruction_with_mode(
+    code: &[u8],
+    pc: usize,
+    mode: ParseMode,
+) -> Result<(&[u8], Instruction), InstructionParseError> {
+    parse_instruction_with_limits(code, pc, mode, ParseLimits::UNLIMITED)
+}
+
+// caps on what a single code array can make the parser allocate; without them
+// a switch claiming i32::MIN..=i32::MAX is only stopped by running out of input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    // entries of a tableswitch (high - low + 1) or pairs of a lookupswitch
+    pub max_switch_entries: u32,
+    // instructions yielded by an `InstructionIter`
+    pub max_instructions: u32,
+}
+
+impl ParseLimits {
+    pub const UNLIMITED: ParseLimits = ParseLimits {
+        max_switch_entries: u32::MAX,
+        max_instructions: u32::MAX,
+    };
+
+    // the most a code array within the 65535 byte limit of the spec can hold
+    pub const SPEC: ParseLimits = ParseLimits {
+        max_switch_entries: 16380,
+        max_instructions: 65535,
+    };
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits::UNLIMITED
+    }
+}
+
+pub fn parse_instruction_with_limits(
+    code: &[u8],
+    pc: usize,
+    mode: ParseMode,
+    limits: ParseLimits,
+) -> Result<(&[u8], Instruction), InstructionParseError> {
+    let input = code.get(pc..).ok_or(parser::ParseError::Eof)?;
+    parse_instruction_with_pc(input, pc, mode, limits)
+}
+
+fn check_zero(opcode: u8, value: u8, mode: ParseMode) -> Result<(), InstructionParseError> {
+    if mode == ParseMode::Strict && value != 0 {
+        return Err(InstructionParseError::NonZeroPadding(opcode));
+    }
+    Ok(())
+}
+
+// number of bytes between a switch opcode at `pc` and its 4-byte aligned operands
+fn switch_padding(pc: usize) -> usize {
+    (4 - (pc + 1) % 4) % 4
+}
+
+fn skip_switch_padding(input: &[u8], pc: usize) -> Result<&[u8], InstructionParseError> {
+    let (input, _) = bytes(input, switch_padding(pc))?;
+    Ok(input)
+}
+
+// rejects switches over the limit, and fails early when the input can't hold
+// `entries` entries of `entry_size` bytes instead of reading up to its end
+fn check_switch_entries(
+    input: &[u8],
+    entries: u64,
+    entry_size: u64,
+    limits: ParseLimits,
+) -> Result<(), InstructionParseError> {
+    if entries > limits.max_switch_entries as u64 {
+        return Err(InstructionParseError::SwitchTooLarge(entries));
+    }
+    if entries * entry_size > input.len() as u64 {
+        return Err(parser::ParseError::Eof.into());
+    }
+    Ok(())
+}
+
+fn parse_tableswitch(
+    input: &[u8],
+    pc: usize,
+    limits: ParseLimits,
+) -> Result<(&[u8], Instruction), InstructionParseError> {
+    let input = skip_switch_padding(input, pc)?;
+    let (input, default) = be_i32(input)?;
+    let (input, low) = be_i32(input)?;
+    let (input, high) = be_i32(input)?;
+    if low > high {
+        return Err(InstructionParseError::InvalidSwitchBounds(low, high));
+    }
+    let entries = (high as i64 - low as i64 + 1) as u64;
+    check_switch_entries(input, entries, 4, limits)?;
+    let mut offsets = Vec::with_capacity(entries as usize);
+    let mut input = input;
+    for _ in low..=high {
+        let (new_input, offset) = be_i32(input)?;
+        input = new_input;
+        offsets.push(offset);
+    }
+    Ok((input, Instruction::Tableswitch(default, low, high, offsets)))
+}
+
+fn parse_lookupswitch(
+    input: &[u8],
+    pc: usize,
+    limits: ParseLimits,
+) -> Result<(&[u8], Instruction), InstructionParseError> {
+    let input = skip_switch_padding(input, pc)?;
+    let (input, default) = be_i32(input)?;
+    let (input, npairs) = be_i32(input)?;
+    if npairs < 0 {
+        return Err(InstructionParseError::InvalidLookupswitchPairs(npairs));
+    }
+    check_switch_entries(input, npairs as u64, 8, limits)?;
+    let mut pairs = Vec::with_capacity(npairs as usize);
+    let mut input = input;
+    for _ in 0..npairs {
+        let (new_input, key) = be_i32(input)?;
+        let (new_input, offset) = be_i32(new_input)?;
+        input = new_input;
+        pairs.push((key, offset));
+    }
+    Ok((input, Instruction::Lookupswitch(default, pairs)))
+}
+
+fn parse_instruction_with_pc(
+    input: &[u8],
+    pc: usize,
+    mode: ParseMode,
+    limits: ParseLimits,
+) -> Result<(&[u8], Instruction), InstructionParseError> {
     let (input, opcode) = be_u8(input)?;
     match opcode {
         0x32 => Ok((input, Instruction::Aaload)),
@@ -452,17 +1257,18 @@ pub fn parse_instruction(input: &[u8]) -> Result<(&[u8], Instruction), Instructi
         }
         0xba => {
             let (input, index) = be_u16(input)?;
-            let (input, _zero1) = be_u8(input)?;
-            let (input, _zero2) = be_u8(input)?;
-            // TODO: Check that zero1 and zero2 are zero
-            Ok((input, Instruction::Invokedynamic(index, 0, 0)))
+            let (input, zero1) = be_u8(input)?;
+            let (input, zero2) = be_u8(input)?;
+            check_zero(opcode, zero1, mode)?;
+            check_zero(opcode, zero2, mode)?;
+            Ok((input, Instruction::Invokedynamic(index, zero1, zero2)))
         }
         0xb9 => {
             let (input, index) = be_u16(input)?;
             let (input, count) = be_u8(input)?;
-            let (input, _zero) = be_u8(input)?;
-            // TODO: Check that zero is zero
-            Ok((input, Instruction::Invokeinterface(index, count, 0)))
+            let (input, zero) = be_u8(input)?;
+            check_zero(opcode, zero, mode)?;
+            Ok((input, Instruction::Invokeinterface(index, count, zero)))
         }
         0xb7 => {
             let (input, index) = be_u16(input)?;
@@ -533,10 +1339,7 @@ pub fn parse_instruction(input: &[u8]) -> Result<(&[u8], Instruction), Instructi
         0x21 => Ok((input, Instruction::Lload3)),
         0x69 => Ok((input, Instruction::Lmul)),
         0x75 => Ok((input, Instruction::Lneg)),
-        0xab => {
-            // 正しく実装するためにはpaddingのためにコードの先頭からのオフセットが必要
-            unimplemented!("lookupswitch")
-        }
+        0xab => parse_lookupswitch(input, pc, limits),
         0x81 => Ok((input, Instruction::Lor)),
         0x71 => Ok((input, Instruction::Lrem)),
         0xad => Ok((input, Instruction::Lreturn)),
@@ -591,10 +1394,7 @@ pub fn parse_instruction(input: &[u8]) -> Result<(&[u8], Instruction), Instructi
             Ok((input, Instruction::Sipush(byte)))
         }
         0x5f => Ok((input, Instruction::Swap)),
-        0xaa => {
-            // 正しく実装するためにはpaddingのためにコードの先頭からのオフセットが必要
-            unimplemented!("tableswitch")
-        }
+        0xaa => parse_tableswitch(input, pc, limits),
         0xc4 => match parse_instruction(input) {
             Ok((_, Instruction::Iload(_))) => {
                 let (input, _) = be_u8(input)?;
@@ -666,6 +1466,96 @@ pub fn parse_instruction(input: &[u8]) -> Result<(&[u8], Instruction), Instructi
     }
 }
 
+pub struct InstructionIter<'a> {
+    code: &'a [u8],
+    pc: usize,
+    mode: ParseMode,
+    limits: ParseLimits,
+    count: u32,
+    previous: Option<(u32, u8)>,
+    recover: bool,
+    failed: bool,
+}
+
+impl<'a> InstructionIter<'a> {
+    pub fn new(code: &'a [u8]) -> Self {
+        Self::with_mode(code, ParseMode::Lenient)
+    }
+
+    pub fn with_mode(code: &'a [u8], mode: ParseMode) -> Self {
+        Self::with_limits(code, mode, ParseLimits::UNLIMITED)
+    }
+
+    pub fn with_limits(code: &'a [u8], mode: ParseMode, limits: ParseLimits) -> Self {
+        InstructionIter {
+            code,
+            pc: 0,
+            mode,
+            limits,
+            count: 0,
+            previous: None,
+            recover: false,
+            failed: false,
+        }
+    }
+
+    // best effort mode: after an error, skip the byte at its pc and keep going,
+    // so every error in a corrupted code array is yielded instead of just the
+    // first; instructions found after an error may be misaligned
+    pub fn recovering(mut self) -> Self {
+        self.recover = true;
+        self
+    }
+
+    pub fn pc(&self) -> u32 {
+        self.pc as u32
+    }
+}
+
+impl<'a> Iterator for InstructionIter<'a> {
+    type Item = Result<(u32, Instruction), InstructionParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.pc >= self.code.len() {
+            return None;
+        }
+        let result = if self.count >= self.limits.max_instructions {
+            Err(InstructionParseError::TooManyInstructions(self.count))
+        } else {
+            parse_instruction_with_limits(self.code, self.pc, self.mode, self.limits)
+        };
+        match result {
+            Ok((rest, instruction)) => {
+                let pc = self.pc as u32;
+                self.count += 1;
+                self.previous = Some((pc, self.code[self.pc]));
+                self.pc = self.code.len() - rest.len();
+                Some(Ok((pc, instruction)))
+            }
+            Err(e) => {
+                let pc = self.pc;
+                if self.recover && !matches!(e, InstructionParseError::TooManyInstructions(_)) {
+                    self.pc += 1;
+                } else {
+                    self.failed = true;
+                }
+                Some(Err(InstructionParseError::AtOffset {
+                    pc: pc as u32,
+                    opcode: self.code[pc],
+                    previous: self.previous,
+                    error: Box::new(e),
+                }))
+            }
+        }
+    }
+}
+
+impl<'a> std::iter::FusedIterator for InstructionIter<'a> {}
+
+pub fn disassemble(code: &[u8]) -> InstructionIter {
+    InstructionIter::new(code)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1313,4 +2203,453 @@ mod tests {
 
         assert_eq!(input.len(), 0);
     }
+
+    #[test]
+    fn test_parse_tableswitch() {
+        let code = &[
+            0x00, // nop
+            0xaa, // tableswitch
+            0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x20, // default
+            0x00, 0x00, 0x00, 0x01, // low
+            0x00, 0x00, 0x00, 0x03, // high
+            0x00, 0x00, 0x00, 0x1c, // 1
+            0xff, 0xff, 0xff, 0xfe, // 2
+            0x00, 0x00, 0x00, 0x24, // 3
+            0xb1, // return
+        ];
+        let (rest, instruction) = parse_instruction_at(code, 1).unwrap();
+        assert_eq!(rest, &[0xb1]);
+        assert_eq!(
+            instruction,
+            Instruction::Tableswitch(0x20, 1, 3, vec![0x1c, -2, 0x24])
+        );
+
+        // no padding when the operands are already aligned
+        let code = &[
+            0x00, 0x00, 0x00, // nop
+            0xaa, // tableswitch
+            0x00, 0x00, 0x00, 0x10, // default
+            0x00, 0x00, 0x00, 0x00, // low
+            0x00, 0x00, 0x00, 0x00, // high
+            0x00, 0x00, 0x00, 0x14, // 0
+        ];
+        let (rest, instruction) = parse_instruction_at(code, 3).unwrap();
+        assert_eq!(rest, &[]);
+        assert_eq!(
+            instruction,
+            Instruction::Tableswitch(0x10, 0, 0, vec![0x14])
+        );
+
+        let code = &[
+            0xaa, // tableswitch
+            0x00, 0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x10, // default
+            0x00, 0x00, 0x00, 0x02, // low
+            0x00, 0x00, 0x00, 0x01, // high
+        ];
+        assert_eq!(
+            parse_instruction(code),
+            Err(InstructionParseError::InvalidSwitchBounds(2, 1))
+        );
+
+        let code = &[
+            0xaa, // tableswitch
+            0x00, 0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x10, // default
+            0x00, 0x00, 0x00, 0x00, // low
+            0x00, 0x00, 0x00, 0x01, // high
+            0x00, 0x00, 0x00, 0x14, // 0
+        ];
+        assert_eq!(
+            parse_instruction(code),
+            Err(InstructionParseError::ParseError(parser::ParseError::Eof))
+        );
+    }
+
+    #[test]
+    fn test_parse_lookupswitch() {
+        let code = &[
+            0x00, 0x00, // nop
+            0xab, // lookupswitch
+            0x00, // padding
+            0x00, 0x00, 0x00, 0x2a, // default
+            0x00, 0x00, 0x00, 0x02, // npairs
+            0xff, 0xff, 0xff, 0xff, // -1
+            0x00, 0x00, 0x00, 0x1a, //
+            0x00, 0x00, 0x03, 0xe8, // 1000
+            0x00, 0x00, 0x00, 0x22, //
+            0xb1, // return
+        ];
+        let (rest, instruction) = parse_instruction_at(code, 2).unwrap();
+        assert_eq!(rest, &[0xb1]);
+        assert_eq!(
+            instruction,
+            Instruction::Lookupswitch(0x2a, vec![(-1, 0x1a), (1000, 0x22)])
+        );
+
+        let code = &[
+            0xab, // lookupswitch
+            0x00, 0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x08, // default
+            0x00, 0x00, 0x00, 0x00, // npairs
+        ];
+        let (rest, instruction) = parse_instruction(code).unwrap();
+        assert_eq!(rest, &[]);
+        assert_eq!(instruction, Instruction::Lookupswitch(8, vec![]));
+
+        let code = &[
+            0xab, // lookupswitch
+            0x00, 0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x08, // default
+            0xff, 0xff, 0xff, 0xff, // npairs
+        ];
+        assert_eq!(
+            parse_instruction(code),
+            Err(InstructionParseError::InvalidLookupswitchPairs(-1))
+        );
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let code = &[
+            0x1a, // iload_0
+            0xab, // lookupswitch
+            0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x15, // default
+            0x00, 0x00, 0x00, 0x01, // npairs
+            0x00, 0x00, 0x00, 0x05, // 5
+            0x00, 0x00, 0x00, 0x13, //
+            0x04, // iconst_1
+            0xac, // ireturn
+            0x03, // iconst_0
+            0xac, // ireturn
+        ];
+        let instructions = disassemble(code).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            instructions,
+            vec![
+                (0, Instruction::Iload0),
+                (1, Instruction::Lookupswitch(0x15, vec![(5, 0x13)])),
+                (20, Instruction::Iconst1),
+                (21, Instruction::Ireturn),
+                (22, Instruction::Iconst0),
+                (23, Instruction::Ireturn),
+            ]
+        );
+
+        let mut iter = disassemble(&[0x04, 0xff, 0x04]);
+        assert_eq!(iter.next(), Some(Ok((0, Instruction::Iconst1))));
+        assert_eq!(
+            iter.next(),
+            Some(Err(InstructionParseError::AtOffset {
+                pc: 1,
+                opcode: 0xff,
+                previous: Some((0, 0x04)),
+                error: Box::new(InstructionParseError::UnknownInstruction(0xff)),
+            }))
+        );
+        assert_eq!(iter.next(), None);
+        assert_eq!(disassemble(&[]).next(), None);
+    }
+
+    #[test]
+    fn test_disassemble_switches() {
+        use crate::class::{parse_classfile, Attribute};
+
+        let data = include_bytes!("../../../../java/Switches.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let mut tableswitches = 0;
+        let mut lookupswitches = 0;
+        for method in &classfile.methods {
+            for attribute in &method.attributes {
+                if let Attribute::Code(code) = attribute {
+                    for result in disassemble(&code.code) {
+                        match result.unwrap() {
+                            (_, Instruction::Tableswitch(_, low, high, offsets)) => {
+                                assert_eq!(offsets.len() as i32, high - low + 1);
+                                tableswitches += 1;
+                            }
+                            (_, Instruction::Lookupswitch(_, pairs)) => {
+                                assert!(!pairs.is_empty());
+                                lookupswitches += 1;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(tableswitches, 2);
+        assert_eq!(lookupswitches, 2);
+    }
+
+    #[test]
+    fn test_encode() {
+        let code: &[u8] = &[
+            0x10, 0xfe, // bipush -2
+            0x11, 0x80, 0x00, // sipush -32768
+            0x84, 0x01, 0xff, // iinc 1, -1
+            0xc4, 0x84, 0x01, 0x00, 0x00, 0x10, // wide iinc 256, 16
+            0xc4, 0x15, 0x01, 0x00, // wide iload 256
+            0xb9, 0x00, 0x05, 0x02, 0x00, // invokeinterface #5, 2
+            0xba, 0x00, 0x06, 0x00, 0x00, // invokedynamic #6
+            0xc5, 0x00, 0x07, 0x02, // multianewarray #7, 2
+            0xa7, 0xff, 0xf0, // goto -16
+            0xc8, 0x00, 0x01, 0x00, 0x00, // goto_w 65536
+            0xaa, // tableswitch
+            0x00, 0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x10, // default
+            0x00, 0x00, 0x00, 0x01, // low
+            0x00, 0x00, 0x00, 0x02, // high
+            0x00, 0x00, 0x00, 0x14, // 1
+            0x00, 0x00, 0x00, 0x18, // 2
+            0xab, // lookupswitch
+            0x00, 0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x08, // default
+            0x00, 0x00, 0x00, 0x01, // npairs
+            0x00, 0x00, 0x00, 0x2a, // 42
+            0x00, 0x00, 0x00, 0x0c, //
+            0xb1, // return
+        ];
+        let mut out = Vec::new();
+        for result in disassemble(code) {
+            let (pc, instruction) = result.unwrap();
+            assert_eq!(pc as usize, out.len());
+            instruction.encode(pc, &mut out);
+        }
+        assert_eq!(out, code);
+    }
+
+    #[test]
+    fn test_encode_switch_padding() {
+        let instruction = Instruction::Tableswitch(8, 0, 0, vec![12]);
+        for pc in 0..4 {
+            let mut out = vec![0; pc];
+            instruction.encode(pc as u32, &mut out);
+            assert_eq!(out.len() % 4, 0);
+            assert_eq!(
+                parse_instruction_at(&out, pc),
+                Ok((&[][..], instruction.clone()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_roundtrip_classfile() {
+        use crate::class::{parse_classfile, Attribute};
+
+        for data in [
+            &include_bytes!("../../../../java/HelloWorld.class")[..],
+            &include_bytes!("../../../../java/Frames.class")[..],
+            &include_bytes!("../../../../java/Switches.class")[..],
+        ] {
+            let (_, classfile) = parse_classfile(data).unwrap();
+            for method in &classfile.methods {
+                for attribute in &method.attributes {
+                    if let Attribute::Code(code) = attribute {
+                        let mut out = Vec::new();
+                        for result in disassemble(&code.code) {
+                            let (pc, instruction) = result.unwrap();
+                            instruction.encode(pc, &mut out);
+                        }
+                        assert_eq!(out, *code.code);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_metadata() {
+        assert_eq!(Instruction::AconstNull.mnemonic(), "aconst_null");
+        assert_eq!(Instruction::Aload0.mnemonic(), "aload_0");
+        assert_eq!(Instruction::IconstM1.mnemonic(), "iconst_m1");
+        assert_eq!(Instruction::I2l.mnemonic(), "i2l");
+        assert_eq!(Instruction::Dup2X1.mnemonic(), "dup2_x1");
+        assert_eq!(Instruction::Pop2.mnemonic(), "pop2");
+        assert_eq!(Instruction::Iconst5.mnemonic(), "iconst_5");
+        assert_eq!(Instruction::IfIcmpge(3).mnemonic(), "if_icmpge");
+        assert_eq!(Instruction::Ldc2W(1).mnemonic(), "ldc2_w");
+        assert_eq!(Instruction::WideIinc(256, 1).mnemonic(), "iinc_w");
+        assert_eq!(Instruction::Invokevirtual(1).opcode(), 0xb6);
+        assert_eq!(Instruction::WideAload(256).opcode(), 0xc4);
+        assert_eq!(Instruction::Lookupswitch(0, vec![(1, 2)]).byte_len(0), 20);
+        assert_eq!(Instruction::Lookupswitch(0, vec![(1, 2)]).byte_len(3), 17);
+
+        let data = include_bytes!("../../../../java/Switches.class");
+        let (_, classfile) = crate::class::parse_classfile(data).unwrap();
+        for method in &classfile.methods {
+            for attribute in &method.attributes {
+                if let crate::class::Attribute::Code(code) = attribute {
+                    for result in disassemble(&code.code) {
+                        let (pc, instruction) = result.unwrap();
+                        let mut out = Vec::new();
+                        instruction.encode(pc, &mut out);
+                        assert_eq!(instruction.byte_len(pc), out.len());
+                        assert_eq!(instruction.opcode(), code.code[pc as usize]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        let code = &[
+            0xba, 0x00, 0x02, 0x00, 0x01, // invokedynamic #2 with a stray byte
+            0xb9, 0x00, 0x03, 0x01, 0x07, // invokeinterface #3, 1 with a stray byte
+        ];
+        let instructions = disassemble(code).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            instructions,
+            vec![
+                (0, Instruction::Invokedynamic(2, 0, 1)),
+                (5, Instruction::Invokeinterface(3, 1, 7)),
+            ]
+        );
+        let mut out = Vec::new();
+        for (pc, instruction) in &instructions {
+            instruction.encode(*pc, &mut out);
+        }
+        assert_eq!(out, code);
+
+        assert_eq!(
+            parse_instruction_with_mode(code, 0, ParseMode::Strict),
+            Err(InstructionParseError::NonZeroPadding(0xba))
+        );
+        assert_eq!(
+            parse_instruction_with_mode(code, 5, ParseMode::Strict),
+            Err(InstructionParseError::NonZeroPadding(0xb9))
+        );
+        assert_eq!(
+            parse_instruction_with_mode(&[0xb9, 0x00, 0x03, 0x01, 0x00], 0, ParseMode::Strict),
+            Ok((&[][..], Instruction::Invokeinterface(3, 1, 0)))
+        );
+        let mut iter = InstructionIter::with_mode(code, ParseMode::Strict);
+        let error = iter.next().unwrap().unwrap_err();
+        assert_eq!(error.pc(), Some(0));
+        assert_eq!(error.root(), &InstructionParseError::NonZeroPadding(0xba));
+        assert_eq!(
+            error.root().to_string(),
+            "non-zero reserved operand byte in 0xba (invokeinterface/invokedynamic)"
+        );
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_disassemble_truncated() {
+        let code = &[
+            0x2a, // aload_0
+            0xb6, 0x00, // invokevirtual, missing the low index byte
+        ];
+        let error = disassemble(code)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_err();
+        assert_eq!(
+            error,
+            InstructionParseError::AtOffset {
+                pc: 1,
+                opcode: 0xb6,
+                previous: Some((0, 0x2a)),
+                error: Box::new(InstructionParseError::ParseError(parser::ParseError::Eof)),
+            }
+        );
+        assert_eq!(
+            error.root(),
+            &InstructionParseError::ParseError(parser::ParseError::Eof)
+        );
+        assert_eq!(parse_instruction(&[0xff]).unwrap_err().pc(), None);
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        // tableswitch -0x7fffffff..=0x7fffffff with no entries present
+        let code = &[
+            0xaa, 0x00, 0x00, 0x00, // tableswitch + padding
+            0x00, 0x00, 0x00, 0x00, // default
+            0x80, 0x00, 0x00, 0x01, // low
+            0x7f, 0xff, 0xff, 0xff, // high
+        ];
+        assert_eq!(
+            parse_instruction(code),
+            Err(InstructionParseError::ParseError(parser::ParseError::Eof))
+        );
+        assert_eq!(
+            parse_instruction_with_limits(code, 0, ParseMode::Lenient, ParseLimits::SPEC),
+            Err(InstructionParseError::SwitchTooLarge(u32::MAX as u64))
+        );
+
+        let code = &[
+            0xab, 0x00, 0x00, 0x00, // lookupswitch + padding
+            0x00, 0x00, 0x00, 0x00, // default
+            0x00, 0x00, 0x00, 0x02, // npairs
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x10, // 1: 16
+            0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x10, // 2: 16
+        ];
+        let limits = ParseLimits {
+            max_switch_entries: 1,
+            ..ParseLimits::UNLIMITED
+        };
+        assert_eq!(
+            parse_instruction_with_limits(code, 0, ParseMode::Lenient, limits),
+            Err(InstructionParseError::SwitchTooLarge(2))
+        );
+        assert!(
+            parse_instruction_with_limits(code, 0, ParseMode::Lenient, ParseLimits::SPEC).is_ok()
+        );
+
+        let limits = ParseLimits {
+            max_instructions: 2,
+            ..ParseLimits::UNLIMITED
+        };
+        let mut iter = InstructionIter::with_limits(&[0x00; 3], ParseMode::Lenient, limits);
+        assert_eq!(iter.next(), Some(Ok((0, Instruction::Nop))));
+        assert_eq!(iter.next(), Some(Ok((1, Instruction::Nop))));
+        let error = iter.next().unwrap().unwrap_err();
+        assert_eq!(error.pc(), Some(2));
+        assert_eq!(error.root(), &InstructionParseError::TooManyInstructions(2));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_disassemble_recovering() {
+        let code = &[
+            0x2a, // aload_0
+            0xff, // impdep2
+            0xcb, // unused opcode
+            0x10, 0x05, // bipush 5
+            0xb6, 0x00, // invokevirtual, missing the low index byte
+        ];
+        // the stray index byte decodes as a nop once the invokevirtual is skipped
+        let results = disassemble(code).recovering().collect::<Vec<_>>();
+        let errors = results
+            .iter()
+            .filter_map(|result| result.as_ref().err())
+            .map(|error| (error.pc().unwrap(), error.root()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                (1, &InstructionParseError::UnknownInstruction(0xff)),
+                (2, &InstructionParseError::UnknownInstruction(0xcb)),
+                (
+                    5,
+                    &InstructionParseError::ParseError(parser::ParseError::Eof)
+                ),
+            ]
+        );
+        let instructions = results
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            instructions,
+            [
+                (0, Instruction::Aload0),
+                (3, Instruction::Bipush(5)),
+                (6, Instruction::Nop)
+            ]
+        );
+    }
 }
@@ -0,0 +1,312 @@
+use super::instruction::Instruction;
+
+// broad instruction categories, roughly following the grouping in JVMS §2.11
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstructionKind {
+    Nop,
+    // aconst_null, iconst_*, bipush, sipush, ldc...
+    Constant,
+    // local variable loads
+    Load,
+    // local variable stores
+    Store,
+    // xaload
+    ArrayLoad,
+    // xastore
+    ArrayStore,
+    // numeric operations, including iinc
+    Arithmetic,
+    // i2l, d2f...
+    Conversion,
+    // lcmp, fcmpl, fcmpg, dcmpl, dcmpg
+    Comparison,
+    // pop, dup, swap...
+    StackManip,
+    // goto and the conditional branches
+    Branch,
+    // tableswitch, lookupswitch
+    Switch,
+    // jsr, ret
+    Subroutine,
+    Return,
+    Throw,
+    // getfield, putfield, getstatic, putstatic
+    FieldAccess,
+    Invoke,
+    // new, newarray, anewarray, multianewarray, arraylength
+    Object,
+    // checkcast, instanceof
+    TypeCheck,
+    // monitorenter, monitorexit
+    Monitor,
+}
+
+impl Instruction {
+    pub fn kind(&self) -> InstructionKind {
+        match self {
+            Instruction::Nop => InstructionKind::Nop,
+            Instruction::AconstNull
+            | Instruction::IconstM1
+            | Instruction::Iconst0
+            | Instruction::Iconst1
+            | Instruction::Iconst2
+            | Instruction::Iconst3
+            | Instruction::Iconst4
+            | Instruction::Iconst5
+            | Instruction::Lconst0
+            | Instruction::Lconst1
+            | Instruction::Fconst0
+            | Instruction::Fconst1
+            | Instruction::Fconst2
+            | Instruction::Dconst0
+            | Instruction::Dconst1
+            | Instruction::Bipush(_)
+            | Instruction::Sipush(_)
+            | Instruction::Ldc(_)
+            | Instruction::LdcW(_)
+            | Instruction::Ldc2W(_) => InstructionKind::Constant,
+            Instruction::Iload(_)
+            | Instruction::Iload0
+            | Instruction::Iload1
+            | Instruction::Iload2
+            | Instruction::Iload3
+            | Instruction::Lload(_)
+            | Instruction::Lload0
+            | Instruction::Lload1
+            | Instruction::Lload2
+            | Instruction::Lload3
+            | Instruction::Fload(_)
+            | Instruction::Fload0
+            | Instruction::Fload1
+            | Instruction::Fload2
+            | Instruction::Fload3
+            | Instruction::Dload(_)
+            | Instruction::Dload0
+            | Instruction::Dload1
+            | Instruction::Dload2
+            | Instruction::Dload3
+            | Instruction::Aload(_)
+            | Instruction::Aload0
+            | Instruction::Aload1
+            | Instruction::Aload2
+            | Instruction::Aload3
+            | Instruction::WideIload(_)
+            | Instruction::WideLload(_)
+            | Instruction::WideFload(_)
+            | Instruction::WideDload(_)
+            | Instruction::WideAload(_) => InstructionKind::Load,
+            Instruction::Istore(_)
+            | Instruction::Istore0
+            | Instruction::Istore1
+            | Instruction::Istore2
+            | Instruction::Istore3
+            | Instruction::Lstore(_)
+            | Instruction::Lstore0
+            | Instruction::Lstore1
+            | Instruction::Lstore2
+            | Instruction::Lstore3
+            | Instruction::Fstore(_)
+            | Instruction::Fstore0
+            | Instruction::Fstore1
+            | Instruction::Fstore2
+            | Instruction::Fstore3
+            | Instruction::Dstore(_)
+            | Instruction::Dstore0
+            | Instruction::Dstore1
+            | Instruction::Dstore2
+            | Instruction::Dstore3
+            | Instruction::Astore(_)
+            | Instruction::Astore0
+            | Instruction::Astore1
+            | Instruction::Astore2
+            | Instruction::Astore3
+            | Instruction::WideIstore(_)
+            | Instruction::WideLstore(_)
+            | Instruction::WideFstore(_)
+            | Instruction::WideDstore(_)
+            | Instruction::WideAstore(_) => InstructionKind::Store,
+            Instruction::Iaload
+            | Instruction::Laload
+            | Instruction::Faload
+            | Instruction::Daload
+            | Instruction::Aaload
+            | Instruction::Baload
+            | Instruction::Caload
+            | Instruction::Saload => InstructionKind::ArrayLoad,
+            Instruction::Iastore
+            | Instruction::Lastore
+            | Instruction::Fastore
+            | Instruction::Dastore
+            | Instruction::Aastore
+            | Instruction::Bastore
+            | Instruction::Castore
+            | Instruction::Sastore => InstructionKind::ArrayStore,
+            Instruction::Iadd
+            | Instruction::Ladd
+            | Instruction::Fadd
+            | Instruction::Dadd
+            | Instruction::Isub
+            | Instruction::Lsub
+            | Instruction::Fsub
+            | Instruction::Dsub
+            | Instruction::Imul
+            | Instruction::Lmul
+            | Instruction::Fmul
+            | Instruction::Dmul
+            | Instruction::Idiv
+            | Instruction::Ldiv
+            | Instruction::Fdiv
+            | Instruction::Ddiv
+            | Instruction::Irem
+            | Instruction::Lrem
+            | Instruction::Frem
+            | Instruction::Drem
+            | Instruction::Ineg
+            | Instruction::Lneg
+            | Instruction::Fneg
+            | Instruction::Dneg
+            | Instruction::Ishl
+            | Instruction::Lshl
+            | Instruction::Ishr
+            | Instruction::Lshr
+            | Instruction::Iushr
+            | Instruction::Lushr
+            | Instruction::Iand
+            | Instruction::Land
+            | Instruction::Ior
+            | Instruction::Lor
+            | Instruction::Ixor
+            | Instruction::Lxor
+            | Instruction::Iinc(..)
+            | Instruction::WideIinc(..) => InstructionKind::Arithmetic,
+            Instruction::I2l
+            | Instruction::I2f
+            | Instruction::I2d
+            | Instruction::L2i
+            | Instruction::L2f
+            | Instruction::L2d
+            | Instruction::F2i
+            | Instruction::F2l
+            | Instruction::F2d
+            | Instruction::D2i
+            | Instruction::D2l
+            | Instruction::D2f
+            | Instruction::I2b
+            | Instruction::I2c
+            | Instruction::I2s => InstructionKind::Conversion,
+            Instruction::Lcmp
+            | Instruction::Fcmpl
+            | Instruction::Fcmpg
+            | Instruction::Dcmpl
+            | Instruction::Dcmpg => InstructionKind::Comparison,
+            Instruction::Pop
+            | Instruction::Pop2
+            | Instruction::Dup
+            | Instruction::DupX1
+            | Instruction::DupX2
+            | Instruction::Dup2
+            | Instruction::Dup2X1
+            | Instruction::Dup2X2
+            | Instruction::Swap => InstructionKind::StackManip,
+            Instruction::Ifeq(_)
+            | Instruction::Ifne(_)
+            | Instruction::Iflt(_)
+            | Instruction::Ifge(_)
+            | Instruction::Ifgt(_)
+            | Instruction::Ifle(_)
+            | Instruction::IfIcmpeq(_)
+            | Instruction::IfIcmpne(_)
+            | Instruction::IfIcmplt(_)
+            | Instruction::IfIcmpge(_)
+            | Instruction::IfIcmpgt(_)
+            | Instruction::IfIcmple(_)
+            | Instruction::IfAcmpeq(_)
+            | Instruction::IfAcmpne(_)
+            | Instruction::Ifnull(_)
+            | Instruction::Ifnonnull(_)
+            | Instruction::Goto(_)
+            | Instruction::GotoW(_) => InstructionKind::Branch,
+            Instruction::Tableswitch(..) | Instruction::Lookupswitch(..) => InstructionKind::Switch,
+            Instruction::Jsr(_)
+            | Instruction::JsrW(_)
+            | Instruction::Ret(_)
+            | Instruction::WideRet(_) => InstructionKind::Subroutine,
+            Instruction::Ireturn
+            | Instruction::Lreturn
+            | Instruction::Freturn
+            | Instruction::Dreturn
+            | Instruction::Areturn
+            | Instruction::Return => InstructionKind::Return,
+            Instruction::Athrow => InstructionKind::Throw,
+            Instruction::Getfield(_)
+            | Instruction::Putfield(_)
+            | Instruction::Getstatic(_)
+            | Instruction::Putstatic(_) => InstructionKind::FieldAccess,
+            Instruction::Invokevirtual(_)
+            | Instruction::Invokespecial(_)
+            | Instruction::Invokestatic(_)
+            | Instruction::Invokeinterface(..)
+            | Instruction::Invokedynamic(..) => InstructionKind::Invoke,
+            Instruction::New(_)
+            | Instruction::Newarray(_)
+            | Instruction::Anewarray(_)
+            | Instruction::Multianewarray(..)
+            | Instruction::Arraylength => InstructionKind::Object,
+            Instruction::Checkcast(_) | Instruction::Instanceof(_) => InstructionKind::TypeCheck,
+            Instruction::Monitorenter | Instruction::Monitorexit => InstructionKind::Monitor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::disassemble;
+    use crate::class::{parse_classfile, Attribute};
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(Instruction::Iconst0.kind(), InstructionKind::Constant);
+        assert_eq!(Instruction::WideAload(300).kind(), InstructionKind::Load);
+        assert_eq!(Instruction::Lstore2.kind(), InstructionKind::Store);
+        assert_eq!(Instruction::Iinc(1, 1).kind(), InstructionKind::Arithmetic);
+        assert_eq!(Instruction::Ifnull(3).kind(), InstructionKind::Branch);
+        assert_eq!(Instruction::WideRet(1).kind(), InstructionKind::Subroutine);
+        assert_eq!(
+            Instruction::Lookupswitch(0, vec![]).kind(),
+            InstructionKind::Switch
+        );
+        assert_eq!(Instruction::Arraylength.kind(), InstructionKind::Object);
+    }
+
+    #[test]
+    fn test_kind_switches() {
+        let data = include_bytes!("../../../../java/Switches.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let mut kinds = Vec::new();
+        for attribute in &classfile.methods[1].attributes {
+            if let Attribute::Code(code) = attribute {
+                for result in disassemble(&code.code) {
+                    kinds.push(result.unwrap().1.kind());
+                }
+            }
+        }
+        assert_eq!(
+            kinds,
+            [
+                InstructionKind::Load,
+                InstructionKind::Switch,
+                InstructionKind::Constant,
+                InstructionKind::Return,
+                InstructionKind::Constant,
+                InstructionKind::Return,
+                InstructionKind::Constant,
+                InstructionKind::Return,
+                InstructionKind::Constant,
+                InstructionKind::Return,
+                InstructionKind::Constant,
+                InstructionKind::Return,
+            ]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use super::instruction::Instruction;
+
+// an instruction whose branch targets are indices into the item list; an index
+// equal to the number of items refers to the end of the code. Items without
+// targets are emitted as they are.
+pub(super) struct Item {
+    pub instruction: Instruction,
+    pub targets: Vec<usize>,
+}
+
+pub(super) struct Layout {
+    pub instructions: Vec<(u32, Instruction)>,
+    // pc of every item, followed by the code length
+    pub pcs: Vec<u32>,
+}
+
+fn inverse_condition(instruction: &Instruction, offset: i16) -> Option<Instruction> {
+    let inverse = match instruction {
+        Instruction::Ifeq(_) => Instruction::Ifne(offset),
+        Instruction::Ifne(_) => Instruction::Ifeq(offset),
+        Instruction::Iflt(_) => Instruction::Ifge(offset),
+        Instruction::Ifge(_) => Instruction::Iflt(offset),
+        Instruction::Ifgt(_) => Instruction::Ifle(offset),
+        Instruction::Ifle(_) => Instruction::Ifgt(offset),
+        Instruction::IfIcmpeq(_) => Instruction::IfIcmpne(offset),
+        Instruction::IfIcmpne(_) => Instruction::IfIcmpeq(offset),
+        Instruction::IfIcmplt(_) => Instruction::IfIcmpge(offset),
+        Instruction::IfIcmpge(_) => Instruction::IfIcmplt(offset),
+        Instruction::IfIcmpgt(_) => Instruction::IfIcmple(offset),
+        Instruction::IfIcmple(_) => Instruction::IfIcmpgt(offset),
+        Instruction::IfAcmpeq(_) => Instruction::IfAcmpne(offset),
+        Instruction::IfAcmpne(_) => Instruction::IfAcmpeq(offset),
+        Instruction::Ifnull(_) => Instruction::Ifnonnull(offset),
+        Instruction::Ifnonnull(_) => Instruction::Ifnull(offset),
+        _ => return None,
+    };
+    Some(inverse)
+}
+
+fn with_short_offset(instruction: &Instruction, offset: i16) -> Instruction {
+    match instruction {
+        Instruction::Goto(_) => Instruction::Goto(offset),
+        Instruction::Jsr(_) => Instruction::Jsr(offset),
+        Instruction::Ifeq(_) => Instruction::Ifeq(offset),
+        Instruction::Ifne(_) => Instruction::Ifne(offset),
+        Instruction::Iflt(_) => Instruction::Iflt(offset),
+        Instruction::Ifge(_) => Instruction::Ifge(offset),
+        Instruction::Ifgt(_) => Instruction::Ifgt(offset),
+        Instruction::Ifle(_) => Instruction::Ifle(offset),
+        Instruction::IfIcmpeq(_) => Instruction::IfIcmpeq(offset),
+        Instruction::IfIcmpne(_) => Instruction::IfIcmpne(offset),
+        Instruction::IfIcmplt(_) => Instruction::IfIcmplt(offset),
+        Instruction::IfIcmpge(_) => Instruction::IfIcmpge(offset),
+        Instruction::IfIcmpgt(_) => Instruction::IfIcmpgt(offset),
+        Instruction::IfIcmple(_) => Instruction::IfIcmple(offset),
+        Instruction::IfAcmpeq(_) => Instruction::IfAcmpeq(offset),
+        Instruction::IfAcmpne(_) => Instruction::IfAcmpne(offset),
+        Instruction::Ifnull(_) => Instruction::Ifnull(offset),
+        Instruction::Ifnonnull(_) => Instruction::Ifnonnull(offset),
+        instruction => instruction.clone(),
+    }
+}
+
+fn with_wide_offset(instruction: &Instruction, offset: i32) -> Instruction {
+    match instruction {
+        Instruction::Jsr(_) | Instruction::JsrW(_) => Instruction::JsrW(offset),
+        _ => Instruction::GotoW(offset),
+    }
+}
+
+// goto/jsr become goto_w/jsr_w and conditional branches an inverted branch over
+// a goto_w when their target is out of i16 range
+fn long_len(instruction: &Instruction) -> u32 {
+    match instruction {
+        Instruction::Goto(_) | Instruction::Jsr(_) => 5,
+        _ => 8,
+    }
+}
+
+fn is_short_branch(item: &Item) -> bool {
+    item.targets.len() == 1
+        && item.instruction.branch_target(0).is_some()
+        && !matches!(
+            item.instruction,
+            Instruction::GotoW(_) | Instruction::JsrW(_)
+        )
+}
+
+pub(super) fn layout(items: Vec<Item>) -> Layout {
+    let mut long_form = vec![false; items.len()];
+    // widening only grows the code, so this settles after a few passes
+    let pcs = loop {
+        let mut pcs = Vec::with_capacity(items.len() + 1);
+        let mut pc = 0;
+        for (item, long_form) in items.iter().zip(&long_form) {
+            pcs.push(pc);
+            pc += if *long_form {
+                long_len(&item.instruction)
+            } else {
+                item.instruction.byte_len(pc) as u32
+            };
+        }
+        pcs.push(pc);
+
+        let mut widened = false;
+        for (i, item) in items.iter().enumerate() {
+            if long_form[i] || !is_short_branch(item) {
+                continue;
+            }
+            let offset = pcs[item.targets[0]] as i64 - pcs[i] as i64;
+            if i16::try_from(offset).is_err() {
+                long_form[i] = true;
+                widened = true;
+            }
+        }
+        if !widened {
+            break pcs;
+        }
+    };
+
+    let mut instructions = Vec::with_capacity(items.len());
+    for (i, item) in items.into_iter().enumerate() {
+        let pc = pcs[i];
+        let offset = |target: &usize| (pcs[*target] as i64 - pc as i64) as i32;
+        let instruction = match &item.instruction {
+            _ if item.targets.is_empty() => item.instruction,
+            Instruction::Tableswitch(_, low, high, _) => Instruction::Tableswitch(
+                offset(&item.targets[0]),
+                *low,
+                *high,
+                item.targets[1..].iter().map(offset).collect(),
+            ),
+            Instruction::Lookupswitch(_, pairs) => Instruction::Lookupswitch(
+                offset(&item.targets[0]),
+                pairs
+                    .iter()
+                    .zip(&item.targets[1..])
+                    .map(|((key, _), target)| (*key, offset(target)))
+                    .collect(),
+            ),
+            Instruction::GotoW(_) | Instruction::JsrW(_) => {
+                with_wide_offset(&item.instruction, offset(&item.targets[0]))
+            }
+            instruction if !long_form[i] => {
+                with_short_offset(instruction, offset(&item.targets[0]) as i16)
+            }
+            Instruction::Goto(_) | Instruction::Jsr(_) => {
+                with_wide_offset(&item.instruction, offset(&item.targets[0]))
+            }
+            instruction => {
+                // branch over the goto_w when the original condition is false
+                instructions.push((pc, inverse_condition(instruction, 8).unwrap()));
+                let offset = (pcs[item.targets[0]] as i64 - (pc + 3) as i64) as i32;
+                instructions.push((pc + 3, Instruction::GotoW(offset)));
+                continue;
+            }
+        };
+        instructions.push((pc, instruction));
+    }
+
+    Layout { instructions, pcs }
+}
@@ -0,0 +1,293 @@
+use super::instruction::Instruction;
+
+// the instruction families that come in shorthand (iload_0), short (iload 4)
+// and wide (wide iload 300) encodings
+#[derive(Debug, Clone, Copy)]
+enum LocalAccess {
+    Iload,
+    Lload,
+    Fload,
+    Dload,
+    Aload,
+    Istore,
+    Lstore,
+    Fstore,
+    Dstore,
+    Astore,
+    Ret,
+}
+
+impl LocalAccess {
+    fn of(instruction: &Instruction) -> Option<LocalAccess> {
+        let access = match instruction {
+            Instruction::Iload(_)
+            | Instruction::Iload0
+            | Instruction::Iload1
+            | Instruction::Iload2
+            | Instruction::Iload3
+            | Instruction::WideIload(_) => LocalAccess::Iload,
+            Instruction::Lload(_)
+            | Instruction::Lload0
+            | Instruction::Lload1
+            | Instruction::Lload2
+            | Instruction::Lload3
+            | Instruction::WideLload(_) => LocalAccess::Lload,
+            Instruction::Fload(_)
+            | Instruction::Fload0
+            | Instruction::Fload1
+            | Instruction::Fload2
+            | Instruction::Fload3
+            | Instruction::WideFload(_) => LocalAccess::Fload,
+            Instruction::Dload(_)
+            | Instruction::Dload0
+            | Instruction::Dload1
+            | Instruction::Dload2
+            | Instruction::Dload3
+            | Instruction::WideDload(_) => LocalAccess::Dload,
+            Instruction::Aload(_)
+            | Instruction::Aload0
+            | Instruction::Aload1
+            | Instruction::Aload2
+            | Instruction::Aload3
+            | Instruction::WideAload(_) => LocalAccess::Aload,
+            Instruction::Istore(_)
+            | Instruction::Istore0
+            | Instruction::Istore1
+            | Instruction::Istore2
+            | Instruction::Istore3
+            | Instruction::WideIstore(_) => LocalAccess::Istore,
+            Instruction::Lstore(_)
+            | Instruction::Lstore0
+            | Instruction::Lstore1
+            | Instruction::Lstore2
+            | Instruction::Lstore3
+            | Instruction::WideLstore(_) => LocalAccess::Lstore,
+            Instruction::Fstore(_)
+            | Instruction::Fstore0
+            | Instruction::Fstore1
+            | Instruction::Fstore2
+            | Instruction::Fstore3
+            | Instruction::WideFstore(_) => LocalAccess::Fstore,
+            Instruction::Dstore(_)
+            | Instruction::Dstore0
+            | Instruction::Dstore1
+            | Instruction::Dstore2
+            | Instruction::Dstore3
+            | Instruction::WideDstore(_) => LocalAccess::Dstore,
+            Instruction::Astore(_)
+            | Instruction::Astore0
+            | Instruction::Astore1
+            | Instruction::Astore2
+            | Instruction::Astore3
+            | Instruction::WideAstore(_) => LocalAccess::Astore,
+            Instruction::Ret(_) | Instruction::WideRet(_) => LocalAccess::Ret,
+            _ => return None,
+        };
+        Some(access)
+    }
+
+    fn wide(self, index: u16) -> Instruction {
+        match self {
+            LocalAccess::Iload => Instruction::WideIload(index),
+            LocalAccess::Lload => Instruction::WideLload(index),
+            LocalAccess::Fload => Instruction::WideFload(index),
+            LocalAccess::Dload => Instruction::WideDload(index),
+            LocalAccess::Aload => Instruction::WideAload(index),
+            LocalAccess::Istore => Instruction::WideIstore(index),
+            LocalAccess::Lstore => Instruction::WideLstore(index),
+            LocalAccess::Fstore => Instruction::WideFstore(index),
+            LocalAccess::Dstore => Instruction::WideDstore(index),
+            LocalAccess::Astore => Instruction::WideAstore(index),
+            LocalAccess::Ret => Instruction::WideRet(index),
+        }
+    }
+
+    fn short(self, index: u8) -> Instruction {
+        match self {
+            LocalAccess::Iload => Instruction::Iload(index),
+            LocalAccess::Lload => Instruction::Lload(index),
+            LocalAccess::Fload => Instruction::Fload(index),
+            LocalAccess::Dload => Instruction::Dload(index),
+            LocalAccess::Aload => Instruction::Aload(index),
+            LocalAccess::Istore => Instruction::Istore(index),
+            LocalAccess::Lstore => Instruction::Lstore(index),
+            LocalAccess::Fstore => Instruction::Fstore(index),
+            LocalAccess::Dstore => Instruction::Dstore(index),
+            LocalAccess::Astore => Instruction::Astore(index),
+            LocalAccess::Ret => Instruction::Ret(index),
+        }
+    }
+
+    fn shorthand(self, index: u8) -> Option<Instruction> {
+        let forms = match self {
+            LocalAccess::Iload => [
+                Instruction::Iload0,
+                Instruction::Iload1,
+                Instruction::Iload2,
+                Instruction::Iload3,
+            ],
+            LocalAccess::Lload => [
+                Instruction::Lload0,
+                Instruction::Lload1,
+                Instruction::Lload2,
+                Instruction::Lload3,
+            ],
+            LocalAccess::Fload => [
+                Instruction::Fload0,
+                Instruction::Fload1,
+                Instruction::Fload2,
+                Instruction::Fload3,
+            ],
+            LocalAccess::Dload => [
+                Instruction::Dload0,
+                Instruction::Dload1,
+                Instruction::Dload2,
+                Instruction::Dload3,
+            ],
+            LocalAccess::Aload => [
+                Instruction::Aload0,
+                Instruction::Aload1,
+                Instruction::Aload2,
+                Instruction::Aload3,
+            ],
+            LocalAccess::Istore => [
+                Instruction::Istore0,
+                Instruction::Istore1,
+                Instruction::Istore2,
+                Instruction::Istore3,
+            ],
+            LocalAccess::Lstore => [
+                Instruction::Lstore0,
+                Instruction::Lstore1,
+                Instruction::Lstore2,
+                Instruction::Lstore3,
+            ],
+            LocalAccess::Fstore => [
+                Instruction::Fstore0,
+                Instruction::Fstore1,
+                Instruction::Fstore2,
+                Instruction::Fstore3,
+            ],
+            LocalAccess::Dstore => [
+                Instruction::Dstore0,
+                Instruction::Dstore1,
+                Instruction::Dstore2,
+                Instruction::Dstore3,
+            ],
+            LocalAccess::Astore => [
+                Instruction::Astore0,
+                Instruction::Astore1,
+                Instruction::Astore2,
+                Instruction::Astore3,
+            ],
+            LocalAccess::Ret => return None,
+        };
+        forms.into_iter().nth(index as usize)
+    }
+}
+
+impl Instruction {
+    // the one form that can express any operand: wide for local variable
+    // instructions and iinc, ldc_w for ldc; others are returned unchanged
+    pub fn normalize(&self) -> Instruction {
+        if let Some(access) = LocalAccess::of(self) {
+            return access.wide(self.local_index().unwrap());
+        }
+        match self {
+            Instruction::Iinc(index, value) => Instruction::WideIinc(*index as u16, *value as i16),
+            Instruction::Ldc(index) => Instruction::LdcW(*index as u16),
+            instruction => instruction.clone(),
+        }
+    }
+
+    // the equivalent instruction with the smallest encoding; since the size can
+    // change, branch offsets around it need relocating afterwards
+    pub fn shrink(&self) -> Instruction {
+        if let Some(access) = LocalAccess::of(self) {
+            let index = self.local_index().unwrap();
+            return match u8::try_from(index) {
+                Ok(index) => access
+                    .shorthand(index)
+                    .unwrap_or_else(|| access.short(index)),
+                Err(_) => access.wide(index),
+            };
+        }
+        match self {
+            Instruction::WideIinc(index, value) => {
+                match (u8::try_from(*index), i8::try_from(*value)) {
+                    (Ok(index), Ok(value)) => Instruction::Iinc(index, value),
+                    _ => self.clone(),
+                }
+            }
+            Instruction::LdcW(index) => match u8::try_from(*index) {
+                Ok(index) => Instruction::Ldc(index),
+                Err(_) => self.clone(),
+            },
+            instruction => instruction.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(Instruction::Iload0.normalize(), Instruction::WideIload(0));
+        assert_eq!(
+            Instruction::Astore(7).normalize(),
+            Instruction::WideAstore(7)
+        );
+        assert_eq!(
+            Instruction::WideDload(300).normalize(),
+            Instruction::WideDload(300)
+        );
+        assert_eq!(Instruction::Ret(2).normalize(), Instruction::WideRet(2));
+        assert_eq!(
+            Instruction::Iinc(1, -1).normalize(),
+            Instruction::WideIinc(1, -1)
+        );
+        assert_eq!(Instruction::Ldc(3).normalize(), Instruction::LdcW(3));
+        assert_eq!(Instruction::Iadd.normalize(), Instruction::Iadd);
+    }
+
+    #[test]
+    fn test_shrink() {
+        assert_eq!(Instruction::WideIload(0).shrink(), Instruction::Iload0);
+        assert_eq!(Instruction::Lstore(3).shrink(), Instruction::Lstore3);
+        assert_eq!(Instruction::WideFload(4).shrink(), Instruction::Fload(4));
+        assert_eq!(
+            Instruction::WideAload(300).shrink(),
+            Instruction::WideAload(300)
+        );
+        assert_eq!(Instruction::WideRet(0).shrink(), Instruction::Ret(0));
+        assert_eq!(
+            Instruction::WideIinc(1, 127).shrink(),
+            Instruction::Iinc(1, 127)
+        );
+        assert_eq!(
+            Instruction::WideIinc(1, 128).shrink(),
+            Instruction::WideIinc(1, 128)
+        );
+        assert_eq!(Instruction::LdcW(255).shrink(), Instruction::Ldc(255));
+        assert_eq!(Instruction::LdcW(256).shrink(), Instruction::LdcW(256));
+    }
+
+    #[test]
+    fn test_normalize_shrink_roundtrip() {
+        let instructions = [
+            Instruction::Dstore2,
+            Instruction::Istore(200),
+            Instruction::WideLload(1000),
+            Instruction::Iinc(5, 5),
+            Instruction::Ldc(1),
+        ];
+        for instruction in instructions {
+            let normalized = instruction.normalize();
+            assert_eq!(normalized.local_index(), instruction.local_index());
+            assert_eq!(normalized.shrink(), instruction);
+            assert!(normalized.byte_len(0) >= instruction.byte_len(0));
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use super::instruction::Instruction;
+
+impl Instruction {
+    pub fn constant_pool_index(&self) -> Option<u16> {
+        match self {
+            Instruction::Ldc(index) => Some(*index as u16),
+            Instruction::Anewarray(index)
+            | Instruction::Checkcast(index)
+            | Instruction::Getfield(index)
+            | Instruction::Getstatic(index)
+            | Instruction::Instanceof(index)
+            | Instruction::Invokedynamic(index, _, _)
+            | Instruction::Invokeinterface(index, _, _)
+            | Instruction::Invokespecial(index)
+            | Instruction::Invokestatic(index)
+            | Instruction::Invokevirtual(index)
+            | Instruction::LdcW(index)
+            | Instruction::Ldc2W(index)
+            | Instruction::Multianewarray(index, _)
+            | Instruction::New(index)
+            | Instruction::Putfield(index)
+            | Instruction::Putstatic(index) => Some(*index),
+            _ => None,
+        }
+    }
+
+    // the same instruction referring to constant `index` instead; None if it
+    // has no constant operand, or for an ldc when `index` doesn't fit a byte
+    pub fn with_constant_pool_index(&self, index: u16) -> Option<Instruction> {
+        let instruction = match *self {
+            Instruction::Ldc(_) => Instruction::Ldc(u8::try_from(index).ok()?),
+            Instruction::Anewarray(_) => Instruction::Anewarray(index),
+            Instruction::Checkcast(_) => Instruction::Checkcast(index),
+            Instruction::Getfield(_) => Instruction::Getfield(index),
+            Instruction::Getstatic(_) => Instruction::Getstatic(index),
+            Instruction::Instanceof(_) => Instruction::Instanceof(index),
+            Instruction::Invokedynamic(_, zero1, zero2) => {
+                Instruction::Invokedynamic(index, zero1, zero2)
+            }
+            Instruction::Invokeinterface(_, count, zero) => {
+                Instruction::Invokeinterface(index, count, zero)
+            }
+            Instruction::Invokespecial(_) => Instruction::Invokespecial(index),
+            Instruction::Invokestatic(_) => Instruction::Invokestatic(index),
+            Instruction::Invokevirtual(_) => Instruction::Invokevirtual(index),
+            Instruction::LdcW(_) => Instruction::LdcW(index),
+            Instruction::Ldc2W(_) => Instruction::Ldc2W(index),
+            Instruction::Multianewarray(_, dimensions) => {
+                Instruction::Multianewarray(index, dimensions)
+            }
+            Instruction::New(_) => Instruction::New(index),
+            Instruction::Putfield(_) => Instruction::Putfield(index),
+            Instruction::Putstatic(_) => Instruction::Putstatic(index),
+            _ => return None,
+        };
+        Some(instruction)
+    }
+
+    // absolute target of a goto, jsr or if* instruction at `pc`; may be out of range
+    // for malformed code, so it is not narrowed to u32
+    pub fn branch_target(&self, pc: u32) -> Option<i64> {
+        let offset = match self {
+            Instruction::Goto(offset)
+            | Instruction::Jsr(offset)
+            | Instruction::IfAcmpeq(offset)
+            | Instruction::IfAcmpne(offset)
+            | Instruction::IfIcmpeq(offset)
+            | Instruction::IfIcmpne(offset)
+            | Instruction::IfIcmplt(offset)
+            | Instruction::IfIcmpge(offset)
+            | Instruction::IfIcmpgt(offset)
+            | Instruction::IfIcmple(offset)
+            | Instruction::Ifeq(offset)
+            | Instruction::Ifne(offset)
+            | Instruction::Iflt(offset)
+            | Instruction::Ifge(offset)
+            | Instruction::Ifgt(offset)
+            | Instruction::Ifle(offset)
+            | Instruction::Ifnonnull(offset)
+            | Instruction::Ifnull(offset) => *offset as i64,
+            Instruction::GotoW(offset) | Instruction::JsrW(offset) => *offset as i64,
+            _ => return None,
+        };
+        Some(pc as i64 + offset)
+    }
+
+    // every explicit jump target, including all switch cases and the default
+    pub fn branch_targets(&self, pc: u32) -> Vec<i64> {
+        let pc = pc as i64;
+        match self {
+            Instruction::Tableswitch(default, _, _, offsets) => std::iter::once(default)
+                .chain(offsets)
+                .map(|offset| pc + *offset as i64)
+                .collect(),
+            Instruction::Lookupswitch(default, pairs) => std::iter::once(default)
+                .chain(pairs.iter().map(|(_, offset)| offset))
+                .map(|offset| pc + *offset as i64)
+                .collect(),
+            _ => self.branch_target(pc as u32).into_iter().collect(),
+        }
+    }
+
+    pub fn local_index(&self) -> Option<u16> {
+        match self {
+            Instruction::Iload(index)
+            | Instruction::Istore(index)
+            | Instruction::Fload(index)
+            | Instruction::Fstore(index)
+            | Instruction::Aload(index)
+            | Instruction::Astore(index)
+            | Instruction::Lload(index)
+            | Instruction::Lstore(index)
+            | Instruction::Dload(index)
+            | Instruction::Dstore(index)
+            | Instruction::Iinc(index, _)
+            | Instruction::Ret(index) => Some(*index as u16),
+            Instruction::WideIload(index)
+            | Instruction::WideIstore(index)
+            | Instruction::WideFload(index)
+            | Instruction::WideFstore(index)
+            | Instruction::WideAload(index)
+            | Instruction::WideAstore(index)
+            | Instruction::WideLload(index)
+            | Instruction::WideLstore(index)
+            | Instruction::WideDload(index)
+            | Instruction::WideDstore(index)
+            | Instruction::WideIinc(index, _)
+            | Instruction::WideRet(index) => Some(*index),
+            Instruction::Iload0
+            | Instruction::Istore0
+            | Instruction::Fload0
+            | Instruction::Fstore0
+            | Instruction::Aload0
+            | Instruction::Astore0
+            | Instruction::Lload0
+            | Instruction::Lstore0
+            | Instruction::Dload0
+            | Instruction::Dstore0 => Some(0),
+            Instruction::Iload1
+            | Instruction::Istore1
+            | Instruction::Fload1
+            | Instruction::Fstore1
+            | Instruction::Aload1
+            | Instruction::Astore1
+            | Instruction::Lload1
+            | Instruction::Lstore1
+            | Instruction::Dload1
+            | Instruction::Dstore1 => Some(1),
+            Instruction::Iload2
+            | Instruction::Istore2
+            | Instruction::Fload2
+            | Instruction::Fstore2
+            | Instruction::Aload2
+            | Instruction::Astore2
+            | Instruction::Lload2
+            | Instruction::Lstore2
+            | Instruction::Dload2
+            | Instruction::Dstore2 => Some(2),
+            Instruction::Iload3
+            | Instruction::Istore3
+            | Instruction::Fload3
+            | Instruction::Fstore3
+            | Instruction::Aload3
+            | Instruction::Astore3
+            | Instruction::Lload3
+            | Instruction::Lstore3
+            | Instruction::Dload3
+            | Instruction::Dstore3 => Some(3),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_pool_index() {
+        assert_eq!(Instruction::Ldc(7).constant_pool_index(), Some(7));
+        assert_eq!(
+            Instruction::Invokeinterface(300, 2, 0).constant_pool_index(),
+            Some(300)
+        );
+        assert_eq!(
+            Instruction::Multianewarray(4, 2).constant_pool_index(),
+            Some(4)
+        );
+        assert_eq!(Instruction::Bipush(7).constant_pool_index(), None);
+    }
+
+    #[test]
+    fn test_with_constant_pool_index() {
+        assert_eq!(
+            Instruction::Invokeinterface(300, 2, 0).with_constant_pool_index(9),
+            Some(Instruction::Invokeinterface(9, 2, 0))
+        );
+        assert_eq!(
+            Instruction::Ldc(7).with_constant_pool_index(255),
+            Some(Instruction::Ldc(255))
+        );
+        assert_eq!(Instruction::Ldc(7).with_constant_pool_index(256), None);
+        assert_eq!(Instruction::Bipush(7).with_constant_pool_index(1), None);
+    }
+
+    #[test]
+    fn test_branch_target() {
+        assert_eq!(Instruction::Goto(-10).branch_target(30), Some(20));
+        assert_eq!(Instruction::JsrW(100_000).branch_target(5), Some(100_005));
+        assert_eq!(Instruction::Ifnull(-8).branch_target(4), Some(-4));
+        assert_eq!(
+            Instruction::Tableswitch(20, 0, 0, vec![12]).branch_target(4),
+            None
+        );
+        assert_eq!(
+            Instruction::Tableswitch(20, 0, 1, vec![12, 16]).branch_targets(4),
+            vec![24, 16, 20]
+        );
+        assert_eq!(
+            Instruction::Lookupswitch(20, vec![(7, 28)]).branch_targets(0),
+            vec![20, 28]
+        );
+        assert_eq!(Instruction::Return.branch_targets(0), vec![]);
+    }
+
+    #[test]
+    fn test_local_index() {
+        assert_eq!(Instruction::Aload0.local_index(), Some(0));
+        assert_eq!(Instruction::Dstore3.local_index(), Some(3));
+        assert_eq!(Instruction::Iinc(5, 1).local_index(), Some(5));
+        assert_eq!(Instruction::WideLload(300).local_index(), Some(300));
+        assert_eq!(Instruction::Ret(2).local_index(), Some(2));
+        assert_eq!(Instruction::Iaload.local_index(), None);
+    }
+}
@@ -0,0 +1,349 @@
+use std::collections::{HashMap, HashSet};
+
+use super::error::RelocationError;
+use super::instruction::Instruction;
+use super::layout::{layout, Item};
+
+pub enum CodeEdit {
+    // inserts the instructions before the instruction at `pc`; branches that
+    // targeted `pc` now target the first inserted instruction
+    Insert {
+        pc: u32,
+        instructions: Vec<Instruction>,
+    },
+    // inserts the instructions before the instruction at `pc`, leaving
+    // branches that targeted `pc` where they were: the new code only runs
+    // when control falls through to it, as for a method prologue
+    InsertFallthrough {
+        pc: u32,
+        instructions: Vec<Instruction>,
+    },
+    // removes the instructions in `start..end`; branches that targeted `start`
+    // now target the instruction that followed the removed range
+    Delete {
+        start: u32,
+        end: u32,
+    },
+}
+
+pub struct Relocation {
+    pub instructions: Vec<(u32, Instruction)>,
+    pc_map: HashMap<u32, u32>,
+}
+
+impl Relocation {
+    // new offset of the instruction that was at `pc`, or of the end of the code
+    pub fn map_pc(&self, pc: u32) -> Option<u32> {
+        self.pc_map.get(&pc).copied()
+    }
+}
+
+fn end_pc(code: &[(u32, Instruction)]) -> u32 {
+    code.last()
+        .map(|(pc, instruction)| pc + instruction.byte_len(*pc) as u32)
+        .unwrap_or(0)
+}
+
+pub fn relocate(
+    code: &[(u32, Instruction)],
+    edit: CodeEdit,
+) -> Result<Relocation, RelocationError> {
+    let old_end = end_pc(code);
+    let boundaries = code
+        .iter()
+        .map(|(pc, _)| *pc)
+        .chain(std::iter::once(old_end))
+        .collect::<HashSet<_>>();
+    let is_boundary = |pc: u32| boundaries.contains(&pc);
+
+    // (original pc, instruction, absolute targets in the original code)
+    let mut entries = Vec::new();
+    for (pc, instruction) in code {
+        let mut targets = Vec::new();
+        for target in instruction.branch_targets(*pc) {
+            let target = u32::try_from(target)
+                .ok()
+                .filter(|target| is_boundary(*target))
+                .ok_or(RelocationError::InvalidBranchTarget(*pc))?;
+            targets.push(target);
+        }
+        entries.push((Some(*pc), instruction.clone(), targets));
+    }
+
+    // original pcs that now resolve to another pc: the insertion point to the
+    // first inserted instruction, or the start of a deleted range to its end
+    let mut redirect = None;
+    let redirects = matches!(edit, CodeEdit::Insert { .. });
+    match edit {
+        CodeEdit::Insert { pc, instructions }
+        | CodeEdit::InsertFallthrough { pc, instructions } => {
+            if !is_boundary(pc) {
+                return Err(RelocationError::InvalidEditRange(pc, pc));
+            }
+            let position = entries
+                .iter()
+                .position(|(origin, _, _)| *origin == Some(pc))
+                .unwrap_or(entries.len());
+            if !instructions.is_empty() && redirects {
+                redirect = Some((pc, None));
+            }
+            let inserted = instructions
+                .into_iter()
+                .map(|instruction| (None, instruction, Vec::new()));
+            entries.splice(position..position, inserted);
+        }
+        CodeEdit::Delete { start, end } => {
+            if start > end || !is_boundary(start) || !is_boundary(end) {
+                return Err(RelocationError::InvalidEditRange(start, end));
+            }
+            entries.retain(|(origin, _, _)| !origin.is_some_and(|pc| start <= pc && pc < end));
+            for (_, _, targets) in &entries {
+                if let Some(target) = targets.iter().find(|t| start < **t && **t < end) {
+                    return Err(RelocationError::BranchIntoDeletedCode(*target));
+                }
+            }
+            redirect = Some((start, Some(end)));
+        }
+    }
+
+    let mut indices = HashMap::new();
+    indices.insert(old_end, entries.len());
+    for (i, (origin, _, _)) in entries.iter().enumerate() {
+        if let Some(origin) = origin {
+            indices.insert(*origin, i);
+        }
+    }
+    match redirect {
+        Some((pc, None)) => {
+            let first = entries.iter().position(|(origin, _, _)| origin.is_none());
+            indices.insert(pc, first.unwrap());
+        }
+        Some((start, Some(end))) => {
+            let index = indices[&end];
+            indices.insert(start, index);
+        }
+        None => {}
+    }
+
+    let items = entries
+        .into_iter()
+        .map(|(_, instruction, targets)| Item {
+            instruction,
+            targets: targets.iter().map(|target| indices[target]).collect(),
+        })
+        .collect();
+    let layout = layout(items);
+    let pc_map = indices
+        .into_iter()
+        .map(|(pc, index)| (pc, layout.pcs[index]))
+        .collect();
+
+    Ok(Relocation {
+        instructions: layout.instructions,
+        pc_map,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::disassemble;
+
+    fn decode(code: &[u8]) -> Vec<(u32, Instruction)> {
+        disassemble(code).collect::<Result<Vec<_>, _>>().unwrap()
+    }
+
+    fn encode(code: &[(u32, Instruction)]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (pc, instruction) in code {
+            assert_eq!(*pc as usize, out.len());
+            instruction.encode(*pc, &mut out);
+        }
+        out
+    }
+
+    #[test]
+    fn test_insert() {
+        let code = decode(&[
+            0x03, // 0: iconst_0
+            0x3c, // 1: istore_1
+            0x84, 0x01, 0x01, // 2: iinc 1, 1
+            0x1b, // 5: iload_1
+            0x10, 0x0a, // 6: bipush 10
+            0xa1, 0xff, 0xfa, // 8: if_icmplt 2
+            0xb1, // 11: return
+        ]);
+        let relocation = relocate(
+            &code,
+            CodeEdit::Insert {
+                pc: 2,
+                instructions: vec![Instruction::Nop, Instruction::Nop],
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            relocation.instructions,
+            vec![
+                (0, Instruction::Iconst0),
+                (1, Instruction::Istore1),
+                (2, Instruction::Nop),
+                (3, Instruction::Nop),
+                (4, Instruction::Iinc(1, 1)),
+                (7, Instruction::Iload1),
+                (8, Instruction::Bipush(10)),
+                (10, Instruction::IfIcmplt(-8)),
+                (13, Instruction::Return),
+            ]
+        );
+        assert_eq!(relocation.map_pc(2), Some(2));
+        assert_eq!(relocation.map_pc(5), Some(7));
+        assert_eq!(relocation.map_pc(12), Some(14));
+        assert_eq!(relocation.map_pc(3), None);
+    }
+
+    #[test]
+    fn test_insert_fallthrough() {
+        let code = decode(&[
+            0x03, // 0: iconst_0
+            0x3c, // 1: istore_1
+            0x84, 0x01, 0x01, // 2: iinc 1, 1
+            0x1b, // 5: iload_1
+            0x10, 0x0a, // 6: bipush 10
+            0xa1, 0xff, 0xfa, // 8: if_icmplt 2
+            0xb1, // 11: return
+        ]);
+        let relocation = relocate(
+            &code,
+            CodeEdit::InsertFallthrough {
+                pc: 2,
+                instructions: vec![Instruction::Nop, Instruction::Nop],
+            },
+        )
+        .unwrap();
+        // the loop still branches back to the iinc, past the nops
+        assert_eq!(relocation.instructions[4], (4, Instruction::Iinc(1, 1)));
+        assert_eq!(relocation.instructions[7], (10, Instruction::IfIcmplt(-6)));
+        assert_eq!(relocation.map_pc(2), Some(4));
+    }
+
+    #[test]
+    fn test_insert_widens_branches() {
+        let code = decode(&[
+            0x1a, // 0: iload_0
+            0x99, 0x00, 0x09, // 1: ifeq 10
+            0x00, // 4: nop
+            0x00, // 5: nop
+            0x00, // 6: nop
+            0xa7, 0xff, 0xf9, // 7: goto 0
+            0xb1, // 10: return
+        ]);
+        let relocation = relocate(
+            &code,
+            CodeEdit::Insert {
+                pc: 4,
+                instructions: vec![Instruction::Nop; 40000],
+            },
+        )
+        .unwrap();
+        let instructions = &relocation.instructions;
+        assert_eq!(instructions[0], (0, Instruction::Iload0));
+        assert_eq!(instructions[1], (1, Instruction::Ifne(8)));
+        assert_eq!(instructions[2], (4, Instruction::GotoW(40013)));
+        assert_eq!(instructions[3], (9, Instruction::Nop));
+        assert_eq!(
+            instructions[instructions.len() - 2..],
+            [
+                (40012, Instruction::GotoW(-40012)),
+                (40017, Instruction::Return)
+            ]
+        );
+        assert_eq!(relocation.map_pc(7), Some(40012));
+        assert_eq!(decode(&encode(instructions)), *instructions);
+    }
+
+    #[test]
+    fn test_switch_padding() {
+        let code = decode(&[
+            0x1a, // 0: iload_0
+            0xaa, // 1: tableswitch
+            0x00, 0x00, // padding
+            0x00, 0x00, 0x00, 0x17, // default: 24
+            0x00, 0x00, 0x00, 0x00, // low
+            0x00, 0x00, 0x00, 0x00, // high
+            0x00, 0x00, 0x00, 0x15, // 0: 22
+            0x04, // 20: iconst_1
+            0xac, // 21: ireturn
+            0x05, // 22: iconst_2
+            0xac, // 23: ireturn
+            0x03, // 24: iconst_0
+            0xac, // 25: ireturn
+        ]);
+        let relocation = relocate(
+            &code,
+            CodeEdit::Insert {
+                pc: 1,
+                instructions: vec![Instruction::Nop],
+            },
+        )
+        .unwrap();
+        // the switch moves to pc 2 and loses a byte of padding, so everything
+        // after it stays where it was
+        assert_eq!(
+            relocation.instructions[2],
+            (2, Instruction::Tableswitch(22, 0, 0, vec![20]))
+        );
+        assert_eq!(relocation.map_pc(20), Some(20));
+        assert_eq!(relocation.map_pc(26), Some(26));
+    }
+
+    #[test]
+    fn test_delete() {
+        let code = decode(&[
+            0x1a, // 0: iload_0
+            0x99, 0x00, 0x06, // 1: ifeq 7
+            0x00, // 4: nop
+            0x00, // 5: nop
+            0x00, // 6: nop
+            0xb1, // 7: return
+        ]);
+        let relocation = relocate(&code, CodeEdit::Delete { start: 4, end: 6 }).unwrap();
+        assert_eq!(
+            relocation.instructions,
+            vec![
+                (0, Instruction::Iload0),
+                (1, Instruction::Ifeq(4)),
+                (4, Instruction::Nop),
+                (5, Instruction::Return),
+            ]
+        );
+        assert_eq!(relocation.map_pc(4), Some(4));
+        assert_eq!(relocation.map_pc(5), None);
+        assert_eq!(relocation.map_pc(7), Some(5));
+        assert_eq!(relocation.map_pc(8), Some(6));
+
+        assert_eq!(
+            relocate(&code, CodeEdit::Delete { start: 1, end: 8 })
+                .unwrap()
+                .instructions,
+            vec![(0, Instruction::Iload0)]
+        );
+        assert_eq!(
+            relocate(&code, CodeEdit::Delete { start: 2, end: 4 }).err(),
+            Some(RelocationError::InvalidEditRange(2, 4))
+        );
+    }
+
+    #[test]
+    fn test_delete_branch_target() {
+        let code = decode(&[
+            0xa7, 0x00, 0x04, // 0: goto 4
+            0x00, // 3: nop
+            0x00, // 4: nop
+            0xb1, // 5: return
+        ]);
+        assert_eq!(
+            relocate(&code, CodeEdit::Delete { start: 3, end: 5 }).err(),
+            Some(RelocationError::BranchIntoDeletedCode(4))
+        );
+    }
+}
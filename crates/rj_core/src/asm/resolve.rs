@@ -0,0 +1,329 @@
+use super::error::ResolveError;
+use super::instruction::Instruction;
+use crate::class::{Constant, ConstantPool, NameAndType, ReferenceKind, SymbolicRef};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberRef {
+    pub class: String,
+    pub name: String,
+    pub descriptor: String,
+}
+
+impl From<SymbolicRef<'_>> for MemberRef {
+    fn from(reference: SymbolicRef) -> Self {
+        MemberRef {
+            class: reference.class_name.into_owned(),
+            name: reference.name.into_owned(),
+            descriptor: reference.descriptor.into_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadableConstant {
+    Integer(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    String(String),
+    Class(String),
+    MethodType(String),
+    MethodHandle {
+        reference_kind: ReferenceKind,
+        reference: MemberRef,
+    },
+    Dynamic {
+        bootstrap_method_attr_index: u16,
+        name: String,
+        descriptor: String,
+    },
+}
+
+// an instruction with its constant pool operand replaced by the symbolic value;
+// instructions without a constant pool operand are kept as they are
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolvedInstruction {
+    Anewarray(String),
+    Checkcast(String),
+    Getfield(MemberRef),
+    Getstatic(MemberRef),
+    Instanceof(String),
+    Invokedynamic {
+        bootstrap_method_attr_index: u16,
+        name: String,
+        descriptor: String,
+    },
+    Invokeinterface(MemberRef, u8),
+    Invokespecial(MemberRef),
+    Invokestatic(MemberRef),
+    Invokevirtual(MemberRef),
+    Ldc(LoadableConstant),
+    LdcW(LoadableConstant),
+    Ldc2W(LoadableConstant),
+    Multianewarray(String, u8),
+    New(String),
+    Putfield(MemberRef),
+    Putstatic(MemberRef),
+    Other(Instruction),
+}
+
+fn get_constant<'a, 'b>(
+    index: u16,
+    constant_pool: &'b ConstantPool<'a>,
+) -> Result<&'b Constant<'a>, ResolveError> {
+    constant_pool
+        .get(index)
+        .ok_or(ResolveError::InvalidConstantPoolIndex(index))
+}
+
+fn get_utf8(index: u16, constant_pool: &ConstantPool) -> Result<String, ResolveError> {
+    Ok(constant_pool.utf8(index)?.into_owned())
+}
+
+fn get_class(index: u16, constant_pool: &ConstantPool) -> Result<String, ResolveError> {
+    Ok(constant_pool.class_name(index)?.into_owned())
+}
+
+fn get_name_and_type(
+    index: u16,
+    constant_pool: &ConstantPool,
+) -> Result<(String, String), ResolveError> {
+    let NameAndType { name, descriptor } = constant_pool.name_and_type(index)?;
+    Ok((name.into_owned(), descriptor.into_owned()))
+}
+
+fn get_member_ref(index: u16, constant_pool: &ConstantPool) -> Result<MemberRef, ResolveError> {
+    match get_constant(index, constant_pool)? {
+        Constant::Fieldref { .. } => get_field_ref(index, constant_pool),
+        _ => get_method_ref(index, constant_pool),
+    }
+}
+
+fn get_field_ref(index: u16, constant_pool: &ConstantPool) -> Result<MemberRef, ResolveError> {
+    Ok(constant_pool.field_ref(index)?.into())
+}
+
+// a Methodref, or an InterfaceMethodref, which invokestatic and invokespecial
+// can also name
+fn get_method_ref(index: u16, constant_pool: &ConstantPool) -> Result<MemberRef, ResolveError> {
+    let reference = match get_constant(index, constant_pool)? {
+        Constant::InterfaceMethodref { .. } => constant_pool.interface_method_ref(index)?,
+        _ => constant_pool.method_ref(index)?,
+    };
+    Ok(reference.into())
+}
+
+fn get_loadable(
+    index: u16,
+    constant_pool: &ConstantPool,
+) -> Result<LoadableConstant, ResolveError> {
+    match get_constant(index, constant_pool)? {
+        Constant::Integer { value } => Ok(LoadableConstant::Integer(*value)),
+        Constant::Float { value } => Ok(LoadableConstant::Float(*value)),
+        Constant::Long { value } => Ok(LoadableConstant::Long(*value)),
+        Constant::Double { value } => Ok(LoadableConstant::Double(*value)),
+        Constant::String { string_index } => Ok(LoadableConstant::String(get_utf8(
+            *string_index,
+            constant_pool,
+        )?)),
+        Constant::Class { name_index } => Ok(LoadableConstant::Class(get_utf8(
+            *name_index,
+            constant_pool,
+        )?)),
+        Constant::MethodType { descriptor_index } => Ok(LoadableConstant::MethodType(get_utf8(
+            *descriptor_index,
+            constant_pool,
+        )?)),
+        Constant::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => Ok(LoadableConstant::MethodHandle {
+            reference_kind: *reference_kind,
+            reference: get_member_ref(*reference_index, constant_pool)?,
+        }),
+        Constant::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => {
+            let (name, descriptor) = get_name_and_type(*name_and_type_index, constant_pool)?;
+            Ok(LoadableConstant::Dynamic {
+                bootstrap_method_attr_index: *bootstrap_method_attr_index,
+                name,
+                descriptor,
+            })
+        }
+        _ => Err(ResolveError::UnexpectedConstant(index)),
+    }
+}
+
+impl Instruction {
+    pub fn resolve(
+        &self,
+        constant_pool: &ConstantPool,
+    ) -> Result<ResolvedInstruction, ResolveError> {
+        let resolved = match self {
+            Instruction::Anewarray(index) => {
+                ResolvedInstruction::Anewarray(get_class(*index, constant_pool)?)
+            }
+            Instruction::Checkcast(index) => {
+                ResolvedInstruction::Checkcast(get_class(*index, constant_pool)?)
+            }
+            Instruction::Getfield(index) => {
+                ResolvedInstruction::Getfield(get_field_ref(*index, constant_pool)?)
+            }
+            Instruction::Getstatic(index) => {
+                ResolvedInstruction::Getstatic(get_field_ref(*index, constant_pool)?)
+            }
+            Instruction::Instanceof(index) => {
+                ResolvedInstruction::Instanceof(get_class(*index, constant_pool)?)
+            }
+            Instruction::Invokedynamic(index, _, _) => match get_constant(*index, constant_pool)? {
+                Constant::InvokeDynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                } => {
+                    let (name, descriptor) =
+                        get_name_and_type(*name_and_type_index, constant_pool)?;
+                    ResolvedInstruction::Invokedynamic {
+                        bootstrap_method_attr_index: *bootstrap_method_attr_index,
+                        name,
+                        descriptor,
+                    }
+                }
+                _ => return Err(ResolveError::UnexpectedConstant(*index)),
+            },
+            Instruction::Invokeinterface(index, count, _) => {
+                ResolvedInstruction::Invokeinterface(get_method_ref(*index, constant_pool)?, *count)
+            }
+            Instruction::Invokespecial(index) => {
+                ResolvedInstruction::Invokespecial(get_method_ref(*index, constant_pool)?)
+            }
+            Instruction::Invokestatic(index) => {
+                ResolvedInstruction::Invokestatic(get_method_ref(*index, constant_pool)?)
+            }
+            Instruction::Invokevirtual(index) => {
+                ResolvedInstruction::Invokevirtual(get_method_ref(*index, constant_pool)?)
+            }
+            Instruction::Ldc(index) => {
+                ResolvedInstruction::Ldc(get_loadable(*index as u16, constant_pool)?)
+            }
+            Instruction::LdcW(index) => {
+                ResolvedInstruction::LdcW(get_loadable(*index, constant_pool)?)
+            }
+            Instruction::Ldc2W(index) => {
+                ResolvedInstruction::Ldc2W(get_loadable(*index, constant_pool)?)
+            }
+            Instruction::Multianewarray(index, dimensions) => {
+                ResolvedInstruction::Multianewarray(get_class(*index, constant_pool)?, *dimensions)
+            }
+            Instruction::New(index) => ResolvedInstruction::New(get_class(*index, constant_pool)?),
+            Instruction::Putfield(index) => {
+                ResolvedInstruction::Putfield(get_field_ref(*index, constant_pool)?)
+            }
+            Instruction::Putstatic(index) => {
+                ResolvedInstruction::Putstatic(get_field_ref(*index, constant_pool)?)
+            }
+            instruction => ResolvedInstruction::Other(instruction.clone()),
+        };
+        Ok(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::disassemble;
+    use crate::class::{parse_classfile, Attribute, ConstantPoolError, ConstantTag};
+
+    #[test]
+    fn test_resolve() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let mut resolved = Vec::new();
+        for method in &classfile.methods {
+            for attribute in &method.attributes {
+                if let Attribute::Code(code) = attribute {
+                    for result in disassemble(&code.code) {
+                        let (_, instruction) = result.unwrap();
+                        resolved.push(instruction.resolve(&classfile.constant_pool).unwrap());
+                    }
+                }
+            }
+        }
+        let member = |class: &str, name: &str, descriptor: &str| MemberRef {
+            class: class.to_string(),
+            name: name.to_string(),
+            descriptor: descriptor.to_string(),
+        };
+        let message = member("HelloWorld", "message", "Ljava/lang/String;");
+        assert_eq!(
+            resolved,
+            vec![
+                ResolvedInstruction::Other(Instruction::Aload0),
+                ResolvedInstruction::Invokespecial(member("java/lang/Object", "<init>", "()V")),
+                ResolvedInstruction::Other(Instruction::Aload0),
+                ResolvedInstruction::Ldc(LoadableConstant::String("Hello, World!".to_string())),
+                ResolvedInstruction::Putfield(message.clone()),
+                ResolvedInstruction::Other(Instruction::Return),
+                ResolvedInstruction::Getstatic(member(
+                    "java/lang/System",
+                    "out",
+                    "Ljava/io/PrintStream;"
+                )),
+                ResolvedInstruction::Other(Instruction::Aload0),
+                ResolvedInstruction::Getfield(message),
+                ResolvedInstruction::Invokevirtual(member(
+                    "java/io/PrintStream",
+                    "println",
+                    "(Ljava/lang/String;)V"
+                )),
+                ResolvedInstruction::Other(Instruction::Return),
+                ResolvedInstruction::New("HelloWorld".to_string()),
+                ResolvedInstruction::Other(Instruction::Dup),
+                ResolvedInstruction::Invokespecial(member("HelloWorld", "<init>", "()V")),
+                ResolvedInstruction::Invokevirtual(member("HelloWorld", "sayHello", "()V")),
+                ResolvedInstruction::Other(Instruction::Return),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_invalid() {
+        let constant_pool = ConstantPool::from(vec![
+            Constant::Utf8 {
+                value: b"Foo".into(),
+            },
+            Constant::Class { name_index: 1 },
+        ]);
+        assert_eq!(
+            Instruction::New(2).resolve(&constant_pool),
+            Ok(ResolvedInstruction::New("Foo".to_string()))
+        );
+        assert_eq!(
+            Instruction::New(1).resolve(&constant_pool),
+            Err(ResolveError::ConstantPoolError(
+                ConstantPoolError::UnexpectedConstant {
+                    index: 1,
+                    expected: ConstantTag::Class,
+                    found: ConstantTag::Utf8,
+                }
+            ))
+        );
+        assert_eq!(
+            Instruction::Getfield(2).resolve(&constant_pool),
+            Err(ResolveError::ConstantPoolError(
+                ConstantPoolError::UnexpectedConstant {
+                    index: 2,
+                    expected: ConstantTag::Fieldref,
+                    found: ConstantTag::Class,
+                }
+            ))
+        );
+        assert_eq!(
+            Instruction::Checkcast(3).resolve(&constant_pool),
+            Err(ResolveError::ConstantPoolError(
+                ConstantPoolError::InvalidIndex(3)
+            ))
+        );
+    }
+}
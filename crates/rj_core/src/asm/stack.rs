@@ -0,0 +1,628 @@
+use std::collections::HashMap;
+
+use super::error::{MaxStackError, StackEffectError};
+use super::instruction::Instruction;
+use crate::class::{
+    parse_field_type, Constant, ConstantPool, ExceptionTableEntry, FieldType, MethodDescriptor,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackKind {
+    Int,
+    Float,
+    Long,
+    Double,
+    Reference,
+    ReturnAddress,
+    // an untyped slot moved by pop/dup/swap; a long or double counts as two
+    Word,
+}
+
+impl StackKind {
+    pub fn size(&self) -> u16 {
+        match self {
+            StackKind::Long | StackKind::Double => 2,
+            _ => 1,
+        }
+    }
+
+    fn from_field_type(field_type: &FieldType) -> Option<Self> {
+        match field_type {
+            FieldType::Boolean
+            | FieldType::Byte
+            | FieldType::Char
+            | FieldType::Short
+            | FieldType::Int => Some(StackKind::Int),
+            FieldType::Float => Some(StackKind::Float),
+            FieldType::Long => Some(StackKind::Long),
+            FieldType::Double => Some(StackKind::Double),
+            FieldType::Object(_) | FieldType::Array(_) => Some(StackKind::Reference),
+            FieldType::Void => None,
+        }
+    }
+}
+
+// popped and pushed are listed bottom to top, as in the JVMS operand stack notation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StackEffect {
+    pub popped: Vec<StackKind>,
+    pub pushed: Vec<StackKind>,
+}
+
+impl StackEffect {
+    fn new(popped: &[StackKind], pushed: &[StackKind]) -> Self {
+        StackEffect {
+            popped: popped.to_vec(),
+            pushed: pushed.to_vec(),
+        }
+    }
+
+    pub fn popped_words(&self) -> u16 {
+        self.popped.iter().map(StackKind::size).sum()
+    }
+
+    pub fn pushed_words(&self) -> u16 {
+        self.pushed.iter().map(StackKind::size).sum()
+    }
+
+    pub fn delta(&self) -> i32 {
+        self.pushed_words() as i32 - self.popped_words() as i32
+    }
+}
+
+fn get_constant<'a, 'b>(
+    index: u16,
+    constant_pool: &'b ConstantPool<'a>,
+) -> Result<&'b Constant<'a>, StackEffectError> {
+    constant_pool
+        .get(index)
+        .ok_or(StackEffectError::InvalidConstantPoolIndex(index))
+}
+
+// descriptor of the NameAndType referenced by a member ref or (invoke)dynamic constant
+fn get_descriptor(index: u16, constant_pool: &ConstantPool) -> Result<String, StackEffectError> {
+    let descriptor = match get_constant(index, constant_pool)? {
+        Constant::Fieldref { .. } => constant_pool.field_ref(index)?.descriptor,
+        Constant::Methodref { .. } => constant_pool.method_ref(index)?.descriptor,
+        Constant::InterfaceMethodref { .. } => {
+            constant_pool.interface_method_ref(index)?.descriptor
+        }
+        Constant::Dynamic {
+            name_and_type_index,
+            ..
+        }
+        | Constant::InvokeDynamic {
+            name_and_type_index,
+            ..
+        } => {
+            constant_pool
+                .name_and_type(*name_and_type_index)?
+                .descriptor
+        }
+        _ => return Err(StackEffectError::InvalidConstantPoolIndex(index)),
+    };
+    Ok(descriptor.into_owned())
+}
+
+fn field_kind(index: u16, constant_pool: &ConstantPool) -> Result<StackKind, StackEffectError> {
+    match parse_field_type(get_descriptor(index, constant_pool)?.as_bytes()) {
+        Ok((b"", field_type)) => StackKind::from_field_type(&field_type)
+            .ok_or(StackEffectError::InvalidDescriptor(index)),
+        _ => Err(StackEffectError::InvalidDescriptor(index)),
+    }
+}
+
+fn ldc_kind(index: u16, constant_pool: &ConstantPool) -> Result<StackKind, StackEffectError> {
+    match get_constant(index, constant_pool)? {
+        Constant::Integer { .. } => Ok(StackKind::Int),
+        Constant::Float { .. } => Ok(StackKind::Float),
+        Constant::Long { .. } => Ok(StackKind::Long),
+        Constant::Double { .. } => Ok(StackKind::Double),
+        Constant::String { .. }
+        | Constant::Class { .. }
+        | Constant::MethodType { .. }
+        | Constant::MethodHandle { .. } => Ok(StackKind::Reference),
+        Constant::Dynamic { .. } => field_kind(index, constant_pool),
+        _ => Err(StackEffectError::InvalidConstantPoolIndex(index)),
+    }
+}
+
+fn invoke_effect(
+    index: u16,
+    has_receiver: bool,
+    constant_pool: &ConstantPool,
+) -> Result<StackEffect, StackEffectError> {
+    let descriptor = get_descriptor(index, constant_pool)?;
+    let descriptor = MethodDescriptor::parse(descriptor.as_bytes())
+        .map_err(|_| StackEffectError::InvalidDescriptor(index))?;
+    let mut popped = Vec::new();
+    if has_receiver {
+        popped.push(StackKind::Reference);
+    }
+    popped.extend(
+        descriptor
+            .parameters
+            .iter()
+            .filter_map(StackKind::from_field_type),
+    );
+    let pushed = StackKind::from_field_type(&descriptor.return_type)
+        .into_iter()
+        .collect();
+    Ok(StackEffect { popped, pushed })
+}
+
+impl Instruction {
+    pub fn stack_effect(
+        &self,
+        constant_pool: &ConstantPool,
+    ) -> Result<StackEffect, StackEffectError> {
+        use StackKind::*;
+
+        let effect = match self {
+            Instruction::Nop
+            | Instruction::Iinc(..)
+            | Instruction::WideIinc(..)
+            | Instruction::Goto(_)
+            | Instruction::GotoW(_)
+            | Instruction::Ret(_)
+            | Instruction::WideRet(_)
+            | Instruction::Return => StackEffect::new(&[], &[]),
+            Instruction::AconstNull
+            | Instruction::Aload(_)
+            | Instruction::WideAload(_)
+            | Instruction::Aload0
+            | Instruction::Aload1
+            | Instruction::Aload2
+            | Instruction::Aload3
+            | Instruction::New(_) => StackEffect::new(&[], &[Reference]),
+            Instruction::IconstM1
+            | Instruction::Iconst0
+            | Instruction::Iconst1
+            | Instruction::Iconst2
+            | Instruction::Iconst3
+            | Instruction::Iconst4
+            | Instruction::Iconst5
+            | Instruction::Bipush(_)
+            | Instruction::Sipush(_)
+            | Instruction::Iload(_)
+            | Instruction::WideIload(_)
+            | Instruction::Iload0
+            | Instruction::Iload1
+            | Instruction::Iload2
+            | Instruction::Iload3 => StackEffect::new(&[], &[Int]),
+            Instruction::Lconst0
+            | Instruction::Lconst1
+            | Instruction::Lload(_)
+            | Instruction::WideLload(_)
+            | Instruction::Lload0
+            | Instruction::Lload1
+            | Instruction::Lload2
+            | Instruction::Lload3 => StackEffect::new(&[], &[Long]),
+            Instruction::Fconst0
+            | Instruction::Fconst1
+            | Instruction::Fconst2
+            | Instruction::Fload(_)
+            | Instruction::WideFload(_)
+            | Instruction::Fload0
+            | Instruction::Fload1
+            | Instruction::Fload2
+            | Instruction::Fload3 => StackEffect::new(&[], &[Float]),
+            Instruction::Dconst0
+            | Instruction::Dconst1
+            | Instruction::Dload(_)
+            | Instruction::WideDload(_)
+            | Instruction::Dload0
+            | Instruction::Dload1
+            | Instruction::Dload2
+            | Instruction::Dload3 => StackEffect::new(&[], &[Double]),
+            Instruction::Ldc(index) => {
+                StackEffect::new(&[], &[ldc_kind(*index as u16, constant_pool)?])
+            }
+            Instruction::LdcW(index) | Instruction::Ldc2W(index) => {
+                StackEffect::new(&[], &[ldc_kind(*index, constant_pool)?])
+            }
+            Instruction::Iaload
+            | Instruction::Baload
+            | Instruction::Caload
+            | Instruction::Saload => StackEffect::new(&[Reference, Int], &[Int]),
+            Instruction::Laload => StackEffect::new(&[Reference, Int], &[Long]),
+            Instruction::Faload => StackEffect::new(&[Reference, Int], &[Float]),
+            Instruction::Daload => StackEffect::new(&[Reference, Int], &[Double]),
+            Instruction::Aaload => StackEffect::new(&[Reference, Int], &[Reference]),
+            Instruction::Istore(_)
+            | Instruction::WideIstore(_)
+            | Instruction::Istore0
+            | Instruction::Istore1
+            | Instruction::Istore2
+            | Instruction::Istore3
+            | Instruction::Ifeq(_)
+            | Instruction::Ifne(_)
+            | Instruction::Iflt(_)
+            | Instruction::Ifge(_)
+            | Instruction::Ifgt(_)
+            | Instruction::Ifle(_)
+            | Instruction::Tableswitch(..)
+            | Instruction::Lookupswitch(..)
+            | Instruction::Ireturn => StackEffect::new(&[Int], &[]),
+            Instruction::Lstore(_)
+            | Instruction::WideLstore(_)
+            | Instruction::Lstore0
+            | Instruction::Lstore1
+            | Instruction::Lstore2
+            | Instruction::Lstore3
+            | Instruction::Lreturn => StackEffect::new(&[Long], &[]),
+            Instruction::Fstore(_)
+            | Instruction::WideFstore(_)
+            | Instruction::Fstore0
+            | Instruction::Fstore1
+            | Instruction::Fstore2
+            | Instruction::Fstore3
+            | Instruction::Freturn => StackEffect::new(&[Float], &[]),
+            Instruction::Dstore(_)
+            | Instruction::WideDstore(_)
+            | Instruction::Dstore0
+            | Instruction::Dstore1
+            | Instruction::Dstore2
+            | Instruction::Dstore3
+            | Instruction::Dreturn => StackEffect::new(&[Double], &[]),
+            // astore also accepts the returnAddress pushed by jsr
+            Instruction::Astore(_)
+            | Instruction::WideAstore(_)
+            | Instruction::Astore0
+            | Instruction::Astore1
+            | Instruction::Astore2
+            | Instruction::Astore3
+            | Instruction::Ifnull(_)
+            | Instruction::Ifnonnull(_)
+            | Instruction::Areturn
+            | Instruction::Athrow
+            | Instruction::Monitorenter
+            | Instruction::Monitorexit => StackEffect::new(&[Reference], &[]),
+            Instruction::Iastore
+            | Instruction::Bastore
+            | Instruction::Castore
+            | Instruction::Sastore => StackEffect::new(&[Reference, Int, Int], &[]),
+            Instruction::Lastore => StackEffect::new(&[Reference, Int, Long], &[]),
+            Instruction::Fastore => StackEffect::new(&[Reference, Int, Float], &[]),
+            Instruction::Dastore => StackEffect::new(&[Reference, Int, Double], &[]),
+            Instruction::Aastore => StackEffect::new(&[Reference, Int, Reference], &[]),
+            Instruction::Pop => StackEffect::new(&[Word], &[]),
+            Instruction::Pop2 => StackEffect::new(&[Word, Word], &[]),
+            Instruction::Dup => StackEffect::new(&[Word], &[Word, Word]),
+            Instruction::DupX1 => StackEffect::new(&[Word, Word], &[Word, Word, Word]),
+            Instruction::DupX2 => StackEffect::new(&[Word, Word, Word], &[Word, Word, Word, Word]),
+            Instruction::Dup2 => StackEffect::new(&[Word, Word], &[Word, Word, Word, Word]),
+            Instruction::Dup2X1 => {
+                StackEffect::new(&[Word, Word, Word], &[Word, Word, Word, Word, Word])
+            }
+            Instruction::Dup2X2 => StackEffect::new(
+                &[Word, Word, Word, Word],
+                &[Word, Word, Word, Word, Word, Word],
+            ),
+            Instruction::Swap => StackEffect::new(&[Word, Word], &[Word, Word]),
+            Instruction::Iadd
+            | Instruction::Isub
+            | Instruction::Imul
+            | Instruction::Idiv
+            | Instruction::Irem
+            | Instruction::Ishl
+            | Instruction::Ishr
+            | Instruction::Iushr
+            | Instruction::Iand
+            | Instruction::Ior
+            | Instruction::Ixor => StackEffect::new(&[Int, Int], &[Int]),
+            Instruction::Ladd
+            | Instruction::Lsub
+            | Instruction::Lmul
+            | Instruction::Ldiv
+            | Instruction::Lrem
+            | Instruction::Land
+            | Instruction::Lor
+            | Instruction::Lxor => StackEffect::new(&[Long, Long], &[Long]),
+            Instruction::Lshl | Instruction::Lshr | Instruction::Lushr => {
+                StackEffect::new(&[Long, Int], &[Long])
+            }
+            Instruction::Fadd
+            | Instruction::Fsub
+            | Instruction::Fmul
+            | Instruction::Fdiv
+            | Instruction::Frem => StackEffect::new(&[Float, Float], &[Float]),
+            Instruction::Dadd
+            | Instruction::Dsub
+            | Instruction::Dmul
+            | Instruction::Ddiv
+            | Instruction::Drem => StackEffect::new(&[Double, Double], &[Double]),
+            Instruction::Ineg | Instruction::I2b | Instruction::I2c | Instruction::I2s => {
+                StackEffect::new(&[Int], &[Int])
+            }
+            Instruction::Lneg => StackEffect::new(&[Long], &[Long]),
+            Instruction::Fneg => StackEffect::new(&[Float], &[Float]),
+            Instruction::Dneg => StackEffect::new(&[Double], &[Double]),
+            Instruction::I2l => StackEffect::new(&[Int], &[Long]),
+            Instruction::I2f => StackEffect::new(&[Int], &[Float]),
+            Instruction::I2d => StackEffect::new(&[Int], &[Double]),
+            Instruction::L2i => StackEffect::new(&[Long], &[Int]),
+            Instruction::L2f => StackEffect::new(&[Long], &[Float]),
+            Instruction::L2d => StackEffect::new(&[Long], &[Double]),
+            Instruction::F2i => StackEffect::new(&[Float], &[Int]),
+            Instruction::F2l => StackEffect::new(&[Float], &[Long]),
+            Instruction::F2d => StackEffect::new(&[Float], &[Double]),
+            Instruction::D2i => StackEffect::new(&[Double], &[Int]),
+            Instruction::D2l => StackEffect::new(&[Double], &[Long]),
+            Instruction::D2f => StackEffect::new(&[Double], &[Float]),
+            Instruction::Lcmp => StackEffect::new(&[Long, Long], &[Int]),
+            Instruction::Fcmpl | Instruction::Fcmpg => StackEffect::new(&[Float, Float], &[Int]),
+            Instruction::Dcmpl | Instruction::Dcmpg => StackEffect::new(&[Double, Double], &[Int]),
+            Instruction::IfIcmpeq(_)
+            | Instruction::IfIcmpne(_)
+            | Instruction::IfIcmplt(_)
+            | Instruction::IfIcmpge(_)
+            | Instruction::IfIcmpgt(_)
+            | Instruction::IfIcmple(_) => StackEffect::new(&[Int, Int], &[]),
+            Instruction::IfAcmpeq(_) | Instruction::IfAcmpne(_) => {
+                StackEffect::new(&[Reference, Reference], &[])
+            }
+            Instruction::Jsr(_) | Instruction::JsrW(_) => StackEffect::new(&[], &[ReturnAddress]),
+            Instruction::Getstatic(index) => {
+                StackEffect::new(&[], &[field_kind(*index, constant_pool)?])
+            }
+            Instruction::Putstatic(index) => {
+                StackEffect::new(&[field_kind(*index, constant_pool)?], &[])
+            }
+            Instruction::Getfield(index) => {
+                StackEffect::new(&[Reference], &[field_kind(*index, constant_pool)?])
+            }
+            Instruction::Putfield(index) => {
+                StackEffect::new(&[Reference, field_kind(*index, constant_pool)?], &[])
+            }
+            Instruction::Invokevirtual(index)
+            | Instruction::Invokespecial(index)
+            | Instruction::Invokeinterface(index, _, _) => {
+                invoke_effect(*index, true, constant_pool)?
+            }
+            Instruction::Invokestatic(index) | Instruction::Invokedynamic(index, _, _) => {
+                invoke_effect(*index, false, constant_pool)?
+            }
+            Instruction::Newarray(_) | Instruction::Anewarray(_) => {
+                StackEffect::new(&[Int], &[Reference])
+            }
+            Instruction::Multianewarray(_, dimensions) => StackEffect {
+                popped: vec![Int; *dimensions as usize],
+                pushed: vec![Reference],
+            },
+            Instruction::Arraylength | Instruction::Instanceof(_) => {
+                StackEffect::new(&[Reference], &[Int])
+            }
+            Instruction::Checkcast(_) => StackEffect::new(&[Reference], &[Reference]),
+        };
+        Ok(effect)
+    }
+
+    pub fn stack_delta(&self, constant_pool: &ConstantPool) -> Result<i32, StackEffectError> {
+        Ok(self.stack_effect(constant_pool)?.delta())
+    }
+}
+
+// instructions after which control never reaches the next one
+fn ends_flow(instruction: &Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::Goto(_)
+            | Instruction::GotoW(_)
+            | Instruction::Tableswitch(..)
+            | Instruction::Lookupswitch(..)
+            | Instruction::Ireturn
+            | Instruction::Lreturn
+            | Instruction::Freturn
+            | Instruction::Dreturn
+            | Instruction::Areturn
+            | Instruction::Return
+            | Instruction::Athrow
+            | Instruction::Ret(_)
+            | Instruction::WideRet(_)
+    )
+}
+
+// the stack height on entry to each reachable instruction
+struct Heights<'a> {
+    index_of: HashMap<u32, usize>,
+    heights: Vec<Option<u32>>,
+    worklist: Vec<usize>,
+    instructions: &'a [(u32, Instruction)],
+    max_stack: u32,
+}
+
+impl<'a> Heights<'a> {
+    fn enter(&mut self, target: i64, height: u32, from: u32) -> Result<(), MaxStackError> {
+        let index = u32::try_from(target)
+            .ok()
+            .and_then(|target| self.index_of.get(&target).copied())
+            .ok_or(MaxStackError::InvalidBranchTarget(from))?;
+        match self.heights[index] {
+            Some(current) if current != height => Err(MaxStackError::InconsistentStackHeight(
+                self.instructions[index].0,
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.heights[index] = Some(height);
+                self.max_stack = self.max_stack.max(height);
+                self.worklist.push(index);
+                Ok(())
+            }
+        }
+    }
+}
+
+// the deepest the operand stack gets, in words, following every path from
+// the first instruction and every exception handler. a handler starts with
+// just the exception on the stack; the instruction after a jsr starts with
+// the height the jsr had, as if the subroutine popped its return address.
+// unreachable instructions are ignored
+pub fn compute_max_stack(
+    instructions: &[(u32, Instruction)],
+    exception_table: &[ExceptionTableEntry],
+    constant_pool: &ConstantPool,
+) -> Result<u16, MaxStackError> {
+    let mut heights = Heights {
+        index_of: instructions
+            .iter()
+            .enumerate()
+            .map(|(index, (pc, _))| (*pc, index))
+            .collect(),
+        heights: vec![None; instructions.len()],
+        worklist: Vec::new(),
+        instructions,
+        max_stack: 0,
+    };
+    if let Some((pc, _)) = instructions.first() {
+        heights.enter(*pc as i64, 0, *pc)?;
+    }
+    for entry in exception_table {
+        let handler_pc = entry.handler_pc as u32;
+        heights.enter(handler_pc as i64, 1, handler_pc)?;
+    }
+    while let Some(index) = heights.worklist.pop() {
+        let (pc, instruction) = &instructions[index];
+        let height = heights.heights[index].unwrap_or(0);
+        let effect = instruction.stack_effect(constant_pool)?;
+        let popped = effect.popped_words() as u32;
+        if height < popped {
+            return Err(MaxStackError::StackUnderflow(*pc));
+        }
+        let output = height - popped + effect.pushed_words() as u32;
+        for target in instruction.branch_targets(*pc) {
+            heights.enter(target, output, *pc)?;
+        }
+        if ends_flow(instruction) {
+            continue;
+        }
+        let next = match instruction {
+            Instruction::Jsr(_) | Instruction::JsrW(_) => height,
+            _ => output,
+        };
+        let next_pc = instructions
+            .get(index + 1)
+            .ok_or(MaxStackError::FallsOffEnd(*pc))?
+            .0;
+        heights.enter(next_pc as i64, next, *pc)?;
+    }
+    Ok(heights.max_stack.min(u16::MAX as u32) as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::disassemble;
+    use crate::class::{parse_classfile, Attribute};
+
+    #[test]
+    fn test_stack_effect() {
+        let constant_pool = ConstantPool::from(vec![]);
+        assert_eq!(Instruction::Lconst1.stack_delta(&constant_pool), Ok(2));
+        assert_eq!(Instruction::Lcmp.stack_delta(&constant_pool), Ok(-3));
+        assert_eq!(Instruction::Dup2X1.stack_delta(&constant_pool), Ok(2));
+        assert_eq!(Instruction::Lastore.stack_delta(&constant_pool), Ok(-4));
+        assert_eq!(
+            Instruction::Multianewarray(1, 3).stack_delta(&constant_pool),
+            Ok(-2)
+        );
+        assert_eq!(
+            Instruction::Lshl.stack_effect(&constant_pool),
+            Ok(StackEffect {
+                popped: vec![StackKind::Long, StackKind::Int],
+                pushed: vec![StackKind::Long],
+            })
+        );
+        assert_eq!(
+            Instruction::Getstatic(1).stack_delta(&constant_pool),
+            Err(StackEffectError::InvalidConstantPoolIndex(1))
+        );
+    }
+
+    #[test]
+    fn test_stack_effect_constant_pool() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        for method in &classfile.methods {
+            for attribute in &method.attributes {
+                if let Attribute::Code(code) = attribute {
+                    let mut height = 0;
+                    let mut max_height = 0;
+                    for result in disassemble(&code.code) {
+                        let (_, instruction) = result.unwrap();
+                        let effect = instruction.stack_effect(&classfile.constant_pool).unwrap();
+                        assert!(height >= effect.popped_words() as i32);
+                        height += effect.delta();
+                        max_height = max_height.max(height);
+                    }
+                    assert_eq!(height, 0);
+                    assert_eq!(max_height, code.max_stack as i32);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_max_stack() {
+        let fixtures: [&[u8]; 6] = [
+            include_bytes!("../../../../java/HelloWorld.class"),
+            include_bytes!("../../../../java/Insns.class"),
+            include_bytes!("../../../../java/Frames.class"),
+            include_bytes!("../../../../java/Switches.class"),
+            include_bytes!("../../../../java/Locals.class"),
+            include_bytes!("../../../../java/Debug.class"),
+        ];
+        for data in fixtures {
+            let (_, classfile) = parse_classfile(data).unwrap();
+            for method in &classfile.methods {
+                let Some(code) = method.code() else {
+                    continue;
+                };
+                let instructions = disassemble(&code.code)
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap();
+                let max_stack = compute_max_stack(
+                    &instructions,
+                    &code.exception_table,
+                    &classfile.constant_pool,
+                );
+                assert_eq!(max_stack, Ok(code.max_stack));
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_max_stack_errors() {
+        let constant_pool = ConstantPool::from(vec![]);
+        let max_stack = |instructions: &[(u32, Instruction)]| {
+            compute_max_stack(instructions, &[], &constant_pool)
+        };
+        assert_eq!(
+            max_stack(&[(0, Instruction::Pop), (1, Instruction::Return)]),
+            Err(MaxStackError::StackUnderflow(0))
+        );
+        assert_eq!(
+            max_stack(&[(0, Instruction::Iconst0)]),
+            Err(MaxStackError::FallsOffEnd(0))
+        );
+        assert_eq!(
+            max_stack(&[(0, Instruction::Goto(7))]),
+            Err(MaxStackError::InvalidBranchTarget(0))
+        );
+        // a loop that pushes one more value each time around
+        assert_eq!(
+            max_stack(&[(0, Instruction::Iconst0), (1, Instruction::Goto(-1))]),
+            Err(MaxStackError::InconsistentStackHeight(0))
+        );
+        // code after the return is never reached
+        assert_eq!(
+            max_stack(&[
+                (0, Instruction::Lconst0),
+                (1, Instruction::Pop2),
+                (2, Instruction::Return),
+                (3, Instruction::Pop),
+            ]),
+            Ok(2)
+        );
+    }
+}
@@ -0,0 +1,745 @@
+// A small line-oriented assembly syntax for method bodies:
+//
+//     iconst_0
+//     istore_1
+//     goto check          // branch operands are labels or signed relative offsets
+//   loop:
+//     iinc 1, 1
+//   check: iload_1
+//     bipush 10
+//     if_icmplt loop
+//     return
+//
+// Instructions are separated by newlines or `;`, constant pool indices may be
+// written with a leading `#`, and switches take their targets inline:
+// `tableswitch <low> <default> <label>...` and
+// `lookupswitch <default> <key>:<label>...`.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::builder::{AssembledCode, CodeBuilder, Label};
+use super::error::CodeBuildError;
+use super::instruction::Instruction;
+
+#[derive(Debug, PartialEq)]
+pub enum TextError {
+    UnknownMnemonic { line: usize, mnemonic: String },
+    InvalidOperand { line: usize, operand: String },
+    MissingOperand { line: usize },
+    UnexpectedOperand { line: usize, operand: String },
+    DuplicateLabel { line: usize, label: String },
+    UndefinedLabel { label: String },
+    Build(CodeBuildError),
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextError::UnknownMnemonic { line, mnemonic } => {
+                write!(f, "line {line}: unknown mnemonic {mnemonic}")
+            }
+            TextError::InvalidOperand { line, operand } => {
+                write!(f, "line {line}: invalid operand {operand}")
+            }
+            TextError::MissingOperand { line } => write!(f, "line {line}: missing operand"),
+            TextError::UnexpectedOperand { line, operand } => {
+                write!(f, "line {line}: unexpected operand {operand}")
+            }
+            TextError::DuplicateLabel { line, label } => {
+                write!(f, "line {line}: label {label} is defined twice")
+            }
+            TextError::UndefinedLabel { label } => write!(f, "undefined label {label}"),
+            TextError::Build(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+fn zero_operand(mnemonic: &str) -> Option<Instruction> {
+    let instruction = match mnemonic {
+        "aaload" => Instruction::Aaload,
+        "aastore" => Instruction::Aastore,
+        "aconst_null" => Instruction::AconstNull,
+        "aload_0" => Instruction::Aload0,
+        "aload_1" => Instruction::Aload1,
+        "aload_2" => Instruction::Aload2,
+        "aload_3" => Instruction::Aload3,
+        "areturn" => Instruction::Areturn,
+        "arraylength" => Instruction::Arraylength,
+        "astore_0" => Instruction::Astore0,
+        "astore_1" => Instruction::Astore1,
+        "astore_2" => Instruction::Astore2,
+        "astore_3" => Instruction::Astore3,
+        "athrow" => Instruction::Athrow,
+        "baload" => Instruction::Baload,
+        "bastore" => Instruction::Bastore,
+        "caload" => Instruction::Caload,
+        "castore" => Instruction::Castore,
+        "d2f" => Instruction::D2f,
+        "d2i" => Instruction::D2i,
+        "d2l" => Instruction::D2l,
+        "dadd" => Instruction::Dadd,
+        "daload" => Instruction::Daload,
+        "dastore" => Instruction::Dastore,
+        "dcmpg" => Instruction::Dcmpg,
+        "dcmpl" => Instruction::Dcmpl,
+        "dconst_0" => Instruction::Dconst0,
+        "dconst_1" => Instruction::Dconst1,
+        "ddiv" => Instruction::Ddiv,
+        "dload_0" => Instruction::Dload0,
+        "dload_1" => Instruction::Dload1,
+        "dload_2" => Instruction::Dload2,
+        "dload_3" => Instruction::Dload3,
+        "dmul" => Instruction::Dmul,
+        "dneg" => Instruction::Dneg,
+        "drem" => Instruction::Drem,
+        "dreturn" => Instruction::Dreturn,
+        "dstore_0" => Instruction::Dstore0,
+        "dstore_1" => Instruction::Dstore1,
+        "dstore_2" => Instruction::Dstore2,
+        "dstore_3" => Instruction::Dstore3,
+        "dsub" => Instruction::Dsub,
+        "dup" => Instruction::Dup,
+        "dup_x1" => Instruction::DupX1,
+        "dup_x2" => Instruction::DupX2,
+        "dup2" => Instruction::Dup2,
+        "dup2_x1" => Instruction::Dup2X1,
+        "dup2_x2" => Instruction::Dup2X2,
+        "f2d" => Instruction::F2d,
+        "f2i" => Instruction::F2i,
+        "f2l" => Instruction::F2l,
+        "fadd" => Instruction::Fadd,
+        "faload" => Instruction::Faload,
+        "fastore" => Instruction::Fastore,
+        "fcmpg" => Instruction::Fcmpg,
+        "fcmpl" => Instruction::Fcmpl,
+        "fconst_0" => Instruction::Fconst0,
+        "fconst_1" => Instruction::Fconst1,
+        "fconst_2" => Instruction::Fconst2,
+        "fdiv" => Instruction::Fdiv,
+        "fload_0" => Instruction::Fload0,
+        "fload_1" => Instruction::Fload1,
+        "fload_2" => Instruction::Fload2,
+        "fload_3" => Instruction::Fload3,
+        "fmul" => Instruction::Fmul,
+        "fneg" => Instruction::Fneg,
+        "frem" => Instruction::Frem,
+        "freturn" => Instruction::Freturn,
+        "fstore_0" => Instruction::Fstore0,
+        "fstore_1" => Instruction::Fstore1,
+        "fstore_2" => Instruction::Fstore2,
+        "fstore_3" => Instruction::Fstore3,
+        "fsub" => Instruction::Fsub,
+        "i2b" => Instruction::I2b,
+        "i2c" => Instruction::I2c,
+        "i2d" => Instruction::I2d,
+        "i2f" => Instruction::I2f,
+        "i2l" => Instruction::I2l,
+        "i2s" => Instruction::I2s,
+        "iadd" => Instruction::Iadd,
+        "iaload" => Instruction::Iaload,
+        "iand" => Instruction::Iand,
+        "iastore" => Instruction::Iastore,
+        "iconst_m1" => Instruction::IconstM1,
+        "iconst_0" => Instruction::Iconst0,
+        "iconst_1" => Instruction::Iconst1,
+        "iconst_2" => Instruction::Iconst2,
+        "iconst_3" => Instruction::Iconst3,
+        "iconst_4" => Instruction::Iconst4,
+        "iconst_5" => Instruction::Iconst5,
+        "idiv" => Instruction::Idiv,
+        "iload_0" => Instruction::Iload0,
+        "iload_1" => Instruction::Iload1,
+        "iload_2" => Instruction::Iload2,
+        "iload_3" => Instruction::Iload3,
+        "imul" => Instruction::Imul,
+        "ineg" => Instruction::Ineg,
+        "ior" => Instruction::Ior,
+        "irem" => Instruction::Irem,
+        "ireturn" => Instruction::Ireturn,
+        "ishl" => Instruction::Ishl,
+        "ishr" => Instruction::Ishr,
+        "istore_0" => Instruction::Istore0,
+        "istore_1" => Instruction::Istore1,
+        "istore_2" => Instruction::Istore2,
+        "istore_3" => Instruction::Istore3,
+        "isub" => Instruction::Isub,
+        "iushr" => Instruction::Iushr,
+        "ixor" => Instruction::Ixor,
+        "l2d" => Instruction::L2d,
+        "l2f" => Instruction::L2f,
+        "l2i" => Instruction::L2i,
+        "ladd" => Instruction::Ladd,
+        "laload" => Instruction::Laload,
+        "land" => Instruction::Land,
+        "lastore" => Instruction::Lastore,
+        "lcmp" => Instruction::Lcmp,
+        "lconst_0" => Instruction::Lconst0,
+        "lconst_1" => Instruction::Lconst1,
+        "ldiv" => Instruction::Ldiv,
+        "lload_0" => Instruction::Lload0,
+        "lload_1" => Instruction::Lload1,
+        "lload_2" => Instruction::Lload2,
+        "lload_3" => Instruction::Lload3,
+        "lmul" => Instruction::Lmul,
+        "lneg" => Instruction::Lneg,
+        "lor" => Instruction::Lor,
+        "lrem" => Instruction::Lrem,
+        "lreturn" => Instruction::Lreturn,
+        "lshl" => Instruction::Lshl,
+        "lshr" => Instruction::Lshr,
+        "lstore_0" => Instruction::Lstore0,
+        "lstore_1" => Instruction::Lstore1,
+        "lstore_2" => Instruction::Lstore2,
+        "lstore_3" => Instruction::Lstore3,
+        "lsub" => Instruction::Lsub,
+        "lushr" => Instruction::Lushr,
+        "lxor" => Instruction::Lxor,
+        "monitorenter" => Instruction::Monitorenter,
+        "monitorexit" => Instruction::Monitorexit,
+        "nop" => Instruction::Nop,
+        "pop" => Instruction::Pop,
+        "pop2" => Instruction::Pop2,
+        "return" => Instruction::Return,
+        "saload" => Instruction::Saload,
+        "sastore" => Instruction::Sastore,
+        "swap" => Instruction::Swap,
+        _ => return None,
+    };
+    Some(instruction)
+}
+
+pub(super) fn parse_number(token: &str) -> Option<i64> {
+    let (negative, digits) = match token.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, token.strip_prefix('+').unwrap_or(token)),
+    };
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse::<i64>().ok()?,
+    };
+    Some(if negative { -value } else { value })
+}
+
+fn is_label_name(token: &str) -> bool {
+    let mut chars = token.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+struct Operands<'a> {
+    line: usize,
+    tokens: std::slice::Iter<'a, &'a str>,
+}
+
+impl<'a> Operands<'a> {
+    fn next(&mut self) -> Result<&'a str, TextError> {
+        self.tokens
+            .next()
+            .copied()
+            .ok_or(TextError::MissingOperand { line: self.line })
+    }
+
+    fn invalid(&self, operand: &str) -> TextError {
+        TextError::InvalidOperand {
+            line: self.line,
+            operand: operand.to_string(),
+        }
+    }
+
+    fn number<T: TryFrom<i64>>(&mut self) -> Result<T, TextError> {
+        let token = self.next()?;
+        parse_number(token)
+            .and_then(|value| T::try_from(value).ok())
+            .ok_or_else(|| self.invalid(token))
+    }
+
+    fn index<T: TryFrom<i64>>(&mut self) -> Result<T, TextError> {
+        let token = self.next()?;
+        parse_number(token.strip_prefix('#').unwrap_or(token))
+            .and_then(|value| T::try_from(value).ok())
+            .ok_or_else(|| self.invalid(token))
+    }
+
+    fn finish(mut self) -> Result<(), TextError> {
+        match self.tokens.next() {
+            Some(token) => Err(TextError::UnexpectedOperand {
+                line: self.line,
+                operand: token.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Assembler {
+    builder: CodeBuilder,
+    labels: HashMap<String, Label>,
+    defined: HashMap<String, usize>,
+}
+
+impl Assembler {
+    fn label(&mut self, name: &str) -> Label {
+        if let Some(label) = self.labels.get(name) {
+            return *label;
+        }
+        let label = self.builder.label();
+        self.labels.insert(name.to_string(), label);
+        label
+    }
+
+    fn define(&mut self, name: &str, line: usize) -> Result<(), TextError> {
+        if self.defined.insert(name.to_string(), line).is_some() {
+            return Err(TextError::DuplicateLabel {
+                line,
+                label: name.to_string(),
+            });
+        }
+        let label = self.label(name);
+        self.builder.bind(label);
+        Ok(())
+    }
+
+    fn target(&mut self, operands: &mut Operands) -> Result<Result<i64, Label>, TextError> {
+        let token = operands.next()?;
+        if let Some(offset) = parse_number(token) {
+            return Ok(Ok(offset));
+        }
+        if !is_label_name(token) {
+            return Err(operands.invalid(token));
+        }
+        Ok(Err(self.label(token)))
+    }
+
+    fn branch(
+        &mut self,
+        operands: &mut Operands,
+        short: fn(i16) -> Instruction,
+        long: Option<fn(i32) -> Instruction>,
+    ) -> Result<(), TextError> {
+        let token = operands.tokens.as_slice().first().copied().unwrap_or("");
+        match self.target(operands)? {
+            Err(label) => {
+                match long {
+                    Some(long) => self.builder.branch(long(0), label),
+                    None => self.builder.branch(short(0), label),
+                };
+            }
+            Ok(offset) => {
+                let instruction = match long {
+                    Some(long) => long(i32::try_from(offset).map_err(|_| operands.invalid(token))?),
+                    None => short(i16::try_from(offset).map_err(|_| operands.invalid(token))?),
+                };
+                self.builder.emit(instruction);
+            }
+        }
+        Ok(())
+    }
+
+    fn switch_label(&mut self, operands: &Operands, token: &str) -> Result<Label, TextError> {
+        if !is_label_name(token) {
+            return Err(operands.invalid(token));
+        }
+        Ok(self.label(token))
+    }
+
+    fn instruction(&mut self, line: usize, tokens: &[&str]) -> Result<(), TextError> {
+        let (mnemonic, mut rest) = (tokens[0], &tokens[1..]);
+        let wide = mnemonic == "wide";
+        let mnemonic = if wide {
+            let (mnemonic, operands) = rest
+                .split_first()
+                .ok_or(TextError::MissingOperand { line })?;
+            rest = operands;
+            mnemonic
+        } else {
+            mnemonic
+        };
+        let mut operands = Operands {
+            line,
+            tokens: rest.iter(),
+        };
+        if let Some(instruction) = zero_operand(mnemonic).filter(|_| !wide) {
+            operands.finish()?;
+            self.builder.emit(instruction);
+            return Ok(());
+        }
+
+        type Local = (fn(u8) -> Instruction, fn(u16) -> Instruction);
+        let local: Option<Local> = match mnemonic.trim_end_matches("_w") {
+            "aload" => Some((Instruction::Aload, Instruction::WideAload)),
+            "astore" => Some((Instruction::Astore, Instruction::WideAstore)),
+            "dload" => Some((Instruction::Dload, Instruction::WideDload)),
+            "dstore" => Some((Instruction::Dstore, Instruction::WideDstore)),
+            "fload" => Some((Instruction::Fload, Instruction::WideFload)),
+            "fstore" => Some((Instruction::Fstore, Instruction::WideFstore)),
+            "iload" => Some((Instruction::Iload, Instruction::WideIload)),
+            "istore" => Some((Instruction::Istore, Instruction::WideIstore)),
+            "lload" => Some((Instruction::Lload, Instruction::WideLload)),
+            "lstore" => Some((Instruction::Lstore, Instruction::WideLstore)),
+            "ret" => Some((Instruction::Ret, Instruction::WideRet)),
+            _ => None,
+        };
+        if let Some((narrow, widened)) = local {
+            let index: u16 = operands.number()?;
+            operands.finish()?;
+            let instruction = match u8::try_from(index) {
+                Ok(index) if !wide && !mnemonic.ends_with("_w") => narrow(index),
+                _ => widened(index),
+            };
+            self.builder.emit(instruction);
+            return Ok(());
+        }
+
+        let cp: Option<fn(u16) -> Instruction> = match mnemonic {
+            "anewarray" => Some(Instruction::Anewarray),
+            "checkcast" => Some(Instruction::Checkcast),
+            "getfield" => Some(Instruction::Getfield),
+            "getstatic" => Some(Instruction::Getstatic),
+            "instanceof" => Some(Instruction::Instanceof),
+            "invokespecial" => Some(Instruction::Invokespecial),
+            "invokestatic" => Some(Instruction::Invokestatic),
+            "invokevirtual" => Some(Instruction::Invokevirtual),
+            "ldc_w" => Some(Instruction::LdcW),
+            "ldc2_w" => Some(Instruction::Ldc2W),
+            "new" => Some(Instruction::New),
+            "putfield" => Some(Instruction::Putfield),
+            "putstatic" => Some(Instruction::Putstatic),
+            _ => None,
+        };
+        if let Some(cp) = cp {
+            let index = operands.index()?;
+            operands.finish()?;
+            self.builder.emit(cp(index));
+            return Ok(());
+        }
+
+        let branch: Option<fn(i16) -> Instruction> = match mnemonic {
+            "goto" => Some(Instruction::Goto),
+            "jsr" => Some(Instruction::Jsr),
+            "if_acmpeq" => Some(Instruction::IfAcmpeq),
+            "if_acmpne" => Some(Instruction::IfAcmpne),
+            "if_icmpeq" => Some(Instruction::IfIcmpeq),
+            "if_icmpne" => Some(Instruction::IfIcmpne),
+            "if_icmplt" => Some(Instruction::IfIcmplt),
+            "if_icmpge" => Some(Instruction::IfIcmpge),
+            "if_icmpgt" => Some(Instruction::IfIcmpgt),
+            "if_icmple" => Some(Instruction::IfIcmple),
+            "ifeq" => Some(Instruction::Ifeq),
+            "ifne" => Some(Instruction::Ifne),
+            "iflt" => Some(Instruction::Iflt),
+            "ifge" => Some(Instruction::Ifge),
+            "ifgt" => Some(Instruction::Ifgt),
+            "ifle" => Some(Instruction::Ifle),
+            "ifnonnull" => Some(Instruction::Ifnonnull),
+            "ifnull" => Some(Instruction::Ifnull),
+            _ => None,
+        };
+        if let Some(short) = branch {
+            self.branch(&mut operands, short, None)?;
+            return operands.finish();
+        }
+
+        match mnemonic {
+            "goto_w" => self.branch(&mut operands, Instruction::Goto, Some(Instruction::GotoW))?,
+            "jsr_w" => self.branch(&mut operands, Instruction::Jsr, Some(Instruction::JsrW))?,
+            "ldc" => {
+                let index = operands.index()?;
+                self.builder.emit(Instruction::Ldc(index));
+            }
+            "bipush" => {
+                let value = operands.number()?;
+                self.builder.emit(Instruction::Bipush(value));
+            }
+            "sipush" => {
+                let value = operands.number()?;
+                self.builder.emit(Instruction::Sipush(value));
+            }
+            "iinc" | "iinc_w" => {
+                let index: u16 = operands.number()?;
+                let value: i16 = operands.number()?;
+                let instruction = match (u8::try_from(index), i8::try_from(value)) {
+                    (Ok(index), Ok(value)) if !wide && mnemonic == "iinc" => {
+                        Instruction::Iinc(index, value)
+                    }
+                    _ => Instruction::WideIinc(index, value),
+                };
+                self.builder.emit(instruction);
+            }
+            "invokeinterface" => {
+                let index = operands.index()?;
+                let count = operands.number()?;
+                self.builder
+                    .emit(Instruction::Invokeinterface(index, count, 0));
+            }
+            "invokedynamic" => {
+                let index = operands.index()?;
+                // javap prints the two reserved bytes as a trailing 0
+                if operands.tokens.as_slice() == ["0"] {
+                    operands.next()?;
+                }
+                self.builder.emit(Instruction::Invokedynamic(index, 0, 0));
+            }
+            "multianewarray" => {
+                let index = operands.index()?;
+                let dimensions = operands.number()?;
+                self.builder
+                    .emit(Instruction::Multianewarray(index, dimensions));
+            }
+            "newarray" => {
+                let token = operands.next()?;
+                let atype = match token {
+                    "boolean" => 4,
+                    "char" => 5,
+                    "float" => 6,
+                    "double" => 7,
+                    "byte" => 8,
+                    "short" => 9,
+                    "int" => 10,
+                    "long" => 11,
+                    _ => parse_number(token)
+                        .and_then(|value| u8::try_from(value).ok())
+                        .ok_or_else(|| operands.invalid(token))?,
+                };
+                self.builder.emit(Instruction::Newarray(atype));
+            }
+            "tableswitch" => {
+                let low = operands.number()?;
+                let default = operands.next()?;
+                let default = self.switch_label(&operands, default)?;
+                let mut targets = Vec::new();
+                while let Some(token) = operands.tokens.next() {
+                    targets.push(self.switch_label(&operands, token)?);
+                }
+                self.builder.tableswitch(low, default, &targets);
+            }
+            "lookupswitch" => {
+                let default = operands.next()?;
+                let default = self.switch_label(&operands, default)?;
+                let mut pairs = Vec::new();
+                while let Some(token) = operands.tokens.next() {
+                    let (key, label) = token
+                        .split_once(':')
+                        .ok_or_else(|| operands.invalid(token))?;
+                    let key = parse_number(key)
+                        .and_then(|key| i32::try_from(key).ok())
+                        .ok_or_else(|| operands.invalid(token))?;
+                    pairs.push((key, self.switch_label(&operands, label)?));
+                }
+                self.builder.lookupswitch(default, &pairs);
+            }
+            _ => {
+                return Err(TextError::UnknownMnemonic {
+                    line,
+                    mnemonic: mnemonic.to_string(),
+                })
+            }
+        }
+        operands.finish()
+    }
+
+    // the code, with the pc each label was bound at
+    fn build(self) -> Result<(AssembledCode, HashMap<String, u32>), TextError> {
+        let code = self.builder.build().map_err(|error| match error {
+            CodeBuildError::UnboundLabel(label) => {
+                let name = self
+                    .labels
+                    .iter()
+                    .find(|(_, l)| **l == label)
+                    .map(|(name, _)| name.clone())
+                    .unwrap_or_default();
+                TextError::UndefinedLabel { label: name }
+            }
+            error => TextError::Build(error),
+        })?;
+        let pcs = self
+            .labels
+            .into_iter()
+            .map(|(name, label)| (name, code.label_pc(label)))
+            .collect();
+        Ok((code, pcs))
+    }
+}
+
+pub fn assemble(text: &str) -> Result<AssembledCode, TextError> {
+    Ok(assemble_with_labels(text)?.0)
+}
+
+// like `assemble`, also returning the pc of each label
+pub fn assemble_with_labels(
+    text: &str,
+) -> Result<(AssembledCode, HashMap<String, u32>), TextError> {
+    let mut assembler = Assembler::default();
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.split("//").next().unwrap();
+        for statement in line.split(';') {
+            let statement = statement.replace(',', " ");
+            let mut tokens = statement.split_whitespace().collect::<Vec<_>>();
+            while let Some(label) = tokens
+                .first()
+                .and_then(|token| token.strip_suffix(':'))
+                .filter(|label| is_label_name(label))
+            {
+                assembler.define(label, line_number)?;
+                tokens.remove(0);
+            }
+            if !tokens.is_empty() {
+                assembler.instruction(line_number, &tokens)?;
+            }
+        }
+    }
+    assembler.build()
+}
+
+pub fn parse(text: &str) -> Result<Vec<Instruction>, TextError> {
+    Ok(assemble(text)?
+        .instructions
+        .into_iter()
+        .map(|(_, instruction)| instruction)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::disassemble;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("aload_0\ninvokespecial #1\nreturn"),
+            Ok(vec![
+                Instruction::Aload0,
+                Instruction::Invokespecial(1),
+                Instruction::Return,
+            ])
+        );
+        assert_eq!(
+            parse(
+                "bipush -3; sipush 1000; iload 4; iload 300; wide aload 2
+                 iinc 1, -1; iinc 1, 200; ldc #2; ldc2_w 5
+                 invokeinterface #7, 2; invokedynamic #8, 0; multianewarray #9, 2
+                 newarray int; goto_w +5; ifeq -3"
+            ),
+            Ok(vec![
+                Instruction::Bipush(-3),
+                Instruction::Sipush(1000),
+                Instruction::Iload(4),
+                Instruction::WideIload(300),
+                Instruction::WideAload(2),
+                Instruction::Iinc(1, -1),
+                Instruction::WideIinc(1, 200),
+                Instruction::Ldc(2),
+                Instruction::Ldc2W(5),
+                Instruction::Invokeinterface(7, 2, 0),
+                Instruction::Invokedynamic(8, 0, 0),
+                Instruction::Multianewarray(9, 2),
+                Instruction::Newarray(10),
+                Instruction::GotoW(5),
+                Instruction::Ifeq(-3),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_assemble_labels() {
+        let code = assemble(
+            "
+                iconst_0
+                istore_1
+                goto check      // jump to the condition first
+            loop:
+                iinc 1, 1
+            check: iload_1
+                bipush 10
+                if_icmplt loop
+                return
+            ",
+        )
+        .unwrap();
+        assert_eq!(
+            code.code,
+            vec![
+                0x03, 0x3c, 0xa7, 0x00, 0x06, 0x84, 0x01, 0x01, 0x1b, 0x10, 0x0a, 0xa1, 0xff, 0xfa,
+                0xb1,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_assemble_switches() {
+        let code = assemble(
+            "
+                iload_0
+                tableswitch 1 other one two
+            one:
+                iload_0
+                lookupswitch other -5:one 100:two
+            two:
+                iconst_1
+                ireturn
+            other:
+                iconst_0
+                ireturn
+            ",
+        )
+        .unwrap();
+        let instructions = disassemble(&code.code)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            instructions[1],
+            (1, Instruction::Tableswitch(53, 1, 2, vec![23, 51]))
+        );
+        assert_eq!(
+            instructions[3],
+            (25, Instruction::Lookupswitch(29, vec![(-5, -1), (100, 27)]))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse("nop\nfrobnicate 1"),
+            Err(TextError::UnknownMnemonic {
+                line: 2,
+                mnemonic: "frobnicate".to_string()
+            })
+        );
+        assert_eq!(
+            parse("bipush 200"),
+            Err(TextError::InvalidOperand {
+                line: 1,
+                operand: "200".to_string()
+            })
+        );
+        assert_eq!(
+            parse("getstatic"),
+            Err(TextError::MissingOperand { line: 1 })
+        );
+        assert_eq!(
+            parse("return 1"),
+            Err(TextError::UnexpectedOperand {
+                line: 1,
+                operand: "1".to_string()
+            })
+        );
+        assert_eq!(
+            parse("a: nop\na: nop"),
+            Err(TextError::DuplicateLabel {
+                line: 2,
+                label: "a".to_string()
+            })
+        );
+        assert_eq!(
+            parse("goto nowhere"),
+            Err(TextError::UndefinedLabel {
+                label: "nowhere".to_string()
+            })
+        );
+    }
+}
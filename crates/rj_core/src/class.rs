@@ -2,16 +2,26 @@ mod access_flags;
 mod attribute;
 mod classfile;
 mod constant;
+mod constant_pool;
+mod constant_pool_builder;
 mod descriptors;
 mod error;
 mod field;
 mod method;
+mod modified_utf8;
+mod version;
+mod writer;
 
 pub use access_flags::*;
 pub use attribute::*;
 pub use classfile::*;
 pub use constant::*;
+pub use constant_pool::*;
+pub use constant_pool_builder::*;
 pub use descriptors::*;
 pub use error::*;
 pub use field::*;
 pub use method::*;
+pub use modified_utf8::*;
+pub use version::*;
+pub use writer::*;
@@ -184,6 +184,33 @@ impl MethodAccessFlags {
     pub const SYNTHETIC: Self = Self::from_bits(Self::ACC_SYNTHETIC);
 }
 
+define_flags!(InnerClassAccessFlags);
+
+impl InnerClassAccessFlags {
+    pub const ACC_PUBLIC: u16 = 0x0001;
+    pub const ACC_PRIVATE: u16 = 0x0002;
+    pub const ACC_PROTECTED: u16 = 0x0004;
+    pub const ACC_STATIC: u16 = 0x0008;
+    pub const ACC_FINAL: u16 = 0x0010;
+    pub const ACC_INTERFACE: u16 = 0x0200;
+    pub const ACC_ABSTRACT: u16 = 0x0400;
+    pub const ACC_SYNTHETIC: u16 = 0x1000;
+    pub const ACC_ANNOTATION: u16 = 0x2000;
+    pub const ACC_ENUM: u16 = 0x4000;
+
+    pub const EMPTY: Self = Self::from_bits(0);
+    pub const PUBLIC: Self = Self::from_bits(Self::ACC_PUBLIC);
+    pub const PRIVATE: Self = Self::from_bits(Self::ACC_PRIVATE);
+    pub const PROTECTED: Self = Self::from_bits(Self::ACC_PROTECTED);
+    pub const STATIC: Self = Self::from_bits(Self::ACC_STATIC);
+    pub const FINAL: Self = Self::from_bits(Self::ACC_FINAL);
+    pub const INTERFACE: Self = Self::from_bits(Self::ACC_INTERFACE);
+    pub const ABSTRACT: Self = Self::from_bits(Self::ACC_ABSTRACT);
+    pub const SYNTHETIC: Self = Self::from_bits(Self::ACC_SYNTHETIC);
+    pub const ANNOTATION: Self = Self::from_bits(Self::ACC_ANNOTATION);
+    pub const ENUM: Self = Self::from_bits(Self::ACC_ENUM);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
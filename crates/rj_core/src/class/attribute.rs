@@ -1,44 +1,237 @@
+mod annotation_default;
+mod annotations;
+mod bootstrap_methods;
 mod code;
+mod constant_value;
+mod custom;
+mod enclosing_method;
+mod inner_classes;
 mod line_number_table;
+mod local_variable_table;
+mod local_variable_type_table;
+mod permitted_subclasses;
+mod record;
+mod signature;
 mod source_file;
+mod stack_map_table;
+mod type_annotations;
 
-pub use self::code::{parse_code, Code};
-pub use self::line_number_table::{parse_line_number_table, LineNumberTable};
+pub use self::annotation_default::{parse_annotation_default, AnnotationDefault};
+pub use self::annotations::{
+    parse_annotation, parse_annotations, parse_element_value, parse_runtime_invisible_annotations,
+    parse_runtime_invisible_parameter_annotations, parse_runtime_visible_annotations,
+    parse_runtime_visible_parameter_annotations, Annotation, ElementValue, ElementValuePair,
+    RuntimeInvisibleAnnotations, RuntimeInvisibleParameterAnnotations, RuntimeVisibleAnnotations,
+    RuntimeVisibleParameterAnnotations,
+};
+pub use self::bootstrap_methods::{
+    parse_bootstrap_method, parse_bootstrap_methods, BootstrapMethod, BootstrapMethods,
+};
+pub use self::code::{parse_code, Code, ExceptionTableEntry};
+pub use self::constant_value::{parse_constant_value, ConstantValue};
+pub use self::custom::{
+    AttributeParserRegistry, AttributeParsers, CustomAttribute, CustomAttributeParser,
+    CustomAttributeValue,
+};
+pub use self::enclosing_method::{parse_enclosing_method, EnclosingMethod};
+pub use self::inner_classes::{parse_inner_class, parse_inner_classes, InnerClass, InnerClasses};
+pub use self::line_number_table::{
+    parse_line_number_table, parse_line_number_table_entry, LineNumberTable, LineNumberTableEntry,
+};
+pub use self::local_variable_table::{
+    parse_local_variable_table, parse_local_variable_table_entry, LocalVariableTable,
+    LocalVariableTableEntry,
+};
+pub use self::local_variable_type_table::{
+    parse_local_variable_type_table, parse_local_variable_type_table_entry, LocalVariableTypeTable,
+    LocalVariableTypeTableEntry,
+};
+pub use self::permitted_subclasses::{parse_permitted_subclasses, PermittedSubclasses};
+pub use self::record::{parse_record, parse_record_component, Record, RecordComponent};
+pub use self::signature::{parse_signature, Signature};
 pub use self::source_file::{parse_source_file, SourceFile};
+pub use self::stack_map_table::{
+    parse_stack_map_table, StackMapFrame, StackMapTable, VerificationTypeInfo,
+};
+pub use self::type_annotations::{
+    parse_runtime_invisible_type_annotations, parse_runtime_visible_type_annotations,
+    parse_target_info, parse_type_annotation, parse_type_path, LocalvarTarget,
+    RuntimeInvisibleTypeAnnotations, RuntimeVisibleTypeAnnotations, TargetInfo, TypeAnnotation,
+    TypePathEntry,
+};
+
+use std::borrow::Cow;
 
 use super::constant::Constant;
+use super::constant_pool::ConstantPool;
 use super::error::ClassParseError;
+use super::version::ClassVersion;
 use crate::parser::{be_u16, be_u32, bytes};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AttributeName {
+    AnnotationDefault,
+    BootstrapMethods,
     Code,
+    ConstantValue,
+    EnclosingMethod,
+    InnerClasses,
     LineNumberTable,
+    LocalVariableTable,
+    LocalVariableTypeTable,
+    PermittedSubclasses,
+    Record,
+    RuntimeInvisibleAnnotations,
+    RuntimeInvisibleParameterAnnotations,
+    RuntimeInvisibleTypeAnnotations,
+    RuntimeVisibleAnnotations,
+    RuntimeVisibleParameterAnnotations,
+    RuntimeVisibleTypeAnnotations,
+    Signature,
     SourceFile,
+    StackMapTable,
     // WIP
 }
 
 impl AttributeName {
     pub fn from_name(name: &[u8]) -> Option<Self> {
         match name {
+            b"AnnotationDefault" => Some(Self::AnnotationDefault),
+            b"BootstrapMethods" => Some(Self::BootstrapMethods),
             b"Code" => Some(Self::Code),
+            b"ConstantValue" => Some(Self::ConstantValue),
+            b"EnclosingMethod" => Some(Self::EnclosingMethod),
+            b"InnerClasses" => Some(Self::InnerClasses),
             b"LineNumberTable" => Some(Self::LineNumberTable),
+            b"LocalVariableTable" => Some(Self::LocalVariableTable),
+            b"LocalVariableTypeTable" => Some(Self::LocalVariableTypeTable),
+            b"PermittedSubclasses" => Some(Self::PermittedSubclasses),
+            b"Record" => Some(Self::Record),
+            b"RuntimeInvisibleAnnotations" => Some(Self::RuntimeInvisibleAnnotations),
+            b"RuntimeInvisibleParameterAnnotations" => {
+                Some(Self::RuntimeInvisibleParameterAnnotations)
+            }
+            b"RuntimeInvisibleTypeAnnotations" => Some(Self::RuntimeInvisibleTypeAnnotations),
+            b"RuntimeVisibleAnnotations" => Some(Self::RuntimeVisibleAnnotations),
+            b"RuntimeVisibleParameterAnnotations" => Some(Self::RuntimeVisibleParameterAnnotations),
+            b"RuntimeVisibleTypeAnnotations" => Some(Self::RuntimeVisibleTypeAnnotations),
+            b"Signature" => Some(Self::Signature),
             b"SourceFile" => Some(Self::SourceFile),
+            b"StackMapTable" => Some(Self::StackMapTable),
             // WIP
             _ => None,
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AnnotationDefault => "AnnotationDefault",
+            Self::BootstrapMethods => "BootstrapMethods",
+            Self::Code => "Code",
+            Self::ConstantValue => "ConstantValue",
+            Self::EnclosingMethod => "EnclosingMethod",
+            Self::InnerClasses => "InnerClasses",
+            Self::LineNumberTable => "LineNumberTable",
+            Self::LocalVariableTable => "LocalVariableTable",
+            Self::LocalVariableTypeTable => "LocalVariableTypeTable",
+            Self::PermittedSubclasses => "PermittedSubclasses",
+            Self::Record => "Record",
+            Self::RuntimeInvisibleAnnotations => "RuntimeInvisibleAnnotations",
+            Self::RuntimeInvisibleParameterAnnotations => "RuntimeInvisibleParameterAnnotations",
+            Self::RuntimeInvisibleTypeAnnotations => "RuntimeInvisibleTypeAnnotations",
+            Self::RuntimeVisibleAnnotations => "RuntimeVisibleAnnotations",
+            Self::RuntimeVisibleParameterAnnotations => "RuntimeVisibleParameterAnnotations",
+            Self::RuntimeVisibleTypeAnnotations => "RuntimeVisibleTypeAnnotations",
+            Self::Signature => "Signature",
+            Self::SourceFile => "SourceFile",
+            Self::StackMapTable => "StackMapTable",
+        }
+    }
+
+    // the first class file version that defines the attribute; older class
+    // files treat it as an unknown attribute
+    // https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-4.html#jvms-4.7-310
+    pub fn first_version(&self) -> ClassVersion {
+        match self {
+            Self::Code
+            | Self::ConstantValue
+            | Self::InnerClasses
+            | Self::LineNumberTable
+            | Self::LocalVariableTable
+            | Self::SourceFile => ClassVersion::new(45, 3),
+            Self::AnnotationDefault
+            | Self::EnclosingMethod
+            | Self::LocalVariableTypeTable
+            | Self::RuntimeInvisibleAnnotations
+            | Self::RuntimeInvisibleParameterAnnotations
+            | Self::RuntimeVisibleAnnotations
+            | Self::RuntimeVisibleParameterAnnotations
+            | Self::Signature => ClassVersion::new(49, 0),
+            Self::StackMapTable => ClassVersion::new(50, 0),
+            Self::BootstrapMethods => ClassVersion::new(51, 0),
+            Self::RuntimeInvisibleTypeAnnotations | Self::RuntimeVisibleTypeAnnotations => {
+                ClassVersion::new(52, 0)
+            }
+            Self::Record => ClassVersion::new(60, 0),
+            Self::PermittedSubclasses => ClassVersion::new(61, 0),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
 pub enum Attribute<'a> {
     Unknown {
         attribute_name_index: u16,
-        data: &'a [u8],
+        // resolved from attribute_name_index
+        name: Cow<'a, [u8]>,
+        data: Cow<'a, [u8]>,
+    },
+    // an attribute not decoded yet, from parse_raw_attribute
+    Raw {
+        attribute_name_index: u16,
+        name: Cow<'a, [u8]>,
+        data: Cow<'a, [u8]>,
     },
+    // produced by a registered AttributeParserRegistry. `data` is the payload
+    // `value` was parsed from, which is what gets written back
+    Custom {
+        attribute_name_index: u16,
+        name: Cow<'a, [u8]>,
+        data: Cow<'a, [u8]>,
+        value: CustomAttributeValue,
+    },
+    AnnotationDefault(AnnotationDefault),
+    BootstrapMethods(BootstrapMethods),
     Code(Code<'a, Attribute<'a>>),
+    ConstantValue(ConstantValue),
+    EnclosingMethod(EnclosingMethod),
+    InnerClasses(InnerClasses),
     LineNumberTable(LineNumberTable),
+    LocalVariableTable(LocalVariableTable),
+    LocalVariableTypeTable(LocalVariableTypeTable),
+    PermittedSubclasses(PermittedSubclasses),
+    Record(Record<Attribute<'a>>),
+    RuntimeInvisibleAnnotations(RuntimeInvisibleAnnotations),
+    RuntimeInvisibleParameterAnnotations(RuntimeInvisibleParameterAnnotations),
+    RuntimeInvisibleTypeAnnotations(RuntimeInvisibleTypeAnnotations),
+    RuntimeVisibleAnnotations(RuntimeVisibleAnnotations),
+    RuntimeVisibleParameterAnnotations(RuntimeVisibleParameterAnnotations),
+    RuntimeVisibleTypeAnnotations(RuntimeVisibleTypeAnnotations),
+    Signature(Signature),
     SourceFile(SourceFile),
+    StackMapTable(StackMapTable),
+}
+
+impl<'a> From<AnnotationDefault> for Attribute<'a> {
+    fn from(annotation_default: AnnotationDefault) -> Self {
+        Attribute::AnnotationDefault(annotation_default)
+    }
+}
+
+impl<'a> From<BootstrapMethods> for Attribute<'a> {
+    fn from(bootstrap_methods: BootstrapMethods) -> Self {
+        Attribute::BootstrapMethods(bootstrap_methods)
+    }
 }
 
 impl<'a> From<Code<'a, Attribute<'a>>> for Attribute<'a> {
@@ -47,25 +240,303 @@ impl<'a> From<Code<'a, Attribute<'a>>> for Attribute<'a> {
     }
 }
 
+impl<'a> From<ConstantValue> for Attribute<'a> {
+    fn from(constant_value: ConstantValue) -> Self {
+        Attribute::ConstantValue(constant_value)
+    }
+}
+
+impl<'a> From<EnclosingMethod> for Attribute<'a> {
+    fn from(enclosing_method: EnclosingMethod) -> Self {
+        Attribute::EnclosingMethod(enclosing_method)
+    }
+}
+
+impl<'a> From<InnerClasses> for Attribute<'a> {
+    fn from(inner_classes: InnerClasses) -> Self {
+        Attribute::InnerClasses(inner_classes)
+    }
+}
+
 impl<'a> From<LineNumberTable> for Attribute<'a> {
     fn from(line_number_table: LineNumberTable) -> Self {
         Attribute::LineNumberTable(line_number_table)
     }
 }
 
+impl<'a> From<LocalVariableTable> for Attribute<'a> {
+    fn from(local_variable_table: LocalVariableTable) -> Self {
+        Attribute::LocalVariableTable(local_variable_table)
+    }
+}
+
+impl<'a> From<LocalVariableTypeTable> for Attribute<'a> {
+    fn from(local_variable_type_table: LocalVariableTypeTable) -> Self {
+        Attribute::LocalVariableTypeTable(local_variable_type_table)
+    }
+}
+
+impl<'a> From<PermittedSubclasses> for Attribute<'a> {
+    fn from(permitted_subclasses: PermittedSubclasses) -> Self {
+        Attribute::PermittedSubclasses(permitted_subclasses)
+    }
+}
+
+impl<'a> From<Record<Attribute<'a>>> for Attribute<'a> {
+    fn from(record: Record<Attribute<'a>>) -> Self {
+        Attribute::Record(record)
+    }
+}
+
+impl<'a> From<RuntimeInvisibleAnnotations> for Attribute<'a> {
+    fn from(runtime_invisible_annotations: RuntimeInvisibleAnnotations) -> Self {
+        Attribute::RuntimeInvisibleAnnotations(runtime_invisible_annotations)
+    }
+}
+
+impl<'a> From<RuntimeVisibleAnnotations> for Attribute<'a> {
+    fn from(runtime_visible_annotations: RuntimeVisibleAnnotations) -> Self {
+        Attribute::RuntimeVisibleAnnotations(runtime_visible_annotations)
+    }
+}
+
+impl<'a> From<RuntimeInvisibleParameterAnnotations> for Attribute<'a> {
+    fn from(runtime_invisible_parameter_annotations: RuntimeInvisibleParameterAnnotations) -> Self {
+        Attribute::RuntimeInvisibleParameterAnnotations(runtime_invisible_parameter_annotations)
+    }
+}
+
+impl<'a> From<RuntimeInvisibleTypeAnnotations> for Attribute<'a> {
+    fn from(runtime_invisible_type_annotations: RuntimeInvisibleTypeAnnotations) -> Self {
+        Attribute::RuntimeInvisibleTypeAnnotations(runtime_invisible_type_annotations)
+    }
+}
+
+impl<'a> From<RuntimeVisibleParameterAnnotations> for Attribute<'a> {
+    fn from(runtime_visible_parameter_annotations: RuntimeVisibleParameterAnnotations) -> Self {
+        Attribute::RuntimeVisibleParameterAnnotations(runtime_visible_parameter_annotations)
+    }
+}
+
+impl<'a> From<RuntimeVisibleTypeAnnotations> for Attribute<'a> {
+    fn from(runtime_visible_type_annotations: RuntimeVisibleTypeAnnotations) -> Self {
+        Attribute::RuntimeVisibleTypeAnnotations(runtime_visible_type_annotations)
+    }
+}
+
+impl<'a> From<Signature> for Attribute<'a> {
+    fn from(signature: Signature) -> Self {
+        Attribute::Signature(signature)
+    }
+}
+
 impl<'a> From<SourceFile> for Attribute<'a> {
     fn from(source_file: SourceFile) -> Self {
         Attribute::SourceFile(source_file)
     }
 }
 
+impl<'a> From<StackMapTable> for Attribute<'a> {
+    fn from(stack_map_table: StackMapTable) -> Self {
+        Attribute::StackMapTable(stack_map_table)
+    }
+}
+
+impl<'a> Attribute<'a> {
+    // None for attributes this crate does not decode
+    pub fn attribute_name(&self) -> Option<AttributeName> {
+        match self {
+            Attribute::Unknown { .. } | Attribute::Custom { .. } => None,
+            Attribute::Raw { name, .. } => AttributeName::from_name(name),
+            Attribute::AnnotationDefault(_) => Some(AttributeName::AnnotationDefault),
+            Attribute::BootstrapMethods(_) => Some(AttributeName::BootstrapMethods),
+            Attribute::Code(_) => Some(AttributeName::Code),
+            Attribute::ConstantValue(_) => Some(AttributeName::ConstantValue),
+            Attribute::EnclosingMethod(_) => Some(AttributeName::EnclosingMethod),
+            Attribute::InnerClasses(_) => Some(AttributeName::InnerClasses),
+            Attribute::LineNumberTable(_) => Some(AttributeName::LineNumberTable),
+            Attribute::LocalVariableTable(_) => Some(AttributeName::LocalVariableTable),
+            Attribute::LocalVariableTypeTable(_) => Some(AttributeName::LocalVariableTypeTable),
+            Attribute::PermittedSubclasses(_) => Some(AttributeName::PermittedSubclasses),
+            Attribute::Record(_) => Some(AttributeName::Record),
+            Attribute::RuntimeInvisibleAnnotations(_) => {
+                Some(AttributeName::RuntimeInvisibleAnnotations)
+            }
+            Attribute::RuntimeInvisibleParameterAnnotations(_) => {
+                Some(AttributeName::RuntimeInvisibleParameterAnnotations)
+            }
+            Attribute::RuntimeInvisibleTypeAnnotations(_) => {
+                Some(AttributeName::RuntimeInvisibleTypeAnnotations)
+            }
+            Attribute::RuntimeVisibleAnnotations(_) => {
+                Some(AttributeName::RuntimeVisibleAnnotations)
+            }
+            Attribute::RuntimeVisibleParameterAnnotations(_) => {
+                Some(AttributeName::RuntimeVisibleParameterAnnotations)
+            }
+            Attribute::RuntimeVisibleTypeAnnotations(_) => {
+                Some(AttributeName::RuntimeVisibleTypeAnnotations)
+            }
+            Attribute::Signature(_) => Some(AttributeName::Signature),
+            Attribute::SourceFile(_) => Some(AttributeName::SourceFile),
+            Attribute::StackMapTable(_) => Some(AttributeName::StackMapTable),
+        }
+    }
+
+    // copies anything borrowed from the input, including nested attributes
+    pub fn into_owned(self) -> Attribute<'static> {
+        let attributes = |attributes: Vec<Attribute>| {
+            attributes.into_iter().map(Attribute::into_owned).collect()
+        };
+        match self {
+            Attribute::Unknown {
+                attribute_name_index,
+                name,
+                data,
+            } => Attribute::Unknown {
+                attribute_name_index,
+                name: Cow::Owned(name.into_owned()),
+                data: Cow::Owned(data.into_owned()),
+            },
+            Attribute::Raw {
+                attribute_name_index,
+                name,
+                data,
+            } => Attribute::Raw {
+                attribute_name_index,
+                name: Cow::Owned(name.into_owned()),
+                data: Cow::Owned(data.into_owned()),
+            },
+            Attribute::Custom {
+                attribute_name_index,
+                name,
+                data,
+                value,
+            } => Attribute::Custom {
+                attribute_name_index,
+                name: Cow::Owned(name.into_owned()),
+                data: Cow::Owned(data.into_owned()),
+                value,
+            },
+            Attribute::AnnotationDefault(attribute) => Attribute::AnnotationDefault(attribute),
+            Attribute::BootstrapMethods(attribute) => Attribute::BootstrapMethods(attribute),
+            Attribute::Code(code) => Attribute::Code(Code {
+                max_stack: code.max_stack,
+                max_locals: code.max_locals,
+                code: Cow::Owned(code.code.into_owned()),
+                exception_table: code.exception_table,
+                attributes: attributes(code.attributes),
+            }),
+            Attribute::ConstantValue(attribute) => Attribute::ConstantValue(attribute),
+            Attribute::EnclosingMethod(attribute) => Attribute::EnclosingMethod(attribute),
+            Attribute::InnerClasses(attribute) => Attribute::InnerClasses(attribute),
+            Attribute::LineNumberTable(attribute) => Attribute::LineNumberTable(attribute),
+            Attribute::LocalVariableTable(attribute) => Attribute::LocalVariableTable(attribute),
+            Attribute::LocalVariableTypeTable(attribute) => {
+                Attribute::LocalVariableTypeTable(attribute)
+            }
+            Attribute::PermittedSubclasses(attribute) => Attribute::PermittedSubclasses(attribute),
+            Attribute::Record(record) => Attribute::Record(Record {
+                components: record
+                    .components
+                    .into_iter()
+                    .map(|component| RecordComponent {
+                        name_index: component.name_index,
+                        descriptor_index: component.descriptor_index,
+                        attributes: attributes(component.attributes),
+                    })
+                    .collect(),
+            }),
+            Attribute::RuntimeInvisibleAnnotations(attribute) => {
+                Attribute::RuntimeInvisibleAnnotations(attribute)
+            }
+            Attribute::RuntimeInvisibleParameterAnnotations(attribute) => {
+                Attribute::RuntimeInvisibleParameterAnnotations(attribute)
+            }
+            Attribute::RuntimeInvisibleTypeAnnotations(attribute) => {
+                Attribute::RuntimeInvisibleTypeAnnotations(attribute)
+            }
+            Attribute::RuntimeVisibleAnnotations(attribute) => {
+                Attribute::RuntimeVisibleAnnotations(attribute)
+            }
+            Attribute::RuntimeVisibleParameterAnnotations(attribute) => {
+                Attribute::RuntimeVisibleParameterAnnotations(attribute)
+            }
+            Attribute::RuntimeVisibleTypeAnnotations(attribute) => {
+                Attribute::RuntimeVisibleTypeAnnotations(attribute)
+            }
+            Attribute::Signature(attribute) => Attribute::Signature(attribute),
+            Attribute::SourceFile(attribute) => Attribute::SourceFile(attribute),
+            Attribute::StackMapTable(attribute) => Attribute::StackMapTable(attribute),
+        }
+    }
+
+    // decodes an Attribute::Raw, including any nested attributes; None for
+    // attributes that are already decoded
+    pub fn decode<'b>(
+        &'b self,
+        constant_pool: &ConstantPool<'b>,
+    ) -> Option<Result<Attribute<'b>, ClassParseError>> {
+        self.decode_with(constant_pool, &AttributeParsers::new())
+    }
+
+    pub fn decode_with<'b>(
+        &'b self,
+        constant_pool: &ConstantPool<'b>,
+        registry: &dyn AttributeParserRegistry,
+    ) -> Option<Result<Attribute<'b>, ClassParseError>> {
+        match self {
+            Attribute::Raw {
+                attribute_name_index,
+                name,
+                data,
+            } => Some(parse_attribute_data(
+                *attribute_name_index,
+                name.clone(),
+                data,
+                constant_pool,
+                registry,
+            )),
+            _ => None,
+        }
+    }
+}
+
 pub fn parse_attribute<'a>(
     input: &'a [u8],
-    constant_pool: &[Constant],
+    constant_pool: &ConstantPool<'a>,
+) -> Result<(&'a [u8], Attribute<'a>), ClassParseError> {
+    parse_attribute_with(input, constant_pool, &AttributeParsers::new())
+}
+
+pub fn parse_attribute_with<'a>(
+    input: &'a [u8],
+    constant_pool: &ConstantPool<'a>,
+    registry: &dyn AttributeParserRegistry,
+) -> Result<(&'a [u8], Attribute<'a>), ClassParseError> {
+    let (input, raw) = parse_raw_attribute(input, constant_pool)?;
+    // parse_raw_attribute only produces an Attribute::Raw borrowing from input
+    let Attribute::Raw {
+        attribute_name_index,
+        name,
+        data: Cow::Borrowed(data),
+    } = raw
+    else {
+        unreachable!()
+    };
+    let attribute =
+        parse_attribute_data(attribute_name_index, name, data, constant_pool, registry)?;
+    Ok((input, attribute))
+}
+
+// lazy mode: keeps the payload as an Attribute::Raw, see Attribute::decode
+pub fn parse_raw_attribute<'a>(
+    input: &'a [u8],
+    constant_pool: &ConstantPool<'a>,
 ) -> Result<(&'a [u8], Attribute<'a>), ClassParseError> {
     let (input, attribute_name_index) = be_u16(input)?;
-    let name = match constant_pool.get(attribute_name_index as usize - 1) {
-        Some(Constant::Utf8 { value }) => *value,
+    let name = match constant_pool.get(attribute_name_index) {
+        Some(Constant::Utf8 { value }) => value.clone(),
         _ => {
             return Err(ClassParseError::InvalidConstantPoolIndex(
                 attribute_name_index,
@@ -73,22 +544,83 @@ pub fn parse_attribute<'a>(
         }
     };
     let (input, attribute_length) = be_u32(input)?;
-    let (input, attribute) = match AttributeName::from_name(name) {
-        Some(AttributeName::Code) => parse_code(input, constant_pool, parse_attribute)?,
-        Some(AttributeName::LineNumberTable) => parse_line_number_table(input)?,
-        Some(AttributeName::SourceFile) => parse_source_file(input)?,
-        _ => {
-            let (input, data) = bytes(input, attribute_length as usize)?;
-            (
-                input,
+    let (input, data) = bytes(input, attribute_length as usize)?;
+    Ok((
+        input,
+        Attribute::Raw {
+            attribute_name_index,
+            name,
+            data: Cow::Borrowed(data),
+        },
+    ))
+}
+
+fn parse_attribute_data<'a>(
+    attribute_name_index: u16,
+    name: Cow<'a, [u8]>,
+    data: &'a [u8],
+    constant_pool: &ConstantPool<'a>,
+    registry: &dyn AttributeParserRegistry,
+) -> Result<Attribute<'a>, ClassParseError> {
+    let parse_nested = |input, constant_pool: &ConstantPool<'a>| {
+        parse_attribute_with(input, constant_pool, registry)
+    };
+    // sub-parsers only see the declared payload, and must consume all of it
+    let (rest, attribute) = match AttributeName::from_name(&name) {
+        Some(AttributeName::AnnotationDefault) => parse_annotation_default(data)?,
+        Some(AttributeName::BootstrapMethods) => parse_bootstrap_methods(data)?,
+        Some(AttributeName::Code) => parse_code(data, constant_pool, parse_nested)?,
+        Some(AttributeName::ConstantValue) => parse_constant_value(data)?,
+        Some(AttributeName::EnclosingMethod) => parse_enclosing_method(data)?,
+        Some(AttributeName::InnerClasses) => parse_inner_classes(data)?,
+        Some(AttributeName::LineNumberTable) => parse_line_number_table(data)?,
+        Some(AttributeName::LocalVariableTable) => parse_local_variable_table(data)?,
+        Some(AttributeName::LocalVariableTypeTable) => parse_local_variable_type_table(data)?,
+        Some(AttributeName::PermittedSubclasses) => parse_permitted_subclasses(data)?,
+        Some(AttributeName::Record) => parse_record(data, constant_pool, parse_nested)?,
+        Some(AttributeName::RuntimeInvisibleAnnotations) => {
+            parse_runtime_invisible_annotations(data)?
+        }
+        Some(AttributeName::RuntimeVisibleAnnotations) => parse_runtime_visible_annotations(data)?,
+        Some(AttributeName::RuntimeInvisibleParameterAnnotations) => {
+            parse_runtime_invisible_parameter_annotations(data)?
+        }
+        Some(AttributeName::RuntimeInvisibleTypeAnnotations) => {
+            parse_runtime_invisible_type_annotations(data)?
+        }
+        Some(AttributeName::RuntimeVisibleParameterAnnotations) => {
+            parse_runtime_visible_parameter_annotations(data)?
+        }
+        Some(AttributeName::RuntimeVisibleTypeAnnotations) => {
+            parse_runtime_visible_type_annotations(data)?
+        }
+        Some(AttributeName::Signature) => parse_signature(data)?,
+        Some(AttributeName::SourceFile) => parse_source_file(data)?,
+        Some(AttributeName::StackMapTable) => parse_stack_map_table(data)?,
+        None => match registry.parse(&name, data, constant_pool) {
+            Some(value) => (
+                &data[data.len()..],
+                Attribute::Custom {
+                    attribute_name_index,
+                    name,
+                    data: Cow::Borrowed(data),
+                    value: value?,
+                },
+            ),
+            None => (
+                &data[data.len()..],
                 Attribute::Unknown {
                     attribute_name_index,
-                    data,
+                    name,
+                    data: Cow::Borrowed(data),
                 },
-            )
-        }
+            ),
+        },
     };
-    Ok((input, attribute))
+    if !rest.is_empty() {
+        return Err(ClassParseError::TrailingAttributeBytes(rest.len() as u32));
+    }
+    Ok(attribute)
 }
 
 #[cfg(test)]
@@ -103,17 +635,269 @@ mod tests {
             0x00, 0x01, 0x02, 0x03, // data
             0x12, 0x34, // rest
         ];
-        let constant_pool = vec![Constant::Utf8 {
-            value: b"Unknown_Attribute_Name",
-        }];
+        let constant_pool = ConstantPool::from(vec![Constant::Utf8 {
+            value: b"Unknown_Attribute_Name".into(),
+        }]);
         let (rest, attribute) = parse_attribute(&input, &constant_pool).unwrap();
         assert_eq!(rest, &[0x12, 0x34]);
         assert_eq!(
             attribute,
             Attribute::Unknown {
                 attribute_name_index: 0x0001,
-                data: &[0x00, 0x01, 0x02, 0x03]
+                name: b"Unknown_Attribute_Name".into(),
+                data: vec![0x00, 0x01, 0x02, 0x03].into()
             }
         );
     }
+
+    #[test]
+    fn test_parse_attribute_length() {
+        let constant_pool = ConstantPool::from(vec![Constant::Utf8 {
+            value: b"SourceFile".into(),
+        }]);
+        let input = [
+            0x00, 0x01, // attribute_name_index
+            0x00, 0x00, 0x00, 0x03, // attribute_length
+            0x00, 0x01, // sourcefile_index
+            0x00, // trailing
+            0x12, 0x34, // rest
+        ];
+        assert_eq!(
+            parse_attribute(&input, &constant_pool),
+            Err(ClassParseError::TrailingAttributeBytes(1))
+        );
+
+        // a payload shorter than the attribute needs is not read past
+        let input = [
+            0x00, 0x01, // attribute_name_index
+            0x00, 0x00, 0x00, 0x01, // attribute_length
+            0x00, 0x01, // sourcefile_index
+        ];
+        assert_eq!(
+            parse_attribute(&input, &constant_pool),
+            Err(ClassParseError::ParseError(crate::parser::ParseError::Eof))
+        );
+    }
+
+    #[test]
+    fn test_parse_line_number_table_attribute() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = crate::class::parse_classfile(data).unwrap();
+        let Some(Attribute::Code(code)) = classfile.methods[0].attributes.first() else {
+            panic!("expected Code");
+        };
+        let Some(Attribute::LineNumberTable(table)) = code.attributes.first() else {
+            panic!("expected LineNumberTable");
+        };
+        let lines = table
+            .line_number_table
+            .iter()
+            .map(|entry| (entry.start_pc, entry.line_number))
+            .collect::<Vec<_>>();
+        assert_eq!(lines, [(0, 1), (4, 3)]);
+
+        let source_file = classfile
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::SourceFile(source_file) => Some(source_file),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            classfile.constant_pool.get(source_file.sourcefile_index),
+            Some(&Constant::Utf8 {
+                value: b"HelloWorld.java".into()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_bootstrap_methods_attribute() {
+        let data = include_bytes!("../../../../java/Insns.class");
+        let (_, classfile) = crate::class::parse_classfile(data).unwrap();
+        let bootstrap_methods = classfile
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::BootstrapMethods(bootstrap_methods) => Some(bootstrap_methods),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            bootstrap_methods.bootstrap_methods,
+            vec![BootstrapMethod {
+                bootstrap_method_ref: 60,
+                bootstrap_arguments: vec![67, 68, 67],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_annotations_attribute() {
+        let data = include_bytes!("../../../../java/Annotated.class");
+        let (_, classfile) = crate::class::parse_classfile(data).unwrap();
+        let visible = classfile
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::RuntimeVisibleAnnotations(annotations) => Some(annotations),
+                _ => None,
+            })
+            .unwrap();
+        // @Info(name = "type", tags = {"a", "b"}, kind = TYPE, type = String.class, nested = @Marker)
+        let values = visible.annotations[0]
+            .element_value_pairs
+            .iter()
+            .map(|pair| &pair.value)
+            .collect::<Vec<_>>();
+        assert_eq!(values.len(), 5);
+        assert!(matches!(values[0], ElementValue::Const { tag: b's', .. }));
+        assert!(matches!(values[1], ElementValue::Array(tags) if tags.len() == 2));
+        assert!(matches!(values[2], ElementValue::Enum { .. }));
+        assert!(matches!(values[3], ElementValue::Class { .. }));
+        assert!(matches!(values[4], ElementValue::Annotation(_)));
+
+        assert!(classfile.attributes.iter().any(|attribute| matches!(
+            attribute,
+            Attribute::RuntimeInvisibleAnnotations(RuntimeInvisibleAnnotations { annotations })
+                if annotations.len() == 1
+        )));
+        let field_annotations = classfile.fields[0]
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::RuntimeVisibleAnnotations(annotations) => Some(annotations),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(field_annotations.annotations.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_parameter_and_type_annotations_attributes() {
+        let data = include_bytes!("../../../../java/Annotated.class");
+        let (_, classfile) = crate::class::parse_classfile(data).unwrap();
+        // method(@Marker int a, int b, @Hidden @Marker String c)
+        let method = &classfile.methods[1];
+        let visible = method
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::RuntimeVisibleParameterAnnotations(annotations) => Some(annotations),
+                _ => None,
+            })
+            .unwrap();
+        let counts = visible
+            .parameter_annotations
+            .iter()
+            .map(Vec::len)
+            .collect::<Vec<_>>();
+        assert_eq!(counts, [1, 0, 1]);
+        assert!(method.attributes.iter().any(|attribute| matches!(
+            attribute,
+            Attribute::RuntimeInvisibleParameterAnnotations(_)
+        )));
+
+        // <@Typed T> List<@Typed String> typed(@Typed Object o)
+        let method = &classfile.methods[2];
+        let targets = method
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::RuntimeVisibleTypeAnnotations(annotations) => Some(annotations),
+                _ => None,
+            })
+            .unwrap()
+            .annotations
+            .iter()
+            .map(|annotation| &annotation.target_info)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            targets,
+            [
+                &TargetInfo::Empty,
+                &TargetInfo::TypeParameter {
+                    type_parameter_index: 0
+                },
+                &TargetInfo::FormalParameter {
+                    formal_parameter_index: 0
+                },
+            ]
+        );
+        // @Typed String s, on the Code attribute
+        let code = method
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Code(code) => Some(code),
+                _ => None,
+            })
+            .unwrap();
+        assert!(code.attributes.iter().any(|attribute| matches!(
+            attribute,
+            Attribute::RuntimeVisibleTypeAnnotations(RuntimeVisibleTypeAnnotations { annotations })
+                if matches!(annotations[0].target_info, TargetInfo::Localvar(_))
+        )));
+    }
+
+    #[test]
+    fn test_parse_annotation_default_attribute() {
+        let data = include_bytes!("../../../../java/Annotated$Info.class");
+        let (_, classfile) = crate::class::parse_classfile(data).unwrap();
+        // name, count, tags, kind, type, nested
+        let defaults = classfile
+            .methods
+            .iter()
+            .map(|method| {
+                method
+                    .attributes
+                    .iter()
+                    .find_map(|attribute| match attribute {
+                        Attribute::AnnotationDefault(annotation_default) => {
+                            Some(&annotation_default.default_value)
+                        }
+                        _ => None,
+                    })
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(defaults.len(), 6);
+        assert!(matches!(defaults[0], ElementValue::Const { tag: b's', .. }));
+        assert!(matches!(defaults[1], ElementValue::Const { tag: b'I', .. }));
+        assert_eq!(defaults[2], &ElementValue::Array(vec![]));
+        assert!(matches!(defaults[3], ElementValue::Enum { .. }));
+        assert!(matches!(defaults[4], ElementValue::Class { .. }));
+        assert!(matches!(defaults[5], ElementValue::Annotation(_)));
+    }
+
+    #[test]
+    fn test_parse_record_attribute() {
+        let data = include_bytes!("../../../../java/Point.class");
+        let (_, classfile) = crate::class::parse_classfile(data).unwrap();
+        let record = classfile
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Record(record) => Some(record),
+                _ => None,
+            })
+            .unwrap();
+        let names = record
+            .components
+            .iter()
+            .map(
+                |component| match classfile.constant_pool.get(component.name_index).unwrap() {
+                    Constant::Utf8 { value } => &**value,
+                    _ => panic!(),
+                },
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(names, [&b"x"[..], b"y", b"tags"]);
+        assert!(record.components[0].attributes.is_empty());
+        assert!(matches!(
+            record.components[2].attributes[..],
+            [Attribute::Signature(_)]
+        ));
+    }
 }
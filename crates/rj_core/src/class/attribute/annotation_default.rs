@@ -0,0 +1,35 @@
+use super::super::error::ClassParseError;
+use super::annotations::{parse_element_value, ElementValue};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotationDefault {
+    // the default of the annotation interface element this method declares
+    pub default_value: ElementValue,
+}
+
+pub fn parse_annotation_default<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<AnnotationDefault>,
+{
+    let (input, default_value) = parse_element_value(input)?;
+    let attribute = AnnotationDefault { default_value };
+    Ok((input, attribute.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_annotation_default() {
+        let input = [b'[', 0x00, 0x00, 0x99];
+        let (input, result) = parse_annotation_default::<AnnotationDefault>(&input).unwrap();
+        assert_eq!(input, &[0x99]);
+        assert_eq!(
+            result,
+            AnnotationDefault {
+                default_value: ElementValue::Array(vec![])
+            }
+        );
+    }
+}
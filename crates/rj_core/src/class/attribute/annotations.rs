@@ -0,0 +1,290 @@
+use super::super::error::ClassParseError;
+use crate::parser::{be_u16, be_u8};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    // a Utf8 constant holding a field descriptor, e.g. `Ljava/lang/Deprecated;`
+    pub type_index: u16,
+    pub element_value_pairs: Vec<ElementValuePair>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementValuePair {
+    pub element_name_index: u16,
+    pub value: ElementValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElementValue {
+    // tag is one of `BCDFIJSZs`; the constant is an Integer, Long, Float,
+    // Double or (for `s`) Utf8
+    Const {
+        tag: u8,
+        const_value_index: u16,
+    },
+    Enum {
+        type_name_index: u16,
+        const_name_index: u16,
+    },
+    Class {
+        // a Utf8 constant holding a return descriptor, e.g. `Ljava/lang/String;` or `V`
+        class_info_index: u16,
+    },
+    Annotation(Annotation),
+    Array(Vec<ElementValue>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeVisibleAnnotations {
+    pub annotations: Vec<Annotation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeInvisibleAnnotations {
+    pub annotations: Vec<Annotation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeVisibleParameterAnnotations {
+    // one list per parameter, possibly fewer than the descriptor has
+    pub parameter_annotations: Vec<Vec<Annotation>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeInvisibleParameterAnnotations {
+    pub parameter_annotations: Vec<Vec<Annotation>>,
+}
+
+pub fn parse_element_value(input: &[u8]) -> Result<(&[u8], ElementValue), ClassParseError> {
+    let (input, tag) = be_u8(input)?;
+    match tag {
+        b'B' | b'C' | b'D' | b'F' | b'I' | b'J' | b'S' | b'Z' | b's' => {
+            let (input, const_value_index) = be_u16(input)?;
+            Ok((
+                input,
+                ElementValue::Const {
+                    tag,
+                    const_value_index,
+                },
+            ))
+        }
+        b'e' => {
+            let (input, type_name_index) = be_u16(input)?;
+            let (input, const_name_index) = be_u16(input)?;
+            Ok((
+                input,
+                ElementValue::Enum {
+                    type_name_index,
+                    const_name_index,
+                },
+            ))
+        }
+        b'c' => {
+            let (input, class_info_index) = be_u16(input)?;
+            Ok((input, ElementValue::Class { class_info_index }))
+        }
+        b'@' => {
+            let (input, annotation) = parse_annotation(input)?;
+            Ok((input, ElementValue::Annotation(annotation)))
+        }
+        b'[' => {
+            let (input, num_values) = be_u16(input)?;
+            let mut values = Vec::new();
+            let mut input = input;
+            for _ in 0..num_values {
+                let (new_input, value) = parse_element_value(input)?;
+                input = new_input;
+                values.push(value);
+            }
+            Ok((input, ElementValue::Array(values)))
+        }
+        _ => Err(ClassParseError::InvalidElementValueTag(tag)),
+    }
+}
+
+pub fn parse_annotation(input: &[u8]) -> Result<(&[u8], Annotation), ClassParseError> {
+    let (input, type_index) = be_u16(input)?;
+    let (input, num_element_value_pairs) = be_u16(input)?;
+    let mut element_value_pairs = Vec::new();
+    let mut input = input;
+    for _ in 0..num_element_value_pairs {
+        let (new_input, element_name_index) = be_u16(input)?;
+        let (new_input, value) = parse_element_value(new_input)?;
+        input = new_input;
+        element_value_pairs.push(ElementValuePair {
+            element_name_index,
+            value,
+        });
+    }
+    Ok((
+        input,
+        Annotation {
+            type_index,
+            element_value_pairs,
+        },
+    ))
+}
+
+pub fn parse_annotations(input: &[u8]) -> Result<(&[u8], Vec<Annotation>), ClassParseError> {
+    let (input, num_annotations) = be_u16(input)?;
+    let mut annotations = Vec::new();
+    let mut input = input;
+    for _ in 0..num_annotations {
+        let (new_input, annotation) = parse_annotation(input)?;
+        input = new_input;
+        annotations.push(annotation);
+    }
+    Ok((input, annotations))
+}
+
+pub fn parse_runtime_visible_annotations<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<RuntimeVisibleAnnotations>,
+{
+    let (input, annotations) = parse_annotations(input)?;
+    let attribute = RuntimeVisibleAnnotations { annotations };
+    Ok((input, attribute.into()))
+}
+
+pub fn parse_runtime_invisible_annotations<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<RuntimeInvisibleAnnotations>,
+{
+    let (input, annotations) = parse_annotations(input)?;
+    let attribute = RuntimeInvisibleAnnotations { annotations };
+    Ok((input, attribute.into()))
+}
+
+fn parse_parameter_annotations(
+    input: &[u8],
+) -> Result<(&[u8], Vec<Vec<Annotation>>), ClassParseError> {
+    let (input, num_parameters) = be_u8(input)?;
+    let mut parameter_annotations = Vec::new();
+    let mut input = input;
+    for _ in 0..num_parameters {
+        let (new_input, annotations) = parse_annotations(input)?;
+        input = new_input;
+        parameter_annotations.push(annotations);
+    }
+    Ok((input, parameter_annotations))
+}
+
+pub fn parse_runtime_visible_parameter_annotations<A>(
+    input: &[u8],
+) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<RuntimeVisibleParameterAnnotations>,
+{
+    let (input, parameter_annotations) = parse_parameter_annotations(input)?;
+    let attribute = RuntimeVisibleParameterAnnotations {
+        parameter_annotations,
+    };
+    Ok((input, attribute.into()))
+}
+
+pub fn parse_runtime_invisible_parameter_annotations<A>(
+    input: &[u8],
+) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<RuntimeInvisibleParameterAnnotations>,
+{
+    let (input, parameter_annotations) = parse_parameter_annotations(input)?;
+    let attribute = RuntimeInvisibleParameterAnnotations {
+        parameter_annotations,
+    };
+    Ok((input, attribute.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_element_value() {
+        let input = [
+            b'[', 0x00, 0x03, // num_values
+            b'I', 0x00, 0x18, // const
+            b'e', 0x00, 0x2f, 0x00, 0x30, // enum
+            b'@', 0x00, 0x20, 0x00, 0x00, // annotation
+            0x99,
+        ];
+        let (input, value) = parse_element_value(&input).unwrap();
+        assert_eq!(input, &[0x99]);
+        assert_eq!(
+            value,
+            ElementValue::Array(vec![
+                ElementValue::Const {
+                    tag: b'I',
+                    const_value_index: 0x18
+                },
+                ElementValue::Enum {
+                    type_name_index: 0x2f,
+                    const_name_index: 0x30
+                },
+                ElementValue::Annotation(Annotation {
+                    type_index: 0x20,
+                    element_value_pairs: vec![]
+                }),
+            ])
+        );
+
+        assert_eq!(
+            parse_element_value(&[b'x', 0x00, 0x01]),
+            Err(ClassParseError::InvalidElementValueTag(b'x'))
+        );
+    }
+
+    #[test]
+    fn test_parse_runtime_visible_annotations() {
+        let input = [
+            0x00, 0x02, // num_annotations
+            0x00, 0x15, 0x00, 0x00, // @Deprecated
+            0x00, 0x16, 0x00, 0x01, 0x00, 0x17, b'I', 0x00, 0x18, // @Info(count = 3)
+        ];
+        let (input, result) =
+            parse_runtime_visible_annotations::<RuntimeVisibleAnnotations>(&input).unwrap();
+        assert!(input.is_empty());
+        assert_eq!(
+            result.annotations,
+            vec![
+                Annotation {
+                    type_index: 0x15,
+                    element_value_pairs: vec![],
+                },
+                Annotation {
+                    type_index: 0x16,
+                    element_value_pairs: vec![ElementValuePair {
+                        element_name_index: 0x17,
+                        value: ElementValue::Const {
+                            tag: b'I',
+                            const_value_index: 0x18
+                        },
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_runtime_visible_parameter_annotations() {
+        let input = [
+            0x03, // num_parameters
+            0x00, 0x01, 0x00, 0x20, 0x00, 0x00, // @Marker
+            0x00, 0x00, // no annotations
+            0x00, 0x01, 0x00, 0x20, 0x00, 0x00, // @Marker
+        ];
+        let (input, result) = parse_runtime_visible_parameter_annotations::<
+            RuntimeVisibleParameterAnnotations,
+        >(&input)
+        .unwrap();
+        assert!(input.is_empty());
+        let marker = Annotation {
+            type_index: 0x20,
+            element_value_pairs: vec![],
+        };
+        assert_eq!(
+            result.parameter_annotations,
+            vec![vec![marker.clone()], vec![], vec![marker]]
+        );
+    }
+}
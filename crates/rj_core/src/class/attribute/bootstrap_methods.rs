@@ -0,0 +1,81 @@
+use super::super::error::ClassParseError;
+use crate::parser::be_u16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootstrapMethod {
+    // a MethodHandle constant
+    pub bootstrap_method_ref: u16,
+    // loadable constants passed as the static arguments
+    pub bootstrap_arguments: Vec<u16>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootstrapMethods {
+    pub bootstrap_methods: Vec<BootstrapMethod>,
+}
+
+pub fn parse_bootstrap_method(input: &[u8]) -> Result<(&[u8], BootstrapMethod), ClassParseError> {
+    let (input, bootstrap_method_ref) = be_u16(input)?;
+    let (input, num_bootstrap_arguments) = be_u16(input)?;
+    let mut bootstrap_arguments = Vec::new();
+    let mut input = input;
+    for _ in 0..num_bootstrap_arguments {
+        let (new_input, argument) = be_u16(input)?;
+        input = new_input;
+        bootstrap_arguments.push(argument);
+    }
+    Ok((
+        input,
+        BootstrapMethod {
+            bootstrap_method_ref,
+            bootstrap_arguments,
+        },
+    ))
+}
+
+pub fn parse_bootstrap_methods<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<BootstrapMethods>,
+{
+    let (input, num_bootstrap_methods) = be_u16(input)?;
+    let mut bootstrap_methods = Vec::new();
+    let mut input = input;
+    for _ in 0..num_bootstrap_methods {
+        let (new_input, bootstrap_method) = parse_bootstrap_method(input)?;
+        input = new_input;
+        bootstrap_methods.push(bootstrap_method);
+    }
+    let attribute = BootstrapMethods { bootstrap_methods };
+    Ok((input, attribute.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bootstrap_methods() {
+        let input = [
+            0x00, 0x02, // num_bootstrap_methods
+            0x00, 0x10, 0x00, 0x03, // bootstrap_methods[0]
+            0x00, 0x11, 0x00, 0x12, 0x00, 0x13, // bootstrap_arguments
+            0x00, 0x20, 0x00, 0x00, // bootstrap_methods[1]
+            0x99, // rest
+        ];
+        let expected = BootstrapMethods {
+            bootstrap_methods: vec![
+                BootstrapMethod {
+                    bootstrap_method_ref: 0x10,
+                    bootstrap_arguments: vec![0x11, 0x12, 0x13],
+                },
+                BootstrapMethod {
+                    bootstrap_method_ref: 0x20,
+                    bootstrap_arguments: vec![],
+                },
+            ],
+        };
+        let (input, result) = parse_bootstrap_methods::<BootstrapMethods>(&input).unwrap();
+        assert_eq!(input, &[0x99]);
+        assert_eq!(result, expected);
+    }
+}
@@ -1,13 +1,15 @@
-use super::super::constant::Constant;
+use std::borrow::Cow;
+
+use super::super::constant_pool::ConstantPool;
 use super::super::error::ClassParseError;
 use crate::parser::{be_u16, be_u32, bytes};
 
 #[derive(Debug, PartialEq)]
 pub struct ExceptionTableEntry {
-    start_pc: u16,
-    end_pc: u16,
-    handler_pc: u16,
-    catch_type: u16,
+    pub start_pc: u16,
+    pub end_pc: u16,
+    pub handler_pc: u16,
+    pub catch_type: u16,
 }
 
 fn parser_exception_table_entry(
@@ -30,21 +32,21 @@ fn parser_exception_table_entry(
 
 #[derive(Debug, PartialEq)]
 pub struct Code<'a, A> {
-    max_stack: u16,
-    max_locals: u16,
-    code: &'a [u8],
-    exception_table: Vec<ExceptionTableEntry>,
-    attributes: Vec<A>,
+    pub max_stack: u16,
+    pub max_locals: u16,
+    pub code: Cow<'a, [u8]>,
+    pub exception_table: Vec<ExceptionTableEntry>,
+    pub attributes: Vec<A>,
 }
 
 pub fn parse_code<'a, A, F>(
     input: &'a [u8],
-    constant_pool: &[Constant],
+    constant_pool: &ConstantPool<'a>,
     parse_attribute: F,
 ) -> Result<(&'a [u8], A), ClassParseError>
 where
     A: From<Code<'a, A>>,
-    F: Fn(&'a [u8], &[Constant]) -> Result<(&'a [u8], A), ClassParseError>,
+    F: Fn(&'a [u8], &ConstantPool<'a>) -> Result<(&'a [u8], A), ClassParseError>,
 {
     let (input, max_stack) = be_u16(input)?;
     let (input, max_locals) = be_u16(input)?;
@@ -75,7 +77,7 @@ where
     let attribute = Code {
         max_stack,
         max_locals,
-        code,
+        code: Cow::Borrowed(code),
         exception_table,
         attributes,
     };
@@ -97,7 +99,7 @@ mod tests {
 
     fn dummy_parse_attribute<'a>(
         _input: &'a [u8],
-        _constant_pool: &[Constant],
+        _constant_pool: &ConstantPool,
     ) -> Result<(&'a [u8], TestAttribute<'a>), ClassParseError> {
         unreachable!()
     }
@@ -117,7 +119,7 @@ mod tests {
             0x00, 0x00, // attributes_count
             0x12, 0x34, // rest
         ];
-        let constant_pool = vec![];
+        let constant_pool = ConstantPool::from(vec![]);
         let (rest, attribute) = parse_code(&input, &constant_pool, dummy_parse_attribute).unwrap();
         assert_eq!(rest, &[0x12, 0x34]);
         assert_eq!(
@@ -125,7 +127,7 @@ mod tests {
             Code {
                 max_stack: 1,
                 max_locals: 2,
-                code: &[0x40, 0x41, 0x42, 0x43],
+                code: vec![0x40, 0x41, 0x42, 0x43].into(),
                 exception_table: vec![ExceptionTableEntry {
                     start_pc: 0x1011,
                     end_pc: 0x1213,
@@ -0,0 +1,35 @@
+use super::super::error::ClassParseError;
+use crate::parser::be_u16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstantValue {
+    // an Integer, Float, Long, Double or String constant
+    pub constantvalue_index: u16,
+}
+
+pub fn parse_constant_value<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<ConstantValue>,
+{
+    let (input, constantvalue_index) = be_u16(input)?;
+    let attribute = ConstantValue {
+        constantvalue_index,
+    };
+    Ok((input, attribute.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_constant_value() {
+        let input = [0x00, 0x07, 0x99];
+        let expected = ConstantValue {
+            constantvalue_index: 7,
+        };
+        let (input, result) = parse_constant_value::<ConstantValue>(&input).unwrap();
+        assert_eq!(input, &[0x99]);
+        assert_eq!(result, expected);
+    }
+}
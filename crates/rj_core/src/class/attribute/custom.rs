@@ -0,0 +1,157 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use super::super::constant_pool::ConstantPool;
+use super::super::error::ClassParseError;
+
+// a user-defined attribute value; implemented for any owned type that is
+// Debug + PartialEq, so it can sit inside Attribute
+pub trait CustomAttribute: Debug {
+    fn as_any(&self) -> &dyn Any;
+    fn eq_custom(&self, other: &dyn CustomAttribute) -> bool;
+}
+
+impl<T> CustomAttribute for T
+where
+    T: Debug + PartialEq + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn eq_custom(&self, other: &dyn CustomAttribute) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+}
+
+#[derive(Debug)]
+pub struct CustomAttributeValue(Box<dyn CustomAttribute>);
+
+impl CustomAttributeValue {
+    pub fn new<T>(value: T) -> Self
+    where
+        T: CustomAttribute + 'static,
+    {
+        Self(Box::new(value))
+    }
+
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.0.as_any().downcast_ref()
+    }
+}
+
+impl PartialEq for CustomAttributeValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.eq_custom(&*other.0)
+    }
+}
+
+// consulted for attributes the crate doesn't know; returning None falls back
+// to Attribute::Unknown
+pub trait AttributeParserRegistry {
+    fn parse(
+        &self,
+        name: &[u8],
+        data: &[u8],
+        constant_pool: &ConstantPool,
+    ) -> Option<Result<CustomAttributeValue, ClassParseError>>;
+}
+
+// parses the whole attribute payload
+pub type CustomAttributeParser =
+    fn(&[u8], &ConstantPool) -> Result<CustomAttributeValue, ClassParseError>;
+
+#[derive(Debug, Clone, Default)]
+pub struct AttributeParsers {
+    parsers: HashMap<Vec<u8>, CustomAttributeParser>,
+}
+
+impl AttributeParsers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, name: &[u8], parser: CustomAttributeParser) -> &mut Self {
+        self.parsers.insert(name.to_vec(), parser);
+        self
+    }
+}
+
+impl AttributeParserRegistry for AttributeParsers {
+    fn parse(
+        &self,
+        name: &[u8],
+        data: &[u8],
+        constant_pool: &ConstantPool,
+    ) -> Option<Result<CustomAttributeValue, ClassParseError>> {
+        let parser = self.parsers.get(name)?;
+        Some(parser(data, constant_pool))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::super::constant::Constant;
+    use super::super::{parse_attribute, parse_attribute_with, Attribute};
+    use super::*;
+    use crate::parser::be_u16;
+
+    #[derive(Debug, PartialEq)]
+    struct ScalaSig {
+        major: u16,
+        minor: u16,
+    }
+
+    fn parse_scala_sig(
+        data: &[u8],
+        _constant_pool: &ConstantPool,
+    ) -> Result<CustomAttributeValue, ClassParseError> {
+        let (data, major) = be_u16(data)?;
+        let (_, minor) = be_u16(data)?;
+        Ok(CustomAttributeValue::new(ScalaSig { major, minor }))
+    }
+
+    #[test]
+    fn test_parse_custom_attribute() {
+        let input = [
+            0x00, 0x01, // attribute_name_index
+            0x00, 0x00, 0x00, 0x04, // attribute_length
+            0x00, 0x05, 0x00, 0x00, // data
+            0x12, 0x34, // rest
+        ];
+        let constant_pool = ConstantPool::from(vec![Constant::Utf8 {
+            value: b"ScalaSig".into(),
+        }]);
+        let mut registry = AttributeParsers::new();
+        registry.register(b"ScalaSig", parse_scala_sig);
+
+        let (rest, attribute) = parse_attribute_with(&input, &constant_pool, &registry).unwrap();
+        assert_eq!(rest, &[0x12, 0x34]);
+        assert_eq!(
+            attribute,
+            Attribute::Custom {
+                attribute_name_index: 1,
+                name: b"ScalaSig".into(),
+                data: [0x00, 0x05, 0x00, 0x00].as_slice().into(),
+                value: CustomAttributeValue::new(ScalaSig { major: 5, minor: 0 }),
+            }
+        );
+        let Attribute::Custom { value, .. } = attribute else {
+            unreachable!()
+        };
+        assert_eq!(value.downcast_ref::<ScalaSig>().unwrap().major, 5);
+        assert_eq!(value.downcast_ref::<u16>(), None);
+
+        // without the registry it stays unknown
+        let (_, attribute) = parse_attribute(&input, &constant_pool).unwrap();
+        assert!(matches!(attribute, Attribute::Unknown { .. }));
+
+        // errors from the custom parser are propagated
+        let input = [0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x00];
+        assert_eq!(
+            parse_attribute_with(&input, &constant_pool, &registry),
+            Err(ClassParseError::ParseError(crate::parser::ParseError::Eof))
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use super::super::error::ClassParseError;
+use crate::parser::be_u16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnclosingMethod {
+    pub class_index: u16,
+    // a NameAndType, or 0 if the class is not enclosed by a method or
+    // constructor (e.g. it appears in a field initializer)
+    pub method_index: u16,
+}
+
+pub fn parse_enclosing_method<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<EnclosingMethod>,
+{
+    let (input, class_index) = be_u16(input)?;
+    let (input, method_index) = be_u16(input)?;
+    let attribute = EnclosingMethod {
+        class_index,
+        method_index,
+    };
+    Ok((input, attribute.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_enclosing_method() {
+        let input = [0x00, 0x13, 0x00, 0x15, 0x99];
+        let expected = EnclosingMethod {
+            class_index: 0x13,
+            method_index: 0x15,
+        };
+        let (input, result) = parse_enclosing_method::<EnclosingMethod>(&input).unwrap();
+        assert_eq!(input, &[0x99]);
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,90 @@
+use super::super::access_flags::InnerClassAccessFlags;
+use super::super::error::ClassParseError;
+use crate::parser::be_u16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InnerClass {
+    pub inner_class_info_index: u16,
+    // 0 unless the class is a member of another class
+    pub outer_class_info_index: u16,
+    // 0 for anonymous classes
+    pub inner_name_index: u16,
+    pub inner_class_access_flags: InnerClassAccessFlags,
+}
+
+impl InnerClass {
+    pub fn is_anonymous(&self) -> bool {
+        self.inner_name_index == 0
+    }
+
+    pub fn is_member(&self) -> bool {
+        self.outer_class_info_index != 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InnerClasses {
+    pub classes: Vec<InnerClass>,
+}
+
+pub fn parse_inner_class(input: &[u8]) -> Result<(&[u8], InnerClass), ClassParseError> {
+    let (input, inner_class_info_index) = be_u16(input)?;
+    let (input, outer_class_info_index) = be_u16(input)?;
+    let (input, inner_name_index) = be_u16(input)?;
+    let (input, inner_class_access_flags) = be_u16(input)?;
+    Ok((
+        input,
+        InnerClass {
+            inner_class_info_index,
+            outer_class_info_index,
+            inner_name_index,
+            inner_class_access_flags: InnerClassAccessFlags::from_bits(inner_class_access_flags),
+        },
+    ))
+}
+
+pub fn parse_inner_classes<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<InnerClasses>,
+{
+    let (input, number_of_classes) = be_u16(input)?;
+    let mut classes = Vec::new();
+    let mut input = input;
+    for _ in 0..number_of_classes {
+        let (new_input, class) = parse_inner_class(input)?;
+        input = new_input;
+        classes.push(class);
+    }
+    let attribute = InnerClasses { classes };
+    Ok((input, attribute.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_inner_classes() {
+        let input = [
+            0x00, 0x02, // number_of_classes
+            0x00, 0x10, 0x00, 0x11, 0x00, 0x12, 0x00, 0x08, // classes[0]
+            0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // classes[1]
+            0x99, // rest
+        ];
+        let (input, result) = parse_inner_classes::<InnerClasses>(&input).unwrap();
+        assert_eq!(input, &[0x99]);
+        assert_eq!(
+            result.classes[0],
+            InnerClass {
+                inner_class_info_index: 0x10,
+                outer_class_info_index: 0x11,
+                inner_name_index: 0x12,
+                inner_class_access_flags: InnerClassAccessFlags::STATIC,
+            }
+        );
+        assert!(result.classes[0].is_member());
+        assert!(!result.classes[0].is_anonymous());
+        assert!(!result.classes[1].is_member());
+        assert!(result.classes[1].is_anonymous());
+    }
+}
@@ -3,8 +3,8 @@ use crate::parser::be_u16;
 
 #[derive(Debug, PartialEq)]
 pub struct LineNumberTableEntry {
-    start_pc: u16,
-    line_number: u16,
+    pub start_pc: u16,
+    pub line_number: u16,
 }
 
 pub fn parse_line_number_table_entry(
@@ -23,7 +23,7 @@ pub fn parse_line_number_table_entry(
 
 #[derive(Debug, PartialEq)]
 pub struct LineNumberTable {
-    line_number_table: Vec<LineNumberTableEntry>,
+    pub line_number_table: Vec<LineNumberTableEntry>,
 }
 
 pub fn parse_line_number_table<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
@@ -0,0 +1,84 @@
+use super::super::error::ClassParseError;
+use crate::parser::be_u16;
+
+#[derive(Debug, PartialEq)]
+pub struct LocalVariableTableEntry {
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub index: u16,
+}
+
+pub fn parse_local_variable_table_entry(
+    input: &[u8],
+) -> Result<(&[u8], LocalVariableTableEntry), ClassParseError> {
+    let (input, start_pc) = be_u16(input)?;
+    let (input, length) = be_u16(input)?;
+    let (input, name_index) = be_u16(input)?;
+    let (input, descriptor_index) = be_u16(input)?;
+    let (input, index) = be_u16(input)?;
+    Ok((
+        input,
+        LocalVariableTableEntry {
+            start_pc,
+            length,
+            name_index,
+            descriptor_index,
+            index,
+        },
+    ))
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LocalVariableTable {
+    pub local_variable_table: Vec<LocalVariableTableEntry>,
+}
+
+pub fn parse_local_variable_table<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<LocalVariableTable>,
+{
+    let (input, local_variable_table_length) = be_u16(input)?;
+    let mut local_variable_table = Vec::new();
+    let mut input = input;
+    for _ in 0..local_variable_table_length {
+        let (new_input, entry) = parse_local_variable_table_entry(input)?;
+        input = new_input;
+        local_variable_table.push(entry);
+    }
+    let attribute = LocalVariableTable {
+        local_variable_table,
+    };
+    Ok((input, attribute.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_local_variable_table() {
+        let input = [
+            0x00, 0x01, // local_variable_table_length
+            0x00, 0x00, // start_pc
+            0x00, 0x05, // length
+            0x00, 0x26, // name_index
+            0x00, 0x27, // descriptor_index
+            0x00, 0x00, // index
+            0x99, 0x99, // rest
+        ];
+        let expected = LocalVariableTable {
+            local_variable_table: vec![LocalVariableTableEntry {
+                start_pc: 0,
+                length: 5,
+                name_index: 0x26,
+                descriptor_index: 0x27,
+                index: 0,
+            }],
+        };
+        let (input, result) = parse_local_variable_table::<LocalVariableTable>(&input).unwrap();
+        assert_eq!(input, &[0x99, 0x99]);
+        assert_eq!(result, expected);
+    }
+}
@@ -0,0 +1,56 @@
+use super::super::error::ClassParseError;
+use crate::parser::be_u16;
+
+// like a LocalVariableTable entry, but for locals with a generic type, which
+// carry a field signature instead of a descriptor
+#[derive(Debug, PartialEq)]
+pub struct LocalVariableTypeTableEntry {
+    pub start_pc: u16,
+    pub length: u16,
+    pub name_index: u16,
+    pub signature_index: u16,
+    pub index: u16,
+}
+
+pub fn parse_local_variable_type_table_entry(
+    input: &[u8],
+) -> Result<(&[u8], LocalVariableTypeTableEntry), ClassParseError> {
+    let (input, start_pc) = be_u16(input)?;
+    let (input, length) = be_u16(input)?;
+    let (input, name_index) = be_u16(input)?;
+    let (input, signature_index) = be_u16(input)?;
+    let (input, index) = be_u16(input)?;
+    Ok((
+        input,
+        LocalVariableTypeTableEntry {
+            start_pc,
+            length,
+            name_index,
+            signature_index,
+            index,
+        },
+    ))
+}
+
+#[derive(Debug, PartialEq)]
+pub struct LocalVariableTypeTable {
+    pub local_variable_type_table: Vec<LocalVariableTypeTableEntry>,
+}
+
+pub fn parse_local_variable_type_table<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<LocalVariableTypeTable>,
+{
+    let (input, local_variable_type_table_length) = be_u16(input)?;
+    let mut local_variable_type_table = Vec::new();
+    let mut input = input;
+    for _ in 0..local_variable_type_table_length {
+        let (new_input, entry) = parse_local_variable_type_table_entry(input)?;
+        input = new_input;
+        local_variable_type_table.push(entry);
+    }
+    let attribute = LocalVariableTypeTable {
+        local_variable_type_table,
+    };
+    Ok((input, attribute.into()))
+}
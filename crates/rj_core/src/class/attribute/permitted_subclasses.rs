@@ -0,0 +1,37 @@
+use super::super::error::ClassParseError;
+use crate::parser::be_u16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PermittedSubclasses {
+    // Class constants
+    pub classes: Vec<u16>,
+}
+
+pub fn parse_permitted_subclasses<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<PermittedSubclasses>,
+{
+    let (input, number_of_classes) = be_u16(input)?;
+    let mut classes = Vec::new();
+    let mut input = input;
+    for _ in 0..number_of_classes {
+        let (new_input, class) = be_u16(input)?;
+        input = new_input;
+        classes.push(class);
+    }
+    let attribute = PermittedSubclasses { classes };
+    Ok((input, attribute.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_permitted_subclasses() {
+        let input = [0x00, 0x02, 0x00, 0x10, 0x00, 0x0e, 0x99];
+        let (input, result) = parse_permitted_subclasses::<PermittedSubclasses>(&input).unwrap();
+        assert_eq!(input, &[0x99]);
+        assert_eq!(result.classes, vec![0x10, 0x0e]);
+    }
+}
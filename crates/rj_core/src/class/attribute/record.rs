@@ -0,0 +1,66 @@
+use super::super::constant_pool::ConstantPool;
+use super::super::error::ClassParseError;
+use crate::parser::be_u16;
+
+#[derive(Debug, PartialEq)]
+pub struct RecordComponent<A> {
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    // e.g. Signature for generic components, or annotations
+    pub attributes: Vec<A>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Record<A> {
+    pub components: Vec<RecordComponent<A>>,
+}
+
+pub fn parse_record_component<'a, A, F>(
+    input: &'a [u8],
+    constant_pool: &ConstantPool<'a>,
+    parse_attribute: &F,
+) -> Result<(&'a [u8], RecordComponent<A>), ClassParseError>
+where
+    F: Fn(&'a [u8], &ConstantPool<'a>) -> Result<(&'a [u8], A), ClassParseError>,
+{
+    let (input, name_index) = be_u16(input)?;
+    let (input, descriptor_index) = be_u16(input)?;
+    let (input, attributes_count) = be_u16(input)?;
+    let mut attributes = Vec::new();
+    let mut input = input;
+    for _ in 0..attributes_count {
+        let (new_input, attribute) = parse_attribute(input, constant_pool)?;
+        input = new_input;
+        attributes.push(attribute);
+    }
+    Ok((
+        input,
+        RecordComponent {
+            name_index,
+            descriptor_index,
+            attributes,
+        },
+    ))
+}
+
+pub fn parse_record<'a, A, F>(
+    input: &'a [u8],
+    constant_pool: &ConstantPool<'a>,
+    parse_attribute: F,
+) -> Result<(&'a [u8], A), ClassParseError>
+where
+    A: From<Record<A>>,
+    F: Fn(&'a [u8], &ConstantPool<'a>) -> Result<(&'a [u8], A), ClassParseError>,
+{
+    let (input, components_count) = be_u16(input)?;
+    let mut components = Vec::new();
+    let mut input = input;
+    for _ in 0..components_count {
+        let (new_input, component) =
+            parse_record_component(input, constant_pool, &parse_attribute)?;
+        input = new_input;
+        components.push(component);
+    }
+    let attribute = Record { components };
+    Ok((input, attribute.into()))
+}
@@ -0,0 +1,35 @@
+use super::super::error::ClassParseError;
+use crate::parser::be_u16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    // a Utf8 constant holding a class, method or field signature
+    pub signature_index: u16,
+}
+
+pub fn parse_signature<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<Signature>,
+{
+    let (input, signature_index) = be_u16(input)?;
+    let attribute = Signature { signature_index };
+    Ok((input, attribute.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signature() {
+        let input = [0x00, 0x0c, 0x99];
+        let (input, result) = parse_signature::<Signature>(&input).unwrap();
+        assert_eq!(input, &[0x99]);
+        assert_eq!(
+            result,
+            Signature {
+                signature_index: 12
+            }
+        );
+    }
+}
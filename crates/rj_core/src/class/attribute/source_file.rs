@@ -3,7 +3,7 @@ use crate::parser::be_u16;
 
 #[derive(Debug, PartialEq)]
 pub struct SourceFile {
-    sourcefile_index: u16,
+    pub sourcefile_index: u16,
 }
 
 pub fn parse_source_file<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
@@ -0,0 +1,297 @@
+use super::super::error::ClassParseError;
+use crate::parser::{be_u16, be_u8};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerificationTypeInfo {
+    Top,
+    Integer,
+    Float,
+    Double,
+    Long,
+    Null,
+    UninitializedThis,
+    Object { cpool_index: u16 },
+    Uninitialized { offset: u16 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StackMapFrame {
+    Same {
+        offset_delta: u8,
+    },
+    SameLocals1StackItem {
+        offset_delta: u8,
+        stack: VerificationTypeInfo,
+    },
+    SameLocals1StackItemExtended {
+        offset_delta: u16,
+        stack: VerificationTypeInfo,
+    },
+    Chop {
+        k: u8,
+        offset_delta: u16,
+    },
+    SameExtended {
+        offset_delta: u16,
+    },
+    Append {
+        offset_delta: u16,
+        locals: Vec<VerificationTypeInfo>,
+    },
+    Full {
+        offset_delta: u16,
+        locals: Vec<VerificationTypeInfo>,
+        stack: Vec<VerificationTypeInfo>,
+    },
+}
+
+impl StackMapFrame {
+    pub fn offset_delta(&self) -> u16 {
+        match self {
+            StackMapFrame::Same { offset_delta } => *offset_delta as u16,
+            StackMapFrame::SameLocals1StackItem { offset_delta, .. } => *offset_delta as u16,
+            StackMapFrame::SameLocals1StackItemExtended { offset_delta, .. } => *offset_delta,
+            StackMapFrame::Chop { offset_delta, .. } => *offset_delta,
+            StackMapFrame::SameExtended { offset_delta } => *offset_delta,
+            StackMapFrame::Append { offset_delta, .. } => *offset_delta,
+            StackMapFrame::Full { offset_delta, .. } => *offset_delta,
+        }
+    }
+
+    // the same frame at another offset delta, switching between the short and
+    // extended forms of same and same_locals_1_stack_item as needed
+    pub fn with_offset_delta(self, offset_delta: u16) -> StackMapFrame {
+        match self {
+            StackMapFrame::Same { .. } | StackMapFrame::SameExtended { .. } => match offset_delta {
+                0..=63 => StackMapFrame::Same {
+                    offset_delta: offset_delta as u8,
+                },
+                _ => StackMapFrame::SameExtended { offset_delta },
+            },
+            StackMapFrame::SameLocals1StackItem { stack, .. }
+            | StackMapFrame::SameLocals1StackItemExtended { stack, .. } => match offset_delta {
+                0..=63 => StackMapFrame::SameLocals1StackItem {
+                    offset_delta: offset_delta as u8,
+                    stack,
+                },
+                _ => StackMapFrame::SameLocals1StackItemExtended {
+                    offset_delta,
+                    stack,
+                },
+            },
+            StackMapFrame::Chop { k, .. } => StackMapFrame::Chop { k, offset_delta },
+            StackMapFrame::Append { locals, .. } => StackMapFrame::Append {
+                offset_delta,
+                locals,
+            },
+            StackMapFrame::Full { locals, stack, .. } => StackMapFrame::Full {
+                offset_delta,
+                locals,
+                stack,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackMapTable {
+    pub entries: Vec<StackMapFrame>,
+}
+
+pub fn parse_verification_type_info(
+    input: &[u8],
+) -> Result<(&[u8], VerificationTypeInfo), ClassParseError> {
+    let (input, tag) = be_u8(input)?;
+    match tag {
+        0 => Ok((input, VerificationTypeInfo::Top)),
+        1 => Ok((input, VerificationTypeInfo::Integer)),
+        2 => Ok((input, VerificationTypeInfo::Float)),
+        3 => Ok((input, VerificationTypeInfo::Double)),
+        4 => Ok((input, VerificationTypeInfo::Long)),
+        5 => Ok((input, VerificationTypeInfo::Null)),
+        6 => Ok((input, VerificationTypeInfo::UninitializedThis)),
+        7 => {
+            let (input, cpool_index) = be_u16(input)?;
+            Ok((input, VerificationTypeInfo::Object { cpool_index }))
+        }
+        8 => {
+            let (input, offset) = be_u16(input)?;
+            Ok((input, VerificationTypeInfo::Uninitialized { offset }))
+        }
+        _ => Err(ClassParseError::InvalidVerificationTypeTag(tag)),
+    }
+}
+
+fn parse_verification_type_infos(
+    input: &[u8],
+    count: usize,
+) -> Result<(&[u8], Vec<VerificationTypeInfo>), ClassParseError> {
+    let mut types = Vec::new();
+    let mut input = input;
+    for _ in 0..count {
+        let (new_input, verification_type) = parse_verification_type_info(input)?;
+        input = new_input;
+        types.push(verification_type);
+    }
+    Ok((input, types))
+}
+
+pub fn parse_stack_map_frame(input: &[u8]) -> Result<(&[u8], StackMapFrame), ClassParseError> {
+    let (input, frame_type) = be_u8(input)?;
+    match frame_type {
+        0..=63 => Ok((
+            input,
+            StackMapFrame::Same {
+                offset_delta: frame_type,
+            },
+        )),
+        64..=127 => {
+            let (input, stack) = parse_verification_type_info(input)?;
+            Ok((
+                input,
+                StackMapFrame::SameLocals1StackItem {
+                    offset_delta: frame_type - 64,
+                    stack,
+                },
+            ))
+        }
+        247 => {
+            let (input, offset_delta) = be_u16(input)?;
+            let (input, stack) = parse_verification_type_info(input)?;
+            Ok((
+                input,
+                StackMapFrame::SameLocals1StackItemExtended {
+                    offset_delta,
+                    stack,
+                },
+            ))
+        }
+        248..=250 => {
+            let (input, offset_delta) = be_u16(input)?;
+            Ok((
+                input,
+                StackMapFrame::Chop {
+                    k: 251 - frame_type,
+                    offset_delta,
+                },
+            ))
+        }
+        251 => {
+            let (input, offset_delta) = be_u16(input)?;
+            Ok((input, StackMapFrame::SameExtended { offset_delta }))
+        }
+        252..=254 => {
+            let (input, offset_delta) = be_u16(input)?;
+            let (input, locals) =
+                parse_verification_type_infos(input, (frame_type - 251) as usize)?;
+            Ok((
+                input,
+                StackMapFrame::Append {
+                    offset_delta,
+                    locals,
+                },
+            ))
+        }
+        255 => {
+            let (input, offset_delta) = be_u16(input)?;
+            let (input, number_of_locals) = be_u16(input)?;
+            let (input, locals) = parse_verification_type_infos(input, number_of_locals as usize)?;
+            let (input, number_of_stack_items) = be_u16(input)?;
+            let (input, stack) =
+                parse_verification_type_infos(input, number_of_stack_items as usize)?;
+            Ok((
+                input,
+                StackMapFrame::Full {
+                    offset_delta,
+                    locals,
+                    stack,
+                },
+            ))
+        }
+        _ => Err(ClassParseError::InvalidStackMapFrameType(frame_type)),
+    }
+}
+
+pub fn parse_stack_map_table<A>(input: &[u8]) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<StackMapTable>,
+{
+    let (input, number_of_entries) = be_u16(input)?;
+    let mut entries = Vec::new();
+    let mut input = input;
+    for _ in 0..number_of_entries {
+        let (new_input, entry) = parse_stack_map_frame(input)?;
+        input = new_input;
+        entries.push(entry);
+    }
+    let attribute = StackMapTable { entries };
+    Ok((input, attribute.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_verification_type_info() {
+        let input = [0x07, 0x00, 0x0c, 0x99];
+        let (rest, result) = parse_verification_type_info(&input).unwrap();
+        assert_eq!(rest, &[0x99]);
+        assert_eq!(result, VerificationTypeInfo::Object { cpool_index: 12 });
+
+        let input = [0x09];
+        assert_eq!(
+            parse_verification_type_info(&input),
+            Err(ClassParseError::InvalidVerificationTypeTag(9))
+        );
+    }
+
+    #[test]
+    fn test_parse_stack_map_table() {
+        let input = [
+            0x00, 0x05, // number_of_entries
+            0x0a, // same
+            0x45, 0x07, 0x00, 0x10, // same_locals_1_stack_item
+            0xf9, 0x00, 0x02, // chop 2
+            0xfc, 0x00, 0x01, 0x01, // append [int]
+            0xff, 0x00, 0x0d, // full_frame
+            0x00, 0x02, 0x04, 0x06, // locals [long, uninitializedThis]
+            0x00, 0x01, 0x08, 0x00, 0x03, // stack [uninitialized(3)]
+            0x99, 0x99, // rest
+        ];
+        let expected = StackMapTable {
+            entries: vec![
+                StackMapFrame::Same { offset_delta: 10 },
+                StackMapFrame::SameLocals1StackItem {
+                    offset_delta: 5,
+                    stack: VerificationTypeInfo::Object { cpool_index: 16 },
+                },
+                StackMapFrame::Chop {
+                    k: 2,
+                    offset_delta: 2,
+                },
+                StackMapFrame::Append {
+                    offset_delta: 1,
+                    locals: vec![VerificationTypeInfo::Integer],
+                },
+                StackMapFrame::Full {
+                    offset_delta: 13,
+                    locals: vec![
+                        VerificationTypeInfo::Long,
+                        VerificationTypeInfo::UninitializedThis,
+                    ],
+                    stack: vec![VerificationTypeInfo::Uninitialized { offset: 3 }],
+                },
+            ],
+        };
+        let (rest, result) = parse_stack_map_table::<StackMapTable>(&input).unwrap();
+        assert_eq!(rest, &[0x99, 0x99]);
+        assert_eq!(result, expected);
+
+        let input = [0x00, 0x01, 0x80];
+        assert_eq!(
+            parse_stack_map_table::<StackMapTable>(&input),
+            Err(ClassParseError::InvalidStackMapFrameType(0x80))
+        );
+    }
+}
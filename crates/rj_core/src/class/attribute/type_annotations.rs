@@ -0,0 +1,287 @@
+// https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-4.html#jvms-4.7.20
+
+use super::super::error::ClassParseError;
+use super::annotations::{parse_annotation, Annotation};
+use crate::parser::{be_u16, be_u8};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalvarTarget {
+    pub start_pc: u16,
+    pub length: u16,
+    pub index: u16,
+}
+
+// the target_info union, selected by target_type
+#[derive(Debug, Clone, PartialEq)]
+pub enum TargetInfo {
+    // 0x00, 0x01
+    TypeParameter {
+        type_parameter_index: u8,
+    },
+    // 0x10; 65535 for the superclass, otherwise an index into interfaces
+    Supertype {
+        supertype_index: u16,
+    },
+    // 0x11, 0x12
+    TypeParameterBound {
+        type_parameter_index: u8,
+        bound_index: u8,
+    },
+    // 0x13, 0x14, 0x15: field type, method return or receiver
+    Empty,
+    // 0x16
+    FormalParameter {
+        formal_parameter_index: u8,
+    },
+    // 0x17
+    Throws {
+        throws_type_index: u16,
+    },
+    // 0x40, 0x41
+    Localvar(Vec<LocalvarTarget>),
+    // 0x42
+    Catch {
+        exception_table_index: u16,
+    },
+    // 0x43-0x46: instanceof, new and method references
+    Offset {
+        offset: u16,
+    },
+    // 0x47-0x4B: casts and explicit type arguments of calls
+    TypeArgument {
+        offset: u16,
+        type_argument_index: u8,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypePathEntry {
+    // 0 array, 1 nested type, 2 wildcard bound, 3 type argument
+    pub type_path_kind: u8,
+    pub type_argument_index: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeAnnotation {
+    pub target_type: u8,
+    pub target_info: TargetInfo,
+    pub target_path: Vec<TypePathEntry>,
+    pub annotation: Annotation,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeVisibleTypeAnnotations {
+    pub annotations: Vec<TypeAnnotation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuntimeInvisibleTypeAnnotations {
+    pub annotations: Vec<TypeAnnotation>,
+}
+
+pub fn parse_target_info(
+    input: &[u8],
+    target_type: u8,
+) -> Result<(&[u8], TargetInfo), ClassParseError> {
+    match target_type {
+        0x00 | 0x01 => {
+            let (input, type_parameter_index) = be_u8(input)?;
+            Ok((
+                input,
+                TargetInfo::TypeParameter {
+                    type_parameter_index,
+                },
+            ))
+        }
+        0x10 => {
+            let (input, supertype_index) = be_u16(input)?;
+            Ok((input, TargetInfo::Supertype { supertype_index }))
+        }
+        0x11 | 0x12 => {
+            let (input, type_parameter_index) = be_u8(input)?;
+            let (input, bound_index) = be_u8(input)?;
+            Ok((
+                input,
+                TargetInfo::TypeParameterBound {
+                    type_parameter_index,
+                    bound_index,
+                },
+            ))
+        }
+        0x13..=0x15 => Ok((input, TargetInfo::Empty)),
+        0x16 => {
+            let (input, formal_parameter_index) = be_u8(input)?;
+            Ok((
+                input,
+                TargetInfo::FormalParameter {
+                    formal_parameter_index,
+                },
+            ))
+        }
+        0x17 => {
+            let (input, throws_type_index) = be_u16(input)?;
+            Ok((input, TargetInfo::Throws { throws_type_index }))
+        }
+        0x40 | 0x41 => {
+            let (input, table_length) = be_u16(input)?;
+            let mut table = Vec::new();
+            let mut input = input;
+            for _ in 0..table_length {
+                let (new_input, start_pc) = be_u16(input)?;
+                let (new_input, length) = be_u16(new_input)?;
+                let (new_input, index) = be_u16(new_input)?;
+                input = new_input;
+                table.push(LocalvarTarget {
+                    start_pc,
+                    length,
+                    index,
+                });
+            }
+            Ok((input, TargetInfo::Localvar(table)))
+        }
+        0x42 => {
+            let (input, exception_table_index) = be_u16(input)?;
+            Ok((
+                input,
+                TargetInfo::Catch {
+                    exception_table_index,
+                },
+            ))
+        }
+        0x43..=0x46 => {
+            let (input, offset) = be_u16(input)?;
+            Ok((input, TargetInfo::Offset { offset }))
+        }
+        0x47..=0x4B => {
+            let (input, offset) = be_u16(input)?;
+            let (input, type_argument_index) = be_u8(input)?;
+            Ok((
+                input,
+                TargetInfo::TypeArgument {
+                    offset,
+                    type_argument_index,
+                },
+            ))
+        }
+        _ => Err(ClassParseError::InvalidTargetType(target_type)),
+    }
+}
+
+pub fn parse_type_path(input: &[u8]) -> Result<(&[u8], Vec<TypePathEntry>), ClassParseError> {
+    let (input, path_length) = be_u8(input)?;
+    let mut path = Vec::new();
+    let mut input = input;
+    for _ in 0..path_length {
+        let (new_input, type_path_kind) = be_u8(input)?;
+        let (new_input, type_argument_index) = be_u8(new_input)?;
+        input = new_input;
+        path.push(TypePathEntry {
+            type_path_kind,
+            type_argument_index,
+        });
+    }
+    Ok((input, path))
+}
+
+pub fn parse_type_annotation(input: &[u8]) -> Result<(&[u8], TypeAnnotation), ClassParseError> {
+    let (input, target_type) = be_u8(input)?;
+    let (input, target_info) = parse_target_info(input, target_type)?;
+    let (input, target_path) = parse_type_path(input)?;
+    let (input, annotation) = parse_annotation(input)?;
+    Ok((
+        input,
+        TypeAnnotation {
+            target_type,
+            target_info,
+            target_path,
+            annotation,
+        },
+    ))
+}
+
+fn parse_type_annotations(input: &[u8]) -> Result<(&[u8], Vec<TypeAnnotation>), ClassParseError> {
+    let (input, num_annotations) = be_u16(input)?;
+    let mut annotations = Vec::new();
+    let mut input = input;
+    for _ in 0..num_annotations {
+        let (new_input, annotation) = parse_type_annotation(input)?;
+        input = new_input;
+        annotations.push(annotation);
+    }
+    Ok((input, annotations))
+}
+
+pub fn parse_runtime_visible_type_annotations<A>(
+    input: &[u8],
+) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<RuntimeVisibleTypeAnnotations>,
+{
+    let (input, annotations) = parse_type_annotations(input)?;
+    let attribute = RuntimeVisibleTypeAnnotations { annotations };
+    Ok((input, attribute.into()))
+}
+
+pub fn parse_runtime_invisible_type_annotations<A>(
+    input: &[u8],
+) -> Result<(&[u8], A), ClassParseError>
+where
+    A: From<RuntimeInvisibleTypeAnnotations>,
+{
+    let (input, annotations) = parse_type_annotations(input)?;
+    let attribute = RuntimeInvisibleTypeAnnotations { annotations };
+    Ok((input, attribute.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_type_annotation() {
+        let input = [
+            0x14, // METHOD_RETURN
+            0x01, 0x03, 0x00, // location=[TYPE_ARGUMENT(0)]
+            0x00, 0x24, 0x00, 0x00, // @Typed
+            0x99,
+        ];
+        let (input, result) = parse_type_annotation(&input).unwrap();
+        assert_eq!(input, &[0x99]);
+        assert_eq!(
+            result,
+            TypeAnnotation {
+                target_type: 0x14,
+                target_info: TargetInfo::Empty,
+                target_path: vec![TypePathEntry {
+                    type_path_kind: 3,
+                    type_argument_index: 0
+                }],
+                annotation: Annotation {
+                    type_index: 0x24,
+                    element_value_pairs: vec![]
+                },
+            }
+        );
+
+        let input = [
+            0x40, // LOCAL_VARIABLE
+            0x00, 0x01, 0x00, 0x03, 0x00, 0x05, 0x00, 0x02, // {start_pc=3, length=5, index=2}
+            0x00, // empty path
+            0x00, 0x24, 0x00, 0x00, // @Typed
+        ];
+        let (_, result) = parse_type_annotation(&input).unwrap();
+        assert_eq!(
+            result.target_info,
+            TargetInfo::Localvar(vec![LocalvarTarget {
+                start_pc: 3,
+                length: 5,
+                index: 2
+            }])
+        );
+
+        assert_eq!(
+            parse_type_annotation(&[0x20, 0x00]),
+            Err(ClassParseError::InvalidTargetType(0x20))
+        );
+    }
+}
@@ -1,18 +1,20 @@
 use crate::parser::{be_u16, be_u32};
 
-use super::access_flags::ClassAccessFlags;
-use super::attribute::{parse_attribute, Attribute};
-use super::constant::{parse_constant, Constant};
+use super::access_flags::{ClassAccessFlags, MethodAccessFlags};
+use super::attribute::{
+    parse_attribute_with, parse_raw_attribute, Attribute, AttributeParserRegistry, AttributeParsers,
+};
+use super::constant_pool::{parse_constant_pool, ConstantPool};
 use super::error::ClassParseError;
-use super::field::{parse_field, Field};
-use super::method::{parse_method, Method};
+use super::field::{parse_field_with, Field};
+use super::method::{parse_method_with, Method};
 
 #[derive(Debug, PartialEq)]
 pub struct ClassFile<'a> {
     pub magic: u32,
     pub minor_version: u16,
     pub major_version: u16,
-    pub constant_pool: Vec<Constant<'a>>,
+    pub constant_pool: ConstantPool<'a>,
     pub access_flags: ClassAccessFlags,
     pub this_class: u16,
     pub super_class: u16,
@@ -22,21 +24,134 @@ pub struct ClassFile<'a> {
     pub attributes: Vec<Attribute<'a>>,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct InterfaceMethodSummary<'a, 'b> {
+    pub abstract_methods: Vec<&'b Method<'a>>,
+    pub default_methods: Vec<&'b Method<'a>>,
+    pub static_methods: Vec<&'b Method<'a>>,
+    pub private_methods: Vec<&'b Method<'a>>,
+}
+
+impl<'a> ClassFile<'a> {
+    // copies everything borrowed from the input, so the class can outlive it
+    pub fn into_owned(self) -> ClassFile<'static> {
+        ClassFile {
+            magic: self.magic,
+            minor_version: self.minor_version,
+            major_version: self.major_version,
+            constant_pool: self.constant_pool.into_owned(),
+            access_flags: self.access_flags,
+            this_class: self.this_class,
+            super_class: self.super_class,
+            interfaces: self.interfaces,
+            fields: self.fields.into_iter().map(Field::into_owned).collect(),
+            methods: self.methods.into_iter().map(Method::into_owned).collect(),
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(Attribute::into_owned)
+                .collect(),
+        }
+    }
+
+    pub fn interface_methods(&self) -> Option<InterfaceMethodSummary<'a, '_>> {
+        if !self.access_flags.contains(ClassAccessFlags::INTERFACE) {
+            return None;
+        }
+        let mut summary = InterfaceMethodSummary {
+            abstract_methods: vec![],
+            default_methods: vec![],
+            static_methods: vec![],
+            private_methods: vec![],
+        };
+        for method in &self.methods {
+            if method.access_flags.contains(MethodAccessFlags::PRIVATE) {
+                summary.private_methods.push(method);
+            } else if method.access_flags.contains(MethodAccessFlags::STATIC) {
+                summary.static_methods.push(method);
+            } else if method.access_flags.contains(MethodAccessFlags::ABSTRACT)
+                || !method.has_code()
+            {
+                summary.abstract_methods.push(method);
+            } else {
+                summary.default_methods.push(method);
+            }
+        }
+        Some(summary)
+    }
+
+    // the methods called `name`, only the one with `descriptor` if it's
+    // given, in the order they're declared
+    pub fn find_methods(&self, name: &str, descriptor: Option<&str>) -> Vec<&Method<'a>> {
+        let matches = |index, expected: &str| {
+            self.constant_pool
+                .utf8(index)
+                .map_or(false, |value| value == expected)
+        };
+        self.methods
+            .iter()
+            .filter(|method| matches(method.name_index, name))
+            .filter(|method| descriptor.map_or(true, |d| matches(method.descriptor_index, d)))
+            .collect()
+    }
+}
+
 pub fn parse_classfile(input: &[u8]) -> Result<(&[u8], ClassFile), ClassParseError> {
+    parse_classfile_with(input, &AttributeParsers::new())
+}
+
+// like parse_classfile, with custom parsers for attributes the crate doesn't know
+pub fn parse_classfile_with<'a>(
+    input: &'a [u8],
+    registry: &dyn AttributeParserRegistry,
+) -> Result<(&'a [u8], ClassFile<'a>), ClassParseError> {
+    parse_classfile_using(input, |input, constant_pool: &ConstantPool<'a>| {
+        parse_attribute_with(input, constant_pool, registry)
+    })
+}
+
+pub const CLASS_FILE_MAGIC: u32 = 0xCAFEBABE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    ClassFile,
+    // a zip archive, such as a jar
+    Zip,
+    Unknown,
+}
+
+// guesses the format from the leading magic number, so that callers can
+// dispatch between class files and archives before parsing
+pub fn sniff(input: &[u8]) -> FileFormat {
+    match input {
+        [0xCA, 0xFE, 0xBA, 0xBE, ..] => FileFormat::ClassFile,
+        // a local file header, or the end of central directory record of
+        // an empty archive
+        [b'P', b'K', 0x03, 0x04, ..] | [b'P', b'K', 0x05, 0x06, ..] => FileFormat::Zip,
+        _ => FileFormat::Unknown,
+    }
+}
+
+// keeps every attribute as an Attribute::Raw to be decoded on demand, for
+// scanning many classes without materializing Code, LineNumberTable, etc.
+pub fn parse_classfile_lazy(input: &[u8]) -> Result<(&[u8], ClassFile), ClassParseError> {
+    parse_classfile_using(input, parse_raw_attribute)
+}
+
+fn parse_classfile_using<'a, F>(
+    input: &'a [u8],
+    parse_attribute: F,
+) -> Result<(&'a [u8], ClassFile<'a>), ClassParseError>
+where
+    F: Fn(&'a [u8], &ConstantPool<'a>) -> Result<(&'a [u8], Attribute<'a>), ClassParseError>,
+{
     let (input, magic) = be_u32(input)?;
+    if magic != CLASS_FILE_MAGIC {
+        return Err(ClassParseError::NotAClassFile(magic));
+    }
     let (input, minor_version) = be_u16(input)?;
     let (input, major_version) = be_u16(input)?;
-    let (input, constant_pool) = {
-        let (input, constant_pool_count) = be_u16(input)?;
-        let mut constant_pool = Vec::new();
-        let mut input = input;
-        for _ in 1..constant_pool_count {
-            let (new_input, constant) = parse_constant(input)?;
-            input = new_input;
-            constant_pool.push(constant);
-        }
-        (input, constant_pool)
-    };
+    let (input, constant_pool) = parse_constant_pool(input)?;
     let (input, access_flags) = be_u16(input)?;
     let (input, this_class) = be_u16(input)?;
     let (input, super_class) = be_u16(input)?;
@@ -56,7 +171,7 @@ pub fn parse_classfile(input: &[u8]) -> Result<(&[u8], ClassFile), ClassParseErr
         let mut fields = Vec::new();
         let mut input = input;
         for _ in 0..fields_count {
-            let (new_input, field) = parse_field(input, &constant_pool)?;
+            let (new_input, field) = parse_field_with(input, &constant_pool, &parse_attribute)?;
             input = new_input;
             fields.push(field);
         }
@@ -67,7 +182,7 @@ pub fn parse_classfile(input: &[u8]) -> Result<(&[u8], ClassFile), ClassParseErr
         let mut methods = Vec::new();
         let mut input = input;
         for _ in 0..methods_count {
-            let (new_input, method) = parse_method(input, &constant_pool)?;
+            let (new_input, method) = parse_method_with(input, &constant_pool, &parse_attribute)?;
             input = new_input;
             methods.push(method);
         }
@@ -114,4 +229,97 @@ mod tests {
         assert_eq!(classfile.magic, 0xCAFEBABE);
         // TODO: Add more assertions
     }
+
+    #[test]
+    fn test_not_a_class_file() {
+        let zip = b"PK\x03\x04\x14\x00\x08\x00";
+        assert_eq!(
+            parse_classfile(zip),
+            Err(ClassParseError::NotAClassFile(0x504B0304))
+        );
+        assert!(parse_classfile_lazy(b"\xCA\xFE\xBA\xBF\x00\x00").is_err());
+    }
+
+    #[test]
+    fn test_sniff() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        assert_eq!(sniff(data), FileFormat::ClassFile);
+        assert_eq!(sniff(b"PK\x03\x04\x14\x00"), FileFormat::Zip);
+        assert_eq!(sniff(b"PK\x05\x06"), FileFormat::Zip);
+        assert_eq!(sniff(b"\xCA\xFE"), FileFormat::Unknown);
+        assert_eq!(sniff(b"#!/bin/sh"), FileFormat::Unknown);
+    }
+
+    #[test]
+    fn test_interface_methods() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        assert_eq!(classfile.interface_methods(), None);
+
+        let data = include_bytes!("../../../../java/Shape.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let summary = classfile.interface_methods().unwrap();
+        assert_eq!(summary.abstract_methods, vec![&classfile.methods[0]]);
+        assert_eq!(summary.default_methods, vec![&classfile.methods[1]]);
+        assert_eq!(summary.static_methods, vec![&classfile.methods[2]]);
+        assert_eq!(summary.private_methods, vec![&classfile.methods[3]]);
+    }
+
+    #[test]
+    fn test_find_methods() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        assert_eq!(
+            classfile.find_methods("main", None),
+            vec![&classfile.methods[2]]
+        );
+        assert_eq!(
+            classfile.find_methods("main", Some("([Ljava/lang/String;)V")),
+            vec![&classfile.methods[2]]
+        );
+        assert!(classfile.find_methods("main", Some("()V")).is_empty());
+        assert_eq!(
+            classfile.find_methods("<init>", None),
+            vec![&classfile.methods[0]]
+        );
+        assert!(classfile.find_methods("message", None).is_empty());
+    }
+
+    #[test]
+    fn test_parse_classfile_lazy() {
+        let data = include_bytes!("../../../../java/Insns.class");
+        let (_, eager) = parse_classfile(data).unwrap();
+        let (_, lazy) = parse_classfile_lazy(data).unwrap();
+        assert_eq!(lazy.constant_pool, eager.constant_pool);
+
+        let lazy_attributes = lazy
+            .attributes
+            .iter()
+            .chain(lazy.fields.iter().flat_map(|field| &field.attributes))
+            .chain(lazy.methods.iter().flat_map(|method| &method.attributes));
+        let eager_attributes = eager
+            .attributes
+            .iter()
+            .chain(eager.fields.iter().flat_map(|field| &field.attributes))
+            .chain(eager.methods.iter().flat_map(|method| &method.attributes));
+        for (raw, decoded) in lazy_attributes.zip(eager_attributes) {
+            assert!(matches!(raw, Attribute::Raw { .. }));
+            assert_eq!(&raw.decode(&lazy.constant_pool).unwrap().unwrap(), decoded);
+            assert_eq!(decoded.decode(&eager.constant_pool), None);
+        }
+        assert!(lazy.methods[0].has_code());
+    }
+
+    #[test]
+    fn test_into_owned() {
+        fn load() -> ClassFile<'static> {
+            let data = include_bytes!("../../../../java/Insns.class").to_vec();
+            let (_, classfile) = parse_classfile(&data).unwrap();
+            classfile.into_owned()
+        }
+
+        let data = include_bytes!("../../../../java/Insns.class");
+        let (_, borrowed) = parse_classfile(data).unwrap();
+        assert_eq!(load(), borrowed);
+    }
 }
@@ -1,9 +1,13 @@
 // class file format
 // https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-4.html
 
+use std::borrow::Cow;
+
 use super::error::ClassParseError;
+use super::modified_utf8::{decode_modified_utf8, ModifiedUtf8Error};
 use crate::parser;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ConstantTag {
     Utf8 = 1,
     Integer = 3,
@@ -49,10 +53,57 @@ impl ConstantTag {
     }
 }
 
-#[derive(Debug, PartialEq)]
+// the kind of a CONSTANT_MethodHandle
+// https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-5.html#jvms-5.4.3.5
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceKind {
+    GetField = 1,
+    GetStatic = 2,
+    PutField = 3,
+    PutStatic = 4,
+    InvokeVirtual = 5,
+    InvokeStatic = 6,
+    InvokeSpecial = 7,
+    NewInvokeSpecial = 8,
+    InvokeInterface = 9,
+}
+
+impl ReferenceKind {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::GetField),
+            2 => Some(Self::GetStatic),
+            3 => Some(Self::PutField),
+            4 => Some(Self::PutStatic),
+            5 => Some(Self::InvokeVirtual),
+            6 => Some(Self::InvokeStatic),
+            7 => Some(Self::InvokeSpecial),
+            8 => Some(Self::NewInvokeSpecial),
+            9 => Some(Self::InvokeInterface),
+            _ => None,
+        }
+    }
+
+    // the constants reference_index may point at; invokestatic and
+    // invokespecial handles may also name an interface method
+    pub fn referenced_tags(&self) -> &'static [ConstantTag] {
+        match self {
+            Self::GetField | Self::GetStatic | Self::PutField | Self::PutStatic => {
+                &[ConstantTag::Fieldref]
+            }
+            Self::InvokeVirtual | Self::NewInvokeSpecial => &[ConstantTag::Methodref],
+            Self::InvokeStatic | Self::InvokeSpecial => {
+                &[ConstantTag::Methodref, ConstantTag::InterfaceMethodref]
+            }
+            Self::InvokeInterface => &[ConstantTag::InterfaceMethodref],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Constant<'a> {
     Utf8 {
-        value: &'a [u8],
+        value: Cow<'a, [u8]>,
     },
     Integer {
         value: i32,
@@ -89,7 +140,7 @@ pub enum Constant<'a> {
         descriptor_index: u16,
     },
     MethodHandle {
-        reference_kind: u8,
+        reference_kind: ReferenceKind,
         reference_index: u16,
     },
     MethodType {
@@ -111,10 +162,113 @@ pub enum Constant<'a> {
     },
 }
 
+impl<'a> Constant<'a> {
+    pub fn tag(&self) -> ConstantTag {
+        match self {
+            Constant::Utf8 { .. } => ConstantTag::Utf8,
+            Constant::Integer { .. } => ConstantTag::Integer,
+            Constant::Float { .. } => ConstantTag::Float,
+            Constant::Long { .. } => ConstantTag::Long,
+            Constant::Double { .. } => ConstantTag::Double,
+            Constant::Class { .. } => ConstantTag::Class,
+            Constant::String { .. } => ConstantTag::String,
+            Constant::Fieldref { .. } => ConstantTag::Fieldref,
+            Constant::Methodref { .. } => ConstantTag::Methodref,
+            Constant::InterfaceMethodref { .. } => ConstantTag::InterfaceMethodref,
+            Constant::NameAndType { .. } => ConstantTag::NameAndType,
+            Constant::MethodHandle { .. } => ConstantTag::MethodHandle,
+            Constant::MethodType { .. } => ConstantTag::MethodType,
+            Constant::Dynamic { .. } => ConstantTag::Dynamic,
+            Constant::InvokeDynamic { .. } => ConstantTag::InvokeDynamic,
+            Constant::Module { .. } => ConstantTag::Module,
+            Constant::Package { .. } => ConstantTag::Package,
+        }
+    }
+
+    pub fn into_owned(self) -> Constant<'static> {
+        match self {
+            Constant::Utf8 { value } => Constant::Utf8 {
+                value: Cow::Owned(value.into_owned()),
+            },
+            Constant::Integer { value } => Constant::Integer { value },
+            Constant::Float { value } => Constant::Float { value },
+            Constant::Long { value } => Constant::Long { value },
+            Constant::Double { value } => Constant::Double { value },
+            Constant::Class { name_index } => Constant::Class { name_index },
+            Constant::String { string_index } => Constant::String { string_index },
+            Constant::Fieldref {
+                class_index,
+                name_and_type_index,
+            } => Constant::Fieldref {
+                class_index,
+                name_and_type_index,
+            },
+            Constant::Methodref {
+                class_index,
+                name_and_type_index,
+            } => Constant::Methodref {
+                class_index,
+                name_and_type_index,
+            },
+            Constant::InterfaceMethodref {
+                class_index,
+                name_and_type_index,
+            } => Constant::InterfaceMethodref {
+                class_index,
+                name_and_type_index,
+            },
+            Constant::NameAndType {
+                name_index,
+                descriptor_index,
+            } => Constant::NameAndType {
+                name_index,
+                descriptor_index,
+            },
+            Constant::MethodHandle {
+                reference_kind,
+                reference_index,
+            } => Constant::MethodHandle {
+                reference_kind,
+                reference_index,
+            },
+            Constant::MethodType { descriptor_index } => Constant::MethodType { descriptor_index },
+            Constant::Dynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            } => Constant::Dynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            },
+            Constant::InvokeDynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            } => Constant::InvokeDynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            },
+            Constant::Module { name_index } => Constant::Module { name_index },
+            Constant::Package { name_index } => Constant::Package { name_index },
+        }
+    }
+
+    // the text of a Utf8 constant; None for any other constant
+    pub fn as_str(&self) -> Option<Result<Cow<'_, str>, ModifiedUtf8Error>> {
+        match self {
+            Constant::Utf8 { value } => Some(decode_modified_utf8(value)),
+            _ => None,
+        }
+    }
+}
+
 fn parse_utf8(input: &[u8]) -> Result<(&[u8], Constant), ClassParseError> {
     let (input, length) = parser::be_u16(input)?;
     let (input, value) = parser::bytes(input, length as usize)?;
-    Ok((input, Constant::Utf8 { value }))
+    Ok((
+        input,
+        Constant::Utf8 {
+            value: Cow::Borrowed(value),
+        },
+    ))
 }
 
 fn parse_integer(input: &[u8]) -> Result<(&[u8], Constant), ClassParseError> {
@@ -197,6 +351,8 @@ fn parse_name_and_type(input: &[u8]) -> Result<(&[u8], Constant), ClassParseErro
 
 fn parse_method_handle(input: &[u8]) -> Result<(&[u8], Constant), ClassParseError> {
     let (input, reference_kind) = parser::be_u8(input)?;
+    let reference_kind = ReferenceKind::from_u8(reference_kind)
+        .ok_or(ClassParseError::InvalidReferenceKind(reference_kind))?;
     let (input, reference_index) = parser::be_u16(input)?;
     Ok((
         input,
@@ -279,7 +435,12 @@ mod tests {
         let input = [0x00, 0x03, 0x41, 0x42, 0x43, 0x44];
         let (rest, constant) = parse_utf8(&input).unwrap();
         assert_eq!(rest, &[0x44]);
-        assert_eq!(constant, Constant::Utf8 { value: b"ABC" });
+        assert_eq!(
+            constant,
+            Constant::Utf8 {
+                value: b"ABC".into()
+            }
+        );
 
         let input = [0x00];
         let result = parse_utf8(&input);
@@ -311,6 +472,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_as_str() {
+        let constant = Constant::Utf8 {
+            value: b"\xC0\x80\xED\xA0\xBD\xED\xB8\x80".into(),
+        };
+        assert_eq!(constant.as_str(), Some(Ok("\0\u{1F600}".into())));
+        let constant = Constant::Utf8 {
+            value: b"\xFF".into(),
+        };
+        assert!(matches!(constant.as_str(), Some(Err(_))));
+        assert_eq!(Constant::Integer { value: 1 }.as_str(), None);
+    }
+
     #[test]
     fn test_parse_float() {
         let input = [0x3f, 0x9d, 0xf3, 0xb6];
@@ -446,10 +620,14 @@ mod tests {
         assert_eq!(
             constant,
             Constant::MethodHandle {
-                reference_kind: 0x01,
+                reference_kind: ReferenceKind::GetField,
                 reference_index: 0x2345
             }
         );
+
+        let input = [0x0A, 0x23, 0x45];
+        let result = parse_method_handle(&input);
+        assert_eq!(result, Err(ClassParseError::InvalidReferenceKind(0x0A)));
     }
 
     #[test]
@@ -0,0 +1,620 @@
+use std::borrow::Cow;
+
+use super::constant::{parse_constant, Constant, ConstantTag, ReferenceKind};
+use super::error::{ClassParseError, ConstantPoolError, ConstantPoolViolation};
+use super::modified_utf8::encode_modified_utf8;
+use crate::parser::be_u16;
+
+// the name and descriptor behind a NameAndType
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameAndType<'a> {
+    pub name: Cow<'a, str>,
+    pub descriptor: Cow<'a, str>,
+}
+
+// the class, name and descriptor behind a Fieldref, Methodref or
+// InterfaceMethodref
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolicRef<'a> {
+    pub class_name: Cow<'a, str>,
+    pub name: Cow<'a, str>,
+    pub descriptor: Cow<'a, str>,
+}
+
+// the constant pool with JVMS indexing: entries are numbered from 1, and
+// Long and Double take two slots, the second of which is unusable
+#[derive(Debug, Default, PartialEq)]
+pub struct ConstantPool<'a> {
+    // slot i holds constant i + 1; None for the slot after a Long or Double
+    slots: Vec<Option<Constant<'a>>>,
+}
+
+impl<'a> ConstantPool<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // appends a constant, followed by the unusable slot for Long and Double;
+    // returns its index
+    pub fn push(&mut self, constant: Constant<'a>) -> u16 {
+        let wide = matches!(constant, Constant::Long { .. } | Constant::Double { .. });
+        self.slots.push(Some(constant));
+        let index = self.slots.len() as u16;
+        if wide {
+            self.slots.push(None);
+        }
+        index
+    }
+
+    // None for index 0, out-of-range indexes and unusable slots
+    pub fn get(&self, index: u16) -> Option<&Constant<'a>> {
+        let slot = (index as usize).checked_sub(1)?;
+        self.slots.get(slot)?.as_ref()
+    }
+
+    pub fn get_mut(&mut self, index: u16) -> Option<&mut Constant<'a>> {
+        let slot = (index as usize).checked_sub(1)?;
+        self.slots.get_mut(slot)?.as_mut()
+    }
+
+    // the number of slots, i.e. constant_pool_count - 1
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn into_owned(self) -> ConstantPool<'static> {
+        ConstantPool {
+            slots: self
+                .slots
+                .into_iter()
+                .map(|constant| constant.map(Constant::into_owned))
+                .collect(),
+        }
+    }
+
+    // usable entries with their indexes
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &Constant<'a>)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, constant)| Some((slot as u16 + 1, constant.as_ref()?)))
+    }
+
+    // the queries below skip entries whose references don't resolve; validate
+    // reports those
+
+    // the internal names of all Class entries
+    pub fn classes(&self) -> impl Iterator<Item = (u16, Cow<'_, str>)> + '_ {
+        self.iter().filter_map(|(index, constant)| match constant {
+            Constant::Class { .. } => Some((index, self.class_name(index).ok()?)),
+            _ => None,
+        })
+    }
+
+    // the values of all String entries
+    pub fn strings(&self) -> impl Iterator<Item = (u16, Cow<'_, str>)> + '_ {
+        self.iter().filter_map(|(index, constant)| match constant {
+            Constant::String { string_index } => Some((index, self.utf8(*string_index).ok()?)),
+            _ => None,
+        })
+    }
+
+    pub fn field_refs(&self) -> impl Iterator<Item = (u16, SymbolicRef<'_>)> + '_ {
+        self.iter().filter_map(|(index, constant)| match constant {
+            Constant::Fieldref { .. } => Some((index, self.field_ref(index).ok()?)),
+            _ => None,
+        })
+    }
+
+    // both Methodref and InterfaceMethodref entries
+    pub fn method_refs(&self) -> impl Iterator<Item = (u16, SymbolicRef<'_>)> + '_ {
+        self.iter().filter_map(|(index, constant)| match constant {
+            Constant::Methodref { .. } => Some((index, self.method_ref(index).ok()?)),
+            Constant::InterfaceMethodref { .. } => {
+                Some((index, self.interface_method_ref(index).ok()?))
+            }
+            _ => None,
+        })
+    }
+
+    // the index of the first entry equal to `constant`, which is appended if
+    // there's none
+    pub fn find_or_push(&mut self, constant: Constant<'a>) -> u16 {
+        let existing = self
+            .iter()
+            .find_map(|(index, existing)| (*existing == constant).then_some(index));
+        existing.unwrap_or_else(|| self.push(constant))
+    }
+
+    // the index of the first Utf8 entry holding `value`
+    pub fn find_utf8(&self, value: &str) -> Option<u16> {
+        let value = encode_modified_utf8(value);
+        self.iter().find_map(|(index, constant)| match constant {
+            Constant::Utf8 { value: bytes } if *bytes == &*value => Some(index),
+            _ => None,
+        })
+    }
+
+    fn lookup(&self, index: u16) -> Result<&Constant<'a>, ConstantPoolError> {
+        self.get(index)
+            .ok_or(ConstantPoolError::InvalidIndex(index))
+    }
+
+    fn expect(&self, index: u16, expected: ConstantTag) -> Result<(), ConstantPoolError> {
+        let constant = self.lookup(index)?;
+        if constant.tag() != expected {
+            return Err(unexpected(index, expected, constant));
+        }
+        Ok(())
+    }
+
+    // checks that every entry's references are in range and point at the kind
+    // of constant it needs; empty for a well-formed pool
+    pub fn validate(&self) -> Vec<ConstantPoolViolation> {
+        self.iter()
+            .filter_map(|(index, constant)| {
+                let error = self.check(index, constant).err()?;
+                Some(ConstantPoolViolation { index, error })
+            })
+            .collect()
+    }
+
+    fn check(&self, index: u16, constant: &Constant<'a>) -> Result<(), ConstantPoolError> {
+        match constant {
+            Constant::Utf8 { .. } => {
+                self.utf8(index)?;
+            }
+            Constant::Integer { .. }
+            | Constant::Float { .. }
+            | Constant::Long { .. }
+            | Constant::Double { .. } => {}
+            Constant::Class { name_index }
+            | Constant::Module { name_index }
+            | Constant::Package { name_index } => self.expect(*name_index, ConstantTag::Utf8)?,
+            Constant::String { string_index } => self.expect(*string_index, ConstantTag::Utf8)?,
+            Constant::MethodType { descriptor_index } => {
+                self.expect(*descriptor_index, ConstantTag::Utf8)?
+            }
+            Constant::Fieldref {
+                class_index,
+                name_and_type_index,
+            }
+            | Constant::Methodref {
+                class_index,
+                name_and_type_index,
+            }
+            | Constant::InterfaceMethodref {
+                class_index,
+                name_and_type_index,
+            } => {
+                self.expect(*class_index, ConstantTag::Class)?;
+                self.expect(*name_and_type_index, ConstantTag::NameAndType)?;
+            }
+            Constant::NameAndType {
+                name_index,
+                descriptor_index,
+            } => {
+                self.expect(*name_index, ConstantTag::Utf8)?;
+                self.expect(*descriptor_index, ConstantTag::Utf8)?;
+            }
+            Constant::MethodHandle {
+                reference_kind,
+                reference_index,
+            } => {
+                let expected = reference_kind.referenced_tags();
+                let reference = self.lookup(*reference_index)?;
+                if !expected.contains(&reference.tag()) {
+                    return Err(unexpected(*reference_index, expected[0], reference));
+                }
+                // a reference with a broken NameAndType is reported on its own
+                let name = match reference {
+                    Constant::Fieldref {
+                        name_and_type_index,
+                        ..
+                    }
+                    | Constant::Methodref {
+                        name_and_type_index,
+                        ..
+                    }
+                    | Constant::InterfaceMethodref {
+                        name_and_type_index,
+                        ..
+                    } => self.name_and_type(*name_and_type_index).ok(),
+                    _ => None,
+                }
+                .map(|name_and_type| name_and_type.name);
+                let valid = match (reference_kind, name.as_deref()) {
+                    (ReferenceKind::NewInvokeSpecial, Some(name)) => name == "<init>",
+                    (
+                        ReferenceKind::InvokeVirtual
+                        | ReferenceKind::InvokeStatic
+                        | ReferenceKind::InvokeSpecial
+                        | ReferenceKind::InvokeInterface,
+                        Some(name),
+                    ) => name != "<init>" && name != "<clinit>",
+                    _ => true,
+                };
+                if !valid {
+                    return Err(ConstantPoolError::InvalidMethodHandleName {
+                        index: *reference_index,
+                        reference_kind: *reference_kind,
+                    });
+                }
+            }
+            Constant::Dynamic {
+                name_and_type_index,
+                ..
+            }
+            | Constant::InvokeDynamic {
+                name_and_type_index,
+                ..
+            } => self.expect(*name_and_type_index, ConstantTag::NameAndType)?,
+        }
+        Ok(())
+    }
+
+    pub fn utf8(&self, index: u16) -> Result<Cow<'_, str>, ConstantPoolError> {
+        let constant = self.lookup(index)?;
+        match constant.as_str() {
+            Some(value) => value.map_err(|error| ConstantPoolError::InvalidUtf8 { index, error }),
+            None => Err(unexpected(index, ConstantTag::Utf8, constant)),
+        }
+    }
+
+    // the internal name of a Class entry, e.g. `java/lang/Object`
+    pub fn class_name(&self, index: u16) -> Result<Cow<'_, str>, ConstantPoolError> {
+        let constant = self.lookup(index)?;
+        match constant {
+            Constant::Class { name_index } => self.utf8(*name_index),
+            _ => Err(unexpected(index, ConstantTag::Class, constant)),
+        }
+    }
+
+    pub fn name_and_type(&self, index: u16) -> Result<NameAndType<'_>, ConstantPoolError> {
+        let constant = self.lookup(index)?;
+        match constant {
+            Constant::NameAndType {
+                name_index,
+                descriptor_index,
+            } => Ok(NameAndType {
+                name: self.utf8(*name_index)?,
+                descriptor: self.utf8(*descriptor_index)?,
+            }),
+            _ => Err(unexpected(index, ConstantTag::NameAndType, constant)),
+        }
+    }
+
+    pub fn field_ref(&self, index: u16) -> Result<SymbolicRef<'_>, ConstantPoolError> {
+        let constant = self.lookup(index)?;
+        match constant {
+            Constant::Fieldref {
+                class_index,
+                name_and_type_index,
+            } => self.symbolic_ref(*class_index, *name_and_type_index),
+            _ => Err(unexpected(index, ConstantTag::Fieldref, constant)),
+        }
+    }
+
+    pub fn method_ref(&self, index: u16) -> Result<SymbolicRef<'_>, ConstantPoolError> {
+        let constant = self.lookup(index)?;
+        match constant {
+            Constant::Methodref {
+                class_index,
+                name_and_type_index,
+            } => self.symbolic_ref(*class_index, *name_and_type_index),
+            _ => Err(unexpected(index, ConstantTag::Methodref, constant)),
+        }
+    }
+
+    pub fn interface_method_ref(&self, index: u16) -> Result<SymbolicRef<'_>, ConstantPoolError> {
+        let constant = self.lookup(index)?;
+        match constant {
+            Constant::InterfaceMethodref {
+                class_index,
+                name_and_type_index,
+            } => self.symbolic_ref(*class_index, *name_and_type_index),
+            _ => Err(unexpected(index, ConstantTag::InterfaceMethodref, constant)),
+        }
+    }
+
+    fn symbolic_ref(
+        &self,
+        class_index: u16,
+        name_and_type_index: u16,
+    ) -> Result<SymbolicRef<'_>, ConstantPoolError> {
+        let NameAndType { name, descriptor } = self.name_and_type(name_and_type_index)?;
+        Ok(SymbolicRef {
+            class_name: self.class_name(class_index)?,
+            name,
+            descriptor,
+        })
+    }
+}
+
+impl<'a> From<Vec<Constant<'a>>> for ConstantPool<'a> {
+    fn from(constants: Vec<Constant<'a>>) -> Self {
+        constants.into_iter().collect()
+    }
+}
+
+impl<'a> FromIterator<Constant<'a>> for ConstantPool<'a> {
+    fn from_iter<I: IntoIterator<Item = Constant<'a>>>(iter: I) -> Self {
+        let mut constant_pool = ConstantPool::new();
+        for constant in iter {
+            constant_pool.push(constant);
+        }
+        constant_pool
+    }
+}
+
+fn unexpected(index: u16, expected: ConstantTag, found: &Constant) -> ConstantPoolError {
+    ConstantPoolError::UnexpectedConstant {
+        index,
+        expected,
+        found: found.tag(),
+    }
+}
+
+pub fn parse_constant_pool(input: &[u8]) -> Result<(&[u8], ConstantPool), ClassParseError> {
+    let (input, constant_pool_count) = be_u16(input)?;
+    let mut constant_pool = ConstantPool::new();
+    let mut input = input;
+    while constant_pool.len() + 1 < constant_pool_count as usize {
+        let (new_input, constant) = parse_constant(input)?;
+        input = new_input;
+        constant_pool.push(constant);
+    }
+    Ok((input, constant_pool))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_constant_pool() {
+        let input = [
+            0x00, 0x05, // constant_pool_count
+            0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x07, // #1 = Long 7
+            0x03, 0x00, 0x00, 0x00, 0x02, // #3 = Integer 2
+            0x01, 0x00, 0x01, 0x41, // #4 = Utf8 A
+            0x99,
+        ];
+        let (rest, constant_pool) = parse_constant_pool(&input).unwrap();
+        assert_eq!(rest, &[0x99]);
+        assert_eq!(constant_pool.len(), 4);
+        assert_eq!(constant_pool.get(0), None);
+        assert_eq!(constant_pool.get(1), Some(&Constant::Long { value: 7 }));
+        assert_eq!(constant_pool.get(2), None);
+        assert_eq!(constant_pool.get(3), Some(&Constant::Integer { value: 2 }));
+        assert_eq!(
+            constant_pool.get(4),
+            Some(&Constant::Utf8 { value: b"A".into() })
+        );
+        assert_eq!(constant_pool.get(5), None);
+        let indexes = constant_pool
+            .iter()
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        assert_eq!(indexes, [1, 3, 4]);
+    }
+
+    #[test]
+    fn test_push() {
+        let mut constant_pool = ConstantPool::new();
+        assert_eq!(constant_pool.push(Constant::Double { value: 1.0 }), 1);
+        assert_eq!(constant_pool.push(Constant::Utf8 { value: b"x".into() }), 3);
+        assert_eq!(
+            constant_pool,
+            ConstantPool::from(vec![
+                Constant::Double { value: 1.0 },
+                Constant::Utf8 { value: b"x".into() },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_typed_lookups() {
+        let constant_pool = ConstantPool::from(vec![
+            Constant::Methodref {
+                class_index: 2,
+                name_and_type_index: 3,
+            },
+            Constant::Class { name_index: 4 },
+            Constant::NameAndType {
+                name_index: 5,
+                descriptor_index: 6,
+            },
+            Constant::Utf8 {
+                value: b"Main".into(),
+            },
+            Constant::Utf8 {
+                value: b"run".into(),
+            },
+            Constant::Utf8 {
+                value: b"()V".into(),
+            },
+            Constant::Long { value: 1 },
+            Constant::Utf8 {
+                value: b"\xFF".into(),
+            },
+        ]);
+        assert_eq!(constant_pool.utf8(5).unwrap(), "run");
+        assert_eq!(constant_pool.class_name(2).unwrap(), "Main");
+        assert_eq!(
+            constant_pool.name_and_type(3),
+            Ok(NameAndType {
+                name: "run".into(),
+                descriptor: "()V".into(),
+            })
+        );
+        assert_eq!(
+            constant_pool.method_ref(1),
+            Ok(SymbolicRef {
+                class_name: "Main".into(),
+                name: "run".into(),
+                descriptor: "()V".into(),
+            })
+        );
+
+        assert_eq!(
+            constant_pool.field_ref(1),
+            Err(ConstantPoolError::UnexpectedConstant {
+                index: 1,
+                expected: ConstantTag::Fieldref,
+                found: ConstantTag::Methodref,
+            })
+        );
+        assert_eq!(
+            constant_pool.class_name(0),
+            Err(ConstantPoolError::InvalidIndex(0))
+        );
+        assert_eq!(
+            constant_pool.utf8(8),
+            Err(ConstantPoolError::InvalidIndex(8))
+        );
+        assert!(matches!(
+            constant_pool.utf8(9),
+            Err(ConstantPoolError::InvalidUtf8 { index: 9, .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let data = include_bytes!("../../../../java/Insns.class");
+        let (_, classfile) = crate::class::parse_classfile(data).unwrap();
+        assert_eq!(classfile.constant_pool.validate(), vec![]);
+
+        let constant_pool = ConstantPool::from(vec![
+            Constant::Class { name_index: 9 },
+            Constant::Methodref {
+                class_index: 3,
+                name_and_type_index: 4,
+            },
+            Constant::Utf8 {
+                value: b"Main".into(),
+            },
+            Constant::Long { value: 0 },
+            Constant::MethodHandle {
+                reference_kind: ReferenceKind::GetField,
+                reference_index: 2,
+            },
+            Constant::String { string_index: 5 },
+        ]);
+        assert_eq!(
+            constant_pool.validate(),
+            vec![
+                ConstantPoolViolation {
+                    index: 1,
+                    error: ConstantPoolError::InvalidIndex(9),
+                },
+                ConstantPoolViolation {
+                    index: 2,
+                    error: ConstantPoolError::UnexpectedConstant {
+                        index: 3,
+                        expected: ConstantTag::Class,
+                        found: ConstantTag::Utf8,
+                    },
+                },
+                ConstantPoolViolation {
+                    index: 6,
+                    error: ConstantPoolError::UnexpectedConstant {
+                        index: 2,
+                        expected: ConstantTag::Fieldref,
+                        found: ConstantTag::Methodref,
+                    },
+                },
+                ConstantPoolViolation {
+                    index: 7,
+                    error: ConstantPoolError::InvalidIndex(5),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_method_handle_name() {
+        let constant_pool = ConstantPool::from(vec![
+            Constant::Methodref {
+                class_index: 2,
+                name_and_type_index: 4,
+            },
+            Constant::Class { name_index: 3 },
+            Constant::Utf8 {
+                value: b"Main".into(),
+            },
+            Constant::NameAndType {
+                name_index: 5,
+                descriptor_index: 6,
+            },
+            Constant::Utf8 {
+                value: b"<init>".into(),
+            },
+            Constant::Utf8 {
+                value: b"()V".into(),
+            },
+            Constant::MethodHandle {
+                reference_kind: ReferenceKind::NewInvokeSpecial,
+                reference_index: 1,
+            },
+            Constant::MethodHandle {
+                reference_kind: ReferenceKind::InvokeVirtual,
+                reference_index: 1,
+            },
+        ]);
+        assert_eq!(
+            constant_pool.validate(),
+            vec![ConstantPoolViolation {
+                index: 8,
+                error: ConstantPoolError::InvalidMethodHandleName {
+                    index: 1,
+                    reference_kind: ReferenceKind::InvokeVirtual,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_queries() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = crate::class::parse_classfile(data).unwrap();
+        let constant_pool = &classfile.constant_pool;
+
+        let classes = constant_pool
+            .classes()
+            .map(|(_, name)| name)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            classes,
+            [
+                "java/lang/Object",
+                "HelloWorld",
+                "java/lang/System",
+                "java/io/PrintStream"
+            ]
+        );
+        let strings = constant_pool.strings().collect::<Vec<_>>();
+        assert_eq!(strings, [(7, "Hello, World!".into())]);
+        let methods = constant_pool
+            .method_refs()
+            .map(|(_, method)| format!("{}.{}", method.class_name, method.name))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            methods,
+            [
+                "java/lang/Object.<init>",
+                "java/io/PrintStream.println",
+                "HelloWorld.<init>",
+                "HelloWorld.sayHello"
+            ]
+        );
+        assert_eq!(constant_pool.field_refs().count(), 2);
+
+        assert_eq!(constant_pool.find_utf8("java/lang/Object"), Some(4));
+        assert_eq!(constant_pool.find_utf8("missing"), None);
+    }
+}
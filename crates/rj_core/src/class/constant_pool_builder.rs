@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+
+use super::constant::{Constant, ConstantTag, ReferenceKind};
+use super::constant_pool::ConstantPool;
+use super::error::ConstantPoolBuildError;
+use super::modified_utf8::encode_modified_utf8;
+
+// the largest constant_pool_count is 65535, so slots run from 1 to 65534
+const MAX_SLOTS: usize = u16::MAX as usize - 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Entry {
+    // modified UTF-8
+    Utf8(Vec<u8>),
+    Integer(i32),
+    // the bits, so that every NaN payload is kept and compared as is
+    Float(u32),
+    Long(i64),
+    Double(u64),
+    Class(u16),
+    String(u16),
+    Fieldref(u16, u16),
+    Methodref(u16, u16),
+    InterfaceMethodref(u16, u16),
+    NameAndType(u16, u16),
+    MethodHandle(ReferenceKind, u16),
+    MethodType(u16),
+    Dynamic(u16, u16),
+    InvokeDynamic(u16, u16),
+    Module(u16),
+    Package(u16),
+}
+
+impl Entry {
+    fn tag(&self) -> ConstantTag {
+        match self {
+            Entry::Utf8(_) => ConstantTag::Utf8,
+            Entry::Integer(_) => ConstantTag::Integer,
+            Entry::Float(_) => ConstantTag::Float,
+            Entry::Long(_) => ConstantTag::Long,
+            Entry::Double(_) => ConstantTag::Double,
+            Entry::Class(_) => ConstantTag::Class,
+            Entry::String(_) => ConstantTag::String,
+            Entry::Fieldref(..) => ConstantTag::Fieldref,
+            Entry::Methodref(..) => ConstantTag::Methodref,
+            Entry::InterfaceMethodref(..) => ConstantTag::InterfaceMethodref,
+            Entry::NameAndType(..) => ConstantTag::NameAndType,
+            Entry::MethodHandle(..) => ConstantTag::MethodHandle,
+            Entry::MethodType(_) => ConstantTag::MethodType,
+            Entry::Dynamic(..) => ConstantTag::Dynamic,
+            Entry::InvokeDynamic(..) => ConstantTag::InvokeDynamic,
+            Entry::Module(_) => ConstantTag::Module,
+            Entry::Package(_) => ConstantTag::Package,
+        }
+    }
+
+    fn to_constant(&self) -> Constant<'static> {
+        match *self {
+            Entry::Utf8(ref value) => Constant::Utf8 {
+                value: value.clone().into(),
+            },
+            Entry::Integer(value) => Constant::Integer { value },
+            Entry::Float(bits) => Constant::Float {
+                value: f32::from_bits(bits),
+            },
+            Entry::Long(value) => Constant::Long { value },
+            Entry::Double(bits) => Constant::Double {
+                value: f64::from_bits(bits),
+            },
+            Entry::Class(name_index) => Constant::Class { name_index },
+            Entry::String(string_index) => Constant::String { string_index },
+            Entry::Fieldref(class_index, name_and_type_index) => Constant::Fieldref {
+                class_index,
+                name_and_type_index,
+            },
+            Entry::Methodref(class_index, name_and_type_index) => Constant::Methodref {
+                class_index,
+                name_and_type_index,
+            },
+            Entry::InterfaceMethodref(class_index, name_and_type_index) => {
+                Constant::InterfaceMethodref {
+                    class_index,
+                    name_and_type_index,
+                }
+            }
+            Entry::NameAndType(name_index, descriptor_index) => Constant::NameAndType {
+                name_index,
+                descriptor_index,
+            },
+            Entry::MethodHandle(reference_kind, reference_index) => Constant::MethodHandle {
+                reference_kind,
+                reference_index,
+            },
+            Entry::MethodType(descriptor_index) => Constant::MethodType { descriptor_index },
+            Entry::Dynamic(bootstrap_method_attr_index, name_and_type_index) => Constant::Dynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            },
+            Entry::InvokeDynamic(bootstrap_method_attr_index, name_and_type_index) => {
+                Constant::InvokeDynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                }
+            }
+            Entry::Module(name_index) => Constant::Module { name_index },
+            Entry::Package(name_index) => Constant::Package { name_index },
+        }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.push(self.tag() as u8);
+        match self {
+            Entry::Utf8(value) => {
+                out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+                out.extend_from_slice(value);
+            }
+            Entry::Integer(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Entry::Float(bits) => out.extend_from_slice(&bits.to_be_bytes()),
+            Entry::Long(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Entry::Double(bits) => out.extend_from_slice(&bits.to_be_bytes()),
+            Entry::Class(index)
+            | Entry::String(index)
+            | Entry::MethodType(index)
+            | Entry::Module(index)
+            | Entry::Package(index) => out.extend_from_slice(&index.to_be_bytes()),
+            Entry::Fieldref(first, second)
+            | Entry::Methodref(first, second)
+            | Entry::InterfaceMethodref(first, second)
+            | Entry::NameAndType(first, second)
+            | Entry::Dynamic(first, second)
+            | Entry::InvokeDynamic(first, second) => {
+                out.extend_from_slice(&first.to_be_bytes());
+                out.extend_from_slice(&second.to_be_bytes());
+            }
+            Entry::MethodHandle(reference_kind, index) => {
+                out.push(*reference_kind as u8);
+                out.extend_from_slice(&index.to_be_bytes());
+            }
+        }
+    }
+}
+
+// builds a constant pool for writing a class file; adding a constant that is
+// already in the pool returns the index of the existing entry
+#[derive(Debug, Default)]
+pub struct ConstantPoolBuilder {
+    entries: Vec<Entry>,
+    indexes: HashMap<Entry, u16>,
+    // the number of slots used, counting two for Long and Double
+    len: usize,
+}
+
+impl ConstantPoolBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // the number of slots, i.e. constant_pool_count - 1
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn intern(&mut self, entry: Entry) -> Result<u16, ConstantPoolBuildError> {
+        if let Some(index) = self.indexes.get(&entry) {
+            return Ok(*index);
+        }
+        let width = match entry {
+            Entry::Long(_) | Entry::Double(_) => 2,
+            _ => 1,
+        };
+        if self.len + width > MAX_SLOTS {
+            return Err(ConstantPoolBuildError::TooManyConstants);
+        }
+        let index = self.len as u16 + 1;
+        self.len += width;
+        self.indexes.insert(entry.clone(), index);
+        self.entries.push(entry);
+        Ok(index)
+    }
+
+    pub fn utf8(&mut self, value: &str) -> Result<u16, ConstantPoolBuildError> {
+        let value = encode_modified_utf8(value);
+        if value.len() > u16::MAX as usize {
+            return Err(ConstantPoolBuildError::Utf8TooLong(value.len()));
+        }
+        self.intern(Entry::Utf8(value.into_owned()))
+    }
+
+    pub fn integer(&mut self, value: i32) -> Result<u16, ConstantPoolBuildError> {
+        self.intern(Entry::Integer(value))
+    }
+
+    pub fn float(&mut self, value: f32) -> Result<u16, ConstantPoolBuildError> {
+        self.intern(Entry::Float(value.to_bits()))
+    }
+
+    pub fn long(&mut self, value: i64) -> Result<u16, ConstantPoolBuildError> {
+        self.intern(Entry::Long(value))
+    }
+
+    pub fn double(&mut self, value: f64) -> Result<u16, ConstantPoolBuildError> {
+        self.intern(Entry::Double(value.to_bits()))
+    }
+
+    // `name` is an internal name, e.g. `java/lang/Object`
+    pub fn class(&mut self, name: &str) -> Result<u16, ConstantPoolBuildError> {
+        let name_index = self.utf8(name)?;
+        self.intern(Entry::Class(name_index))
+    }
+
+    pub fn string(&mut self, value: &str) -> Result<u16, ConstantPoolBuildError> {
+        let string_index = self.utf8(value)?;
+        self.intern(Entry::String(string_index))
+    }
+
+    pub fn name_and_type(
+        &mut self,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<u16, ConstantPoolBuildError> {
+        let name_index = self.utf8(name)?;
+        let descriptor_index = self.utf8(descriptor)?;
+        self.intern(Entry::NameAndType(name_index, descriptor_index))
+    }
+
+    pub fn field_ref(
+        &mut self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<u16, ConstantPoolBuildError> {
+        let class_index = self.class(class)?;
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        self.intern(Entry::Fieldref(class_index, name_and_type_index))
+    }
+
+    pub fn method_ref(
+        &mut self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<u16, ConstantPoolBuildError> {
+        let class_index = self.class(class)?;
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        self.intern(Entry::Methodref(class_index, name_and_type_index))
+    }
+
+    pub fn interface_method_ref(
+        &mut self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<u16, ConstantPoolBuildError> {
+        let class_index = self.class(class)?;
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        self.intern(Entry::InterfaceMethodref(class_index, name_and_type_index))
+    }
+
+    // `reference_index` is the index of a Fieldref, Methodref or
+    // InterfaceMethodref returned by this builder
+    pub fn method_handle(
+        &mut self,
+        reference_kind: ReferenceKind,
+        reference_index: u16,
+    ) -> Result<u16, ConstantPoolBuildError> {
+        self.intern(Entry::MethodHandle(reference_kind, reference_index))
+    }
+
+    pub fn method_type(&mut self, descriptor: &str) -> Result<u16, ConstantPoolBuildError> {
+        let descriptor_index = self.utf8(descriptor)?;
+        self.intern(Entry::MethodType(descriptor_index))
+    }
+
+    pub fn dynamic(
+        &mut self,
+        bootstrap_method_attr_index: u16,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<u16, ConstantPoolBuildError> {
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        self.intern(Entry::Dynamic(
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        ))
+    }
+
+    pub fn invoke_dynamic(
+        &mut self,
+        bootstrap_method_attr_index: u16,
+        name: &str,
+        descriptor: &str,
+    ) -> Result<u16, ConstantPoolBuildError> {
+        let name_and_type_index = self.name_and_type(name, descriptor)?;
+        self.intern(Entry::InvokeDynamic(
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        ))
+    }
+
+    pub fn module(&mut self, name: &str) -> Result<u16, ConstantPoolBuildError> {
+        let name_index = self.utf8(name)?;
+        self.intern(Entry::Module(name_index))
+    }
+
+    pub fn package(&mut self, name: &str) -> Result<u16, ConstantPoolBuildError> {
+        let name_index = self.utf8(name)?;
+        self.intern(Entry::Package(name_index))
+    }
+
+    // the pool built so far, as the parser would read it back
+    pub fn to_constant_pool(&self) -> ConstantPool<'static> {
+        self.entries.iter().map(Entry::to_constant).collect()
+    }
+
+    // constant_pool_count followed by the entries
+    pub fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(self.len as u16 + 1).to_be_bytes());
+        for entry in &self.entries {
+            entry.write(out);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::constant::Constant;
+    use super::super::constant_pool::{parse_constant_pool, SymbolicRef};
+    use super::*;
+
+    #[test]
+    fn test_build() {
+        let mut builder = ConstantPoolBuilder::new();
+        assert_eq!(
+            builder.method_ref("java/lang/Object", "<init>", "()V"),
+            Ok(6)
+        );
+        assert_eq!(builder.class("java/lang/Object"), Ok(2));
+        assert_eq!(builder.long(7), Ok(7));
+        assert_eq!(builder.string("()V"), Ok(9));
+        assert_eq!(builder.long(7), Ok(7));
+        assert_eq!(builder.utf8("x"), Ok(10));
+        assert_eq!(
+            builder.method_handle(ReferenceKind::NewInvokeSpecial, 6),
+            Ok(11)
+        );
+        assert_eq!(builder.len(), 11);
+
+        let mut bytes = vec![];
+        builder.write(&mut bytes);
+        let (rest, constant_pool) = parse_constant_pool(&bytes).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(constant_pool.validate(), vec![]);
+        assert_eq!(
+            constant_pool.method_ref(6),
+            Ok(SymbolicRef {
+                class_name: "java/lang/Object".into(),
+                name: "<init>".into(),
+                descriptor: "()V".into(),
+            })
+        );
+        assert_eq!(constant_pool.get(7), Some(&Constant::Long { value: 7 }));
+        assert_eq!(constant_pool.get(8), None);
+        assert_eq!(
+            constant_pool.get(9),
+            Some(&Constant::String { string_index: 4 })
+        );
+        assert_eq!(builder.to_constant_pool(), constant_pool);
+    }
+
+    #[test]
+    fn test_too_many_constants() {
+        let mut builder = ConstantPoolBuilder::new();
+        for value in 0..MAX_SLOTS as i32 - 1 {
+            builder.integer(value).unwrap();
+        }
+        assert_eq!(
+            builder.long(0),
+            Err(ConstantPoolBuildError::TooManyConstants)
+        );
+        assert_eq!(builder.integer(-1), Ok(MAX_SLOTS as u16));
+        assert_eq!(
+            builder.integer(-2),
+            Err(ConstantPoolBuildError::TooManyConstants)
+        );
+        assert_eq!(builder.integer(0), Ok(1));
+
+        let value = "x".repeat(u16::MAX as usize + 1);
+        assert_eq!(
+            ConstantPoolBuilder::new().utf8(&value),
+            Err(ConstantPoolBuildError::Utf8TooLong(value.len()))
+        );
+    }
+}
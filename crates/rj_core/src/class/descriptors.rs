@@ -1,5 +1,11 @@
+mod class_names;
 mod field_descriptor;
 mod method_descriptor;
+mod names;
+mod signature;
 
+pub(crate) use self::class_names::{class_names, Text};
 pub use self::field_descriptor::*;
 pub use self::method_descriptor::*;
+pub use self::names::*;
+pub use self::signature::*;
@@ -0,0 +1,116 @@
+use super::field_descriptor::{parse_field_type, FieldType};
+use super::method_descriptor::parse_method_descriptor;
+use super::signature::{
+    parse_class_signature, parse_field_signature, parse_method_signature, ClassTypeSignature,
+    TypeArgument, TypeParameter, TypeSignature,
+};
+
+// what a Utf8 entry holds, which decides where the class names in it are
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Text {
+    // an internal name, or an array descriptor for an array class
+    ClassName,
+    FieldDescriptor,
+    // a field descriptor or V, as in an annotation's class element
+    ReturnDescriptor,
+    MethodDescriptor,
+    // either, as in a NameAndType
+    Descriptor,
+    ClassSignature,
+    MethodSignature,
+    FieldSignature,
+}
+
+fn field_type_names<'v>(field_type: &FieldType<'v>, names: &mut Vec<&'v [u8]>) {
+    if let FieldType::Object(name) = field_type.element_type() {
+        names.push(name);
+    }
+}
+
+fn type_signature_names<'v>(signature: &TypeSignature<'v>, names: &mut Vec<&'v [u8]>) {
+    match signature {
+        TypeSignature::Class(class) => class_type_names(class, names),
+        TypeSignature::Array(component) => type_signature_names(component, names),
+        TypeSignature::Base(_) | TypeSignature::TypeVariable(_) => {}
+    }
+}
+
+// the simple names of inner classes are left out; they're relative to the
+// class before them
+fn class_type_names<'v>(class: &ClassTypeSignature<'v>, names: &mut Vec<&'v [u8]>) {
+    names.push(class.name);
+    let inner = class.inner.iter().flat_map(|inner| &inner.type_arguments);
+    for argument in class.type_arguments.iter().chain(inner) {
+        match argument {
+            TypeArgument::Wildcard => {}
+            TypeArgument::Extends(bound)
+            | TypeArgument::Super(bound)
+            | TypeArgument::Exact(bound) => type_signature_names(bound, names),
+        }
+    }
+}
+
+fn type_parameter_names<'v>(parameters: &[TypeParameter<'v>], names: &mut Vec<&'v [u8]>) {
+    for parameter in parameters {
+        for bound in parameter
+            .class_bound
+            .iter()
+            .chain(&parameter.interface_bounds)
+        {
+            type_signature_names(bound, names);
+        }
+    }
+}
+
+// the class names in `value`, as slices of it; None if it doesn't parse
+pub(crate) fn class_names(value: &[u8], text: Text) -> Option<Vec<&[u8]>> {
+    let mut names = Vec::new();
+    let rest = match text {
+        Text::ClassName if value.first() != Some(&b'[') => {
+            names.push(value);
+            &[]
+        }
+        Text::ReturnDescriptor if value == b"V" => &[],
+        Text::ClassName | Text::FieldDescriptor | Text::ReturnDescriptor => {
+            let (rest, field_type) = parse_field_type(value).ok()?;
+            field_type_names(&field_type, &mut names);
+            rest
+        }
+        Text::Descriptor if value.first() == Some(&b'(') => {
+            return class_names(value, Text::MethodDescriptor)
+        }
+        Text::Descriptor => return class_names(value, Text::FieldDescriptor),
+        Text::MethodDescriptor => {
+            let (rest, descriptor) = parse_method_descriptor(value).ok()?;
+            for parameter in &descriptor.parameters {
+                field_type_names(parameter, &mut names);
+            }
+            field_type_names(&descriptor.return_type, &mut names);
+            rest
+        }
+        Text::ClassSignature => {
+            let (rest, signature) = parse_class_signature(value).ok()?;
+            type_parameter_names(&signature.type_parameters, &mut names);
+            class_type_names(&signature.superclass, &mut names);
+            for interface in &signature.interfaces {
+                class_type_names(interface, &mut names);
+            }
+            rest
+        }
+        Text::MethodSignature => {
+            let (rest, signature) = parse_method_signature(value).ok()?;
+            type_parameter_names(&signature.type_parameters, &mut names);
+            let types = signature.parameters.iter().chain([&signature.result]);
+            for signature in types.chain(&signature.throws) {
+                type_signature_names(signature, &mut names);
+            }
+            rest
+        }
+        Text::FieldSignature => {
+            let (rest, signature) = parse_field_signature(value).ok()?;
+            type_signature_names(&signature, &mut names);
+            rest
+        }
+    };
+    rest.is_empty().then_some(names)
+}
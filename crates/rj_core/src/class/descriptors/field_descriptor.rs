@@ -1,4 +1,5 @@
 use super::super::error::ClassParseError;
+use super::super::modified_utf8::decode_modified_utf8;
 use crate::parser::{be_u8, take_until};
 
 #[derive(Debug, PartialEq)]
@@ -16,6 +17,99 @@ pub enum FieldType<'a> {
     Void,
 }
 
+impl<'a> FieldType<'a> {
+    pub fn array_of(element_type: FieldType<'a>, dimensions: u8) -> Self {
+        let mut field_type = element_type;
+        for _ in 0..dimensions {
+            field_type = FieldType::Array(Box::new(field_type));
+        }
+        field_type
+    }
+
+    pub fn dimensions(&self) -> u8 {
+        let mut dimensions = 0;
+        let mut field_type = self;
+        while let FieldType::Array(inner) = field_type {
+            dimensions += 1;
+            field_type = inner;
+        }
+        dimensions
+    }
+
+    pub fn element_type(&self) -> &FieldType<'a> {
+        let mut field_type = self;
+        while let FieldType::Array(inner) = field_type {
+            field_type = inner;
+        }
+        field_type
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, FieldType::Array(_))
+    }
+
+    pub fn is_primitive(&self) -> bool {
+        matches!(
+            self,
+            FieldType::Byte
+                | FieldType::Char
+                | FieldType::Double
+                | FieldType::Float
+                | FieldType::Int
+                | FieldType::Long
+                | FieldType::Short
+                | FieldType::Boolean
+        )
+    }
+
+    pub fn is_reference(&self) -> bool {
+        matches!(self, FieldType::Object(_) | FieldType::Array(_))
+    }
+
+    // the local variable or operand stack slots a value of this type takes
+    pub fn slot_size(&self) -> u16 {
+        match self {
+            FieldType::Long | FieldType::Double => 2,
+            FieldType::Void => 0,
+            _ => 1,
+        }
+    }
+
+    // the descriptor, e.g. `[Ljava/lang/String;`; the inverse of
+    // parse_field_type
+    pub fn write(&self) -> String {
+        let mut descriptor = String::new();
+        self.write_to(&mut descriptor);
+        descriptor
+    }
+
+    pub(super) fn write_to(&self, descriptor: &mut String) {
+        match self {
+            FieldType::Byte => descriptor.push('B'),
+            FieldType::Char => descriptor.push('C'),
+            FieldType::Double => descriptor.push('D'),
+            FieldType::Float => descriptor.push('F'),
+            FieldType::Int => descriptor.push('I'),
+            FieldType::Long => descriptor.push('J'),
+            FieldType::Short => descriptor.push('S'),
+            FieldType::Boolean => descriptor.push('Z'),
+            FieldType::Object(class_name) => {
+                descriptor.push('L');
+                match decode_modified_utf8(class_name) {
+                    Ok(class_name) => descriptor.push_str(&class_name),
+                    Err(_) => descriptor.push_str(&String::from_utf8_lossy(class_name)),
+                }
+                descriptor.push(';');
+            }
+            FieldType::Array(component_type) => {
+                descriptor.push('[');
+                component_type.write_to(descriptor);
+            }
+            FieldType::Void => descriptor.push('V'),
+        }
+    }
+}
+
 fn parse_base_type(input: &[u8]) -> Result<(&[u8], FieldType), ClassParseError> {
     let (rest, tag) = be_u8(input)?;
     match tag {
@@ -40,13 +134,16 @@ fn parse_object_type(input: &[u8]) -> Result<(&[u8], FieldType), ClassParseError
     Ok((rest, FieldType::Object(class_name)))
 }
 
+// the JVMS allows at most 255 dimensions, so `dimensions` fits in a u8
 fn parse_array_type(input: &[u8]) -> Result<(&[u8], FieldType), ClassParseError> {
-    let (rest, tag) = be_u8(input)?;
-    if tag != b'[' {
-        return Err(ClassParseError::InvalidFieldDescriptor);
-    }
-    let (rest, field_type) = parse_field_type(rest)?;
-    Ok((rest, FieldType::Array(Box::new(field_type))))
+    let dimensions = input.iter().take_while(|&&byte| byte == b'[').count();
+    let dimensions = match u8::try_from(dimensions) {
+        Ok(dimensions) if dimensions > 0 => dimensions,
+        _ => return Err(ClassParseError::InvalidFieldDescriptor),
+    };
+    let rest = &input[dimensions as usize..];
+    let (rest, element_type) = parse_base_type(rest).or_else(|_| parse_object_type(rest))?;
+    Ok((rest, FieldType::array_of(element_type, dimensions)))
 }
 
 pub fn parse_field_type(input: &[u8]) -> Result<(&[u8], FieldType), ClassParseError> {
@@ -127,4 +224,72 @@ mod tests {
             Err(ClassParseError::InvalidFieldDescriptor)
         );
     }
+
+    #[test]
+    fn test_array_helpers() {
+        let (_, field_type) = parse_field_type(b"[[[I").unwrap();
+        assert_eq!(field_type.dimensions(), 3);
+        assert_eq!(field_type.element_type(), &FieldType::Int);
+        assert!(field_type.is_array());
+        assert!(field_type.is_reference());
+        assert!(!field_type.is_primitive());
+        assert_eq!(field_type, FieldType::array_of(FieldType::Int, 3));
+
+        let (_, field_type) = parse_field_type(b"[Ljava/lang/String;").unwrap();
+        assert_eq!(field_type.dimensions(), 1);
+        assert_eq!(
+            field_type.element_type(),
+            &FieldType::Object(b"java/lang/String")
+        );
+        assert!(field_type.is_array());
+        assert!(field_type.is_reference());
+        assert!(!field_type.is_primitive());
+
+        let mut descriptor = "[".repeat(255);
+        descriptor.push('I');
+        let (_, field_type) = parse_field_type(descriptor.as_bytes()).unwrap();
+        assert_eq!(field_type.dimensions(), 255);
+        descriptor.insert(0, '[');
+        assert_eq!(
+            parse_field_type(descriptor.as_bytes()),
+            Err(ClassParseError::InvalidFieldDescriptor)
+        );
+
+        let field_type = FieldType::Long;
+        assert_eq!(field_type.dimensions(), 0);
+        assert_eq!(field_type.element_type(), &FieldType::Long);
+        assert!(!field_type.is_array());
+        assert!(!field_type.is_reference());
+        assert!(field_type.is_primitive());
+        assert_eq!(FieldType::array_of(FieldType::Long, 0), FieldType::Long);
+    }
+
+    #[test]
+    fn test_slot_size() {
+        assert_eq!(FieldType::Long.slot_size(), 2);
+        assert_eq!(FieldType::Double.slot_size(), 2);
+        assert_eq!(FieldType::Int.slot_size(), 1);
+        assert_eq!(FieldType::array_of(FieldType::Long, 1).slot_size(), 1);
+        assert_eq!(FieldType::Object(b"java/lang/Object").slot_size(), 1);
+        assert_eq!(FieldType::Void.slot_size(), 0);
+    }
+
+    #[test]
+    fn test_write() {
+        for descriptor in [
+            "B",
+            "Z",
+            "J",
+            "Ljava/lang/String;",
+            "[[D",
+            "[Ljava/util/Map$Entry;",
+        ] {
+            let (_, field_type) = parse_field_type(descriptor.as_bytes()).unwrap();
+            assert_eq!(field_type.write(), descriptor);
+        }
+        assert_eq!(
+            FieldType::array_of(FieldType::Object(b"java/lang/Object"), 2).write(),
+            "[[Ljava/lang/Object;"
+        );
+    }
 }
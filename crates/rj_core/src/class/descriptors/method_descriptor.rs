@@ -8,6 +8,36 @@ pub struct MethodDescriptor<'a> {
     pub return_type: FieldType<'a>,
 }
 
+impl<'a> MethodDescriptor<'a> {
+    // parses a whole descriptor, rejecting anything after the return type
+    pub fn parse(input: &'a [u8]) -> Result<Self, ClassParseError> {
+        match parse_method_descriptor(input)? {
+            (b"", descriptor) => Ok(descriptor),
+            _ => Err(ClassParseError::InvalidMethodDescriptor),
+        }
+    }
+
+    // the local variable slots the arguments take, not counting `this`
+    pub fn parameter_slots(&self) -> u16 {
+        self.parameters
+            .iter()
+            .map(|parameter| parameter.slot_size())
+            .sum()
+    }
+
+    // the descriptor, e.g. `(I[Ljava/lang/String;)V`; the inverse of
+    // parse_method_descriptor
+    pub fn write(&self) -> String {
+        let mut descriptor = String::from("(");
+        for parameter in &self.parameters {
+            parameter.write_to(&mut descriptor);
+        }
+        descriptor.push(')');
+        self.return_type.write_to(&mut descriptor);
+        descriptor
+    }
+}
+
 fn parse_return_type(input: &[u8]) -> Result<(&[u8], FieldType), ClassParseError> {
     let (rest, tag) = be_u8(input)?;
     match tag {
@@ -20,19 +50,29 @@ fn parse_return_type(input: &[u8]) -> Result<(&[u8], FieldType), ClassParseError
 }
 
 pub fn parse_method_descriptor(input: &[u8]) -> Result<(&[u8], MethodDescriptor), ClassParseError> {
-    let (rest, _) = be_u8(input)?; // '('
-    let mut parameter_types: Vec<FieldType> = vec![];
-    let mut rest = rest;
-    while let Ok((new_rest, field_type)) = parse_field_type(rest) {
-        parameter_types.push(field_type);
-        rest = new_rest;
+    let (mut rest, tag) = be_u8(input)?;
+    if tag != b'(' {
+        return Err(ClassParseError::InvalidMethodDescriptor);
+    }
+    let mut parameters = vec![];
+    loop {
+        match rest.first() {
+            Some(b')') => break,
+            Some(_) => {
+                let (new_rest, field_type) =
+                    parse_field_type(rest).map_err(|_| ClassParseError::InvalidMethodDescriptor)?;
+                parameters.push(field_type);
+                rest = new_rest;
+            }
+            None => return Err(ClassParseError::InvalidMethodDescriptor),
+        }
     }
-    let (rest, _) = be_u8(rest)?; // ')'
-    let (rest, return_type) = parse_return_type(rest)?;
+    let (rest, return_type) =
+        parse_return_type(&rest[1..]).map_err(|_| ClassParseError::InvalidMethodDescriptor)?;
     Ok((
         rest,
         MethodDescriptor {
-            parameters: parameter_types,
+            parameters,
             return_type,
         },
     ))
@@ -78,4 +118,54 @@ mod tests {
         assert_eq!(rest, b"");
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_parse_invalid_method_descriptor() {
+        for input in [
+            &b"I)V"[..],
+            b"(I",
+            b"(IV",
+            b"(Q)V",
+            b"(V)V",
+            b"(I)",
+            b"(I)Q",
+        ] {
+            assert_eq!(
+                parse_method_descriptor(input),
+                Err(ClassParseError::InvalidMethodDescriptor)
+            );
+        }
+        assert_eq!(parse_method_descriptor(b"()Vxxx").unwrap().0, b"xxx");
+        assert_eq!(
+            MethodDescriptor::parse(b"()Vxxx"),
+            Err(ClassParseError::InvalidMethodDescriptor)
+        );
+        assert_eq!(
+            MethodDescriptor::parse(b"()V"),
+            Ok(MethodDescriptor {
+                parameters: vec![],
+                return_type: FieldType::Void,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parameter_slots() {
+        let descriptor = MethodDescriptor::parse(b"(IJLjava/lang/String;D[J)V").unwrap();
+        assert_eq!(descriptor.parameter_slots(), 7);
+        let descriptor = MethodDescriptor::parse(b"()J").unwrap();
+        assert_eq!(descriptor.parameter_slots(), 0);
+    }
+
+    #[test]
+    fn test_write() {
+        for descriptor in [
+            "()V",
+            "(IDLjava/lang/Thread;)Ljava/lang/Object;",
+            "([[JZ)[I",
+        ] {
+            let (_, method_descriptor) = parse_method_descriptor(descriptor.as_bytes()).unwrap();
+            assert_eq!(method_descriptor.write(), descriptor);
+        }
+    }
 }
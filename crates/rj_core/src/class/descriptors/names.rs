@@ -0,0 +1,113 @@
+// conversions between the forms class and type names are written in
+// https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-4.html#jvms-4.2.1
+
+use super::field_descriptor::parse_field_type;
+
+// `java/lang/String` to `java.lang.String`
+pub fn internal_to_binary_name(name: &str) -> String {
+    name.replace('/', ".")
+}
+
+// `java.lang.String` to `java/lang/String`
+pub fn binary_to_internal_name(name: &str) -> String {
+    name.replace('.', "/")
+}
+
+// `Ljava/lang/String;` to `java.lang.String`, `[I` to `int[]` and `V` to
+// `void`
+pub fn descriptor_to_java_name(descriptor: &str) -> Option<String> {
+    if descriptor == "V" {
+        return Some("void".to_string());
+    }
+    match parse_field_type(descriptor.as_bytes()) {
+        Ok((b"", field_type)) => Some(field_type.print()),
+        _ => None,
+    }
+}
+
+// the inverse of descriptor_to_java_name
+pub fn java_name_to_descriptor(name: &str) -> Option<String> {
+    let mut element_type = name;
+    let mut dimensions = 0;
+    while let Some(rest) = element_type.strip_suffix("[]") {
+        element_type = rest;
+        dimensions += 1;
+    }
+    let element_type = match element_type {
+        "byte" => "B".to_string(),
+        "char" => "C".to_string(),
+        "double" => "D".to_string(),
+        "float" => "F".to_string(),
+        "int" => "I".to_string(),
+        "long" => "J".to_string(),
+        "short" => "S".to_string(),
+        "boolean" => "Z".to_string(),
+        "void" if dimensions == 0 => "V".to_string(),
+        "" | "void" => return None,
+        class_name if class_name.contains(['/', ';', '[', ']', '<', '>']) => return None,
+        class_name => format!("L{};", binary_to_internal_name(class_name)),
+    };
+    Some("[".repeat(dimensions) + &element_type)
+}
+
+// the name of a CONSTANT_Class, which is an internal name or, for array
+// classes, a descriptor, as Java source writes it
+pub fn class_name_to_java_name(name: &str) -> String {
+    if name.starts_with('[') {
+        if let Some(java_name) = descriptor_to_java_name(name) {
+            return java_name;
+        }
+    }
+    internal_to_binary_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!(
+            internal_to_binary_name("java/util/Map$Entry"),
+            "java.util.Map$Entry"
+        );
+        assert_eq!(
+            binary_to_internal_name("java.util.Map$Entry"),
+            "java/util/Map$Entry"
+        );
+        assert_eq!(
+            class_name_to_java_name("java/lang/String"),
+            "java.lang.String"
+        );
+        assert_eq!(
+            class_name_to_java_name("[[Ljava/lang/String;"),
+            "java.lang.String[][]"
+        );
+    }
+
+    #[test]
+    fn test_descriptors() {
+        let cases = [
+            ("I", "int"),
+            ("V", "void"),
+            ("[I", "int[]"),
+            ("Ljava/lang/String;", "java.lang.String"),
+            ("[[Ljava/lang/Object;", "java.lang.Object[][]"),
+        ];
+        for (descriptor, java_name) in cases {
+            assert_eq!(
+                descriptor_to_java_name(descriptor).as_deref(),
+                Some(java_name)
+            );
+            assert_eq!(
+                java_name_to_descriptor(java_name).as_deref(),
+                Some(descriptor)
+            );
+        }
+        assert_eq!(descriptor_to_java_name("[V"), None);
+        assert_eq!(descriptor_to_java_name("Ljava/lang/String"), None);
+        assert_eq!(java_name_to_descriptor("void[]"), None);
+        assert_eq!(java_name_to_descriptor("[]"), None);
+        assert_eq!(java_name_to_descriptor("java/lang/String"), None);
+    }
+}
@@ -0,0 +1,478 @@
+// Generic signatures as stored in the Signature attribute
+// https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-4.html#jvms-4.7.9.1
+
+use super::super::error::ClassParseError;
+use super::field_descriptor::FieldType;
+use crate::parser::be_u8;
+
+#[derive(Debug, PartialEq)]
+pub enum TypeSignature<'a> {
+    // a primitive type, or Void for a method result
+    Base(FieldType<'a>),
+    Class(ClassTypeSignature<'a>),
+    TypeVariable(&'a [u8]),
+    Array(Box<TypeSignature<'a>>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ClassTypeSignature<'a> {
+    // internal name, e.g. `java/util/List`
+    pub name: &'a [u8],
+    pub type_arguments: Vec<TypeArgument<'a>>,
+    // the `.Inner<TU;>` suffixes of a member class of a generic class
+    pub inner: Vec<SimpleClassTypeSignature<'a>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SimpleClassTypeSignature<'a> {
+    // the simple name of the member class, e.g. `Inner`
+    pub name: &'a [u8],
+    pub type_arguments: Vec<TypeArgument<'a>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TypeArgument<'a> {
+    // `?`
+    Wildcard,
+    // `? extends T`
+    Extends(TypeSignature<'a>),
+    // `? super T`
+    Super(TypeSignature<'a>),
+    Exact(TypeSignature<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TypeParameter<'a> {
+    pub name: &'a [u8],
+    // absent when the only bounds are interfaces, e.g. `K::Ljava/lang/Comparable<TK;>;`
+    pub class_bound: Option<TypeSignature<'a>>,
+    pub interface_bounds: Vec<TypeSignature<'a>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ClassSignature<'a> {
+    pub type_parameters: Vec<TypeParameter<'a>>,
+    pub superclass: ClassTypeSignature<'a>,
+    pub interfaces: Vec<ClassTypeSignature<'a>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MethodSignature<'a> {
+    pub type_parameters: Vec<TypeParameter<'a>>,
+    pub parameters: Vec<TypeSignature<'a>>,
+    pub result: TypeSignature<'a>,
+    // class types or type variables
+    pub throws: Vec<TypeSignature<'a>>,
+}
+
+fn expect(input: &[u8], expected: u8) -> Result<&[u8], ClassParseError> {
+    let (rest, tag) = be_u8(input)?;
+    if tag != expected {
+        return Err(ClassParseError::InvalidSignature);
+    }
+    Ok(rest)
+}
+
+fn parse_identifier(input: &[u8]) -> Result<(&[u8], &[u8]), ClassParseError> {
+    let end = input
+        .iter()
+        .position(|b| matches!(b, b'.' | b';' | b'[' | b'/' | b'<' | b'>' | b':'))
+        .unwrap_or(input.len());
+    if end == 0 {
+        return Err(ClassParseError::InvalidSignature);
+    }
+    Ok((&input[end..], &input[..end]))
+}
+
+fn parse_base_type(input: &[u8]) -> Option<(&[u8], FieldType)> {
+    let field_type = match input.first()? {
+        b'B' => FieldType::Byte,
+        b'C' => FieldType::Char,
+        b'D' => FieldType::Double,
+        b'F' => FieldType::Float,
+        b'I' => FieldType::Int,
+        b'J' => FieldType::Long,
+        b'S' => FieldType::Short,
+        b'Z' => FieldType::Boolean,
+        _ => return None,
+    };
+    Some((&input[1..], field_type))
+}
+
+fn parse_type_arguments(input: &[u8]) -> Result<(&[u8], Vec<TypeArgument>), ClassParseError> {
+    if input.first() != Some(&b'<') {
+        return Ok((input, vec![]));
+    }
+    let mut input = &input[1..];
+    let mut type_arguments = vec![];
+    while input.first() != Some(&b'>') {
+        let (rest, type_argument) = match input.first() {
+            Some(b'*') => (&input[1..], TypeArgument::Wildcard),
+            Some(b'+') => {
+                let (rest, bound) = parse_reference_type(&input[1..])?;
+                (rest, TypeArgument::Extends(bound))
+            }
+            Some(b'-') => {
+                let (rest, bound) = parse_reference_type(&input[1..])?;
+                (rest, TypeArgument::Super(bound))
+            }
+            _ => {
+                let (rest, argument) = parse_reference_type(input)?;
+                (rest, TypeArgument::Exact(argument))
+            }
+        };
+        input = rest;
+        type_arguments.push(type_argument);
+    }
+    if type_arguments.is_empty() {
+        return Err(ClassParseError::InvalidSignature);
+    }
+    Ok((&input[1..], type_arguments))
+}
+
+fn parse_class_type(input: &[u8]) -> Result<(&[u8], ClassTypeSignature), ClassParseError> {
+    let input = expect(input, b'L')?;
+    let end = input
+        .iter()
+        .position(|b| matches!(b, b'<' | b';' | b'.'))
+        .ok_or(ClassParseError::InvalidSignature)?;
+    if end == 0 {
+        return Err(ClassParseError::InvalidSignature);
+    }
+    let (name, input) = input.split_at(end);
+    let (mut input, type_arguments) = parse_type_arguments(input)?;
+    let mut inner = vec![];
+    while input.first() == Some(&b'.') {
+        let (rest, name) = parse_identifier(&input[1..])?;
+        let (rest, type_arguments) = parse_type_arguments(rest)?;
+        input = rest;
+        inner.push(SimpleClassTypeSignature {
+            name,
+            type_arguments,
+        });
+    }
+    let input = expect(input, b';')?;
+    Ok((
+        input,
+        ClassTypeSignature {
+            name,
+            type_arguments,
+            inner,
+        },
+    ))
+}
+
+fn parse_reference_type(input: &[u8]) -> Result<(&[u8], TypeSignature), ClassParseError> {
+    match input.first() {
+        Some(b'L') => {
+            let (rest, class_type) = parse_class_type(input)?;
+            Ok((rest, TypeSignature::Class(class_type)))
+        }
+        Some(b'T') => {
+            let (rest, name) = parse_identifier(&input[1..])?;
+            let rest = expect(rest, b';')?;
+            Ok((rest, TypeSignature::TypeVariable(name)))
+        }
+        Some(b'[') => {
+            let (rest, component) = parse_java_type(&input[1..])?;
+            Ok((rest, TypeSignature::Array(Box::new(component))))
+        }
+        _ => Err(ClassParseError::InvalidSignature),
+    }
+}
+
+fn parse_java_type(input: &[u8]) -> Result<(&[u8], TypeSignature), ClassParseError> {
+    match parse_base_type(input) {
+        Some((rest, base_type)) => Ok((rest, TypeSignature::Base(base_type))),
+        None => parse_reference_type(input),
+    }
+}
+
+fn parse_type_parameters(input: &[u8]) -> Result<(&[u8], Vec<TypeParameter>), ClassParseError> {
+    if input.first() != Some(&b'<') {
+        return Ok((input, vec![]));
+    }
+    let mut input = &input[1..];
+    let mut type_parameters = vec![];
+    while input.first() != Some(&b'>') {
+        let (rest, name) = parse_identifier(input)?;
+        let rest = expect(rest, b':')?;
+        let (mut rest, class_bound) = match rest.first() {
+            Some(b'L' | b'T' | b'[') => {
+                let (rest, bound) = parse_reference_type(rest)?;
+                (rest, Some(bound))
+            }
+            _ => (rest, None),
+        };
+        let mut interface_bounds = vec![];
+        while rest.first() == Some(&b':') {
+            let (new_rest, bound) = parse_reference_type(&rest[1..])?;
+            rest = new_rest;
+            interface_bounds.push(bound);
+        }
+        input = rest;
+        type_parameters.push(TypeParameter {
+            name,
+            class_bound,
+            interface_bounds,
+        });
+    }
+    if type_parameters.is_empty() {
+        return Err(ClassParseError::InvalidSignature);
+    }
+    Ok((&input[1..], type_parameters))
+}
+
+pub fn parse_class_signature(input: &[u8]) -> Result<(&[u8], ClassSignature), ClassParseError> {
+    let (input, type_parameters) = parse_type_parameters(input)?;
+    let (mut input, superclass) = parse_class_type(input)?;
+    let mut interfaces = vec![];
+    while input.first() == Some(&b'L') {
+        let (rest, interface) = parse_class_type(input)?;
+        input = rest;
+        interfaces.push(interface);
+    }
+    Ok((
+        input,
+        ClassSignature {
+            type_parameters,
+            superclass,
+            interfaces,
+        },
+    ))
+}
+
+pub fn parse_method_signature(input: &[u8]) -> Result<(&[u8], MethodSignature), ClassParseError> {
+    let (input, type_parameters) = parse_type_parameters(input)?;
+    let mut input = expect(input, b'(')?;
+    let mut parameters = vec![];
+    while input.first() != Some(&b')') {
+        let (rest, parameter) = parse_java_type(input)?;
+        input = rest;
+        parameters.push(parameter);
+    }
+    let input = expect(input, b')')?;
+    let (mut input, result) = match input.first() {
+        Some(b'V') => (&input[1..], TypeSignature::Base(FieldType::Void)),
+        _ => parse_java_type(input)?,
+    };
+    let mut throws = vec![];
+    while input.first() == Some(&b'^') {
+        let (rest, exception) = match input.get(1) {
+            Some(b'L' | b'T') => parse_reference_type(&input[1..])?,
+            _ => return Err(ClassParseError::InvalidSignature),
+        };
+        input = rest;
+        throws.push(exception);
+    }
+    Ok((
+        input,
+        MethodSignature {
+            type_parameters,
+            parameters,
+            result,
+            throws,
+        },
+    ))
+}
+
+pub fn parse_field_signature(input: &[u8]) -> Result<(&[u8], TypeSignature), ClassParseError> {
+    parse_reference_type(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(name: &[u8]) -> TypeSignature {
+        TypeSignature::Class(ClassTypeSignature {
+            name,
+            type_arguments: vec![],
+            inner: vec![],
+        })
+    }
+
+    fn generic<'a>(name: &'a [u8], type_arguments: Vec<TypeArgument<'a>>) -> TypeSignature<'a> {
+        TypeSignature::Class(ClassTypeSignature {
+            name,
+            type_arguments,
+            inner: vec![],
+        })
+    }
+
+    #[test]
+    fn test_parse_field_signature() {
+        let (rest, signature) =
+            parse_field_signature(b"Ljava/util/List<Ljava/lang/String;>;xxx").unwrap();
+        assert_eq!(rest, b"xxx");
+        assert_eq!(
+            signature,
+            generic(
+                b"java/util/List",
+                vec![TypeArgument::Exact(class(b"java/lang/String"))]
+            )
+        );
+
+        let (_, signature) = parse_field_signature(b"Ljava/util/Map<TK;+TV;>;").unwrap();
+        assert_eq!(
+            signature,
+            generic(
+                b"java/util/Map",
+                vec![
+                    TypeArgument::Exact(TypeSignature::TypeVariable(b"K")),
+                    TypeArgument::Extends(TypeSignature::TypeVariable(b"V")),
+                ]
+            )
+        );
+
+        let (_, signature) =
+            parse_field_signature(b"Ljava/util/List<-Ljava/lang/Integer;>;").unwrap();
+        assert_eq!(
+            signature,
+            generic(
+                b"java/util/List",
+                vec![TypeArgument::Super(class(b"java/lang/Integer"))]
+            )
+        );
+
+        let (_, signature) = parse_field_signature(b"Ljava/util/List<*>;").unwrap();
+        assert_eq!(
+            signature,
+            generic(b"java/util/List", vec![TypeArgument::Wildcard])
+        );
+
+        let (_, signature) = parse_field_signature(b"[[TV;").unwrap();
+        assert_eq!(
+            signature,
+            TypeSignature::Array(Box::new(TypeSignature::Array(Box::new(
+                TypeSignature::TypeVariable(b"V")
+            ))))
+        );
+
+        assert_eq!(
+            parse_field_signature(b"I"),
+            Err(ClassParseError::InvalidSignature)
+        );
+        assert_eq!(
+            parse_field_signature(b"Ljava/util/List<>;"),
+            Err(ClassParseError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_parse_inner_class_signature() {
+        let (rest, signature) = parse_field_signature(b"LOuter<TT;>.Inner<TU;>.Leaf;xxx").unwrap();
+        assert_eq!(rest, b"xxx");
+        assert_eq!(
+            signature,
+            TypeSignature::Class(ClassTypeSignature {
+                name: b"Outer",
+                type_arguments: vec![TypeArgument::Exact(TypeSignature::TypeVariable(b"T"))],
+                inner: vec![
+                    SimpleClassTypeSignature {
+                        name: b"Inner",
+                        type_arguments: vec![TypeArgument::Exact(TypeSignature::TypeVariable(
+                            b"U"
+                        ))],
+                    },
+                    SimpleClassTypeSignature {
+                        name: b"Leaf",
+                        type_arguments: vec![],
+                    },
+                ],
+            })
+        );
+
+        assert_eq!(
+            parse_field_signature(b"LOuter<TT;>.;"),
+            Err(ClassParseError::InvalidSignature)
+        );
+        assert_eq!(
+            parse_field_signature(b"LOuter.Inner/Leaf;"),
+            Err(ClassParseError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_parse_class_signature() {
+        let input = b"<K::Ljava/lang/Comparable<TK;>;V:Ljava/lang/Object;>Ljava/util/AbstractMap<TK;TV;>;Ljava/lang/Iterable<TK;>;";
+        let (rest, signature) = parse_class_signature(input).unwrap();
+        assert_eq!(rest, b"");
+        let variable = |name| TypeArgument::Exact(TypeSignature::TypeVariable(name));
+        assert_eq!(
+            signature,
+            ClassSignature {
+                type_parameters: vec![
+                    TypeParameter {
+                        name: b"K",
+                        class_bound: None,
+                        interface_bounds: vec![generic(
+                            b"java/lang/Comparable",
+                            vec![variable(b"K")]
+                        )],
+                    },
+                    TypeParameter {
+                        name: b"V",
+                        class_bound: Some(class(b"java/lang/Object")),
+                        interface_bounds: vec![],
+                    },
+                ],
+                superclass: ClassTypeSignature {
+                    name: b"java/util/AbstractMap",
+                    type_arguments: vec![variable(b"K"), variable(b"V")],
+                    inner: vec![],
+                },
+                interfaces: vec![ClassTypeSignature {
+                    name: b"java/lang/Iterable",
+                    type_arguments: vec![variable(b"K")],
+                    inner: vec![],
+                }],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_method_signature() {
+        let input = b"<T:Ljava/lang/Number;:Ljava/lang/Comparable<TT;>;>(Ljava/util/List<+TT;>;I)TT;^Ljava/io/IOException;^TE;";
+        let (rest, signature) = parse_method_signature(input).unwrap();
+        assert_eq!(rest, b"");
+        assert_eq!(
+            signature,
+            MethodSignature {
+                type_parameters: vec![TypeParameter {
+                    name: b"T",
+                    class_bound: Some(class(b"java/lang/Number")),
+                    interface_bounds: vec![generic(
+                        b"java/lang/Comparable",
+                        vec![TypeArgument::Exact(TypeSignature::TypeVariable(b"T"))]
+                    )],
+                }],
+                parameters: vec![
+                    generic(
+                        b"java/util/List",
+                        vec![TypeArgument::Extends(TypeSignature::TypeVariable(b"T"))]
+                    ),
+                    TypeSignature::Base(FieldType::Int),
+                ],
+                result: TypeSignature::TypeVariable(b"T"),
+                throws: vec![
+                    class(b"java/io/IOException"),
+                    TypeSignature::TypeVariable(b"E")
+                ],
+            }
+        );
+
+        let (_, signature) = parse_method_signature(b"()V").unwrap();
+        assert_eq!(signature.parameters, vec![]);
+        assert_eq!(signature.result, TypeSignature::Base(FieldType::Void));
+
+        assert_eq!(
+            parse_method_signature(b"(I"),
+            Err(ClassParseError::InvalidSignature)
+        );
+        assert_eq!(
+            parse_method_signature(b"()V^I"),
+            Err(ClassParseError::InvalidSignature)
+        );
+    }
+}
@@ -1,14 +1,28 @@
+use std::fmt;
 use std::string::FromUtf8Error;
 
+use super::constant::{ConstantTag, ReferenceKind};
+use super::modified_utf8::ModifiedUtf8Error;
 use crate::parser;
 
 #[derive(Debug, PartialEq)]
 pub enum ClassParseError {
+    // the input does not start with 0xCAFEBABE; holds the value found instead
+    NotAClassFile(u32),
     ParseError(parser::ParseError),
     Utf8Error(FromUtf8Error),
     InvalidConstantTag(u8),
     InvalidConstantPoolIndex(u16),
+    InvalidReferenceKind(u8),
     InvalidFieldDescriptor,
+    InvalidMethodDescriptor,
+    InvalidSignature,
+    InvalidStackMapFrameType(u8),
+    InvalidVerificationTypeTag(u8),
+    InvalidElementValueTag(u8),
+    InvalidTargetType(u8),
+    // bytes left over after parsing a known attribute
+    TrailingAttributeBytes(u32),
 }
 
 impl From<parser::ParseError> for ClassParseError {
@@ -22,3 +36,197 @@ impl From<FromUtf8Error> for ClassParseError {
         ClassParseError::Utf8Error(error)
     }
 }
+
+// a failed typed lookup on the constant pool
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConstantPoolError {
+    // index 0, past the end of the pool, or the slot after a Long or Double
+    InvalidIndex(u16),
+    UnexpectedConstant {
+        index: u16,
+        expected: ConstantTag,
+        found: ConstantTag,
+    },
+    InvalidUtf8 {
+        index: u16,
+        error: ModifiedUtf8Error,
+    },
+    // a NewInvokeSpecial handle to a method other than `<init>`, or another
+    // method handle to `<init>` or `<clinit>`
+    InvalidMethodHandleName {
+        index: u16,
+        reference_kind: ReferenceKind,
+    },
+}
+
+// an entry of the constant pool that fails `ConstantPool::validate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstantPoolViolation {
+    pub index: u16,
+    pub error: ConstantPoolError,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConstantPoolBuildError {
+    // more than 65534 slots
+    TooManyConstants,
+    // a Utf8 longer than 65535 bytes once encoded
+    Utf8TooLong(usize),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ClassWriteError {
+    ConstantPoolBuildError(ConstantPoolBuildError),
+    // a decoded attribute whose name isn't a Utf8 in the constant pool
+    MissingAttributeName(&'static str),
+    // an attribute name that isn't valid modified UTF-8
+    InvalidAttributeName(Vec<u8>),
+    // a table with more entries than its count can hold
+    TooManyEntries(usize),
+    // code or an attribute longer than its u32 length can hold
+    TooLong(usize),
+    // a frame whose offset_delta or number of locals doesn't fit its type
+    InvalidStackMapFrame,
+}
+
+impl From<ConstantPoolBuildError> for ClassWriteError {
+    fn from(error: ConstantPoolBuildError) -> Self {
+        ClassWriteError::ConstantPoolBuildError(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RoundTripError {
+    ClassParseError(ClassParseError),
+    ClassWriteError(ClassWriteError),
+    // the written class differs from the input from this byte on
+    Mismatch(usize),
+}
+
+impl From<ClassParseError> for RoundTripError {
+    fn from(error: ClassParseError) -> Self {
+        RoundTripError::ClassParseError(error)
+    }
+}
+
+impl From<ClassWriteError> for RoundTripError {
+    fn from(error: ClassWriteError) -> Self {
+        RoundTripError::ClassWriteError(error)
+    }
+}
+
+impl fmt::Display for ClassParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClassParseError::NotAClassFile(magic) => {
+                write!(f, "not a class file: the magic number is {magic:#010x}")
+            }
+            ClassParseError::ParseError(error) => write!(f, "{error}"),
+            ClassParseError::Utf8Error(error) => write!(f, "{error}"),
+            ClassParseError::InvalidConstantTag(tag) => write!(f, "invalid constant tag {tag}"),
+            ClassParseError::InvalidConstantPoolIndex(index) => {
+                write!(f, "invalid constant pool index #{index}")
+            }
+            ClassParseError::InvalidReferenceKind(kind) => {
+                write!(f, "invalid reference kind {kind}")
+            }
+            ClassParseError::InvalidFieldDescriptor => write!(f, "invalid field descriptor"),
+            ClassParseError::InvalidMethodDescriptor => write!(f, "invalid method descriptor"),
+            ClassParseError::InvalidSignature => write!(f, "invalid signature"),
+            ClassParseError::InvalidStackMapFrameType(frame_type) => {
+                write!(f, "invalid stack map frame type {frame_type}")
+            }
+            ClassParseError::InvalidVerificationTypeTag(tag) => {
+                write!(f, "invalid verification type tag {tag}")
+            }
+            ClassParseError::InvalidElementValueTag(tag) => {
+                write!(f, "invalid element value tag {:?}", *tag as char)
+            }
+            ClassParseError::InvalidTargetType(target_type) => {
+                write!(f, "invalid type annotation target type {target_type:#04x}")
+            }
+            ClassParseError::TrailingAttributeBytes(count) => {
+                write!(f, "{count} bytes left over after an attribute")
+            }
+        }
+    }
+}
+
+// tags by their names in the JVMS, like `Methodref`
+impl fmt::Display for ConstantPoolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConstantPoolError::InvalidIndex(index) => {
+                write!(f, "invalid constant pool index #{index}")
+            }
+            ConstantPoolError::UnexpectedConstant {
+                index,
+                expected,
+                found,
+            } => write!(f, "#{index} is a {found:?}, not a {expected:?}"),
+            ConstantPoolError::InvalidUtf8 { index, error } => write!(f, "#{index}: {error}"),
+            ConstantPoolError::InvalidMethodHandleName {
+                index,
+                reference_kind,
+            } => write!(
+                f,
+                "#{index}: invalid method name for a {reference_kind:?} method handle"
+            ),
+        }
+    }
+}
+
+// the error names the entry's index
+impl fmt::Display for ConstantPoolViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl fmt::Display for ConstantPoolBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConstantPoolBuildError::TooManyConstants => write!(f, "more than 65534 constants"),
+            ConstantPoolBuildError::Utf8TooLong(length) => {
+                write!(f, "a Utf8 constant of {length} bytes, over 65535")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ClassWriteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClassWriteError::ConstantPoolBuildError(error) => write!(f, "{error}"),
+            ClassWriteError::MissingAttributeName(name) => {
+                write!(f, "no Utf8 constant names the {name} attribute")
+            }
+            ClassWriteError::InvalidAttributeName(name) => write!(
+                f,
+                "attribute name {:?} isn't modified UTF-8",
+                String::from_utf8_lossy(name)
+            ),
+            ClassWriteError::TooManyEntries(count) => {
+                write!(f, "{count} entries, more than the table's count can hold")
+            }
+            ClassWriteError::TooLong(length) => {
+                write!(f, "{length} bytes, more than a u32 length can hold")
+            }
+            ClassWriteError::InvalidStackMapFrame => {
+                write!(f, "a stack map frame that doesn't fit its frame type")
+            }
+        }
+    }
+}
+
+impl fmt::Display for RoundTripError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoundTripError::ClassParseError(error) => write!(f, "{error}"),
+            RoundTripError::ClassWriteError(error) => write!(f, "{error}"),
+            RoundTripError::Mismatch(offset) => {
+                write!(f, "the written class differs from byte {offset} on")
+            }
+        }
+    }
+}
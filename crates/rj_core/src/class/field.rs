@@ -1,6 +1,8 @@
 use crate::parser::be_u16;
 
-use super::{constant::Constant, parse_attribute, Attribute, ClassParseError, FieldAccessFlags};
+use super::{
+    constant_pool::ConstantPool, parse_attribute, Attribute, ClassParseError, FieldAccessFlags,
+};
 
 #[derive(Debug, PartialEq)]
 pub struct Field<'a> {
@@ -10,10 +12,36 @@ pub struct Field<'a> {
     pub attributes: Vec<Attribute<'a>>,
 }
 
+impl<'a> Field<'a> {
+    pub fn into_owned(self) -> Field<'static> {
+        Field {
+            access_flags: self.access_flags,
+            name_index: self.name_index,
+            descriptor_index: self.descriptor_index,
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(Attribute::into_owned)
+                .collect(),
+        }
+    }
+}
+
 pub fn parse_field<'a>(
     input: &'a [u8],
-    constant_pool: &[Constant],
+    constant_pool: &ConstantPool<'a>,
 ) -> Result<(&'a [u8], Field<'a>), ClassParseError> {
+    parse_field_with(input, constant_pool, parse_attribute)
+}
+
+pub fn parse_field_with<'a, F>(
+    input: &'a [u8],
+    constant_pool: &ConstantPool<'a>,
+    parse_attribute: F,
+) -> Result<(&'a [u8], Field<'a>), ClassParseError>
+where
+    F: Fn(&'a [u8], &ConstantPool<'a>) -> Result<(&'a [u8], Attribute<'a>), ClassParseError>,
+{
     let (input, access_flags) = be_u16(input)?;
     let (input, name_index) = be_u16(input)?;
     let (input, descriptor_index) = be_u16(input)?;
@@ -42,6 +70,7 @@ pub fn parse_field<'a>(
 
 #[cfg(test)]
 mod tests {
+    use super::super::constant::Constant;
     use super::*;
 
     #[test]
@@ -56,15 +85,17 @@ mod tests {
             0x00, 0x01, 0x02, 0x03, // data
             0x12, 0x34, // rest
         ];
-        let constant_pool = vec![
-            Constant::Utf8 { value: b"name" },
+        let constant_pool = ConstantPool::from(vec![
             Constant::Utf8 {
-                value: b"descriptor",
+                value: b"name".into(),
             },
             Constant::Utf8 {
-                value: b"Unknown_Attribute_Name",
+                value: b"descriptor".into(),
             },
-        ];
+            Constant::Utf8 {
+                value: b"Unknown_Attribute_Name".into(),
+            },
+        ]);
         let (rest, field) = parse_field(&data, &constant_pool).unwrap();
         assert_eq!(rest, &[0x12, 0x34]);
         assert_eq!(
@@ -75,7 +106,8 @@ mod tests {
                 descriptor_index: 2,
                 attributes: vec![Attribute::Unknown {
                     attribute_name_index: 0x0003,
-                    data: &[0x00, 0x01, 0x02, 0x03]
+                    name: b"Unknown_Attribute_Name".into(),
+                    data: vec![0x00, 0x01, 0x02, 0x03].into()
                 }]
             }
         );
@@ -1,8 +1,8 @@
 use crate::parser::be_u16;
 
 use super::access_flags::MethodAccessFlags;
-use super::attribute::{parse_attribute, Attribute};
-use super::constant::Constant;
+use super::attribute::{parse_attribute, Attribute, Code};
+use super::constant_pool::ConstantPool;
 use super::error::ClassParseError;
 
 #[derive(Debug, PartialEq)]
@@ -13,10 +13,55 @@ pub struct Method<'a> {
     pub attributes: Vec<Attribute<'a>>,
 }
 
+impl<'a> Method<'a> {
+    pub fn into_owned(self) -> Method<'static> {
+        Method {
+            access_flags: self.access_flags,
+            name_index: self.name_index,
+            descriptor_index: self.descriptor_index,
+            attributes: self
+                .attributes
+                .into_iter()
+                .map(Attribute::into_owned)
+                .collect(),
+        }
+    }
+
+    pub fn has_code(&self) -> bool {
+        self.attributes.iter().any(|attribute| match attribute {
+            Attribute::Code(_) => true,
+            Attribute::Raw { name, .. } => **name == *b"Code",
+            _ => false,
+        })
+    }
+
+    // the decoded Code attribute; None for abstract and native methods, and
+    // for a Code attribute that was left raw
+    pub fn code(&self) -> Option<&Code<'a, Attribute<'a>>> {
+        self.attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Code(code) => Some(code),
+                _ => None,
+            })
+    }
+}
+
 pub fn parse_method<'a>(
     input: &'a [u8],
-    constant_pool: &[Constant],
+    constant_pool: &ConstantPool<'a>,
 ) -> Result<(&'a [u8], Method<'a>), ClassParseError> {
+    parse_method_with(input, constant_pool, parse_attribute)
+}
+
+pub fn parse_method_with<'a, F>(
+    input: &'a [u8],
+    constant_pool: &ConstantPool<'a>,
+    parse_attribute: F,
+) -> Result<(&'a [u8], Method<'a>), ClassParseError>
+where
+    F: Fn(&'a [u8], &ConstantPool<'a>) -> Result<(&'a [u8], Attribute<'a>), ClassParseError>,
+{
     let (input, access_flags) = be_u16(input)?;
     let (input, name_index) = be_u16(input)?;
     let (input, descriptor_index) = be_u16(input)?;
@@ -45,6 +90,7 @@ pub fn parse_method<'a>(
 
 #[cfg(test)]
 mod tests {
+    use super::super::constant::Constant;
     use super::*;
 
     #[test]
@@ -59,15 +105,17 @@ mod tests {
             0x00, 0x01, 0x02, 0x03, // data
             0x12, 0x34, // rest
         ];
-        let constant_pool = vec![
-            Constant::Utf8 { value: b"name" },
+        let constant_pool = ConstantPool::from(vec![
             Constant::Utf8 {
-                value: b"descriptor",
+                value: b"name".into(),
             },
             Constant::Utf8 {
-                value: b"Unknown_Attribute_Name",
+                value: b"descriptor".into(),
             },
-        ];
+            Constant::Utf8 {
+                value: b"Unknown_Attribute_Name".into(),
+            },
+        ]);
         let (rest, method) = parse_method(&data, &constant_pool).unwrap();
         assert_eq!(rest, &[0x12, 0x34]);
         assert_eq!(
@@ -78,7 +126,8 @@ mod tests {
                 descriptor_index: 2,
                 attributes: vec![Attribute::Unknown {
                     attribute_name_index: 0x0003,
-                    data: &[0x00, 0x01, 0x02, 0x03]
+                    name: b"Unknown_Attribute_Name".into(),
+                    data: vec![0x00, 0x01, 0x02, 0x03].into()
                 }]
             }
         );
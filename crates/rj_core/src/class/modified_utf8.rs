@@ -0,0 +1,151 @@
+// modified UTF-8, the string encoding of CONSTANT_Utf8
+// https://docs.oracle.com/javase/specs/jvms/se21/html/jvms-4.html#jvms-4.4.7
+
+use std::borrow::Cow;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModifiedUtf8Error {
+    valid_up_to: usize,
+}
+
+impl ModifiedUtf8Error {
+    // the offset of the first malformed byte
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+}
+
+impl fmt::Display for ModifiedUtf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid modified UTF-8 at byte {}", self.valid_up_to)
+    }
+}
+
+// NUL is encoded as 0xC0 0x80 and supplementary characters as a surrogate
+// pair of 3-byte sequences; unpaired surrogates, which Java strings may hold,
+// become U+FFFD
+pub fn decode_modified_utf8(bytes: &[u8]) -> Result<Cow<str>, ModifiedUtf8Error> {
+    // without NUL or 4-byte sequences, valid UTF-8 reads the same
+    if !bytes.iter().any(|&b| b == 0 || b >= 0xF0) {
+        if let Ok(value) = core::str::from_utf8(bytes) {
+            return Ok(Cow::Borrowed(value));
+        }
+    }
+
+    let continuation = |offset: usize| match bytes.get(offset) {
+        Some(&b) if b & 0xC0 == 0x80 => Some((b & 0x3F) as u16),
+        _ => None,
+    };
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let b = bytes[offset];
+        let malformed = ModifiedUtf8Error {
+            valid_up_to: offset,
+        };
+        let (unit, length) = match b {
+            0x01..=0x7F => (b as u16, 1),
+            0xC0..=0xDF => {
+                let b1 = continuation(offset + 1).ok_or(malformed)?;
+                (((b & 0x1F) as u16) << 6 | b1, 2)
+            }
+            0xE0..=0xEF => {
+                let b1 = continuation(offset + 1).ok_or(malformed)?;
+                let b2 = continuation(offset + 2).ok_or(malformed)?;
+                (((b & 0x0F) as u16) << 12 | b1 << 6 | b2, 3)
+            }
+            _ => return Err(malformed),
+        };
+        units.push(unit);
+        offset += length;
+    }
+    Ok(Cow::Owned(
+        char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect(),
+    ))
+}
+
+// the inverse of decode_modified_utf8
+pub fn encode_modified_utf8(value: &str) -> Cow<[u8]> {
+    if !value.chars().any(|c| c == '\0' || c > '\u{FFFF}') {
+        return Cow::Borrowed(value.as_bytes());
+    }
+    let mut bytes = Vec::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '\0' => bytes.extend_from_slice(&[0xC0, 0x80]),
+            c if c > '\u{FFFF}' => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    bytes.extend_from_slice(&[
+                        0xE0 | (*unit >> 12) as u8,
+                        0x80 | (*unit >> 6 & 0x3F) as u8,
+                        0x80 | (*unit & 0x3F) as u8,
+                    ]);
+                }
+            }
+            c => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+    Cow::Owned(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_modified_utf8() {
+        assert_eq!(
+            decode_modified_utf8(b"java/lang/Object"),
+            Ok(Cow::Borrowed("java/lang/Object"))
+        );
+        assert_eq!(
+            decode_modified_utf8("caf\u{e9}".as_bytes()),
+            Ok(Cow::Borrowed("caf\u{e9}"))
+        );
+        assert_eq!(
+            decode_modified_utf8(b"a\xC0\x80b").unwrap(),
+            "a\0b".to_string()
+        );
+        // U+1F600 as the surrogate pair D83D DE00
+        assert_eq!(
+            decode_modified_utf8(b"\xED\xA0\xBD\xED\xB8\x80").unwrap(),
+            "\u{1F600}".to_string()
+        );
+        assert_eq!(
+            decode_modified_utf8(b"\xED\xA0\xBDx").unwrap(),
+            "\u{FFFD}x".to_string()
+        );
+    }
+
+    #[test]
+    fn test_encode_modified_utf8() {
+        assert_eq!(
+            encode_modified_utf8("caf\u{e9}"),
+            Cow::Borrowed("caf\u{e9}".as_bytes())
+        );
+        let encoded = encode_modified_utf8("a\0\u{1F600}");
+        assert_eq!(&*encoded, b"a\xC0\x80\xED\xA0\xBD\xED\xB8\x80");
+        assert_eq!(decode_modified_utf8(&encoded).unwrap(), "a\0\u{1F600}");
+    }
+
+    #[test]
+    fn test_decode_modified_utf8_invalid() {
+        assert_eq!(decode_modified_utf8(b"ab\0").unwrap_err().valid_up_to(), 2);
+        assert_eq!(
+            decode_modified_utf8("\u{1F600}".as_bytes())
+                .unwrap_err()
+                .valid_up_to(),
+            0
+        );
+        assert_eq!(
+            decode_modified_utf8(b"a\xE0\x80")
+                .unwrap_err()
+                .valid_up_to(),
+            1
+        );
+        assert_eq!(decode_modified_utf8(b"\x80").unwrap_err().valid_up_to(), 0);
+    }
+}
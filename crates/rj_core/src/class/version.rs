@@ -0,0 +1,122 @@
+use super::attribute::AttributeName;
+use super::classfile::ClassFile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClassVersion {
+    pub major: u16,
+    pub minor: u16,
+}
+
+impl ClassVersion {
+    pub const PREVIEW_MINOR_VERSION: u16 = 0xFFFF;
+    // preview features were introduced in Java 12
+    pub const FIRST_PREVIEW_MAJOR_VERSION: u16 = 56;
+
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+
+    pub fn is_preview(&self) -> bool {
+        self.major >= Self::FIRST_PREVIEW_MAJOR_VERSION && self.minor == Self::PREVIEW_MINOR_VERSION
+    }
+
+    // the class file version javac emits for `--release`, e.g. 52.0 for 8
+    pub const fn for_release(release: u16) -> Self {
+        Self::new(release + 44, 0)
+    }
+
+    // the Java release that introduced this version, e.g. 21 for 65.x; JDK
+    // 1.0.2 and 1.1 share major version 45
+    pub fn java_release(&self) -> u16 {
+        self.major.saturating_sub(44).max(1)
+    }
+
+    pub fn supports_stack_map_table(&self) -> bool {
+        self.major >= 50
+    }
+
+    // invokedynamic and the MethodHandle, MethodType and InvokeDynamic
+    // constants
+    pub fn supports_invokedynamic(&self) -> bool {
+        self.major >= 51
+    }
+
+    pub fn supports_modules(&self) -> bool {
+        self.major >= 53
+    }
+
+    // the Dynamic constant and the NestHost and NestMembers attributes
+    pub fn supports_dynamic_constants(&self) -> bool {
+        self.major >= 55
+    }
+
+    pub fn supports_records(&self) -> bool {
+        self.major >= 60
+    }
+
+    pub fn supports_sealed_classes(&self) -> bool {
+        self.major >= 61
+    }
+
+    // whether the attribute is defined for this version rather than being
+    // treated as unknown
+    pub fn allows(&self, attribute_name: AttributeName) -> bool {
+        *self >= attribute_name.first_version()
+    }
+}
+
+impl<'a> ClassFile<'a> {
+    pub fn version(&self) -> ClassVersion {
+        ClassVersion::new(self.major_version, self.minor_version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::parse_classfile;
+
+    #[test]
+    fn test_is_preview() {
+        assert!(ClassVersion::new(65, 0xFFFF).is_preview());
+        assert!(!ClassVersion::new(65, 0).is_preview());
+        assert!(!ClassVersion::new(52, 0xFFFF).is_preview());
+    }
+
+    #[test]
+    fn test_releases() {
+        assert_eq!(ClassVersion::for_release(8), ClassVersion::new(52, 0));
+        assert_eq!(ClassVersion::new(65, 0).java_release(), 21);
+        assert_eq!(ClassVersion::new(65, 0xFFFF).java_release(), 21);
+        assert_eq!(ClassVersion::new(45, 3).java_release(), 1);
+        assert_eq!(ClassVersion::for_release(17).java_release(), 17);
+
+        let java8 = ClassVersion::for_release(8);
+        assert!(java8.supports_invokedynamic());
+        assert!(!java8.supports_modules());
+        assert!(!java8.supports_records());
+        assert!(ClassVersion::for_release(16).supports_records());
+        assert!(!ClassVersion::for_release(16).supports_sealed_classes());
+        assert!(!ClassVersion::for_release(6).supports_invokedynamic());
+    }
+
+    #[test]
+    fn test_allows() {
+        let java8 = ClassVersion::for_release(8);
+        assert!(java8.allows(AttributeName::Code));
+        assert!(java8.allows(AttributeName::RuntimeVisibleTypeAnnotations));
+        assert!(!java8.allows(AttributeName::Record));
+        assert!(!ClassVersion::new(45, 0).allows(AttributeName::Code));
+        assert!(ClassVersion::new(61, 0).allows(AttributeName::PermittedSubclasses));
+    }
+
+    #[test]
+    fn test_preview_classfile() {
+        let mut data = include_bytes!("../../../../java/HelloWorld.class").to_vec();
+        data[4] = 0xFF;
+        data[5] = 0xFF;
+        let (_, classfile) = parse_classfile(&data).unwrap();
+        assert_eq!(classfile.version(), ClassVersion::new(65, 0xFFFF));
+        assert!(classfile.version().is_preview());
+    }
+}
@@ -0,0 +1,981 @@
+// serializes a parsed class file back to bytes, keeping every constant pool
+// index as it is, so parsing the output gives an equal ClassFile. single
+// attributes can also be written against a ConstantPoolBuilder
+
+use super::attribute::{
+    Annotation, AnnotationDefault, Attribute, AttributeName, BootstrapMethods, Code, ConstantValue,
+    ElementValue, EnclosingMethod, InnerClasses, LineNumberTable, LocalVariableTable,
+    LocalVariableTypeTable, LocalvarTarget, PermittedSubclasses, Record,
+    RuntimeInvisibleAnnotations, RuntimeInvisibleParameterAnnotations,
+    RuntimeInvisibleTypeAnnotations, RuntimeVisibleAnnotations, RuntimeVisibleParameterAnnotations,
+    RuntimeVisibleTypeAnnotations, Signature, SourceFile, StackMapFrame, StackMapTable, TargetInfo,
+    TypeAnnotation, VerificationTypeInfo,
+};
+use super::classfile::{parse_classfile, parse_classfile_lazy, ClassFile};
+use super::constant::Constant;
+use super::constant_pool::ConstantPool;
+use super::constant_pool_builder::ConstantPoolBuilder;
+use super::error::{ClassWriteError, RoundTripError};
+use super::field::Field;
+use super::method::Method;
+use super::modified_utf8::decode_modified_utf8;
+
+fn write_u8(out: &mut Vec<u8>, value: u8) {
+    out.push(value);
+}
+
+fn write_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+fn write_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+// the count of a table whose length is stored in a u16
+fn write_count(out: &mut Vec<u8>, len: usize) -> Result<(), ClassWriteError> {
+    let count = u16::try_from(len).map_err(|_| ClassWriteError::TooManyEntries(len))?;
+    write_u16(out, count);
+    Ok(())
+}
+
+// the count of a table whose length is stored in a single byte
+fn write_short_count(out: &mut Vec<u8>, len: usize) -> Result<(), ClassWriteError> {
+    let count = u8::try_from(len).map_err(|_| ClassWriteError::TooManyEntries(len))?;
+    write_u8(out, count);
+    Ok(())
+}
+
+fn write_constant(out: &mut Vec<u8>, constant: &Constant) -> Result<(), ClassWriteError> {
+    write_u8(out, constant.tag() as u8);
+    match constant {
+        Constant::Utf8 { value } => {
+            write_count(out, value.len())?;
+            out.extend_from_slice(value);
+        }
+        Constant::Integer { value } => out.extend_from_slice(&value.to_be_bytes()),
+        Constant::Float { value } => write_u32(out, value.to_bits()),
+        Constant::Long { value } => out.extend_from_slice(&value.to_be_bytes()),
+        Constant::Double { value } => out.extend_from_slice(&value.to_bits().to_be_bytes()),
+        Constant::Class { name_index: index }
+        | Constant::String {
+            string_index: index,
+        }
+        | Constant::MethodType {
+            descriptor_index: index,
+        }
+        | Constant::Module { name_index: index }
+        | Constant::Package { name_index: index } => write_u16(out, *index),
+        Constant::Fieldref {
+            class_index: first,
+            name_and_type_index: second,
+        }
+        | Constant::Methodref {
+            class_index: first,
+            name_and_type_index: second,
+        }
+        | Constant::InterfaceMethodref {
+            class_index: first,
+            name_and_type_index: second,
+        }
+        | Constant::NameAndType {
+            name_index: first,
+            descriptor_index: second,
+        }
+        | Constant::Dynamic {
+            bootstrap_method_attr_index: first,
+            name_and_type_index: second,
+        }
+        | Constant::InvokeDynamic {
+            bootstrap_method_attr_index: first,
+            name_and_type_index: second,
+        } => {
+            write_u16(out, *first);
+            write_u16(out, *second);
+        }
+        Constant::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => {
+            write_u8(out, *reference_kind as u8);
+            write_u16(out, *reference_index);
+        }
+    }
+    Ok(())
+}
+
+impl<'a> ConstantPool<'a> {
+    // constant_pool_count followed by the entries, as in a class file
+    pub fn write(&self, out: &mut Vec<u8>) -> Result<(), ClassWriteError> {
+        let count = self.len() + 1;
+        write_count(out, count)?;
+        for (_, constant) in self.iter() {
+            write_constant(out, constant)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_element_value(out: &mut Vec<u8>, value: &ElementValue) -> Result<(), ClassWriteError> {
+    match value {
+        ElementValue::Const {
+            tag,
+            const_value_index,
+        } => {
+            write_u8(out, *tag);
+            write_u16(out, *const_value_index);
+        }
+        ElementValue::Enum {
+            type_name_index,
+            const_name_index,
+        } => {
+            write_u8(out, b'e');
+            write_u16(out, *type_name_index);
+            write_u16(out, *const_name_index);
+        }
+        ElementValue::Class { class_info_index } => {
+            write_u8(out, b'c');
+            write_u16(out, *class_info_index);
+        }
+        ElementValue::Annotation(annotation) => {
+            write_u8(out, b'@');
+            write_annotation(out, annotation)?;
+        }
+        ElementValue::Array(values) => {
+            write_u8(out, b'[');
+            write_count(out, values.len())?;
+            for value in values {
+                write_element_value(out, value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_annotation(out: &mut Vec<u8>, annotation: &Annotation) -> Result<(), ClassWriteError> {
+    write_u16(out, annotation.type_index);
+    write_count(out, annotation.element_value_pairs.len())?;
+    for pair in &annotation.element_value_pairs {
+        write_u16(out, pair.element_name_index);
+        write_element_value(out, &pair.value)?;
+    }
+    Ok(())
+}
+
+fn write_annotations(out: &mut Vec<u8>, annotations: &[Annotation]) -> Result<(), ClassWriteError> {
+    write_count(out, annotations.len())?;
+    for annotation in annotations {
+        write_annotation(out, annotation)?;
+    }
+    Ok(())
+}
+
+fn write_parameter_annotations(
+    out: &mut Vec<u8>,
+    parameter_annotations: &[Vec<Annotation>],
+) -> Result<(), ClassWriteError> {
+    write_short_count(out, parameter_annotations.len())?;
+    for annotations in parameter_annotations {
+        write_annotations(out, annotations)?;
+    }
+    Ok(())
+}
+
+fn write_type_annotations(
+    out: &mut Vec<u8>,
+    annotations: &[TypeAnnotation],
+) -> Result<(), ClassWriteError> {
+    write_count(out, annotations.len())?;
+    for annotation in annotations {
+        write_u8(out, annotation.target_type);
+        match &annotation.target_info {
+            TargetInfo::TypeParameter {
+                type_parameter_index,
+            } => write_u8(out, *type_parameter_index),
+            TargetInfo::Supertype { supertype_index } => write_u16(out, *supertype_index),
+            TargetInfo::TypeParameterBound {
+                type_parameter_index,
+                bound_index,
+            } => {
+                write_u8(out, *type_parameter_index);
+                write_u8(out, *bound_index);
+            }
+            TargetInfo::Empty => {}
+            TargetInfo::FormalParameter {
+                formal_parameter_index,
+            } => write_u8(out, *formal_parameter_index),
+            TargetInfo::Throws { throws_type_index } => write_u16(out, *throws_type_index),
+            TargetInfo::Localvar(table) => {
+                write_count(out, table.len())?;
+                for LocalvarTarget {
+                    start_pc,
+                    length,
+                    index,
+                } in table
+                {
+                    write_u16(out, *start_pc);
+                    write_u16(out, *length);
+                    write_u16(out, *index);
+                }
+            }
+            TargetInfo::Catch {
+                exception_table_index,
+            } => write_u16(out, *exception_table_index),
+            TargetInfo::Offset { offset } => write_u16(out, *offset),
+            TargetInfo::TypeArgument {
+                offset,
+                type_argument_index,
+            } => {
+                write_u16(out, *offset);
+                write_u8(out, *type_argument_index);
+            }
+        }
+        write_short_count(out, annotation.target_path.len())?;
+        for entry in &annotation.target_path {
+            write_u8(out, entry.type_path_kind);
+            write_u8(out, entry.type_argument_index);
+        }
+        write_annotation(out, &annotation.annotation)?;
+    }
+    Ok(())
+}
+
+fn write_verification_type(out: &mut Vec<u8>, verification_type: &VerificationTypeInfo) {
+    match verification_type {
+        VerificationTypeInfo::Top => write_u8(out, 0),
+        VerificationTypeInfo::Integer => write_u8(out, 1),
+        VerificationTypeInfo::Float => write_u8(out, 2),
+        VerificationTypeInfo::Double => write_u8(out, 3),
+        VerificationTypeInfo::Long => write_u8(out, 4),
+        VerificationTypeInfo::Null => write_u8(out, 5),
+        VerificationTypeInfo::UninitializedThis => write_u8(out, 6),
+        VerificationTypeInfo::Object { cpool_index } => {
+            write_u8(out, 7);
+            write_u16(out, *cpool_index);
+        }
+        VerificationTypeInfo::Uninitialized { offset } => {
+            write_u8(out, 8);
+            write_u16(out, *offset);
+        }
+    }
+}
+
+fn write_verification_types(
+    out: &mut Vec<u8>,
+    verification_types: &[VerificationTypeInfo],
+) -> Result<(), ClassWriteError> {
+    write_count(out, verification_types.len())?;
+    for verification_type in verification_types {
+        write_verification_type(out, verification_type);
+    }
+    Ok(())
+}
+
+// the frame types are checked when parsing; a frame built by hand with a
+// delta or count out of its type's range is reported as invalid
+fn write_stack_map_frame(out: &mut Vec<u8>, frame: &StackMapFrame) -> Result<(), ClassWriteError> {
+    match frame {
+        StackMapFrame::Same { offset_delta } if *offset_delta < 64 => write_u8(out, *offset_delta),
+        StackMapFrame::SameLocals1StackItem {
+            offset_delta,
+            stack,
+        } if *offset_delta < 64 => {
+            write_u8(out, 64 + offset_delta);
+            write_verification_type(out, stack);
+        }
+        StackMapFrame::SameLocals1StackItemExtended {
+            offset_delta,
+            stack,
+        } => {
+            write_u8(out, 247);
+            write_u16(out, *offset_delta);
+            write_verification_type(out, stack);
+        }
+        StackMapFrame::Chop { k, offset_delta } if (1..=3).contains(k) => {
+            write_u8(out, 251 - k);
+            write_u16(out, *offset_delta);
+        }
+        StackMapFrame::SameExtended { offset_delta } => {
+            write_u8(out, 251);
+            write_u16(out, *offset_delta);
+        }
+        StackMapFrame::Append {
+            offset_delta,
+            locals,
+        } if (1..=3).contains(&locals.len()) => {
+            write_u8(out, 251 + locals.len() as u8);
+            write_u16(out, *offset_delta);
+            for local in locals {
+                write_verification_type(out, local);
+            }
+        }
+        StackMapFrame::Full {
+            offset_delta,
+            locals,
+            stack,
+        } => {
+            write_u8(out, 255);
+            write_u16(out, *offset_delta);
+            write_verification_types(out, locals)?;
+            write_verification_types(out, stack)?;
+        }
+        _ => return Err(ClassWriteError::InvalidStackMapFrame),
+    }
+    Ok(())
+}
+
+// where the indices of attribute names come from: the pool of a parsed
+// class, where they're looked up, or a pool being built, where they're added
+trait AttributeNames {
+    fn known(&mut self, name: AttributeName) -> Result<u16, ClassWriteError>;
+
+    // an attribute kept by name, like Attribute::Unknown
+    fn other(&mut self, attribute_name_index: u16, name: &[u8]) -> Result<u16, ClassWriteError>;
+}
+
+// decoded attributes don't keep the index of their name, so it's looked up
+impl<'a> AttributeNames for &ConstantPool<'a> {
+    fn known(&mut self, name: AttributeName) -> Result<u16, ClassWriteError> {
+        self.find_utf8(name.as_str())
+            .ok_or(ClassWriteError::MissingAttributeName(name.as_str()))
+    }
+
+    fn other(&mut self, attribute_name_index: u16, _: &[u8]) -> Result<u16, ClassWriteError> {
+        Ok(attribute_name_index)
+    }
+}
+
+impl AttributeNames for ConstantPoolBuilder {
+    fn known(&mut self, name: AttributeName) -> Result<u16, ClassWriteError> {
+        Ok(self.utf8(name.as_str())?)
+    }
+
+    fn other(&mut self, _: u16, name: &[u8]) -> Result<u16, ClassWriteError> {
+        let name = decode_modified_utf8(name)
+            .map_err(|_| ClassWriteError::InvalidAttributeName(name.to_vec()))?;
+        Ok(self.utf8(&name)?)
+    }
+}
+
+// an attribute type with a fixed name
+trait AttributeData {
+    const NAME: AttributeName;
+
+    // the payload, without the name and length
+    fn write_data<N: AttributeNames>(
+        &self,
+        out: &mut Vec<u8>,
+        names: &mut N,
+    ) -> Result<(), ClassWriteError>;
+}
+
+// attribute_name_index, attribute_length, then the payload
+fn write_attribute_with<N, F>(
+    out: &mut Vec<u8>,
+    name_index: u16,
+    names: &mut N,
+    write_data: F,
+) -> Result<(), ClassWriteError>
+where
+    F: FnOnce(&mut Vec<u8>, &mut N) -> Result<(), ClassWriteError>,
+{
+    write_u16(out, name_index);
+    let mut data = Vec::new();
+    write_data(&mut data, names)?;
+    let length = u32::try_from(data.len()).map_err(|_| ClassWriteError::TooLong(data.len()))?;
+    write_u32(out, length);
+    out.extend_from_slice(&data);
+    Ok(())
+}
+
+fn write_known<T, N>(out: &mut Vec<u8>, attribute: &T, names: &mut N) -> Result<(), ClassWriteError>
+where
+    T: AttributeData,
+    N: AttributeNames,
+{
+    let name_index = names.known(T::NAME)?;
+    write_attribute_with(out, name_index, names, |out, names| {
+        attribute.write_data(out, names)
+    })
+}
+
+impl<'a> AttributeData for Code<'a, Attribute<'a>> {
+    const NAME: AttributeName = AttributeName::Code;
+
+    fn write_data<N: AttributeNames>(
+        &self,
+        out: &mut Vec<u8>,
+        names: &mut N,
+    ) -> Result<(), ClassWriteError> {
+        write_u16(out, self.max_stack);
+        write_u16(out, self.max_locals);
+        let code_length = u32::try_from(self.code.len())
+            .map_err(|_| ClassWriteError::TooLong(self.code.len()))?;
+        write_u32(out, code_length);
+        out.extend_from_slice(&self.code);
+        write_count(out, self.exception_table.len())?;
+        for entry in &self.exception_table {
+            write_u16(out, entry.start_pc);
+            write_u16(out, entry.end_pc);
+            write_u16(out, entry.handler_pc);
+            write_u16(out, entry.catch_type);
+        }
+        write_attributes(out, &self.attributes, names)
+    }
+}
+
+impl<'a> AttributeData for Record<Attribute<'a>> {
+    const NAME: AttributeName = AttributeName::Record;
+
+    fn write_data<N: AttributeNames>(
+        &self,
+        out: &mut Vec<u8>,
+        names: &mut N,
+    ) -> Result<(), ClassWriteError> {
+        write_count(out, self.components.len())?;
+        for component in &self.components {
+            write_u16(out, component.name_index);
+            write_u16(out, component.descriptor_index);
+            write_attributes(out, &component.attributes, names)?;
+        }
+        Ok(())
+    }
+}
+
+// the attributes whose payload holds no nested attributes
+macro_rules! attribute_data {
+    ($attribute: ident, |$self: ident, $out: ident| $body: block) => {
+        impl AttributeData for $attribute {
+            const NAME: AttributeName = AttributeName::$attribute;
+
+            fn write_data<N: AttributeNames>(
+                &$self,
+                $out: &mut Vec<u8>,
+                _: &mut N,
+            ) -> Result<(), ClassWriteError> {
+                $body
+                Ok(())
+            }
+        }
+    };
+}
+
+attribute_data!(AnnotationDefault, |self, out| {
+    write_element_value(out, &self.default_value)?;
+});
+
+attribute_data!(BootstrapMethods, |self, out| {
+    write_count(out, self.bootstrap_methods.len())?;
+    for method in &self.bootstrap_methods {
+        write_u16(out, method.bootstrap_method_ref);
+        write_count(out, method.bootstrap_arguments.len())?;
+        for argument in &method.bootstrap_arguments {
+            write_u16(out, *argument);
+        }
+    }
+});
+
+attribute_data!(ConstantValue, |self, out| {
+    write_u16(out, self.constantvalue_index);
+});
+
+attribute_data!(EnclosingMethod, |self, out| {
+    write_u16(out, self.class_index);
+    write_u16(out, self.method_index);
+});
+
+attribute_data!(InnerClasses, |self, out| {
+    write_count(out, self.classes.len())?;
+    for class in &self.classes {
+        write_u16(out, class.inner_class_info_index);
+        write_u16(out, class.outer_class_info_index);
+        write_u16(out, class.inner_name_index);
+        write_u16(out, class.inner_class_access_flags.bits());
+    }
+});
+
+attribute_data!(LineNumberTable, |self, out| {
+    write_count(out, self.line_number_table.len())?;
+    for entry in &self.line_number_table {
+        write_u16(out, entry.start_pc);
+        write_u16(out, entry.line_number);
+    }
+});
+
+attribute_data!(LocalVariableTable, |self, out| {
+    write_count(out, self.local_variable_table.len())?;
+    for entry in &self.local_variable_table {
+        write_u16(out, entry.start_pc);
+        write_u16(out, entry.length);
+        write_u16(out, entry.name_index);
+        write_u16(out, entry.descriptor_index);
+        write_u16(out, entry.index);
+    }
+});
+
+attribute_data!(LocalVariableTypeTable, |self, out| {
+    write_count(out, self.local_variable_type_table.len())?;
+    for entry in &self.local_variable_type_table {
+        write_u16(out, entry.start_pc);
+        write_u16(out, entry.length);
+        write_u16(out, entry.name_index);
+        write_u16(out, entry.signature_index);
+        write_u16(out, entry.index);
+    }
+});
+
+attribute_data!(PermittedSubclasses, |self, out| {
+    write_count(out, self.classes.len())?;
+    for class in &self.classes {
+        write_u16(out, *class);
+    }
+});
+
+attribute_data!(RuntimeInvisibleAnnotations, |self, out| {
+    write_annotations(out, &self.annotations)?;
+});
+
+attribute_data!(RuntimeVisibleAnnotations, |self, out| {
+    write_annotations(out, &self.annotations)?;
+});
+
+attribute_data!(RuntimeInvisibleParameterAnnotations, |self, out| {
+    write_parameter_annotations(out, &self.parameter_annotations)?;
+});
+
+attribute_data!(RuntimeVisibleParameterAnnotations, |self, out| {
+    write_parameter_annotations(out, &self.parameter_annotations)?;
+});
+
+attribute_data!(RuntimeInvisibleTypeAnnotations, |self, out| {
+    write_type_annotations(out, &self.annotations)?;
+});
+
+attribute_data!(RuntimeVisibleTypeAnnotations, |self, out| {
+    write_type_annotations(out, &self.annotations)?;
+});
+
+attribute_data!(Signature, |self, out| {
+    write_u16(out, self.signature_index);
+});
+
+attribute_data!(SourceFile, |self, out| {
+    write_u16(out, self.sourcefile_index);
+});
+
+attribute_data!(StackMapTable, |self, out| {
+    write_count(out, self.entries.len())?;
+    for frame in &self.entries {
+        write_stack_map_frame(out, frame)?;
+    }
+});
+
+fn write_attribute<N: AttributeNames>(
+    out: &mut Vec<u8>,
+    attribute: &Attribute,
+    names: &mut N,
+) -> Result<(), ClassWriteError> {
+    match attribute {
+        // copied as they are, under their own name. a Custom attribute's
+        // value isn't written, so changes to it are lost
+        Attribute::Unknown {
+            attribute_name_index,
+            name,
+            data,
+        }
+        | Attribute::Raw {
+            attribute_name_index,
+            name,
+            data,
+        }
+        | Attribute::Custom {
+            attribute_name_index,
+            name,
+            data,
+            ..
+        } => {
+            let name_index = names.other(*attribute_name_index, name)?;
+            write_attribute_with(out, name_index, names, |out, _| {
+                out.extend_from_slice(data);
+                Ok(())
+            })
+        }
+        Attribute::AnnotationDefault(attribute) => write_known(out, attribute, names),
+        Attribute::BootstrapMethods(attribute) => write_known(out, attribute, names),
+        Attribute::Code(attribute) => write_known(out, attribute, names),
+        Attribute::ConstantValue(attribute) => write_known(out, attribute, names),
+        Attribute::EnclosingMethod(attribute) => write_known(out, attribute, names),
+        Attribute::InnerClasses(attribute) => write_known(out, attribute, names),
+        Attribute::LineNumberTable(attribute) => write_known(out, attribute, names),
+        Attribute::LocalVariableTable(attribute) => write_known(out, attribute, names),
+        Attribute::LocalVariableTypeTable(attribute) => write_known(out, attribute, names),
+        Attribute::PermittedSubclasses(attribute) => write_known(out, attribute, names),
+        Attribute::Record(attribute) => write_known(out, attribute, names),
+        Attribute::RuntimeInvisibleAnnotations(attribute) => write_known(out, attribute, names),
+        Attribute::RuntimeInvisibleParameterAnnotations(attribute) => {
+            write_known(out, attribute, names)
+        }
+        Attribute::RuntimeInvisibleTypeAnnotations(attribute) => write_known(out, attribute, names),
+        Attribute::RuntimeVisibleAnnotations(attribute) => write_known(out, attribute, names),
+        Attribute::RuntimeVisibleParameterAnnotations(attribute) => {
+            write_known(out, attribute, names)
+        }
+        Attribute::RuntimeVisibleTypeAnnotations(attribute) => write_known(out, attribute, names),
+        Attribute::Signature(attribute) => write_known(out, attribute, names),
+        Attribute::SourceFile(attribute) => write_known(out, attribute, names),
+        Attribute::StackMapTable(attribute) => write_known(out, attribute, names),
+    }
+}
+
+fn write_attributes<N: AttributeNames>(
+    out: &mut Vec<u8>,
+    attributes: &[Attribute],
+    names: &mut N,
+) -> Result<(), ClassWriteError> {
+    write_count(out, attributes.len())?;
+    for attribute in attributes {
+        write_attribute(out, attribute, names)?;
+    }
+    Ok(())
+}
+
+impl<'a> Attribute<'a> {
+    // the whole attribute, its name and those of any nested attributes added
+    // to `pool`. other indices are written as they are, so they must already
+    // refer to `pool`. unknown attributes are copied verbatim
+    pub fn write(
+        &self,
+        pool: &mut ConstantPoolBuilder,
+        out: &mut Vec<u8>,
+    ) -> Result<(), ClassWriteError> {
+        write_attribute(out, self, pool)
+    }
+}
+
+impl<'a> Code<'a, Attribute<'a>> {
+    pub fn write(
+        &self,
+        pool: &mut ConstantPoolBuilder,
+        out: &mut Vec<u8>,
+    ) -> Result<(), ClassWriteError> {
+        write_known(out, self, pool)
+    }
+}
+
+impl<'a> Record<Attribute<'a>> {
+    pub fn write(
+        &self,
+        pool: &mut ConstantPoolBuilder,
+        out: &mut Vec<u8>,
+    ) -> Result<(), ClassWriteError> {
+        write_known(out, self, pool)
+    }
+}
+
+// `write` for the attributes without nested ones, as on Attribute
+macro_rules! impl_write {
+    ($($attribute: ident),*) => {
+        $(
+            impl $attribute {
+                pub fn write(
+                    &self,
+                    pool: &mut ConstantPoolBuilder,
+                    out: &mut Vec<u8>,
+                ) -> Result<(), ClassWriteError> {
+                    write_known(out, self, pool)
+                }
+            }
+        )*
+    };
+}
+
+impl_write!(
+    AnnotationDefault,
+    BootstrapMethods,
+    ConstantValue,
+    EnclosingMethod,
+    InnerClasses,
+    LineNumberTable,
+    LocalVariableTable,
+    LocalVariableTypeTable,
+    PermittedSubclasses,
+    RuntimeInvisibleAnnotations,
+    RuntimeInvisibleParameterAnnotations,
+    RuntimeInvisibleTypeAnnotations,
+    RuntimeVisibleAnnotations,
+    RuntimeVisibleParameterAnnotations,
+    RuntimeVisibleTypeAnnotations,
+    Signature,
+    SourceFile,
+    StackMapTable
+);
+
+fn write_field(
+    out: &mut Vec<u8>,
+    field: &Field,
+    mut constant_pool: &ConstantPool,
+) -> Result<(), ClassWriteError> {
+    write_u16(out, field.access_flags.bits());
+    write_u16(out, field.name_index);
+    write_u16(out, field.descriptor_index);
+    write_attributes(out, &field.attributes, &mut constant_pool)
+}
+
+fn write_method(
+    out: &mut Vec<u8>,
+    method: &Method,
+    mut constant_pool: &ConstantPool,
+) -> Result<(), ClassWriteError> {
+    write_u16(out, method.access_flags.bits());
+    write_u16(out, method.name_index);
+    write_u16(out, method.descriptor_index);
+    write_attributes(out, &method.attributes, &mut constant_pool)
+}
+
+impl<'a> ClassFile<'a> {
+    // the class file this structure describes; parsing the bytes gives back
+    // an equal ClassFile. for a class that was parsed and left alone these
+    // are the bytes it was parsed from, unless its pool holds the name of a
+    // decoded attribute twice and the attribute used the later copy; lazy
+    // parsing keeps those indices too
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ClassWriteError> {
+        let mut out = Vec::new();
+        write_u32(&mut out, self.magic);
+        write_u16(&mut out, self.minor_version);
+        write_u16(&mut out, self.major_version);
+        self.constant_pool.write(&mut out)?;
+        write_u16(&mut out, self.access_flags.bits());
+        write_u16(&mut out, self.this_class);
+        write_u16(&mut out, self.super_class);
+        write_count(&mut out, self.interfaces.len())?;
+        for interface in &self.interfaces {
+            write_u16(&mut out, *interface);
+        }
+        write_count(&mut out, self.fields.len())?;
+        for field in &self.fields {
+            write_field(&mut out, field, &self.constant_pool)?;
+        }
+        write_count(&mut out, self.methods.len())?;
+        for method in &self.methods {
+            write_method(&mut out, method, &self.constant_pool)?;
+        }
+        write_attributes(&mut out, &self.attributes, &mut &self.constant_pool)?;
+        Ok(out)
+    }
+}
+
+fn compare(expected: &[u8], written: &[u8]) -> Result<(), RoundTripError> {
+    if expected == written {
+        return Ok(());
+    }
+    let offset = expected
+        .iter()
+        .zip(written)
+        .position(|(a, b)| a != b)
+        .unwrap_or(expected.len().min(written.len()));
+    Err(RoundTripError::Mismatch(offset))
+}
+
+// parses `data` eagerly and lazily and writes both back, checking that the
+// output matches the input byte for byte. matching output parses back to
+// the same ClassFile, so only the bytes are compared; ClassFiles can't be,
+// as a NaN constant never equals itself
+pub fn verify_round_trip(data: &[u8]) -> Result<(), RoundTripError> {
+    let (_, classfile) = parse_classfile(data)?;
+    compare(data, &classfile.to_bytes()?)?;
+
+    let (_, lazy) = parse_classfile_lazy(data)?;
+    compare(data, &lazy.to_bytes()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::{
+        parse_classfile_with, AttributeParsers, CustomAttributeValue, LineNumberTableEntry,
+    };
+
+    #[test]
+    fn test_round_trip() {
+        let classes: [&[u8]; 23] = [
+            include_bytes!("../../../../java/Annotated$Hidden.class"),
+            include_bytes!("../../../../java/Annotated$Info.class"),
+            include_bytes!("../../../../java/Annotated$Marker.class"),
+            include_bytes!("../../../../java/Annotated$Typed.class"),
+            include_bytes!("../../../../java/Annotated.class"),
+            include_bytes!("../../../../java/Constants.class"),
+            include_bytes!("../../../../java/Debug.class"),
+            include_bytes!("../../../../java/Frames.class"),
+            include_bytes!("../../../../java/Generics.class"),
+            include_bytes!("../../../../java/HelloWorld.class"),
+            include_bytes!("../../../../java/Insns$Greeter.class"),
+            include_bytes!("../../../../java/Insns.class"),
+            include_bytes!("../../../../java/Locals.class"),
+            include_bytes!("../../../../java/Outer$1.class"),
+            include_bytes!("../../../../java/Outer$Inner.class"),
+            include_bytes!("../../../../java/Outer$Nested.class"),
+            include_bytes!("../../../../java/Outer.class"),
+            include_bytes!("../../../../java/Point.class"),
+            include_bytes!("../../../../java/Shape.class"),
+            include_bytes!("../../../../java/Switches.class"),
+            include_bytes!("../../../../java/Vehicle$Car.class"),
+            include_bytes!("../../../../java/Vehicle$Truck.class"),
+            include_bytes!("../../../../java/Vehicle.class"),
+        ];
+        for data in classes {
+            assert_eq!(verify_round_trip(data), Ok(()));
+        }
+
+        // trailing bytes aren't part of the class
+        let mut data = include_bytes!("../../../../java/HelloWorld.class").to_vec();
+        let len = data.len();
+        data.push(0);
+        assert_eq!(verify_round_trip(&data), Err(RoundTripError::Mismatch(len)));
+    }
+
+    #[test]
+    fn test_round_trip_nan() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        classfile
+            .constant_pool
+            .push(Constant::Float { value: f32::NAN });
+        // a NaN other than the canonical one keeps its bits
+        classfile.constant_pool.push(Constant::Double {
+            value: f64::from_bits(0x7ff8_0000_0000_0001),
+        });
+        let data = classfile.to_bytes().unwrap();
+        assert_eq!(verify_round_trip(&data), Ok(()));
+    }
+
+    #[test]
+    fn test_round_trip_duplicate_name() {
+        // a second "SourceFile" Utf8, used by the attribute
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let index = classfile.constant_pool.push(Constant::Utf8 {
+            value: b"SourceFile".into(),
+        });
+        let mut data = classfile.to_bytes().unwrap();
+        let end = data.len();
+        data[end - 8..end - 6].copy_from_slice(&index.to_be_bytes());
+
+        let first = classfile.constant_pool.find_utf8("SourceFile").unwrap();
+        assert_ne!(first, index);
+        assert_eq!(
+            verify_round_trip(&data),
+            Err(RoundTripError::Mismatch(end - 7))
+        );
+        let (_, lazy) = parse_classfile_lazy(&data).unwrap();
+        assert_eq!(lazy.to_bytes().unwrap(), data);
+    }
+
+    #[test]
+    fn test_round_trip_custom() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let attribute_name_index = classfile.constant_pool.push(Constant::Utf8 {
+            value: b"Extra".into(),
+        });
+        classfile.attributes.push(Attribute::Unknown {
+            attribute_name_index,
+            name: b"Extra".into(),
+            data: [0, 7].as_slice().into(),
+        });
+        let data = classfile.to_bytes().unwrap();
+
+        let mut registry = AttributeParsers::new();
+        registry.register(b"Extra", |data, _| {
+            Ok(CustomAttributeValue::new(data.to_vec()))
+        });
+        let (_, classfile) = parse_classfile_with(&data, &registry).unwrap();
+        assert!(matches!(
+            classfile.attributes.last(),
+            Some(Attribute::Custom { .. })
+        ));
+        assert_eq!(classfile.to_bytes().unwrap(), data);
+    }
+
+    #[test]
+    fn test_write_errors() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        // HelloWorld has no generics, so no Utf8 to name a Signature
+        classfile.fields[0]
+            .attributes
+            .push(Attribute::Signature(Signature { signature_index: 1 }));
+        assert_eq!(
+            classfile.to_bytes(),
+            Err(ClassWriteError::MissingAttributeName("Signature"))
+        );
+
+        // a same frame's offset_delta is its type, which stops at 63
+        let data = include_bytes!("../../../../java/Frames.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let Attribute::Code(code) = &mut classfile.methods[0].attributes[0] else {
+            panic!("expected Code");
+        };
+        code.attributes
+            .push(Attribute::StackMapTable(StackMapTable {
+                entries: vec![StackMapFrame::Same { offset_delta: 64 }],
+            }));
+        assert_eq!(
+            classfile.to_bytes(),
+            Err(ClassWriteError::InvalidStackMapFrame)
+        );
+    }
+
+    #[test]
+    fn test_write_with_builder() {
+        let mut pool = ConstantPoolBuilder::new();
+        let sourcefile_index = pool.utf8("Hello.java").unwrap();
+        let mut out = Vec::new();
+        SourceFile { sourcefile_index }
+            .write(&mut pool, &mut out)
+            .unwrap();
+        assert_eq!(out, [0, 2, 0, 0, 0, 2, 0, 1]);
+        assert_eq!(pool.to_constant_pool().find_utf8("SourceFile"), Some(2));
+
+        // nested names are added too, and unknown attributes copied as they are
+        let code = Code {
+            max_stack: 0,
+            max_locals: 0,
+            code: vec![0xb1].into(),
+            exception_table: vec![],
+            attributes: vec![
+                Attribute::LineNumberTable(LineNumberTable {
+                    line_number_table: vec![LineNumberTableEntry {
+                        start_pc: 0,
+                        line_number: 7,
+                    }],
+                }),
+                Attribute::Unknown {
+                    attribute_name_index: 40,
+                    name: b"Extra".as_slice().into(),
+                    data: b"\x01\x02".as_slice().into(),
+                },
+            ],
+        };
+        let mut out = Vec::new();
+        Attribute::Code(code).write(&mut pool, &mut out).unwrap();
+        #[rustfmt::skip]
+        let expected = [
+            0, 3, 0, 0, 0, 33, // Code
+            0, 0, 0, 0, 0, 0, 0, 1, 0xb1, 0, 0, 0, 2,
+            0, 4, 0, 0, 0, 6, 0, 1, 0, 0, 0, 7, // LineNumberTable
+            0, 5, 0, 0, 0, 2, 1, 2, // Extra
+        ];
+        assert_eq!(out, expected);
+        assert_eq!(pool.len(), 5);
+
+        let invalid = Attribute::Unknown {
+            attribute_name_index: 1,
+            name: b"\xff".as_slice().into(),
+            data: b"".as_slice().into(),
+        };
+        assert_eq!(
+            invalid.write(&mut pool, &mut out),
+            Err(ClassWriteError::InvalidAttributeName(vec![0xff]))
+        );
+    }
+}
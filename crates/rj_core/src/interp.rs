@@ -0,0 +1,16 @@
+mod builtins;
+mod class_loader;
+mod error;
+mod exception;
+mod frame;
+mod heap;
+mod interpreter;
+mod value;
+
+pub use class_loader::*;
+pub use error::*;
+pub use exception::*;
+pub use frame::*;
+pub use heap::*;
+pub use interpreter::*;
+pub use value::*;
@@ -0,0 +1,212 @@
+use std::io::Write;
+
+use super::error::ExecutionError;
+use super::interpreter::Interpreter;
+use super::value::Value;
+use crate::asm::{ClassBuilder, Instruction};
+use crate::class::{ClassAccessFlags, ClassFile, FieldAccessFlags, MethodAccessFlags};
+
+// the parameters PrintStream's print and println take
+const PRINTABLE: [&str; 5] = ["(Z)V", "(C)V", "(I)V", "(J)V", "(Ljava/lang/String;)V"];
+
+// the builtin classes the interpreter depends on the fields or native
+// methods of, which it loads even when the class path has the JDK's
+pub(crate) fn is_intrinsic_class(name: &str) -> bool {
+    matches!(
+        name,
+        "java/lang/String" | "java/lang/System" | "java/lang/Throwable" | "java/io/PrintStream"
+    )
+}
+
+// classes the interpreter provides when the class path doesn't have them,
+// so code can run without a JDK
+pub(crate) fn builtin_class(name: &str) -> Option<ClassFile<'static>> {
+    let mut builder = ClassBuilder::new(name);
+    let final_class = ClassAccessFlags::PUBLIC | ClassAccessFlags::FINAL | ClassAccessFlags::SUPER;
+    match name {
+        "java/lang/Object" => {
+            builder.public_method("<init>", "()V", |body| {
+                body.code.emit(Instruction::Return);
+                Ok(())
+            });
+        }
+        // the text of a String is its UTF-16 code units
+        "java/lang/String" => {
+            builder.access_flags(final_class).field(
+                FieldAccessFlags::PRIVATE | FieldAccessFlags::FINAL,
+                "value",
+                "[C",
+            );
+        }
+        "java/lang/System" => {
+            builder
+                .access_flags(final_class)
+                .field(
+                    FieldAccessFlags::PUBLIC | FieldAccessFlags::STATIC | FieldAccessFlags::FINAL,
+                    "out",
+                    "Ljava/io/PrintStream;",
+                )
+                .method(MethodAccessFlags::STATIC, "<clinit>", "()V", |body| {
+                    let print_stream = body.constant_pool.class("java/io/PrintStream")?;
+                    let init =
+                        body.constant_pool
+                            .method_ref("java/io/PrintStream", "<init>", "()V")?;
+                    let out = body.constant_pool.field_ref(
+                        "java/lang/System",
+                        "out",
+                        "Ljava/io/PrintStream;",
+                    )?;
+                    body.code
+                        .emit(Instruction::New(print_stream))
+                        .emit(Instruction::Dup)
+                        .emit(Instruction::Invokespecial(init))
+                        .emit(Instruction::Putstatic(out))
+                        .emit(Instruction::Return);
+                    Ok(())
+                });
+        }
+        // a message, and a stack trace the interpreter keeps for it
+        "java/lang/Throwable" => {
+            builder
+                .field(
+                    FieldAccessFlags::PRIVATE,
+                    "detailMessage",
+                    "Ljava/lang/String;",
+                )
+                .default_constructor()
+                .public_method("<init>", "(Ljava/lang/String;)V", |body| {
+                    let init =
+                        body.constant_pool
+                            .method_ref("java/lang/Object", "<init>", "()V")?;
+                    let message = body.constant_pool.field_ref(
+                        "java/lang/Throwable",
+                        "detailMessage",
+                        "Ljava/lang/String;",
+                    )?;
+                    body.code
+                        .emit(Instruction::Aload0)
+                        .emit(Instruction::Invokespecial(init))
+                        .emit(Instruction::Aload0)
+                        .emit(Instruction::Aload1)
+                        .emit(Instruction::Putfield(message))
+                        .emit(Instruction::Return);
+                    Ok(())
+                })
+                .public_method("getMessage", "()Ljava/lang/String;", |body| {
+                    let message = body.constant_pool.field_ref(
+                        "java/lang/Throwable",
+                        "detailMessage",
+                        "Ljava/lang/String;",
+                    )?;
+                    body.code
+                        .emit(Instruction::Aload0)
+                        .emit(Instruction::Getfield(message))
+                        .emit(Instruction::Areturn);
+                    Ok(())
+                });
+        }
+        "java/lang/Exception" | "java/lang/RuntimeException" => {
+            let super_name = match name {
+                "java/lang/Exception" => "java/lang/Throwable",
+                _ => "java/lang/Exception",
+            };
+            builder
+                .super_class(super_name)
+                .default_constructor()
+                .public_method("<init>", "(Ljava/lang/String;)V", |body| {
+                    let init = body.constant_pool.method_ref(
+                        super_name,
+                        "<init>",
+                        "(Ljava/lang/String;)V",
+                    )?;
+                    body.code
+                        .emit(Instruction::Aload0)
+                        .emit(Instruction::Aload1)
+                        .emit(Instruction::Invokespecial(init))
+                        .emit(Instruction::Return);
+                    Ok(())
+                });
+        }
+        // standard output, which is the interpreter's
+        "java/io/PrintStream" => {
+            builder.default_constructor();
+            let native = MethodAccessFlags::PUBLIC | MethodAccessFlags::NATIVE;
+            for descriptor in PRINTABLE {
+                builder
+                    .declare_method(native, "print", descriptor)
+                    .declare_method(native, "println", descriptor);
+            }
+            builder.declare_method(native, "println", "()V");
+        }
+        _ => return None,
+    }
+    let mut classfile = builder.build().ok()?;
+    if name == "java/lang/Object" {
+        // the builder always names a superclass
+        classfile.super_class = 0;
+    }
+    Some(classfile)
+}
+
+// a native method the interpreter implements itself
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Intrinsic {
+    // PrintStream's print and println, by the descriptor of what they
+    // print, or None for println()
+    Print {
+        parameter: Option<&'static str>,
+        newline: bool,
+    },
+}
+
+impl Intrinsic {
+    pub(crate) fn find(class: &str, name: &str, descriptor: &str) -> Option<Intrinsic> {
+        let parameter = PRINTABLE
+            .iter()
+            .find(|printable| **printable == descriptor)
+            .map(|printable| &printable[1..printable.len() - 2]);
+        match (class, name, descriptor) {
+            ("java/io/PrintStream", "println", "()V") => Some(Intrinsic::Print {
+                parameter: None,
+                newline: true,
+            }),
+            ("java/io/PrintStream", "print" | "println", _) => Some(Intrinsic::Print {
+                parameter: Some(parameter?),
+                newline: name == "println",
+            }),
+            _ => None,
+        }
+    }
+
+    // runs the method, which takes its receiver as the first argument
+    pub(crate) fn call(
+        self,
+        interpreter: &mut Interpreter,
+        args: &[Value],
+    ) -> Result<Option<Value>, ExecutionError> {
+        match self {
+            Intrinsic::Print { parameter, newline } => {
+                let mut text = match (parameter, args) {
+                    (None, [_]) => String::new(),
+                    (Some("Z"), [_, Value::Int(value)]) => (*value != 0).to_string(),
+                    (Some("C"), [_, Value::Int(value)]) => {
+                        String::from_utf16_lossy(&[*value as u16])
+                    }
+                    (Some("I"), [_, Value::Int(value)]) => value.to_string(),
+                    (Some("J"), [_, Value::Long(value)]) => value.to_string(),
+                    (Some(_), [_, Value::Reference(None)]) => "null".to_string(),
+                    (Some(_), [_, Value::Reference(Some(string))]) => interpreter
+                        .string_value(*string)
+                        .ok_or(ExecutionError::InvalidArguments)?,
+                    _ => return Err(ExecutionError::InvalidArguments),
+                };
+                if newline {
+                    text.push('\n');
+                }
+                // like java's, a PrintStream doesn't report failing to write
+                let _ = interpreter.out.write_all(text.as_bytes());
+                Ok(None)
+            }
+        }
+    }
+}
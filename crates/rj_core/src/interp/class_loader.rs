@@ -0,0 +1,1025 @@
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use super::builtins::{builtin_class, is_intrinsic_class};
+use super::error::LinkageError;
+use super::value::Value;
+use crate::archive::ClassPath;
+use crate::class::{
+    parse_classfile, Attribute, ClassAccessFlags, ClassFile, Constant, ConstantPool,
+    FieldAccessFlags, Method, MethodAccessFlags,
+};
+
+// where a field's value is kept: a slot in each instance, or one of the
+// declaring class's static slots
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldSlot {
+    pub slot: usize,
+    pub is_static: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedField {
+    // the class declaring the field, which may be a superclass or
+    // superinterface of the one the Fieldref names
+    pub class: Rc<RuntimeClass>,
+    pub slot: FieldSlot,
+}
+
+// a method instances of a class dispatch to, by the class declaring it and
+// its index in that class's methods
+#[derive(Debug, Clone, PartialEq)]
+pub struct VtableEntry {
+    pub class: String,
+    pub method: usize,
+}
+
+// a method by the class declaring it and its index in the class's methods
+#[derive(Debug, Clone)]
+pub struct ResolvedMethod {
+    pub class: Rc<RuntimeClass>,
+    pub method: usize,
+}
+
+impl ResolvedMethod {
+    pub fn access_flags(&self) -> MethodAccessFlags {
+        self.class.method(self.method).access_flags
+    }
+}
+
+#[derive(Debug)]
+enum Resolved {
+    Class(Rc<RuntimeClass>),
+    Field(ResolvedField),
+    Method(ResolvedMethod),
+}
+
+// a loaded and linked class: its superclass and superinterfaces are loaded,
+// and its fields and virtual methods are laid out
+#[derive(Debug)]
+pub struct RuntimeClass {
+    pub name: String,
+    pub classfile: ClassFile<'static>,
+    pub super_class: Option<Rc<RuntimeClass>>,
+    pub interfaces: Vec<Rc<RuntimeClass>>,
+    // the slots of the fields the class declares, by name and descriptor.
+    // instance slots follow the superclass's, static ones start at 0
+    fields: HashMap<(String, String), FieldSlot>,
+    // the value each instance slot starts as, the superclasses' first
+    pub field_defaults: Vec<Value>,
+    statics: RefCell<Vec<Value>>,
+    // the superclass's vtable, with the methods this class overrides
+    // replaced and its other virtual methods appended. interfaces have none
+    pub vtable: Vec<VtableEntry>,
+    vtable_indices: HashMap<(String, String), usize>,
+    // for each interface the class implements, directly or not, the vtable
+    // index of each of the interface's methods; None for the static and
+    // private ones
+    itables: HashMap<String, Vec<Option<usize>>>,
+    // whether the static initializer has been started
+    initialized: Cell<bool>,
+    // constant pool entries resolved so far, by index
+    resolved: RefCell<HashMap<u16, Resolved>>,
+}
+
+impl RuntimeClass {
+    pub fn is_interface(&self) -> bool {
+        self.classfile
+            .access_flags
+            .contains(ClassAccessFlags::INTERFACE)
+    }
+
+    pub fn method(&self, index: usize) -> &Method<'static> {
+        &self.classfile.methods[index]
+    }
+
+    // the name and descriptor of one of the class's methods
+    pub fn method_signature(&self, index: usize) -> (Cow<'_, str>, Cow<'_, str>) {
+        let method = self.method(index);
+        let pool = &self.classfile.constant_pool;
+        let name = pool.utf8(method.name_index).unwrap_or_default();
+        let descriptor = pool.utf8(method.descriptor_index).unwrap_or_default();
+        (name, descriptor)
+    }
+
+    // the index of a method the class declares
+    pub fn find_method(&self, name: &str, descriptor: &str) -> Option<usize> {
+        (0..self.classfile.methods.len()).find(|index| {
+            let (method_name, method_descriptor) = self.method_signature(*index);
+            method_name == name && method_descriptor == descriptor
+        })
+    }
+
+    // the slot of a field the class declares
+    pub fn field_slot(&self, name: &str, descriptor: &str) -> Option<FieldSlot> {
+        self.fields
+            .get(&(name.to_string(), descriptor.to_string()))
+            .copied()
+    }
+
+    // the number of instance slots, including the superclasses'
+    pub fn instance_size(&self) -> usize {
+        self.field_defaults.len()
+    }
+
+    pub fn static_count(&self) -> usize {
+        self.statics.borrow().len()
+    }
+
+    pub fn static_value(&self, slot: usize) -> Value {
+        self.statics.borrow()[slot]
+    }
+
+    pub fn set_static_value(&self, slot: usize, value: Value) {
+        self.statics.borrow_mut()[slot] = value;
+    }
+
+    pub fn vtable_index(&self, name: &str, descriptor: &str) -> Option<usize> {
+        self.vtable_indices
+            .get(&(name.to_string(), descriptor.to_string()))
+            .copied()
+    }
+
+    // the vtable index of an interface's method, by its index in the
+    // interface's methods, if the class implements the interface
+    pub fn itable_index(&self, interface: &str, method: usize) -> Option<usize> {
+        *self.itables.get(interface)?.get(method)?
+    }
+
+    // whether the class is `name`, or extends or implements it
+    pub fn is_subclass_of(&self, name: &str) -> bool {
+        self.name == name
+            || self
+                .super_class
+                .iter()
+                .any(|class| class.is_subclass_of(name))
+            || self
+                .interfaces
+                .iter()
+                .any(|class| class.is_subclass_of(name))
+    }
+
+    // marks the class initialized, returning whether it wasn't already
+    pub(crate) fn start_initialization(&self) -> bool {
+        !self.initialized.replace(true)
+    }
+}
+
+// whether a method can be selected by invokevirtual or invokeinterface
+fn is_virtual(class: &RuntimeClass, method: usize) -> bool {
+    let flags = class.method(method).access_flags;
+    !flags.contains(MethodAccessFlags::STATIC)
+        && !flags.contains(MethodAccessFlags::PRIVATE)
+        && !class.method_signature(method).0.starts_with('<')
+}
+
+// finds a method in a class, then its superclasses
+fn lookup_method(class: &Rc<RuntimeClass>, name: &str, descriptor: &str) -> Option<ResolvedMethod> {
+    match class.find_method(name, descriptor) {
+        Some(method) => Some(ResolvedMethod {
+            class: class.clone(),
+            method,
+        }),
+        None => lookup_method(class.super_class.as_ref()?, name, descriptor),
+    }
+}
+
+// every interface a class with these superclass and interfaces implements,
+// directly or not, each once
+fn superinterfaces(
+    super_class: Option<&Rc<RuntimeClass>>,
+    interfaces: &[Rc<RuntimeClass>],
+) -> Vec<Rc<RuntimeClass>> {
+    let mut found: Vec<Rc<RuntimeClass>> = Vec::new();
+    let mut pending = interfaces.to_vec();
+    let mut class = super_class;
+    while let Some(current) = class {
+        pending.extend(current.interfaces.iter().cloned());
+        class = current.super_class.as_ref();
+    }
+    while let Some(interface) = pending.pop() {
+        if found.iter().all(|other| other.name != interface.name) {
+            pending.extend(interface.interfaces.iter().cloned());
+            found.push(interface);
+        }
+    }
+    found
+}
+
+// picks among the superinterface methods of a class: the only non-abstract
+// one of the maximally-specific methods, those no other candidate's
+// interface overrides, or else any of them (JVMS 5.4.3.3)
+fn lookup_interface_method(
+    interfaces: &[Rc<RuntimeClass>],
+    name: &str,
+    descriptor: &str,
+) -> Option<ResolvedMethod> {
+    let candidates = interfaces
+        .iter()
+        .filter_map(|interface| {
+            let method = interface.find_method(name, descriptor)?;
+            is_virtual(interface, method).then(|| ResolvedMethod {
+                class: interface.clone(),
+                method,
+            })
+        })
+        .collect::<Vec<_>>();
+    let maximally_specific = candidates
+        .iter()
+        .filter(|candidate| {
+            !candidates.iter().any(|other| {
+                other.class.name != candidate.class.name
+                    && other.class.is_subclass_of(&candidate.class.name)
+            })
+        })
+        .collect::<Vec<_>>();
+    let mut concrete = maximally_specific.iter().filter(|candidate| {
+        !candidate
+            .access_flags()
+            .contains(MethodAccessFlags::ABSTRACT)
+    });
+    match (concrete.next(), concrete.next()) {
+        (Some(method), None) => Some((*method).clone()),
+        _ => maximally_specific.first().map(|method| (*method).clone()),
+    }
+}
+
+// the value of an Integer, Float, Long or Double constant
+pub(crate) fn numeric_constant(pool: &ConstantPool, index: u16) -> Option<Value> {
+    match pool.get(index)? {
+        Constant::Integer { value } => Some(Value::Int(*value)),
+        Constant::Float { value } => Some(Value::Float(*value)),
+        Constant::Long { value } => Some(Value::Long(*value)),
+        Constant::Double { value } => Some(Value::Double(*value)),
+        _ => None,
+    }
+}
+
+// finds a field in a class, then its superinterfaces, then its superclass,
+// as field resolution does (JVMS 5.4.3.2)
+fn lookup_field(class: &Rc<RuntimeClass>, name: &str, descriptor: &str) -> Option<ResolvedField> {
+    if let Some(slot) = class.field_slot(name, descriptor) {
+        return Some(ResolvedField {
+            class: class.clone(),
+            slot,
+        });
+    }
+    class
+        .interfaces
+        .iter()
+        .chain(&class.super_class)
+        .find_map(|class| lookup_field(class, name, descriptor))
+}
+
+// loads classes from a class path, links them and keeps them for the
+// lifetime of the loader, like the JVM's method area. java/lang/Object is
+// built in when the class path doesn't have it. access checks aren't made
+pub struct ClassLoader {
+    class_path: ClassPath,
+    classes: RefCell<HashMap<String, Rc<RuntimeClass>>>,
+    // the classes being linked, to catch one that is its own superclass
+    linking: RefCell<HashSet<String>>,
+}
+
+impl ClassLoader {
+    pub fn new(class_path: ClassPath) -> Self {
+        ClassLoader {
+            class_path,
+            classes: RefCell::new(HashMap::new()),
+            linking: RefCell::new(HashSet::new()),
+        }
+    }
+
+    // the class named `name`, an internal name, loading and linking it and
+    // its superclasses the first time
+    pub fn load(&self, name: &str) -> Result<Rc<RuntimeClass>, LinkageError> {
+        if let Some(class) = self.classes.borrow().get(name) {
+            return Ok(class.clone());
+        }
+        if self.linking.borrow().contains(name) {
+            return Err(LinkageError::ClassCircularity(name.to_string()));
+        }
+        let found = match is_intrinsic_class(name) {
+            true => None,
+            false => self.class_path.find(name)?,
+        };
+        let classfile = match found {
+            Some((_, data)) => parse_classfile(&data)
+                .map(|(_, classfile)| classfile.into_owned())
+                .map_err(|error| LinkageError::ClassFormatError(name.to_string(), error))?,
+            None => {
+                builtin_class(name).ok_or_else(|| LinkageError::ClassNotFound(name.to_string()))?
+            }
+        };
+        let found = classfile.constant_pool.class_name(classfile.this_class)?;
+        if found != name {
+            return Err(LinkageError::WrongName {
+                expected: name.to_string(),
+                found: found.into_owned(),
+            });
+        }
+        self.link(classfile)
+    }
+
+    // defines a class from a class file's bytes, as if it had been found on
+    // the class path
+    pub fn define(&self, data: &[u8]) -> Result<Rc<RuntimeClass>, LinkageError> {
+        let (_, classfile) = parse_classfile(data)
+            .map_err(|error| LinkageError::ClassFormatError(String::new(), error))?;
+        self.link(classfile.into_owned())
+    }
+
+    fn link(&self, classfile: ClassFile<'static>) -> Result<Rc<RuntimeClass>, LinkageError> {
+        let name = classfile
+            .constant_pool
+            .class_name(classfile.this_class)?
+            .into_owned();
+        if self.classes.borrow().contains_key(&name) {
+            return Err(LinkageError::DuplicateClass(name));
+        }
+        self.linking.borrow_mut().insert(name.clone());
+        let class = self.link_class(name.clone(), classfile);
+        self.linking.borrow_mut().remove(&name);
+        let class = Rc::new(class?);
+        self.classes.borrow_mut().insert(name, class.clone());
+        Ok(class)
+    }
+
+    fn link_class(
+        &self,
+        name: String,
+        classfile: ClassFile<'static>,
+    ) -> Result<RuntimeClass, LinkageError> {
+        let pool = &classfile.constant_pool;
+        let super_class = match classfile.super_class {
+            0 => None,
+            index => Some(self.load(&pool.class_name(index)?)?),
+        };
+        if super_class
+            .as_ref()
+            .is_some_and(|class| class.is_interface())
+        {
+            return Err(LinkageError::IncompatibleClassChange(name));
+        }
+        let mut interfaces = Vec::new();
+        for index in &classfile.interfaces {
+            let interface = self.load(&pool.class_name(*index)?)?;
+            if !interface.is_interface() {
+                return Err(LinkageError::IncompatibleClassChange(name));
+            }
+            interfaces.push(interface);
+        }
+
+        let mut fields = HashMap::new();
+        let mut field_defaults = super_class
+            .as_ref()
+            .map_or(Vec::new(), |class| class.field_defaults.clone());
+        let mut statics = Vec::new();
+        for field in &classfile.fields {
+            let key = (
+                pool.utf8(field.name_index)?.into_owned(),
+                pool.utf8(field.descriptor_index)?.into_owned(),
+            );
+            let is_static = field.access_flags.contains(FieldAccessFlags::STATIC);
+            let default = Value::default_for(&key.1);
+            let values = match is_static {
+                true => &mut statics,
+                false => &mut field_defaults,
+            };
+            fields.insert(
+                key,
+                FieldSlot {
+                    slot: values.len(),
+                    is_static,
+                },
+            );
+            // static finals with a ConstantValue start as it, when it's a number
+            let constant = field
+                .attributes
+                .iter()
+                .find_map(|attribute| match attribute {
+                    Attribute::ConstantValue(value) if is_static => {
+                        numeric_constant(pool, value.constantvalue_index)
+                    }
+                    _ => None,
+                });
+            values.push(constant.unwrap_or(default));
+        }
+
+        let (mut vtable, mut vtable_indices) = match &super_class {
+            Some(class) => (class.vtable.clone(), class.vtable_indices.clone()),
+            None => (Vec::new(), HashMap::new()),
+        };
+        let is_interface = classfile.access_flags.contains(ClassAccessFlags::INTERFACE);
+        for (index, method) in classfile.methods.iter().enumerate() {
+            let method_name = pool.utf8(method.name_index)?;
+            if is_interface
+                || method.access_flags.contains(MethodAccessFlags::STATIC)
+                || method.access_flags.contains(MethodAccessFlags::PRIVATE)
+                || method_name.starts_with('<')
+            {
+                continue;
+            }
+            let key = (
+                method_name.into_owned(),
+                pool.utf8(method.descriptor_index)?.into_owned(),
+            );
+            let entry = VtableEntry {
+                class: name.clone(),
+                method: index,
+            };
+            match vtable_indices.get(&key) {
+                Some(slot) => vtable[*slot] = entry,
+                None => {
+                    vtable_indices.insert(key, vtable.len());
+                    vtable.push(entry);
+                }
+            }
+        }
+
+        // interface methods the class doesn't implement dispatch to the
+        // maximally-specific default, or an abstract method if there's none
+        let all_interfaces = superinterfaces(super_class.as_ref(), &interfaces);
+        let mut itables = HashMap::new();
+        if !is_interface {
+            for interface in &all_interfaces {
+                for method in 0..interface.classfile.methods.len() {
+                    if !is_virtual(interface, method) {
+                        continue;
+                    }
+                    let (name, descriptor) = interface.method_signature(method);
+                    let key = (name.into_owned(), descriptor.into_owned());
+                    // a method of a class wins over any default, but an
+                    // inherited default is selected again, as the class may
+                    // implement a more specific one
+                    let slot = vtable_indices.get(&key).copied();
+                    if slot.is_some_and(|slot| {
+                        all_interfaces
+                            .iter()
+                            .all(|interface| interface.name != vtable[slot].class)
+                    }) {
+                        continue;
+                    }
+                    let Some(selected) = lookup_interface_method(&all_interfaces, &key.0, &key.1)
+                    else {
+                        continue;
+                    };
+                    let entry = VtableEntry {
+                        class: selected.class.name.clone(),
+                        method: selected.method,
+                    };
+                    match slot {
+                        Some(slot) => vtable[slot] = entry,
+                        None => {
+                            vtable_indices.insert(key, vtable.len());
+                            vtable.push(entry);
+                        }
+                    }
+                }
+            }
+            for interface in &all_interfaces {
+                let itable = (0..interface.classfile.methods.len())
+                    .map(|method| {
+                        if !is_virtual(interface, method) {
+                            return None;
+                        }
+                        let (name, descriptor) = interface.method_signature(method);
+                        vtable_indices
+                            .get(&(name.into_owned(), descriptor.into_owned()))
+                            .copied()
+                    })
+                    .collect();
+                itables.insert(interface.name.clone(), itable);
+            }
+        }
+
+        Ok(RuntimeClass {
+            name,
+            super_class,
+            interfaces,
+            fields,
+            field_defaults,
+            statics: RefCell::new(statics),
+            vtable,
+            vtable_indices,
+            itables,
+            initialized: Cell::new(false),
+            resolved: RefCell::new(HashMap::new()),
+            classfile,
+        })
+    }
+
+    // the class a Class constant of `class` names, loaded the first time
+    // it's resolved
+    pub fn resolve_class(
+        &self,
+        class: &RuntimeClass,
+        index: u16,
+    ) -> Result<Rc<RuntimeClass>, LinkageError> {
+        if let Some(Resolved::Class(resolved)) = class.resolved.borrow().get(&index) {
+            return Ok(resolved.clone());
+        }
+        let name = class.classfile.constant_pool.class_name(index)?;
+        let resolved = self.load(&name)?;
+        class
+            .resolved
+            .borrow_mut()
+            .insert(index, Resolved::Class(resolved.clone()));
+        Ok(resolved)
+    }
+
+    // the field a Fieldref constant of `class` refers to, looked up from
+    // the class it names
+    pub fn resolve_field(
+        &self,
+        class: &RuntimeClass,
+        index: u16,
+    ) -> Result<ResolvedField, LinkageError> {
+        if let Some(Resolved::Field(resolved)) = class.resolved.borrow().get(&index) {
+            return Ok(resolved.clone());
+        }
+        let pool = &class.classfile.constant_pool;
+        let field_ref = pool.field_ref(index)?;
+        let owner = self.load(&field_ref.class_name)?;
+        let resolved =
+            lookup_field(&owner, &field_ref.name, &field_ref.descriptor).ok_or_else(|| {
+                LinkageError::NoSuchField {
+                    class: field_ref.class_name.to_string(),
+                    name: field_ref.name.to_string(),
+                    descriptor: field_ref.descriptor.to_string(),
+                }
+            })?;
+        class
+            .resolved
+            .borrow_mut()
+            .insert(index, Resolved::Field(resolved.clone()));
+        Ok(resolved)
+    }
+
+    // the method a Methodref or InterfaceMethodref constant of `class`
+    // refers to, looked up from the class it names as method resolution and
+    // interface method resolution do (JVMS 5.4.3.3, 5.4.3.4)
+    pub fn resolve_method(
+        &self,
+        class: &RuntimeClass,
+        index: u16,
+    ) -> Result<ResolvedMethod, LinkageError> {
+        if let Some(Resolved::Method(resolved)) = class.resolved.borrow().get(&index) {
+            return Ok(resolved.clone());
+        }
+        let pool = &class.classfile.constant_pool;
+        let (method_ref, is_interface_ref) = match pool.get(index) {
+            Some(Constant::InterfaceMethodref { .. }) => (pool.interface_method_ref(index)?, true),
+            _ => (pool.method_ref(index)?, false),
+        };
+        let owner = self.load(&method_ref.class_name)?;
+        if owner.is_interface() != is_interface_ref {
+            return Err(LinkageError::IncompatibleClassChange(owner.name.clone()));
+        }
+        let (name, descriptor) = (&method_ref.name, &method_ref.descriptor);
+        let found = match is_interface_ref {
+            false => lookup_method(&owner, name, descriptor),
+            // an interface's own methods, then Object's public instance
+            // methods
+            true => owner
+                .find_method(name, descriptor)
+                .map(|method| ResolvedMethod {
+                    class: owner.clone(),
+                    method,
+                })
+                .or_else(|| {
+                    let object = self.load("java/lang/Object").ok()?;
+                    let method = object.find_method(name, descriptor)?;
+                    let flags = object.method(method).access_flags;
+                    (flags.contains(MethodAccessFlags::PUBLIC)
+                        && !flags.contains(MethodAccessFlags::STATIC))
+                    .then_some(ResolvedMethod {
+                        class: object,
+                        method,
+                    })
+                }),
+        };
+        let resolved = found
+            .or_else(|| {
+                let interfaces = superinterfaces(owner.super_class.as_ref(), &owner.interfaces);
+                lookup_interface_method(&interfaces, name, descriptor)
+            })
+            .ok_or_else(|| LinkageError::NoSuchMethod {
+                class: method_ref.class_name.to_string(),
+                name: name.to_string(),
+                descriptor: descriptor.to_string(),
+            })?;
+        class
+            .resolved
+            .borrow_mut()
+            .insert(index, Resolved::Method(resolved.clone()));
+        Ok(resolved)
+    }
+
+    // the method invokespecial runs when its method, resolved from `class`,
+    // is in a superclass: the one the superclass of `class` would inherit,
+    // so a super call skips overrides in between
+    pub fn select_special(
+        &self,
+        class: &RuntimeClass,
+        resolved: &ResolvedMethod,
+    ) -> ResolvedMethod {
+        let (name, descriptor) = resolved.class.method_signature(resolved.method);
+        let super_class = match &class.super_class {
+            Some(super_class)
+                if name != "<init>"
+                    && !resolved.class.is_interface()
+                    && class.name != resolved.class.name
+                    && class.is_subclass_of(&resolved.class.name) =>
+            {
+                super_class
+            }
+            _ => return resolved.clone(),
+        };
+        lookup_method(super_class, &name, &descriptor)
+            .or_else(|| {
+                let interfaces = superinterfaces(Some(super_class), &[]);
+                lookup_interface_method(&interfaces, &name, &descriptor)
+            })
+            .unwrap_or_else(|| resolved.clone())
+    }
+
+    // the method invokevirtual or invokeinterface runs for a receiver of
+    // class `receiver`: the resolved one if it's private, or else the one in
+    // the receiver's vtable slot for it (JVMS 5.4.6)
+    pub fn select_virtual(
+        &self,
+        receiver: &RuntimeClass,
+        resolved: &ResolvedMethod,
+    ) -> Result<ResolvedMethod, LinkageError> {
+        if resolved.access_flags().contains(MethodAccessFlags::PRIVATE) {
+            return Ok(resolved.clone());
+        }
+        let index = match resolved.class.is_interface() {
+            true => receiver
+                .itable_index(&resolved.class.name, resolved.method)
+                .ok_or_else(|| LinkageError::IncompatibleClassChange(receiver.name.clone()))?,
+            false => {
+                let (name, descriptor) = resolved.class.method_signature(resolved.method);
+                match resolved.class.vtable_index(&name, &descriptor) {
+                    Some(index) => index,
+                    // final methods of classes without a vtable slot for them
+                    None => return Ok(resolved.clone()),
+                }
+            }
+        };
+        let entry = &receiver.vtable[index];
+        Ok(ResolvedMethod {
+            class: self.load(&entry.class)?,
+            method: entry.method,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs;
+
+    use crate::asm::{ClassBuilder, Instruction};
+
+    fn loader() -> ClassLoader {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/../../java");
+        ClassLoader::new(ClassPath::parse(fixtures).unwrap())
+    }
+
+    // a class whose one method reads the static field `class.name`, so its
+    // pool has a Fieldref for it
+    fn probe_class(name: &str, field: (&str, &str, &str)) -> Vec<u8> {
+        let (class, field_name, descriptor) = field;
+        let mut builder = ClassBuilder::new(name);
+        builder.public_static_method("probe", "()V", |body| {
+            let field = body
+                .constant_pool
+                .field_ref(class, field_name, descriptor)?;
+            body.code
+                .emit(Instruction::Getstatic(field))
+                .emit(Instruction::Pop)
+                .emit(Instruction::Return);
+            Ok(())
+        });
+        builder.build().unwrap().to_bytes().unwrap()
+    }
+
+    fn field_ref_index(class: &RuntimeClass, owner: &str, name: &str) -> u16 {
+        let pool = &class.classfile.constant_pool;
+        pool.field_refs()
+            .find(|(_, field)| field.class_name == owner && field.name == name)
+            .map(|(index, _)| index)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_load() {
+        let loader = loader();
+        let dog = loader.load("Animal$Dog").unwrap();
+        assert_eq!(dog.name, "Animal$Dog");
+        let animal = dog.super_class.clone().unwrap();
+        assert_eq!(animal.name, "Animal");
+        // Object comes from the loader, as the fixtures don't include it
+        let object = animal.super_class.clone().unwrap();
+        assert_eq!(object.name, "java/lang/Object");
+        assert!(object.super_class.is_none());
+        assert_eq!(object.find_method("<init>", "()V"), Some(0));
+        let names = dog.interfaces.iter().map(|class| class.name.as_str());
+        assert_eq!(names.collect::<Vec<_>>(), ["Animal$Pet"]);
+        assert!(dog.interfaces[0].is_interface());
+
+        assert!(Rc::ptr_eq(&loader.load("Animal$Dog").unwrap(), &dog));
+        assert!(Rc::ptr_eq(&loader.load("Animal").unwrap(), &animal));
+        assert!(Rc::ptr_eq(
+            &loader
+                .load("Animal$Bird")
+                .unwrap()
+                .super_class
+                .clone()
+                .unwrap(),
+            &animal
+        ));
+    }
+
+    #[test]
+    fn test_layout() {
+        let loader = loader();
+        let animal = loader.load("Animal").unwrap();
+        let slot = |slot, is_static| Some(FieldSlot { slot, is_static });
+        assert_eq!(animal.field_slot("legs", "I"), slot(0, false));
+        assert_eq!(animal.field_slot("id", "J"), slot(1, false));
+        assert_eq!(animal.field_slot("count", "I"), slot(0, true));
+        assert_eq!((animal.instance_size(), animal.static_count()), (2, 1));
+
+        let dog = loader.load("Animal$Dog").unwrap();
+        assert_eq!(dog.field_slot("tricks", "I"), slot(2, false));
+        assert_eq!(dog.field_slot("legs", "I"), None);
+        assert_eq!((dog.instance_size(), dog.static_count()), (3, 0));
+        // Bird's legs gets a slot of its own next to Animal's
+        let bird = loader.load("Animal$Bird").unwrap();
+        assert_eq!(bird.field_slot("legs", "I"), slot(2, false));
+        assert_eq!(bird.field_slot("wingspan", "D"), slot(3, false));
+        assert_eq!(bird.instance_size(), 4);
+
+        let methods = |class: &RuntimeClass| {
+            class
+                .vtable
+                .iter()
+                .map(|entry| {
+                    let declaring = loader.load(&entry.class).unwrap();
+                    let (name, _) = declaring.method_signature(entry.method);
+                    format!("{}.{name}", entry.class)
+                })
+                .collect::<Vec<_>>()
+        };
+        // secret is private and census static, so neither is virtual
+        assert_eq!(methods(&animal), ["Animal.legs", "Animal.sound"]);
+        // Dog inherits Pet's default cuteness
+        assert_eq!(
+            methods(&dog),
+            [
+                "Animal.legs",
+                "Animal$Dog.sound",
+                "Animal$Dog.tricks",
+                "Animal$Pet.cuteness"
+            ]
+        );
+        assert_eq!(dog.vtable_index("sound", "()I"), Some(1));
+        assert_eq!(dog.vtable_index("<init>", "()V"), None);
+        // Pet declares tricks, then cuteness
+        assert_eq!(dog.itable_index("Animal$Pet", 0), Some(2));
+        assert_eq!(dog.itable_index("Animal$Pet", 1), Some(3));
+        assert_eq!(animal.itable_index("Animal$Pet", 0), None);
+        let puppy = loader.load("Animal$Puppy").unwrap();
+        assert_eq!(
+            methods(&puppy),
+            [
+                "Animal.legs",
+                "Animal$Puppy.sound",
+                "Animal$Dog.tricks",
+                "Animal$Pet.cuteness"
+            ]
+        );
+        assert_eq!(puppy.itable_index("Animal$Pet", 1), Some(3));
+        assert!(puppy.is_subclass_of("Animal$Pet"));
+        assert!(puppy.is_subclass_of("java/lang/Object"));
+        assert!(!animal.is_subclass_of("Animal$Pet"));
+        assert_eq!(
+            methods(&loader.load("Animal$Pet").unwrap()),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            methods(&animal.super_class.clone().unwrap()),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let loader = loader();
+        let bird = loader.load("Animal$Bird").unwrap();
+        let index = field_ref_index(&bird, "Animal$Bird", "legs");
+        let field = loader.resolve_field(&bird, index).unwrap();
+        assert_eq!(field.class.name, "Animal$Bird");
+        assert_eq!(
+            field.slot,
+            FieldSlot {
+                slot: 2,
+                is_static: false
+            }
+        );
+        // resolved once, then cached
+        let again = loader.resolve_field(&bird, index).unwrap();
+        assert!(Rc::ptr_eq(&field.class, &again.class));
+
+        // a field named through a subclass resolves to the one it inherits
+        let probe = loader
+            .define(&probe_class("Probe", ("Animal$Dog", "count", "I")))
+            .unwrap();
+        let index = field_ref_index(&probe, "Animal$Dog", "count");
+        let field = loader.resolve_field(&probe, index).unwrap();
+        assert_eq!(field.class.name, "Animal");
+        assert_eq!(
+            field.slot,
+            FieldSlot {
+                slot: 0,
+                is_static: true
+            }
+        );
+
+        let index = probe.classfile.this_class;
+        assert!(Rc::ptr_eq(
+            &loader.resolve_class(&probe, index).unwrap(),
+            &probe
+        ));
+    }
+
+    fn method_ref_index(class: &RuntimeClass, owner: &str, name: &str) -> u16 {
+        let pool = &class.classfile.constant_pool;
+        pool.method_refs()
+            .find(|(_, method)| method.class_name == owner && method.name == name)
+            .map(|(index, _)| index)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_method() {
+        let loader = loader();
+        let animal = loader.load("Animal").unwrap();
+        let signature = |method: &ResolvedMethod| {
+            let (name, _) = method.class.method_signature(method.method);
+            format!("{}.{name}", method.class.name)
+        };
+        let index = method_ref_index(&animal, "Animal$Pet", "cuteness");
+        let cuteness = loader.resolve_method(&animal, index).unwrap();
+        assert_eq!(signature(&cuteness), "Animal$Pet.cuteness");
+        let again = loader.resolve_method(&animal, index).unwrap();
+        assert!(Rc::ptr_eq(&cuteness.class, &again.class));
+
+        // invokevirtual and invokeinterface select the receiver's override
+        let puppy = loader.load("Animal$Puppy").unwrap();
+        let sound = method_ref_index(&animal, "Animal", "sound");
+        let sound = loader.resolve_method(&animal, sound).unwrap();
+        assert_eq!(signature(&sound), "Animal.sound");
+        let selected = loader.select_virtual(&puppy, &sound).unwrap();
+        assert_eq!(signature(&selected), "Animal$Puppy.sound");
+        let selected = loader.select_virtual(&puppy, &cuteness).unwrap();
+        assert_eq!(signature(&selected), "Animal$Pet.cuteness");
+        assert_eq!(
+            loader.select_virtual(&animal, &cuteness).unwrap_err(),
+            LinkageError::IncompatibleClassChange("Animal".to_string())
+        );
+        // private methods aren't overridden
+        let secret = method_ref_index(&animal, "Animal", "secret");
+        let secret = loader.resolve_method(&animal, secret).unwrap();
+        let selected = loader.select_virtual(&puppy, &secret).unwrap();
+        assert_eq!(signature(&selected), "Animal.secret");
+
+        // super.sound() in Puppy runs Dog's, and a constructor runs as named
+        let index = method_ref_index(&puppy, "Animal$Dog", "sound");
+        let resolved = loader.resolve_method(&puppy, index).unwrap();
+        let selected = loader.select_special(&puppy, &resolved);
+        assert_eq!(signature(&selected), "Animal$Dog.sound");
+        let index = method_ref_index(&puppy, "Animal$Dog", "<init>");
+        let resolved = loader.resolve_method(&puppy, index).unwrap();
+        let selected = loader.select_special(&puppy, &resolved);
+        assert_eq!(signature(&selected), "Animal$Dog.<init>");
+
+        // a Methodref naming a method Dog inherits from Animal or from Pet
+        let mut builder = ClassBuilder::new("Probe");
+        builder.public_static_method("probe", "()V", |body| {
+            for name in ["legs", "cuteness", "missing"] {
+                let method = body.constant_pool.method_ref("Animal$Dog", name, "()I")?;
+                body.code.emit(Instruction::Invokestatic(method));
+            }
+            let method = body
+                .constant_pool
+                .interface_method_ref("Animal", "legs", "()I")?;
+            body.code
+                .emit(Instruction::Invokestatic(method))
+                .emit(Instruction::Return);
+            Ok(())
+        });
+        let probe = builder.build().unwrap().to_bytes().unwrap();
+        let probe = loader.define(&probe).unwrap();
+        let resolve =
+            |name| loader.resolve_method(&probe, method_ref_index(&probe, "Animal$Dog", name));
+        assert_eq!(signature(&resolve("legs").unwrap()), "Animal.legs");
+        assert_eq!(
+            signature(&resolve("cuteness").unwrap()),
+            "Animal$Pet.cuteness"
+        );
+        assert_eq!(
+            resolve("missing").unwrap_err(),
+            LinkageError::NoSuchMethod {
+                class: "Animal$Dog".to_string(),
+                name: "missing".to_string(),
+                descriptor: "()I".to_string()
+            }
+        );
+        // an InterfaceMethodref naming a class
+        let index = method_ref_index(&probe, "Animal", "legs");
+        assert_eq!(
+            loader.resolve_method(&probe, index).unwrap_err(),
+            LinkageError::IncompatibleClassChange("Animal".to_string())
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let loader = loader();
+        assert_eq!(
+            loader.load("Missing").unwrap_err(),
+            LinkageError::ClassNotFound("Missing".to_string())
+        );
+        let probe = loader
+            .define(&probe_class("Probe", ("Animal", "missing", "I")))
+            .unwrap();
+        let index = field_ref_index(&probe, "Animal", "missing");
+        assert_eq!(
+            loader.resolve_field(&probe, index).unwrap_err(),
+            LinkageError::NoSuchField {
+                class: "Animal".to_string(),
+                name: "missing".to_string(),
+                descriptor: "I".to_string(),
+            }
+        );
+        assert_eq!(
+            loader
+                .define(&probe_class("Probe", ("Animal", "legs", "I")))
+                .unwrap_err(),
+            LinkageError::DuplicateClass("Probe".to_string())
+        );
+
+        let mut builder = ClassBuilder::new("Bad");
+        builder.super_class("Animal$Pet");
+        let data = builder.build().unwrap().to_bytes().unwrap();
+        assert_eq!(
+            loader.define(&data).unwrap_err(),
+            LinkageError::IncompatibleClassChange("Bad".to_string())
+        );
+        let mut builder = ClassBuilder::new("Bad");
+        builder.interface("Animal");
+        let data = builder.build().unwrap().to_bytes().unwrap();
+        assert_eq!(
+            loader.define(&data).unwrap_err(),
+            LinkageError::IncompatibleClassChange("Bad".to_string())
+        );
+    }
+
+    #[test]
+    fn test_circularity() {
+        let directory = env::temp_dir().join(format!("rj-loader-test-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        for (name, super_name, file) in [
+            ("First", "Second", "First"),
+            ("Second", "First", "Second"),
+            ("First", "java/lang/Object", "Renamed"),
+        ] {
+            let mut builder = ClassBuilder::new(name);
+            builder.super_class(super_name);
+            let data = builder.build().unwrap().to_bytes().unwrap();
+            fs::write(directory.join(format!("{file}.class")), data).unwrap();
+        }
+        let loader = ClassLoader::new(ClassPath::parse(directory.to_str().unwrap()).unwrap());
+        let first = loader.load("First");
+        let renamed = loader.load("Renamed");
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(
+            first.unwrap_err(),
+            LinkageError::ClassCircularity("First".to_string())
+        );
+        assert_eq!(
+            renamed.unwrap_err(),
+            LinkageError::WrongName {
+                expected: "Renamed".to_string(),
+                found: "First".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use std::fmt;
+
+use super::exception::UncaughtException;
+use crate::archive::ClassPathError;
+use crate::asm::InstructionParseError;
+use crate::class::{ClassParseError, ConstantPoolError};
+
+// an error loading, linking or resolving a class, named for the java error
+// it corresponds to
+#[derive(Debug, PartialEq)]
+pub enum LinkageError {
+    ClassNotFound(String),
+    ClassPathError(ClassPathError),
+    ClassFormatError(String, ClassParseError),
+    ConstantPoolError(ConstantPoolError),
+    // a class file found for one name that defines another
+    WrongName {
+        expected: String,
+        found: String,
+    },
+    // a class defined twice
+    DuplicateClass(String),
+    // a class that is its own superclass or superinterface
+    ClassCircularity(String),
+    // a class extending an interface, or implementing a class
+    IncompatibleClassChange(String),
+    // a new of an interface or abstract class
+    Instantiation(String),
+    NoSuchField {
+        class: String,
+        name: String,
+        descriptor: String,
+    },
+    NoSuchMethod {
+        class: String,
+        name: String,
+        descriptor: String,
+    },
+    // a native method the interpreter has no implementation of
+    UnsatisfiedLink {
+        class: String,
+        name: String,
+        descriptor: String,
+    },
+    // an invocation selecting an abstract method, by its class and name
+    AbstractMethod {
+        class: String,
+        name: String,
+    },
+}
+
+impl From<ClassPathError> for LinkageError {
+    fn from(error: ClassPathError) -> Self {
+        LinkageError::ClassPathError(error)
+    }
+}
+
+impl From<ConstantPoolError> for LinkageError {
+    fn from(error: ConstantPoolError) -> Self {
+        LinkageError::ConstantPoolError(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ExecutionError {
+    // an abstract or native method, or one whose Code attribute was left raw
+    NoCode,
+    LinkageError(LinkageError),
+    ConstantPoolError(ConstantPoolError),
+    InvalidMethodDescriptor,
+    // arguments that don't match the method's parameters
+    InvalidArguments,
+    InvalidCode(InstructionParseError),
+    // an instruction popping more than the stack holds, by pc
+    StackUnderflow(u32),
+    // an instruction finding a value of the wrong type on the stack or in a local
+    TypeMismatch(u32),
+    InvalidLocal {
+        pc: u32,
+        index: u16,
+    },
+    InvalidBranchTarget(u32),
+    // an ldc of a constant that isn't loadable as a value
+    InvalidConstant {
+        pc: u32,
+        index: u16,
+    },
+    // integer division or remainder by zero
+    DivisionByZero(u32),
+    NullPointer(u32),
+    ArrayIndexOutOfBounds {
+        pc: u32,
+        index: i32,
+        length: usize,
+    },
+    NegativeArraySize {
+        pc: u32,
+        size: i32,
+    },
+    // a checkcast of an object not of the class it names
+    ClassCast {
+        pc: u32,
+        class: String,
+        target: String,
+    },
+    // an invocation nesting deeper than the interpreter allows, by its pc
+    StackOverflow(u32),
+    // an exception thrown out of the method the interpreter was running
+    Uncaught(UncaughtException),
+    // a newarray of an element type other than the eight it has codes for
+    InvalidArrayType {
+        pc: u32,
+        atype: u8,
+    },
+    UnsupportedInstruction {
+        pc: u32,
+        mnemonic: &'static str,
+    },
+    // the last instruction continues past the end of the code
+    FallsOffEnd(u32),
+}
+
+impl From<ConstantPoolError> for ExecutionError {
+    fn from(error: ConstantPoolError) -> Self {
+        ExecutionError::ConstantPoolError(error)
+    }
+}
+
+impl From<InstructionParseError> for ExecutionError {
+    fn from(error: InstructionParseError) -> Self {
+        ExecutionError::InvalidCode(error)
+    }
+}
+
+impl From<LinkageError> for ExecutionError {
+    fn from(error: LinkageError) -> Self {
+        ExecutionError::LinkageError(error)
+    }
+}
+
+// like the messages of the java errors, with classes by internal name
+impl fmt::Display for LinkageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkageError::ClassNotFound(name) => write!(f, "class {name} not found"),
+            LinkageError::ClassPathError(error) => write!(f, "{error}"),
+            LinkageError::ClassFormatError(name, error) => write!(f, "{name}: {error}"),
+            LinkageError::ConstantPoolError(error) => write!(f, "{error}"),
+            LinkageError::WrongName { expected, found } => {
+                write!(f, "{expected} (wrong name: {found})")
+            }
+            LinkageError::DuplicateClass(name) => write!(f, "class {name} defined twice"),
+            LinkageError::ClassCircularity(name) => {
+                write!(f, "class {name} is its own superclass or superinterface")
+            }
+            LinkageError::IncompatibleClassChange(name) => {
+                write!(f, "incompatible class change: {name}")
+            }
+            LinkageError::Instantiation(name) => write!(f, "can't instantiate {name}"),
+            LinkageError::NoSuchField {
+                class,
+                name,
+                descriptor,
+            } => write!(f, "no field {class}.{name}:{descriptor}"),
+            LinkageError::NoSuchMethod {
+                class,
+                name,
+                descriptor,
+            } => write!(f, "no method {class}.{name}{descriptor}"),
+            LinkageError::UnsatisfiedLink {
+                class,
+                name,
+                descriptor,
+            } => write!(f, "no implementation of native {class}.{name}{descriptor}"),
+            LinkageError::AbstractMethod { class, name } => {
+                write!(f, "{class}.{name} is abstract")
+            }
+        }
+    }
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecutionError::NoCode => write!(f, "the method has no code"),
+            ExecutionError::LinkageError(error) => write!(f, "{error}"),
+            ExecutionError::ConstantPoolError(error) => write!(f, "{error}"),
+            ExecutionError::InvalidMethodDescriptor => write!(f, "invalid method descriptor"),
+            ExecutionError::InvalidArguments => {
+                write!(f, "arguments that don't match the method's parameters")
+            }
+            ExecutionError::InvalidCode(error) => write!(f, "{error}"),
+            ExecutionError::StackUnderflow(pc) => write!(f, "stack underflow at pc {pc}"),
+            ExecutionError::TypeMismatch(pc) => write!(f, "type mismatch at pc {pc}"),
+            ExecutionError::InvalidLocal { pc, index } => {
+                write!(f, "invalid local {index} at pc {pc}")
+            }
+            ExecutionError::InvalidBranchTarget(pc) => {
+                write!(f, "invalid branch target from pc {pc}")
+            }
+            ExecutionError::InvalidConstant { pc, index } => {
+                write!(f, "#{index} isn't loadable, at pc {pc}")
+            }
+            ExecutionError::DivisionByZero(pc) => write!(f, "/ by zero at pc {pc}"),
+            ExecutionError::NullPointer(pc) => write!(f, "null pointer at pc {pc}"),
+            ExecutionError::ArrayIndexOutOfBounds { pc, index, length } => write!(
+                f,
+                "index {index} out of bounds for length {length} at pc {pc}"
+            ),
+            ExecutionError::NegativeArraySize { pc, size } => {
+                write!(f, "negative array size {size} at pc {pc}")
+            }
+            ExecutionError::ClassCast { pc, class, target } => {
+                write!(f, "{class} can't be cast to {target} at pc {pc}")
+            }
+            ExecutionError::StackOverflow(pc) => write!(f, "stack overflow at pc {pc}"),
+            ExecutionError::Uncaught(exception) => write!(f, "uncaught {exception}"),
+            ExecutionError::InvalidArrayType { pc, atype } => {
+                write!(f, "invalid array type {atype} at pc {pc}")
+            }
+            ExecutionError::UnsupportedInstruction { pc, mnemonic } => {
+                write!(f, "unsupported instruction {mnemonic} at pc {pc}")
+            }
+            ExecutionError::FallsOffEnd(pc) => write!(f, "code falls off the end at pc {pc}"),
+        }
+    }
+}
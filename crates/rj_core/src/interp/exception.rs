@@ -0,0 +1,50 @@
+use std::fmt;
+
+use super::value::ObjectRef;
+
+// a method an exception was thrown through, at the line it was running when
+// the source file and line numbers are known
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackTraceElement {
+    pub class: String,
+    pub method: String,
+    pub file: Option<String>,
+    pub line: Option<u16>,
+}
+
+// `Thrower.fail(Thrower.java:5)`, as java prints them
+impl fmt::Display for StackTraceElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.class.replace('/', "."), self.method)?;
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "({file}:{line})"),
+            (Some(file), None) => write!(f, "({file})"),
+            (None, _) => write!(f, "(Unknown Source)"),
+        }
+    }
+}
+
+// an exception no method caught, with the stack trace from where it was
+// first thrown, innermost method first
+#[derive(Debug, Clone, PartialEq)]
+pub struct UncaughtException {
+    pub exception: ObjectRef,
+    pub class: String,
+    pub message: Option<String>,
+    pub stack_trace: Vec<StackTraceElement>,
+}
+
+// the class and message, then a line for each method, like
+// Throwable.printStackTrace
+impl fmt::Display for UncaughtException {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.class.replace('/', "."))?;
+        if let Some(message) = &self.message {
+            write!(f, ": {message}")?;
+        }
+        for element in &self.stack_trace {
+            write!(f, "\n\tat {element}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use super::error::ExecutionError;
+use super::value::{ObjectRef, Value};
+
+// a method's local variables and operand stack, with the pc of the instruction
+// being executed for errors
+#[derive(Debug, PartialEq)]
+pub struct Frame {
+    pub pc: u32,
+    // the second slot of a long or double is None, as is any slot not yet
+    // stored to
+    pub locals: Vec<Option<Value>>,
+    pub stack: Vec<Value>,
+}
+
+impl Frame {
+    // a frame with the arguments in the first local variable slots
+    pub fn new(max_locals: u16, args: &[Value]) -> Result<Self, ExecutionError> {
+        let mut frame = Frame {
+            pc: 0,
+            locals: vec![None; max_locals as usize],
+            stack: Vec::new(),
+        };
+        let mut index = 0;
+        for arg in args {
+            frame
+                .set_local(index, *arg)
+                .map_err(|_| ExecutionError::InvalidArguments)?;
+            index += if arg.is_wide() { 2 } else { 1 };
+        }
+        Ok(frame)
+    }
+
+    pub fn push(&mut self, value: Value) {
+        self.stack.push(value);
+    }
+
+    pub fn pop(&mut self) -> Result<Value, ExecutionError> {
+        self.stack
+            .pop()
+            .ok_or(ExecutionError::StackUnderflow(self.pc))
+    }
+
+    fn pop_as<T>(&mut self, convert: fn(&Value) -> Option<T>) -> Result<T, ExecutionError> {
+        let value = self.pop()?;
+        convert(&value).ok_or(ExecutionError::TypeMismatch(self.pc))
+    }
+
+    pub fn pop_int(&mut self) -> Result<i32, ExecutionError> {
+        self.pop_as(Value::as_int)
+    }
+
+    pub fn pop_long(&mut self) -> Result<i64, ExecutionError> {
+        self.pop_as(Value::as_long)
+    }
+
+    pub fn pop_float(&mut self) -> Result<f32, ExecutionError> {
+        self.pop_as(Value::as_float)
+    }
+
+    pub fn pop_double(&mut self) -> Result<f64, ExecutionError> {
+        self.pop_as(Value::as_double)
+    }
+
+    pub fn pop_reference(&mut self) -> Result<Option<ObjectRef>, ExecutionError> {
+        self.pop_as(Value::as_reference)
+    }
+
+    // a reference that mustn't be null, as for getfield or arraylength
+    pub fn pop_object(&mut self) -> Result<ObjectRef, ExecutionError> {
+        self.pop_reference()?
+            .ok_or(ExecutionError::NullPointer(self.pc))
+    }
+
+    // pops values totalling `words` stack words, in the order they were
+    // pushed; a long or double can't be split, so it has to fit exactly
+    pub fn pop_words(&mut self, words: usize) -> Result<Vec<Value>, ExecutionError> {
+        let mut values = Vec::new();
+        let mut popped = 0;
+        while popped < words {
+            let value = self.pop()?;
+            popped += if value.is_wide() { 2 } else { 1 };
+            values.push(value);
+        }
+        if popped != words {
+            return Err(ExecutionError::TypeMismatch(self.pc));
+        }
+        values.reverse();
+        Ok(values)
+    }
+
+    // the dup family: copies the top `words` stack words, inserting the
+    // copy `below` words further down
+    pub fn dup_words(&mut self, words: usize, below: usize) -> Result<(), ExecutionError> {
+        let top = self.pop_words(words)?;
+        let under = self.pop_words(below)?;
+        self.stack.extend(&top);
+        self.stack.extend(under);
+        self.stack.extend(top);
+        Ok(())
+    }
+
+    pub fn local(&self, index: u16) -> Result<Value, ExecutionError> {
+        self.locals
+            .get(index as usize)
+            .copied()
+            .flatten()
+            .ok_or(ExecutionError::InvalidLocal { pc: self.pc, index })
+    }
+
+    // stores a value, invalidating a long or double whose second half it
+    // overwrites
+    pub fn set_local(&mut self, index: u16, value: Value) -> Result<(), ExecutionError> {
+        let invalid = ExecutionError::InvalidLocal { pc: self.pc, index };
+        let slot = index as usize;
+        let end = slot + if value.is_wide() { 2 } else { 1 };
+        if end > self.locals.len() {
+            return Err(invalid);
+        }
+        if slot > 0 && self.locals[slot - 1].is_some_and(|value| value.is_wide()) {
+            self.locals[slot - 1] = None;
+        }
+        self.locals[slot] = Some(value);
+        if value.is_wide() {
+            self.locals[slot + 1] = None;
+        }
+        Ok(())
+    }
+}
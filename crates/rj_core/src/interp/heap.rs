@@ -0,0 +1,88 @@
+use std::rc::Rc;
+
+use super::class_loader::RuntimeClass;
+use super::value::{ObjectRef, Value};
+
+#[derive(Debug)]
+pub enum Object {
+    // an instance's fields, by the slots its class lays them out in
+    Instance {
+        class: Rc<RuntimeClass>,
+        fields: Vec<Value>,
+    },
+    // `class` is the array's descriptor, like `[I` or `[Ljava/lang/String;`.
+    // booleans, bytes, chars and shorts are kept as ints
+    Array {
+        class: String,
+        elements: Vec<Value>,
+    },
+}
+
+impl Object {
+    // the internal name of the object's class, or the descriptor of an array
+    pub fn class_name(&self) -> &str {
+        match self {
+            Object::Instance { class, .. } => &class.name,
+            Object::Array { class, .. } => class,
+        }
+    }
+}
+
+// the objects allocated so far, which live as long as the heap does
+#[derive(Debug, Default)]
+pub struct Heap {
+    objects: Vec<Object>,
+}
+
+impl Heap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    fn push(&mut self, object: Object) -> ObjectRef {
+        self.objects.push(object);
+        ObjectRef(self.objects.len() - 1)
+    }
+
+    // a new instance of `class` with its fields at their default values
+    pub fn allocate(&mut self, class: &Rc<RuntimeClass>) -> ObjectRef {
+        self.push(Object::Instance {
+            class: class.clone(),
+            fields: class.field_defaults.clone(),
+        })
+    }
+
+    // a new array of the array class `class` with `length` elements at the
+    // default value of its component type
+    pub fn allocate_array(&mut self, class: &str, length: usize) -> ObjectRef {
+        let component = class.strip_prefix('[').unwrap_or_default();
+        self.push(Object::Array {
+            class: class.to_string(),
+            elements: vec![Value::default_for(component); length],
+        })
+    }
+
+    // an array holding `elements`, which should be of its component type
+    pub fn allocate_array_of(&mut self, class: &str, elements: Vec<Value>) -> ObjectRef {
+        self.push(Object::Array {
+            class: class.to_string(),
+            elements,
+        })
+    }
+
+    pub fn get(&self, reference: ObjectRef) -> &Object {
+        &self.objects[reference.0]
+    }
+
+    pub fn get_mut(&mut self, reference: ObjectRef) -> &mut Object {
+        &mut self.objects[reference.0]
+    }
+}
@@ -0,0 +1,1912 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use super::builtins::Intrinsic;
+use super::class_loader::{numeric_constant, ClassLoader, ResolvedMethod, RuntimeClass};
+use super::error::{ExecutionError, LinkageError};
+use super::exception::{StackTraceElement, UncaughtException};
+use super::frame::Frame;
+use super::heap::{Heap, Object};
+use super::value::{ObjectRef, Value};
+use crate::archive::ClassPath;
+use crate::asm::{disassemble, Instruction};
+use crate::class::{
+    Attribute, ClassAccessFlags, Constant, ConstantPool, FieldAccessFlags, MethodAccessFlags,
+    MethodDescriptor,
+};
+
+// how deeply invocations can nest before a StackOverflow
+const MAX_DEPTH: usize = 1024;
+
+// runs methods of the classes a ClassLoader loads, allocating objects on a
+// heap of its own
+pub struct Interpreter {
+    pub loader: ClassLoader,
+    pub heap: Heap,
+    // where System.out writes
+    pub out: Box<dyn Write>,
+    // the String for each literal loaded so far
+    strings: HashMap<String, ObjectRef>,
+    // the stack trace of each exception thrown so far, from where it was
+    // first thrown, so rethrowing it keeps the trace
+    stack_traces: HashMap<ObjectRef, Vec<StackTraceElement>>,
+    // the number of methods being executed
+    depth: usize,
+}
+
+// a method being executed, with the index of its next instruction
+struct Activation {
+    class: Rc<RuntimeClass>,
+    method: usize,
+    instructions: Vec<(u32, Instruction)>,
+    frame: Frame,
+    next: usize,
+}
+
+impl Activation {
+    // checks the arguments against the method's parameters, taking an
+    // instance method's receiver as the first
+    fn new(class: &Rc<RuntimeClass>, index: usize, args: &[Value]) -> Result<Self, ExecutionError> {
+        let method = class.method(index);
+        let code = method.code().ok_or(ExecutionError::NoCode)?;
+        let descriptor = class
+            .classfile
+            .constant_pool
+            .utf8(method.descriptor_index)?;
+        let descriptor = MethodDescriptor::parse(descriptor.as_bytes())
+            .map_err(|_| ExecutionError::InvalidMethodDescriptor)?;
+        let parameters = match method.access_flags.contains(MethodAccessFlags::STATIC) {
+            true => args,
+            false => match args.split_first() {
+                Some((Value::Reference(Some(_)), parameters)) => parameters,
+                _ => return Err(ExecutionError::InvalidArguments),
+            },
+        };
+        if parameters.len() != descriptor.parameters.len()
+            || !parameters
+                .iter()
+                .zip(&descriptor.parameters)
+                .all(|(arg, parameter)| arg.is_assignable_to(parameter))
+        {
+            return Err(ExecutionError::InvalidArguments);
+        }
+        Ok(Activation {
+            class: class.clone(),
+            method: index,
+            instructions: disassemble(&code.code).collect::<Result<Vec<_>, _>>()?,
+            frame: Frame::new(code.max_locals, args)?,
+            next: 0,
+        })
+    }
+
+    // where in its source the method is, by the LineNumberTable entry
+    // starting last at or before the pc
+    fn stack_trace_element(&self) -> StackTraceElement {
+        let classfile = &self.class.classfile;
+        let pool = &classfile.constant_pool;
+        let file = classfile
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::SourceFile(source_file) => pool.utf8(source_file.sourcefile_index).ok(),
+                _ => None,
+            });
+        let pc = self.frame.pc;
+        let line = self
+            .class
+            .method(self.method)
+            .code()
+            .into_iter()
+            .flat_map(|code| &code.attributes)
+            .filter_map(|attribute| match attribute {
+                Attribute::LineNumberTable(table) => Some(&table.line_number_table),
+                _ => None,
+            })
+            .flatten()
+            .filter(|entry| entry.start_pc as u32 <= pc)
+            .max_by_key(|entry| entry.start_pc)
+            .map(|entry| entry.line_number);
+        StackTraceElement {
+            class: self.class.name.clone(),
+            method: self.class.method_signature(self.method).0.into_owned(),
+            file: file.map(|file| file.into_owned()),
+            line,
+        }
+    }
+}
+
+// what running an instruction leaves the interpreter to do
+enum Step {
+    Next,
+    // run a method with these arguments
+    Invoke(ResolvedMethod, Vec<Value>),
+    Return(Option<Value>),
+    // unwind to the handler for an exception
+    Throw(ObjectRef),
+}
+
+impl Interpreter {
+    pub fn new(class_path: ClassPath) -> Self {
+        Interpreter {
+            loader: ClassLoader::new(class_path),
+            heap: Heap::new(),
+            out: Box::new(io::stdout()),
+            strings: HashMap::new(),
+            stack_traces: HashMap::new(),
+            depth: 0,
+        }
+    }
+
+    // a new String of `text`
+    pub fn new_string(&mut self, text: &str) -> Result<ObjectRef, ExecutionError> {
+        let class = self.loader.load("java/lang/String")?;
+        let slot = class
+            .field_slot("value", "[C")
+            .ok_or_else(|| LinkageError::NoSuchField {
+                class: class.name.clone(),
+                name: "value".to_string(),
+                descriptor: "[C".to_string(),
+            })?;
+        self.initialize(&class)?;
+        let chars = text.encode_utf16().map(|char| Value::Int(char as i32));
+        let value = self.heap.allocate_array_of("[C", chars.collect());
+        let string = self.heap.allocate(&class);
+        if let Object::Instance { fields, .. } = self.heap.get_mut(string) {
+            fields[slot.slot] = Value::Reference(Some(value));
+        }
+        Ok(string)
+    }
+
+    // the String for a literal, the same one each time its text is loaded
+    pub fn intern(&mut self, text: &str) -> Result<ObjectRef, ExecutionError> {
+        if let Some(string) = self.strings.get(text) {
+            return Ok(*string);
+        }
+        let string = self.new_string(text)?;
+        self.strings.insert(text.to_string(), string);
+        Ok(string)
+    }
+
+    // the text of a String, or None if `string` isn't one
+    pub fn string_value(&self, string: ObjectRef) -> Option<String> {
+        let Object::Instance { class, fields } = self.heap.get(string) else {
+            return None;
+        };
+        let slot = class.field_slot("value", "[C")?;
+        let Value::Reference(Some(value)) = fields.get(slot.slot)? else {
+            return None;
+        };
+        let Object::Array { elements, .. } = self.heap.get(*value) else {
+            return None;
+        };
+        let chars = elements
+            .iter()
+            .map(|element| element.as_int().unwrap_or_default() as u16);
+        Some(String::from_utf16_lossy(&chars.collect::<Vec<_>>()))
+    }
+
+    // runs a static method of the class named `class`, returning what it
+    // returns, or None for a void method
+    pub fn invoke_static(
+        &mut self,
+        class: &str,
+        name: &str,
+        descriptor: &str,
+        args: &[Value],
+    ) -> Result<Option<Value>, ExecutionError> {
+        let class = self.loader.load(class)?;
+        let method =
+            class
+                .find_method(name, descriptor)
+                .ok_or_else(|| LinkageError::NoSuchMethod {
+                    class: class.name.clone(),
+                    name: name.to_string(),
+                    descriptor: descriptor.to_string(),
+                })?;
+        if !class
+            .method(method)
+            .access_flags
+            .contains(MethodAccessFlags::STATIC)
+        {
+            return Err(ExecutionError::InvalidArguments);
+        }
+        self.initialize(&class)?;
+        self.execute(&class, method, args)
+    }
+
+    // runs a class's static initializer the first time the class is used,
+    // after its superclass's (JVMS 5.5)
+    pub fn initialize(&mut self, class: &Rc<RuntimeClass>) -> Result<(), ExecutionError> {
+        if !class.start_initialization() {
+            return Ok(());
+        }
+        if let Some(super_class) = &class.super_class {
+            self.initialize(super_class)?;
+        }
+        // static final Strings with a ConstantValue start as it
+        let pool = &class.classfile.constant_pool;
+        for field in &class.classfile.fields {
+            if !field.access_flags.contains(FieldAccessFlags::STATIC) {
+                continue;
+            }
+            for attribute in &field.attributes {
+                let Attribute::ConstantValue(value) = attribute else {
+                    continue;
+                };
+                let Some(Constant::String { string_index }) = pool.get(value.constantvalue_index)
+                else {
+                    continue;
+                };
+                let name = pool.utf8(field.name_index)?;
+                let descriptor = pool.utf8(field.descriptor_index)?;
+                if let Some(slot) = class.field_slot(&name, &descriptor) {
+                    let string = self.intern(&pool.utf8(*string_index)?)?;
+                    class.set_static_value(slot.slot, Value::Reference(Some(string)));
+                }
+            }
+        }
+        match class.find_method("<clinit>", "()V") {
+            Some(method) => self.execute(class, method, &[]).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    // runs one of a class's methods, by its index in the class's methods.
+    // an instance method takes its receiver as the first argument
+    pub fn execute(
+        &mut self,
+        class: &Rc<RuntimeClass>,
+        method: usize,
+        args: &[Value],
+    ) -> Result<Option<Value>, ExecutionError> {
+        if class
+            .method(method)
+            .access_flags
+            .contains(MethodAccessFlags::NATIVE)
+        {
+            return self.call_native(class, method, args);
+        }
+        let activation = Activation::new(class, method, args)?;
+        let depth = self.depth;
+        self.depth += 1;
+        let result = self.run(activation);
+        self.depth = depth;
+        result
+    }
+
+    // runs a native method by the interpreter's implementation of it
+    fn call_native(
+        &mut self,
+        class: &RuntimeClass,
+        method: usize,
+        args: &[Value],
+    ) -> Result<Option<Value>, ExecutionError> {
+        let (name, descriptor) = class.method_signature(method);
+        let intrinsic = Intrinsic::find(&class.name, &name, &descriptor).ok_or_else(|| {
+            LinkageError::UnsatisfiedLink {
+                class: class.name.clone(),
+                name: name.to_string(),
+                descriptor: descriptor.to_string(),
+            }
+        })?;
+        intrinsic.call(self, args)
+    }
+
+    // pops an invoke instruction's arguments, and its receiver unless it's
+    // invokestatic, for the method it runs: the one the receiver's class
+    // selects for invokevirtual and invokeinterface, and `method` otherwise
+    fn invoke(
+        &mut self,
+        frame: &mut Frame,
+        instruction: &Instruction,
+        method: ResolvedMethod,
+    ) -> Result<Step, ExecutionError> {
+        let (_, descriptor) = method.class.method_signature(method.method);
+        let descriptor = MethodDescriptor::parse(descriptor.as_bytes())
+            .map_err(|_| ExecutionError::InvalidMethodDescriptor)?;
+        let mut args = (0..descriptor.parameters.len())
+            .map(|_| frame.pop())
+            .collect::<Result<Vec<_>, _>>()?;
+        let selected = match instruction {
+            Instruction::Invokestatic(_) => method,
+            _ => {
+                let receiver = frame.pop_object()?;
+                args.push(Value::Reference(Some(receiver)));
+                let class = match self.heap.get(receiver) {
+                    Object::Instance { class, .. } => class.clone(),
+                    // arrays have the methods of Object
+                    Object::Array { .. } => self.loader.load("java/lang/Object")?,
+                };
+                match instruction {
+                    Instruction::Invokespecial(_) => method,
+                    _ => self.loader.select_virtual(&class, &method)?,
+                }
+            }
+        };
+        args.reverse();
+        if selected
+            .access_flags()
+            .contains(MethodAccessFlags::ABSTRACT)
+        {
+            let (name, _) = selected.class.method_signature(selected.method);
+            return Err(LinkageError::AbstractMethod {
+                class: selected.class.name.clone(),
+                name: name.into_owned(),
+            }
+            .into());
+        }
+        Ok(Step::Invoke(selected, args))
+    }
+
+    // whether an object of the class or array class `class` can be cast to
+    // `target`
+    fn is_assignable(&self, class: &str, target: &str) -> Result<bool, ExecutionError> {
+        if class == target {
+            return Ok(true);
+        }
+        let Some(component) = class.strip_prefix('[') else {
+            return Ok(self.loader.load(class)?.is_subclass_of(target));
+        };
+        match target.strip_prefix('[') {
+            // primitive components have to be the same, and reference
+            // components assignable
+            Some(target) => match (reference_component(component), reference_component(target)) {
+                (Some(component), Some(target)) => self.is_assignable(component, target),
+                _ => Ok(false),
+            },
+            None => Ok(matches!(
+                target,
+                "java/lang/Object" | "java/lang/Cloneable" | "java/io/Serializable"
+            )),
+        }
+    }
+
+    // runs methods until the one `activation` is for returns, pushing an
+    // activation for each method invoked instead of recursing, so how deep
+    // java code can go doesn't depend on the native stack
+    fn run(&mut self, activation: Activation) -> Result<Option<Value>, ExecutionError> {
+        let mut activations = vec![activation];
+        loop {
+            let activation = activations.last_mut().unwrap();
+            match self.step(activation)? {
+                Step::Next => {}
+                Step::Invoke(method, args) => {
+                    if self.depth >= MAX_DEPTH {
+                        return Err(ExecutionError::StackOverflow(activation.frame.pc));
+                    }
+                    if method.access_flags().contains(MethodAccessFlags::NATIVE) {
+                        let value = self.call_native(&method.class, method.method, &args)?;
+                        if let (Some(caller), Some(value)) = (activations.last_mut(), value) {
+                            caller.frame.push(value);
+                        }
+                        continue;
+                    }
+                    let callee = Activation::new(&method.class, method.method, &args)?;
+                    activations.push(callee);
+                    self.depth += 1;
+                }
+                Step::Return(value) => {
+                    activations.pop();
+                    self.depth -= 1;
+                    match (activations.last_mut(), value) {
+                        (None, value) => return Ok(value),
+                        (Some(caller), Some(value)) => caller.frame.push(value),
+                        (Some(_), None) => {}
+                    }
+                }
+                Step::Throw(exception) => {
+                    self.stack_traces.entry(exception).or_insert_with(|| {
+                        let activations = activations.iter().rev();
+                        activations.map(Activation::stack_trace_element).collect()
+                    });
+                    // pops methods until one has a handler for the exception
+                    loop {
+                        let Some(activation) = activations.last_mut() else {
+                            return Err(ExecutionError::Uncaught(self.uncaught(exception)));
+                        };
+                        if let Some(handler) = self.find_handler(activation, exception)? {
+                            activation.frame.stack.clear();
+                            activation.frame.push(Value::Reference(Some(exception)));
+                            activation.next = handler;
+                            break;
+                        }
+                        activations.pop();
+                        self.depth -= 1;
+                    }
+                }
+            }
+        }
+    }
+
+    // the index of the instruction an activation's method handles an
+    // exception at its pc with: the first exception table entry covering
+    // the pc that catches any exception or a superclass of this one's
+    fn find_handler(
+        &self,
+        activation: &Activation,
+        exception: ObjectRef,
+    ) -> Result<Option<usize>, ExecutionError> {
+        let class = &activation.class;
+        let Some(code) = class.method(activation.method).code() else {
+            return Ok(None);
+        };
+        let pc = activation.frame.pc;
+        let thrown = self.heap.get(exception).class_name();
+        for entry in &code.exception_table {
+            if pc < entry.start_pc as u32 || pc >= entry.end_pc as u32 {
+                continue;
+            }
+            let catches = match entry.catch_type {
+                0 => true,
+                index => {
+                    let catch_class = self.loader.resolve_class(class, index)?;
+                    self.is_assignable(thrown, &catch_class.name)?
+                }
+            };
+            if catches {
+                let handler = jump(&activation.instructions, pc, entry.handler_pc as i64)?;
+                return Ok(Some(handler));
+            }
+        }
+        Ok(None)
+    }
+
+    // an exception for reporting, with its message if it has one
+    fn uncaught(&self, exception: ObjectRef) -> UncaughtException {
+        let class = self.heap.get(exception).class_name().to_string();
+        let mut message = None;
+        if let Object::Instance { class, fields } = self.heap.get(exception) {
+            // detailMessage is Throwable's, so look for it up the hierarchy
+            let mut declaring = Some(class);
+            while let Some(current) = declaring {
+                if let Some(slot) = current.field_slot("detailMessage", "Ljava/lang/String;") {
+                    if let Some(Value::Reference(Some(string))) = fields.get(slot.slot) {
+                        message = self.string_value(*string);
+                    }
+                    break;
+                }
+                declaring = current.super_class.as_ref();
+            }
+        }
+        UncaughtException {
+            exception,
+            class,
+            message,
+            stack_trace: self
+                .stack_traces
+                .get(&exception)
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
+    // runs the next instruction of a method
+    fn step(&mut self, activation: &mut Activation) -> Result<Step, ExecutionError> {
+        let Activation {
+            class,
+            instructions,
+            frame,
+            next: index,
+            ..
+        } = activation;
+        let class: &Rc<RuntimeClass> = class;
+        let instructions: &[(u32, Instruction)] = instructions;
+        let pool = &class.classfile.constant_pool;
+        let (pc, instruction) = match instructions.get(*index) {
+            Some((pc, instruction)) => (*pc, instruction),
+            None => return Err(ExecutionError::FallsOffEnd(frame.pc)),
+        };
+        frame.pc = pc;
+        *index += 1;
+        match instruction {
+            Instruction::Nop => {}
+
+            // constants
+            Instruction::IconstM1 => frame.push(Value::Int(-1)),
+            Instruction::Iconst0 => frame.push(Value::Int(0)),
+            Instruction::Iconst1 => frame.push(Value::Int(1)),
+            Instruction::Iconst2 => frame.push(Value::Int(2)),
+            Instruction::Iconst3 => frame.push(Value::Int(3)),
+            Instruction::Iconst4 => frame.push(Value::Int(4)),
+            Instruction::Iconst5 => frame.push(Value::Int(5)),
+            Instruction::Lconst0 => frame.push(Value::Long(0)),
+            Instruction::Lconst1 => frame.push(Value::Long(1)),
+            Instruction::Fconst0 => frame.push(Value::Float(0.0)),
+            Instruction::Fconst1 => frame.push(Value::Float(1.0)),
+            Instruction::Fconst2 => frame.push(Value::Float(2.0)),
+            Instruction::Dconst0 => frame.push(Value::Double(0.0)),
+            Instruction::Dconst1 => frame.push(Value::Double(1.0)),
+            Instruction::Bipush(value) => frame.push(Value::Int(*value as i32)),
+            Instruction::Sipush(value) => frame.push(Value::Int(*value as i32)),
+            Instruction::Ldc(_) | Instruction::LdcW(_) | Instruction::Ldc2W(_) => {
+                let index = instruction.constant_pool_index().unwrap_or_default();
+                let value = match pool.get(index) {
+                    Some(Constant::String { string_index }) => {
+                        let string = self.intern(&pool.utf8(*string_index)?)?;
+                        Value::Reference(Some(string))
+                    }
+                    _ => load_constant(pool, pc, index)?,
+                };
+                // ldc and ldc_w can't load a long or double, nor ldc2_w an int or float
+                if value.is_wide() != matches!(instruction, Instruction::Ldc2W(_)) {
+                    return Err(ExecutionError::InvalidConstant { pc, index });
+                }
+                frame.push(value);
+            }
+
+            // locals
+            Instruction::Iload(_)
+            | Instruction::WideIload(_)
+            | Instruction::Lload(_)
+            | Instruction::WideLload(_)
+            | Instruction::Fload(_)
+            | Instruction::WideFload(_)
+            | Instruction::Dload(_)
+            | Instruction::WideDload(_)
+            | Instruction::Iload0
+            | Instruction::Iload1
+            | Instruction::Iload2
+            | Instruction::Iload3
+            | Instruction::Lload0
+            | Instruction::Lload1
+            | Instruction::Lload2
+            | Instruction::Lload3
+            | Instruction::Fload0
+            | Instruction::Fload1
+            | Instruction::Fload2
+            | Instruction::Fload3
+            | Instruction::Dload0
+            | Instruction::Dload1
+            | Instruction::Dload2
+            | Instruction::Dload3
+            | Instruction::Aload(_)
+            | Instruction::WideAload(_)
+            | Instruction::Aload0
+            | Instruction::Aload1
+            | Instruction::Aload2
+            | Instruction::Aload3 => {
+                let value = frame.local(instruction.local_index().unwrap_or_default())?;
+                if !loads(instruction, &value) {
+                    return Err(ExecutionError::TypeMismatch(pc));
+                }
+                frame.push(value);
+            }
+            Instruction::Istore(_)
+            | Instruction::WideIstore(_)
+            | Instruction::Lstore(_)
+            | Instruction::WideLstore(_)
+            | Instruction::Fstore(_)
+            | Instruction::WideFstore(_)
+            | Instruction::Dstore(_)
+            | Instruction::WideDstore(_)
+            | Instruction::Istore0
+            | Instruction::Istore1
+            | Instruction::Istore2
+            | Instruction::Istore3
+            | Instruction::Lstore0
+            | Instruction::Lstore1
+            | Instruction::Lstore2
+            | Instruction::Lstore3
+            | Instruction::Fstore0
+            | Instruction::Fstore1
+            | Instruction::Fstore2
+            | Instruction::Fstore3
+            | Instruction::Dstore0
+            | Instruction::Dstore1
+            | Instruction::Dstore2
+            | Instruction::Dstore3
+            | Instruction::Astore(_)
+            | Instruction::WideAstore(_)
+            | Instruction::Astore0
+            | Instruction::Astore1
+            | Instruction::Astore2
+            | Instruction::Astore3 => {
+                let value = frame.pop()?;
+                if !stores(instruction, &value) {
+                    return Err(ExecutionError::TypeMismatch(pc));
+                }
+                frame.set_local(instruction.local_index().unwrap_or_default(), value)?;
+            }
+            Instruction::Iinc(_, delta) => iinc(frame, instruction, *delta as i32)?,
+            Instruction::WideIinc(_, delta) => iinc(frame, instruction, *delta as i32)?,
+
+            // stack
+            Instruction::Pop => drop(frame.pop_words(1)?),
+            Instruction::Pop2 => drop(frame.pop_words(2)?),
+            Instruction::Dup => frame.dup_words(1, 0)?,
+            Instruction::DupX1 => frame.dup_words(1, 1)?,
+            Instruction::DupX2 => frame.dup_words(1, 2)?,
+            Instruction::Dup2 => frame.dup_words(2, 0)?,
+            Instruction::Dup2X1 => frame.dup_words(2, 1)?,
+            Instruction::Dup2X2 => frame.dup_words(2, 2)?,
+            Instruction::Swap => {
+                let top = frame.pop_words(1)?;
+                let under = frame.pop_words(1)?;
+                frame.stack.extend(top);
+                frame.stack.extend(under);
+            }
+
+            // arithmetic
+            Instruction::Iadd => int_op(frame, |a, b| Some(a.wrapping_add(b)))?,
+            Instruction::Isub => int_op(frame, |a, b| Some(a.wrapping_sub(b)))?,
+            Instruction::Imul => int_op(frame, |a, b| Some(a.wrapping_mul(b)))?,
+            Instruction::Idiv => int_op(frame, |a, b| (b != 0).then(|| a.wrapping_div(b)))?,
+            Instruction::Irem => int_op(frame, |a, b| (b != 0).then(|| a.wrapping_rem(b)))?,
+            Instruction::Iand => int_op(frame, |a, b| Some(a & b))?,
+            Instruction::Ior => int_op(frame, |a, b| Some(a | b))?,
+            Instruction::Ixor => int_op(frame, |a, b| Some(a ^ b))?,
+            Instruction::Ishl => int_op(frame, |a, b| Some(a.wrapping_shl(b as u32)))?,
+            Instruction::Ishr => int_op(frame, |a, b| Some(a.wrapping_shr(b as u32)))?,
+            Instruction::Iushr => {
+                int_op(frame, |a, b| Some((a as u32).wrapping_shr(b as u32) as i32))?
+            }
+            Instruction::Ineg => {
+                let value = frame.pop_int()?;
+                frame.push(Value::Int(value.wrapping_neg()));
+            }
+            Instruction::Ladd => long_op(frame, |a, b| Some(a.wrapping_add(b)))?,
+            Instruction::Lsub => long_op(frame, |a, b| Some(a.wrapping_sub(b)))?,
+            Instruction::Lmul => long_op(frame, |a, b| Some(a.wrapping_mul(b)))?,
+            Instruction::Ldiv => long_op(frame, |a, b| (b != 0).then(|| a.wrapping_div(b)))?,
+            Instruction::Lrem => long_op(frame, |a, b| (b != 0).then(|| a.wrapping_rem(b)))?,
+            Instruction::Land => long_op(frame, |a, b| Some(a & b))?,
+            Instruction::Lor => long_op(frame, |a, b| Some(a | b))?,
+            Instruction::Lxor => long_op(frame, |a, b| Some(a ^ b))?,
+            // the shift distance of a long shift is an int
+            Instruction::Lshl | Instruction::Lshr | Instruction::Lushr => {
+                let distance = frame.pop_int()? as u32;
+                let value = frame.pop_long()?;
+                frame.push(Value::Long(match instruction {
+                    Instruction::Lshl => value.wrapping_shl(distance),
+                    Instruction::Lshr => value.wrapping_shr(distance),
+                    _ => (value as u64).wrapping_shr(distance) as i64,
+                }));
+            }
+            Instruction::Lneg => {
+                let value = frame.pop_long()?;
+                frame.push(Value::Long(value.wrapping_neg()));
+            }
+            Instruction::Fadd => float_op(frame, |a, b| a + b)?,
+            Instruction::Fsub => float_op(frame, |a, b| a - b)?,
+            Instruction::Fmul => float_op(frame, |a, b| a * b)?,
+            Instruction::Fdiv => float_op(frame, |a, b| a / b)?,
+            Instruction::Frem => float_op(frame, |a, b| a % b)?,
+            Instruction::Fneg => {
+                let value = frame.pop_float()?;
+                frame.push(Value::Float(-value));
+            }
+            Instruction::Dadd => double_op(frame, |a, b| a + b)?,
+            Instruction::Dsub => double_op(frame, |a, b| a - b)?,
+            Instruction::Dmul => double_op(frame, |a, b| a * b)?,
+            Instruction::Ddiv => double_op(frame, |a, b| a / b)?,
+            Instruction::Drem => double_op(frame, |a, b| a % b)?,
+            Instruction::Dneg => {
+                let value = frame.pop_double()?;
+                frame.push(Value::Double(-value));
+            }
+
+            // conversions; rust's `as` saturates and maps NaN to 0 like java
+            Instruction::I2l => {
+                let value = frame.pop_int()?;
+                frame.push(Value::Long(value as i64));
+            }
+            Instruction::I2f => {
+                let value = frame.pop_int()?;
+                frame.push(Value::Float(value as f32));
+            }
+            Instruction::I2d => {
+                let value = frame.pop_int()?;
+                frame.push(Value::Double(value as f64));
+            }
+            Instruction::I2b => {
+                let value = frame.pop_int()?;
+                frame.push(Value::Int(value as i8 as i32));
+            }
+            Instruction::I2c => {
+                let value = frame.pop_int()?;
+                frame.push(Value::Int(value as u16 as i32));
+            }
+            Instruction::I2s => {
+                let value = frame.pop_int()?;
+                frame.push(Value::Int(value as i16 as i32));
+            }
+            Instruction::L2i => {
+                let value = frame.pop_long()?;
+                frame.push(Value::Int(value as i32));
+            }
+            Instruction::L2f => {
+                let value = frame.pop_long()?;
+                frame.push(Value::Float(value as f32));
+            }
+            Instruction::L2d => {
+                let value = frame.pop_long()?;
+                frame.push(Value::Double(value as f64));
+            }
+            Instruction::F2i => {
+                let value = frame.pop_float()?;
+                frame.push(Value::Int(value as i32));
+            }
+            Instruction::F2l => {
+                let value = frame.pop_float()?;
+                frame.push(Value::Long(value as i64));
+            }
+            Instruction::F2d => {
+                let value = frame.pop_float()?;
+                frame.push(Value::Double(value as f64));
+            }
+            Instruction::D2i => {
+                let value = frame.pop_double()?;
+                frame.push(Value::Int(value as i32));
+            }
+            Instruction::D2l => {
+                let value = frame.pop_double()?;
+                frame.push(Value::Long(value as i64));
+            }
+            Instruction::D2f => {
+                let value = frame.pop_double()?;
+                frame.push(Value::Float(value as f32));
+            }
+
+            // comparisons
+            Instruction::Lcmp => {
+                let b = frame.pop_long()?;
+                let a = frame.pop_long()?;
+                frame.push(Value::Int(a.cmp(&b) as i32));
+            }
+            Instruction::Fcmpl | Instruction::Fcmpg => {
+                let b = frame.pop_float()?;
+                let a = frame.pop_float()?;
+                let nan = if *instruction == Instruction::Fcmpg {
+                    1
+                } else {
+                    -1
+                };
+                frame.push(compare(a, b, nan));
+            }
+            Instruction::Dcmpl | Instruction::Dcmpg => {
+                let b = frame.pop_double()?;
+                let a = frame.pop_double()?;
+                let nan = if *instruction == Instruction::Dcmpg {
+                    1
+                } else {
+                    -1
+                };
+                frame.push(compare(a, b, nan));
+            }
+
+            // branches
+            Instruction::Ifeq(_)
+            | Instruction::Ifne(_)
+            | Instruction::Iflt(_)
+            | Instruction::Ifge(_)
+            | Instruction::Ifgt(_)
+            | Instruction::Ifle(_) => {
+                let value = frame.pop_int()?;
+                if branches(instruction, value.cmp(&0)) {
+                    *index = branch(instructions, pc, instruction)?;
+                }
+            }
+            Instruction::IfIcmpeq(_)
+            | Instruction::IfIcmpne(_)
+            | Instruction::IfIcmplt(_)
+            | Instruction::IfIcmpge(_)
+            | Instruction::IfIcmpgt(_)
+            | Instruction::IfIcmple(_) => {
+                let b = frame.pop_int()?;
+                let a = frame.pop_int()?;
+                if branches(instruction, a.cmp(&b)) {
+                    *index = branch(instructions, pc, instruction)?;
+                }
+            }
+            Instruction::Goto(_) | Instruction::GotoW(_) => {
+                *index = branch(instructions, pc, instruction)?;
+            }
+            Instruction::Tableswitch(default, low, high, offsets) => {
+                let key = frame.pop_int()?;
+                let offset = match (*low..=*high).contains(&key) {
+                    true => offsets[(key as i64 - *low as i64) as usize],
+                    false => *default,
+                };
+                *index = jump(instructions, pc, pc as i64 + offset as i64)?;
+            }
+            Instruction::Lookupswitch(default, pairs) => {
+                let key = frame.pop_int()?;
+                let offset = pairs
+                    .iter()
+                    .find(|(value, _)| *value == key)
+                    .map_or(*default, |(_, offset)| *offset);
+                *index = jump(instructions, pc, pc as i64 + offset as i64)?;
+            }
+            Instruction::IfAcmpeq(_) | Instruction::IfAcmpne(_) => {
+                let b = frame.pop_reference()?;
+                let a = frame.pop_reference()?;
+                if (a == b) == matches!(instruction, Instruction::IfAcmpeq(_)) {
+                    *index = branch(instructions, pc, instruction)?;
+                }
+            }
+            Instruction::Ifnull(_) | Instruction::Ifnonnull(_) => {
+                let value = frame.pop_reference()?;
+                if value.is_none() == matches!(instruction, Instruction::Ifnull(_)) {
+                    *index = branch(instructions, pc, instruction)?;
+                }
+            }
+            Instruction::Jsr(_) | Instruction::JsrW(_) => {
+                let next = pc + instruction.byte_len(pc) as u32;
+                frame.push(Value::ReturnAddress(next));
+                *index = branch(instructions, pc, instruction)?;
+            }
+            Instruction::Ret(_) | Instruction::WideRet(_) => {
+                let local = instruction.local_index().unwrap_or_default();
+                match frame.local(local)? {
+                    Value::ReturnAddress(target) => *index = jump(instructions, pc, target as i64)?,
+                    _ => return Err(ExecutionError::TypeMismatch(pc)),
+                }
+            }
+
+            // objects
+            Instruction::AconstNull => frame.push(Value::NULL),
+            Instruction::Athrow => {
+                let exception = frame.pop_object()?;
+                let is_throwable = match self.heap.get(exception) {
+                    Object::Instance { class, .. } => class.is_subclass_of("java/lang/Throwable"),
+                    Object::Array { .. } => false,
+                };
+                if !is_throwable {
+                    return Err(ExecutionError::TypeMismatch(pc));
+                }
+                return Ok(Step::Throw(exception));
+            }
+            Instruction::New(index) => {
+                let new_class = self.loader.resolve_class(class, *index)?;
+                let flags = new_class.classfile.access_flags;
+                if flags.contains(ClassAccessFlags::INTERFACE)
+                    || flags.contains(ClassAccessFlags::ABSTRACT)
+                {
+                    return Err(LinkageError::Instantiation(new_class.name.clone()).into());
+                }
+                self.initialize(&new_class)?;
+                let object = self.heap.allocate(&new_class);
+                frame.push(Value::Reference(Some(object)));
+            }
+            Instruction::Checkcast(index) | Instruction::Instanceof(index) => {
+                let target = pool.class_name(*index)?;
+                if !target.starts_with('[') {
+                    self.loader.resolve_class(class, *index)?;
+                }
+                let object = frame.pop_reference()?;
+                let is_instance = match object {
+                    Some(object) => {
+                        self.is_assignable(self.heap.get(object).class_name(), &target)?
+                    }
+                    None => false,
+                };
+                match (instruction, object) {
+                    (Instruction::Instanceof(_), _) => frame.push(Value::Int(is_instance as i32)),
+                    // null can be cast to anything
+                    (_, None) => frame.push(Value::NULL),
+                    (_, Some(object)) if is_instance => frame.push(Value::Reference(Some(object))),
+                    (_, Some(object)) => {
+                        return Err(ExecutionError::ClassCast {
+                            pc,
+                            class: self.heap.get(object).class_name().to_string(),
+                            target: target.into_owned(),
+                        })
+                    }
+                }
+            }
+            Instruction::Getfield(index) | Instruction::Putfield(index) => {
+                let field = self.loader.resolve_field(class, *index)?;
+                if field.slot.is_static {
+                    let name = field.class.name.clone();
+                    return Err(LinkageError::IncompatibleClassChange(name).into());
+                }
+                let value = match instruction {
+                    Instruction::Putfield(_) => Some(frame.pop()?),
+                    _ => None,
+                };
+                let object = frame.pop_object()?;
+                let Object::Instance { fields, .. } = self.heap.get_mut(object) else {
+                    return Err(ExecutionError::TypeMismatch(pc));
+                };
+                let slot = fields
+                    .get_mut(field.slot.slot)
+                    .ok_or(ExecutionError::TypeMismatch(pc))?;
+                match value {
+                    Some(value) if value.is_same_kind(slot) => *slot = value,
+                    Some(_) => return Err(ExecutionError::TypeMismatch(pc)),
+                    None => frame.push(*slot),
+                }
+            }
+            Instruction::Getstatic(index) | Instruction::Putstatic(index) => {
+                let field = self.loader.resolve_field(class, *index)?;
+                if !field.slot.is_static {
+                    let name = field.class.name.clone();
+                    return Err(LinkageError::IncompatibleClassChange(name).into());
+                }
+                self.initialize(&field.class)?;
+                let current = field.class.static_value(field.slot.slot);
+                match instruction {
+                    Instruction::Putstatic(_) => {
+                        let value = frame.pop()?;
+                        if !value.is_same_kind(&current) {
+                            return Err(ExecutionError::TypeMismatch(pc));
+                        }
+                        field.class.set_static_value(field.slot.slot, value);
+                    }
+                    _ => frame.push(current),
+                }
+            }
+
+            // arrays
+            Instruction::Newarray(atype) => {
+                let array_class = match atype {
+                    4 => "[Z",
+                    5 => "[C",
+                    6 => "[F",
+                    7 => "[D",
+                    8 => "[B",
+                    9 => "[S",
+                    10 => "[I",
+                    11 => "[J",
+                    _ => return Err(ExecutionError::InvalidArrayType { pc, atype: *atype }),
+                };
+                let length = pop_length(frame)?;
+                let array = self.heap.allocate_array(array_class, length);
+                frame.push(Value::Reference(Some(array)));
+            }
+            Instruction::Anewarray(index) => {
+                let component = pool.class_name(*index)?;
+                let array_class = match component.starts_with('[') {
+                    true => format!("[{component}"),
+                    false => {
+                        self.loader.resolve_class(class, *index)?;
+                        format!("[L{component};")
+                    }
+                };
+                let length = pop_length(frame)?;
+                let array = self.heap.allocate_array(&array_class, length);
+                frame.push(Value::Reference(Some(array)));
+            }
+            Instruction::Multianewarray(index, dimensions) => {
+                let array_class = pool.class_name(*index)?;
+                let array_dimensions = array_class.bytes().take_while(|b| *b == b'[').count();
+                if *dimensions == 0 || *dimensions as usize > array_dimensions {
+                    return Err(ExecutionError::TypeMismatch(pc));
+                }
+                let mut lengths = (0..*dimensions)
+                    .map(|_| pop_length(frame))
+                    .collect::<Result<Vec<_>, _>>()?;
+                lengths.reverse();
+                let array = allocate_multi_array(&mut self.heap, &array_class, &lengths);
+                frame.push(Value::Reference(Some(array)));
+            }
+            Instruction::Arraylength => {
+                let array = frame.pop_object()?;
+                let Object::Array { elements, .. } = self.heap.get(array) else {
+                    return Err(ExecutionError::TypeMismatch(pc));
+                };
+                frame.push(Value::Int(elements.len() as i32));
+            }
+            Instruction::Iaload
+            | Instruction::Laload
+            | Instruction::Faload
+            | Instruction::Daload
+            | Instruction::Aaload
+            | Instruction::Baload
+            | Instruction::Caload
+            | Instruction::Saload => {
+                let element = frame.pop_int()?;
+                let array = frame.pop_object()?;
+                let slot = self.array_element(pc, instruction, array, element)?;
+                frame.push(*slot);
+            }
+            Instruction::Iastore
+            | Instruction::Lastore
+            | Instruction::Fastore
+            | Instruction::Dastore
+            | Instruction::Aastore
+            | Instruction::Bastore
+            | Instruction::Castore
+            | Instruction::Sastore => {
+                let value = frame.pop()?;
+                let element = frame.pop_int()?;
+                let array = frame.pop_object()?;
+                let is_boolean = self.heap.get(array).class_name() == "[Z";
+                let slot = self.array_element(pc, instruction, array, element)?;
+                // narrower elements keep the low bits of the int stored
+                let value = match (instruction, value) {
+                    (Instruction::Bastore, Value::Int(value)) if is_boolean => {
+                        Value::Int(value & 1)
+                    }
+                    (Instruction::Bastore, Value::Int(value)) => Value::Int(value as i8 as i32),
+                    (Instruction::Castore, Value::Int(value)) => Value::Int(value as u16 as i32),
+                    (Instruction::Sastore, Value::Int(value)) => Value::Int(value as i16 as i32),
+                    (_, value) => value,
+                };
+                // the classes of references stored aren't checked
+                if !value.is_same_kind(slot) {
+                    return Err(ExecutionError::TypeMismatch(pc));
+                }
+                *slot = value;
+            }
+
+            // invocations
+            Instruction::Invokestatic(index) => {
+                let method = self.loader.resolve_method(class, *index)?;
+                if !method.access_flags().contains(MethodAccessFlags::STATIC) {
+                    let name = method.class.name.clone();
+                    return Err(LinkageError::IncompatibleClassChange(name).into());
+                }
+                self.initialize(&method.class)?;
+                return self.invoke(frame, instruction, method);
+            }
+            Instruction::Invokespecial(index)
+            | Instruction::Invokevirtual(index)
+            | Instruction::Invokeinterface(index, _, _) => {
+                let mut method = self.loader.resolve_method(class, *index)?;
+                if method.access_flags().contains(MethodAccessFlags::STATIC) {
+                    let name = method.class.name.clone();
+                    return Err(LinkageError::IncompatibleClassChange(name).into());
+                }
+                if let Instruction::Invokespecial(_) = instruction {
+                    method = self.loader.select_special(class, &method);
+                }
+                return self.invoke(frame, instruction, method);
+            }
+
+            // returns
+            Instruction::Ireturn => return Ok(Step::Return(Some(Value::Int(frame.pop_int()?)))),
+            Instruction::Lreturn => return Ok(Step::Return(Some(Value::Long(frame.pop_long()?)))),
+            Instruction::Freturn => {
+                return Ok(Step::Return(Some(Value::Float(frame.pop_float()?))))
+            }
+            Instruction::Dreturn => {
+                return Ok(Step::Return(Some(Value::Double(frame.pop_double()?))))
+            }
+            Instruction::Areturn => {
+                return Ok(Step::Return(Some(Value::Reference(frame.pop_reference()?))))
+            }
+            Instruction::Return => return Ok(Step::Return(None)),
+
+            _ => {
+                return Err(ExecutionError::UnsupportedInstruction {
+                    pc,
+                    mnemonic: instruction.mnemonic(),
+                })
+            }
+        }
+        Ok(Step::Next)
+    }
+
+    // the element of an array an xaload or xastore instruction accesses
+    fn array_element(
+        &mut self,
+        pc: u32,
+        instruction: &Instruction,
+        array: ObjectRef,
+        index: i32,
+    ) -> Result<&mut Value, ExecutionError> {
+        let Object::Array { class, elements } = self.heap.get_mut(array) else {
+            return Err(ExecutionError::TypeMismatch(pc));
+        };
+        let component = class.as_bytes()[1];
+        let accessible = match instruction {
+            Instruction::Iaload | Instruction::Iastore => component == b'I',
+            Instruction::Laload | Instruction::Lastore => component == b'J',
+            Instruction::Faload | Instruction::Fastore => component == b'F',
+            Instruction::Daload | Instruction::Dastore => component == b'D',
+            Instruction::Aaload | Instruction::Aastore => matches!(component, b'L' | b'['),
+            Instruction::Baload | Instruction::Bastore => matches!(component, b'B' | b'Z'),
+            Instruction::Caload | Instruction::Castore => component == b'C',
+            _ => component == b'S',
+        };
+        if !accessible {
+            return Err(ExecutionError::TypeMismatch(pc));
+        }
+        let length = elements.len();
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| elements.get_mut(index))
+            .ok_or(ExecutionError::ArrayIndexOutOfBounds { pc, index, length })
+    }
+}
+
+// the class name of an array component descriptor, or None for a primitive
+// component
+fn reference_component(descriptor: &str) -> Option<&str> {
+    match descriptor.strip_prefix('L') {
+        Some(name) => name.strip_suffix(';'),
+        None => descriptor.starts_with('[').then_some(descriptor),
+    }
+}
+
+// the index of the instruction at `target`
+fn jump(
+    instructions: &[(u32, Instruction)],
+    pc: u32,
+    target: i64,
+) -> Result<usize, ExecutionError> {
+    instructions
+        .binary_search_by_key(&target, |(pc, _)| *pc as i64)
+        .map_err(|_| ExecutionError::InvalidBranchTarget(pc))
+}
+
+// whether an if* or if_icmp* instruction branches, given how its operand
+// compares to zero or to the other operand
+fn branches(instruction: &Instruction, ordering: Ordering) -> bool {
+    match instruction {
+        Instruction::Ifeq(_) | Instruction::IfIcmpeq(_) => ordering.is_eq(),
+        Instruction::Ifne(_) | Instruction::IfIcmpne(_) => ordering.is_ne(),
+        Instruction::Iflt(_) | Instruction::IfIcmplt(_) => ordering.is_lt(),
+        Instruction::Ifge(_) | Instruction::IfIcmpge(_) => ordering.is_ge(),
+        Instruction::Ifgt(_) | Instruction::IfIcmpgt(_) => ordering.is_gt(),
+        Instruction::Ifle(_) | Instruction::IfIcmple(_) => ordering.is_le(),
+        _ => false,
+    }
+}
+
+// fcmp and dcmp push -1, 0 or 1, or `nan` when either operand is NaN
+fn compare<T: PartialOrd>(a: T, b: T, nan: i32) -> Value {
+    Value::Int(match a.partial_cmp(&b) {
+        Some(ordering) => ordering as i32,
+        None => nan,
+    })
+}
+
+fn load_constant(pool: &ConstantPool, pc: u32, index: u16) -> Result<Value, ExecutionError> {
+    numeric_constant(pool, index).ok_or(ExecutionError::InvalidConstant { pc, index })
+}
+
+fn branch(
+    instructions: &[(u32, Instruction)],
+    pc: u32,
+    instruction: &Instruction,
+) -> Result<usize, ExecutionError> {
+    let target = instruction
+        .branch_target(pc)
+        .ok_or(ExecutionError::InvalidBranchTarget(pc))?;
+    jump(instructions, pc, target)
+}
+
+// an array length popped for newarray, anewarray or multianewarray
+fn pop_length(frame: &mut Frame) -> Result<usize, ExecutionError> {
+    let length = frame.pop_int()?;
+    usize::try_from(length).map_err(|_| ExecutionError::NegativeArraySize {
+        pc: frame.pc,
+        size: length,
+    })
+}
+
+// an array of arrays, with as many levels as there are lengths; deeper
+// levels of the array class are left null
+fn allocate_multi_array(heap: &mut Heap, class: &str, lengths: &[usize]) -> ObjectRef {
+    match lengths {
+        [length, inner @ ..] if !inner.is_empty() => {
+            let elements = (0..*length)
+                .map(|_| Value::Reference(Some(allocate_multi_array(heap, &class[1..], inner))))
+                .collect();
+            heap.allocate_array_of(class, elements)
+        }
+        lengths => heap.allocate_array(class, lengths.first().copied().unwrap_or_default()),
+    }
+}
+
+// whether a load instruction can load the value
+fn loads(instruction: &Instruction, value: &Value) -> bool {
+    match value {
+        Value::Int(_) => matches!(
+            instruction,
+            Instruction::Iload(_)
+                | Instruction::WideIload(_)
+                | Instruction::Iload0
+                | Instruction::Iload1
+                | Instruction::Iload2
+                | Instruction::Iload3
+        ),
+        Value::Long(_) => matches!(
+            instruction,
+            Instruction::Lload(_)
+                | Instruction::WideLload(_)
+                | Instruction::Lload0
+                | Instruction::Lload1
+                | Instruction::Lload2
+                | Instruction::Lload3
+        ),
+        Value::Float(_) => matches!(
+            instruction,
+            Instruction::Fload(_)
+                | Instruction::WideFload(_)
+                | Instruction::Fload0
+                | Instruction::Fload1
+                | Instruction::Fload2
+                | Instruction::Fload3
+        ),
+        Value::Double(_) => matches!(
+            instruction,
+            Instruction::Dload(_)
+                | Instruction::WideDload(_)
+                | Instruction::Dload0
+                | Instruction::Dload1
+                | Instruction::Dload2
+                | Instruction::Dload3
+        ),
+        Value::Reference(_) => matches!(
+            instruction,
+            Instruction::Aload(_)
+                | Instruction::WideAload(_)
+                | Instruction::Aload0
+                | Instruction::Aload1
+                | Instruction::Aload2
+                | Instruction::Aload3
+        ),
+        // a return address can be stored, but only ret can use it
+        Value::ReturnAddress(_) => false,
+    }
+}
+
+// whether a store instruction can store the value
+fn stores(instruction: &Instruction, value: &Value) -> bool {
+    match value {
+        Value::Int(_) => matches!(
+            instruction,
+            Instruction::Istore(_)
+                | Instruction::WideIstore(_)
+                | Instruction::Istore0
+                | Instruction::Istore1
+                | Instruction::Istore2
+                | Instruction::Istore3
+        ),
+        Value::Long(_) => matches!(
+            instruction,
+            Instruction::Lstore(_)
+                | Instruction::WideLstore(_)
+                | Instruction::Lstore0
+                | Instruction::Lstore1
+                | Instruction::Lstore2
+                | Instruction::Lstore3
+        ),
+        Value::Float(_) => matches!(
+            instruction,
+            Instruction::Fstore(_)
+                | Instruction::WideFstore(_)
+                | Instruction::Fstore0
+                | Instruction::Fstore1
+                | Instruction::Fstore2
+                | Instruction::Fstore3
+        ),
+        Value::Double(_) => matches!(
+            instruction,
+            Instruction::Dstore(_)
+                | Instruction::WideDstore(_)
+                | Instruction::Dstore0
+                | Instruction::Dstore1
+                | Instruction::Dstore2
+                | Instruction::Dstore3
+        ),
+        Value::Reference(_) | Value::ReturnAddress(_) => matches!(
+            instruction,
+            Instruction::Astore(_)
+                | Instruction::WideAstore(_)
+                | Instruction::Astore0
+                | Instruction::Astore1
+                | Instruction::Astore2
+                | Instruction::Astore3
+        ),
+    }
+}
+
+fn iinc(frame: &mut Frame, instruction: &Instruction, delta: i32) -> Result<(), ExecutionError> {
+    let index = instruction.local_index().unwrap_or_default();
+    let value = frame
+        .local(index)?
+        .as_int()
+        .ok_or(ExecutionError::TypeMismatch(frame.pc))?;
+    frame.set_local(index, Value::Int(value.wrapping_add(delta)))
+}
+
+// a binary int instruction; `op` returns None for a division by zero
+fn int_op(frame: &mut Frame, op: fn(i32, i32) -> Option<i32>) -> Result<(), ExecutionError> {
+    let b = frame.pop_int()?;
+    let a = frame.pop_int()?;
+    let result = op(a, b).ok_or(ExecutionError::DivisionByZero(frame.pc))?;
+    frame.push(Value::Int(result));
+    Ok(())
+}
+
+fn long_op(frame: &mut Frame, op: fn(i64, i64) -> Option<i64>) -> Result<(), ExecutionError> {
+    let b = frame.pop_long()?;
+    let a = frame.pop_long()?;
+    let result = op(a, b).ok_or(ExecutionError::DivisionByZero(frame.pc))?;
+    frame.push(Value::Long(result));
+    Ok(())
+}
+
+fn float_op(frame: &mut Frame, op: fn(f32, f32) -> f32) -> Result<(), ExecutionError> {
+    let b = frame.pop_float()?;
+    let a = frame.pop_float()?;
+    frame.push(Value::Float(op(a, b)));
+    Ok(())
+}
+
+fn double_op(frame: &mut Frame, op: fn(f64, f64) -> f64) -> Result<(), ExecutionError> {
+    let b = frame.pop_double()?;
+    let a = frame.pop_double()?;
+    frame.push(Value::Double(op(a, b)));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+    use crate::asm::class_text::assemble;
+    use crate::asm::ClassBuilder;
+
+    fn interpreter() -> Interpreter {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/../../java");
+        Interpreter::new(ClassPath::parse(fixtures).unwrap())
+    }
+
+    // calls the method of Calc named `name`
+    fn call(name: &str, args: &[Value]) -> Result<Option<Value>, ExecutionError> {
+        let mut interpreter = interpreter();
+        let class = interpreter.loader.load("Calc").unwrap();
+        let method = (0..class.classfile.methods.len())
+            .find(|index| class.method_signature(*index).0 == name)
+            .unwrap();
+        interpreter.execute(&class, method, args)
+    }
+
+    // runs code as a static method of a class Text with room for four
+    // locals, an int field x and a static int field s. its pool has #1
+    // Integer 7, #2 Long 1 << 40, #4 Utf8 "seven", #6 Class Text, #13
+    // Fieldref x and #16 Fieldref s
+    fn run_text(code: &str) -> Result<Option<Value>, ExecutionError> {
+        let text = format!(
+            r#"
+                .const #1 = Integer 7
+                .const #2 = Long 1099511627776
+                .const #4 = Utf8 "seven"
+                .const #5 = Utf8 "Text"
+                .const #6 = Class #5
+                .const #7 = Utf8 "run"
+                .const #8 = Utf8 "()V"
+                .const #9 = Utf8 "Code"
+                .const #10 = Utf8 "x"
+                .const #11 = Utf8 "I"
+                .const #12 = NameAndType #10:#11
+                .const #13 = Fieldref #6.#12
+                .const #14 = Utf8 "s"
+                .const #15 = NameAndType #14:#11
+                .const #16 = Fieldref #6.#15
+                .class 0x0021 #6
+                .field 0x0000 #10 #11
+                .end field
+                .field 0x0008 #14 #11
+                .end field
+                .method 0x0009 #7 #8
+                .code 8 4
+                {code}
+                .end code
+                .end method
+            "#
+        );
+        let data = assemble(&text).unwrap().to_bytes().unwrap();
+        let mut interpreter = interpreter();
+        let class = interpreter.loader.define(&data).unwrap();
+        interpreter.execute(&class, 0, &[])
+    }
+
+    #[test]
+    fn test_add() {
+        assert_eq!(
+            call("add", &[Value::Int(2), Value::Int(3)]),
+            Ok(Some(Value::Int(5)))
+        );
+        assert_eq!(
+            call("add", &[Value::Int(i32::MAX), Value::Int(1)]),
+            Ok(Some(Value::Int(i32::MIN)))
+        );
+    }
+
+    #[test]
+    fn test_branches() {
+        assert_eq!(
+            call("max", &[Value::Int(4), Value::Int(9)]),
+            Ok(Some(Value::Int(9)))
+        );
+        assert_eq!(
+            call("max", &[Value::Int(9), Value::Int(4)]),
+            Ok(Some(Value::Int(9)))
+        );
+        assert_eq!(
+            call("factorial", &[Value::Int(20)]),
+            Ok(Some(Value::Long(2432902008176640000)))
+        );
+        assert_eq!(
+            call("collatz", &[Value::Int(27)]),
+            Ok(Some(Value::Int(111)))
+        );
+        for (n, result) in [(0, -1), (1, 10), (3, 30), (4, -1)] {
+            assert_eq!(
+                call("dense", &[Value::Int(n)]),
+                Ok(Some(Value::Int(result)))
+            );
+        }
+        for (n, result) in [(-100, 1), (0, 2), (1000, 3), (5, 0)] {
+            assert_eq!(
+                call("sparse", &[Value::Int(n)]),
+                Ok(Some(Value::Int(result)))
+            );
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let compare = |a, b| call("compare", &[Value::Double(a), Value::Double(b)]);
+        assert_eq!(compare(1.0, 2.0), Ok(Some(Value::Int(-1))));
+        assert_eq!(compare(2.0, 1.0), Ok(Some(Value::Int(1))));
+        assert_eq!(compare(f64::NAN, 1.0), Ok(Some(Value::Int(0))));
+        assert_eq!(
+            call(
+                "mean",
+                &[Value::Long(1), Value::Float(2.5), Value::Double(5.5)]
+            ),
+            Ok(Some(Value::Double(3.0)))
+        );
+        // (byte) 0x1ff + (char) 0x1ff + (short) 0x1ff
+        assert_eq!(
+            call("narrow", &[Value::Int(0x1ff)]),
+            Ok(Some(Value::Int(-1 + 0x1ff + 0x1ff)))
+        );
+        assert_eq!(
+            call("divide", &[Value::Int(i32::MIN), Value::Int(-1)]),
+            Ok(Some(Value::Int(i32::MIN)))
+        );
+        assert_eq!(
+            call("divide", &[Value::Int(1), Value::Int(0)]),
+            Err(ExecutionError::DivisionByZero(2))
+        );
+        assert_eq!(
+            run_text("iconst_m1\nbipush 33\niushr\nireturn"),
+            Ok(Some(Value::Int(i32::MAX)))
+        );
+        assert_eq!(
+            run_text("ldc2_w #2\nldc #1\nlshr\nbipush 20\nlushr\nl2i\nireturn"),
+            Ok(Some(Value::Int(1 << 13)))
+        );
+    }
+
+    #[test]
+    fn test_stack() {
+        // 1, 2L -> 2L, 1, 2L via dup2_x1, then 2 - 1 + 2
+        assert_eq!(
+            run_text("iconst_1\nlconst_1\nlconst_1\nladd\ndup2_x1\nlstore_0\ni2l\nlsub\nlload_0\nladd\nl2i\nireturn"),
+            Ok(Some(Value::Int(3)))
+        );
+        assert_eq!(
+            run_text("iconst_1\niconst_2\nswap\nisub\nireturn"),
+            Ok(Some(Value::Int(1)))
+        );
+        // pop can't take half of a long
+        assert_eq!(
+            run_text("lconst_0\npop\nreturn"),
+            Err(ExecutionError::TypeMismatch(1))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            call("add", &[Value::Int(2)]),
+            Err(ExecutionError::InvalidArguments)
+        );
+        assert_eq!(
+            call("add", &[Value::Int(2), Value::Long(3)]),
+            Err(ExecutionError::InvalidArguments)
+        );
+        assert_eq!(run_text("iconst_0\nireturn\n"), Ok(Some(Value::Int(0))));
+        assert_eq!(run_text("iconst_0\n"), Err(ExecutionError::FallsOffEnd(0)));
+        assert_eq!(run_text("ireturn"), Err(ExecutionError::StackUnderflow(0)));
+        assert_eq!(
+            run_text("iload_3\nireturn"),
+            Err(ExecutionError::InvalidLocal { pc: 0, index: 3 })
+        );
+        assert_eq!(
+            run_text("lconst_0\nlstore_3\nreturn"),
+            Err(ExecutionError::InvalidLocal { pc: 1, index: 3 })
+        );
+        assert_eq!(
+            run_text("fconst_0\nistore_0\nreturn"),
+            Err(ExecutionError::TypeMismatch(1))
+        );
+        assert_eq!(
+            run_text("aconst_null\nmonitorenter"),
+            Err(ExecutionError::UnsupportedInstruction {
+                pc: 1,
+                mnemonic: "monitorenter"
+            })
+        );
+        assert_eq!(
+            run_text("aconst_null\nathrow"),
+            Err(ExecutionError::NullPointer(1))
+        );
+        assert_eq!(
+            run_text("new #6\nathrow"),
+            Err(ExecutionError::TypeMismatch(3))
+        );
+        assert_eq!(
+            run_text("ldc #4\nareturn"),
+            Err(ExecutionError::InvalidConstant { pc: 0, index: 4 })
+        );
+        assert_eq!(
+            run_text("ldc #2\nlreturn"),
+            Err(ExecutionError::InvalidConstant { pc: 0, index: 2 })
+        );
+    }
+
+    // calls a static method of Matrix
+    fn call_matrix(
+        interpreter: &mut Interpreter,
+        name: &str,
+        descriptor: &str,
+        args: &[Value],
+    ) -> Result<Option<Value>, ExecutionError> {
+        interpreter.invoke_static("Matrix", name, descriptor, args)
+    }
+
+    #[test]
+    fn test_arrays() {
+        let mut interpreter = interpreter();
+        let mut call = |name, descriptor, args: &[Value]| {
+            call_matrix(&mut interpreter, name, descriptor, args)
+        };
+        assert_eq!(call("trace", "()I", &[]), Ok(Some(Value::Int(6))));
+        assert_eq!(call("jagged", "()I", &[]), Ok(Some(Value::Int(2))));
+        // -56 + 'a' - 1 + 1 + (1 << 40) + 2 + 2
+        assert_eq!(
+            call("primitives", "()J", &[]),
+            Ok(Some(Value::Long(45 + (1 << 40))))
+        );
+        assert_eq!(call("objects", "()I", &[]), Ok(Some(Value::Int(17))));
+        assert_eq!(
+            call("element", "(I)I", &[Value::Int(1)]),
+            Ok(Some(Value::Int(0)))
+        );
+        assert_eq!(
+            call("element", "(I)I", &[Value::Int(2)]),
+            Err(ExecutionError::ArrayIndexOutOfBounds {
+                pc: 6,
+                index: 2,
+                length: 2
+            })
+        );
+        assert_eq!(
+            call("element", "(I)I", &[Value::Int(-1)]),
+            Err(ExecutionError::ArrayIndexOutOfBounds {
+                pc: 6,
+                index: -1,
+                length: 2
+            })
+        );
+        assert_eq!(
+            call("allocate", "(I)I", &[Value::Int(-3)]),
+            Err(ExecutionError::NegativeArraySize { pc: 1, size: -3 })
+        );
+        assert_eq!(
+            call("length", "([I)I", &[Value::NULL]),
+            Err(ExecutionError::NullPointer(1))
+        );
+
+        let array = interpreter.heap.allocate_array("[I", 5);
+        let values = Value::Reference(Some(array));
+        assert_eq!(
+            call_matrix(&mut interpreter, "length", "([I)I", &[values]),
+            Ok(Some(Value::Int(5)))
+        );
+        match interpreter.heap.get(array) {
+            Object::Array { class, elements } => {
+                assert_eq!(class, "[I");
+                assert_eq!(elements, &[Value::Int(0); 5]);
+            }
+            object => panic!("expected an array, got {object:?}"),
+        }
+    }
+
+    #[test]
+    fn test_fields() {
+        let mut interpreter = interpreter();
+        let matrix = interpreter.loader.load("Matrix").unwrap();
+        let slot = |name, descriptor| matrix.field_slot(name, descriptor).unwrap().slot;
+        // statics start as their ConstantValue or their type's default
+        assert_eq!(matrix.static_value(slot("SIZE", "I")), Value::Int(3));
+        assert_eq!(matrix.static_value(slot("SEED", "J")), Value::Long(1 << 40));
+        assert_eq!(matrix.static_value(slot("calls", "I")), Value::Int(0));
+        call_matrix(&mut interpreter, "trace", "()I", &[]).unwrap();
+        call_matrix(&mut interpreter, "trace", "()I", &[]).unwrap();
+        assert_eq!(matrix.static_value(slot("calls", "I")), Value::Int(2));
+
+        assert_eq!(
+            run_text(
+                "new #6\ndup\nastore_0\nbipush 9\nputfield #13\naload_0\ngetfield #13\nireturn"
+            ),
+            Ok(Some(Value::Int(9)))
+        );
+        assert_eq!(
+            run_text("getstatic #16\niconst_2\niadd\nputstatic #16\ngetstatic #16\nireturn"),
+            Ok(Some(Value::Int(2)))
+        );
+        assert_eq!(
+            run_text("new #6\nlconst_1\nputfield #13\nreturn"),
+            Err(ExecutionError::TypeMismatch(4))
+        );
+        assert_eq!(
+            run_text("aconst_null\ngetfield #13\nireturn"),
+            Err(ExecutionError::NullPointer(1))
+        );
+        assert_eq!(
+            run_text("new #6\ngetfield #16\nireturn"),
+            Err(ExecutionError::LinkageError(
+                LinkageError::IncompatibleClassChange("Text".to_string())
+            ))
+        );
+        assert_eq!(
+            run_text("getstatic #13\nireturn"),
+            Err(ExecutionError::LinkageError(
+                LinkageError::IncompatibleClassChange("Text".to_string())
+            ))
+        );
+    }
+
+    #[test]
+    fn test_references() {
+        assert_eq!(
+            run_text("aconst_null\nifnull null\niconst_0\nireturn\nnull: iconst_1\nireturn"),
+            Ok(Some(Value::Int(1)))
+        );
+        assert_eq!(
+            run_text(
+                "new #6\ndup\nif_acmpeq same\niconst_0\nireturn\nsame: new #6\nnew #6\nif_acmpne other\niconst_0\nireturn\nother: iconst_1\nireturn"
+            ),
+            Ok(Some(Value::Int(1)))
+        );
+        // a finally subroutine, the way javac compiled them before 50.0
+        assert_eq!(
+            run_text("iconst_1\nistore_1\njsr double\njsr double\niload_1\nireturn\ndouble: astore_2\niload_1\niconst_2\nimul\nistore_1\nret 2"),
+            Ok(Some(Value::Int(4)))
+        );
+        assert_eq!(
+            run_text("iconst_0\nistore_2\nret 2"),
+            Err(ExecutionError::TypeMismatch(2))
+        );
+        assert_eq!(
+            run_text("jsr sub\nreturn\nsub: aload_0\nareturn"),
+            Err(ExecutionError::InvalidLocal { pc: 4, index: 0 })
+        );
+        assert_eq!(
+            run_text("iconst_0\nnewarray 3\nareturn"),
+            Err(ExecutionError::InvalidArrayType { pc: 1, atype: 3 })
+        );
+    }
+
+    #[test]
+    fn test_invoke() {
+        let mut interpreter = interpreter();
+        let mut call = |class, name, args: &[Value]| {
+            let descriptor = match args.len() {
+                0 => "()I",
+                _ => "(I)I",
+            };
+            interpreter.invoke_static(class, name, descriptor, args)
+        };
+        // virtual calls to overrides, a shadowed field and an array of
+        // objects
+        assert_eq!(call("Animal", "census", &[]), Ok(Some(Value::Int(123))));
+        // a default method calling an interface method, and a private one
+        assert_eq!(call("Animal", "pet", &[]), Ok(Some(Value::Int(13))));
+        // a super call, and a checkcast to an interface
+        assert_eq!(call("Animal", "puppy", &[]), Ok(Some(Value::Int(17))));
+        assert_eq!(
+            call("Counter", "fib", &[Value::Int(10)]),
+            Ok(Some(Value::Int(55)))
+        );
+        assert_eq!(
+            call("Counter", "recurse", &[Value::Int(0)]),
+            Err(ExecutionError::StackOverflow(3))
+        );
+
+        // Counter's static initializer runs once, before count reads base
+        assert_eq!(
+            call("Counter", "count", &[Value::Int(5)]),
+            Ok(Some(Value::Int(15)))
+        );
+        assert_eq!(
+            call("Counter", "count", &[Value::Int(2)]),
+            Ok(Some(Value::Int(12)))
+        );
+        let counter = interpreter.loader.load("Counter").unwrap();
+        let slot = counter.field_slot("initialized", "I").unwrap().slot;
+        assert_eq!(counter.static_value(slot), Value::Int(1));
+    }
+
+    #[test]
+    fn test_casts() {
+        assert_eq!(
+            run_text("new #6\ninstanceof #6\nireturn"),
+            Ok(Some(Value::Int(1)))
+        );
+        assert_eq!(
+            run_text("aconst_null\ninstanceof #6\nireturn"),
+            Ok(Some(Value::Int(0)))
+        );
+        assert_eq!(
+            run_text("aconst_null\ncheckcast #6\nareturn"),
+            Ok(Some(Value::NULL))
+        );
+        assert_eq!(
+            run_text("iconst_1\nnewarray 10\ncheckcast #6\nareturn"),
+            Err(ExecutionError::ClassCast {
+                pc: 3,
+                class: "[I".to_string(),
+                target: "Text".to_string()
+            })
+        );
+
+        let interpreter = interpreter();
+        let assignable = |class, target| interpreter.is_assignable(class, target).unwrap();
+        assert!(assignable("Animal$Puppy", "Animal$Pet"));
+        assert!(assignable("Animal$Puppy", "java/lang/Object"));
+        assert!(!assignable("Animal", "Animal$Dog"));
+        assert!(assignable("[[LAnimal$Dog;", "[[LAnimal;"));
+        assert!(assignable("[[I", "[Ljava/lang/Object;"));
+        assert!(assignable("[I", "java/lang/Cloneable"));
+        assert!(!assignable("[I", "[J"));
+        assert!(!assignable("[LAnimal;", "[LAnimal$Dog;"));
+    }
+
+    // what an interpreter writes to System.out, kept for the test to read
+    #[derive(Clone, Default)]
+    struct Output(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(data)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    // runs a class's main method, returning what it prints
+    fn run_main(class: &str) -> Result<String, ExecutionError> {
+        let output = Output::default();
+        let mut interpreter = interpreter();
+        interpreter.out = Box::new(output.clone());
+        let args = [Value::NULL];
+        interpreter.invoke_static(class, "main", "([Ljava/lang/String;)V", &args)?;
+        let printed = output.0.borrow().clone();
+        Ok(String::from_utf8(printed).unwrap())
+    }
+
+    #[test]
+    fn test_jdk_classes() {
+        let Some(java_home) = crate::test_support::java_home() else {
+            return;
+        };
+        let mut class_path = ClassPath::new();
+        class_path.push_system(java_home).unwrap();
+        class_path
+            .push(concat!(env!("CARGO_MANIFEST_DIR"), "/../../java"))
+            .unwrap();
+        let output = Output::default();
+        let mut interpreter = Interpreter::new(class_path);
+        interpreter.out = Box::new(output.clone());
+        // String, System and PrintStream are the interpreter's own, though
+        // Object and the exceptions come from the JDK
+        let args = [Value::NULL];
+        let main = interpreter.invoke_static("HelloWorld", "main", "([Ljava/lang/String;)V", &args);
+        assert_eq!(main, Ok(None));
+        assert_eq!(*output.0.borrow(), b"Hello, World!\n");
+        let checked = interpreter.invoke_static("Thrower", "checked", "()I", &[]);
+        assert_eq!(checked, Ok(Some(Value::Int(2))));
+        // the JDK's RuntimeException, as the builtins have no SourceFile
+        let runtime_exception = interpreter
+            .loader
+            .load("java/lang/RuntimeException")
+            .unwrap();
+        assert!(runtime_exception
+            .classfile
+            .attributes
+            .iter()
+            .any(|attribute| matches!(attribute, Attribute::SourceFile(_))));
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_eq!(run_main("HelloWorld"), Ok("Hello, World!\n".to_string()));
+        assert_eq!(
+            run_main("Printer"),
+            Ok("rj\natrue-42\n1099511627776\n\nnull\ncaf\u{e9} \u{1f600}\n".to_string())
+        );
+
+        // literals with the same text are the same String
+        let mut interpreter = interpreter();
+        let same = interpreter.invoke_static("Printer", "same", "()Z", &[]);
+        assert_eq!(same, Ok(Some(Value::Int(1))));
+        let string = interpreter.new_string("text").unwrap();
+        assert_eq!(interpreter.string_value(string), Some("text".to_string()));
+        assert_ne!(interpreter.intern("text"), Ok(string));
+        assert_eq!(interpreter.intern("text"), interpreter.intern("text"));
+        let array = interpreter.heap.allocate_array("[C", 1);
+        assert_eq!(interpreter.string_value(array), None);
+
+        // a static final String starts as its ConstantValue
+        let printer = interpreter.loader.load("Printer").unwrap();
+        let slot = printer.field_slot("NAME", "Ljava/lang/String;").unwrap();
+        let Value::Reference(Some(name)) = printer.static_value(slot.slot) else {
+            panic!("NAME isn't set");
+        };
+        assert_eq!(interpreter.string_value(name), Some("rj".to_string()));
+
+        let mut builder = ClassBuilder::new("Native");
+        let flags =
+            MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC | MethodAccessFlags::NATIVE;
+        builder.declare_method(flags, "hash", "()I");
+        let data = builder.build().unwrap().to_bytes().unwrap();
+        let class = interpreter.loader.define(&data).unwrap();
+        assert_eq!(
+            interpreter.execute(&class, 0, &[]),
+            Err(ExecutionError::LinkageError(
+                LinkageError::UnsatisfiedLink {
+                    class: "Native".to_string(),
+                    name: "hash".to_string(),
+                    descriptor: "()I".to_string()
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn test_exceptions() {
+        let mut interpreter = interpreter();
+        let mut call = |name| interpreter.invoke_static("Thrower", name, "()I", &[]);
+        // caught three calls up from where it's thrown
+        assert_eq!(call("caught"), Ok(Some(Value::Int(70))));
+        // a finally block rethrowing to an outer handler
+        assert_eq!(call("rethrown"), Ok(Some(Value::Int(11))));
+        // the first handler whose class the exception is an instance of
+        assert_eq!(call("checked"), Ok(Some(Value::Int(2))));
+
+        let Err(ExecutionError::Uncaught(uncaught)) =
+            interpreter.invoke_static("Thrower", "uncaught", "()V", &[])
+        else {
+            panic!("uncaught returned");
+        };
+        assert_eq!(uncaught.class, "Thrower$Oops");
+        assert_eq!(uncaught.message, Some("failed".to_string()));
+        assert_eq!(
+            uncaught.to_string(),
+            "Thrower$Oops: failed
+\tat Thrower.fail(Thrower.java:5)
+\tat Thrower.nested(Thrower.java:11)
+\tat Thrower.nested(Thrower.java:13)
+\tat Thrower.uncaught(Thrower.java:51)"
+        );
+        // the finally block ran on the way out
+        let thrower = interpreter.loader.load("Thrower").unwrap();
+        let slot = thrower.field_slot("depth", "I").unwrap();
+        assert_eq!(thrower.static_value(slot.slot), Value::Int(-1));
+
+        // methods of classes without a SourceFile or LineNumberTable
+        let mut element = uncaught.stack_trace[0].clone();
+        element.class = "com/example/Main".to_string();
+        element.line = None;
+        assert_eq!(element.to_string(), "com.example.Main.fail(Thrower.java)");
+        element.file = None;
+        assert_eq!(element.to_string(), "com.example.Main.fail(Unknown Source)");
+    }
+}
@@ -0,0 +1,104 @@
+use std::mem;
+
+use crate::class::FieldType;
+
+// an object or array on the heap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectRef(pub(crate) usize);
+
+// a value on the operand stack or in a local variable
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    // None is null
+    Reference(Option<ObjectRef>),
+    // the pc after a jsr, for ret to return to
+    ReturnAddress(u32),
+}
+
+impl Value {
+    pub const NULL: Value = Value::Reference(None);
+
+    // the value a field or array element of type `descriptor` starts as
+    pub fn default_for(descriptor: &str) -> Value {
+        match descriptor.as_bytes().first() {
+            Some(b'J') => Value::Long(0),
+            Some(b'F') => Value::Float(0.0),
+            Some(b'D') => Value::Double(0.0),
+            Some(b'L' | b'[') => Value::NULL,
+            _ => Value::Int(0),
+        }
+    }
+
+    // longs and doubles take two local variable slots and two stack words
+    pub fn is_wide(&self) -> bool {
+        matches!(self, Value::Long(_) | Value::Double(_))
+    }
+
+    // whether both values are ints, both longs, and so on; a field or array
+    // element only ever holds values of one kind
+    pub fn is_same_kind(&self, other: &Value) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            Value::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_long(&self) -> Option<i64> {
+        match self {
+            Value::Long(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            Value::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_double(&self) -> Option<f64> {
+        match self {
+            Value::Double(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_reference(&self) -> Option<Option<ObjectRef>> {
+        match self {
+            Value::Reference(reference) => Some(*reference),
+            _ => None,
+        }
+    }
+
+    // whether the value can be passed for a parameter of this type; booleans,
+    // bytes, chars and shorts are ints on the stack. the classes of
+    // references aren't checked
+    pub fn is_assignable_to(&self, field_type: &FieldType) -> bool {
+        matches!(
+            (self, field_type),
+            (
+                Value::Int(_),
+                FieldType::Boolean
+                    | FieldType::Byte
+                    | FieldType::Char
+                    | FieldType::Short
+                    | FieldType::Int
+            ) | (Value::Long(_), FieldType::Long)
+                | (Value::Float(_), FieldType::Float)
+                | (Value::Double(_), FieldType::Double)
+                | (
+                    Value::Reference(_),
+                    FieldType::Object(_) | FieldType::Array(_)
+                )
+        )
+    }
+}
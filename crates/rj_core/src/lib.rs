@@ -1,7 +1,14 @@
+pub mod analysis;
+pub mod archive;
 pub mod asm;
 pub mod class;
+pub mod interp;
 pub mod parser;
 pub mod print;
+pub mod transform;
+
+#[cfg(test)]
+mod test_support;
 
 pub fn add(left: usize, right: usize) -> usize {
     left + right
@@ -1,4 +1,14 @@
+use std::fmt;
+
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
     Eof,
 }
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Eof => write!(f, "unexpected end of input"),
+        }
+    }
+}
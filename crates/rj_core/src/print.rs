@@ -1,4 +1,20 @@
 mod access_flags;
+mod attribute;
 mod classfile;
+mod code;
+mod color;
 mod constant;
+mod diff;
 mod error;
+mod html;
+mod instruction;
+mod json;
+mod options;
+mod signature;
+mod stable;
+mod system_info;
+
+pub use constant::DisplayConstant;
+pub use diff::{ArchiveDiff, ClassDiff, CodeLine, MemberDiff, ValueChange};
+pub use options::{PrintOptions, Visibility};
+pub use system_info::{print_system_info, sha256};
@@ -1,4 +1,14 @@
-use crate::class::{ClassAccessFlags, FieldAccessFlags, MethodAccessFlags};
+use crate::class::{ClassAccessFlags, FieldAccessFlags, InnerClassAccessFlags, MethodAccessFlags};
+
+// `flags: (0x0021) ACC_PUBLIC, ACC_SUPER`, or just `flags: (0x0000)`
+fn print_flags(bits: u16, flags: &[&str]) -> String {
+    let mut output = format!("flags: (0x{bits:04x})");
+    if !flags.is_empty() {
+        output.push(' ');
+        output.push_str(&flags.join(", "));
+    }
+    output
+}
 
 impl ClassAccessFlags {
     pub fn print(&self) -> String {
@@ -30,7 +40,7 @@ impl ClassAccessFlags {
         if self.contains(ClassAccessFlags::MODULE) {
             flags.push("ACC_MODULE");
         }
-        format!("flags: (0x{:04X}) {}", self.bits(), flags.join(", "))
+        print_flags(self.bits(), &flags)
     }
 
     pub fn print_program(&self) -> String {
@@ -41,7 +51,9 @@ impl ClassAccessFlags {
         if self.contains(ClassAccessFlags::FINAL) {
             flags.push("final");
         }
-        if self.contains(ClassAccessFlags::ABSTRACT) {
+        // interfaces are implicitly abstract
+        if self.contains(ClassAccessFlags::ABSTRACT) && !self.contains(ClassAccessFlags::INTERFACE)
+        {
             flags.push("abstract");
         }
         {
@@ -57,6 +69,15 @@ impl ClassAccessFlags {
         }
         flags.join(" ")
     }
+
+    // records are implicitly final and can't be abstract
+    pub fn print_record(&self) -> String {
+        if self.contains(ClassAccessFlags::PUBLIC) {
+            "public record".to_string()
+        } else {
+            "record".to_string()
+        }
+    }
 }
 
 impl FieldAccessFlags {
@@ -89,7 +110,7 @@ impl FieldAccessFlags {
         if self.contains(FieldAccessFlags::ENUM) {
             flags.push("ACC_ENUM");
         }
-        format!("flags: (0x{:04X}) {}", self.bits(), flags.join(", "))
+        print_flags(self.bits(), &flags)
     }
 
     pub fn print_program(&self) -> String {
@@ -161,7 +182,7 @@ impl MethodAccessFlags {
         if self.contains(MethodAccessFlags::SYNTHETIC) {
             flags.push("ACC_SYNTHETIC");
         }
-        format!("flags: (0x{:04X}) {}", self.bits(), flags.join(", "))
+        print_flags(self.bits(), &flags)
     }
 
     pub fn print_program(&self) -> String {
@@ -197,6 +218,70 @@ impl MethodAccessFlags {
     }
 }
 
+impl InnerClassAccessFlags {
+    pub fn print(&self) -> String {
+        let mut flags = vec![];
+        if self.contains(InnerClassAccessFlags::PUBLIC) {
+            flags.push("ACC_PUBLIC");
+        }
+        if self.contains(InnerClassAccessFlags::PRIVATE) {
+            flags.push("ACC_PRIVATE");
+        }
+        if self.contains(InnerClassAccessFlags::PROTECTED) {
+            flags.push("ACC_PROTECTED");
+        }
+        if self.contains(InnerClassAccessFlags::STATIC) {
+            flags.push("ACC_STATIC");
+        }
+        if self.contains(InnerClassAccessFlags::FINAL) {
+            flags.push("ACC_FINAL");
+        }
+        if self.contains(InnerClassAccessFlags::INTERFACE) {
+            flags.push("ACC_INTERFACE");
+        }
+        if self.contains(InnerClassAccessFlags::ABSTRACT) {
+            flags.push("ACC_ABSTRACT");
+        }
+        if self.contains(InnerClassAccessFlags::SYNTHETIC) {
+            flags.push("ACC_SYNTHETIC");
+        }
+        if self.contains(InnerClassAccessFlags::ANNOTATION) {
+            flags.push("ACC_ANNOTATION");
+        }
+        if self.contains(InnerClassAccessFlags::ENUM) {
+            flags.push("ACC_ENUM");
+        }
+        print_flags(self.bits(), &flags)
+    }
+
+    // the modifiers javap shows in an InnerClasses entry; interfaces are
+    // implicitly abstract so that one is left out
+    pub fn print_program(&self) -> String {
+        let mut flags = vec![];
+        if self.contains(InnerClassAccessFlags::PUBLIC) {
+            flags.push("public");
+        }
+        if self.contains(InnerClassAccessFlags::PRIVATE) {
+            flags.push("private");
+        }
+        if self.contains(InnerClassAccessFlags::PROTECTED) {
+            flags.push("protected");
+        }
+        if self.contains(InnerClassAccessFlags::STATIC) {
+            flags.push("static");
+        }
+        if self.contains(InnerClassAccessFlags::ABSTRACT)
+            && !self.contains(InnerClassAccessFlags::INTERFACE)
+        {
+            flags.push("abstract");
+        }
+        if self.contains(InnerClassAccessFlags::FINAL) {
+            flags.push("final");
+        }
+        flags.join(" ")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -220,5 +305,21 @@ mod tests {
         let flags = MethodAccessFlags::from_bits(0x0001);
         assert_eq!(flags.print(), "flags: (0x0001) ACC_PUBLIC");
         assert_eq!(flags.print_program(), "public");
+
+        let flags = MethodAccessFlags::from_bits(0x010a);
+        assert_eq!(
+            flags.print(),
+            "flags: (0x010a) ACC_PRIVATE, ACC_STATIC, ACC_NATIVE"
+        );
+    }
+
+    #[test]
+    fn test_inner_class_access_flags() {
+        let flags = InnerClassAccessFlags::from_bits(0x0608);
+        assert_eq!(
+            flags.print(),
+            "flags: (0x0608) ACC_STATIC, ACC_INTERFACE, ACC_ABSTRACT"
+        );
+        assert_eq!(flags.print_program(), "static");
     }
 }
@@ -0,0 +1,304 @@
+use crate::class::{
+    internal_to_binary_name, parse_field_type, Annotation, Attribute, BootstrapMethods, Constant,
+    ConstantPool, ElementValue, EnclosingMethod, InnerClasses,
+};
+
+use super::classfile::escape;
+use super::constant::{format_double, format_float, get_string_value};
+use super::error::PrintError;
+use super::instruction::{get_class, get_constant, push_comment};
+
+impl EnclosingMethod {
+    pub fn print(&self, constant_pool: &ConstantPool) -> Result<String, PrintError> {
+        let mut line = format!(
+            "EnclosingMethod: #{}.#{}",
+            self.class_index, self.method_index
+        );
+        let mut comment = internal_to_binary_name(&constant_pool.class_name(self.class_index)?);
+        if self.method_index != 0 {
+            comment.push('.');
+            comment.push_str(&constant_pool.name_and_type(self.method_index)?.name);
+        }
+        push_comment(&mut line, &comment);
+        line.push('\n');
+        Ok(line)
+    }
+}
+
+impl InnerClasses {
+    pub fn print(&self, constant_pool: &ConstantPool) -> Result<String, PrintError> {
+        let mut output = String::from("InnerClasses:\n");
+        for class in &self.classes {
+            let mut line = class.inner_class_access_flags.print_program();
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            let mut comment = String::new();
+            if !class.is_anonymous() {
+                line.push_str(&format!("#{}= ", class.inner_name_index));
+                let name = constant_pool.utf8(class.inner_name_index)?;
+                comment.push_str(&format!("{name}="));
+            }
+            line.push_str(&format!("#{}", class.inner_class_info_index));
+            let inner = get_class(constant_pool, class.inner_class_info_index)?;
+            comment.push_str(&format!("class {inner}"));
+            if class.is_member() {
+                line.push_str(&format!(" of #{}", class.outer_class_info_index));
+                let outer = get_class(constant_pool, class.outer_class_info_index)?;
+                comment.push_str(&format!(" of class {outer}"));
+            }
+            line.push(';');
+            push_comment(&mut line, &comment);
+            output.push_str(&format!("  {line}\n"));
+        }
+        Ok(output)
+    }
+}
+
+impl BootstrapMethods {
+    // the `BootstrapMethods:` trailer of `javap -v`; entries are numbered
+    // by the index that InvokeDynamic and Dynamic constants refer to
+    pub fn print(&self, constant_pool: &ConstantPool) -> Result<String, PrintError> {
+        let mut output = String::from("BootstrapMethods:\n");
+        for (i, bootstrap_method) in self.bootstrap_methods.iter().enumerate() {
+            output.push_str(&format!(
+                "  {i}: #{} {}\n",
+                bootstrap_method.bootstrap_method_ref,
+                get_string_value(constant_pool, bootstrap_method.bootstrap_method_ref)?
+            ));
+            output.push_str("    Method arguments:\n");
+            for argument in &bootstrap_method.bootstrap_arguments {
+                output.push_str(&format!(
+                    "      #{argument} {}\n",
+                    get_string_value(constant_pool, *argument)?
+                ));
+            }
+        }
+        Ok(output)
+    }
+}
+
+// `java.lang.String` for a field descriptor, `void` for a `V` return type
+fn print_descriptor(constant_pool: &ConstantPool, index: u16) -> Result<String, PrintError> {
+    let descriptor = constant_pool.utf8(index)?;
+    if descriptor == "V" {
+        return Ok("void".to_string());
+    }
+    match parse_field_type(descriptor.as_bytes()) {
+        Ok((b"", field_type)) => Ok(field_type.print()),
+        _ => Err(PrintError::InvalidConstant),
+    }
+}
+
+impl ElementValue {
+    // the value as it would be written in source: `"x"`, `{1, 2}`,
+    // `java.lang.annotation.ElementType.TYPE` or `java.lang.String.class`
+    pub fn print(&self, constant_pool: &ConstantPool) -> Result<String, PrintError> {
+        let value = match self {
+            ElementValue::Const {
+                tag,
+                const_value_index,
+            } => match (tag, get_constant(constant_pool, *const_value_index)?) {
+                (b'Z', Constant::Integer { value }) => (*value != 0).to_string(),
+                (b'C', Constant::Integer { value }) => {
+                    let c = char::from_u32(*value as u32).ok_or(PrintError::InvalidConstant)?;
+                    format!("'{}'", escape(&c.to_string(), '\''))
+                }
+                (b'B' | b'S' | b'I', Constant::Integer { value }) => value.to_string(),
+                (b'J', Constant::Long { value }) => format!("{value}l"),
+                (b'F', Constant::Float { value }) => format!("{}f", format_float(*value)),
+                (b'D', Constant::Double { value }) => format!("{}d", format_double(*value)),
+                (b's', _) => {
+                    let value = constant_pool.utf8(*const_value_index)?;
+                    format!("\"{}\"", escape(&value, '"'))
+                }
+                _ => return Err(PrintError::UnsupportedConstant(*const_value_index)),
+            },
+            ElementValue::Enum {
+                type_name_index,
+                const_name_index,
+            } => format!(
+                "{}.{}",
+                print_descriptor(constant_pool, *type_name_index)?,
+                constant_pool.utf8(*const_name_index)?
+            ),
+            ElementValue::Class { class_info_index } => {
+                format!(
+                    "{}.class",
+                    print_descriptor(constant_pool, *class_info_index)?
+                )
+            }
+            ElementValue::Annotation(annotation) => annotation.print(constant_pool)?,
+            ElementValue::Array(values) => {
+                let values = values
+                    .iter()
+                    .map(|value| value.print(constant_pool))
+                    .collect::<Result<Vec<_>, _>>()?;
+                format!("{{{}}}", values.join(", "))
+            }
+        };
+        Ok(value)
+    }
+}
+
+impl Annotation {
+    // `@java.lang.Deprecated`, or `@Info(name="type", count=3)`. a lone
+    // `value` element is written without its name, as in source
+    pub fn print(&self, constant_pool: &ConstantPool) -> Result<String, PrintError> {
+        let mut output = format!("@{}", print_descriptor(constant_pool, self.type_index)?);
+        if self.element_value_pairs.is_empty() {
+            return Ok(output);
+        }
+        let mut pairs = vec![];
+        for pair in &self.element_value_pairs {
+            let name = constant_pool.utf8(pair.element_name_index)?;
+            let value = pair.value.print(constant_pool)?;
+            if name == "value" && self.element_value_pairs.len() == 1 {
+                pairs.push(value);
+            } else {
+                pairs.push(format!("{name}={value}"));
+            }
+        }
+        output.push_str(&format!("({})", pairs.join(", ")));
+        Ok(output)
+    }
+}
+
+// one line per annotation on a class, field or method, visible ones first
+pub(super) fn print_annotations(
+    attributes: &[Attribute],
+    constant_pool: &ConstantPool,
+) -> Result<String, PrintError> {
+    let mut output = String::new();
+    let visible = attributes.iter().filter_map(|attribute| match attribute {
+        Attribute::RuntimeVisibleAnnotations(annotations) => Some(&annotations.annotations),
+        _ => None,
+    });
+    let invisible = attributes.iter().filter_map(|attribute| match attribute {
+        Attribute::RuntimeInvisibleAnnotations(annotations) => Some(&annotations.annotations),
+        _ => None,
+    });
+    for annotation in visible.chain(invisible).flatten() {
+        output.push_str(&annotation.print(constant_pool)?);
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::{parse_classfile, ElementValuePair};
+
+    #[test]
+    fn test_print_enclosing_method() {
+        let data = include_bytes!("../../../../java/Outer$1.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let enclosing_method = classfile
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::EnclosingMethod(enclosing_method) => Some(enclosing_method),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(
+            enclosing_method.print(&classfile.constant_pool).unwrap(),
+            "EnclosingMethod: #19.#21                // Outer.anonymous\n"
+        );
+
+        let enclosing_method = EnclosingMethod {
+            class_index: 1,
+            method_index: 0,
+        };
+        let constant_pool = ConstantPool::from(vec![
+            Constant::Class { name_index: 2 },
+            Constant::Utf8 {
+                value: b"a/b/C".into(),
+            },
+        ]);
+        assert_eq!(
+            enclosing_method.print(&constant_pool).unwrap(),
+            "EnclosingMethod: #1.#0                  // a.b.C\n"
+        );
+    }
+
+    #[test]
+    fn test_print_inner_classes() {
+        let data = include_bytes!("../../../../java/Insns.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let inner_classes = classfile
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::InnerClasses(inner_classes) => Some(inner_classes),
+                _ => None,
+            })
+            .unwrap();
+        let expected = r#"
+InnerClasses:
+  static #72= #22 of #32;                 // Greeter=class Insns$Greeter of class Insns
+  public static final #77= #73 of #75;    // Lookup=class java/lang/invoke/MethodHandles$Lookup of class java/lang/invoke/MethodHandles
+"#;
+        assert_eq!(
+            inner_classes.print(&classfile.constant_pool).unwrap(),
+            expected[1..]
+        );
+    }
+
+    #[test]
+    fn test_print_annotation() {
+        let constant_pool = ConstantPool::from(vec![
+            Constant::Utf8 {
+                value: b"Ljava/lang/SuppressWarnings;".into(),
+            },
+            Constant::Utf8 {
+                value: b"value".into(),
+            },
+            Constant::Utf8 {
+                value: b"say \"x\"".into(),
+            },
+            Constant::Integer { value: 'q' as i32 },
+            Constant::Long { value: 7 },
+            Constant::Utf8 { value: b"V".into() },
+        ]);
+        let annotation = Annotation {
+            type_index: 1,
+            element_value_pairs: vec![ElementValuePair {
+                element_name_index: 2,
+                value: ElementValue::Array(vec![
+                    ElementValue::Const {
+                        tag: b's',
+                        const_value_index: 3,
+                    },
+                    ElementValue::Const {
+                        tag: b'C',
+                        const_value_index: 4,
+                    },
+                    ElementValue::Const {
+                        tag: b'J',
+                        const_value_index: 5,
+                    },
+                    ElementValue::Class {
+                        class_info_index: 7,
+                    },
+                    ElementValue::Array(vec![]),
+                ]),
+            }],
+        };
+        assert_eq!(
+            annotation.print(&constant_pool).unwrap(),
+            r#"@java.lang.SuppressWarnings({"say \"x\"", 'q', 7l, void.class, {}})"#
+        );
+
+        // a constant of the wrong kind for its tag
+        let value = ElementValue::Const {
+            tag: b'J',
+            const_value_index: 4,
+        };
+        assert_eq!(
+            value.print(&constant_pool),
+            Err(PrintError::UnsupportedConstant(4))
+        );
+    }
+}
@@ -1,55 +1,122 @@
 use std::borrow::Cow;
+use std::fmt;
 
 use crate::class::{
-    parse_field_type, parse_method_descriptor, ClassFile, Constant, FieldType, MethodDescriptor,
+    decode_modified_utf8, internal_to_binary_name, parse_class_signature, parse_field_signature,
+    parse_field_type, parse_method_signature, Attribute, ClassAccessFlags, ClassFile,
+    ClassSignature, Constant, ConstantPool, Field, FieldAccessFlags, FieldType, Method,
+    MethodAccessFlags, MethodDescriptor, Record, Signature,
 };
 
+use super::attribute::print_annotations;
+use super::code::{indent, ordered};
+use super::color::{colorize_line, Colorize};
+use super::constant::{format_double, format_float, index_width};
 use super::error::PrintError;
+use super::instruction::{get_class, get_comment, push_comment};
+use super::options::{PrintOptions, Visibility};
+use super::signature::print_type_parameters;
+use super::stable::{strip_indices, Stable};
 
-fn get_classname<'a>(
-    index: u16,
-    constant_pool: &'a [crate::class::Constant<'a>],
-) -> Option<Cow<'a, str>> {
-    // let class_info = constant_pool.get
-    if let Some(Constant::Class { name_index }) = constant_pool.get(index as usize - 1) {
-        if let Some(Constant::Utf8 { value }) = constant_pool.get(*name_index as usize - 1) {
-            return Some(Cow::Borrowed(core::str::from_utf8(value).unwrap()));
-        }
-    }
-    None
+fn get_classname<'a>(index: u16, constant_pool: &'a ConstantPool) -> Option<Cow<'a, str>> {
+    constant_pool.class_name(index).ok()
 }
 
-fn get_utf8<'a>(index: u16, constant_pool: &'a [Constant<'a>]) -> Option<Cow<'a, str>> {
-    if let Some(Constant::Utf8 { value }) = constant_pool.get(index as usize - 1) {
-        return Some(Cow::Borrowed(core::str::from_utf8(value).unwrap()));
-    }
-    None
+fn get_utf8<'a>(index: u16, constant_pool: &'a ConstantPool) -> Option<Cow<'a, str>> {
+    constant_pool.utf8(index).ok()
 }
 
 fn get_field_descriptor<'a>(
     index: u16,
-    constant_pool: &'a [Constant<'a>],
+    constant_pool: &'a ConstantPool<'a>,
 ) -> Option<FieldType<'a>> {
-    if let Some(Constant::Utf8 { value }) = constant_pool.get(index as usize - 1) {
+    if let Some(Constant::Utf8 { value }) = constant_pool.get(index) {
         let value = core::str::from_utf8(value).ok()?;
-        let (_, field_type) = parse_field_type(value.as_bytes()).ok()?;
-        return Some(field_type);
+        if let Ok((b"", field_type)) = parse_field_type(value.as_bytes()) {
+            return Some(field_type);
+        }
     }
     None
 }
 
 fn get_method_descriptor<'a>(
     index: u16,
-    constant_pool: &'a [Constant<'a>],
+    constant_pool: &'a ConstantPool<'a>,
 ) -> Option<MethodDescriptor<'a>> {
-    if let Some(Constant::Utf8 { value }) = constant_pool.get(index as usize - 1) {
+    if let Some(Constant::Utf8 { value }) = constant_pool.get(index) {
         let value = core::str::from_utf8(value).ok()?;
-        let (_, method_descriptor) = parse_method_descriptor(value.as_bytes()).ok()?;
-        return Some(method_descriptor);
+        return MethodDescriptor::parse(value.as_bytes()).ok();
     }
     None
 }
 
+// the raw signature of a member with a Signature attribute
+fn get_signature<'a>(
+    attributes: &[Attribute],
+    constant_pool: &'a ConstantPool<'a>,
+) -> Option<&'a [u8]> {
+    attributes.iter().find_map(|attribute| match attribute {
+        Attribute::Signature(signature) => match constant_pool.get(signature.signature_index) {
+            Some(Constant::Utf8 { value }) => Some(value.as_ref()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+pub(super) fn escape(value: &str, quote: char) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c if c == quote => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// the initial value of a field with a ConstantValue attribute, rendered for
+// its declared type the way `javap -constants` does
+fn get_constant_value<'a>(
+    field: &Field,
+    field_type: &FieldType,
+    constant_pool: &'a ConstantPool<'a>,
+) -> Option<String> {
+    let index = field
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::ConstantValue(constant_value) => Some(constant_value.constantvalue_index),
+            _ => None,
+        })?;
+    let value = match (field_type, constant_pool.get(index)?) {
+        (FieldType::Boolean, Constant::Integer { value }) => (*value != 0).to_string(),
+        (FieldType::Char, Constant::Integer { value }) => {
+            let c = char::from_u32(*value as u32)?;
+            format!("'{}'", escape(&c.to_string(), '\''))
+        }
+        (_, Constant::Integer { value }) => value.to_string(),
+        (_, Constant::Float { value }) => format!("{}f", format_float(*value)),
+        (_, Constant::Long { value }) => format!("{value}l"),
+        (_, Constant::Double { value }) => format!("{}d", format_double(*value)),
+        (_, Constant::String { string_index }) => {
+            format!(
+                "\"{}\"",
+                escape(&get_utf8(*string_index, constant_pool)?, '"')
+            )
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
 impl<'a> FieldType<'a> {
     pub fn print(&self) -> String {
         match self {
@@ -61,11 +128,14 @@ impl<'a> FieldType<'a> {
             FieldType::Long => "long".to_string(),
             FieldType::Short => "short".to_string(),
             FieldType::Boolean => "boolean".to_string(),
-            FieldType::Object(name) => core::str::from_utf8(name)
-                .map(|s| s.replace('/', "."))
-                .unwrap_or("".to_string())
-                .to_string(),
-            FieldType::Array(inner) => format!("{}[]", inner.print()),
+            FieldType::Object(name) => decode_modified_utf8(name)
+                .map(|name| internal_to_binary_name(&name))
+                .unwrap_or_default(),
+            FieldType::Array(_) => format!(
+                "{}{}",
+                self.element_type().print(),
+                "[]".repeat(self.dimensions() as usize)
+            ),
             FieldType::Void => "void".to_string(),
         }
     }
@@ -86,77 +156,699 @@ impl<'a> MethodDescriptor<'a> {
 }
 
 impl<'a> ClassFile<'a> {
-    pub fn print(&self) -> Result<String, PrintError> {
+    // `int x, java.util.List<T> tags`
+    fn print_record_components(&self, record: &Record<Attribute>) -> Result<String, PrintError> {
+        let mut components = vec![];
+        for component in &record.components {
+            let name = get_utf8(component.name_index, &self.constant_pool)
+                .ok_or(PrintError::InvalidConstant)?;
+            let signature = get_signature(&component.attributes, &self.constant_pool)
+                .and_then(|signature| parse_field_signature(signature).ok());
+            let component_type = match signature {
+                Some((_, signature)) => signature.print(),
+                None => get_field_descriptor(component.descriptor_index, &self.constant_pool)
+                    .ok_or(PrintError::InvalidConstant)?
+                    .print(),
+            };
+            components.push(format!("{component_type} {name}"));
+        }
+        Ok(components.join(", "))
+    }
+
+    // `Vehicle$Car, Vehicle$Truck` for a sealed class
+    fn print_permitted_subclasses(&self) -> Result<Option<String>, PrintError> {
+        let permitted_subclasses = self
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::PermittedSubclasses(permitted_subclasses) => Some(permitted_subclasses),
+                _ => None,
+            });
+        let Some(permitted_subclasses) = permitted_subclasses else {
+            return Ok(None);
+        };
+        let mut classes = vec![];
+        for class in &permitted_subclasses.classes {
+            let name =
+                get_classname(*class, &self.constant_pool).ok_or(PrintError::InvalidConstant)?;
+            classes.push(internal_to_binary_name(&name));
+        }
+        Ok(Some(classes.join(", ")))
+    }
+
+    // the parsed class Signature attribute, if there is one and it's valid
+    fn class_signature(&self) -> Option<ClassSignature<'_>> {
+        get_signature(&self.attributes, &self.constant_pool)
+            .and_then(|signature| parse_class_signature(signature).ok())
+            .map(|(_, signature)| signature)
+    }
+
+    // `java.lang.Runnable, java.lang.Iterable<K>`
+    fn print_interfaces(&self, signature: Option<&ClassSignature>) -> Result<String, PrintError> {
+        if let Some(signature) = signature {
+            let interfaces = signature
+                .interfaces
+                .iter()
+                .map(|interface| interface.print())
+                .collect::<Vec<_>>();
+            return Ok(interfaces.join(", "));
+        }
+        let mut interfaces = vec![];
+        for interface in &self.interfaces {
+            let name = get_classname(*interface, &self.constant_pool)
+                .ok_or(PrintError::InvalidConstant)?;
+            interfaces.push(internal_to_binary_name(&name));
+        }
+        Ok(interfaces.join(", "))
+    }
+
+    // ` extends Base<T> implements java.lang.Runnable`. interfaces extend the
+    // interfaces they list, and java.lang.Object is left implicit
+    fn print_supertypes(&self, signature: Option<&ClassSignature>) -> Result<String, PrintError> {
+        let interfaces = self.print_interfaces(signature)?;
         let mut output = String::new();
+        if self.access_flags.contains(ClassAccessFlags::INTERFACE) {
+            if !interfaces.is_empty() {
+                output.push_str(&format!(" extends {interfaces}"));
+            }
+            return Ok(output);
+        }
+        let super_class = match signature {
+            Some(signature) => Some(signature.superclass.print()),
+            None if self.super_class != 0 => {
+                let super_class = get_classname(self.super_class, &self.constant_pool)
+                    .ok_or(PrintError::InvalidConstant)?;
+                Some(internal_to_binary_name(&super_class))
+            }
+            None => None,
+        };
+        if let Some(super_class) = super_class.filter(|name| name != "java.lang.Object") {
+            output.push_str(&format!(" extends {super_class}"));
+        }
+        if !interfaces.is_empty() {
+            output.push_str(&format!(" implements {interfaces}"));
+        }
+        Ok(output)
+    }
 
-        let access_flags = self.access_flags.print_program();
+    // `public class HelloWorld<T> extends Base`, or the record or sealed
+    // class header. generic classes show their signature, falling back to
+    // the erased supertypes
+    fn print_declaration(&self) -> Result<String, PrintError> {
         let classname = get_classname(self.this_class, &self.constant_pool)
             .ok_or(PrintError::InvalidConstant)?;
-        output.push_str(&format!("{access_flags} {classname}\n"));
+        let signature = self.class_signature();
+        let type_parameters = signature
+            .as_ref()
+            .map(|signature| print_type_parameters(&signature.type_parameters))
+            .unwrap_or_default();
+        let classname = internal_to_binary_name(&classname) + &type_parameters;
+        let record = self
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Record(record) => Some(record),
+                _ => None,
+            });
+        let mut output = String::new();
+        match record {
+            Some(record) => {
+                let access_flags = self.access_flags.print_record();
+                let components = self.print_record_components(record)?;
+                output.push_str(&format!("{access_flags} {classname}({components})"));
+                // the java.lang.Record superclass is implied by the header
+                let interfaces = self.print_interfaces(signature.as_ref())?;
+                if !interfaces.is_empty() {
+                    output.push_str(&format!(" implements {interfaces}"));
+                }
+                output.push('\n');
+            }
+            None => {
+                let access_flags = self.access_flags.print_program();
+                let supertypes = self.print_supertypes(signature.as_ref())?;
+                output.push_str(&format!("{access_flags} {classname}{supertypes}"));
+                if let Some(permits) = self.print_permitted_subclasses()? {
+                    output.push_str(&format!(" permits {permits}"));
+                }
+                output.push('\n');
+            }
+        }
+        Ok(output)
+    }
 
-        output.push_str(&format!("  minor version: {}\n", self.minor_version));
-        output.push_str(&format!("  major version: {}\n", self.major_version));
-        output.push_str(&format!(
-            "  interfaces: {}, fields: {}, methods: {}, attributes: {}\n",
+    fn print_version(&self) -> String {
+        let mut output = String::new();
+        if self.version().is_preview() {
+            output.push_str(&format!(
+                "minor version: {} (preview)\n",
+                self.minor_version
+            ));
+        } else {
+            output.push_str(&format!("minor version: {}\n", self.minor_version));
+        }
+        output.push_str(&format!("major version: {}\n", self.major_version));
+        output
+    }
+
+    fn print_counts(&self) -> String {
+        format!(
+            "interfaces: {}, fields: {}, methods: {}, attributes: {}\n",
             self.interfaces.len(),
             self.fields.len(),
             self.methods.len(),
             self.attributes.len()
-        ));
+        )
+    }
 
-        output.push_str("Constant pool:\n");
-        for (i, constant) in self.constant_pool.iter().enumerate() {
-            output.push_str(&format!(
-                "  #{} = {}\n",
-                i + 1,
+    fn write_constant_pool<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        indent_width: usize,
+    ) -> Result<(), PrintError> {
+        w.write_str("Constant pool:\n")?;
+        let width = index_width(&self.constant_pool);
+        for (index, constant) in self.constant_pool.iter() {
+            writeln!(
+                w,
+                "{:indent_width$}{:>width$} = {}",
+                "",
+                format!("#{index}"),
                 constant.print(&self.constant_pool)?
-            ));
+            )?;
         }
+        Ok(())
+    }
 
-        output.push_str("{\n");
+    // the file name from the SourceFile attribute
+    fn source_file(&self) -> Result<Option<Cow<'_, str>>, PrintError> {
+        for attribute in &self.attributes {
+            if let Attribute::SourceFile(source_file) = attribute {
+                return Ok(Some(self.constant_pool.utf8(source_file.sourcefile_index)?));
+            }
+        }
+        Ok(None)
+    }
 
-        // fields
-        {
-            for field in &self.fields {
-                let access_flags = field.access_flags.print_program();
-                let name = get_utf8(field.name_index, &self.constant_pool)
-                    .ok_or(PrintError::InvalidConstant)?;
-                let descriptor = get_field_descriptor(field.descriptor_index, &self.constant_pool)
-                    .ok_or(PrintError::InvalidConstant)?
-                    .print();
-                output.push_str(&format!("  {} {} {};\n", access_flags, descriptor, name));
+    // `private java.lang.String message`, with the initial value of constant
+    // fields when `constants` is set
+    pub(super) fn print_field_declaration(
+        &self,
+        field: &Field,
+        constants: bool,
+    ) -> Result<String, PrintError> {
+        let mut output = field.access_flags.print_program();
+        let name =
+            get_utf8(field.name_index, &self.constant_pool).ok_or(PrintError::InvalidConstant)?;
+        let field_type = get_field_descriptor(field.descriptor_index, &self.constant_pool)
+            .ok_or(PrintError::InvalidConstant)?;
+        // generic members show their signature, falling back to the erased descriptor
+        let descriptor = get_signature(&field.attributes, &self.constant_pool)
+            .and_then(|signature| parse_field_signature(signature).ok())
+            .map(|(_, signature)| signature.print())
+            .unwrap_or_else(|| field_type.print());
+        if !output.is_empty() {
+            output.push(' ');
+        }
+        output.push_str(&format!("{descriptor} {name}"));
+        if constants {
+            if let Some(value) = get_constant_value(field, &field_type, &self.constant_pool) {
+                output.push_str(&format!(" = {value}"));
             }
-            output.push('\n');
         }
+        Ok(output)
+    }
 
-        // methods
+    // `public static void main(java.lang.String[])`; constructors are named
+    // after the class and the static initializer is `static {}`
+    pub(super) fn print_method_declaration(
+        &self,
+        method: &Method,
+        default_methods: &[&Method],
+    ) -> Result<String, PrintError> {
+        let mut output = method.access_flags.print_program();
+        if default_methods
+            .iter()
+            .any(|default_method| std::ptr::eq(*default_method, method))
         {
-            for method in &self.methods {
-                let access_flags = method.access_flags.print_program();
-                let name = get_utf8(method.name_index, &self.constant_pool)
-                    .ok_or(PrintError::InvalidConstant)?;
-                let descriptor =
-                    get_method_descriptor(method.descriptor_index, &self.constant_pool)
-                        .ok_or(PrintError::InvalidConstant)?;
-                output.push_str(&format!(
-                    "  {} {} {}({});\n",
-                    access_flags,
-                    descriptor.print_return(),
-                    name,
-                    descriptor.print_parameters()
-                ));
+            output.push_str(" default");
+        }
+        let name =
+            get_utf8(method.name_index, &self.constant_pool).ok_or(PrintError::InvalidConstant)?;
+        let descriptor = get_method_descriptor(method.descriptor_index, &self.constant_pool)
+            .ok_or(PrintError::InvalidConstant)?;
+        if name == "<clinit>" {
+            output.push_str(" {}");
+            return Ok(output.trim_start().to_string());
+        }
+        let signature = get_signature(&method.attributes, &self.constant_pool)
+            .and_then(|signature| parse_method_signature(signature).ok());
+        let (return_type, parameters) = match signature {
+            Some((_, signature)) => {
+                let type_parameters = print_type_parameters(&signature.type_parameters);
+                if !type_parameters.is_empty() {
+                    if !output.is_empty() {
+                        output.push(' ');
+                    }
+                    output.push_str(&type_parameters);
+                }
+                (signature.print_return(), signature.print_parameters())
             }
+            None => (descriptor.print_return(), descriptor.print_parameters()),
+        };
+        if !output.is_empty() {
+            output.push(' ');
+        }
+        if name == "<init>" {
+            let classname = get_classname(self.this_class, &self.constant_pool)
+                .ok_or(PrintError::InvalidConstant)?;
+            output.push_str(&internal_to_binary_name(&classname));
+        } else {
+            output.push_str(&format!("{return_type} {name}"));
+        }
+        output.push_str(&format!("({parameters})"));
+        Ok(output)
+    }
+
+    pub fn print(&self) -> Result<String, PrintError> {
+        if let Some(violation) = self.constant_pool.validate().into_iter().next() {
+            return Err(PrintError::InvalidConstantPool(violation));
         }
+        let mut output = self.print_declaration()?;
+        output.push_str(&indent(&self.print_version(), 2));
+        output.push_str(&indent(&self.print_counts(), 2));
+        self.write_constant_pool(&mut output, 2)?;
 
+        output.push_str("{\n");
+        for field in &self.fields {
+            output.push_str(&format!(
+                "  {};\n",
+                self.print_field_declaration(field, true)?
+            ));
+        }
+        output.push('\n');
+        let default_methods = self
+            .interface_methods()
+            .map(|summary| summary.default_methods)
+            .unwrap_or_default();
+        let mut methods = vec![];
+        for method in &self.methods {
+            let mut member = format!(
+                "{};\n",
+                self.print_method_declaration(method, &default_methods)?
+            );
+            let options = PrintOptions {
+                show_code: true,
+                ..PrintOptions::JAVAP
+            };
+            member.push_str(&indent(&self.print_method_body(method, &options)?, 2));
+            methods.push(member);
+        }
+        output.push_str(&indent(&methods.join("\n"), 2));
         output.push_str("}\n");
 
+        for attribute in &self.attributes {
+            match attribute {
+                Attribute::EnclosingMethod(enclosing_method) => {
+                    output.push_str(&enclosing_method.print(&self.constant_pool)?)
+                }
+                Attribute::InnerClasses(inner_classes) => {
+                    output.push_str(&inner_classes.print(&self.constant_pool)?)
+                }
+                _ => {}
+            }
+        }
+
+        Ok(output)
+    }
+
+    // `Signature: #14                          // Ljava/util/List<...>;`
+    fn print_signature_attribute(&self, signature: &Signature) -> Result<String, PrintError> {
+        let mut line = format!("Signature: #{}", signature.signature_index);
+        push_comment(
+            &mut line,
+            &self.constant_pool.utf8(signature.signature_index)?,
+        );
+        line.push('\n');
+        Ok(line)
+    }
+
+    // the descriptor, flags and attributes javap -v lists under each member
+    fn print_field_details(
+        &self,
+        field: &Field,
+        options: &PrintOptions,
+    ) -> Result<String, PrintError> {
+        let mut output = format!(
+            "descriptor: {}\n",
+            self.constant_pool.utf8(field.descriptor_index)?
+        );
+        output.push_str(&format!("{}\n", field.access_flags.print()));
+        for attribute in ordered(&field.attributes, options.stable) {
+            match attribute {
+                Attribute::ConstantValue(constant_value) => output.push_str(&format!(
+                    "ConstantValue: {}\n",
+                    get_comment(&self.constant_pool, constant_value.constantvalue_index)?
+                )),
+                Attribute::Signature(signature) => {
+                    output.push_str(&self.print_signature_attribute(signature)?)
+                }
+                _ => {}
+            }
+        }
+        Ok(output)
+    }
+
+    fn print_method_details(
+        &self,
+        method: &Method,
+        options: &PrintOptions,
+    ) -> Result<String, PrintError> {
+        let mut output = format!(
+            "descriptor: {}\n",
+            self.constant_pool.utf8(method.descriptor_index)?
+        );
+        output.push_str(&format!("{}\n", method.access_flags.print()));
+        for attribute in ordered(&method.attributes, options.stable) {
+            match attribute {
+                Attribute::Code(code) => {
+                    let descriptor =
+                        get_method_descriptor(method.descriptor_index, &self.constant_pool)
+                            .ok_or(PrintError::InvalidConstant)?;
+                    let mut args_size = descriptor.parameters.len() as u16;
+                    if !method.access_flags.contains(MethodAccessFlags::STATIC) {
+                        args_size += 1;
+                    }
+                    output.push_str(&code.print_with(
+                        args_size,
+                        Some(self.this_class),
+                        &self.constant_pool,
+                        options,
+                    )?);
+                }
+                Attribute::Signature(signature) => {
+                    output.push_str(&self.print_signature_attribute(signature)?)
+                }
+                _ => {}
+            }
+        }
         Ok(output)
     }
+
+    // the Code listing and the LineNumberTable and LocalVariableTable that
+    // `-c` and `-l` show under a method outside verbose mode
+    fn print_method_body(
+        &self,
+        method: &Method,
+        options: &PrintOptions,
+    ) -> Result<String, PrintError> {
+        let mut output = String::new();
+        let Some(code) = method.code() else {
+            return Ok(output);
+        };
+        if options.show_code {
+            output.push_str("Code:\n");
+            output.push_str(&code.print_instructions(Some(self.this_class), &self.constant_pool)?);
+        }
+        if options.show_line_numbers {
+            for attribute in ordered(&code.attributes, options.stable) {
+                match attribute {
+                    Attribute::LineNumberTable(line_number_table) => {
+                        if !options.stable {
+                            output.push_str(&line_number_table.print())
+                        }
+                    }
+                    Attribute::LocalVariableTable(local_variable_table) => {
+                        output.push_str(&local_variable_table.print(&self.constant_pool)?)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    // flags, this_class and super_class, between the versions and counts
+    fn print_class_info(&self) -> Result<String, PrintError> {
+        let mut output = format!("{}\n", self.access_flags.print());
+        let mut this_class = format!("this_class: #{}", self.this_class);
+        push_comment(
+            &mut this_class,
+            &get_class(&self.constant_pool, self.this_class)?,
+        );
+        output.push_str(&format!("{this_class}\n"));
+        let mut super_class = format!("super_class: #{}", self.super_class);
+        if self.super_class != 0 {
+            push_comment(
+                &mut super_class,
+                &get_class(&self.constant_pool, self.super_class)?,
+            );
+        }
+        output.push_str(&format!("{super_class}\n"));
+        Ok(output)
+    }
+
+    // orders members by name, then descriptor, given their indices of both
+    fn sort_members<T>(
+        &self,
+        members: &mut Vec<&T>,
+        indices: impl Fn(&T) -> (u16, u16),
+    ) -> Result<(), PrintError> {
+        let mut keyed = Vec::with_capacity(members.len());
+        for member in members.drain(..) {
+            let (name_index, descriptor_index) = indices(member);
+            let name = self.constant_pool.utf8(name_index)?;
+            let descriptor = self.constant_pool.utf8(descriptor_index)?;
+            keyed.push((name, descriptor, member));
+        }
+        keyed.sort_by(|a, b| (&a.0, &a.1).cmp(&(&b.0, &b.1)));
+        members.extend(keyed.into_iter().map(|(_, _, member)| member));
+        Ok(())
+    }
+
+    // a method's annotations, declaration and, as the options ask, its
+    // details or code, as the listing has them before they're indented
+    fn print_method_member(
+        &self,
+        method: &Method,
+        default_methods: &[&Method],
+        options: &PrintOptions,
+    ) -> Result<String, PrintError> {
+        let mut member = String::new();
+        if options.show_annotations {
+            member.push_str(&print_annotations(&method.attributes, &self.constant_pool)?);
+        }
+        let declaration = self.print_method_declaration(method, default_methods)?;
+        member.push_str(&format!("{declaration};\n"));
+        let details = if options.verbose {
+            self.print_method_details(method, options)?
+        } else {
+            self.print_method_body(method, options)?
+        };
+        member.push_str(&indent(&details, options.indent_width));
+        Ok(member)
+    }
+
+    // one of this class's methods as `print_with` lists it, without the
+    // class around it. visibility and member patterns are ignored
+    pub fn print_method(
+        &self,
+        method: &Method,
+        options: &PrintOptions,
+    ) -> Result<String, PrintError> {
+        if let Some(violation) = self.constant_pool.validate().into_iter().next() {
+            return Err(PrintError::InvalidConstantPool(violation));
+        }
+        let default_methods = self
+            .interface_methods()
+            .map(|summary| summary.default_methods)
+            .unwrap_or_default();
+        let member = self.print_method_member(method, &default_methods, options)?;
+        let mut output = String::new();
+        for line in member.lines() {
+            let line = match options.stable {
+                true => strip_indices(line),
+                false => line.to_string(),
+            };
+            match options.color {
+                true => output.push_str(&colorize_line(&line)),
+                false => output.push_str(&line),
+            }
+            output.push('\n');
+        }
+        Ok(output)
+    }
+
+    // the `javap -v -p` listing, without the file name, size and checksum
+    // lines that javap reads from the file system
+    pub fn print_verbose(&self) -> Result<String, PrintError> {
+        self.print_with(&PrintOptions::VERBOSE)
+    }
+
+    pub fn print_with(&self, options: &PrintOptions) -> Result<String, PrintError> {
+        let mut output = String::new();
+        self.write_to(&mut output, options)?;
+        Ok(output)
+    }
+
+    // streams the listing member by member, so batch printing doesn't hold a
+    // whole class in memory
+    pub fn write_to<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        options: &PrintOptions,
+    ) -> Result<(), PrintError> {
+        if options.color {
+            let mut w = Colorize::new(w);
+            self.write_listing(&mut w, options)?;
+            w.finish()?;
+            return Ok(());
+        }
+        self.write_listing(w, options)
+    }
+
+    pub(super) fn write_listing<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        options: &PrintOptions,
+    ) -> Result<(), PrintError> {
+        if options.stable {
+            let mut w = Stable::new(w);
+            self.write_members(&mut w, options)?;
+            w.finish()?;
+            return Ok(());
+        }
+        self.write_members(w, options)
+    }
+
+    fn write_members<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        options: &PrintOptions,
+    ) -> Result<(), PrintError> {
+        if let Some(violation) = self.constant_pool.validate().into_iter().next() {
+            return Err(PrintError::InvalidConstantPool(violation));
+        }
+        let width = options.indent_width;
+        if let Some(source_file) = self.source_file()? {
+            let line = format!("Compiled from \"{source_file}\"\n");
+            // javap nests it under the file details
+            if options.show_system_info {
+                w.write_str(&indent(&line, width))?;
+            } else {
+                w.write_str(&line)?;
+            }
+        }
+
+        if options.show_annotations {
+            w.write_str(&print_annotations(&self.attributes, &self.constant_pool)?)?;
+        }
+        let declaration = self.print_declaration()?;
+        if options.verbose {
+            w.write_str(&declaration)?;
+            let mut info = self.print_version();
+            info.push_str(&self.print_class_info()?);
+            info.push_str(&self.print_counts());
+            w.write_str(&indent(&info, width))?;
+            if !options.stable {
+                self.write_constant_pool(w, width)?;
+            }
+            w.write_str("{\n")?;
+        } else {
+            writeln!(w, "{} {{", declaration.trim_end())?;
+        }
+
+        let separator = if options.has_details() { "\n" } else { "" };
+        let mut first = true;
+        let mut write_member = |w: &mut W, member: &str| -> fmt::Result {
+            if !first {
+                w.write_str(separator)?;
+            }
+            first = false;
+            w.write_str(&indent(member, width))
+        };
+        let mut fields: Vec<_> = self.fields.iter().collect();
+        let mut methods: Vec<_> = self.methods.iter().collect();
+        if options.stable {
+            self.sort_members(&mut fields, |field| {
+                (field.name_index, field.descriptor_index)
+            })?;
+            self.sort_members(&mut methods, |method| {
+                (method.name_index, method.descriptor_index)
+            })?;
+        }
+        for field in fields {
+            let visibility = Visibility::of(
+                field.access_flags.contains(FieldAccessFlags::PUBLIC),
+                field.access_flags.contains(FieldAccessFlags::PROTECTED),
+                field.access_flags.contains(FieldAccessFlags::PRIVATE),
+            );
+            let name = self.constant_pool.utf8(field.name_index)?;
+            if !options.shows_member(visibility, &name) {
+                continue;
+            }
+            let mut member = String::new();
+            if options.show_annotations {
+                member.push_str(&print_annotations(&field.attributes, &self.constant_pool)?);
+            }
+            let declaration = self.print_field_declaration(field, options.show_constants)?;
+            member.push_str(&format!("{declaration};\n"));
+            if options.verbose {
+                member.push_str(&indent(&self.print_field_details(field, options)?, width));
+            }
+            write_member(w, &member)?;
+        }
+        let default_methods = self
+            .interface_methods()
+            .map(|summary| summary.default_methods)
+            .unwrap_or_default();
+        for method in methods {
+            let visibility = Visibility::of(
+                method.access_flags.contains(MethodAccessFlags::PUBLIC),
+                method.access_flags.contains(MethodAccessFlags::PROTECTED),
+                method.access_flags.contains(MethodAccessFlags::PRIVATE),
+            );
+            let name = self.constant_pool.utf8(method.name_index)?;
+            if !options.shows_member(visibility, &name) {
+                continue;
+            }
+            let member = self.print_method_member(method, &default_methods, options)?;
+            write_member(w, &member)?;
+        }
+        w.write_str("}\n")?;
+
+        if options.verbose {
+            for attribute in ordered(&self.attributes, options.stable) {
+                match attribute {
+                    Attribute::SourceFile(source_file) => writeln!(
+                        w,
+                        "SourceFile: \"{}\"",
+                        self.constant_pool.utf8(source_file.sourcefile_index)?
+                    )?,
+                    Attribute::Signature(signature) => {
+                        w.write_str(&self.print_signature_attribute(signature)?)?
+                    }
+                    Attribute::EnclosingMethod(enclosing_method) => {
+                        w.write_str(&enclosing_method.print(&self.constant_pool)?)?
+                    }
+                    Attribute::InnerClasses(inner_classes) => {
+                        w.write_str(&inner_classes.print(&self.constant_pool)?)?
+                    }
+                    Attribute::BootstrapMethods(bootstrap_methods) => {
+                        w.write_str(&bootstrap_methods.print(&self.constant_pool)?)?
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::class::parse_classfile;
+    use super::{PrintError, PrintOptions, Visibility};
+    use crate::class::{parse_classfile, ConstantPoolError, ConstantPoolViolation};
 
     // use super::*;
 
@@ -172,15 +864,15 @@ public class HelloWorld
   major version: 65
   interfaces: 0, fields: 1, methods: 3, attributes: 1
 Constant pool:
-  #1 = Methodref          #2.#3          // java/lang/Object.<init>:()V
-  #2 = Class              #4             // java/lang/Object
-  #3 = NameAndType        #5:#6          // <init>:()V
-  #4 = Utf8               java/lang/Object
-  #5 = Utf8               <init>
-  #6 = Utf8               ()V
-  #7 = String             #8             // Hello, World!
-  #8 = Utf8               Hello, World!
-  #9 = Fieldref           #10.#11        // HelloWorld.message:Ljava/lang/String;
+   #1 = Methodref          #2.#3          // java/lang/Object."<init>":()V
+   #2 = Class              #4             // java/lang/Object
+   #3 = NameAndType        #5:#6          // "<init>":()V
+   #4 = Utf8               java/lang/Object
+   #5 = Utf8               <init>
+   #6 = Utf8               ()V
+   #7 = String             #8             // Hello, World!
+   #8 = Utf8               Hello, World!
+   #9 = Fieldref           #10.#11        // HelloWorld.message:Ljava/lang/String;
   #10 = Class              #12            // HelloWorld
   #11 = NameAndType        #13:#14        // message:Ljava/lang/String;
   #12 = Utf8               HelloWorld
@@ -198,7 +890,7 @@ Constant pool:
   #24 = Utf8               java/io/PrintStream
   #25 = Utf8               println
   #26 = Utf8               (Ljava/lang/String;)V
-  #27 = Methodref          #10.#3         // HelloWorld.<init>:()V
+  #27 = Methodref          #10.#3         // HelloWorld."<init>":()V
   #28 = Methodref          #10.#29        // HelloWorld.sayHello:()V
   #29 = NameAndType        #30:#6         // sayHello:()V
   #30 = Utf8               sayHello
@@ -211,11 +903,666 @@ Constant pool:
 {
   private java.lang.String message;
 
-  public void <init>();
+  public HelloWorld();
+    Code:
+       0: aload_0
+       1: invokespecial #1                  // Method java/lang/Object."<init>":()V
+       4: aload_0
+       5: ldc           #7                  // String Hello, World!
+       7: putfield      #9                  // Field message:Ljava/lang/String;
+      10: return
+
   private void sayHello();
+    Code:
+       0: getstatic     #15                 // Field java/lang/System.out:Ljava/io/PrintStream;
+       3: aload_0
+       4: getfield      #9                  // Field message:Ljava/lang/String;
+       7: invokevirtual #21                 // Method java/io/PrintStream.println:(Ljava/lang/String;)V
+      10: return
+
+  public static void main(java.lang.String[]);
+    Code:
+       0: new           #10                 // class HelloWorld
+       3: dup
+       4: invokespecial #27                 // Method "<init>":()V
+       7: invokevirtual #28                 // Method sayHello:()V
+      10: return
+}
+"#;
+        assert_eq!(output, expected[1..]);
+    }
+
+    #[test]
+    fn test_print_code() {
+        let data = include_bytes!("../../../../java/Frames.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print().unwrap();
+        let expected = r#"
+  public static int parse(java.lang.String);
+    Code:
+       0: aload_0
+       1: invokestatic  #13                 // Method java/lang/Integer.parseInt:(Ljava/lang/String;)I
+       4: ireturn
+       5: astore_1
+       6: iconst_m1
+       7: ireturn
+    Exception table:
+       from    to  target type
+           0     4     5   Class java/lang/NumberFormatException
+
+  public static long twice(long, boolean);
+"#;
+        assert!(output.contains(&expected[1..]));
+
+        // abstract methods have no Code section
+        let data = include_bytes!("../../../../java/Shape.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let output = classfile.print().unwrap();
+        assert!(output.contains("  public abstract double area();\n\n  public default"));
+
+        // the handlers javac emits for synchronized blocks catch anything
+        let data = include_bytes!("../../../../java/Insns.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let output = classfile.print().unwrap();
+        let expected = r#"
+    Exception table:
+       from    to  target type
+          90   122   125   any
+         125   130   125   any
+"#;
+        assert!(output.contains(&expected[1..]));
+    }
+
+    #[test]
+    fn test_print_with() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print_with(&PrintOptions::default()).unwrap();
+        let expected = r#"
+Compiled from "HelloWorld.java"
+public class HelloWorld {
+  public HelloWorld();
+  public static void main(java.lang.String[]);
+}
+"#;
+        assert_eq!(output, expected[1..]);
+
+        let options = PrintOptions {
+            visibility: Visibility::Private,
+            show_line_numbers: true,
+            show_system_info: true,
+            indent_width: 4,
+            ..PrintOptions::JAVAP
+        };
+        let output = classfile.print_with(&options).unwrap();
+        let expected = r#"
+    Compiled from "HelloWorld.java"
+public class HelloWorld {
+    private java.lang.String message;
+
+    public HelloWorld();
+        LineNumberTable:
+          line 1: 0
+          line 3: 4
+
+    private void sayHello();
+        LineNumberTable:
+          line 6: 0
+          line 7: 10
+
+    public static void main(java.lang.String[]);
+        LineNumberTable:
+          line 10: 0
+          line 11: 10
+}
+"#;
+        assert_eq!(output, expected[1..]);
+
+        let data = include_bytes!("../../../../java/Constants.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let options = PrintOptions {
+            show_constants: true,
+            ..PrintOptions::JAVAP
+        };
+        let output = classfile.print_with(&options).unwrap();
+        assert!(output.contains("\n  public static final int ANSWER = 42;\n"));
+        assert!(output.contains("\n  static int notConstant;\n"));
+    }
+
+    #[test]
+    fn test_print_local_variables() {
+        let data = include_bytes!("../../../../java/Debug.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let options = PrintOptions {
+            show_line_numbers: true,
+            ..PrintOptions::JAVAP
+        };
+        let output = classfile.print_with(&options).unwrap();
+        let expected = r#"
+  public java.lang.String greet(java.lang.String);
+    LineNumberTable:
+      line 15: 0
+      line 16: 7
+    LocalVariableTable:
+      Start  Length  Slot  Name   Signature
+          0       9     0  this   LDebug;
+          0       9     1  name   Ljava/lang/String;
+          7       2     2 greeting   Ljava/lang/String;
+}
+"#;
+        assert!(output.ends_with(&expected[1..]));
+
+        // verbose output adds the generic types of locals
+        let output = classfile.print_verbose().unwrap();
+        let expected = r#"
+      LocalVariableTypeTable:
+        Start  Length  Slot  Name   Signature
+            0      40     0 values   Ljava/util/List<Ljava/lang/Integer;>;
+      StackMapTable: number_of_entries = 2
+"#;
+        assert!(output.contains(&expected[1..]));
+    }
+
+    #[test]
+    fn test_print_annotations() {
+        let options = PrintOptions {
+            show_annotations: true,
+            ..PrintOptions::JAVAP
+        };
+
+        let data = include_bytes!("../../../../java/Annotated.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let output = classfile.print_with(&options).unwrap();
+        let expected = r#"
+Compiled from "Annotated.java"
+@Annotated$Info(name="type", tags={"a", "b"}, kind=java.lang.annotation.ElementType.TYPE, type=java.lang.String.class, nested=@Annotated$Marker)
+@Annotated$Hidden
+public class Annotated {
+  @java.lang.Deprecated
+  @Annotated$Info(count=3)
+  int field;
+  public Annotated();
+  @Annotated$Hidden
+  public void method(int, int, java.lang.String);
+"#;
+        assert!(output.starts_with(&expected[1..]));
+
+        let data = include_bytes!("../../../../java/Annotated$Typed.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let output = classfile.print_with(&options).unwrap();
+        assert!(output.contains(
+            "\n@java.lang.annotation.Target({java.lang.annotation.ElementType.TYPE_USE, java.lang.annotation.ElementType.TYPE_PARAMETER})\n"
+        ));
+    }
+
+    #[test]
+    fn test_print_filtered() {
+        let data = include_bytes!("../../../../java/Locals.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let options = PrintOptions {
+            member_patterns: vec!["sum".to_string(), "c*t".to_string()],
+            ..PrintOptions::VERBOSE
+        };
+        let output = classfile.print_with(&options).unwrap();
+        assert!(output.contains("\n  private int count;\n"));
+        assert!(output.contains("\n  public static long sum(long, int, double);\n"));
+        assert!(!output.contains("public int loop(int);"));
+        assert!(!output.contains("public static void main("));
+
+        let options = PrintOptions {
+            visibility: Visibility::Public,
+            ..PrintOptions::JAVAP
+        };
+        let output = classfile.print_with(&options).unwrap();
+        let expected = r#"
+public class Locals {
+  public Locals();
+  public static long sum(long, int, double);
+  public int loop(int);
+  public void increment();
   public static void main(java.lang.String[]);
 }
+"#;
+        assert!(output.ends_with(&expected[1..]));
+    }
+
+    #[test]
+    fn test_print_method() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let method = classfile.find_methods("sayHello", None)[0];
+
+        let expected = r#"
+private void sayHello();
+  descriptor: ()V
+  flags: (0x0002) ACC_PRIVATE
+  Code:
+    stack=2, locals=1, args_size=1
+       0: getstatic     #15                 // Field java/lang/System.out:Ljava/io/PrintStream;
+       3: aload_0
+       4: getfield      #9                  // Field message:Ljava/lang/String;
+       7: invokevirtual #21                 // Method java/io/PrintStream.println:(Ljava/lang/String;)V
+      10: return
+    LineNumberTable:
+      line 6: 0
+      line 7: 10
+"#;
+        let output = classfile
+            .print_method(method, &PrintOptions::VERBOSE)
+            .unwrap();
+        assert_eq!(output, &expected[1..]);
+        // the same lines as in the whole listing, less indented
+        let listing = classfile.print_verbose().unwrap();
+        for line in output.lines() {
+            assert!(listing.contains(&format!("  {line}\n")));
+        }
+
+        let options = PrintOptions {
+            show_code: true,
+            stable: true,
+            ..PrintOptions::JAVAP
+        };
+        let output = classfile.print_method(method, &options).unwrap();
+        assert!(output.starts_with("private void sayHello();\n  Code:\n"));
+        assert!(output.contains(": getfield      Field message:Ljava/lang/String;\n"));
+    }
+
+    #[test]
+    fn test_print_color() {
+        let data = include_bytes!("../../../../java/Frames.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let options = PrintOptions {
+            color: true,
+            ..PrintOptions::VERBOSE
+        };
+        let output = classfile.print_with(&options).unwrap();
+        assert!(output.contains("\x1b[35mpublic\x1b[0m \x1b[35mclass\x1b[0m Frames\n"));
+        assert!(output.contains(": \x1b[33minvokestatic\x1b[0m  \x1b[36m#13\x1b[0m "));
+
+        // without the escapes it's the plain listing
+        let mut plain = String::new();
+        let mut rest = output.as_str();
+        while let Some(start) = rest.find('\x1b') {
+            plain.push_str(&rest[..start]);
+            rest = &rest[start..];
+            rest = &rest[rest.find('m').unwrap() + 1..];
+        }
+        plain.push_str(rest);
+        assert_eq!(plain, classfile.print_verbose().unwrap());
+    }
+
+    #[test]
+    fn test_print_stable() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        // methods move into name order, and references print what they
+        // resolve to in place of their index
+        let options = PrintOptions {
+            stable: true,
+            ..PrintOptions::VERBOSE
+        };
+        let output = classfile.print_with(&options).unwrap();
+        let expected = r#"
+  Compiled from "HelloWorld.java"
+public class HelloWorld
+  minor version: 0
+  major version: 65
+  flags: (0x0021) ACC_PUBLIC, ACC_SUPER
+  this_class: HelloWorld
+  super_class: java/lang/Object
+  interfaces: 0, fields: 1, methods: 3, attributes: 1
+{
+  private java.lang.String message;
+    descriptor: Ljava/lang/String;
+    flags: (0x0002) ACC_PRIVATE
+
+  public HelloWorld();
+    descriptor: ()V
+    flags: (0x0001) ACC_PUBLIC
+    Code:
+      stack=2, locals=1, args_size=1
+         0: aload_0
+         1: invokespecial Method java/lang/Object."<init>":()V
+         4: aload_0
+         5: ldc           String Hello, World!
+         7: putfield      Field message:Ljava/lang/String;
+        10: return
+
+  public static void main(java.lang.String[]);
+    descriptor: ([Ljava/lang/String;)V
+    flags: (0x0009) ACC_PUBLIC, ACC_STATIC
+    Code:
+      stack=2, locals=1, args_size=1
+         0: new           class HelloWorld
+         3: dup
+         4: invokespecial Method "<init>":()V
+         7: invokevirtual Method sayHello:()V
+        10: return
+
+  private void sayHello();
+    descriptor: ()V
+    flags: (0x0002) ACC_PRIVATE
+    Code:
+      stack=2, locals=1, args_size=1
+         0: getstatic     Field java/lang/System.out:Ljava/io/PrintStream;
+         3: aload_0
+         4: getfield      Field message:Ljava/lang/String;
+         7: invokevirtual Method java/io/PrintStream.println:(Ljava/lang/String;)V
+        10: return
+}
+SourceFile: "HelloWorld.java"
 "#;
         assert_eq!(output, expected[1..]);
     }
+
+    #[test]
+    fn test_write_to() {
+        use std::fmt;
+
+        // accepts a fixed number of bytes, then fails
+        struct Limited(usize);
+
+        impl fmt::Write for Limited {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0 = self.0.checked_sub(s.len()).ok_or(fmt::Error)?;
+                Ok(())
+            }
+        }
+
+        let data = include_bytes!("../../../../java/Frames.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let mut output = String::new();
+        classfile
+            .write_to(&mut output, &PrintOptions::VERBOSE)
+            .unwrap();
+        assert_eq!(output, classfile.print_verbose().unwrap());
+
+        assert_eq!(
+            classfile.write_to(&mut Limited(100), &PrintOptions::VERBOSE),
+            Err(PrintError::WriteError)
+        );
+        assert!(classfile
+            .write_to(&mut Limited(output.len()), &PrintOptions::VERBOSE)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_print_verbose() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        // javap also prints the file name, modification time and checksum
+        // before the class
+        let disasm = include_str!("../../../../java/HelloWorld.disasm");
+        let expected = disasm.lines().skip(3).collect::<Vec<_>>().join("\n") + "\n";
+        assert_eq!(classfile.print_verbose().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_print_verbose_code() {
+        let data = include_bytes!("../../../../java/Frames.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print_verbose().unwrap();
+        let expected = r#"
+  public static int parse(java.lang.String);
+    descriptor: (Ljava/lang/String;)I
+    flags: (0x0009) ACC_PUBLIC, ACC_STATIC
+    Code:
+      stack=1, locals=2, args_size=1
+         0: aload_0
+         1: invokestatic  #13                 // Method java/lang/Integer.parseInt:(Ljava/lang/String;)I
+         4: ireturn
+         5: astore_1
+         6: iconst_m1
+         7: ireturn
+      Exception table:
+         from    to  target type
+             0     4     5   Class java/lang/NumberFormatException
+      LineNumberTable:
+        line 27: 0
+        line 28: 5
+        line 29: 6
+      StackMapTable: number_of_entries = 1
+        frame_type = 69 /* same_locals_1_stack_item */
+          stack = [ class java/lang/NumberFormatException ]
+
+  public static long twice(long, boolean);
+    descriptor: (JZ)J
+    flags: (0x0009) ACC_PUBLIC, ACC_STATIC
+    Code:
+      stack=4, locals=5, args_size=2
+"#;
+        assert!(output.contains(&expected[1..]));
+    }
+
+    #[test]
+    fn test_print_verbose_members() {
+        let data = include_bytes!("../../../../java/Constants.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print_verbose().unwrap();
+        let expected = r#"
+  static final java.lang.String GREETING;
+    descriptor: Ljava/lang/String;
+    flags: (0x0018) ACC_STATIC, ACC_FINAL
+    ConstantValue: String hi \"there\"
+"#;
+        assert!(output.contains(&expected[1..]));
+
+        let data = include_bytes!("../../../../java/Generics.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print_verbose().unwrap();
+        let expected = r#"
+  int plain;
+    descriptor: I
+    flags: (0x0000)
+
+"#;
+        assert!(output.contains(&expected[1..]));
+        assert!(output.contains("    Signature: #24                          // [TV;\n"));
+    }
+
+    #[test]
+    fn test_print_default_method() {
+        let data = include_bytes!("../../../../java/Shape.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print().unwrap();
+        assert!(output.contains("  public abstract double area();\n"));
+        assert!(output.contains("  public default java.lang.String describe();\n"));
+        assert!(output.contains("  public static int sides();\n"));
+        assert!(output.contains("  private int helper();\n"));
+    }
+
+    #[test]
+    fn test_print_invoke_dynamic() {
+        let data = include_bytes!("../../../../java/Insns.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print().unwrap();
+        assert!(output.contains(
+            "  #11 = InvokeDynamic      #0:#12         // #0:run:()Ljava/lang/Runnable;\n"
+        ));
+        assert!(output
+            .contains("  #15 = InterfaceMethodref #16.#17        // java/lang/Runnable.run:()V\n"));
+        assert!(output.contains("  #67 = MethodType         #6             //  ()V\n"));
+        assert!(output.contains(
+            "  #68 = MethodHandle       6:#69          // REF_invokeStatic Insns.lambda$misc$0:()V\n"
+        ));
+
+        // the instruction names the bootstrap method listed after the class
+        let output = classfile.print_verbose().unwrap();
+        assert!(output.contains(
+            "17: invokedynamic #11,  0             // InvokeDynamic #0:run:()Ljava/lang/Runnable;\n"
+        ));
+        let expected = r#"
+BootstrapMethods:
+  0: #60 REF_invokeStatic java/lang/invoke/LambdaMetafactory.metafactory:(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodHandle;Ljava/lang/invoke/MethodType;)Ljava/lang/invoke/CallSite;
+    Method arguments:
+      #67 ()V
+      #68 REF_invokeStatic Insns.lambda$misc$0:()V
+      #67 ()V
+InnerClasses:
+"#;
+        assert!(output.contains(&expected[1..]));
+    }
+
+    #[test]
+    fn test_print_constant_values() {
+        let data = include_bytes!("../../../../java/Constants.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print().unwrap();
+        let expected = r#"
+  public static final int ANSWER = 42;
+  static final java.lang.String GREETING = "hi \"there\"";
+  static final float RATIO = 1.5f;
+  static final char LETTER = 'x';
+  static final boolean FLAG = true;
+  static final short SMALL = -7;
+  static final byte BYTE = 3;
+  final int instance = 5;
+  static int notConstant;
+  static final long BIG = 1099511627776l;
+  static final double HALF = 0.25d;
+  static final java.lang.String AFTER = "after";
+"#;
+        assert!(output.contains(&expected[1..]));
+        // Long and Double take two slots, so later entries keep javap's numbering
+        assert!(output.contains("  #45 = Double             0.25d\n"));
+        assert!(output.contains("  #48 = String             #49            // after\n"));
+    }
+
+    #[test]
+    fn test_print_inner_classes() {
+        let data = include_bytes!("../../../../java/Outer.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print().unwrap();
+        let expected = r#"
+}
+InnerClasses:
+  #7;                                     // class Outer$1
+  #26= #21 of #12;                        // Inner=class Outer$Inner of class Outer
+  static #27= #23 of #12;                 // Nested=class Outer$Nested of class Outer
+"#;
+        assert!(output.ends_with(&expected[1..]));
+    }
+
+    #[test]
+    fn test_print_generic_members() {
+        let data = include_bytes!("../../../../java/Generics.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print().unwrap();
+        assert!(output.contains(" java.util.List<java.lang.String> names;\n"));
+        assert!(output.contains(" java.util.Map<K, ? extends V> map;\n"));
+        assert!(output.contains(" java.util.List<?> any;\n"));
+        assert!(output.contains(" java.util.List<? super java.lang.Integer> sink;\n"));
+        assert!(output.contains(" V[] values;\n"));
+        assert!(output.contains(" int plain;\n"));
+        assert!(output.contains(
+            "  public <T extends java.lang.Number & java.lang.Comparable<T>> T max(java.util.List<? extends T>);\n"
+        ));
+        assert!(output.contains("  public java.util.Set<java.util.Map$Entry<K, V>> entrySet();\n"));
+        assert!(output.contains("  public java.util.Iterator<K> iterator();\n"));
+        assert!(output.contains("  <T> T[] first(T[]);\n"));
+    }
+
+    #[test]
+    fn test_print_invalid_signature() {
+        let mut data = include_bytes!("../../../../java/Generics.class").to_vec();
+        let signature = b"Ljava/util/List<Ljava/lang/String;>;";
+        let offset = data
+            .windows(signature.len())
+            .position(|window| window == signature)
+            .unwrap();
+        data[offset] = b'X';
+        let (_, classfile) = parse_classfile(&data).unwrap();
+
+        // an unparseable signature falls back to the erased descriptor
+        let output = classfile.print().unwrap();
+        assert!(output.contains(" java.util.List names;\n"));
+        assert!(output.contains(" java.util.List<?> any;\n"));
+    }
+
+    #[test]
+    fn test_print_record() {
+        let data = include_bytes!("../../../../java/Point.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print().unwrap();
+        assert!(
+            output.starts_with("public record Point<T>(int x, int y, java.util.List<T> tags)\n")
+        );
+        assert!(output.contains("  public java.util.List<T> tags();\n"));
+    }
+
+    #[test]
+    fn test_print_permitted_subclasses() {
+        let data = include_bytes!("../../../../java/Vehicle.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print().unwrap();
+        assert!(output.starts_with("public class Vehicle permits Vehicle$Car, Vehicle$Truck\n"));
+    }
+
+    #[test]
+    fn test_print_supertypes() {
+        let data = include_bytes!("../../../../java/Vehicle$Car.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let output = classfile.print_with(&PrintOptions::JAVAP).unwrap();
+        assert!(output.starts_with(
+            "Compiled from \"Vehicle.java\"\nfinal class Vehicle$Car extends Vehicle {\n"
+        ));
+
+        let data = include_bytes!("../../../../java/Generics.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let output = classfile.print().unwrap();
+        assert!(output.starts_with(
+            "public class Generics<K extends java.lang.Comparable<K>, V> extends java.util.AbstractMap<K, V> implements java.lang.Iterable<K>\n"
+        ));
+
+        let data = include_bytes!("../../../../java/Annotated$Marker.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let output = classfile.print_with(&PrintOptions::JAVAP).unwrap();
+        assert!(output
+            .contains("\ninterface Annotated$Marker extends java.lang.annotation.Annotation {\n"));
+    }
+
+    #[test]
+    fn test_print_preview() {
+        let mut data = include_bytes!("../../../../java/HelloWorld.class").to_vec();
+        data[4] = 0xFF;
+        data[5] = 0xFF;
+        let (_, classfile) = parse_classfile(&data).unwrap();
+
+        let output = classfile.print().unwrap();
+        assert!(output.contains("  minor version: 65535 (preview)\n"));
+    }
+
+    #[test]
+    fn test_print_invalid_constant_pool() {
+        let mut data = include_bytes!("../../../../java/HelloWorld.class").to_vec();
+        // #2 = Class #48, past the end of the pool
+        data[17] = 0x30;
+        let (_, classfile) = parse_classfile(&data).unwrap();
+
+        assert_eq!(
+            classfile.print(),
+            Err(PrintError::InvalidConstantPool(ConstantPoolViolation {
+                index: 2,
+                error: ConstantPoolError::InvalidIndex(48),
+            }))
+        );
+    }
 }
@@ -0,0 +1,323 @@
+use crate::asm::disassemble;
+use crate::class::{
+    Attribute, Code, ConstantPool, LineNumberTable, LocalVariableTable, LocalVariableTypeTable,
+    StackMapFrame, StackMapTable, VerificationTypeInfo,
+};
+
+use super::error::PrintError;
+use super::instruction::get_class;
+use super::options::PrintOptions;
+
+// shifts every non-empty line of `text` right by `width` spaces
+pub(super) fn indent(text: &str, width: usize) -> String {
+    let mut output = String::new();
+    for line in text.split_inclusive('\n') {
+        if line != "\n" {
+            output.push_str(&" ".repeat(width));
+        }
+        output.push_str(line);
+    }
+    output
+}
+
+// the attributes in the order they're printed: as stored, or sorted by name
+// for stable output
+pub(super) fn ordered<'b, 'a>(
+    attributes: &'b [Attribute<'a>],
+    stable: bool,
+) -> Vec<&'b Attribute<'a>> {
+    let mut ordered: Vec<_> = attributes.iter().collect();
+    if stable {
+        ordered.sort_by_key(|attribute| attribute.attribute_name().map(|name| name.as_str()));
+    }
+    ordered
+}
+
+impl<'a> Code<'a, Attribute<'a>> {
+    // the instructions and exception table, as listed by `javap -c`.
+    // references to members of `this_class` are printed without the class
+    pub fn print_instructions(
+        &self,
+        this_class: Option<u16>,
+        constant_pool: &ConstantPool,
+    ) -> Result<String, PrintError> {
+        let mut output = String::new();
+        for result in disassemble(&self.code) {
+            let (pc, instruction) = result?;
+            output.push_str(&instruction.print_in(pc, constant_pool, this_class)?);
+            output.push('\n');
+        }
+
+        if !self.exception_table.is_empty() {
+            output.push_str("Exception table:\n");
+            output.push_str("   from    to  target type\n");
+            for entry in &self.exception_table {
+                output.push_str(&format!(
+                    "   {:>5} {:>5} {:>5}   ",
+                    entry.start_pc, entry.end_pc, entry.handler_pc
+                ));
+                if entry.catch_type == 0 {
+                    output.push_str("any\n");
+                } else {
+                    let catch_type = get_class(constant_pool, entry.catch_type)?;
+                    output.push_str(&format!("Class {catch_type}\n"));
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    // the `Code:` section of `javap -v`; javap reports the number of declared
+    // parameters (plus `this`) as args_size, which the attribute doesn't store
+    pub fn print(
+        &self,
+        args_size: u16,
+        this_class: Option<u16>,
+        constant_pool: &ConstantPool,
+    ) -> Result<String, PrintError> {
+        self.print_with(args_size, this_class, constant_pool, &PrintOptions::VERBOSE)
+    }
+
+    // the stable option leaves out the LineNumberTable and lists the other
+    // attributes by name
+    pub(super) fn print_with(
+        &self,
+        args_size: u16,
+        this_class: Option<u16>,
+        constant_pool: &ConstantPool,
+        options: &PrintOptions,
+    ) -> Result<String, PrintError> {
+        let mut body = format!(
+            "stack={}, locals={}, args_size={}\n",
+            self.max_stack, self.max_locals, args_size
+        );
+        body.push_str(&self.print_instructions(this_class, constant_pool)?);
+        for attribute in ordered(&self.attributes, options.stable) {
+            match attribute {
+                Attribute::LineNumberTable(line_number_table) => {
+                    if !options.stable {
+                        body.push_str(&line_number_table.print())
+                    }
+                }
+                Attribute::LocalVariableTable(local_variable_table) => {
+                    body.push_str(&local_variable_table.print(constant_pool)?)
+                }
+                Attribute::LocalVariableTypeTable(local_variable_type_table) => {
+                    body.push_str(&local_variable_type_table.print(constant_pool)?)
+                }
+                Attribute::StackMapTable(stack_map_table) => {
+                    body.push_str(&stack_map_table.print(constant_pool)?)
+                }
+                _ => {}
+            }
+        }
+        Ok(format!("Code:\n{}", indent(&body, 2)))
+    }
+}
+
+impl LineNumberTable {
+    pub fn print(&self) -> String {
+        let mut output = String::from("LineNumberTable:\n");
+        for entry in &self.line_number_table {
+            output.push_str(&format!(
+                "  line {}: {}\n",
+                entry.line_number, entry.start_pc
+            ));
+        }
+        output
+    }
+}
+
+// one row of a local variable table, in javap's columns
+fn print_local_variable(
+    start_pc: u16,
+    length: u16,
+    index: u16,
+    name_index: u16,
+    descriptor_index: u16,
+    constant_pool: &ConstantPool,
+) -> Result<String, PrintError> {
+    Ok(format!(
+        "  {start_pc:>5} {length:>7} {index:>5} {:>5}   {}\n",
+        constant_pool.utf8(name_index)?,
+        constant_pool.utf8(descriptor_index)?
+    ))
+}
+
+impl LocalVariableTable {
+    pub fn print(&self, constant_pool: &ConstantPool) -> Result<String, PrintError> {
+        let mut output = String::from("LocalVariableTable:\n");
+        output.push_str("  Start  Length  Slot  Name   Signature\n");
+        for entry in &self.local_variable_table {
+            output.push_str(&print_local_variable(
+                entry.start_pc,
+                entry.length,
+                entry.index,
+                entry.name_index,
+                entry.descriptor_index,
+                constant_pool,
+            )?);
+        }
+        Ok(output)
+    }
+}
+
+impl LocalVariableTypeTable {
+    pub fn print(&self, constant_pool: &ConstantPool) -> Result<String, PrintError> {
+        let mut output = String::from("LocalVariableTypeTable:\n");
+        output.push_str("  Start  Length  Slot  Name   Signature\n");
+        for entry in &self.local_variable_type_table {
+            output.push_str(&print_local_variable(
+                entry.start_pc,
+                entry.length,
+                entry.index,
+                entry.name_index,
+                entry.signature_index,
+                constant_pool,
+            )?);
+        }
+        Ok(output)
+    }
+}
+
+fn print_verification_type(
+    verification_type: &VerificationTypeInfo,
+    constant_pool: &ConstantPool,
+) -> Result<String, PrintError> {
+    let name = match verification_type {
+        VerificationTypeInfo::Top => "top".to_string(),
+        VerificationTypeInfo::Integer => "int".to_string(),
+        VerificationTypeInfo::Float => "float".to_string(),
+        VerificationTypeInfo::Double => "double".to_string(),
+        VerificationTypeInfo::Long => "long".to_string(),
+        VerificationTypeInfo::Null => "null".to_string(),
+        VerificationTypeInfo::UninitializedThis => "this".to_string(),
+        VerificationTypeInfo::Object { cpool_index } => {
+            format!("class {}", get_class(constant_pool, *cpool_index)?)
+        }
+        VerificationTypeInfo::Uninitialized { offset } => format!("uninitialized {offset}"),
+    };
+    Ok(name)
+}
+
+// `locals = [ int, long ]`, or `stack = []` when empty
+fn print_verification_types(
+    label: &str,
+    verification_types: &[VerificationTypeInfo],
+    constant_pool: &ConstantPool,
+) -> Result<String, PrintError> {
+    if verification_types.is_empty() {
+        return Ok(format!("    {label} = []\n"));
+    }
+    let names = verification_types
+        .iter()
+        .map(|verification_type| print_verification_type(verification_type, constant_pool))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!("    {label} = [ {} ]\n", names.join(", ")))
+}
+
+impl StackMapTable {
+    pub fn print(&self, constant_pool: &ConstantPool) -> Result<String, PrintError> {
+        let mut output = format!(
+            "StackMapTable: number_of_entries = {}\n",
+            self.entries.len()
+        );
+        for frame in &self.entries {
+            // the parsed frame drops the frame_type byte, but it can be
+            // recovered from the variant and its offset or counts
+            let (frame_type, name) = match frame {
+                StackMapFrame::Same { offset_delta } => (*offset_delta, "same"),
+                StackMapFrame::SameLocals1StackItem { offset_delta, .. } => {
+                    (64 + *offset_delta, "same_locals_1_stack_item")
+                }
+                StackMapFrame::SameLocals1StackItemExtended { .. } => {
+                    (247, "same_locals_1_stack_item_frame_extended")
+                }
+                StackMapFrame::Chop { k, .. } => (251 - *k, "chop"),
+                StackMapFrame::SameExtended { .. } => (251, "same_frame_extended"),
+                StackMapFrame::Append { locals, .. } => (251 + locals.len() as u8, "append"),
+                StackMapFrame::Full { .. } => (255, "full_frame"),
+            };
+            output.push_str(&format!("  frame_type = {frame_type} /* {name} */\n"));
+            if !matches!(
+                frame,
+                StackMapFrame::Same { .. } | StackMapFrame::SameLocals1StackItem { .. }
+            ) {
+                output.push_str(&format!("    offset_delta = {}\n", frame.offset_delta()));
+            }
+            match frame {
+                StackMapFrame::SameLocals1StackItem { stack, .. }
+                | StackMapFrame::SameLocals1StackItemExtended { stack, .. } => {
+                    output.push_str(&print_verification_types(
+                        "stack",
+                        std::slice::from_ref(stack),
+                        constant_pool,
+                    )?);
+                }
+                StackMapFrame::Append { locals, .. } => {
+                    output.push_str(&print_verification_types("locals", locals, constant_pool)?);
+                }
+                StackMapFrame::Full { locals, stack, .. } => {
+                    output.push_str(&print_verification_types("locals", locals, constant_pool)?);
+                    output.push_str(&print_verification_types("stack", stack, constant_pool)?);
+                }
+                _ => {}
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::Constant;
+
+    #[test]
+    fn test_print_stack_map_table() {
+        let constant_pool = ConstantPool::from(vec![
+            Constant::Class { name_index: 2 },
+            Constant::Utf8 {
+                value: b"[I".into(),
+            },
+        ]);
+        let stack_map_table = StackMapTable {
+            entries: vec![
+                StackMapFrame::Chop {
+                    k: 2,
+                    offset_delta: 9,
+                },
+                StackMapFrame::SameExtended { offset_delta: 300 },
+                StackMapFrame::Full {
+                    offset_delta: 4,
+                    locals: vec![
+                        VerificationTypeInfo::UninitializedThis,
+                        VerificationTypeInfo::Object { cpool_index: 1 },
+                    ],
+                    stack: vec![],
+                },
+            ],
+        };
+        let expected = r#"
+StackMapTable: number_of_entries = 3
+  frame_type = 249 /* chop */
+    offset_delta = 9
+  frame_type = 251 /* same_frame_extended */
+    offset_delta = 300
+  frame_type = 255 /* full_frame */
+    offset_delta = 4
+    locals = [ this, class "[I" ]
+    stack = []
+"#;
+        assert_eq!(
+            stack_map_table.print(&constant_pool).unwrap(),
+            expected[1..]
+        );
+    }
+
+    #[test]
+    fn test_indent() {
+        assert_eq!(indent("a\n\n  b\n", 2), "  a\n\n    b\n");
+        assert_eq!(indent("a", 4), "    a");
+    }
+}
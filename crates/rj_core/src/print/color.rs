@@ -0,0 +1,221 @@
+use std::fmt;
+
+const RESET: &str = "\x1b[0m";
+const KEYWORD: &str = "\x1b[35m";
+const INDEX: &str = "\x1b[36m";
+const MNEMONIC: &str = "\x1b[33m";
+const COMMENT: &str = "\x1b[90m";
+
+// modifiers and declaration keywords; type names are left alone so
+// descriptors don't light up
+const KEYWORDS: [&str; 18] = [
+    "abstract",
+    "class",
+    "default",
+    "enum",
+    "extends",
+    "final",
+    "implements",
+    "interface",
+    "native",
+    "permits",
+    "private",
+    "protected",
+    "public",
+    "record",
+    "static",
+    "synchronized",
+    "transient",
+    "volatile",
+];
+
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_identifier_part(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+// where a javap `//` comment starts, if the line has one. Utf8 entries are
+// printed verbatim, so a `//` inside them isn't a comment
+pub(super) fn comment_start(line: &str) -> Option<usize> {
+    if line.contains(" = Utf8 ") {
+        return None;
+    }
+    line.find("// ")
+        .filter(|&start| start == 0 || line[..start].ends_with(' '))
+}
+
+// the byte range of the mnemonic in an instruction line like `  4: ireturn`
+fn mnemonic_range(line: &str) -> Option<(usize, usize)> {
+    let trimmed = line.trim_start();
+    let offset = line.len() - trimmed.len();
+    let digits = trimmed.find(|c: char| !c.is_ascii_digit())?;
+    if digits == 0 || !trimmed[digits..].starts_with(": ") {
+        return None;
+    }
+    let start = offset + digits + 2;
+    // switch cases look the same but jump to an offset
+    if !line[start..].starts_with(|c: char| c.is_ascii_lowercase()) {
+        return None;
+    }
+    let end = line[start..]
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .map_or(line.len(), |end| start + end);
+    (end > start).then_some((start, end))
+}
+
+fn paint(output: &mut String, color: &str, text: &str) {
+    output.push_str(color);
+    output.push_str(text);
+    output.push_str(RESET);
+}
+
+// wraps keywords, `#n` constant indices, instruction mnemonics and trailing
+// comments of one line in ANSI colors
+pub(super) fn colorize_line(line: &str) -> String {
+    let (code, comment) = match comment_start(line) {
+        Some(start) => line.split_at(start),
+        None => (line, ""),
+    };
+    let mnemonic = mnemonic_range(code);
+
+    let mut output = String::new();
+    let mut in_string = false;
+    let mut previous = ' ';
+    let mut chars = code.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if let Some((start, end)) = mnemonic.filter(|&(start, _)| start == i) {
+            paint(&mut output, MNEMONIC, &code[start..end]);
+            while chars.peek().is_some_and(|&(j, _)| j < end) {
+                chars.next();
+            }
+            previous = 'a';
+            continue;
+        }
+        if c == '"' {
+            in_string = !in_string;
+        }
+        if in_string || c == '"' {
+            output.push(c);
+            previous = c;
+            continue;
+        }
+        if c == '#' && chars.peek().is_some_and(|&(_, next)| next.is_ascii_digit()) {
+            let mut end = i + 1;
+            while let Some(&(j, next)) = chars.peek() {
+                if !next.is_ascii_digit() {
+                    break;
+                }
+                end = j + next.len_utf8();
+                chars.next();
+            }
+            paint(&mut output, INDEX, &code[i..end]);
+            previous = '0';
+            continue;
+        }
+        if is_identifier_start(c) && !is_identifier_part(previous) {
+            let mut end = i + c.len_utf8();
+            while let Some(&(j, next)) = chars.peek() {
+                if !is_identifier_part(next) {
+                    break;
+                }
+                end = j + next.len_utf8();
+                chars.next();
+            }
+            let word = &code[i..end];
+            // parts of qualified names, like the `default` in `a.default.B`
+            let qualified = matches!(previous, '.' | '/');
+            if !qualified && KEYWORDS.contains(&word) {
+                paint(&mut output, KEYWORD, word);
+            } else {
+                output.push_str(word);
+            }
+            previous = 'a';
+            continue;
+        }
+        output.push(c);
+        previous = c;
+    }
+    if !comment.is_empty() {
+        paint(&mut output, COMMENT, comment);
+    }
+    output
+}
+
+// colors everything written through it a line at a time, holding back any
+// unfinished line until the rest of it arrives or `finish` is called
+pub(super) struct Colorize<'w, W: fmt::Write> {
+    inner: &'w mut W,
+    line: String,
+}
+
+impl<'w, W: fmt::Write> Colorize<'w, W> {
+    pub(super) fn new(inner: &'w mut W) -> Self {
+        Colorize {
+            inner,
+            line: String::new(),
+        }
+    }
+
+    pub(super) fn finish(self) -> fmt::Result {
+        if self.line.is_empty() {
+            return Ok(());
+        }
+        self.inner.write_str(&colorize_line(&self.line))
+    }
+}
+
+impl<'w, W: fmt::Write> fmt::Write for Colorize<'w, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.line.push_str(s);
+        while let Some(end) = self.line.find('\n') {
+            let line = colorize_line(&self.line[..end]);
+            self.inner.write_str(&line)?;
+            self.inner.write_char('\n')?;
+            self.line.drain(..=end);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn test_colorize_line() {
+        assert_eq!(
+            colorize_line("public class HelloWorld extends java.lang.Object {"),
+            "\x1b[35mpublic\x1b[0m \x1b[35mclass\x1b[0m HelloWorld \x1b[35mextends\x1b[0m java.lang.Object {"
+        );
+        assert_eq!(
+            colorize_line("       1: invokespecial #1                  // Method a.b:()V"),
+            "       1: \x1b[33minvokespecial\x1b[0m \x1b[36m#1\x1b[0m                  \x1b[90m// Method a.b:()V\x1b[0m"
+        );
+        // strings, qualified names and Utf8 values stay plain
+        assert_eq!(
+            colorize_line("Compiled from \"public.java\""),
+            "Compiled from \"public.java\""
+        );
+        assert_eq!(colorize_line("  a.static.B x;"), "  a.static.B x;");
+        assert_eq!(
+            colorize_line("   #8 = Utf8               http://x // y"),
+            "   \x1b[36m#8\x1b[0m = Utf8               http://x // y"
+        );
+        assert_eq!(colorize_line("  line 4: 0"), "  line 4: 0");
+        assert_eq!(colorize_line("            1: 28"), "            1: 28");
+    }
+
+    #[test]
+    fn test_colorize_writer() {
+        let mut output = String::new();
+        let mut w = Colorize::new(&mut output);
+        w.write_str("pub").unwrap();
+        w.write_str("lic int x;\nfinal").unwrap();
+        w.finish().unwrap();
+        assert_eq!(output, "\x1b[35mpublic\x1b[0m int x;\n\x1b[35mfinal\x1b[0m");
+    }
+}
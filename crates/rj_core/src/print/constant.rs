@@ -1,8 +1,10 @@
 use std::borrow::Cow;
+use std::fmt;
 
-use crate::class::Constant;
+use crate::class::{decode_modified_utf8, Constant, ConstantPool, NameAndType};
 
 use super::error::PrintError;
+use super::instruction::{check_name, get_constant, get_reference_kind};
 
 fn get_constant_name(constant: &Constant) -> &'static str {
     match constant {
@@ -26,16 +28,86 @@ fn get_constant_name(constant: &Constant) -> &'static str {
     }
 }
 
+// javap's escaping of Utf8 values, used both for Utf8 entries and for the
+// String comments that point at them
+pub(super) fn escape(value: &str) -> Cow<str> {
+    if !value
+        .chars()
+        .any(|c| c.is_control() || c == '"' || c == '\'' || c == '\\')
+    {
+        return Cow::Borrowed(value);
+    }
+    let mut escaped = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            '"' | '\'' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+// Java's Float.toString and Double.toString, which javap uses: plain
+// notation from 10^-3 up to 10^7, computerized scientific notation outside it
+fn format_java_float(value: f64, shortest: String, exponent: String) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    let magnitude = value.abs();
+    if magnitude == 0.0 || (1e-3..1e7).contains(&magnitude) {
+        return shortest;
+    }
+    let Some((mantissa, exponent)) = exponent.split_once('e') else {
+        return shortest;
+    };
+    if mantissa.contains('.') {
+        format!("{mantissa}E{exponent}")
+    } else {
+        format!("{mantissa}.0E{exponent}")
+    }
+}
+
+pub(super) fn format_float(value: f32) -> String {
+    format_java_float(value as f64, format!("{value:?}"), format!("{value:e}"))
+}
+
+pub(super) fn format_double(value: f64) -> String {
+    format_java_float(value, format!("{value:?}"), format!("{value:e}"))
+}
+
 fn get_value<'a>(constant: &'a Constant) -> Result<Cow<'a, str>, PrintError> {
     match constant {
-        Constant::Utf8 { value } => Ok(core::str::from_utf8(value)?.into()),
+        Constant::Utf8 { value } => match decode_modified_utf8(value)? {
+            Cow::Borrowed(value) => Ok(escape(value)),
+            Cow::Owned(value) => Ok(escape(&value).into_owned().into()),
+        },
+        Constant::Integer { value } => Ok(value.to_string().into()),
+        Constant::Float { value } => Ok(format!("{}f", format_float(*value)).into()),
+        Constant::Long { value } => Ok(format!("{value}l").into()),
+        Constant::Double { value } => Ok(format!("{}d", format_double(*value)).into()),
         Constant::Class { name_index } => Ok(format!("#{}", name_index).into()),
         Constant::String { string_index } => Ok(format!("#{}", string_index).into()),
         Constant::Fieldref {
             class_index,
             name_and_type_index,
-        } => Ok(format!("#{}.#{}", class_index, name_and_type_index).into()),
-        Constant::Methodref {
+        }
+        | Constant::Methodref {
+            class_index,
+            name_and_type_index,
+        }
+        | Constant::InterfaceMethodref {
             class_index,
             name_and_type_index,
         } => Ok(format!("#{}.#{}", class_index, name_and_type_index).into()),
@@ -43,124 +115,367 @@ fn get_value<'a>(constant: &'a Constant) -> Result<Cow<'a, str>, PrintError> {
             name_index,
             descriptor_index,
         } => Ok(format!("#{}:#{}", name_index, descriptor_index).into()),
-        _ => unimplemented!("constant: {:?}", constant),
-    }
-}
-
-fn validate_utf8<'a>(
-    constant_pool: &'a [Constant],
-    index: u16,
-) -> Result<Cow<'a, str>, PrintError> {
-    let reference = &constant_pool[index as usize - 1];
-    match reference {
-        Constant::Utf8 { .. } => Ok(get_value(reference)?),
-        _ => Err(PrintError::InvalidConstant),
-    }
-}
-
-fn validate_class<'a>(
-    constant_pool: &'a [Constant],
-    index: u16,
-) -> Result<Cow<'a, str>, PrintError> {
-    let reference = &constant_pool[index as usize - 1];
-    match reference {
-        Constant::Class { .. } => Ok(get_comment(reference, constant_pool)?),
-        _ => Err(PrintError::InvalidConstant),
+        Constant::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => Ok(format!("{}:#{}", *reference_kind as u8, reference_index).into()),
+        Constant::MethodType { descriptor_index } => Ok(format!("#{}", descriptor_index).into()),
+        Constant::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        }
+        | Constant::InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => Ok(format!("#{}:#{}", bootstrap_method_attr_index, name_and_type_index).into()),
+        Constant::Module { name_index } | Constant::Package { name_index } => {
+            Ok(format!("#{}", name_index).into())
+        }
     }
 }
 
-fn validate_name_and_type<'a>(
-    constant_pool: &'a [Constant],
-    index: u16,
-) -> Result<Cow<'a, str>, PrintError> {
-    let reference = &constant_pool[index as usize - 1];
-    match reference {
-        Constant::NameAndType { .. } => Ok(get_comment(reference, constant_pool)?),
-        _ => Err(PrintError::InvalidConstant),
-    }
+fn get_name_and_type(constant_pool: &ConstantPool, index: u16) -> Result<String, PrintError> {
+    let NameAndType { name, descriptor } = constant_pool.name_and_type(index)?;
+    Ok(format!("{}:{descriptor}", check_name(&name)))
 }
 
 fn get_comment<'a>(
-    constant: &'a Constant,
-    constant_pool: &[Constant],
+    constant: &Constant,
+    constant_pool: &'a ConstantPool,
 ) -> Result<Cow<'a, str>, PrintError> {
     match constant {
-        Constant::Utf8 { .. } => Ok("".into()),
-        Constant::Class { name_index } => {
-            let value = validate_utf8(constant_pool, *name_index)?;
-            Ok(format!("{value}").into())
-        }
-        Constant::String { string_index } => {
-            let value = validate_utf8(constant_pool, *string_index)?;
-            Ok(format!("{value}").into())
+        Constant::Utf8 { .. }
+        | Constant::Integer { .. }
+        | Constant::Float { .. }
+        | Constant::Long { .. }
+        | Constant::Double { .. } => Ok("".into()),
+        Constant::Class { name_index } | Constant::Module { name_index } => {
+            Ok(check_name(&constant_pool.utf8(*name_index)?).into())
         }
+        Constant::String { string_index } => Ok(escape(&constant_pool.utf8(*string_index)?)
+            .into_owned()
+            .into()),
         Constant::Fieldref {
             class_index,
             name_and_type_index,
-        } => {
-            let class = validate_class(constant_pool, *class_index)?;
-            let name_and_type = validate_name_and_type(constant_pool, *name_and_type_index)?;
-            Ok(format!("{class}.{}", name_and_type).into())
         }
-        Constant::Methodref {
+        | Constant::Methodref {
+            class_index,
+            name_and_type_index,
+        }
+        | Constant::InterfaceMethodref {
             class_index,
             name_and_type_index,
         } => {
-            let class = validate_class(constant_pool, *class_index)?;
-            let name_and_type = validate_name_and_type(constant_pool, *name_and_type_index)?;
-            Ok(format!("{class}.{}", name_and_type).into())
+            let class = check_name(&constant_pool.class_name(*class_index)?);
+            let name_and_type = get_name_and_type(constant_pool, *name_and_type_index)?;
+            Ok(format!("{class}.{name_and_type}").into())
         }
         Constant::NameAndType {
             name_index,
             descriptor_index,
         } => {
-            let name = validate_utf8(constant_pool, *name_index)?;
-            let descriptor = validate_utf8(constant_pool, *descriptor_index)?;
+            let name = check_name(&constant_pool.utf8(*name_index)?);
+            let descriptor = constant_pool.utf8(*descriptor_index)?;
             Ok(format!("{name}:{descriptor}").into())
         }
-        _ => unimplemented!("constant: {:?}", constant),
+        Constant::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => {
+            let reference = get_constant(constant_pool, *reference_index)?;
+            let reference = get_comment(reference, constant_pool)?;
+            Ok(format!("{} {reference}", get_reference_kind(*reference_kind)).into())
+        }
+        // javap leaves two spaces after the `//` of a MethodType
+        Constant::MethodType { descriptor_index } => {
+            Ok(format!(" {}", constant_pool.utf8(*descriptor_index)?).into())
+        }
+        Constant::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        }
+        | Constant::InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => {
+            let name_and_type = get_name_and_type(constant_pool, *name_and_type_index)?;
+            Ok(format!("#{bootstrap_method_attr_index}:{name_and_type}").into())
+        }
+        Constant::Package { name_index } => Ok(constant_pool.utf8(*name_index)?),
+    }
+}
+
+// a constant as javap lists it without its `#n` references, e.g. for the
+// arguments of a bootstrap method
+pub(super) fn get_string_value(
+    constant_pool: &ConstantPool,
+    index: u16,
+) -> Result<String, PrintError> {
+    let constant = get_constant(constant_pool, index)?;
+    let value = match constant {
+        Constant::Utf8 { .. }
+        | Constant::Integer { .. }
+        | Constant::Float { .. }
+        | Constant::Long { .. }
+        | Constant::Double { .. } => get_value(constant)?,
+        Constant::MethodType { descriptor_index } => constant_pool.utf8(*descriptor_index)?,
+        _ => get_comment(constant, constant_pool)?,
+    };
+    Ok(value.into_owned())
+}
+
+// the width of the right-aligned `#n` column, which javap sizes from
+// constant_pool_count
+pub(crate) fn index_width(constant_pool: &ConstantPool) -> usize {
+    (constant_pool.len() + 1).to_string().len() + 1
+}
+
+// javap starts comments at column 42 of the line, which begins with two spaces
+// of indentation, the index and ` = `, followed by the tag name and a space;
+// a longer value is separated from its comment by a single space
+fn value_width(constant_pool: &ConstantPool) -> usize {
+    42usize.saturating_sub(2 + index_width(constant_pool) + 3 + 19 + 1)
+}
+
+fn write_constant(
+    f: &mut impl fmt::Write,
+    name: &str,
+    value: &str,
+    comment: &str,
+    value_width: usize,
+) -> fmt::Result {
+    if comment.is_empty() {
+        return write!(f, "{name:<18} {value}");
+    }
+    write!(f, "{name:<18} {value:<value_width$} // {comment}")
+}
+
+// a constant pool entry as javap prints it after `#n = `
+pub struct DisplayConstant<'a, 'b> {
+    constant: &'b Constant<'a>,
+    constant_pool: &'b ConstantPool<'a>,
+}
+
+impl fmt::Display for DisplayConstant<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = get_value(self.constant).map_err(|_| fmt::Error)?;
+        let comment = get_comment(self.constant, self.constant_pool).map_err(|_| fmt::Error)?;
+        write_constant(
+            f,
+            get_constant_name(self.constant),
+            &value,
+            &comment,
+            value_width(self.constant_pool),
+        )
     }
 }
 
 impl<'a> Constant<'a> {
-    pub fn print(&self, constant_pool: &[Constant]) -> Result<String, PrintError> {
-        let name = get_constant_name(self);
+    pub fn display<'b>(&'b self, constant_pool: &'b ConstantPool<'a>) -> DisplayConstant<'a, 'b> {
+        DisplayConstant {
+            constant: self,
+            constant_pool,
+        }
+    }
+
+    pub fn print(&self, constant_pool: &ConstantPool) -> Result<String, PrintError> {
         let value = get_value(self)?;
         let comment = get_comment(self, constant_pool)?;
-        let comment = if comment.is_empty() {
-            "".to_string()
-        } else {
-            format!("// {}", comment)
-        };
-        Ok(format!("{name:<19}{value:<15}{comment}")
-            .trim_end()
-            .to_string())
+        let mut output = String::new();
+        write_constant(
+            &mut output,
+            get_constant_name(self),
+            &value,
+            &comment,
+            value_width(constant_pool),
+        )?;
+        Ok(output)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::class::ReferenceKind;
 
     #[test]
     fn test_utf8() {
-        let constant_pool = [Constant::Utf8 {
-            value: b"Hello, World!",
-        }];
-        let constant = &constant_pool[0];
+        let constant_pool = ConstantPool::from(vec![Constant::Utf8 {
+            value: b"Hello, World!".into(),
+        }]);
+        let constant = constant_pool.get(1).unwrap();
         assert_eq!("Hello, World!", get_value(constant).unwrap());
         assert_eq!("", get_comment(constant, &constant_pool).unwrap());
     }
 
+    #[test]
+    fn test_numeric() {
+        let constant_pool = ConstantPool::from(vec![
+            Constant::Integer { value: -7 },
+            Constant::Float { value: 1.5 },
+        ]);
+        assert_eq!("-7", get_value(constant_pool.get(1).unwrap()).unwrap());
+        assert_eq!("1.5f", get_value(constant_pool.get(2).unwrap()).unwrap());
+        assert_eq!(
+            "Integer            -7",
+            constant_pool.get(1).unwrap().print(&constant_pool).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_java_floats() {
+        assert_eq!("1.0E10", format_float(1e10));
+        assert_eq!("1.5E-5", format_float(1.5e-5));
+        assert_eq!("9999999.0", format_float(9999999.0));
+        assert_eq!("-0.0", format_float(-0.0));
+        assert_eq!("Infinity", format_float(f32::INFINITY));
+        assert_eq!("1.0E7", format_double(1e7));
+        assert_eq!("1.23456789E8", format_double(123456789.0));
+        assert_eq!("0.001", format_double(0.001));
+        assert_eq!("NaN", format_double(f64::NAN));
+
+        let constant_pool = ConstantPool::from(vec![
+            Constant::Long { value: 123 },
+            Constant::Double { value: 2.0 },
+        ]);
+        assert_eq!(
+            "Long               123l",
+            constant_pool.get(1).unwrap().print(&constant_pool).unwrap()
+        );
+        assert_eq!(
+            "Double             2.0d",
+            constant_pool.get(3).unwrap().print(&constant_pool).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_method_handle() {
+        let constant_pool = ConstantPool::from(vec![
+            Constant::MethodHandle {
+                reference_kind: ReferenceKind::InvokeStatic,
+                reference_index: 2,
+            },
+            Constant::InterfaceMethodref {
+                class_index: 3,
+                name_and_type_index: 5,
+            },
+            Constant::Class { name_index: 4 },
+            Constant::Utf8 {
+                value: b"java/util/List".into(),
+            },
+            Constant::NameAndType {
+                name_index: 6,
+                descriptor_index: 7,
+            },
+            Constant::Utf8 {
+                value: b"of".into(),
+            },
+            Constant::Utf8 {
+                value: b"()Ljava/util/List;".into(),
+            },
+            Constant::MethodType {
+                descriptor_index: 7,
+            },
+        ]);
+        let print = |index| {
+            constant_pool
+                .get(index)
+                .unwrap()
+                .print(&constant_pool)
+                .unwrap()
+        };
+        assert_eq!(
+            "MethodHandle       6:#2            // REF_invokeStatic java/util/List.of:()Ljava/util/List;",
+            print(1)
+        );
+        assert_eq!(
+            "InterfaceMethodref #3.#5           // java/util/List.of:()Ljava/util/List;",
+            print(2)
+        );
+        assert_eq!(
+            "MethodType         #7              //  ()Ljava/util/List;",
+            print(8)
+        );
+    }
+
+    #[test]
+    fn test_dynamic() {
+        let constant_pool = ConstantPool::from(vec![
+            Constant::InvokeDynamic {
+                bootstrap_method_attr_index: 0,
+                name_and_type_index: 3,
+            },
+            Constant::Dynamic {
+                bootstrap_method_attr_index: 1,
+                name_and_type_index: 4,
+            },
+            Constant::NameAndType {
+                name_index: 5,
+                descriptor_index: 6,
+            },
+            Constant::NameAndType {
+                name_index: 7,
+                descriptor_index: 8,
+            },
+            Constant::Utf8 {
+                value: b"run".into(),
+            },
+            Constant::Utf8 {
+                value: b"()Ljava/lang/Runnable;".into(),
+            },
+            Constant::Utf8 { value: b"_".into() },
+            Constant::Utf8 { value: b"I".into() },
+        ]);
+        let print = |index| {
+            constant_pool
+                .get(index)
+                .unwrap()
+                .print(&constant_pool)
+                .unwrap()
+        };
+        assert_eq!(
+            "InvokeDynamic      #0:#3           // #0:run:()Ljava/lang/Runnable;",
+            print(1)
+        );
+        assert_eq!("Dynamic            #1:#4           // #1:_:I", print(2));
+    }
+
+    #[test]
+    fn test_module_and_package() {
+        let constant_pool = ConstantPool::from(vec![
+            Constant::Module { name_index: 3 },
+            Constant::Package { name_index: 4 },
+            Constant::Utf8 {
+                value: b"java.base".into(),
+            },
+            Constant::Utf8 {
+                value: b"demo/pkg".into(),
+            },
+        ]);
+        let print = |index| {
+            constant_pool
+                .get(index)
+                .unwrap()
+                .print(&constant_pool)
+                .unwrap()
+        };
+        assert_eq!(
+            "Module             #3              // \"java.base\"",
+            print(1)
+        );
+        assert_eq!("Package            #4              // demo/pkg", print(2));
+    }
+
     #[test]
     fn test_class() {
-        let constant_pool = [
+        let constant_pool = ConstantPool::from(vec![
             Constant::Class { name_index: 2 },
             Constant::Utf8 {
-                value: b"java/lang/Object",
+                value: b"java/lang/Object".into(),
             },
-        ];
-        let constant = &constant_pool[0];
+        ]);
+        let constant = constant_pool.get(1).unwrap();
         assert_eq!("#2", get_value(constant).unwrap());
         assert_eq!(
             "java/lang/Object",
@@ -170,13 +485,13 @@ mod tests {
 
     #[test]
     fn test_string() {
-        let constant_pool = [
+        let constant_pool = ConstantPool::from(vec![
             Constant::String { string_index: 2 },
             Constant::Utf8 {
-                value: b"Hello, World!",
+                value: b"Hello, World!".into(),
             },
-        ];
-        let constant = &constant_pool[0];
+        ]);
+        let constant = constant_pool.get(1).unwrap();
         assert_eq!("#2", get_value(constant).unwrap());
         assert_eq!(
             "Hello, World!",
@@ -186,7 +501,7 @@ mod tests {
 
     #[test]
     fn test_fieldref() {
-        let constant_pool = [
+        let constant_pool = ConstantPool::from(vec![
             Constant::Fieldref {
                 class_index: 2,
                 name_and_type_index: 3,
@@ -196,13 +511,17 @@ mod tests {
                 name_index: 5,
                 descriptor_index: 6,
             },
-            Constant::Utf8 { value: b"Main" },
-            Constant::Utf8 { value: b"field" },
             Constant::Utf8 {
-                value: b"Ljava/lang/String;",
+                value: b"Main".into(),
             },
-        ];
-        let constant = &constant_pool[0];
+            Constant::Utf8 {
+                value: b"field".into(),
+            },
+            Constant::Utf8 {
+                value: b"Ljava/lang/String;".into(),
+            },
+        ]);
+        let constant = constant_pool.get(1).unwrap();
         assert_eq!("#2.#3", get_value(constant).unwrap());
         assert_eq!(
             "Main.field:Ljava/lang/String;",
@@ -212,7 +531,7 @@ mod tests {
 
     #[test]
     fn test_methodref() {
-        let constant_pool = [
+        let constant_pool = ConstantPool::from(vec![
             Constant::Methodref {
                 class_index: 2,
                 name_and_type_index: 3,
@@ -222,11 +541,17 @@ mod tests {
                 name_index: 5,
                 descriptor_index: 6,
             },
-            Constant::Utf8 { value: b"Main" },
-            Constant::Utf8 { value: b"method" },
-            Constant::Utf8 { value: b"()V" },
-        ];
-        let constant = &constant_pool[0];
+            Constant::Utf8 {
+                value: b"Main".into(),
+            },
+            Constant::Utf8 {
+                value: b"method".into(),
+            },
+            Constant::Utf8 {
+                value: b"()V".into(),
+            },
+        ]);
+        let constant = constant_pool.get(1).unwrap();
         assert_eq!("#2.#3", get_value(constant).unwrap());
         assert_eq!(
             "Main.method:()V",
@@ -236,20 +561,90 @@ mod tests {
 
     #[test]
     fn test_name_and_type() {
-        let constant_pool = [
-            Constant::Utf8 { value: b"toString" },
+        let constant_pool = ConstantPool::from(vec![
             Constant::Utf8 {
-                value: b"()Ljava/lang/String;",
+                value: b"toString".into(),
+            },
+            Constant::Utf8 {
+                value: b"()Ljava/lang/String;".into(),
             },
             Constant::NameAndType {
                 name_index: 1,
                 descriptor_index: 2,
             },
-        ];
-        assert_eq!("#1:#2", get_value(&constant_pool[2]).unwrap());
+        ]);
+        assert_eq!("#1:#2", get_value(constant_pool.get(3).unwrap()).unwrap());
         assert_eq!(
             "toString:()Ljava/lang/String;",
-            get_comment(&constant_pool[2], &constant_pool).unwrap()
+            get_comment(constant_pool.get(3).unwrap(), &constant_pool).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        let constant_pool = ConstantPool::from(vec![
+            Constant::String { string_index: 2 },
+            Constant::Utf8 {
+                value: b"a\tb\n\"c\" \\ \x01".into(),
+            },
+        ]);
+        assert_eq!(
+            "String             #2              // a\\tb\\n\\\"c\\\" \\\\ \\u0001",
+            constant_pool.get(1).unwrap().print(&constant_pool).unwrap()
+        );
+        assert_eq!(
+            "Utf8               a\\tb\\n\\\"c\\\" \\\\ \\u0001",
+            constant_pool.get(2).unwrap().print(&constant_pool).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_display_alignment() {
+        let constants = || {
+            vec![
+                Constant::Class { name_index: 2 },
+                Constant::Utf8 {
+                    value: b"java/lang/Object".into(),
+                },
+                Constant::Class { name_index: 4 },
+                Constant::Utf8 {
+                    value: b"[[[[[[[[[[Ljava/lang/Object;".into(),
+                },
+            ]
+        };
+        let constant_pool = ConstantPool::from(constants());
+        assert_eq!(2, index_width(&constant_pool));
+        assert_eq!(
+            "Class              #2              // java/lang/Object",
+            constant_pool
+                .get(1)
+                .unwrap()
+                .display(&constant_pool)
+                .to_string()
+        );
+
+        // from 100 entries the index column widens and the value column
+        // shrinks, so that comments stay in the same place
+        let constant_pool: ConstantPool = constants()
+            .into_iter()
+            .chain((0..96).map(|value| Constant::Integer { value }))
+            .collect();
+        assert_eq!(4, index_width(&constant_pool));
+        assert_eq!(
+            "Class              #2            // java/lang/Object",
+            constant_pool
+                .get(1)
+                .unwrap()
+                .display(&constant_pool)
+                .to_string()
+        );
+        assert_eq!(
+            constant_pool.get(3).unwrap().print(&constant_pool).unwrap(),
+            constant_pool
+                .get(3)
+                .unwrap()
+                .display(&constant_pool)
+                .to_string()
         );
     }
 }
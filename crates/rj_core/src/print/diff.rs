@@ -0,0 +1,728 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::asm::disassemble;
+use crate::class::{Attribute, ClassFile, Code, Constant, ConstantPool, Field, Method};
+
+use super::error::PrintError;
+use super::instruction::get_class;
+use super::json::{string, Json};
+use super::stable::strip_indices;
+
+// a property that differs between the two versions, like a method's
+// declaration or the class's major version
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    pub name: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+// an instruction or exception table entry in only one version of a method,
+// printed as `javap -c` would without constant pool indices
+#[derive(Debug, Clone, PartialEq)]
+pub enum CodeLine {
+    Removed(String),
+    Added(String),
+}
+
+// a field or method, named as `name:descriptor`
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemberDiff {
+    Added(String),
+    Removed(String),
+    Changed {
+        member: String,
+        changes: Vec<ValueChange>,
+        code: Vec<CodeLine>,
+    },
+}
+
+// how two versions of a class differ, from `ClassFile::diff`. the constant
+// pool order, constant indices and debug tables don't count
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassDiff {
+    // the name of the newer class
+    pub name: String,
+    pub changes: Vec<ValueChange>,
+    pub fields: Vec<MemberDiff>,
+    pub methods: Vec<MemberDiff>,
+    // the constants that aren't just names and descriptors, like
+    // `String hi` or `Methodref java/lang/Object."<init>":()V`, by how many
+    // more or fewer of them there are
+    pub removed_constants: Vec<String>,
+    pub added_constants: Vec<String>,
+}
+
+fn change(name: &'static str, old: String, new: String) -> Option<ValueChange> {
+    (old != new).then_some(ValueChange { name, old, new })
+}
+
+fn attribute_names(attributes: &[Attribute]) -> String {
+    let mut names = attributes
+        .iter()
+        .map(|attribute| match attribute {
+            Attribute::Unknown { name, .. }
+            | Attribute::Raw { name, .. }
+            | Attribute::Custom { name, .. } => String::from_utf8_lossy(name).into_owned(),
+            attribute => attribute
+                .attribute_name()
+                .map_or("", |name| name.as_str())
+                .to_string(),
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names.join(", ")
+}
+
+// a constant without its indices and javap's column padding
+fn constant_value(constant: &Constant, constant_pool: &ConstantPool) -> Result<String, PrintError> {
+    let line = strip_indices(&constant.print(constant_pool)?);
+    Ok(line.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+// the constants that would show up in a listing, counted by value
+fn constant_counts(constant_pool: &ConstantPool) -> Result<HashMap<String, i32>, PrintError> {
+    let mut counts = HashMap::new();
+    for (_, constant) in constant_pool.iter() {
+        if let Constant::Utf8 { .. } | Constant::NameAndType { .. } = constant {
+            continue;
+        }
+        *counts
+            .entry(constant_value(constant, constant_pool)?)
+            .or_default() += 1;
+    }
+    Ok(counts)
+}
+
+fn member_name(
+    constant_pool: &ConstantPool,
+    name_index: u16,
+    descriptor_index: u16,
+) -> Result<String, PrintError> {
+    let name = constant_pool.utf8(name_index)?;
+    let descriptor = constant_pool.utf8(descriptor_index)?;
+    Ok(format!("{name}:{descriptor}"))
+}
+
+// each instruction as `(text, line)`: the text without its pc or constant
+// indices is what's compared, the line with the pc is what's shown
+fn code_lines(
+    code: &Code<Attribute>,
+    this_class: u16,
+    constant_pool: &ConstantPool,
+) -> Result<Vec<(String, String)>, PrintError> {
+    let mut lines = Vec::new();
+    for result in disassemble(&code.code) {
+        let (pc, instruction) = result?;
+        let printed = instruction.print_in(pc, constant_pool, Some(this_class))?;
+        let printed = printed
+            .lines()
+            .map(strip_indices)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let text = match printed.split_once(": ") {
+            Some((_, text)) => text.to_string(),
+            None => printed.clone(),
+        };
+        lines.push((text, printed));
+    }
+    for entry in &code.exception_table {
+        let catch_type = match entry.catch_type {
+            0 => "any".to_string(),
+            index => get_class(constant_pool, index)?,
+        };
+        let line = format!(
+            "exception {} {} {} {catch_type}",
+            entry.start_pc, entry.end_pc, entry.handler_pc
+        );
+        lines.push((line.clone(), line));
+    }
+    Ok(lines)
+}
+
+enum Edit {
+    Keep,
+    Remove(usize),
+    Add(usize),
+}
+
+// past this many steps of the search, the rest of a method is shown as
+// entirely replaced rather than spending more memory on a minimal diff
+const MAX_EDIT_TRACE: usize = 1 << 24;
+
+// the shortest edit script from `old` to `new`, by Myers' algorithm
+fn edit_script<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Edit> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = (n + m) as usize;
+    let offset = max as isize;
+    let mut v = vec![0isize; 2 * max + 2];
+    let mut trace = Vec::new();
+    let mut found = false;
+    'search: for d in 0..=max as isize {
+        if trace.len() * v.len() > MAX_EDIT_TRACE {
+            break;
+        }
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = match k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                true => v[i + 1],
+                false => v[i - 1] + 1,
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                found = true;
+                break 'search;
+            }
+        }
+    }
+    if !found {
+        let removed = (0..old.len()).map(Edit::Remove);
+        return removed.chain((0..new.len()).map(Edit::Add)).collect();
+    }
+
+    // back from the end, through the diagonal each step came from
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let i = (k + offset) as usize;
+        let previous_k = match k == -d || (k != d && v[i - 1] < v[i + 1]) {
+            true => k + 1,
+            false => k - 1,
+        };
+        let previous_x = v[(previous_k + offset) as usize];
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Keep);
+        }
+        if d > 0 {
+            if x == previous_x {
+                y -= 1;
+                edits.push(Edit::Add(y as usize));
+            } else {
+                x -= 1;
+                edits.push(Edit::Remove(x as usize));
+            }
+        }
+    }
+    edits.reverse();
+    edits
+}
+
+fn diff_code(old: &[(String, String)], new: &[(String, String)]) -> Vec<CodeLine> {
+    let old_text = old.iter().map(|(text, _)| text).collect::<Vec<_>>();
+    let new_text = new.iter().map(|(text, _)| text).collect::<Vec<_>>();
+    edit_script(&old_text, &new_text)
+        .into_iter()
+        .filter_map(|edit| match edit {
+            Edit::Keep => None,
+            Edit::Remove(i) => Some(CodeLine::Removed(old[i].1.clone())),
+            Edit::Add(i) => Some(CodeLine::Added(new[i].1.clone())),
+        })
+        .collect()
+}
+
+// pairs members up by name and descriptor: those only in the old class in
+// its order, then those only in the new one
+fn diff_members<'m, M>(
+    old: &'m [M],
+    new: &'m [M],
+    name: impl Fn(&M, bool) -> Result<String, PrintError>,
+    mut compare: impl FnMut(&M, &M) -> Result<(Vec<ValueChange>, Vec<CodeLine>), PrintError>,
+) -> Result<Vec<MemberDiff>, PrintError> {
+    let mut new_members = BTreeMap::new();
+    for member in new {
+        new_members.insert(name(member, true)?, member);
+    }
+    let mut diffs = Vec::new();
+    let mut matched = Vec::new();
+    for member in old {
+        let member_name = name(member, false)?;
+        match new_members.get(&member_name) {
+            None => diffs.push(MemberDiff::Removed(member_name)),
+            Some(new_member) => {
+                let (changes, code) = compare(member, new_member)?;
+                if !changes.is_empty() || !code.is_empty() {
+                    diffs.push(MemberDiff::Changed {
+                        member: member_name.clone(),
+                        changes,
+                        code,
+                    });
+                }
+                matched.push(member_name);
+            }
+        }
+    }
+    for member in new {
+        let member_name = name(member, true)?;
+        if !matched.contains(&member_name) {
+            diffs.push(MemberDiff::Added(member_name));
+        }
+    }
+    Ok(diffs)
+}
+
+impl<'a> ClassFile<'a> {
+    fn interface_names(&self) -> Result<String, PrintError> {
+        let names = self
+            .interfaces
+            .iter()
+            .map(|index| get_class(&self.constant_pool, *index))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(names.join(", "))
+    }
+
+    fn super_class_name(&self) -> Result<String, PrintError> {
+        match self.super_class {
+            0 => Ok(String::new()),
+            index => get_class(&self.constant_pool, index),
+        }
+    }
+
+    fn diff_field(
+        &self,
+        field: &Field,
+        other: &ClassFile,
+        other_field: &Field,
+    ) -> Result<Vec<ValueChange>, PrintError> {
+        let changes = [
+            change(
+                "declaration",
+                self.print_field_declaration(field, true)?,
+                other.print_field_declaration(other_field, true)?,
+            ),
+            change(
+                "attributes",
+                attribute_names(&field.attributes),
+                attribute_names(&other_field.attributes),
+            ),
+        ];
+        Ok(changes.into_iter().flatten().collect())
+    }
+
+    fn diff_method(
+        &self,
+        method: &Method,
+        other: &ClassFile,
+        other_method: &Method,
+    ) -> Result<(Vec<ValueChange>, Vec<CodeLine>), PrintError> {
+        let mut changes = vec![
+            change(
+                "declaration",
+                self.print_method_declaration(method, &[])?,
+                other.print_method_declaration(other_method, &[])?,
+            ),
+            change(
+                "attributes",
+                attribute_names(&method.attributes),
+                attribute_names(&other_method.attributes),
+            ),
+        ];
+        let mut code = Vec::new();
+        if let (Some(old), Some(new)) = (method.code(), other_method.code()) {
+            changes.push(change(
+                "max_stack",
+                old.max_stack.to_string(),
+                new.max_stack.to_string(),
+            ));
+            changes.push(change(
+                "max_locals",
+                old.max_locals.to_string(),
+                new.max_locals.to_string(),
+            ));
+            let old = code_lines(old, self.this_class, &self.constant_pool)?;
+            let new = code_lines(new, other.this_class, &other.constant_pool)?;
+            code = diff_code(&old, &new);
+        }
+        Ok((changes.into_iter().flatten().collect(), code))
+    }
+
+    // how `other`, a later version of this class, differs from it: its
+    // header, its members by name and descriptor, their code instruction by
+    // instruction, and the constants it uses
+    pub fn diff(&self, other: &ClassFile) -> Result<ClassDiff, PrintError> {
+        let (old_version, new_version) = (self.version(), other.version());
+        let changes = [
+            change(
+                "name",
+                get_class(&self.constant_pool, self.this_class)?,
+                get_class(&other.constant_pool, other.this_class)?,
+            ),
+            change(
+                "version",
+                format!("{}.{}", old_version.major, old_version.minor),
+                format!("{}.{}", new_version.major, new_version.minor),
+            ),
+            change(
+                "flags",
+                self.access_flags.print(),
+                other.access_flags.print(),
+            ),
+            change("super", self.super_class_name()?, other.super_class_name()?),
+            change(
+                "interfaces",
+                self.interface_names()?,
+                other.interface_names()?,
+            ),
+            change(
+                "attributes",
+                attribute_names(&self.attributes),
+                attribute_names(&other.attributes),
+            ),
+        ];
+
+        let field_name = |field: &Field, is_new: bool| {
+            let constant_pool = if is_new {
+                &other.constant_pool
+            } else {
+                &self.constant_pool
+            };
+            member_name(constant_pool, field.name_index, field.descriptor_index)
+        };
+        let fields = diff_members(&self.fields, &other.fields, field_name, |old, new| {
+            Ok((self.diff_field(old, other, new)?, Vec::new()))
+        })?;
+        let method_name = |method: &Method, is_new: bool| {
+            let constant_pool = if is_new {
+                &other.constant_pool
+            } else {
+                &self.constant_pool
+            };
+            member_name(constant_pool, method.name_index, method.descriptor_index)
+        };
+        let methods = diff_members(&self.methods, &other.methods, method_name, |old, new| {
+            self.diff_method(old, other, new)
+        })?;
+
+        let mut counts = constant_counts(&self.constant_pool)?;
+        for (value, count) in constant_counts(&other.constant_pool)? {
+            *counts.entry(value).or_default() -= count;
+        }
+        let mut counts = counts.into_iter().collect::<Vec<_>>();
+        counts.sort();
+        let mut removed_constants = Vec::new();
+        let mut added_constants = Vec::new();
+        for (value, count) in counts {
+            for _ in 0..count {
+                removed_constants.push(value.clone());
+            }
+            for _ in count..0 {
+                added_constants.push(value.clone());
+            }
+        }
+
+        Ok(ClassDiff {
+            name: get_class(&other.constant_pool, other.this_class)?,
+            changes: changes.into_iter().flatten().collect(),
+            fields,
+            methods,
+            removed_constants,
+            added_constants,
+        })
+    }
+}
+
+fn print_changes(output: &mut String, changes: &[ValueChange], indent: &str) {
+    for change in changes {
+        output.push_str(&format!(
+            "{indent}{}: {} -> {}\n",
+            change.name, change.old, change.new
+        ));
+    }
+}
+
+fn print_members(output: &mut String, kind: &str, members: &[MemberDiff]) {
+    for member in members {
+        match member {
+            MemberDiff::Added(name) => output.push_str(&format!("+ {kind} {name}\n")),
+            MemberDiff::Removed(name) => output.push_str(&format!("- {kind} {name}\n")),
+            MemberDiff::Changed {
+                member,
+                changes,
+                code,
+            } => {
+                output.push_str(&format!("~ {kind} {member}\n"));
+                print_changes(output, changes, "    ");
+                for line in code {
+                    let (sign, line) = match line {
+                        CodeLine::Removed(line) => ('-', line),
+                        CodeLine::Added(line) => ('+', line),
+                    };
+                    for line in line.lines() {
+                        output.push_str(&format!("    {sign} {line}\n"));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn changes_to_json(changes: &[ValueChange]) -> Json {
+    let changes = changes
+        .iter()
+        .map(|change| {
+            Json::Object(vec![
+                ("name", string(change.name)),
+                ("old", string(&change.old)),
+                ("new", string(&change.new)),
+            ])
+        })
+        .collect();
+    Json::Array(changes)
+}
+
+fn members_to_json(members: &[MemberDiff]) -> Json {
+    let members = members
+        .iter()
+        .map(|member| match member {
+            MemberDiff::Added(name) => {
+                Json::Object(vec![("member", string(name)), ("status", string("added"))])
+            }
+            MemberDiff::Removed(name) => Json::Object(vec![
+                ("member", string(name)),
+                ("status", string("removed")),
+            ]),
+            MemberDiff::Changed {
+                member,
+                changes,
+                code,
+            } => {
+                let code = code
+                    .iter()
+                    .map(|line| {
+                        let (status, line) = match line {
+                            CodeLine::Removed(line) => ("removed", line),
+                            CodeLine::Added(line) => ("added", line),
+                        };
+                        Json::Object(vec![("status", string(status)), ("line", string(line))])
+                    })
+                    .collect();
+                Json::Object(vec![
+                    ("member", string(member)),
+                    ("status", string("changed")),
+                    ("changes", changes_to_json(changes)),
+                    ("code", Json::Array(code)),
+                ])
+            }
+        })
+        .collect();
+    Json::Array(members)
+}
+
+fn strings_to_json(values: &[String]) -> Json {
+    Json::Array(values.iter().map(string).collect())
+}
+
+impl ClassDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+            && self.fields.is_empty()
+            && self.methods.is_empty()
+            && self.removed_constants.is_empty()
+            && self.added_constants.is_empty()
+    }
+
+    // a line per change, marked `+` for what was added, `-` for what was
+    // removed and `~` for what changed; nothing when the classes match
+    pub fn print(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let mut output = format!("~ class {}\n", self.name);
+        print_changes(&mut output, &self.changes, "    ");
+        print_members(&mut output, "field", &self.fields);
+        print_members(&mut output, "method", &self.methods);
+        for constant in &self.removed_constants {
+            output.push_str(&format!("- constant {constant}\n"));
+        }
+        for constant in &self.added_constants {
+            output.push_str(&format!("+ constant {constant}\n"));
+        }
+        output
+    }
+
+    pub(super) fn to_json_value(&self) -> Json {
+        Json::Object(vec![
+            ("name", string(&self.name)),
+            ("changes", changes_to_json(&self.changes)),
+            ("fields", members_to_json(&self.fields)),
+            ("methods", members_to_json(&self.methods)),
+            (
+                "constants",
+                Json::Object(vec![
+                    ("removed", strings_to_json(&self.removed_constants)),
+                    ("added", strings_to_json(&self.added_constants)),
+                ]),
+            ),
+        ])
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut output = String::new();
+        // writing to a String can't fail
+        let _ = self.to_json_value().write(&mut output, 0);
+        output.push('\n');
+        output
+    }
+}
+
+// how two sets of classes, like two builds of a jar, differ: the classes
+// only in one of them by name, and the diffs of those that changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchiveDiff {
+    pub removed: Vec<String>,
+    pub added: Vec<String>,
+    pub changed: Vec<ClassDiff>,
+}
+
+impl ArchiveDiff {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.changed.is_empty()
+    }
+
+    pub fn print(&self) -> String {
+        let mut output = String::new();
+        for name in &self.removed {
+            output.push_str(&format!("- class {name}\n"));
+        }
+        for name in &self.added {
+            output.push_str(&format!("+ class {name}\n"));
+        }
+        for diff in &self.changed {
+            output.push_str(&diff.print());
+        }
+        output
+    }
+
+    pub fn to_json(&self) -> String {
+        let changed = self.changed.iter().map(ClassDiff::to_json_value).collect();
+        let value = Json::Object(vec![
+            ("removed", strings_to_json(&self.removed)),
+            ("added", strings_to_json(&self.added)),
+            ("changed", Json::Array(changed)),
+        ]);
+        let mut output = String::new();
+        let _ = value.write(&mut output, 0);
+        output.push('\n');
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::parse_classfile;
+
+    #[test]
+    fn test_edit_script() {
+        let script = |old: &str, new: &str| {
+            let old = old.chars().collect::<Vec<_>>();
+            let new = new.chars().collect::<Vec<_>>();
+            edit_script(&old, &new)
+                .into_iter()
+                .map(|edit| match edit {
+                    Edit::Keep => "=".to_string(),
+                    Edit::Remove(i) => format!("-{}", old[i]),
+                    Edit::Add(i) => format!("+{}", new[i]),
+                })
+                .collect::<String>()
+        };
+        assert_eq!(script("", ""), "");
+        assert_eq!(script("abc", "abc"), "===");
+        assert_eq!(script("abc", ""), "-a-b-c");
+        assert_eq!(script("", "ab"), "+a+b");
+        assert_eq!(script("abcabba", "cbabac"), "-a-b=+b==-b=+c");
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = include_bytes!("../../../../java/diff/old/Counter.class");
+        let new = include_bytes!("../../../../java/diff/new/Counter.class");
+        let (_, old) = parse_classfile(old).unwrap();
+        let (_, new) = parse_classfile(new).unwrap();
+        assert!(old.diff(&old).unwrap().is_empty());
+        assert_eq!(old.diff(&old).unwrap().print(), "");
+
+        let diff = old.diff(&new).unwrap();
+        assert_eq!(diff.name, "Counter");
+        assert_eq!(diff.changes, []);
+        assert_eq!(diff.fields, [MemberDiff::Added("step:I".into())]);
+        assert_eq!(
+            diff.methods[0],
+            MemberDiff::Changed {
+                member: "<init>:()V".into(),
+                changes: vec![ValueChange {
+                    name: "max_stack",
+                    old: "1".into(),
+                    new: "2".into(),
+                }],
+                code: vec![
+                    CodeLine::Added("   4: aload_0".into()),
+                    CodeLine::Added("   5: iconst_1".into()),
+                    CodeLine::Added("   6: putfield      Field step:I".into()),
+                ],
+            }
+        );
+        assert_eq!(diff.removed_constants, ["String counter"]);
+        assert_eq!(
+            diff.added_constants,
+            ["Fieldref Counter.step:I", "String Counter"]
+        );
+        assert_eq!(
+            diff.print(),
+            "\
+~ class Counter
++ field step:I
+~ method <init>:()V
+    max_stack: 1 -> 2
+    +    4: aload_0
+    +    5: iconst_1
+    +    6: putfield      Field step:I
+~ method increment:()V
+    declaration: public void increment() -> public synchronized void increment()
+    -    5: iconst_1
+    +    5: aload_0
+    +    6: getfield      Field step:I
+- method reset:()V
+~ method name:()Ljava/lang/String;
+    -    0: ldc           String counter
+    +    0: ldc           String Counter
+- constant String counter
++ constant Fieldref Counter.step:I
++ constant String Counter
+"
+        );
+    }
+
+    #[test]
+    fn test_diff_json() {
+        let old = include_bytes!("../../../../java/diff/old/Counter.class");
+        let new = include_bytes!("../../../../java/diff/new/Counter.class");
+        let (_, old) = parse_classfile(old).unwrap();
+        let (_, new) = parse_classfile(new).unwrap();
+        let json = old.diff(&new).unwrap().to_json();
+        assert!(json.starts_with("{\n  \"name\": \"Counter\",\n  \"changes\": [],\n"));
+        assert!(json.contains("\"member\": \"reset:()V\",\n      \"status\": \"removed\""));
+        assert!(json.contains("\"line\": \"   0: ldc           String Counter\""));
+
+        let diff = ArchiveDiff {
+            removed: vec!["Point".into()],
+            added: vec![],
+            changed: vec![],
+        };
+        assert_eq!(diff.print(), "- class Point\n");
+        assert_eq!(
+            diff.to_json(),
+            "{\n  \"removed\": [\n    \"Point\"\n  ],\n  \"added\": [],\n  \"changed\": []\n}\n"
+        );
+    }
+}
@@ -1,11 +1,64 @@
+use std::fmt;
+
+use crate::asm::InstructionParseError;
+use crate::class::{ConstantPoolError, ConstantPoolViolation, ModifiedUtf8Error};
+
 #[derive(Debug, PartialEq)]
 pub enum PrintError {
-    Utf8Error(core::str::Utf8Error),
+    Utf8Error(ModifiedUtf8Error),
     InvalidConstant,
+    // an index with no constant behind it: zero, past the end of the pool or
+    // the unusable slot after a Long or Double
+    IndexOutOfRange(u16),
+    // a constant of a kind that can't appear where it's referenced, like a
+    // Utf8 as an instruction operand
+    UnsupportedConstant(u16),
+    ConstantPoolError(ConstantPoolError),
+    // the first entry that fails `ConstantPool::validate`
+    InvalidConstantPool(ConstantPoolViolation),
+    // bytecode in a Code attribute that doesn't disassemble
+    InvalidInstruction(InstructionParseError),
+    // the sink passed to `ClassFile::write_to` failed
+    WriteError,
 }
 
-impl From<core::str::Utf8Error> for PrintError {
-    fn from(e: core::str::Utf8Error) -> Self {
+impl From<ModifiedUtf8Error> for PrintError {
+    fn from(e: ModifiedUtf8Error) -> Self {
         PrintError::Utf8Error(e)
     }
 }
+
+impl From<ConstantPoolError> for PrintError {
+    fn from(e: ConstantPoolError) -> Self {
+        PrintError::ConstantPoolError(e)
+    }
+}
+
+impl From<InstructionParseError> for PrintError {
+    fn from(e: InstructionParseError) -> Self {
+        PrintError::InvalidInstruction(e)
+    }
+}
+
+impl From<fmt::Error> for PrintError {
+    fn from(_: fmt::Error) -> Self {
+        PrintError::WriteError
+    }
+}
+
+impl fmt::Display for PrintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PrintError::Utf8Error(error) => write!(f, "{error}"),
+            PrintError::InvalidConstant => write!(f, "invalid constant"),
+            PrintError::IndexOutOfRange(index) => write!(f, "no constant at #{index}"),
+            PrintError::UnsupportedConstant(index) => {
+                write!(f, "#{index} can't be used where it's referenced")
+            }
+            PrintError::ConstantPoolError(error) => write!(f, "{error}"),
+            PrintError::InvalidConstantPool(violation) => write!(f, "{violation}"),
+            PrintError::InvalidInstruction(error) => write!(f, "{error}"),
+            PrintError::WriteError => write!(f, "failed to write the output"),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::class::{internal_to_binary_name, ClassFile};
+
+use super::color::comment_start;
+use super::error::PrintError;
+use super::options::PrintOptions;
+
+const STYLE: &str = "body { margin: 2em; }
+pre { font: 13px/1.4 monospace; }
+a { color: inherit; }
+a[href] { text-decoration: underline dotted; }
+:target { background: #fff3b0; }
+.comment { color: #6a737d; }";
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// `#12` at the start of `text`, as the index and its length
+fn constant_reference(text: &str) -> Option<(u16, usize)> {
+    let index = text.strip_prefix('#')?;
+    let digits = index
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(index.len());
+    if digits == 0 {
+        return None;
+    }
+    Some((index[..digits].parse().ok()?, digits + 1))
+}
+
+// where the listing's anchors and links point
+struct Links {
+    // declaration line, as printed without indentation, to its anchor
+    members: HashMap<String, String>,
+    // Fieldref and Methodref constants of the class itself to the anchor of
+    // the member they name
+    references: HashMap<u16, String>,
+}
+
+// turns the text listing into HTML a line at a time: constant pool entries
+// get `cp<n>` anchors, members get anchors on their declarations, and `#n`
+// references link to the constants, or for members of the class itself,
+// their comments link to the declaration
+struct HtmlWriter<'w, W: fmt::Write> {
+    inner: &'w mut W,
+    links: Links,
+    line: String,
+}
+
+impl<'w, W: fmt::Write> HtmlWriter<'w, W> {
+    fn write_line(&mut self, line: &str) -> fmt::Result {
+        let trimmed = line.trim_start();
+        let indentation = &line[..line.len() - trimmed.len()];
+        if let Some(anchor) = self.links.members.get(trimmed) {
+            return writeln!(
+                self.inner,
+                "{indentation}<a id=\"{anchor}\">{}</a>",
+                escape_html(trimmed)
+            );
+        }
+
+        let (code, comment) = match comment_start(line) {
+            Some(start) => line.split_at(start),
+            None => (line, ""),
+        };
+        let mut output = String::new();
+        let mut rest = code;
+        let mut member = None;
+        // a constant pool entry: anchor its own index, and leave a Utf8
+        // value alone, which may contain `#` itself
+        if let Some((index, length)) = constant_reference(trimmed) {
+            if trimmed[length..].starts_with(" = ") {
+                output.push_str(indentation);
+                output.push_str(&format!("<a id=\"cp{index}\">#{index}</a>"));
+                rest = &code[indentation.len() + length..];
+                if rest.starts_with(" = Utf8 ") {
+                    output.push_str(&escape_html(rest));
+                    output.push('\n');
+                    return self.inner.write_str(&output);
+                }
+            }
+        }
+        while let Some(start) = rest.find('#') {
+            output.push_str(&escape_html(&rest[..start]));
+            rest = &rest[start..];
+            match constant_reference(rest) {
+                Some((index, length)) => {
+                    output.push_str(&format!("<a href=\"#cp{index}\">#{index}</a>"));
+                    if member.is_none() {
+                        member = self.links.references.get(&index);
+                    }
+                    rest = &rest[length..];
+                }
+                None => {
+                    output.push('#');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        output.push_str(&escape_html(rest));
+        if !comment.is_empty() {
+            let comment = escape_html(comment);
+            match member {
+                Some(anchor) => output.push_str(&format!(
+                    "<a class=\"comment\" href=\"#{anchor}\">{comment}</a>"
+                )),
+                None => output.push_str(&format!("<span class=\"comment\">{comment}</span>")),
+            }
+        }
+        output.push('\n');
+        self.inner.write_str(&output)
+    }
+}
+
+impl<'w, W: fmt::Write> fmt::Write for HtmlWriter<'w, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.line.push_str(s);
+        while let Some(end) = self.line.find('\n') {
+            let line = self.line[..end].to_string();
+            self.write_line(&line)?;
+            self.line.drain(..=end);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ClassFile<'a> {
+    fn html_links(&self, options: &PrintOptions) -> Result<Links, PrintError> {
+        let mut members = HashMap::new();
+        let mut references = HashMap::new();
+        let this_class = self.constant_pool.class_name(self.this_class)?;
+
+        for (i, field) in self.fields.iter().enumerate() {
+            let anchor = format!("field{i}");
+            let declaration = self.print_field_declaration(field, options.show_constants)?;
+            members
+                .entry(format!("{declaration};"))
+                .or_insert(anchor.clone());
+            let name = self.constant_pool.utf8(field.name_index)?;
+            let descriptor = self.constant_pool.utf8(field.descriptor_index)?;
+            for (index, field_ref) in self.constant_pool.field_refs() {
+                if field_ref.class_name == this_class
+                    && field_ref.name == name
+                    && field_ref.descriptor == descriptor
+                {
+                    references.insert(index, anchor.clone());
+                }
+            }
+        }
+
+        let default_methods = self
+            .interface_methods()
+            .map(|summary| summary.default_methods)
+            .unwrap_or_default();
+        for (i, method) in self.methods.iter().enumerate() {
+            let anchor = format!("method{i}");
+            let declaration = self.print_method_declaration(method, &default_methods)?;
+            members
+                .entry(format!("{declaration};"))
+                .or_insert(anchor.clone());
+            let name = self.constant_pool.utf8(method.name_index)?;
+            let descriptor = self.constant_pool.utf8(method.descriptor_index)?;
+            for (index, method_ref) in self.constant_pool.method_refs() {
+                if method_ref.class_name == this_class
+                    && method_ref.name == name
+                    && method_ref.descriptor == descriptor
+                {
+                    references.insert(index, anchor.clone());
+                }
+            }
+        }
+        Ok(Links {
+            members,
+            references,
+        })
+    }
+
+    // a standalone HTML page of the listing `print_with` produces, with
+    // links from references to the constants and members they name
+    pub fn print_html(&self, options: &PrintOptions) -> Result<String, PrintError> {
+        let mut output = String::new();
+        self.write_html(&mut output, options)?;
+        Ok(output)
+    }
+
+    pub fn write_html<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        options: &PrintOptions,
+    ) -> Result<(), PrintError> {
+        let title = internal_to_binary_name(&self.constant_pool.class_name(self.this_class)?);
+        write!(
+            w,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n<pre>\n",
+            escape_html(&title)
+        )?;
+        let mut html = HtmlWriter {
+            inner: w,
+            links: self.html_links(options)?,
+            line: String::new(),
+        };
+        // the markup takes the place of colors
+        self.write_listing(&mut html, options)?;
+        if !html.line.is_empty() {
+            let line = std::mem::take(&mut html.line);
+            html.write_line(&line)?;
+        }
+        w.write_str("</pre>\n</body>\n</html>\n")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::parse_classfile;
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("<init> & \"x\""),
+            "&lt;init&gt; &amp; &quot;x&quot;"
+        );
+    }
+
+    #[test]
+    fn test_constant_reference() {
+        assert_eq!(constant_reference("#12 = Utf8"), Some((12, 3)));
+        assert_eq!(constant_reference("#7"), Some((7, 2)));
+        assert_eq!(constant_reference("#x"), None);
+        assert_eq!(constant_reference("12"), None);
+    }
+
+    #[test]
+    fn test_print_html() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.print_html(&PrintOptions::VERBOSE).unwrap();
+        assert!(output.starts_with("<!DOCTYPE html>\n"));
+        assert!(output.contains("<title>HelloWorld</title>"));
+        assert!(output.ends_with("</pre>\n</body>\n</html>\n"));
+        assert!(output.contains(
+            "\n   <a id=\"cp7\">#7</a> = String             <a href=\"#cp8\">#8</a>             <span class=\"comment\">// Hello, World!</span>\n"
+        ));
+        assert!(output.contains("\n   <a id=\"cp5\">#5</a> = Utf8               &lt;init&gt;\n"));
+        assert!(output.contains("\n  <a id=\"field0\">private java.lang.String message;</a>\n"));
+        assert!(output.contains("\n  <a id=\"method1\">private void sayHello();</a>\n"));
+        // references to the class's own members link to their declarations
+        assert!(output.contains(
+            "getfield      <a href=\"#cp9\">#9</a>                  <a class=\"comment\" href=\"#field0\">// Field message:Ljava/lang/String;</a>\n"
+        ));
+        assert!(output.contains(
+            "invokespecial <a href=\"#cp27\">#27</a>                 <a class=\"comment\" href=\"#method0\">// Method &quot;&lt;init&gt;&quot;:()V</a>\n"
+        ));
+    }
+}
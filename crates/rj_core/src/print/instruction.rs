@@ -0,0 +1,401 @@
+use crate::asm::Instruction;
+use crate::class::{Constant, ConstantPool, NameAndType, ReferenceKind};
+
+use super::constant::{escape, format_double, format_float};
+use super::error::PrintError;
+
+// javap starts `//` comments at this column, counted from the start of the
+// indented text
+const COMMENT_COLUMN: usize = 40;
+
+pub(super) fn push_comment(line: &mut String, comment: &str) {
+    let padding = COMMENT_COLUMN.saturating_sub(line.len()).max(1);
+    line.push_str(&format!("{:padding$}// {comment}", ""));
+}
+
+pub(super) fn get_constant<'a, 'b>(
+    constant_pool: &'b ConstantPool<'a>,
+    index: u16,
+) -> Result<&'b Constant<'a>, PrintError> {
+    constant_pool
+        .get(index)
+        .ok_or(PrintError::IndexOutOfRange(index))
+}
+
+// quotes names that are not plain identifiers, e.g. `"<init>"` or `"[I"`
+pub(super) fn check_name(name: &str) -> String {
+    let mut previous = '/';
+    for c in name.chars() {
+        let start = previous == '/' && !(c.is_alphabetic() || c == '_' || c == '$');
+        let part = c != '/' && !(c.is_alphanumeric() || c == '_' || c == '$');
+        if start || part {
+            return format!("\"{name}\"");
+        }
+        previous = c;
+    }
+    if name.is_empty() {
+        return "\"\"".to_string();
+    }
+    name.to_string()
+}
+
+pub(super) fn get_class(constant_pool: &ConstantPool, index: u16) -> Result<String, PrintError> {
+    Ok(check_name(&constant_pool.class_name(index)?))
+}
+
+fn get_name_and_type(constant_pool: &ConstantPool, index: u16) -> Result<String, PrintError> {
+    let NameAndType { name, descriptor } = constant_pool.name_and_type(index)?;
+    Ok(format!("{}:{descriptor}", check_name(&name)))
+}
+
+pub(super) fn get_reference_kind(reference_kind: ReferenceKind) -> &'static str {
+    match reference_kind {
+        ReferenceKind::GetField => "REF_getField",
+        ReferenceKind::GetStatic => "REF_getStatic",
+        ReferenceKind::PutField => "REF_putField",
+        ReferenceKind::PutStatic => "REF_putStatic",
+        ReferenceKind::InvokeVirtual => "REF_invokeVirtual",
+        ReferenceKind::InvokeStatic => "REF_invokeStatic",
+        ReferenceKind::InvokeSpecial => "REF_invokeSpecial",
+        ReferenceKind::NewInvokeSpecial => "REF_newInvokeSpecial",
+        ReferenceKind::InvokeInterface => "REF_invokeInterface",
+    }
+}
+
+// the text after `// ` for an instruction's constant pool operand
+pub(super) fn get_comment(constant_pool: &ConstantPool, index: u16) -> Result<String, PrintError> {
+    let comment = match get_constant(constant_pool, index)? {
+        Constant::Integer { value } => format!("int {value}"),
+        Constant::Float { value } => format!("float {}f", format_float(*value)),
+        Constant::Long { value } => format!("long {value}l"),
+        Constant::Double { value } => format!("double {}d", format_double(*value)),
+        Constant::Class { .. } => format!("class {}", get_class(constant_pool, index)?),
+        Constant::String { string_index } => {
+            format!("String {}", escape(&constant_pool.utf8(*string_index)?))
+        }
+        Constant::Fieldref {
+            class_index,
+            name_and_type_index,
+        } => format!(
+            "Field {}.{}",
+            get_class(constant_pool, *class_index)?,
+            get_name_and_type(constant_pool, *name_and_type_index)?
+        ),
+        Constant::Methodref {
+            class_index,
+            name_and_type_index,
+        } => format!(
+            "Method {}.{}",
+            get_class(constant_pool, *class_index)?,
+            get_name_and_type(constant_pool, *name_and_type_index)?
+        ),
+        Constant::InterfaceMethodref {
+            class_index,
+            name_and_type_index,
+        } => format!(
+            "InterfaceMethod {}.{}",
+            get_class(constant_pool, *class_index)?,
+            get_name_and_type(constant_pool, *name_and_type_index)?
+        ),
+        Constant::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => {
+            let reference = get_comment(constant_pool, *reference_index)?;
+            format!(
+                "MethodHandle {}:{reference}",
+                get_reference_kind(*reference_kind)
+            )
+        }
+        Constant::MethodType { descriptor_index } => {
+            format!("MethodType {}", constant_pool.utf8(*descriptor_index)?)
+        }
+        Constant::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => format!(
+            "Dynamic #{bootstrap_method_attr_index}:{}",
+            get_name_and_type(constant_pool, *name_and_type_index)?
+        ),
+        Constant::InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => format!(
+            "InvokeDynamic #{bootstrap_method_attr_index}:{}",
+            get_name_and_type(constant_pool, *name_and_type_index)?
+        ),
+        Constant::Utf8 { .. }
+        | Constant::NameAndType { .. }
+        | Constant::Module { .. }
+        | Constant::Package { .. } => return Err(PrintError::UnsupportedConstant(index)),
+    };
+    Ok(comment)
+}
+
+// like get_comment, but javap drops the class from references to members of
+// the class being printed, e.g. `Field message:Ljava/lang/String;`
+fn get_member_comment(
+    constant_pool: &ConstantPool,
+    index: u16,
+    this_class: Option<u16>,
+) -> Result<String, PrintError> {
+    let (kind, class_index, name_and_type_index) = match get_constant(constant_pool, index)? {
+        Constant::Fieldref {
+            class_index,
+            name_and_type_index,
+        } => ("Field", class_index, name_and_type_index),
+        Constant::Methodref {
+            class_index,
+            name_and_type_index,
+        } => ("Method", class_index, name_and_type_index),
+        Constant::InterfaceMethodref {
+            class_index,
+            name_and_type_index,
+        } => ("InterfaceMethod", class_index, name_and_type_index),
+        _ => return get_comment(constant_pool, index),
+    };
+    if this_class != Some(*class_index) {
+        return get_comment(constant_pool, index);
+    }
+    Ok(format!(
+        "{kind} {}",
+        get_name_and_type(constant_pool, *name_and_type_index)?
+    ))
+}
+
+fn get_array_type(atype: u8) -> &'static str {
+    match atype {
+        4 => "boolean",
+        5 => "char",
+        6 => "float",
+        7 => "double",
+        8 => "byte",
+        9 => "short",
+        10 => "int",
+        11 => "long",
+        _ => "BOGUS_TYPE",
+    }
+}
+
+fn print_switch(header: String, pairs: &[(String, i64)]) -> String {
+    let mut output = header;
+    for (key, target) in pairs {
+        output.push_str(&format!("\n{key:>18}: {target}"));
+    }
+    output.push_str("\n      }");
+    output
+}
+
+impl Instruction {
+    // a single javap `-c` line without the method indentation, e.g.
+    // `   4: invokevirtual #21                 // Method ...`; switches span
+    // several lines
+    pub fn print(&self, pc: u32, constant_pool: &ConstantPool) -> Result<String, PrintError> {
+        self.print_in(pc, constant_pool, None)
+    }
+
+    // `print` as it appears inside a class, where references to the class's
+    // own members are shortened
+    pub(super) fn print_in(
+        &self,
+        pc: u32,
+        constant_pool: &ConstantPool,
+        this_class: Option<u16>,
+    ) -> Result<String, PrintError> {
+        let line = format!("{pc:>4}: {:<13} ", self.mnemonic());
+        let target = |offset: i64| pc as i64 + offset;
+        let (operands, index) = match self {
+            Instruction::Aload(index)
+            | Instruction::Astore(index)
+            | Instruction::Dload(index)
+            | Instruction::Dstore(index)
+            | Instruction::Fload(index)
+            | Instruction::Fstore(index)
+            | Instruction::Iload(index)
+            | Instruction::Istore(index)
+            | Instruction::Lload(index)
+            | Instruction::Lstore(index)
+            | Instruction::Ret(index) => (index.to_string(), None),
+            Instruction::WideAload(index)
+            | Instruction::WideAstore(index)
+            | Instruction::WideDload(index)
+            | Instruction::WideDstore(index)
+            | Instruction::WideFload(index)
+            | Instruction::WideFstore(index)
+            | Instruction::WideIload(index)
+            | Instruction::WideIstore(index)
+            | Instruction::WideLload(index)
+            | Instruction::WideLstore(index)
+            | Instruction::WideRet(index) => (index.to_string(), None),
+            Instruction::Bipush(value) => (value.to_string(), None),
+            Instruction::Sipush(value) => (value.to_string(), None),
+            Instruction::Iinc(index, value) => (format!("{index}, {value}"), None),
+            Instruction::WideIinc(index, value) => (format!("{index}, {value}"), None),
+            Instruction::Newarray(atype) => (format!(" {}", get_array_type(*atype)), None),
+            Instruction::Ldc(index) => (format!("#{index}"), Some(*index as u16)),
+            Instruction::Anewarray(index)
+            | Instruction::Checkcast(index)
+            | Instruction::Getfield(index)
+            | Instruction::Getstatic(index)
+            | Instruction::Instanceof(index)
+            | Instruction::Invokespecial(index)
+            | Instruction::Invokestatic(index)
+            | Instruction::Invokevirtual(index)
+            | Instruction::LdcW(index)
+            | Instruction::Ldc2W(index)
+            | Instruction::New(index)
+            | Instruction::Putfield(index)
+            | Instruction::Putstatic(index) => (format!("#{index}"), Some(*index)),
+            Instruction::Invokedynamic(index, ..) => (format!("#{index},  0"), Some(*index)),
+            Instruction::Invokeinterface(index, count, _) => {
+                (format!("#{index},  {count}"), Some(*index))
+            }
+            Instruction::Multianewarray(index, dimensions) => {
+                (format!("#{index},  {dimensions}"), Some(*index))
+            }
+            Instruction::Tableswitch(default, low, high, offsets) => {
+                let mut pairs = offsets
+                    .iter()
+                    .enumerate()
+                    .map(|(i, offset)| {
+                        let key = *low as i64 + i as i64;
+                        (key.to_string(), target(*offset as i64))
+                    })
+                    .collect::<Vec<_>>();
+                pairs.push(("default".to_string(), target(*default as i64)));
+                let header = format!("{line}{{ // {low} to {high}");
+                return Ok(print_switch(header, &pairs));
+            }
+            Instruction::Lookupswitch(default, pairs) => {
+                let header = format!("{line}{{ // {}", pairs.len());
+                let mut pairs = pairs
+                    .iter()
+                    .map(|(key, offset)| (key.to_string(), target(*offset as i64)))
+                    .collect::<Vec<_>>();
+                pairs.push(("default".to_string(), target(*default as i64)));
+                return Ok(print_switch(header, &pairs));
+            }
+            instruction => match instruction.branch_target(pc) {
+                Some(target) => (target.to_string(), None),
+                None => return Ok(format!("{pc:>4}: {}", self.mnemonic())),
+            },
+        };
+        let mut output = format!("{line}{operands}");
+        if let Some(index) = index {
+            let comment = get_member_comment(constant_pool, index, this_class)?;
+            push_comment(&mut output, &comment);
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::disassemble;
+    use crate::class::{parse_classfile, Attribute, ClassFile};
+
+    fn print_code(classfile: &ClassFile, method: usize) -> Vec<String> {
+        let code = classfile.methods[method]
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Code(code) => Some(&*code.code),
+                _ => None,
+            })
+            .unwrap();
+        disassemble(code)
+            .map(|result| {
+                let (pc, instruction) = result.unwrap();
+                instruction.print(pc, &classfile.constant_pool).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_print_hello_world() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let expected = r#"
+   0: aload_0
+   1: invokespecial #1                  // Method java/lang/Object."<init>":()V
+   4: aload_0
+   5: ldc           #7                  // String Hello, World!
+   7: putfield      #9                  // Field HelloWorld.message:Ljava/lang/String;
+  10: return"#;
+        assert_eq!(print_code(&classfile, 0).join("\n"), expected[1..]);
+    }
+
+    #[test]
+    fn test_print_operands() {
+        let data = include_bytes!("../../../../java/Insns.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let lines = print_code(&classfile, 1);
+        let expected = [
+            "   1: newarray       int",
+            "   5: anewarray     #7                  // class java/lang/String",
+            "  11: multianewarray #9,  2             // class \"[[I\"",
+            "  15: astore        4",
+            "  17: invokedynamic #11,  0             // InvokeDynamic #0:run:()Ljava/lang/Runnable;",
+            "  26: invokeinterface #15,  1           // InterfaceMethod java/lang/Runnable.run:()V",
+            "  32: ldc           #19                 // String x",
+            "  46: instanceof    #27                 // class \"[Ljava/lang/String;\"",
+            "  49: ifeq          58",
+            "  62: sipush        1000",
+            "  67: ldc           #29                 // float 2.5f",
+            "  71: ldc           #30                 // int 123456",
+            "  80: newarray       boolean",
+            "  90: getstatic     #31                 // Field Insns.counter:I",
+            " 122: goto          133",
+        ];
+        for line in expected {
+            assert!(lines.iter().any(|l| l == line), "missing {line:?}");
+        }
+
+        let lines = print_code(&classfile, 2);
+        assert!(lines.contains(&"  46: if_icmpge     63".to_string()));
+        assert!(lines.contains(&"  57: iinc          13, 1".to_string()));
+    }
+
+    #[test]
+    fn test_print_switches() {
+        let data = include_bytes!("../../../../java/Switches.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+        let expected = r#"
+   1: tableswitch   { // 1 to 4
+                 1: 32
+                 2: 35
+                 3: 38
+                 4: 41
+           default: 44
+      }"#;
+        assert_eq!(print_code(&classfile, 1)[1], expected[1..]);
+        let expected = r#"
+   1: lookupswitch  { // 3
+              -100: 36
+                 7: 38
+            100000: 40
+           default: 42
+      }"#;
+        assert_eq!(print_code(&classfile, 2)[1], expected[1..]);
+    }
+
+    #[test]
+    fn test_print_invalid() {
+        assert_eq!(
+            Instruction::Getstatic(1).print(0, &ConstantPool::new()),
+            Err(PrintError::IndexOutOfRange(1))
+        );
+        let constant_pool = ConstantPool::from(vec![Constant::Utf8 { value: b"x".into() }]);
+        assert_eq!(
+            Instruction::Ldc(1).print(0, &constant_pool),
+            Err(PrintError::UnsupportedConstant(1))
+        );
+        assert_eq!(
+            Instruction::WideIinc(300, -2)
+                .print(7, &ConstantPool::new())
+                .unwrap(),
+            "   7: iinc_w        300, -2"
+        );
+    }
+}
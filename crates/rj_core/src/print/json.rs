@@ -0,0 +1,811 @@
+use std::fmt;
+
+use crate::asm::disassemble;
+use crate::class::{
+    Annotation, Attribute, ClassFile, Constant, ConstantPool, ElementValue, Field, Method,
+    StackMapFrame, TargetInfo, TypeAnnotation, VerificationTypeInfo,
+};
+
+use super::error::PrintError;
+
+// just enough of a JSON document model to export a class without pulling in
+// a serialization crate. keys keep their insertion order, so the output is
+// stable across runs
+pub(super) enum Json {
+    Null,
+    Bool(bool),
+    // already formatted
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+pub(super) fn number(value: impl fmt::Display) -> Json {
+    Json::Number(value.to_string())
+}
+
+// NaN and the infinities have no JSON number form
+fn float(value: f64) -> Json {
+    if value.is_nan() {
+        Json::String("NaN".to_string())
+    } else if value.is_infinite() {
+        let sign = if value < 0.0 { "-" } else { "" };
+        Json::String(format!("{sign}Infinity"))
+    } else {
+        number(value)
+    }
+}
+
+pub(super) fn string(value: impl Into<String>) -> Json {
+    Json::String(value.into())
+}
+
+// a resolved name alongside the raw index, or null when the index doesn't
+// point at a Utf8 constant
+fn utf8(constant_pool: &ConstantPool, index: u16) -> Json {
+    match constant_pool.utf8(index) {
+        Ok(value) => string(value),
+        Err(_) => Json::Null,
+    }
+}
+
+fn class_name(constant_pool: &ConstantPool, index: u16) -> Json {
+    match constant_pool.class_name(index) {
+        Ok(value) => string(value),
+        Err(_) => Json::Null,
+    }
+}
+
+fn write_string<W: fmt::Write>(w: &mut W, value: &str) -> fmt::Result {
+    w.write_char('"')?;
+    for c in value.chars() {
+        match c {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(w, "\\u{:04x}", c as u32)?,
+            c => w.write_char(c)?,
+        }
+    }
+    w.write_char('"')
+}
+
+impl Json {
+    // two spaces per level; empty arrays and objects stay on one line
+    pub(super) fn write<W: fmt::Write>(&self, w: &mut W, depth: usize) -> fmt::Result {
+        let indent = |w: &mut W, depth: usize| write!(w, "{:width$}", "", width = depth * 2);
+        match self {
+            Json::Null => w.write_str("null"),
+            Json::Bool(value) => write!(w, "{value}"),
+            Json::Number(value) => w.write_str(value),
+            Json::String(value) => write_string(w, value),
+            Json::Array(values) if values.is_empty() => w.write_str("[]"),
+            Json::Array(values) => {
+                w.write_str("[\n")?;
+                for (i, value) in values.iter().enumerate() {
+                    indent(w, depth + 1)?;
+                    value.write(w, depth + 1)?;
+                    w.write_str(if i + 1 < values.len() { ",\n" } else { "\n" })?;
+                }
+                indent(w, depth)?;
+                w.write_char(']')
+            }
+            Json::Object(entries) if entries.is_empty() => w.write_str("{}"),
+            Json::Object(entries) => {
+                w.write_str("{\n")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    indent(w, depth + 1)?;
+                    write_string(w, key)?;
+                    w.write_str(": ")?;
+                    value.write(w, depth + 1)?;
+                    w.write_str(if i + 1 < entries.len() { ",\n" } else { "\n" })?;
+                }
+                indent(w, depth)?;
+                w.write_char('}')
+            }
+        }
+    }
+}
+
+fn constant_to_json(index: u16, constant: &Constant) -> Json {
+    let mut entries = vec![
+        ("index", number(index)),
+        ("tag", string(format!("{:?}", constant.tag()))),
+    ];
+    match constant {
+        Constant::Utf8 { value } => {
+            let value = match constant.as_str() {
+                Some(Ok(value)) => value.into_owned(),
+                _ => String::from_utf8_lossy(value).into_owned(),
+            };
+            entries.push(("value", string(value)));
+        }
+        Constant::Integer { value } => entries.push(("value", number(value))),
+        Constant::Float { value } => entries.push(("value", float(*value as f64))),
+        Constant::Long { value } => entries.push(("value", number(value))),
+        Constant::Double { value } => entries.push(("value", float(*value))),
+        Constant::Class { name_index }
+        | Constant::Module { name_index }
+        | Constant::Package { name_index } => entries.push(("name_index", number(name_index))),
+        Constant::String { string_index } => entries.push(("string_index", number(string_index))),
+        Constant::Fieldref {
+            class_index,
+            name_and_type_index,
+        }
+        | Constant::Methodref {
+            class_index,
+            name_and_type_index,
+        }
+        | Constant::InterfaceMethodref {
+            class_index,
+            name_and_type_index,
+        } => {
+            entries.push(("class_index", number(class_index)));
+            entries.push(("name_and_type_index", number(name_and_type_index)));
+        }
+        Constant::NameAndType {
+            name_index,
+            descriptor_index,
+        } => {
+            entries.push(("name_index", number(name_index)));
+            entries.push(("descriptor_index", number(descriptor_index)));
+        }
+        Constant::MethodHandle {
+            reference_kind,
+            reference_index,
+        } => {
+            entries.push(("reference_kind", number(*reference_kind as u8)));
+            entries.push(("reference_index", number(reference_index)));
+        }
+        Constant::MethodType { descriptor_index } => {
+            entries.push(("descriptor_index", number(descriptor_index)))
+        }
+        Constant::Dynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        }
+        | Constant::InvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => {
+            entries.push((
+                "bootstrap_method_attr_index",
+                number(bootstrap_method_attr_index),
+            ));
+            entries.push(("name_and_type_index", number(name_and_type_index)));
+        }
+    }
+    Json::Object(entries)
+}
+
+fn element_value_to_json(value: &ElementValue) -> Json {
+    match value {
+        ElementValue::Const {
+            tag,
+            const_value_index,
+        } => Json::Object(vec![
+            ("tag", string((*tag as char).to_string())),
+            ("const_value_index", number(const_value_index)),
+        ]),
+        ElementValue::Enum {
+            type_name_index,
+            const_name_index,
+        } => Json::Object(vec![
+            ("tag", string("e")),
+            ("type_name_index", number(type_name_index)),
+            ("const_name_index", number(const_name_index)),
+        ]),
+        ElementValue::Class { class_info_index } => Json::Object(vec![
+            ("tag", string("c")),
+            ("class_info_index", number(class_info_index)),
+        ]),
+        ElementValue::Annotation(annotation) => Json::Object(vec![
+            ("tag", string("@")),
+            ("annotation", annotation_to_json(annotation)),
+        ]),
+        ElementValue::Array(values) => Json::Object(vec![
+            ("tag", string("[")),
+            (
+                "values",
+                Json::Array(values.iter().map(element_value_to_json).collect()),
+            ),
+        ]),
+    }
+}
+
+fn annotation_to_json(annotation: &Annotation) -> Json {
+    let pairs = annotation
+        .element_value_pairs
+        .iter()
+        .map(|pair| {
+            Json::Object(vec![
+                ("element_name_index", number(pair.element_name_index)),
+                ("value", element_value_to_json(&pair.value)),
+            ])
+        })
+        .collect();
+    Json::Object(vec![
+        ("type_index", number(annotation.type_index)),
+        ("element_value_pairs", Json::Array(pairs)),
+    ])
+}
+
+fn annotations_to_json(annotations: &[Annotation]) -> Json {
+    Json::Array(annotations.iter().map(annotation_to_json).collect())
+}
+
+fn target_info_to_json(target_info: &TargetInfo) -> Json {
+    let entries = match target_info {
+        TargetInfo::TypeParameter {
+            type_parameter_index,
+        } => vec![("type_parameter_index", number(type_parameter_index))],
+        TargetInfo::Supertype { supertype_index } => {
+            vec![("supertype_index", number(supertype_index))]
+        }
+        TargetInfo::TypeParameterBound {
+            type_parameter_index,
+            bound_index,
+        } => vec![
+            ("type_parameter_index", number(type_parameter_index)),
+            ("bound_index", number(bound_index)),
+        ],
+        TargetInfo::Empty => vec![],
+        TargetInfo::FormalParameter {
+            formal_parameter_index,
+        } => vec![("formal_parameter_index", number(formal_parameter_index))],
+        TargetInfo::Throws { throws_type_index } => {
+            vec![("throws_type_index", number(throws_type_index))]
+        }
+        TargetInfo::Localvar(table) => {
+            let table = table
+                .iter()
+                .map(|entry| {
+                    Json::Object(vec![
+                        ("start_pc", number(entry.start_pc)),
+                        ("length", number(entry.length)),
+                        ("index", number(entry.index)),
+                    ])
+                })
+                .collect();
+            vec![("table", Json::Array(table))]
+        }
+        TargetInfo::Catch {
+            exception_table_index,
+        } => vec![("exception_table_index", number(exception_table_index))],
+        TargetInfo::Offset { offset } => vec![("offset", number(offset))],
+        TargetInfo::TypeArgument {
+            offset,
+            type_argument_index,
+        } => vec![
+            ("offset", number(offset)),
+            ("type_argument_index", number(type_argument_index)),
+        ],
+    };
+    Json::Object(entries)
+}
+
+fn type_annotations_to_json(annotations: &[TypeAnnotation]) -> Json {
+    let annotations = annotations
+        .iter()
+        .map(|annotation| {
+            let target_path = annotation
+                .target_path
+                .iter()
+                .map(|entry| {
+                    Json::Object(vec![
+                        ("type_path_kind", number(entry.type_path_kind)),
+                        ("type_argument_index", number(entry.type_argument_index)),
+                    ])
+                })
+                .collect();
+            Json::Object(vec![
+                ("target_type", number(annotation.target_type)),
+                ("target_info", target_info_to_json(&annotation.target_info)),
+                ("target_path", Json::Array(target_path)),
+                ("annotation", annotation_to_json(&annotation.annotation)),
+            ])
+        })
+        .collect();
+    Json::Array(annotations)
+}
+
+fn verification_types_to_json(verification_types: &[VerificationTypeInfo]) -> Json {
+    let verification_types = verification_types
+        .iter()
+        .map(|verification_type| {
+            let (tag, entries) = match verification_type {
+                VerificationTypeInfo::Top => ("Top", vec![]),
+                VerificationTypeInfo::Integer => ("Integer", vec![]),
+                VerificationTypeInfo::Float => ("Float", vec![]),
+                VerificationTypeInfo::Double => ("Double", vec![]),
+                VerificationTypeInfo::Long => ("Long", vec![]),
+                VerificationTypeInfo::Null => ("Null", vec![]),
+                VerificationTypeInfo::UninitializedThis => ("UninitializedThis", vec![]),
+                VerificationTypeInfo::Object { cpool_index } => {
+                    ("Object", vec![("cpool_index", number(cpool_index))])
+                }
+                VerificationTypeInfo::Uninitialized { offset } => {
+                    ("Uninitialized", vec![("offset", number(offset))])
+                }
+            };
+            let mut object = vec![("tag", string(tag))];
+            object.extend(entries);
+            Json::Object(object)
+        })
+        .collect();
+    Json::Array(verification_types)
+}
+
+fn stack_map_frame_to_json(frame: &StackMapFrame) -> Json {
+    let (frame_type, locals, stack) = match frame {
+        StackMapFrame::Same { .. } => ("same", None, None),
+        StackMapFrame::SameLocals1StackItem { stack, .. } => (
+            "same_locals_1_stack_item",
+            None,
+            Some(std::slice::from_ref(stack)),
+        ),
+        StackMapFrame::SameLocals1StackItemExtended { stack, .. } => (
+            "same_locals_1_stack_item_extended",
+            None,
+            Some(std::slice::from_ref(stack)),
+        ),
+        StackMapFrame::Chop { .. } => ("chop", None, None),
+        StackMapFrame::SameExtended { .. } => ("same_extended", None, None),
+        StackMapFrame::Append { locals, .. } => ("append", Some(&locals[..]), None),
+        StackMapFrame::Full { locals, stack, .. } => ("full", Some(&locals[..]), Some(&stack[..])),
+    };
+    let mut entries = vec![
+        ("frame_type", string(frame_type)),
+        ("offset_delta", number(frame.offset_delta())),
+    ];
+    if let StackMapFrame::Chop { k, .. } = frame {
+        entries.push(("k", number(k)));
+    }
+    if let Some(locals) = locals {
+        entries.push(("locals", verification_types_to_json(locals)));
+    }
+    if let Some(stack) = stack {
+        entries.push(("stack", verification_types_to_json(stack)));
+    }
+    Json::Object(entries)
+}
+
+fn hex(data: &[u8]) -> Json {
+    let mut output = String::with_capacity(data.len() * 2);
+    for byte in data {
+        output.push_str(&format!("{byte:02x}"));
+    }
+    string(output)
+}
+
+fn attribute_to_json(
+    attribute: &Attribute,
+    constant_pool: &ConstantPool,
+) -> Result<Json, PrintError> {
+    let name = match attribute {
+        Attribute::Unknown { name, .. }
+        | Attribute::Raw { name, .. }
+        | Attribute::Custom { name, .. } => String::from_utf8_lossy(name).into_owned(),
+        attribute => attribute
+            .attribute_name()
+            .map(|name| name.as_str())
+            .unwrap_or_default()
+            .to_string(),
+    };
+    let mut entries = vec![("name", string(name))];
+    match attribute {
+        // the bytes of attributes that aren't decoded, as lowercase hex
+        Attribute::Unknown { data, .. } | Attribute::Raw { data, .. } => {
+            entries.push(("data", hex(data)))
+        }
+        Attribute::Custom { .. } => {}
+        Attribute::AnnotationDefault(annotation_default) => entries.push((
+            "default_value",
+            element_value_to_json(&annotation_default.default_value),
+        )),
+        Attribute::BootstrapMethods(bootstrap_methods) => {
+            let methods = bootstrap_methods
+                .bootstrap_methods
+                .iter()
+                .map(|method| {
+                    Json::Object(vec![
+                        ("bootstrap_method_ref", number(method.bootstrap_method_ref)),
+                        (
+                            "bootstrap_arguments",
+                            Json::Array(method.bootstrap_arguments.iter().map(number).collect()),
+                        ),
+                    ])
+                })
+                .collect();
+            entries.push(("bootstrap_methods", Json::Array(methods)));
+        }
+        Attribute::Code(code) => {
+            entries.push(("max_stack", number(code.max_stack)));
+            entries.push(("max_locals", number(code.max_locals)));
+            let mut instructions = vec![];
+            for result in disassemble(&code.code) {
+                let (pc, instruction) = result?;
+                let mut bytes = vec![];
+                instruction.encode(pc, &mut bytes);
+                let mut object = vec![
+                    ("pc", number(pc)),
+                    ("opcode", number(instruction.opcode())),
+                    ("mnemonic", string(instruction.mnemonic())),
+                    // everything after the opcode, including switch padding
+                    ("operands", hex(&bytes[1..])),
+                ];
+                if let Some(index) = instruction.constant_pool_index() {
+                    object.push(("constant", number(index)));
+                }
+                let targets = instruction.branch_targets(pc);
+                if !targets.is_empty() {
+                    object.push(("targets", Json::Array(targets.iter().map(number).collect())));
+                }
+                instructions.push(Json::Object(object));
+            }
+            entries.push(("instructions", Json::Array(instructions)));
+            let exception_table = code
+                .exception_table
+                .iter()
+                .map(|entry| {
+                    Json::Object(vec![
+                        ("start_pc", number(entry.start_pc)),
+                        ("end_pc", number(entry.end_pc)),
+                        ("handler_pc", number(entry.handler_pc)),
+                        ("catch_type", number(entry.catch_type)),
+                    ])
+                })
+                .collect();
+            entries.push(("exception_table", Json::Array(exception_table)));
+            entries.push((
+                "attributes",
+                attributes_to_json(&code.attributes, constant_pool)?,
+            ));
+        }
+        Attribute::ConstantValue(constant_value) => entries.push((
+            "constantvalue_index",
+            number(constant_value.constantvalue_index),
+        )),
+        Attribute::EnclosingMethod(enclosing_method) => {
+            entries.push(("class_index", number(enclosing_method.class_index)));
+            entries.push(("method_index", number(enclosing_method.method_index)));
+        }
+        Attribute::InnerClasses(inner_classes) => {
+            let classes = inner_classes
+                .classes
+                .iter()
+                .map(|class| {
+                    Json::Object(vec![
+                        (
+                            "inner_class_info_index",
+                            number(class.inner_class_info_index),
+                        ),
+                        (
+                            "outer_class_info_index",
+                            number(class.outer_class_info_index),
+                        ),
+                        ("inner_name_index", number(class.inner_name_index)),
+                        (
+                            "inner_class_access_flags",
+                            number(class.inner_class_access_flags.bits()),
+                        ),
+                    ])
+                })
+                .collect();
+            entries.push(("classes", Json::Array(classes)));
+        }
+        Attribute::LineNumberTable(line_number_table) => {
+            let lines = line_number_table
+                .line_number_table
+                .iter()
+                .map(|entry| {
+                    Json::Object(vec![
+                        ("start_pc", number(entry.start_pc)),
+                        ("line_number", number(entry.line_number)),
+                    ])
+                })
+                .collect();
+            entries.push(("line_number_table", Json::Array(lines)));
+        }
+        Attribute::LocalVariableTable(local_variable_table) => {
+            let locals = local_variable_table
+                .local_variable_table
+                .iter()
+                .map(|entry| {
+                    Json::Object(vec![
+                        ("start_pc", number(entry.start_pc)),
+                        ("length", number(entry.length)),
+                        ("name_index", number(entry.name_index)),
+                        ("name", utf8(constant_pool, entry.name_index)),
+                        ("descriptor_index", number(entry.descriptor_index)),
+                        ("descriptor", utf8(constant_pool, entry.descriptor_index)),
+                        ("index", number(entry.index)),
+                    ])
+                })
+                .collect();
+            entries.push(("local_variable_table", Json::Array(locals)));
+        }
+        Attribute::LocalVariableTypeTable(local_variable_type_table) => {
+            let locals = local_variable_type_table
+                .local_variable_type_table
+                .iter()
+                .map(|entry| {
+                    Json::Object(vec![
+                        ("start_pc", number(entry.start_pc)),
+                        ("length", number(entry.length)),
+                        ("name_index", number(entry.name_index)),
+                        ("name", utf8(constant_pool, entry.name_index)),
+                        ("signature_index", number(entry.signature_index)),
+                        ("signature", utf8(constant_pool, entry.signature_index)),
+                        ("index", number(entry.index)),
+                    ])
+                })
+                .collect();
+            entries.push(("local_variable_type_table", Json::Array(locals)));
+        }
+        Attribute::PermittedSubclasses(permitted_subclasses) => entries.push((
+            "classes",
+            Json::Array(permitted_subclasses.classes.iter().map(number).collect()),
+        )),
+        Attribute::Record(record) => {
+            let mut components = vec![];
+            for component in &record.components {
+                components.push(Json::Object(vec![
+                    ("name_index", number(component.name_index)),
+                    ("name", utf8(constant_pool, component.name_index)),
+                    ("descriptor_index", number(component.descriptor_index)),
+                    (
+                        "descriptor",
+                        utf8(constant_pool, component.descriptor_index),
+                    ),
+                    (
+                        "attributes",
+                        attributes_to_json(&component.attributes, constant_pool)?,
+                    ),
+                ]));
+            }
+            entries.push(("components", Json::Array(components)));
+        }
+        Attribute::RuntimeInvisibleAnnotations(annotations) => {
+            entries.push(("annotations", annotations_to_json(&annotations.annotations)))
+        }
+        Attribute::RuntimeVisibleAnnotations(annotations) => {
+            entries.push(("annotations", annotations_to_json(&annotations.annotations)))
+        }
+        Attribute::RuntimeInvisibleParameterAnnotations(annotations) => entries.push((
+            "parameter_annotations",
+            Json::Array(
+                annotations
+                    .parameter_annotations
+                    .iter()
+                    .map(|annotations| annotations_to_json(annotations))
+                    .collect(),
+            ),
+        )),
+        Attribute::RuntimeVisibleParameterAnnotations(annotations) => entries.push((
+            "parameter_annotations",
+            Json::Array(
+                annotations
+                    .parameter_annotations
+                    .iter()
+                    .map(|annotations| annotations_to_json(annotations))
+                    .collect(),
+            ),
+        )),
+        Attribute::RuntimeInvisibleTypeAnnotations(annotations) => entries.push((
+            "annotations",
+            type_annotations_to_json(&annotations.annotations),
+        )),
+        Attribute::RuntimeVisibleTypeAnnotations(annotations) => entries.push((
+            "annotations",
+            type_annotations_to_json(&annotations.annotations),
+        )),
+        Attribute::Signature(signature) => {
+            entries.push(("signature_index", number(signature.signature_index)));
+            entries.push(("signature", utf8(constant_pool, signature.signature_index)));
+        }
+        Attribute::SourceFile(source_file) => {
+            entries.push(("sourcefile_index", number(source_file.sourcefile_index)));
+            entries.push((
+                "sourcefile",
+                utf8(constant_pool, source_file.sourcefile_index),
+            ));
+        }
+        Attribute::StackMapTable(stack_map_table) => entries.push((
+            "entries",
+            Json::Array(
+                stack_map_table
+                    .entries
+                    .iter()
+                    .map(stack_map_frame_to_json)
+                    .collect(),
+            ),
+        )),
+    }
+    Ok(Json::Object(entries))
+}
+
+fn attributes_to_json(
+    attributes: &[Attribute],
+    constant_pool: &ConstantPool,
+) -> Result<Json, PrintError> {
+    let attributes = attributes
+        .iter()
+        .map(|attribute| attribute_to_json(attribute, constant_pool))
+        .collect::<Result<_, _>>()?;
+    Ok(Json::Array(attributes))
+}
+
+fn member_to_json(
+    access_flags: u16,
+    name_index: u16,
+    descriptor_index: u16,
+    attributes: &[Attribute],
+    constant_pool: &ConstantPool,
+) -> Result<Json, PrintError> {
+    Ok(Json::Object(vec![
+        ("access_flags", number(access_flags)),
+        ("name_index", number(name_index)),
+        ("name", utf8(constant_pool, name_index)),
+        ("descriptor_index", number(descriptor_index)),
+        ("descriptor", utf8(constant_pool, descriptor_index)),
+        ("attributes", attributes_to_json(attributes, constant_pool)?),
+    ]))
+}
+
+impl<'a> ClassFile<'a> {
+    fn to_json_value(&self) -> Result<Json, PrintError> {
+        let constant_pool = &self.constant_pool;
+        let constants = constant_pool
+            .iter()
+            .map(|(index, constant)| constant_to_json(index, constant))
+            .collect();
+        let interfaces = self
+            .interfaces
+            .iter()
+            .map(|index| {
+                Json::Object(vec![
+                    ("index", number(index)),
+                    ("name", class_name(constant_pool, *index)),
+                ])
+            })
+            .collect();
+        let fields = self
+            .fields
+            .iter()
+            .map(|field: &Field| {
+                member_to_json(
+                    field.access_flags.bits(),
+                    field.name_index,
+                    field.descriptor_index,
+                    &field.attributes,
+                    constant_pool,
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        let methods = self
+            .methods
+            .iter()
+            .map(|method: &Method| {
+                member_to_json(
+                    method.access_flags.bits(),
+                    method.name_index,
+                    method.descriptor_index,
+                    &method.attributes,
+                    constant_pool,
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        let super_class = match self.super_class {
+            0 => Json::Null,
+            index => class_name(constant_pool, index),
+        };
+        Ok(Json::Object(vec![
+            ("magic", number(self.magic)),
+            ("minor_version", number(self.minor_version)),
+            ("major_version", number(self.major_version)),
+            ("preview", Json::Bool(self.version().is_preview())),
+            ("constant_pool", Json::Array(constants)),
+            ("access_flags", number(self.access_flags.bits())),
+            ("this_class", number(self.this_class)),
+            (
+                "this_class_name",
+                class_name(constant_pool, self.this_class),
+            ),
+            ("super_class", number(self.super_class)),
+            ("super_class_name", super_class),
+            ("interfaces", Json::Array(interfaces)),
+            ("fields", Json::Array(fields)),
+            ("methods", Json::Array(methods)),
+            (
+                "attributes",
+                attributes_to_json(&self.attributes, constant_pool)?,
+            ),
+        ]))
+    }
+
+    // the parsed class as a JSON document for tools outside Rust. constants,
+    // members and attributes keep their raw indices, with names resolved
+    // next to them where the reference is unambiguous
+    pub fn to_json(&self) -> Result<String, PrintError> {
+        let mut output = String::new();
+        self.write_json(&mut output)?;
+        Ok(output)
+    }
+
+    pub fn write_json<W: fmt::Write>(&self, w: &mut W) -> Result<(), PrintError> {
+        self.to_json_value()?.write(w, 0)?;
+        w.write_char('\n')?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::parse_classfile;
+
+    #[test]
+    fn test_write_json() {
+        let value = Json::Object(vec![
+            ("empty", Json::Array(vec![])),
+            ("text", string("a \"quoted\"\\\n\u{1}")),
+            (
+                "values",
+                Json::Array(vec![
+                    Json::Null,
+                    Json::Bool(true),
+                    float(f64::NAN),
+                    float(1.5),
+                ]),
+            ),
+        ]);
+        let mut output = String::new();
+        value.write(&mut output, 0).unwrap();
+        let expected = r#"
+{
+  "empty": [],
+  "text": "a \"quoted\"\\\n\u0001",
+  "values": [
+    null,
+    true,
+    "NaN",
+    1.5
+  ]
+}"#;
+        assert_eq!(output, expected[1..]);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, classfile) = parse_classfile(data).unwrap();
+
+        let output = classfile.to_json().unwrap();
+        assert!(output.starts_with("{\n  \"magic\": 3405691582,\n"));
+        assert!(output.contains("\"this_class_name\": \"HelloWorld\""));
+        assert!(output.contains("\"super_class_name\": \"java/lang/Object\""));
+        assert!(output.contains(
+            r#"
+    {
+      "index": 8,
+      "tag": "Utf8",
+      "value": "Hello, World!"
+    },"#
+        ));
+        assert!(output.contains(
+            r#"
+            {
+              "pc": 1,
+              "opcode": 183,
+              "mnemonic": "invokespecial",
+              "operands": "0001",
+              "constant": 1
+            },"#
+        ));
+        assert!(output.ends_with("}\n"));
+    }
+}
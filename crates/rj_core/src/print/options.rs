@@ -0,0 +1,170 @@
+// the least visible members to print, like javap's -public, -protected,
+// -package and -private flags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Visibility {
+    Public,
+    Protected,
+    Package,
+    Private,
+}
+
+impl Visibility {
+    pub fn of(public: bool, protected: bool, private: bool) -> Visibility {
+        if public {
+            Visibility::Public
+        } else if protected {
+            Visibility::Protected
+        } else if private {
+            Visibility::Private
+        } else {
+            Visibility::Package
+        }
+    }
+}
+
+// what `ClassFile::print_with` includes, following javap's flags. the
+// defaults match a bare `javap`: non-private member declarations only
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintOptions {
+    // -v: versions, flags, the constant pool and every member's descriptor,
+    // flags and attributes, including Code and its tables
+    pub verbose: bool,
+    // -c: the instructions and exception table of each method
+    pub show_code: bool,
+    // members at least this visible; -p is Visibility::Private
+    pub visibility: Visibility,
+    // only members whose name matches one of these patterns, where `*`
+    // matches any run of characters and `?` any one. empty prints them all
+    pub member_patterns: Vec<String>,
+    // -l: each method's LineNumberTable and LocalVariableTable
+    pub show_line_numbers: bool,
+    // -constants: the values of fields with a ConstantValue attribute
+    pub show_constants: bool,
+    // annotations above the class and each member, as they'd be written in
+    // source. javap has no flag for this and only lists them under -v
+    pub show_annotations: bool,
+    // -sysinfo: indents "Compiled from" under the file details; the size and
+    // checksum come from `print_system_info`, since a parsed class doesn't
+    // keep its bytes
+    pub show_system_info: bool,
+    // ANSI colors for keywords, constant indices, mnemonics and comments
+    pub color: bool,
+    // output that only changes with the semantics of the class, for diffing
+    // two compilations: no constant pool or indices into it, no line
+    // numbers, members sorted by name and descriptor and attributes by name
+    pub stable: bool,
+    // spaces per nesting level of members and their details
+    pub indent_width: usize,
+}
+
+impl PrintOptions {
+    pub const JAVAP: PrintOptions = PrintOptions {
+        verbose: false,
+        show_code: false,
+        visibility: Visibility::Package,
+        member_patterns: Vec::new(),
+        show_line_numbers: false,
+        show_constants: false,
+        show_annotations: false,
+        show_system_info: false,
+        color: false,
+        stable: false,
+        indent_width: 2,
+    };
+
+    // `javap -v -p`: everything, private members included, which -v alone
+    // doesn't show
+    pub const VERBOSE: PrintOptions = PrintOptions {
+        verbose: true,
+        show_code: true,
+        visibility: Visibility::Private,
+        member_patterns: Vec::new(),
+        show_line_numbers: true,
+        show_constants: false,
+        show_annotations: false,
+        show_system_info: true,
+        color: false,
+        stable: false,
+        indent_width: 2,
+    };
+
+    // whether members are separated by blank lines, as javap does once they
+    // have more than a declaration
+    pub(super) fn has_details(&self) -> bool {
+        self.verbose || self.show_code || self.show_line_numbers
+    }
+
+    // whether a member passes the visibility and name filters
+    pub(super) fn shows_member(&self, visibility: Visibility, name: &str) -> bool {
+        visibility <= self.visibility
+            && (self.member_patterns.is_empty()
+                || self
+                    .member_patterns
+                    .iter()
+                    .any(|pattern| matches_pattern(pattern.as_bytes(), name.as_bytes())))
+    }
+}
+
+// glob matching with `*` and `?`, backtracking to the last `*` on a mismatch
+fn matches_pattern(pattern: &[u8], name: &[u8]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    let mut star = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == b'?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+impl Default for PrintOptions {
+    fn default() -> Self {
+        PrintOptions::JAVAP
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern(b"main", b"main"));
+        assert!(!matches_pattern(b"main", b"mains"));
+        assert!(matches_pattern(b"get*", b"getName"));
+        assert!(matches_pattern(b"get*", b"get"));
+        assert!(matches_pattern(b"*Name", b"getName"));
+        assert!(matches_pattern(b"*a*e*", b"getName"));
+        assert!(!matches_pattern(b"*x*", b"getName"));
+        assert!(matches_pattern(b"<*init>", b"<clinit>"));
+        assert!(matches_pattern(b"<*init>", b"<init>"));
+        assert!(matches_pattern(b"<??init>", b"<clinit>"));
+        assert!(!matches_pattern(b"?", b""));
+        assert!(matches_pattern(b"*", b""));
+    }
+
+    #[test]
+    fn test_shows_member() {
+        let options = PrintOptions {
+            visibility: Visibility::Protected,
+            member_patterns: vec!["get*".to_string(), "size".to_string()],
+            ..PrintOptions::JAVAP
+        };
+        assert!(options.shows_member(Visibility::Public, "getName"));
+        assert!(options.shows_member(Visibility::Protected, "size"));
+        assert!(!options.shows_member(Visibility::Package, "getName"));
+        assert!(!options.shows_member(Visibility::Public, "setName"));
+        assert!(PrintOptions::JAVAP.shows_member(Visibility::Package, "anything"));
+        assert!(!PrintOptions::JAVAP.shows_member(Visibility::Private, "anything"));
+    }
+}
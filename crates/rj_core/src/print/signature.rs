@@ -0,0 +1,163 @@
+use crate::class::{
+    decode_modified_utf8, internal_to_binary_name, ClassTypeSignature, MethodSignature,
+    SimpleClassTypeSignature, TypeArgument, TypeParameter, TypeSignature,
+};
+
+fn print_name(name: &[u8]) -> String {
+    decode_modified_utf8(name)
+        .map(|name| internal_to_binary_name(&name))
+        .unwrap_or_default()
+}
+
+impl<'a> TypeSignature<'a> {
+    pub fn print(&self) -> String {
+        match self {
+            TypeSignature::Base(base_type) => base_type.print(),
+            TypeSignature::Class(class_type) => class_type.print(),
+            TypeSignature::TypeVariable(name) => print_name(name),
+            TypeSignature::Array(component) => format!("{}[]", component.print()),
+        }
+    }
+}
+
+// `<K, ? extends V>`, or empty without type arguments
+fn print_type_arguments(type_arguments: &[TypeArgument]) -> String {
+    if type_arguments.is_empty() {
+        return String::new();
+    }
+    let type_arguments = type_arguments
+        .iter()
+        .map(|type_argument| type_argument.print())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("<{type_arguments}>")
+}
+
+impl<'a> ClassTypeSignature<'a> {
+    // `java.util.Map<K, V>`, or `Outer<T>.Inner<U>` for a member class
+    pub fn print(&self) -> String {
+        let mut output = print_name(self.name) + &print_type_arguments(&self.type_arguments);
+        for inner in &self.inner {
+            output.push('.');
+            output.push_str(&inner.print());
+        }
+        output
+    }
+}
+
+impl<'a> SimpleClassTypeSignature<'a> {
+    pub fn print(&self) -> String {
+        print_name(self.name) + &print_type_arguments(&self.type_arguments)
+    }
+}
+
+impl<'a> TypeArgument<'a> {
+    pub fn print(&self) -> String {
+        match self {
+            TypeArgument::Wildcard => "?".to_string(),
+            TypeArgument::Extends(bound) => format!("? extends {}", bound.print()),
+            TypeArgument::Super(bound) => format!("? super {}", bound.print()),
+            TypeArgument::Exact(argument) => argument.print(),
+        }
+    }
+}
+
+impl<'a> TypeParameter<'a> {
+    // a lone java.lang.Object bound is implied, as in the source
+    pub fn print(&self) -> String {
+        let bounds = self
+            .class_bound
+            .iter()
+            .chain(&self.interface_bounds)
+            .filter(|bound| {
+                **bound
+                    != TypeSignature::Class(ClassTypeSignature {
+                        name: b"java/lang/Object",
+                        type_arguments: vec![],
+                        inner: vec![],
+                    })
+            })
+            .map(|bound| bound.print())
+            .collect::<Vec<_>>();
+        let name = print_name(self.name);
+        if bounds.is_empty() {
+            name
+        } else {
+            format!("{name} extends {}", bounds.join(" & "))
+        }
+    }
+}
+
+// `<K extends java.lang.Comparable<K>, V>`, or empty without type parameters
+pub(super) fn print_type_parameters(type_parameters: &[TypeParameter]) -> String {
+    if type_parameters.is_empty() {
+        return String::new();
+    }
+    let type_parameters = type_parameters
+        .iter()
+        .map(|type_parameter| type_parameter.print())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("<{type_parameters}>")
+}
+
+impl<'a> MethodSignature<'a> {
+    pub fn print_return(&self) -> String {
+        self.result.print()
+    }
+
+    pub fn print_parameters(&self) -> String {
+        self.parameters
+            .iter()
+            .map(|p| p.print())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::class::{parse_field_signature, parse_method_signature};
+
+    use super::*;
+
+    #[test]
+    fn test_print_field_signature() {
+        let cases: [(&[u8], &str); 6] = [
+            (b"Ljava/util/Map<TK;+TV;>;", "java.util.Map<K, ? extends V>"),
+            (b"Ljava/util/List<*>;", "java.util.List<?>"),
+            (
+                b"Ljava/util/List<-Ljava/lang/Integer;>;",
+                "java.util.List<? super java.lang.Integer>",
+            ),
+            (b"[[TV;", "V[][]"),
+            (
+                b"Ljava/util/Set<Ljava/util/Map$Entry<TK;TV;>;>;",
+                "java.util.Set<java.util.Map$Entry<K, V>>",
+            ),
+            (
+                b"Lpkg/Outer<TT;>.Inner<-TU;>;",
+                "pkg.Outer<T>.Inner<? super U>",
+            ),
+        ];
+        for (input, expected) in cases {
+            let (_, signature) = parse_field_signature(input).unwrap();
+            assert_eq!(signature.print(), expected);
+        }
+    }
+
+    #[test]
+    fn test_print_method_signature() {
+        let input = b"<T:Ljava/lang/Number;:Ljava/lang/Comparable<TT;>;U:Ljava/lang/Object;>(Ljava/util/List<+TT;>;[I)TT;";
+        let (_, signature) = parse_method_signature(input).unwrap();
+        assert_eq!(
+            print_type_parameters(&signature.type_parameters),
+            "<T extends java.lang.Number & java.lang.Comparable<T>, U>"
+        );
+        assert_eq!(
+            signature.print_parameters(),
+            "java.util.List<? extends T>, int[]"
+        );
+        assert_eq!(signature.print_return(), "T");
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+
+use super::color::comment_start;
+
+// the byte ranges of the `#n` constant indices in `code`, skipping quoted
+// strings
+fn index_ranges(code: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut in_string = false;
+    let mut chars = code.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '"' {
+            in_string = !in_string;
+        }
+        let index = c == '#' && chars.peek().is_some_and(|&(_, next)| next.is_ascii_digit());
+        if in_string || !index {
+            continue;
+        }
+        let mut end = i + 1;
+        while let Some(&(j, next)) = chars.peek() {
+            if !next.is_ascii_digit() {
+                break;
+            }
+            end = j + 1;
+            chars.next();
+        }
+        ranges.push((i, end));
+    }
+    ranges
+}
+
+// rewrites one line without constant pool indices. the comment javap
+// resolves an index to takes the place of the index, so
+// `ldc #7 // String hi` becomes `ldc String hi`, and where there's no
+// comment the indices are dropped
+pub(super) fn strip_indices(line: &str) -> String {
+    let (code, comment) = match comment_start(line) {
+        Some(start) => line.split_at(start),
+        None => (line, ""),
+    };
+    let ranges = index_ranges(code);
+    let (Some(&(first, _)), Some(&(_, last))) = (ranges.first(), ranges.last()) else {
+        return line.to_string();
+    };
+    if let Some(text) = comment.strip_prefix("// ") {
+        // operands after the index, like the dimensions of multianewarray
+        let operands = code[last..].split_whitespace().collect::<Vec<_>>();
+        return format!("{}{text}{}", &code[..first], operands.join(" "));
+    }
+
+    let mut output = String::new();
+    let mut rest = 0;
+    for (start, end) in ranges {
+        output.push_str(&code[rest..start]);
+        rest = end;
+        if code[rest..].starts_with(' ') {
+            rest += 1;
+        }
+    }
+    output.push_str(&code[rest..]);
+    output.trim_end().to_string()
+}
+
+// strips the indices of everything written through it a line at a time,
+// holding back any unfinished line until the rest of it arrives or `finish`
+// is called
+pub(super) struct Stable<'w, W: fmt::Write> {
+    inner: &'w mut W,
+    line: String,
+}
+
+impl<'w, W: fmt::Write> Stable<'w, W> {
+    pub(super) fn new(inner: &'w mut W) -> Self {
+        Stable {
+            inner,
+            line: String::new(),
+        }
+    }
+
+    pub(super) fn finish(self) -> fmt::Result {
+        if self.line.is_empty() {
+            return Ok(());
+        }
+        self.inner.write_str(&strip_indices(&self.line))
+    }
+}
+
+impl<'w, W: fmt::Write> fmt::Write for Stable<'w, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.line.push_str(s);
+        while let Some(end) = self.line.find('\n') {
+            let line = strip_indices(&self.line[..end]);
+            self.inner.write_str(&line)?;
+            self.inner.write_char('\n')?;
+            self.line.drain(..=end);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_indices() {
+        assert_eq!(
+            strip_indices("       1: invokespecial #1                  // Method a.b:()V"),
+            "       1: invokespecial Method a.b:()V"
+        );
+        assert_eq!(
+            strip_indices("      11: multianewarray #9,  2             // class \"[[I\""),
+            "      11: multianewarray class \"[[I\", 2"
+        );
+        assert_eq!(
+            strip_indices("  this_class: #32                         // Insns"),
+            "  this_class: Insns"
+        );
+        assert_eq!(
+            strip_indices("  static #72= #22 of #32;                 // Greeter=class Insns$Greeter of class Insns"),
+            "  static Greeter=class Insns$Greeter of class Insns;"
+        );
+        assert_eq!(
+            strip_indices("  0: #60 REF_invokeStatic a.b:()V"),
+            "  0: REF_invokeStatic a.b:()V"
+        );
+        assert_eq!(strip_indices("      #67 ()V"), "      ()V");
+        // quoted strings are left alone
+        assert_eq!(strip_indices("@A(value=\"#1\")"), "@A(value=\"#1\")");
+        assert_eq!(strip_indices("  line 4: 0"), "  line 4: 0");
+    }
+}
@@ -0,0 +1,17 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// the JDK JAVA_HOME names, or else the one the `java` on the PATH is from,
+// for tests that need the JDK's classes or to run java. they pass without
+// checking anything when there's none
+pub(crate) fn java_home() -> Option<PathBuf> {
+    if let Some(java_home) = env::var_os("JAVA_HOME") {
+        return Some(PathBuf::from(java_home));
+    }
+    let java = env::split_paths(&env::var_os("PATH")?)
+        .map(|directory| directory.join("java"))
+        .find(|java| java.is_file())?;
+    let java = fs::canonicalize(java).ok()?;
+    Some(java.parent()?.parent()?.to_path_buf())
+}
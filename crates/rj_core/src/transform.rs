@@ -0,0 +1,12 @@
+mod error;
+mod instrument;
+mod remap;
+mod rename;
+mod replace;
+mod strip;
+
+pub use error::*;
+pub use instrument::*;
+pub use remap::*;
+pub use rename::*;
+pub use strip::*;
@@ -0,0 +1,193 @@
+use std::fmt;
+
+use crate::asm::frames::FrameError;
+use crate::asm::{InstructionParseError, MaxStackError, RelocationError};
+
+#[derive(Debug, PartialEq)]
+pub enum RemapError {
+    InstructionParseError(InstructionParseError),
+    // a reference to an index outside the pool, or to an entry being removed
+    InvalidIndex(u16),
+    // merges that lead back to where they started
+    MergeCycle(u16),
+    // an ldc at this pc whose constant moved past index 255
+    LdcIndexTooLarge(u32),
+    // an attribute with a payload whose references can't be found, which
+    // would be left pointing at the wrong entries
+    OpaqueAttribute(Vec<u8>),
+    // a lazily parsed attribute, whose payload can't be rewritten until it's
+    // decoded
+    UndecodedAttribute(Vec<u8>),
+}
+
+impl From<InstructionParseError> for RemapError {
+    fn from(error: InstructionParseError) -> Self {
+        RemapError::InstructionParseError(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ReplaceCodeError {
+    // the code being replaced doesn't disassemble
+    InstructionParseError(InstructionParseError),
+    // no method with this name and descriptor
+    MethodNotFound(String, String),
+    // an abstract or native method, or one whose Code attribute was left raw
+    MissingCode,
+    InvalidDescriptor(String),
+    // a branch at this pc that doesn't land on an instruction
+    InvalidBranchTarget(u32),
+    EmptyCode,
+    CodeTooLarge(u32),
+    MaxStackError(MaxStackError),
+    FrameError(FrameError),
+}
+
+impl From<InstructionParseError> for ReplaceCodeError {
+    fn from(error: InstructionParseError) -> Self {
+        ReplaceCodeError::InstructionParseError(error)
+    }
+}
+
+impl From<MaxStackError> for ReplaceCodeError {
+    fn from(error: MaxStackError) -> Self {
+        ReplaceCodeError::MaxStackError(error)
+    }
+}
+
+impl From<FrameError> for ReplaceCodeError {
+    fn from(error: FrameError) -> Self {
+        ReplaceCodeError::FrameError(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RenameError {
+    // not the kind of entry expected here
+    InvalidIndex(u16),
+    // the Utf8 at this index should hold a class name or descriptor
+    InvalidDescriptor(u16),
+    // the Utf8 at this index should hold a generic signature
+    InvalidSignature(u16),
+    // a lazily parsed attribute, which may name classes that can't be renamed
+    // until it's decoded
+    UndecodedAttribute(Vec<u8>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InstrumentError {
+    InstructionParseError(InstructionParseError),
+    RelocationError(RelocationError),
+    // not the kind of entry expected here
+    InvalidIndex(u16),
+    // a StackMapTable frame at this offset, which isn't an instruction's
+    InvalidFrameOffset(u32),
+    // a Code attribute that was left raw
+    UndecodedAttribute(Vec<u8>),
+    CodeTooLarge(u32),
+    MaxStackError(MaxStackError),
+}
+
+impl From<InstructionParseError> for InstrumentError {
+    fn from(error: InstructionParseError) -> Self {
+        InstrumentError::InstructionParseError(error)
+    }
+}
+
+impl From<RelocationError> for InstrumentError {
+    fn from(error: RelocationError) -> Self {
+        InstrumentError::RelocationError(error)
+    }
+}
+
+impl From<MaxStackError> for InstrumentError {
+    fn from(error: MaxStackError) -> Self {
+        InstrumentError::MaxStackError(error)
+    }
+}
+
+impl fmt::Display for RemapError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RemapError::InstructionParseError(error) => write!(f, "{error}"),
+            RemapError::InvalidIndex(index) => write!(f, "invalid constant pool index #{index}"),
+            RemapError::MergeCycle(index) => write!(f, "merging #{index} leads back to itself"),
+            RemapError::LdcIndexTooLarge(pc) => {
+                write!(f, "the constant of the ldc at pc {pc} moved past #255")
+            }
+            RemapError::OpaqueAttribute(name) => write!(
+                f,
+                "can't remap the references in the {} attribute",
+                String::from_utf8_lossy(name)
+            ),
+            RemapError::UndecodedAttribute(name) => write!(
+                f,
+                "the {} attribute isn't decoded",
+                String::from_utf8_lossy(name)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ReplaceCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplaceCodeError::InstructionParseError(error) => write!(f, "{error}"),
+            ReplaceCodeError::MethodNotFound(name, descriptor) => {
+                write!(f, "no method {name}:{descriptor}")
+            }
+            ReplaceCodeError::MissingCode => write!(f, "the method has no code"),
+            ReplaceCodeError::InvalidDescriptor(descriptor) => {
+                write!(f, "invalid method descriptor {descriptor}")
+            }
+            ReplaceCodeError::InvalidBranchTarget(pc) => {
+                write!(f, "invalid branch target from pc {pc}")
+            }
+            ReplaceCodeError::EmptyCode => write!(f, "empty code"),
+            ReplaceCodeError::CodeTooLarge(length) => {
+                write!(f, "{length} bytes of code, over 65535")
+            }
+            ReplaceCodeError::MaxStackError(error) => write!(f, "{error}"),
+            ReplaceCodeError::FrameError(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenameError::InvalidIndex(index) => write!(f, "unexpected constant #{index}"),
+            RenameError::InvalidDescriptor(index) => {
+                write!(f, "#{index} isn't a class name or descriptor")
+            }
+            RenameError::InvalidSignature(index) => write!(f, "#{index} isn't a signature"),
+            RenameError::UndecodedAttribute(name) => write!(
+                f,
+                "the {} attribute isn't decoded",
+                String::from_utf8_lossy(name)
+            ),
+        }
+    }
+}
+
+impl fmt::Display for InstrumentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InstrumentError::InstructionParseError(error) => write!(f, "{error}"),
+            InstrumentError::RelocationError(error) => write!(f, "{error}"),
+            InstrumentError::InvalidIndex(index) => write!(f, "unexpected constant #{index}"),
+            InstrumentError::InvalidFrameOffset(offset) => {
+                write!(f, "a stack map frame at {offset}, between instructions")
+            }
+            InstrumentError::UndecodedAttribute(name) => write!(
+                f,
+                "the {} attribute isn't decoded",
+                String::from_utf8_lossy(name)
+            ),
+            InstrumentError::CodeTooLarge(length) => {
+                write!(f, "{length} bytes of code, over 65535")
+            }
+            InstrumentError::MaxStackError(error) => write!(f, "{error}"),
+        }
+    }
+}
@@ -0,0 +1,508 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use super::error::InstrumentError;
+use super::replace::remap_offsets;
+use crate::asm::{
+    compute_max_stack, disassemble, relocate, CodeEdit, Instruction, InstructionKind,
+};
+use crate::class::{
+    encode_modified_utf8, Attribute, ClassFile, Code, Constant, ConstantPool, StackMapFrame,
+    StackMapTable, VerificationTypeInfo,
+};
+
+// what the hooks are called with: the class's internal name, and the
+// method's name followed by its descriptor
+const HOOK_DESCRIPTOR: &str = "(Ljava/lang/String;Ljava/lang/String;)V";
+
+// decides by name and descriptor which methods are instrumented
+type MethodFilter = Box<dyn Fn(&str, &str) -> bool>;
+
+struct Hook {
+    owner: String,
+    name: String,
+}
+
+fn utf8(constant_pool: &mut ConstantPool, value: &str) -> u16 {
+    constant_pool.find_or_push(Constant::Utf8 {
+        value: encode_modified_utf8(value).into_owned().into(),
+    })
+}
+
+fn string(constant_pool: &mut ConstantPool, value: &str) -> u16 {
+    let string_index = utf8(constant_pool, value);
+    constant_pool.find_or_push(Constant::String { string_index })
+}
+
+fn ldc(index: u16) -> Instruction {
+    match u8::try_from(index) {
+        Ok(index) => Instruction::Ldc(index),
+        Err(_) => Instruction::LdcW(index),
+    }
+}
+
+impl Hook {
+    fn method_ref(&self, constant_pool: &mut ConstantPool) -> u16 {
+        let name_index = utf8(constant_pool, &self.owner);
+        let class_index = constant_pool.find_or_push(Constant::Class { name_index });
+        let name_index = utf8(constant_pool, &self.name);
+        let descriptor_index = utf8(constant_pool, HOOK_DESCRIPTOR);
+        let name_and_type_index = constant_pool.find_or_push(Constant::NameAndType {
+            name_index,
+            descriptor_index,
+        });
+        constant_pool.find_or_push(Constant::Methodref {
+            class_index,
+            name_and_type_index,
+        })
+    }
+}
+
+// the frames of a StackMapTable moved to where their offsets went
+fn remap_frames(
+    table: &mut StackMapTable,
+    map_pc: &dyn Fn(u32) -> Option<u32>,
+) -> Result<(), InstrumentError> {
+    let mut previous = None;
+    let mut new_previous = None;
+    let entries = std::mem::take(&mut table.entries);
+    for mut frame in entries {
+        let pc = match previous {
+            None => frame.offset_delta() as u32,
+            Some(previous) => previous + frame.offset_delta() as u32 + 1,
+        };
+        let new_pc = map_pc(pc).ok_or(InstrumentError::InvalidFrameOffset(pc))?;
+        let offset_delta = match new_previous {
+            None => new_pc,
+            Some(previous) => new_pc - previous - 1,
+        };
+        let verification_types: Vec<_> = match &mut frame {
+            StackMapFrame::SameLocals1StackItem { stack, .. }
+            | StackMapFrame::SameLocals1StackItemExtended { stack, .. } => vec![stack],
+            StackMapFrame::Append { locals, .. } => locals.iter_mut().collect(),
+            StackMapFrame::Full { locals, stack, .. } => {
+                locals.iter_mut().chain(stack.iter_mut()).collect()
+            }
+            _ => Vec::new(),
+        };
+        for verification_type in verification_types {
+            if let VerificationTypeInfo::Uninitialized { offset } = verification_type {
+                let pc = *offset as u32;
+                *offset = map_pc(pc).ok_or(InstrumentError::InvalidFrameOffset(pc))? as u16;
+            }
+        }
+        table
+            .entries
+            .push(frame.with_offset_delta(offset_delta as u16));
+        (previous, new_previous) = (Some(pc), Some(new_pc));
+    }
+    Ok(())
+}
+
+// wraps methods with calls to static hooks: one on entry, before the first
+// instruction, and one before every return and athrow. each hook is a
+// `static void` method taking two Strings, the class's internal name and the
+// method's name and descriptor, e.g. `run()V`. exceptions thrown by calls
+// out of the method don't reach the exit hook.
+//
+// the code around the calls is left as it was, so the StackMapTable, the
+// exception table and the debug tables are moved along with it rather than
+// computed again
+#[derive(Default)]
+pub struct Instrumenter {
+    on_entry: Option<Hook>,
+    on_exit: Option<Hook>,
+    filter: Option<MethodFilter>,
+}
+
+impl Instrumenter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // calls `owner.name` on entry to every instrumented method
+    pub fn on_entry(&mut self, owner: &str, name: &str) -> &mut Self {
+        self.on_entry = Some(Hook {
+            owner: owner.to_string(),
+            name: name.to_string(),
+        });
+        self
+    }
+
+    // calls `owner.name` before every return and athrow
+    pub fn on_exit(&mut self, owner: &str, name: &str) -> &mut Self {
+        self.on_exit = Some(Hook {
+            owner: owner.to_string(),
+            name: name.to_string(),
+        });
+        self
+    }
+
+    // instruments just the methods `filter` accepts, by name and descriptor.
+    // by default every method with code is. the hooks themselves never are
+    pub fn filter<F>(&mut self, filter: F) -> &mut Self
+    where
+        F: Fn(&str, &str) -> bool + 'static,
+    {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    fn is_hook(&self, class: &str, name: &str, descriptor: &str) -> bool {
+        let hooks = self.on_entry.iter().chain(&self.on_exit);
+        descriptor == HOOK_DESCRIPTOR
+            && hooks
+                .into_iter()
+                .any(|hook| hook.owner == class && hook.name == name)
+    }
+
+    pub fn apply(&self, classfile: &mut ClassFile) -> Result<(), InstrumentError> {
+        if self.on_entry.is_none() && self.on_exit.is_none() {
+            return Ok(());
+        }
+        let constant_pool = &mut classfile.constant_pool;
+        let class = constant_pool
+            .class_name(classfile.this_class)
+            .map_err(|_| InstrumentError::InvalidIndex(classfile.this_class))?
+            .into_owned();
+        let on_entry = self
+            .on_entry
+            .as_ref()
+            .map(|hook| hook.method_ref(constant_pool));
+        let on_exit = self
+            .on_exit
+            .as_ref()
+            .map(|hook| hook.method_ref(constant_pool));
+        let class_string = string(constant_pool, &class);
+
+        for method in &mut classfile.methods {
+            let name = constant_pool
+                .utf8(method.name_index)
+                .map_err(|_| InstrumentError::InvalidIndex(method.name_index))?
+                .into_owned();
+            let descriptor = constant_pool
+                .utf8(method.descriptor_index)
+                .map_err(|_| InstrumentError::InvalidIndex(method.descriptor_index))?
+                .into_owned();
+            let accepted = self.filter.as_ref().map_or(true, |f| f(&name, &descriptor));
+            if !accepted || self.is_hook(&class, &name, &descriptor) {
+                continue;
+            }
+            let mut code = None;
+            for attribute in &mut method.attributes {
+                match attribute {
+                    Attribute::Code(decoded) => code = Some(decoded),
+                    Attribute::Raw { name, .. } if **name == *b"Code" => {
+                        return Err(InstrumentError::UndecodedAttribute(name.to_vec()));
+                    }
+                    _ => {}
+                }
+            }
+            let Some(code) = code else {
+                continue;
+            };
+
+            let method_string = string(constant_pool, &format!("{name}{descriptor}"));
+            let call = |hook: u16| {
+                vec![
+                    ldc(class_string),
+                    ldc(method_string),
+                    Instruction::Invokestatic(hook),
+                ]
+            };
+            instrument(code, on_entry.map(call), on_exit.map(call), constant_pool)?;
+        }
+        Ok(())
+    }
+}
+
+fn instrument(
+    code: &mut Code<Attribute>,
+    on_entry: Option<Vec<Instruction>>,
+    on_exit: Option<Vec<Instruction>>,
+    constant_pool: &ConstantPool,
+) -> Result<(), InstrumentError> {
+    let mut instructions = disassemble(&code.code).collect::<Result<Vec<_>, _>>()?;
+    let exits = instructions
+        .iter()
+        .filter(|(_, instruction)| {
+            matches!(
+                instruction.kind(),
+                InstructionKind::Return | InstructionKind::Throw
+            )
+        })
+        .map(|(pc, _)| *pc)
+        .collect::<Vec<_>>();
+
+    // where each original offset is now, through every edit so far
+    let mut pcs = instructions
+        .iter()
+        .map(|(pc, _)| (*pc, *pc))
+        .chain(std::iter::once((
+            code.code.len() as u32,
+            code.code.len() as u32,
+        )))
+        .collect::<HashMap<_, _>>();
+    let mut edits = Vec::new();
+    if let Some(on_entry) = on_entry {
+        // a loop back to the first instruction doesn't enter again
+        edits.push(CodeEdit::InsertFallthrough {
+            pc: 0,
+            instructions: on_entry,
+        });
+    }
+    for pc in exits {
+        if let Some(on_exit) = &on_exit {
+            edits.push(CodeEdit::Insert {
+                pc,
+                instructions: on_exit.clone(),
+            });
+        }
+    }
+    for edit in edits {
+        let edit = match edit {
+            CodeEdit::Insert { pc, instructions } => CodeEdit::Insert {
+                pc: pcs[&pc],
+                instructions,
+            },
+            edit => edit,
+        };
+        let relocation = relocate(&instructions, edit)?;
+        for pc in pcs.values_mut() {
+            *pc = relocation.map_pc(*pc).unwrap_or(*pc);
+        }
+        instructions = relocation.instructions;
+    }
+
+    let mut bytes = Vec::new();
+    for (pc, instruction) in &instructions {
+        instruction.encode(*pc, &mut bytes);
+    }
+    if bytes.len() > u16::MAX as usize {
+        return Err(InstrumentError::CodeTooLarge(bytes.len() as u32));
+    }
+    code.code = Cow::Owned(bytes);
+    remap_offsets(code, &|pc| pcs.get(&(pc as u32)).map(|pc| *pc as u16));
+    for attribute in &mut code.attributes {
+        if let Attribute::StackMapTable(table) = attribute {
+            remap_frames(table, &|pc| pcs.get(&pc).copied())?;
+        }
+    }
+    code.max_stack = compute_max_stack(&instructions, &code.exception_table, constant_pool)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::ClassBuilder;
+    use crate::class::{parse_classfile, ClassVersion};
+
+    fn method_code<'c>(classfile: &'c ClassFile, name: &str) -> &'c Code<'c, Attribute<'c>> {
+        let method = classfile
+            .methods
+            .iter()
+            .find(|method| classfile.constant_pool.utf8(method.name_index).unwrap() == name)
+            .unwrap();
+        method.code().unwrap()
+    }
+
+    fn instructions(classfile: &ClassFile, name: &str) -> Vec<(u32, Instruction)> {
+        disassemble(&method_code(classfile, name).code)
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    // the ldc, ldc, invokestatic of a hook call, as strings
+    fn hook_call(classfile: &ClassFile, code: &[(u32, Instruction)]) -> (String, String, String) {
+        let constant_pool = &classfile.constant_pool;
+        let string = |instruction: &Instruction| {
+            let index = instruction.constant_pool_index().unwrap();
+            let Some(Constant::String { string_index }) = constant_pool.get(index) else {
+                panic!("expected a String");
+            };
+            constant_pool.utf8(*string_index).unwrap().into_owned()
+        };
+        let Instruction::Invokestatic(index) = code[2].1 else {
+            panic!("expected an invokestatic");
+        };
+        let Some(Constant::Methodref {
+            name_and_type_index,
+            ..
+        }) = constant_pool.get(index)
+        else {
+            panic!("expected a Methodref");
+        };
+        let Some(Constant::NameAndType { name_index, .. }) =
+            constant_pool.get(*name_and_type_index)
+        else {
+            panic!("expected a NameAndType");
+        };
+        (
+            string(&code[0].1),
+            string(&code[1].1),
+            constant_pool.utf8(*name_index).unwrap().into_owned(),
+        )
+    }
+
+    #[test]
+    fn test_instrument() {
+        let data = include_bytes!("../../../../java/Frames.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let old = &method_code(&classfile, "parse").exception_table[0];
+        let old = (old.start_pc, old.end_pc, old.handler_pc);
+        Instrumenter::new()
+            .on_entry("Hooks", "enter")
+            .on_exit("Hooks", "exit")
+            .apply(&mut classfile)
+            .unwrap();
+
+        let code = instructions(&classfile, "sum");
+        assert_eq!(
+            hook_call(&classfile, &code[..3]),
+            ("Frames".into(), "sum(I)I".into(), "enter".into())
+        );
+        // the loop and its frames moved along by the 7 bytes of the call
+        assert_eq!(code[10], (16, Instruction::IfIcmpge(13)));
+        assert_eq!(code[16], (26, Instruction::Goto(-12)));
+        assert_eq!(
+            hook_call(&classfile, &code[18..21]),
+            ("Frames".into(), "sum(I)I".into(), "exit".into())
+        );
+        assert_eq!(code[21], (37, Instruction::Ireturn));
+        let Some(Attribute::StackMapTable(table)) = method_code(&classfile, "sum")
+            .attributes
+            .iter()
+            .find(|attribute| matches!(attribute, Attribute::StackMapTable(_)))
+        else {
+            panic!("expected a StackMapTable");
+        };
+        let deltas = table
+            .entries
+            .iter()
+            .map(StackMapFrame::offset_delta)
+            .collect::<Vec<_>>();
+        assert_eq!(deltas, [14, 14]);
+
+        // both returns in the try and the catch call the exit hook first. the
+        // call before the try's return is past its end, so the handler doesn't
+        // catch what the hook throws
+        let code = method_code(&classfile, "parse");
+        let handlers = code
+            .exception_table
+            .iter()
+            .map(|entry| (entry.start_pc, entry.end_pc, entry.handler_pc))
+            .collect::<Vec<_>>();
+        assert_eq!(handlers, [(old.0 + 7, old.1 + 7, old.2 + 14)]);
+        let exits = instructions(&classfile, "parse")
+            .iter()
+            .filter(|(_, instruction)| *instruction == Instruction::Ireturn)
+            .count();
+        assert_eq!(exits, 2);
+        // the return value is under the hook's two arguments
+        assert_eq!(code.max_stack, 3);
+
+        let bytes = classfile.to_bytes().unwrap();
+        assert_eq!(parse_classfile(&bytes).unwrap().1, classfile);
+    }
+
+    #[test]
+    fn test_instrument_full_frames() {
+        let data = include_bytes!("../../../../java/Insns.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let (_, original) = parse_classfile(data).unwrap();
+        Instrumenter::new()
+            .on_exit("Hooks", "exit")
+            .apply(&mut classfile)
+            .unwrap();
+        let frames = |classfile: &ClassFile| {
+            let code = method_code(classfile, "misc");
+            let Some(Attribute::StackMapTable(table)) = code
+                .attributes
+                .iter()
+                .find(|attribute| matches!(attribute, Attribute::StackMapTable(_)))
+            else {
+                panic!("expected a StackMapTable");
+            };
+            table
+                .entries
+                .iter()
+                .map(|frame| match frame {
+                    StackMapFrame::Full { locals, stack, .. } => {
+                        (frame.offset_delta(), locals.len(), stack.len())
+                    }
+                    _ => (frame.offset_delta(), 0, 0),
+                })
+                .collect::<Vec<_>>()
+        };
+        // the handler's frame keeps its Throwable on the stack, and the frame
+        // after the handler's athrow moves past the exit call before it
+        assert_eq!(frames(&original), [(58, 8, 0), (66, 15, 1), (7, 0, 0)]);
+        assert_eq!(frames(&classfile), [(58, 8, 0), (66, 15, 1), (14, 0, 0)]);
+    }
+
+    #[test]
+    fn test_instrument_loop_to_entry() {
+        let mut class = ClassBuilder::new("Count");
+        class
+            .version(ClassVersion::new(52, 0))
+            .public_static_method("count", "(I)V", |body| {
+                let head = body.code.label();
+                let end = body.code.label();
+                body.code
+                    .bind(head)
+                    .emit(Instruction::Iload0)
+                    .branch(Instruction::Ifle(0), end)
+                    .emit(Instruction::Iinc(0, -1))
+                    .goto(head)
+                    .bind(end)
+                    .emit(Instruction::Return);
+                Ok(())
+            });
+        let mut classfile = class.build().unwrap();
+        Instrumenter::new()
+            .on_entry("Count", "enter")
+            .apply(&mut classfile)
+            .unwrap();
+        let code = instructions(&classfile, "count");
+        // the loop goes back to the iload, not the entry hook
+        assert_eq!(code[3], (7, Instruction::Iload0));
+        assert_eq!(code[6], (14, Instruction::Goto(-7)));
+        assert_eq!(code[7], (17, Instruction::Return));
+    }
+
+    #[test]
+    fn test_instrument_filter() {
+        let data = include_bytes!("../../../../java/Frames.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        Instrumenter::new()
+            .on_exit("Frames", "exit")
+            .filter(|name, _| name == "choose")
+            .apply(&mut classfile)
+            .unwrap();
+        let (_, original) = parse_classfile(data).unwrap();
+        for name in ["<init>", "sum", "parse", "twice"] {
+            assert_eq!(method_code(&classfile, name), method_code(&original, name));
+        }
+        let code = instructions(&classfile, "choose");
+        assert_eq!(code[0], (0, Instruction::Iload0));
+        assert_eq!(
+            hook_call(&classfile, &code[9..12]),
+            ("Frames".into(), "choose(II)I".into(), "exit".into())
+        );
+
+        // the hooks aren't instrumented, filtered or not
+        let mut class = ClassBuilder::new("Hooks");
+        class.public_static_method("exit", HOOK_DESCRIPTOR, |body| {
+            body.code.emit(Instruction::Return);
+            Ok(())
+        });
+        let mut classfile = class.build().unwrap();
+        Instrumenter::new()
+            .on_exit("Hooks", "exit")
+            .filter(|_, _| true)
+            .apply(&mut classfile)
+            .unwrap();
+        assert_eq!(instructions(&classfile, "exit"), [(0, Instruction::Return)]);
+    }
+}
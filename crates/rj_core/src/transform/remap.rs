@@ -0,0 +1,670 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use super::error::RemapError;
+use crate::asm::disassemble;
+use crate::class::{
+    Annotation, Attribute, ClassFile, Code, Constant, ConstantPool, ElementValue, StackMapFrame,
+    VerificationTypeInfo,
+};
+
+// called with every constant pool index found, returning the index to
+// write in its place
+type Visit<'f> = dyn FnMut(u16) -> Result<u16, RemapError> + 'f;
+
+fn visit(index: &mut u16, f: &mut Visit) -> Result<(), RemapError> {
+    *index = f(*index)?;
+    Ok(())
+}
+
+// an index where 0 means none, like a catch-all handler's catch_type
+fn visit_optional(index: &mut u16, f: &mut Visit) -> Result<(), RemapError> {
+    if *index != 0 {
+        visit(index, f)?;
+    }
+    Ok(())
+}
+
+fn visit_constant(constant: &mut Constant, f: &mut Visit) -> Result<(), RemapError> {
+    match constant {
+        Constant::Utf8 { .. }
+        | Constant::Integer { .. }
+        | Constant::Float { .. }
+        | Constant::Long { .. }
+        | Constant::Double { .. } => {}
+        Constant::Class { name_index: index }
+        | Constant::String {
+            string_index: index,
+        }
+        | Constant::MethodHandle {
+            reference_index: index,
+            ..
+        }
+        | Constant::MethodType {
+            descriptor_index: index,
+        }
+        | Constant::Dynamic {
+            name_and_type_index: index,
+            ..
+        }
+        | Constant::InvokeDynamic {
+            name_and_type_index: index,
+            ..
+        }
+        | Constant::Module { name_index: index }
+        | Constant::Package { name_index: index } => visit(index, f)?,
+        Constant::Fieldref {
+            class_index,
+            name_and_type_index,
+        }
+        | Constant::Methodref {
+            class_index,
+            name_and_type_index,
+        }
+        | Constant::InterfaceMethodref {
+            class_index,
+            name_and_type_index,
+        } => {
+            visit(class_index, f)?;
+            visit(name_and_type_index, f)?;
+        }
+        Constant::NameAndType {
+            name_index,
+            descriptor_index,
+        } => {
+            visit(name_index, f)?;
+            visit(descriptor_index, f)?;
+        }
+    }
+    Ok(())
+}
+
+fn visit_element_value(value: &mut ElementValue, f: &mut Visit) -> Result<(), RemapError> {
+    match value {
+        ElementValue::Const {
+            const_value_index, ..
+        } => visit(const_value_index, f),
+        ElementValue::Enum {
+            type_name_index,
+            const_name_index,
+        } => {
+            visit(type_name_index, f)?;
+            visit(const_name_index, f)
+        }
+        ElementValue::Class { class_info_index } => visit(class_info_index, f),
+        ElementValue::Annotation(annotation) => visit_annotation(annotation, f),
+        ElementValue::Array(values) => values
+            .iter_mut()
+            .try_for_each(|value| visit_element_value(value, f)),
+    }
+}
+
+fn visit_annotation(annotation: &mut Annotation, f: &mut Visit) -> Result<(), RemapError> {
+    visit(&mut annotation.type_index, f)?;
+    for pair in &mut annotation.element_value_pairs {
+        visit(&mut pair.element_name_index, f)?;
+        visit_element_value(&mut pair.value, f)?;
+    }
+    Ok(())
+}
+
+fn visit_annotations(annotations: &mut [Annotation], f: &mut Visit) -> Result<(), RemapError> {
+    annotations
+        .iter_mut()
+        .try_for_each(|annotation| visit_annotation(annotation, f))
+}
+
+fn visit_verification_types(
+    verification_types: &mut [VerificationTypeInfo],
+    f: &mut Visit,
+) -> Result<(), RemapError> {
+    for verification_type in verification_types {
+        if let VerificationTypeInfo::Object { cpool_index } = verification_type {
+            visit(cpool_index, f)?;
+        }
+    }
+    Ok(())
+}
+
+fn visit_code(code: &mut Code<Attribute>, f: &mut Visit) -> Result<(), RemapError> {
+    let mut instructions = disassemble(&code.code).collect::<Result<Vec<_>, _>>()?;
+    let mut changed = false;
+    for (pc, instruction) in &mut instructions {
+        let Some(index) = instruction.constant_pool_index() else {
+            continue;
+        };
+        let new_index = f(index)?;
+        if new_index != index {
+            *instruction = instruction
+                .with_constant_pool_index(new_index)
+                .ok_or(RemapError::LdcIndexTooLarge(*pc))?;
+            changed = true;
+        }
+    }
+    // operands keep their width, so every instruction stays where it was
+    if changed {
+        let mut bytes = Vec::with_capacity(code.code.len());
+        for (pc, instruction) in &instructions {
+            instruction.encode(*pc, &mut bytes);
+        }
+        code.code = Cow::Owned(bytes);
+    }
+    for entry in &mut code.exception_table {
+        visit_optional(&mut entry.catch_type, f)?;
+    }
+    visit_attributes(&mut code.attributes, f)
+}
+
+// offsets of the indices in the payload of an attribute that isn't decoded,
+// for the standard ones simple enough to follow as they are
+fn payload_indices(name: &[u8], data: &[u8]) -> Option<Vec<usize>> {
+    let count = |width: usize| -> Option<usize> {
+        match width {
+            1 => data.first().map(|count| *count as usize),
+            _ => Some(u16::from_be_bytes(data.get(..2)?.try_into().ok()?) as usize),
+        }
+    };
+    let offsets = match name {
+        b"Synthetic" | b"Deprecated" | b"SourceDebugExtension" => Vec::new(),
+        b"NestHost" | b"ModuleMainClass" => vec![0],
+        b"Exceptions" | b"NestMembers" | b"ModulePackages" => {
+            (0..count(2)?).map(|i| 2 + i * 2).collect()
+        }
+        // name_index and access_flags
+        b"MethodParameters" => (0..count(1)?).map(|i| 1 + i * 4).collect(),
+        _ => return None,
+    };
+    let end = match name {
+        b"SourceDebugExtension" => data.len(),
+        b"MethodParameters" => 1 + offsets.len() * 4,
+        _ => offsets.last().map_or(0, |offset| offset + 2),
+    };
+    (end == data.len()).then_some(offsets)
+}
+
+fn visit_payload(name: &[u8], data: &mut Cow<[u8]>, f: &mut Visit) -> Result<(), RemapError> {
+    let offsets = match payload_indices(name, data) {
+        Some(offsets) => offsets,
+        None if data.is_empty() => return Ok(()),
+        None => return Err(RemapError::OpaqueAttribute(name.to_vec())),
+    };
+    for offset in offsets {
+        let index = u16::from_be_bytes([data[offset], data[offset + 1]]);
+        // a parameter without a name has index 0
+        if index == 0 && name == b"MethodParameters" {
+            continue;
+        }
+        let new_index = f(index)?;
+        if new_index != index {
+            data.to_mut()[offset..offset + 2].copy_from_slice(&new_index.to_be_bytes());
+        }
+    }
+    Ok(())
+}
+
+fn visit_attribute(attribute: &mut Attribute, f: &mut Visit) -> Result<(), RemapError> {
+    match attribute {
+        Attribute::Unknown {
+            attribute_name_index,
+            name,
+            data,
+        }
+        | Attribute::Custom {
+            attribute_name_index,
+            name,
+            data,
+            ..
+        } => {
+            visit(attribute_name_index, f)?;
+            visit_payload(name, data, f)?;
+        }
+        Attribute::Raw { name, .. } => {
+            return Err(RemapError::UndecodedAttribute(name.to_vec()));
+        }
+        Attribute::AnnotationDefault(attribute) => {
+            visit_element_value(&mut attribute.default_value, f)?
+        }
+        Attribute::BootstrapMethods(attribute) => {
+            for method in &mut attribute.bootstrap_methods {
+                visit(&mut method.bootstrap_method_ref, f)?;
+                for argument in &mut method.bootstrap_arguments {
+                    visit(argument, f)?;
+                }
+            }
+        }
+        Attribute::Code(code) => visit_code(code, f)?,
+        Attribute::ConstantValue(attribute) => visit(&mut attribute.constantvalue_index, f)?,
+        Attribute::EnclosingMethod(attribute) => {
+            visit(&mut attribute.class_index, f)?;
+            visit_optional(&mut attribute.method_index, f)?;
+        }
+        Attribute::InnerClasses(attribute) => {
+            for class in &mut attribute.classes {
+                visit(&mut class.inner_class_info_index, f)?;
+                visit_optional(&mut class.outer_class_info_index, f)?;
+                visit_optional(&mut class.inner_name_index, f)?;
+            }
+        }
+        Attribute::LineNumberTable(_) => {}
+        Attribute::LocalVariableTable(attribute) => {
+            for entry in &mut attribute.local_variable_table {
+                visit(&mut entry.name_index, f)?;
+                visit(&mut entry.descriptor_index, f)?;
+            }
+        }
+        Attribute::LocalVariableTypeTable(attribute) => {
+            for entry in &mut attribute.local_variable_type_table {
+                visit(&mut entry.name_index, f)?;
+                visit(&mut entry.signature_index, f)?;
+            }
+        }
+        Attribute::PermittedSubclasses(attribute) => {
+            for class in &mut attribute.classes {
+                visit(class, f)?;
+            }
+        }
+        Attribute::Record(record) => {
+            for component in &mut record.components {
+                visit(&mut component.name_index, f)?;
+                visit(&mut component.descriptor_index, f)?;
+                visit_attributes(&mut component.attributes, f)?;
+            }
+        }
+        Attribute::RuntimeInvisibleAnnotations(attribute) => {
+            visit_annotations(&mut attribute.annotations, f)?
+        }
+        Attribute::RuntimeVisibleAnnotations(attribute) => {
+            visit_annotations(&mut attribute.annotations, f)?
+        }
+        Attribute::RuntimeInvisibleParameterAnnotations(attribute) => {
+            for annotations in &mut attribute.parameter_annotations {
+                visit_annotations(annotations, f)?;
+            }
+        }
+        Attribute::RuntimeVisibleParameterAnnotations(attribute) => {
+            for annotations in &mut attribute.parameter_annotations {
+                visit_annotations(annotations, f)?;
+            }
+        }
+        Attribute::RuntimeInvisibleTypeAnnotations(attribute) => {
+            for annotation in &mut attribute.annotations {
+                visit_annotation(&mut annotation.annotation, f)?;
+            }
+        }
+        Attribute::RuntimeVisibleTypeAnnotations(attribute) => {
+            for annotation in &mut attribute.annotations {
+                visit_annotation(&mut annotation.annotation, f)?;
+            }
+        }
+        Attribute::Signature(attribute) => visit(&mut attribute.signature_index, f)?,
+        Attribute::SourceFile(attribute) => visit(&mut attribute.sourcefile_index, f)?,
+        Attribute::StackMapTable(attribute) => {
+            for frame in &mut attribute.entries {
+                match frame {
+                    StackMapFrame::SameLocals1StackItem { stack, .. }
+                    | StackMapFrame::SameLocals1StackItemExtended { stack, .. } => {
+                        visit_verification_types(std::slice::from_mut(stack), f)?
+                    }
+                    StackMapFrame::Append { locals, .. } => visit_verification_types(locals, f)?,
+                    StackMapFrame::Full { locals, stack, .. } => {
+                        visit_verification_types(locals, f)?;
+                        visit_verification_types(stack, f)?;
+                    }
+                    StackMapFrame::Same { .. }
+                    | StackMapFrame::SameExtended { .. }
+                    | StackMapFrame::Chop { .. } => {}
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn visit_attributes(attributes: &mut [Attribute], f: &mut Visit) -> Result<(), RemapError> {
+    attributes
+        .iter_mut()
+        .try_for_each(|attribute| visit_attribute(attribute, f))
+}
+
+// every index outside the constant pool itself
+fn visit_classfile(classfile: &mut ClassFile, f: &mut Visit) -> Result<(), RemapError> {
+    visit(&mut classfile.this_class, f)?;
+    visit_optional(&mut classfile.super_class, f)?;
+    for interface in &mut classfile.interfaces {
+        visit(interface, f)?;
+    }
+    for field in &mut classfile.fields {
+        visit(&mut field.name_index, f)?;
+        visit(&mut field.descriptor_index, f)?;
+        visit_attributes(&mut field.attributes, f)?;
+    }
+    for method in &mut classfile.methods {
+        visit(&mut method.name_index, f)?;
+        visit(&mut method.descriptor_index, f)?;
+        visit_attributes(&mut method.attributes, f)?;
+    }
+    visit_attributes(&mut classfile.attributes, f)
+}
+
+// decoded attributes are written under the first Utf8 holding their name
+fn visit_attribute_names(attributes: &[Attribute], names: &mut Vec<&'static str>) {
+    for attribute in attributes {
+        if let Some(name) = attribute.attribute_name() {
+            names.push(name.as_str());
+        }
+        match attribute {
+            Attribute::Code(code) => visit_attribute_names(&code.attributes, names),
+            Attribute::Record(record) => {
+                for component in &record.components {
+                    visit_attribute_names(&component.attributes, names);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn attribute_names(classfile: &ClassFile) -> Vec<&'static str> {
+    let mut names = Vec::new();
+    for field in &classfile.fields {
+        visit_attribute_names(&field.attributes, &mut names);
+    }
+    for method in &classfile.methods {
+        visit_attribute_names(&method.attributes, &mut names);
+    }
+    visit_attribute_names(&classfile.attributes, &mut names);
+    names
+}
+
+// where each entry of the old pool went
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexMap {
+    // by old index; 0 for the entries that were dropped
+    indices: Vec<u16>,
+}
+
+impl IndexMap {
+    // the new index of the old entry `index`, or of the entry it was merged
+    // into; None if it was removed
+    pub fn get(&self, index: u16) -> Option<u16> {
+        match self.indices.get(index as usize) {
+            Some(0) | None => None,
+            Some(index) => Some(*index),
+        }
+    }
+}
+
+// rewrites a class after entries of its constant pool are merged or removed:
+// the pool is compacted and every reference to it, from other constants,
+// members, attributes and instructions, is renumbered. the payloads of
+// unknown and custom attributes can't be followed and are left as they are
+#[derive(Debug, Default)]
+pub struct ConstantPoolRemapper {
+    merged: HashMap<u16, u16>,
+    removed: HashSet<u16>,
+    remove_unused: bool,
+}
+
+impl ConstantPoolRemapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // references to `from` become references to `into`, and `from` is
+    // dropped
+    pub fn merge(&mut self, from: u16, into: u16) -> &mut Self {
+        self.merged.insert(from, into);
+        self
+    }
+
+    // drops the entry; anything still referring to it is an error
+    pub fn remove(&mut self, index: u16) -> &mut Self {
+        self.removed.insert(index);
+        self
+    }
+
+    // also drops every entry nothing refers to, directly or through other
+    // constants. Utf8 entries naming a decoded attribute are kept
+    pub fn remove_unused(&mut self) -> &mut Self {
+        self.remove_unused = true;
+        self
+    }
+
+    // the entry `index` ends up as, after following merges
+    fn resolve(&self, mut index: u16, limit: usize) -> Result<u16, RemapError> {
+        let start = index;
+        for _ in 0..=limit {
+            match self.merged.get(&index) {
+                Some(into) => index = *into,
+                None => return Ok(index),
+            }
+        }
+        Err(RemapError::MergeCycle(start))
+    }
+
+    // the entries that are referred to, after merges
+    fn used(&self, classfile: &mut ClassFile) -> Result<HashSet<u16>, RemapError> {
+        let limit = classfile.constant_pool.len();
+        let mut used = HashSet::new();
+        let mut pending = Vec::new();
+        visit_classfile(classfile, &mut |index| {
+            pending.push(self.resolve(index, limit)?);
+            Ok(index)
+        })?;
+        for name in attribute_names(classfile) {
+            pending.extend(classfile.constant_pool.find_utf8(name));
+        }
+        while let Some(index) = pending.pop() {
+            if !used.insert(index) {
+                continue;
+            }
+            let Some(constant) = classfile.constant_pool.get(index) else {
+                continue;
+            };
+            visit_constant(&mut constant.clone(), &mut |index| {
+                pending.push(self.resolve(index, limit)?);
+                Ok(index)
+            })?;
+        }
+        Ok(used)
+    }
+
+    // compacts the pool of `classfile` and renumbers its references,
+    // returning where each old entry went. on error the class is left
+    // partly rewritten
+    pub fn apply(&self, classfile: &mut ClassFile) -> Result<IndexMap, RemapError> {
+        let limit = classfile.constant_pool.len();
+        let used = match self.remove_unused {
+            true => Some(self.used(classfile)?),
+            false => None,
+        };
+
+        let mut indices = vec![0; limit + 1];
+        let mut kept = Vec::new();
+        let mut numbering = ConstantPool::new();
+        for (index, constant) in classfile.constant_pool.iter() {
+            let keep = !self.removed.contains(&index)
+                && !self.merged.contains_key(&index)
+                && used.as_ref().map_or(true, |used| used.contains(&index));
+            if keep {
+                indices[index as usize] = numbering.push(constant.clone());
+                kept.push(constant.clone());
+            }
+        }
+        for &from in self.merged.keys() {
+            let into = self.resolve(from, limit)?;
+            let index = match self.removed.contains(&into) {
+                true => 0,
+                false => *indices.get(into as usize).unwrap_or(&0),
+            };
+            if let Some(slot) = indices.get_mut(from as usize) {
+                *slot = index;
+            }
+        }
+
+        let index_map = IndexMap { indices };
+        let mut renumber = |index: u16| index_map.get(index).ok_or(RemapError::InvalidIndex(index));
+        let mut constant_pool = ConstantPool::new();
+        for mut constant in kept {
+            visit_constant(&mut constant, &mut renumber)?;
+            constant_pool.push(constant);
+        }
+        visit_classfile(classfile, &mut renumber)?;
+        classfile.constant_pool = constant_pool;
+        Ok(index_map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::{ClassBuilder, Instruction};
+    use crate::class::{parse_classfile, ClassVersion};
+
+    fn greeter(unused: bool) -> ClassFile<'static> {
+        let mut class = ClassBuilder::new("Greeter");
+        class
+            .version(ClassVersion::new(52, 0))
+            .public_static_method("greet", "(Ljava/lang/Object;)Ljava/lang/String;", |body| {
+                if unused {
+                    body.constant_pool.string("unused")?;
+                }
+                let hello = body.constant_pool.string("hello")?;
+                let null = body.code.label();
+                body.code
+                    .emit(Instruction::Aload0)
+                    .branch(Instruction::Ifnull(0), null)
+                    .emit(Instruction::Ldc(hello as u8))
+                    .emit(Instruction::Areturn)
+                    .bind(null)
+                    .emit(Instruction::AconstNull)
+                    .emit(Instruction::Areturn);
+                Ok(())
+            });
+        class.build().unwrap()
+    }
+
+    #[test]
+    fn test_remap_unchanged() {
+        let classes: [&[u8]; 23] = [
+            include_bytes!("../../../../java/Annotated$Hidden.class"),
+            include_bytes!("../../../../java/Annotated$Info.class"),
+            include_bytes!("../../../../java/Annotated$Marker.class"),
+            include_bytes!("../../../../java/Annotated$Typed.class"),
+            include_bytes!("../../../../java/Annotated.class"),
+            include_bytes!("../../../../java/Constants.class"),
+            include_bytes!("../../../../java/Debug.class"),
+            include_bytes!("../../../../java/Frames.class"),
+            include_bytes!("../../../../java/Generics.class"),
+            include_bytes!("../../../../java/HelloWorld.class"),
+            include_bytes!("../../../../java/Insns$Greeter.class"),
+            include_bytes!("../../../../java/Insns.class"),
+            include_bytes!("../../../../java/Locals.class"),
+            include_bytes!("../../../../java/Outer$1.class"),
+            include_bytes!("../../../../java/Outer$Inner.class"),
+            include_bytes!("../../../../java/Outer$Nested.class"),
+            include_bytes!("../../../../java/Outer.class"),
+            include_bytes!("../../../../java/Point.class"),
+            include_bytes!("../../../../java/Shape.class"),
+            include_bytes!("../../../../java/Switches.class"),
+            include_bytes!("../../../../java/Vehicle$Car.class"),
+            include_bytes!("../../../../java/Vehicle$Truck.class"),
+            include_bytes!("../../../../java/Vehicle.class"),
+        ];
+        for data in classes {
+            let (_, mut classfile) = parse_classfile(data).unwrap();
+            let len = classfile.constant_pool.len() as u16;
+            let index_map = ConstantPoolRemapper::new()
+                .remove_unused()
+                .apply(&mut classfile)
+                .unwrap();
+            assert_eq!(classfile.to_bytes().unwrap(), data);
+            assert_eq!(index_map.get(1), Some(1));
+            assert_eq!(index_map.get(0), None);
+            assert_eq!(index_map.get(len + 1), None);
+        }
+    }
+
+    #[test]
+    fn test_remap_merge() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let this_class = classfile.this_class;
+        let duplicate = classfile
+            .constant_pool
+            .push(classfile.constant_pool.get(this_class).unwrap().clone());
+        classfile.this_class = duplicate;
+
+        let index_map = ConstantPoolRemapper::new()
+            .merge(duplicate, this_class)
+            .apply(&mut classfile)
+            .unwrap();
+        assert_eq!(index_map.get(duplicate), Some(this_class));
+        assert_eq!(classfile.to_bytes().unwrap(), data);
+    }
+
+    #[test]
+    fn test_remap_compact() {
+        let mut classfile = greeter(true);
+        let unused = classfile
+            .constant_pool
+            .strings()
+            .find(|(_, value)| value == "unused")
+            .unwrap()
+            .0;
+        let index_map = ConstantPoolRemapper::new()
+            .remove_unused()
+            .apply(&mut classfile)
+            .unwrap();
+        assert_eq!(index_map.get(unused), None);
+        assert_eq!(index_map.get(unused + 2), Some(unused));
+
+        // the ldc, the frame's Object and everything else moved down
+        let expected = greeter(false);
+        assert_eq!(classfile, expected);
+        assert_eq!(classfile.to_bytes(), expected.to_bytes());
+    }
+
+    #[test]
+    fn test_remap_errors() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let this_class = classfile.this_class;
+        assert_eq!(
+            ConstantPoolRemapper::new()
+                .remove(this_class)
+                .apply(&mut classfile),
+            Err(RemapError::InvalidIndex(this_class))
+        );
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        assert!(matches!(
+            ConstantPoolRemapper::new()
+                .merge(1, 2)
+                .merge(2, 1)
+                .apply(&mut classfile),
+            Err(RemapError::MergeCycle(1 | 2))
+        ));
+
+        // nothing says where the indices in an unknown payload are
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let attribute_name_index = classfile.constant_pool.push(Constant::Utf8 {
+            value: b"Extra".into(),
+        });
+        classfile.attributes.push(Attribute::Unknown {
+            attribute_name_index,
+            name: b"Extra".into(),
+            data: [0, 7].as_slice().into(),
+        });
+        assert_eq!(
+            ConstantPoolRemapper::new().apply(&mut classfile),
+            Err(RemapError::OpaqueAttribute(b"Extra".to_vec()))
+        );
+
+        // a lazily parsed class has attributes that can't be followed
+        let (_, mut lazy) = crate::class::parse_classfile_lazy(data).unwrap();
+        assert!(matches!(
+            ConstantPoolRemapper::new().apply(&mut lazy),
+            Err(RemapError::UndecodedAttribute(_))
+        ));
+    }
+}
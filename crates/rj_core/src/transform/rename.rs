@@ -0,0 +1,566 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use super::error::RenameError;
+use crate::class::{
+    class_names, encode_modified_utf8, Annotation, Attribute, ClassFile, Constant, ConstantPool,
+    ElementValue, Text,
+};
+
+impl Text {
+    fn error(self, index: u16) -> RenameError {
+        match self {
+            Text::ClassSignature | Text::MethodSignature | Text::FieldSignature => {
+                RenameError::InvalidSignature(index)
+            }
+            _ => RenameError::InvalidDescriptor(index),
+        }
+    }
+}
+
+fn utf8<'v>(constant_pool: &'v ConstantPool, index: u16) -> Result<&'v [u8], RenameError> {
+    match constant_pool.get(index) {
+        Some(Constant::Utf8 { value }) => Ok(value),
+        _ => Err(RenameError::InvalidIndex(index)),
+    }
+}
+
+// renames classes and members throughout a class: the Class entries, the
+// descriptors and generic signatures that mention a class, and the member
+// references and declarations of a renamed field or method. names are
+// internal names, and members are picked out by their owner, name and
+// descriptor as they were before any renaming. applying the same Renamer to
+// every class of a program renames consistently across them.
+//
+// entries are never changed in place, since a Utf8 can be shared by unrelated
+// uses, such as a String with a class's name; new ones are added instead,
+// and ConstantPoolRemapper::remove_unused drops the old ones. members
+// referenced through a subclass, inner class simple names and the payloads of
+// unknown attributes are left alone
+#[derive(Debug, Default)]
+pub struct Renamer {
+    classes: HashMap<Vec<u8>, Vec<u8>>,
+    fields: HashMap<(String, String, String), String>,
+    methods: HashMap<(String, String, String), String>,
+}
+
+impl Renamer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rename_class(&mut self, from: &str, to: &str) -> &mut Self {
+        self.classes.insert(
+            encode_modified_utf8(from).into_owned(),
+            encode_modified_utf8(to).into_owned(),
+        );
+        self
+    }
+
+    pub fn rename_field(
+        &mut self,
+        owner: &str,
+        name: &str,
+        descriptor: &str,
+        to: &str,
+    ) -> &mut Self {
+        let key = (owner.to_string(), name.to_string(), descriptor.to_string());
+        self.fields.insert(key, to.to_string());
+        self
+    }
+
+    pub fn rename_method(
+        &mut self,
+        owner: &str,
+        name: &str,
+        descriptor: &str,
+        to: &str,
+    ) -> &mut Self {
+        let key = (owner.to_string(), name.to_string(), descriptor.to_string());
+        self.methods.insert(key, to.to_string());
+        self
+    }
+
+    // `value` with every class name in it renamed; None if it doesn't parse
+    fn renamed(&self, value: &[u8], text: Text) -> Option<Vec<u8>> {
+        let mut names = class_names(value, text)?;
+        names.sort_by_key(|name| name.as_ptr());
+        let mut renamed = Vec::with_capacity(value.len());
+        let mut end = 0;
+        for name in names {
+            let Some(to) = self.classes.get(name) else {
+                continue;
+            };
+            let start = name.as_ptr() as usize - value.as_ptr() as usize;
+            renamed.extend_from_slice(&value[end..start]);
+            renamed.extend_from_slice(to);
+            end = start + name.len();
+        }
+        renamed.extend_from_slice(&value[end..]);
+        Some(renamed)
+    }
+
+    pub fn apply(&self, classfile: &mut ClassFile) -> Result<(), RenameError> {
+        let this_class = classfile
+            .constant_pool
+            .class_name(classfile.this_class)
+            .map_err(|_| RenameError::InvalidIndex(classfile.this_class))?
+            .into_owned();
+        let mut renaming = Renaming {
+            renamer: self,
+            constant_pool: &mut classfile.constant_pool,
+        };
+
+        // references are matched while the Class entries still hold the old
+        // names, and before the descriptors of NameAndTypes change
+        renaming.member_refs()?;
+        for attribute in &mut classfile.attributes {
+            if let Attribute::EnclosingMethod(attribute) = attribute {
+                if attribute.method_index != 0 {
+                    attribute.method_index = renaming.member_ref(
+                        &self.methods,
+                        attribute.class_index,
+                        attribute.method_index,
+                    )?;
+                }
+            }
+        }
+        renaming.constants()?;
+
+        for field in &mut classfile.fields {
+            field.name_index = renaming.member(
+                &self.fields,
+                &this_class,
+                field.name_index,
+                field.descriptor_index,
+            )?;
+            field.descriptor_index =
+                renaming.text(field.descriptor_index, Text::FieldDescriptor)?;
+            renaming.attributes(&mut field.attributes, Text::FieldSignature)?;
+        }
+        for method in &mut classfile.methods {
+            method.name_index = renaming.member(
+                &self.methods,
+                &this_class,
+                method.name_index,
+                method.descriptor_index,
+            )?;
+            method.descriptor_index =
+                renaming.text(method.descriptor_index, Text::MethodDescriptor)?;
+            renaming.attributes(&mut method.attributes, Text::MethodSignature)?;
+        }
+        renaming.attributes(&mut classfile.attributes, Text::ClassSignature)
+    }
+}
+
+struct Renaming<'r, 'p, 'a> {
+    renamer: &'r Renamer,
+    constant_pool: &'p mut ConstantPool<'a>,
+}
+
+impl<'r, 'p, 'a> Renaming<'r, 'p, 'a> {
+    fn intern_utf8(&mut self, value: Vec<u8>) -> u16 {
+        self.constant_pool.find_or_push(Constant::Utf8 {
+            value: Cow::Owned(value),
+        })
+    }
+
+    fn intern_name_and_type(&mut self, name_index: u16, descriptor_index: u16) -> u16 {
+        self.constant_pool.find_or_push(Constant::NameAndType {
+            name_index,
+            descriptor_index,
+        })
+    }
+
+    // the index of the Utf8 holding the text at `index` once renamed
+    fn text(&mut self, index: u16, text: Text) -> Result<u16, RenameError> {
+        let value = utf8(self.constant_pool, index)?;
+        let renamed = self
+            .renamer
+            .renamed(value, text)
+            .ok_or_else(|| text.error(index))?;
+        if renamed == value {
+            return Ok(index);
+        }
+        Ok(self.intern_utf8(renamed))
+    }
+
+    fn member_name(
+        &mut self,
+        members: &HashMap<(String, String, String), String>,
+        owner: &str,
+        name_index: u16,
+        descriptor_index: u16,
+    ) -> Result<Option<u16>, RenameError> {
+        let name = String::from_utf8_lossy(utf8(self.constant_pool, name_index)?);
+        let descriptor = String::from_utf8_lossy(utf8(self.constant_pool, descriptor_index)?);
+        let key = (
+            owner.to_string(),
+            name.into_owned(),
+            descriptor.into_owned(),
+        );
+        Ok(members
+            .get(&key)
+            .map(|to| self.intern_utf8(encode_modified_utf8(to).into_owned())))
+    }
+
+    // the name of a member this class declares
+    fn member(
+        &mut self,
+        members: &HashMap<(String, String, String), String>,
+        owner: &str,
+        name_index: u16,
+        descriptor_index: u16,
+    ) -> Result<u16, RenameError> {
+        let renamed = self.member_name(members, owner, name_index, descriptor_index)?;
+        Ok(renamed.unwrap_or(name_index))
+    }
+
+    // the NameAndType a reference to a member of `class_index` should use; a
+    // renamed member gets one of its own, as others may share the old one
+    fn member_ref(
+        &mut self,
+        members: &HashMap<(String, String, String), String>,
+        class_index: u16,
+        name_and_type_index: u16,
+    ) -> Result<u16, RenameError> {
+        let owner = self
+            .constant_pool
+            .class_name(class_index)
+            .map_err(|_| RenameError::InvalidIndex(class_index))?
+            .into_owned();
+        let (name_index, descriptor_index) = match self.constant_pool.get(name_and_type_index) {
+            Some(Constant::NameAndType {
+                name_index,
+                descriptor_index,
+            }) => (*name_index, *descriptor_index),
+            _ => return Err(RenameError::InvalidIndex(name_and_type_index)),
+        };
+        match self.member_name(members, &owner, name_index, descriptor_index)? {
+            Some(name_index) => Ok(self.intern_name_and_type(name_index, descriptor_index)),
+            None => Ok(name_and_type_index),
+        }
+    }
+
+    fn member_refs(&mut self) -> Result<(), RenameError> {
+        let renamer = self.renamer;
+        let refs = self
+            .constant_pool
+            .iter()
+            .filter_map(|(index, constant)| match constant {
+                Constant::Fieldref {
+                    class_index,
+                    name_and_type_index,
+                } => Some((index, &renamer.fields, *class_index, *name_and_type_index)),
+                Constant::Methodref {
+                    class_index,
+                    name_and_type_index,
+                }
+                | Constant::InterfaceMethodref {
+                    class_index,
+                    name_and_type_index,
+                } => Some((index, &renamer.methods, *class_index, *name_and_type_index)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for (index, members, class_index, name_and_type_index) in refs {
+            let renamed = self.member_ref(members, class_index, name_and_type_index)?;
+            match self.constant_pool.get_mut(index) {
+                Some(
+                    Constant::Fieldref {
+                        name_and_type_index,
+                        ..
+                    }
+                    | Constant::Methodref {
+                        name_and_type_index,
+                        ..
+                    }
+                    | Constant::InterfaceMethodref {
+                        name_and_type_index,
+                        ..
+                    },
+                ) => *name_and_type_index = renamed,
+                _ => unreachable!(),
+            }
+        }
+        Ok(())
+    }
+
+    // Class entries, and the descriptors in NameAndType and MethodType
+    fn constants(&mut self) -> Result<(), RenameError> {
+        for index in 1..=self.constant_pool.len() as u16 {
+            let (target, text) = match self.constant_pool.get(index) {
+                Some(Constant::Class { name_index }) => (*name_index, Text::ClassName),
+                Some(Constant::NameAndType {
+                    descriptor_index, ..
+                }) => (*descriptor_index, Text::Descriptor),
+                Some(Constant::MethodType { descriptor_index }) => {
+                    (*descriptor_index, Text::MethodDescriptor)
+                }
+                _ => continue,
+            };
+            let renamed = self.text(target, text)?;
+            match self.constant_pool.get_mut(index) {
+                Some(
+                    Constant::Class { name_index: slot }
+                    | Constant::NameAndType {
+                        descriptor_index: slot,
+                        ..
+                    }
+                    | Constant::MethodType {
+                        descriptor_index: slot,
+                    },
+                ) => *slot = renamed,
+                _ => unreachable!(),
+            }
+        }
+        Ok(())
+    }
+
+    fn element_value(&mut self, value: &mut ElementValue) -> Result<(), RenameError> {
+        match value {
+            ElementValue::Const { .. } => {}
+            ElementValue::Enum {
+                type_name_index, ..
+            } => *type_name_index = self.text(*type_name_index, Text::FieldDescriptor)?,
+            ElementValue::Class { class_info_index } => {
+                *class_info_index = self.text(*class_info_index, Text::ReturnDescriptor)?
+            }
+            ElementValue::Annotation(annotation) => self.annotation(annotation)?,
+            ElementValue::Array(values) => {
+                for value in values {
+                    self.element_value(value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn annotation(&mut self, annotation: &mut Annotation) -> Result<(), RenameError> {
+        annotation.type_index = self.text(annotation.type_index, Text::FieldDescriptor)?;
+        for pair in &mut annotation.element_value_pairs {
+            self.element_value(&mut pair.value)?;
+        }
+        Ok(())
+    }
+
+    fn annotations<'x>(
+        &mut self,
+        annotations: impl IntoIterator<Item = &'x mut Annotation>,
+    ) -> Result<(), RenameError> {
+        annotations
+            .into_iter()
+            .try_for_each(|annotation| self.annotation(annotation))
+    }
+
+    // `signature` is what a Signature attribute among these holds
+    fn attributes(
+        &mut self,
+        attributes: &mut [Attribute],
+        signature: Text,
+    ) -> Result<(), RenameError> {
+        for attribute in attributes {
+            match attribute {
+                Attribute::Raw { name, .. } => {
+                    return Err(RenameError::UndecodedAttribute(name.to_vec()));
+                }
+                Attribute::Signature(attribute) => {
+                    attribute.signature_index = self.text(attribute.signature_index, signature)?
+                }
+                Attribute::Code(code) => self.attributes(&mut code.attributes, signature)?,
+                Attribute::LocalVariableTable(attribute) => {
+                    for entry in &mut attribute.local_variable_table {
+                        entry.descriptor_index =
+                            self.text(entry.descriptor_index, Text::FieldDescriptor)?;
+                    }
+                }
+                Attribute::LocalVariableTypeTable(attribute) => {
+                    for entry in &mut attribute.local_variable_type_table {
+                        entry.signature_index =
+                            self.text(entry.signature_index, Text::FieldSignature)?;
+                    }
+                }
+                Attribute::Record(record) => {
+                    for component in &mut record.components {
+                        component.descriptor_index =
+                            self.text(component.descriptor_index, Text::FieldDescriptor)?;
+                        self.attributes(&mut component.attributes, Text::FieldSignature)?;
+                    }
+                }
+                Attribute::AnnotationDefault(attribute) => {
+                    self.element_value(&mut attribute.default_value)?
+                }
+                Attribute::RuntimeVisibleAnnotations(attribute) => {
+                    self.annotations(&mut attribute.annotations)?
+                }
+                Attribute::RuntimeInvisibleAnnotations(attribute) => {
+                    self.annotations(&mut attribute.annotations)?
+                }
+                Attribute::RuntimeVisibleParameterAnnotations(attribute) => {
+                    self.annotations(attribute.parameter_annotations.iter_mut().flatten())?
+                }
+                Attribute::RuntimeInvisibleParameterAnnotations(attribute) => {
+                    self.annotations(attribute.parameter_annotations.iter_mut().flatten())?
+                }
+                Attribute::RuntimeVisibleTypeAnnotations(attribute) => self.annotations(
+                    attribute
+                        .annotations
+                        .iter_mut()
+                        .map(|annotation| &mut annotation.annotation),
+                )?,
+                Attribute::RuntimeInvisibleTypeAnnotations(attribute) => self.annotations(
+                    attribute
+                        .annotations
+                        .iter_mut()
+                        .map(|annotation| &mut annotation.annotation),
+                )?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::{parse_classfile, parse_classfile_lazy};
+
+    fn signature(classfile: &ClassFile, attributes: &[Attribute]) -> String {
+        attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                Attribute::Signature(signature) => {
+                    let value = classfile.constant_pool.utf8(signature.signature_index);
+                    Some(value.unwrap().into_owned())
+                }
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_rename_class() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        Renamer::new()
+            .rename_class("HelloWorld", "demo/Greeting")
+            .apply(&mut classfile)
+            .unwrap();
+        let constant_pool = &classfile.constant_pool;
+        assert_eq!(
+            constant_pool.class_name(classfile.this_class).unwrap(),
+            "demo/Greeting"
+        );
+        assert!(constant_pool
+            .classes()
+            .all(|(_, name)| name != "HelloWorld"));
+        // only the Class entry moves; the old Utf8 is left for the remapper
+        assert!(constant_pool.find_utf8("HelloWorld").is_some());
+
+        let bytes = classfile.to_bytes().unwrap();
+        assert_eq!(parse_classfile(&bytes).unwrap().1, classfile);
+    }
+
+    #[test]
+    fn test_rename_descriptors_and_signatures() {
+        let data = include_bytes!("../../../../java/Generics.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        Renamer::new()
+            .rename_class("java/util/List", "java/util/Sequence")
+            .rename_class("java/util/Map$Entry", "java/util/Pair")
+            .rename_class("java/lang/Iterable", "java/lang/Walkable")
+            .apply(&mut classfile)
+            .unwrap();
+        let constant_pool = &classfile.constant_pool;
+
+        let names = &classfile.fields[0];
+        assert_eq!(
+            constant_pool.utf8(names.descriptor_index).unwrap(),
+            "Ljava/util/Sequence;"
+        );
+        assert_eq!(
+            signature(&classfile, &names.attributes),
+            "Ljava/util/Sequence<Ljava/lang/String;>;"
+        );
+        assert_eq!(
+            signature(&classfile, &classfile.attributes),
+            "<K::Ljava/lang/Comparable<TK;>;V:Ljava/lang/Object;>Ljava/util/AbstractMap<TK;TV;>;Ljava/lang/Walkable<TK;>;"
+        );
+        let max = &classfile.methods[1];
+        assert_eq!(
+            constant_pool.utf8(max.descriptor_index).unwrap(),
+            "(Ljava/util/Sequence;)Ljava/lang/Number;"
+        );
+        assert_eq!(
+            signature(&classfile, &max.attributes),
+            "<T:Ljava/lang/Number;:Ljava/lang/Comparable<TT;>;>(Ljava/util/Sequence<+TT;>;)TT;"
+        );
+        let entry_set = &classfile.methods[2];
+        assert_eq!(
+            signature(&classfile, &entry_set.attributes),
+            "()Ljava/util/Set<Ljava/util/Pair<TK;TV;>;>;"
+        );
+        assert!(constant_pool
+            .classes()
+            .any(|(_, name)| name == "java/lang/Walkable"));
+    }
+
+    #[test]
+    fn test_rename_members() {
+        let data = include_bytes!("../../../../java/Insns.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        Renamer::new()
+            .rename_field("Insns", "counter", "I", "hits")
+            .rename_method("Insns", "lambda$misc$0", "()V", "bump")
+            // not what the class declares, so nothing changes
+            .rename_field("Insns", "total", "I", "amount")
+            .apply(&mut classfile)
+            .unwrap();
+        let constant_pool = &classfile.constant_pool;
+
+        let names = |indices: Vec<u16>| {
+            indices
+                .into_iter()
+                .map(|index| constant_pool.utf8(index).unwrap().into_owned())
+                .collect::<Vec<_>>()
+        };
+        let fields = classfile.fields.iter().map(|field| field.name_index);
+        assert_eq!(names(fields.collect()), ["hits", "total"]);
+        let methods = classfile.methods.iter().map(|method| method.name_index);
+        assert_eq!(names(methods.collect()), ["<init>", "misc", "sum", "bump"]);
+
+        let field_refs = constant_pool
+            .field_refs()
+            .map(|(_, field_ref)| field_ref.name.into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(field_refs, ["hits"]);
+        // the lambda's handle in BootstrapMethods
+        assert!(constant_pool
+            .method_refs()
+            .any(|(_, method_ref)| method_ref.class_name == "Insns" && method_ref.name == "bump"));
+        // invokedynamic's NameAndType names the interface method, not a member
+        assert!(constant_pool
+            .method_refs()
+            .all(|(_, method_ref)| method_ref.name != "lambda$misc$0"));
+    }
+
+    #[test]
+    fn test_rename_errors() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, mut lazy) = parse_classfile_lazy(data).unwrap();
+        assert!(matches!(
+            Renamer::new().apply(&mut lazy),
+            Err(RenameError::UndecodedAttribute(_))
+        ));
+
+        // a field whose descriptor is its name
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let name_index = classfile.fields[0].name_index;
+        classfile.fields[0].descriptor_index = name_index;
+        assert_eq!(
+            Renamer::new().apply(&mut classfile),
+            Err(RenameError::InvalidDescriptor(name_index))
+        );
+    }
+}
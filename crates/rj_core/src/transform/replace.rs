@@ -0,0 +1,363 @@
+use std::borrow::Cow;
+
+use super::error::ReplaceCodeError;
+use crate::analysis::locals::compute_max_locals;
+use crate::asm::frames::{compute_method_stack_map_table, SimpleClassHierarchy};
+use crate::asm::{compute_max_stack, disassemble, Instruction};
+use crate::class::{
+    Attribute, AttributeName, ClassAccessFlags, ClassFile, Code, MethodAccessFlags,
+    MethodDescriptor,
+};
+
+// lays the instructions out back to back, checking that every branch lands
+// on one of them
+fn lay_out(new_code: Vec<Instruction>) -> Result<Vec<(u32, Instruction)>, ReplaceCodeError> {
+    let mut instructions = Vec::with_capacity(new_code.len());
+    let mut pc = 0u32;
+    for instruction in new_code {
+        let len = instruction.byte_len(pc) as u32;
+        instructions.push((pc, instruction));
+        pc += len;
+    }
+    match pc {
+        0 => return Err(ReplaceCodeError::EmptyCode),
+        pc if pc > u16::MAX as u32 => return Err(ReplaceCodeError::CodeTooLarge(pc)),
+        _ => {}
+    }
+    for (pc, instruction) in &instructions {
+        for target in instruction.branch_targets(*pc) {
+            let lands = u32::try_from(target).is_ok_and(|target| {
+                instructions
+                    .binary_search_by_key(&target, |(pc, _)| *pc)
+                    .is_ok()
+            });
+            if !lands {
+                return Err(ReplaceCodeError::InvalidBranchTarget(*pc));
+            }
+        }
+    }
+    Ok(instructions)
+}
+
+// maps the offsets of the old code onto the new one, instruction by
+// instruction; the end of the code maps to the new end
+struct PcMap {
+    old: Vec<u32>,
+    new: Vec<u32>,
+}
+
+impl PcMap {
+    fn get(&self, pc: u16) -> Option<u16> {
+        let index = self.old.binary_search(&(pc as u32)).ok()?;
+        self.new.get(index).map(|pc| *pc as u16)
+    }
+}
+
+fn end_pc(instructions: &[(u32, Instruction)]) -> u32 {
+    instructions
+        .last()
+        .map_or(0, |(pc, instruction)| pc + instruction.byte_len(*pc) as u32)
+}
+
+fn offsets(instructions: &[(u32, Instruction)]) -> Vec<u32> {
+    instructions
+        .iter()
+        .map(|(pc, _)| *pc)
+        .chain(std::iter::once(end_pc(instructions)))
+        .collect()
+}
+
+// the code that the tables keyed by offset describe is gone
+fn drop_offsets(code: &mut Code<Attribute>) {
+    code.exception_table.clear();
+    code.attributes.retain(|attribute| {
+        !matches!(
+            attribute,
+            Attribute::LineNumberTable(_)
+                | Attribute::LocalVariableTable(_)
+                | Attribute::LocalVariableTypeTable(_)
+        )
+    });
+}
+
+fn map_range(map_pc: &dyn Fn(u16) -> Option<u16>, start: u16, length: u16) -> Option<(u16, u16)> {
+    let end = start.checked_add(length)?;
+    let (start, end) = (map_pc(start)?, map_pc(end)?);
+    Some((start, end.checked_sub(start)?))
+}
+
+// carries the exception table, line numbers and local variable tables over
+// to new code, dropping the entries whose offsets don't map
+pub(super) fn remap_offsets(code: &mut Code<Attribute>, map_pc: &dyn Fn(u16) -> Option<u16>) {
+    code.exception_table.retain_mut(|entry| {
+        let mapped = (
+            map_pc(entry.start_pc),
+            map_pc(entry.end_pc),
+            map_pc(entry.handler_pc),
+        );
+        let (Some(start_pc), Some(end_pc), Some(handler_pc)) = mapped else {
+            return false;
+        };
+        entry.start_pc = start_pc;
+        entry.end_pc = end_pc;
+        entry.handler_pc = handler_pc;
+        true
+    });
+    for attribute in &mut code.attributes {
+        match attribute {
+            Attribute::LineNumberTable(table) => {
+                table.line_number_table.retain_mut(|entry| {
+                    let Some(start_pc) = map_pc(entry.start_pc) else {
+                        return false;
+                    };
+                    entry.start_pc = start_pc;
+                    true
+                });
+            }
+            Attribute::LocalVariableTable(table) => {
+                table.local_variable_table.retain_mut(|entry| {
+                    let Some((start_pc, length)) = map_range(map_pc, entry.start_pc, entry.length)
+                    else {
+                        return false;
+                    };
+                    (entry.start_pc, entry.length) = (start_pc, length);
+                    true
+                });
+            }
+            Attribute::LocalVariableTypeTable(table) => {
+                table.local_variable_type_table.retain_mut(|entry| {
+                    let Some((start_pc, length)) = map_range(map_pc, entry.start_pc, entry.length)
+                    else {
+                        return false;
+                    };
+                    (entry.start_pc, entry.length) = (start_pc, length);
+                    true
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<'a> ClassFile<'a> {
+    // replaces the body of a method with `new_code`, laid out back to back
+    // with branch offsets relative to that layout. max_stack, max_locals and
+    // the StackMapTable are computed again. when the new code has as many
+    // instructions as the old, the n-th new instruction stands in for the
+    // n-th old one, and the exception table, line numbers and local variable
+    // tables follow it; otherwise they no longer apply and are dropped
+    pub fn replace_method_code(
+        &mut self,
+        name: &str,
+        descriptor: &str,
+        new_code: Vec<Instruction>,
+    ) -> Result<(), ReplaceCodeError> {
+        let constant_pool = &self.constant_pool;
+        let matches = |index: u16, value: &str| constant_pool.utf8(index).is_ok_and(|v| v == value);
+        let index = self
+            .methods
+            .iter()
+            .position(|method| {
+                matches(method.name_index, name) && matches(method.descriptor_index, descriptor)
+            })
+            .ok_or_else(|| ReplaceCodeError::MethodNotFound(name.into(), descriptor.into()))?;
+        let parsed = MethodDescriptor::parse(descriptor.as_bytes())
+            .map_err(|_| ReplaceCodeError::InvalidDescriptor(descriptor.into()))?;
+
+        let method = &mut self.methods[index];
+        let is_static = method.access_flags.contains(MethodAccessFlags::STATIC);
+        let code = method
+            .attributes
+            .iter_mut()
+            .find_map(|attribute| match attribute {
+                Attribute::Code(code) => Some(code),
+                _ => None,
+            })
+            .ok_or(ReplaceCodeError::MissingCode)?;
+
+        let old = disassemble(&code.code).collect::<Result<Vec<_>, _>>()?;
+        let instructions = lay_out(new_code)?;
+        let pc_map = (old.len() == instructions.len()).then(|| PcMap {
+            old: offsets(&old),
+            new: offsets(&instructions),
+        });
+        match &pc_map {
+            Some(pc_map) => remap_offsets(code, &|pc| pc_map.get(pc)),
+            None => drop_offsets(code),
+        }
+
+        let mut bytes = Vec::with_capacity(end_pc(&instructions) as usize);
+        for (pc, instruction) in &instructions {
+            instruction.encode(*pc, &mut bytes);
+        }
+        code.code = Cow::Owned(bytes);
+        code.max_stack = compute_max_stack(&instructions, &code.exception_table, constant_pool)?;
+        let max_locals = compute_max_locals(&instructions, &parsed, is_static);
+        // local variable tables that were kept may still name slots the new
+        // code leaves alone
+        code.max_locals = match pc_map {
+            Some(_) => max_locals.max(code.max_locals),
+            None => max_locals,
+        };
+        code.attributes
+            .retain(|attribute| attribute.attribute_name() != Some(AttributeName::StackMapTable));
+
+        // all that's known of the hierarchy is this class; other classes are
+        // taken to extend Object
+        let mut hierarchy = SimpleClassHierarchy::new();
+        if let Ok(this_class) = constant_pool.class_name(self.this_class) {
+            let super_class = constant_pool.class_name(self.super_class).ok();
+            hierarchy.add_class(
+                &this_class,
+                super_class.as_deref(),
+                self.access_flags.contains(ClassAccessFlags::INTERFACE),
+            );
+        }
+        compute_method_stack_map_table(self, index, &hierarchy)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::{parse_classfile, LineNumberTableEntry};
+
+    fn method_code<'c>(classfile: &'c ClassFile, name: &str) -> &'c Code<'c, Attribute<'c>> {
+        let method = classfile
+            .methods
+            .iter()
+            .find(|method| classfile.constant_pool.utf8(method.name_index).unwrap() == name)
+            .unwrap();
+        method.code().unwrap()
+    }
+
+    fn instructions(classfile: &ClassFile, name: &str) -> Vec<Instruction> {
+        disassemble(&method_code(classfile, name).code)
+            .map(|result| result.unwrap().1)
+            .collect()
+    }
+
+    #[test]
+    fn test_replace_unchanged() {
+        let classes: [&[u8]; 2] = [
+            include_bytes!("../../../../java/Frames.class"),
+            include_bytes!("../../../../java/HelloWorld.class"),
+        ];
+        for data in classes {
+            let (_, mut classfile) = parse_classfile(data).unwrap();
+            for index in 0..classfile.methods.len() {
+                let method = &classfile.methods[index];
+                let name = classfile.constant_pool.utf8(method.name_index).unwrap();
+                let descriptor = classfile.constant_pool.utf8(method.descriptor_index);
+                let (name, descriptor) = (name.into_owned(), descriptor.unwrap().into_owned());
+                let code = instructions(&classfile, &name);
+                classfile
+                    .replace_method_code(&name, &descriptor, code)
+                    .unwrap();
+            }
+            assert_eq!(classfile.to_bytes().unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_replace_in_place() {
+        let data = include_bytes!("../../../../java/Insns.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let descriptor = "(LInsns$Greeter;I)Ljava/lang/Object;";
+        let old = method_code(&classfile, "misc");
+        let old_length = old.code.len();
+        let old_handlers = old
+            .exception_table
+            .iter()
+            .map(|entry| (entry.start_pc, entry.end_pc, entry.handler_pc))
+            .collect::<Vec<_>>();
+
+        // a wide iload_1 pushes everything after it one byte along
+        let mut code = instructions(&classfile, "misc");
+        assert_eq!(code[0], Instruction::Iload1);
+        code[0] = Instruction::Iload(1);
+        classfile
+            .replace_method_code("misc", descriptor, code)
+            .unwrap();
+
+        let new = method_code(&classfile, "misc");
+        assert_eq!(new.code.len(), old_length + 1);
+        assert_eq!((new.max_stack, new.max_locals), (3, 16));
+        let handlers = new
+            .exception_table
+            .iter()
+            .map(|entry| (entry.start_pc - 1, entry.end_pc - 1, entry.handler_pc - 1))
+            .collect::<Vec<_>>();
+        assert_eq!(handlers, old_handlers);
+        let Some(Attribute::LineNumberTable(table)) = new.attributes.first() else {
+            panic!("expected a LineNumberTable");
+        };
+        assert_eq!(
+            table.line_number_table[..2],
+            [
+                LineNumberTableEntry {
+                    start_pc: 0,
+                    line_number: 7
+                },
+                LineNumberTableEntry {
+                    start_pc: 5,
+                    line_number: 8
+                },
+            ]
+        );
+        let bytes = classfile.to_bytes().unwrap();
+        assert_eq!(parse_classfile(&bytes).unwrap().1, classfile);
+    }
+
+    #[test]
+    fn test_replace_body() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        classfile
+            .replace_method_code("main", "([Ljava/lang/String;)V", vec![Instruction::Return])
+            .unwrap();
+        let code = method_code(&classfile, "main");
+        assert_eq!(*code.code, [0xb1]);
+        assert_eq!((code.max_stack, code.max_locals), (0, 1));
+        assert!(code.attributes.is_empty());
+    }
+
+    #[test]
+    fn test_replace_errors() {
+        let data = include_bytes!("../../../../java/Shape.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        assert_eq!(
+            classfile.replace_method_code("area", "()D", vec![Instruction::Dconst0]),
+            Err(ReplaceCodeError::MissingCode)
+        );
+        assert_eq!(
+            classfile.replace_method_code("area", "()I", vec![Instruction::Iconst0]),
+            Err(ReplaceCodeError::MethodNotFound(
+                "area".into(),
+                "()I".into()
+            ))
+        );
+        assert_eq!(
+            classfile.replace_method_code("sides", "()I", vec![]),
+            Err(ReplaceCodeError::EmptyCode)
+        );
+        // lands in the middle of the goto itself
+        assert_eq!(
+            classfile.replace_method_code(
+                "sides",
+                "()I",
+                vec![
+                    Instruction::Goto(1),
+                    Instruction::Iconst0,
+                    Instruction::Ireturn
+                ]
+            ),
+            Err(ReplaceCodeError::InvalidBranchTarget(0))
+        );
+        assert!(matches!(
+            classfile.replace_method_code("sides", "()I", vec![Instruction::Ireturn]),
+            Err(ReplaceCodeError::MaxStackError(_))
+        ));
+    }
+}
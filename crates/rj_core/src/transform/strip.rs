@@ -0,0 +1,246 @@
+use super::error::RemapError;
+use super::remap::ConstantPoolRemapper;
+use crate::class::{Attribute, AttributeName, ClassFile};
+
+// which attributes `ClassFile::strip` removes. none of them change how the
+// class runs, only what debuggers, stack traces and reflection see
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StripOptions {
+    // LineNumberTable, so stack traces lose their line numbers
+    pub line_numbers: bool,
+    // LocalVariableTable and LocalVariableTypeTable
+    pub local_variables: bool,
+    // SourceFile and SourceDebugExtension
+    pub source_file: bool,
+    // the visible and invisible annotations of the class, its members,
+    // parameters, record components and the types in its code. an
+    // annotation interface's AnnotationDefault is kept
+    pub annotations: bool,
+}
+
+impl StripOptions {
+    // what `javac -g:none` leaves out
+    pub const DEBUG: StripOptions = StripOptions {
+        line_numbers: true,
+        local_variables: true,
+        source_file: true,
+        annotations: false,
+    };
+
+    pub const ALL: StripOptions = StripOptions {
+        line_numbers: true,
+        local_variables: true,
+        source_file: true,
+        annotations: true,
+    };
+
+    fn strips(&self, attribute: &Attribute) -> bool {
+        let name = match attribute {
+            Attribute::Unknown { name, .. }
+            | Attribute::Raw { name, .. }
+            | Attribute::Custom { name, .. } => name,
+            attribute => match attribute.attribute_name() {
+                Some(name) => name.as_str().as_bytes(),
+                None => return false,
+            },
+        };
+        if name == b"SourceDebugExtension" {
+            return self.source_file;
+        }
+        match AttributeName::from_name(name) {
+            Some(AttributeName::LineNumberTable) => self.line_numbers,
+            Some(AttributeName::LocalVariableTable | AttributeName::LocalVariableTypeTable) => {
+                self.local_variables
+            }
+            Some(AttributeName::SourceFile) => self.source_file,
+            Some(
+                AttributeName::RuntimeVisibleAnnotations
+                | AttributeName::RuntimeInvisibleAnnotations
+                | AttributeName::RuntimeVisibleParameterAnnotations
+                | AttributeName::RuntimeInvisibleParameterAnnotations
+                | AttributeName::RuntimeVisibleTypeAnnotations
+                | AttributeName::RuntimeInvisibleTypeAnnotations,
+            ) => self.annotations,
+            _ => false,
+        }
+    }
+
+    fn strip_attributes(&self, attributes: &mut Vec<Attribute>) {
+        attributes.retain(|attribute| !self.strips(attribute));
+        for attribute in attributes {
+            match attribute {
+                Attribute::Code(code) => self.strip_attributes(&mut code.attributes),
+                Attribute::Record(record) => {
+                    for component in &mut record.components {
+                        self.strip_attributes(&mut component.attributes);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Default for StripOptions {
+    fn default() -> Self {
+        Self::DEBUG
+    }
+}
+
+impl ClassFile<'_> {
+    // removes the attributes `options` selects, then the constants only they
+    // referred to. fails like `ConstantPoolRemapper::apply` on attributes
+    // whose references can't be followed
+    pub fn strip(&mut self, options: StripOptions) -> Result<(), RemapError> {
+        options.strip_attributes(&mut self.attributes);
+        for field in &mut self.fields {
+            options.strip_attributes(&mut field.attributes);
+        }
+        for method in &mut self.methods {
+            options.strip_attributes(&mut method.attributes);
+        }
+        ConstantPoolRemapper::new()
+            .remove_unused()
+            .apply(self)
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::class::{parse_classfile, Constant};
+
+    fn names(attributes: &[Attribute], out: &mut Vec<String>) {
+        for attribute in attributes {
+            match attribute {
+                Attribute::Unknown { name, .. } => {
+                    out.push(String::from_utf8_lossy(name).into_owned())
+                }
+                attribute => out.push(attribute.attribute_name().unwrap().as_str().into()),
+            }
+            if let Attribute::Code(code) = attribute {
+                names(&code.attributes, out);
+            }
+        }
+    }
+
+    fn attribute_names(classfile: &ClassFile) -> Vec<String> {
+        let mut out = Vec::new();
+        names(&classfile.attributes, &mut out);
+        for field in &classfile.fields {
+            names(&field.attributes, &mut out);
+        }
+        for method in &classfile.methods {
+            names(&method.attributes, &mut out);
+        }
+        out.sort();
+        out.dedup();
+        out
+    }
+
+    fn has_utf8(classfile: &ClassFile, value: &str) -> bool {
+        classfile.constant_pool.find_utf8(value).is_some()
+    }
+
+    #[test]
+    fn test_strip_debug() {
+        let data = include_bytes!("../../../../java/Debug.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        assert!(has_utf8(&classfile, "greeting"));
+        let length = classfile.constant_pool.len();
+        classfile.strip(StripOptions::DEBUG).unwrap();
+
+        assert_eq!(
+            attribute_names(&classfile),
+            [
+                "BootstrapMethods",
+                "Code",
+                "InnerClasses",
+                "Signature",
+                "StackMapTable"
+            ]
+        );
+        for name in [
+            "LineNumberTable",
+            "LocalVariableTable",
+            "LocalVariableTypeTable",
+            "SourceFile",
+            "Debug.java",
+            "greeting",
+            "Ljava/util/List<Ljava/lang/Integer;>;",
+        ] {
+            assert!(!has_utf8(&classfile, name), "{name}");
+        }
+        // what the code still refers to stays
+        assert!(has_utf8(&classfile, "total"));
+        assert!(classfile.constant_pool.len() < length);
+        let bytes = classfile.to_bytes().unwrap();
+        assert!(bytes.len() < data.len());
+        assert_eq!(parse_classfile(&bytes).unwrap().1, classfile);
+    }
+
+    #[test]
+    fn test_strip_annotations() {
+        let data = include_bytes!("../../../../java/Annotated.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        classfile.strip(StripOptions::DEBUG).unwrap();
+        assert!(attribute_names(&classfile).contains(&"RuntimeVisibleAnnotations".into()));
+        assert!(has_utf8(&classfile, "LAnnotated$Info;"));
+
+        classfile.strip(StripOptions::ALL).unwrap();
+        assert_eq!(
+            attribute_names(&classfile),
+            [
+                "Code",
+                "Deprecated",
+                "InnerClasses",
+                "NestMembers",
+                "Signature"
+            ]
+        );
+        for name in [
+            "LAnnotated$Info;",
+            "LAnnotated$Hidden;",
+            "LAnnotated$Typed;",
+        ] {
+            assert!(!has_utf8(&classfile, name), "{name}");
+        }
+        // the nest and inner classes still name the annotation interfaces
+        let mut classes = classfile
+            .constant_pool
+            .iter()
+            .filter(|(_, constant)| matches!(constant, Constant::Class { .. }))
+            .map(|(index, _)| classfile.constant_pool.class_name(index).unwrap())
+            .collect::<Vec<_>>();
+        classes.sort();
+        assert_eq!(
+            classes,
+            [
+                "Annotated",
+                "Annotated$Hidden",
+                "Annotated$Info",
+                "Annotated$Marker",
+                "Annotated$Typed",
+                "java/lang/Object",
+                "java/util/List"
+            ]
+        );
+        let bytes = classfile.to_bytes().unwrap();
+        assert_eq!(parse_classfile(&bytes).unwrap().1, classfile);
+    }
+
+    #[test]
+    fn test_strip_nothing() {
+        let data = include_bytes!("../../../../java/HelloWorld.class");
+        let (_, mut classfile) = parse_classfile(data).unwrap();
+        let options = StripOptions {
+            line_numbers: false,
+            local_variables: false,
+            source_file: false,
+            annotations: false,
+        };
+        classfile.strip(options).unwrap();
+        assert_eq!(classfile.to_bytes().unwrap(), data);
+    }
+}
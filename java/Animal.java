@@ -0,0 +1,89 @@
+public class Animal {
+    static int count;
+
+    int legs;
+    long id;
+
+    Animal(int legs) {
+        this.legs = legs;
+        id = ++count;
+    }
+
+    int legs() {
+        return legs;
+    }
+
+    int sound() {
+        return 0;
+    }
+
+    private int secret() {
+        return 7;
+    }
+
+    static int census() {
+        Animal[] animals = {new Dog(), new Bird(), new Animal(6)};
+        int total = 0;
+        for (Animal animal : animals) {
+            total += animal.legs() * 10 + animal.sound();
+        }
+        return total;
+    }
+
+    static int pet() {
+        Pet pet = new Dog();
+        return pet.cuteness() + new Animal(1).secret();
+    }
+
+    static int puppy() {
+        Animal puppy = new Puppy();
+        return puppy.sound() + ((Pet) puppy).cuteness();
+    }
+
+    interface Pet {
+        int tricks();
+
+        default int cuteness() {
+            return tricks() * 2;
+        }
+    }
+
+    static class Dog extends Animal implements Pet {
+        int tricks = 3;
+
+        Dog() {
+            super(4);
+        }
+
+        @Override
+        int sound() {
+            return 1;
+        }
+
+        public int tricks() {
+            return tricks;
+        }
+    }
+
+    static class Bird extends Animal {
+        // shadows Animal.legs
+        int legs = 99;
+        double wingspan = 0.5;
+
+        Bird() {
+            super(2);
+        }
+
+        @Override
+        int sound() {
+            return 2;
+        }
+    }
+
+    static class Puppy extends Dog {
+        @Override
+        int sound() {
+            return super.sound() + 10;
+        }
+    }
+}
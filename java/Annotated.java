@@ -0,0 +1,40 @@
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.lang.annotation.Target;
+import java.util.List;
+
+@Annotated.Info(name = "type", tags = {"a", "b"}, kind = ElementType.TYPE, type = String.class, nested = @Annotated.Marker)
+@Annotated.Hidden
+public class Annotated {
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Info {
+        String name() default "none";
+        int count() default 1;
+        String[] tags() default {};
+        ElementType kind() default ElementType.FIELD;
+        Class<?> type() default Object.class;
+        Marker nested() default @Marker;
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Marker {}
+
+    @interface Hidden {}
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @Target({ElementType.TYPE_USE, ElementType.TYPE_PARAMETER})
+    @interface Typed {}
+
+    @Deprecated
+    @Info(count = 3)
+    int field;
+
+    @Hidden
+    public void method(@Marker int a, int b, @Hidden @Marker String c) {}
+
+    public <@Typed T> List<@Typed String> typed(@Typed Object o) {
+        @Typed String s = "x";
+        return List.of(s);
+    }
+}
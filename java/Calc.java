@@ -0,0 +1,60 @@
+public class Calc {
+    static int add(int a, int b) {
+        return a + b;
+    }
+
+    static int max(int a, int b) {
+        return a > b ? a : b;
+    }
+
+    static long factorial(int n) {
+        long result = 1;
+        for (int i = 2; i <= n; i++) {
+            result *= i;
+        }
+        return result;
+    }
+
+    static int collatz(int n) {
+        int steps = 0;
+        while (n != 1) {
+            n = n % 2 == 0 ? n / 2 : 3 * n + 1;
+            steps++;
+        }
+        return steps;
+    }
+
+    static int dense(int n) {
+        switch (n) {
+            case 1: return 10;
+            case 2: return 20;
+            case 3: return 30;
+            default: return -1;
+        }
+    }
+
+    static int sparse(int n) {
+        switch (n) {
+            case -100: return 1;
+            case 0: return 2;
+            case 1000: return 3;
+            default: return 0;
+        }
+    }
+
+    static int compare(double a, double b) {
+        return a < b ? -1 : a > b ? 1 : 0;
+    }
+
+    static double mean(long a, float b, double c) {
+        return (a + b + c) / 3;
+    }
+
+    static int narrow(int n) {
+        return (byte) n + (char) n + (short) n;
+    }
+
+    static int divide(int a, int b) {
+        return a / b;
+    }
+}
@@ -0,0 +1,14 @@
+public class Constants {
+    public static final int ANSWER = 42;
+    static final String GREETING = "hi \"there\"";
+    static final float RATIO = 1.5f;
+    static final char LETTER = 'x';
+    static final boolean FLAG = true;
+    static final short SMALL = -7;
+    static final byte BYTE = 3;
+    final int instance = 5;
+    static int notConstant = 1;
+    static final long BIG = 1L << 40;
+    static final double HALF = 0.25;
+    static final String AFTER = "after";
+}
@@ -0,0 +1,35 @@
+public class Counter {
+    static int base = 10;
+    static int initialized;
+
+    static {
+        initialized++;
+    }
+
+    private int value;
+
+    Counter(int start) {
+        value = base + start;
+    }
+
+    int next() {
+        return ++value;
+    }
+
+    static int count(int times) {
+        Counter counter = new Counter(0);
+        int last = 0;
+        for (int i = 0; i < times; i++) {
+            last = counter.next();
+        }
+        return last;
+    }
+
+    static int fib(int n) {
+        return n < 2 ? n : fib(n - 1) + fib(n - 2);
+    }
+
+    static int recurse(int depth) {
+        return recurse(depth + 1);
+    }
+}
@@ -0,0 +1,18 @@
+import java.util.List;
+
+// compiled with `javac -g` for the local variable tables
+public class Debug {
+
+    public static int total(List<Integer> values) {
+        int sum = 0;
+        for (int value : values) {
+            sum += value;
+        }
+        return sum;
+    }
+
+    public String greet(String name) {
+        String greeting = "Hello, " + name;
+        return greeting;
+    }
+}
@@ -0,0 +1,40 @@
+public class Frames {
+
+    private int base;
+
+    public static int choose(int a, int b) {
+        int r;
+        if (a > b) {
+            r = a;
+        } else {
+            r = b;
+        }
+        return r;
+    }
+
+    public int sum(int n) {
+        int total = base;
+        int i = 0;
+        while (i < n) {
+            total += i;
+            i++;
+        }
+        return total;
+    }
+
+    public static int parse(String s) {
+        try {
+            return Integer.parseInt(s);
+        } catch (NumberFormatException e) {
+            return -1;
+        }
+    }
+
+    public static long twice(long value, boolean flag) {
+        long result = value;
+        if (flag) {
+            result = value + value;
+        }
+        return result;
+    }
+}
@@ -0,0 +1,30 @@
+import java.util.AbstractMap;
+import java.util.Iterator;
+import java.util.List;
+import java.util.Map;
+import java.util.Set;
+
+public class Generics<K extends Comparable<K>, V> extends AbstractMap<K, V> implements Iterable<K> {
+    List<String> names;
+    Map<K, ? extends V> map;
+    List<?> any;
+    List<? super Integer> sink;
+    V[] values;
+    int plain;
+
+    public <T extends Number & Comparable<T>> T max(List<? extends T> xs) throws IllegalStateException {
+        return null;
+    }
+
+    public Set<Map.Entry<K, V>> entrySet() {
+        return null;
+    }
+
+    public Iterator<K> iterator() {
+        return null;
+    }
+
+    <T> T[] first(T[] xs) {
+        return xs;
+    }
+}
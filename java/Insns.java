@@ -0,0 +1,36 @@
+public class Insns {
+    interface Greeter { String greet(String name); }
+    static int counter;
+    long total;
+
+    public static Object misc(Greeter greeter, int n) throws Exception {
+        int[] ints = new int[n];
+        String[] strings = new String[3];
+        int[][] grid = new int[2][n];
+        Runnable r = () -> counter++;
+        r.run();
+        String s = greeter.greet("x");
+        Object o = strings;
+        if (o instanceof String[]) {
+            strings = (String[]) o;
+        }
+        byte b = 100;
+        short sh = 1000;
+        float f = 2.5f;
+        int big = 123456;
+        char c = 'c';
+        boolean[] flags = new boolean[1];
+        synchronized (o) {
+            counter += ints.length + grid.length + b + sh + (int) f + big + c;
+        }
+        return flags;
+    }
+
+    public static int sum() {
+        int a0 = 0, a1 = 1, a2 = 2, a3 = 3, a4 = 4, a5 = 5, a6 = 6, a7 = 7, a8 = 8, a9 = 9;
+        int[] pad = new int[0];
+        long x = 0;
+        for (int i = 0; i < 300; i++) { x += i; }
+        return (int) x + a0 + a1 + a2 + a3 + a4 + a5 + a6 + a7 + a8 + a9 + pad.length;
+    }
+}
@@ -0,0 +1,26 @@
+public class Locals {
+
+    private int count;
+
+    public static long sum(long a, int b, double c) {
+        double total = a + b + c;
+        return (long) total;
+    }
+
+    public int loop(int n) {
+        int result = 0;
+        for (int i = 0; i < n; i++) {
+            result += i;
+        }
+        return result;
+    }
+
+    public void increment() {
+        count++;
+    }
+
+    public static void main(String[] args) {
+        long value = sum(1L, 2, 1.0);
+        System.out.println(value);
+    }
+}
@@ -0,0 +1,58 @@
+public class Matrix {
+    static final int SIZE = 3;
+    static final long SEED = 1L << 40;
+    static int calls;
+
+    static int trace() {
+        calls++;
+        int[][] cells = new int[SIZE][SIZE];
+        for (int i = 0; i < SIZE; i++) {
+            cells[i][i] = i + 1;
+        }
+        int sum = 0;
+        for (int[] row : cells) {
+            for (int cell : row) {
+                sum += cell;
+            }
+        }
+        return sum;
+    }
+
+    static int jagged() {
+        int[][] rows = new int[3][];
+        rows[1] = new int[2];
+        return rows[0] == null ? rows[1].length : -1;
+    }
+
+    static long primitives() {
+        byte[] bytes = {(byte) 200};
+        char[] chars = {'a'};
+        short[] shorts = {-1};
+        boolean[] booleans = {true};
+        long[] longs = {SEED};
+        float[] floats = {0.5f};
+        double[] doubles = {0.25};
+        return bytes[0] + chars[0] + shorts[0] + (booleans[0] ? 1 : 0) + longs[0]
+            + (long) (floats[0] * 4) + (long) (doubles[0] * 8);
+    }
+
+    static int objects() {
+        Object[] flat = new Object[4];
+        Object[][] nested = new Object[2][3];
+        flat[1] = nested;
+        return flat.length + nested[1].length + (flat[1] == nested ? 10 : 0);
+    }
+
+    static int element(int index) {
+        int[] values = new int[2];
+        return values[index];
+    }
+
+    static int length(int[] values) {
+        return values.length;
+    }
+
+    static int allocate(int size) {
+        return new int[size].length;
+    }
+}
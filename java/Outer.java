@@ -0,0 +1,9 @@
+public class Outer {
+    static class Nested {}
+
+    class Inner {}
+
+    Object anonymous() {
+        return new Object() {};
+    }
+}
@@ -0,0 +1,9 @@
+public class Overloads {
+    static int twice(int x) {
+        return x * 2;
+    }
+
+    static long twice(long x) {
+        return x * 2;
+    }
+}
@@ -0,0 +1,20 @@
+import java.util.List;
+
+// equals, hashCode and toString are spelled out so javac doesn't emit an
+// invokedynamic ObjectMethods bootstrap
+public record Point<T>(int x, int y, List<T> tags) {
+    @Override
+    public boolean equals(Object o) {
+        return o == this;
+    }
+
+    @Override
+    public int hashCode() {
+        return x;
+    }
+
+    @Override
+    public String toString() {
+        return "Point";
+    }
+}
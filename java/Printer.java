@@ -0,0 +1,21 @@
+public class Printer {
+    static final String NAME = "rj";
+
+    static boolean same() {
+        String a = "text";
+        String b = "text";
+        return a == b;
+    }
+
+    public static void main(String[] args) {
+        System.out.println(NAME);
+        System.out.print('a');
+        System.out.print(true);
+        System.out.println(-42);
+        System.out.println(1L << 40);
+        System.out.println();
+        String missing = null;
+        System.out.println(missing);
+        System.out.print("caf\u00e9 \ud83d\ude00\n");
+    }
+}
@@ -0,0 +1,16 @@
+public interface Shape {
+
+    double area();
+
+    default String describe() {
+        return "shape";
+    }
+
+    static int sides() {
+        return 0;
+    }
+
+    private int helper() {
+        return 1;
+    }
+}
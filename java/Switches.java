@@ -0,0 +1,34 @@
+public class Switches {
+    public static int dense(int x) {
+        switch (x) {
+            case 1: return 10;
+            case 2: return 20;
+            case 3: return 30;
+            case 4: return 40;
+            default: return 0;
+        }
+    }
+
+    public static int sparse(int x) {
+        switch (x) {
+            case -100: return 1;
+            case 7: return 2;
+            case 100000: return 3;
+            default: return 4;
+        }
+    }
+
+    public static int both(int x, int y) {
+        int r = 0;
+        switch (x) {
+            case 0: r = 1; break;
+            case 1: r = 2; break;
+            case 2: r = 3; break;
+        }
+        switch (y) {
+            case 10: r += 1; break;
+            case 1000: r += 2; break;
+        }
+        return r;
+    }
+}
@@ -0,0 +1,71 @@
+public class Thrower {
+    static int depth;
+
+    static void fail(int code) {
+        throw new Oops("failed", code);
+    }
+
+    static int nested(int n) {
+        depth = n;
+        if (n == 0) {
+            fail(7);
+        }
+        return nested(n - 1);
+    }
+
+    static int caught() {
+        try {
+            nested(3);
+            return -1;
+        } catch (Oops oops) {
+            return oops.code * 10 + depth;
+        }
+    }
+
+    static int rethrown() {
+        int result = 0;
+        try {
+            try {
+                fail(1);
+            } finally {
+                result += 10;
+            }
+        } catch (RuntimeException e) {
+            result += 1;
+        }
+        return result;
+    }
+
+    static int checked() {
+        try {
+            throw new Checked("checked");
+        } catch (Oops e) {
+            return 1;
+        } catch (Exception e) {
+            return e.getMessage() == "checked" ? 2 : 3;
+        }
+    }
+
+    static void uncaught() {
+        try {
+            nested(1);
+        } finally {
+            depth = -1;
+        }
+    }
+
+    static class Oops extends RuntimeException {
+        int code;
+
+        Oops(String message, int code) {
+            super(message);
+            this.code = code;
+        }
+    }
+
+    static class Checked extends Exception {
+        Checked(String message) {
+            super(message);
+        }
+    }
+}
@@ -0,0 +1,5 @@
+public sealed class Vehicle permits Vehicle.Car, Vehicle.Truck {
+    static final class Car extends Vehicle {}
+
+    static non-sealed class Truck extends Vehicle {}
+}
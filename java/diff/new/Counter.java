@@ -0,0 +1,16 @@
+public class Counter {
+    private int count;
+    private int step = 1;
+
+    public synchronized void increment() {
+        count += step;
+    }
+
+    public int get() {
+        return count;
+    }
+
+    public String name() {
+        return "Counter";
+    }
+}
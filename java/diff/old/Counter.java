@@ -0,0 +1,19 @@
+public class Counter {
+    private int count;
+
+    public void increment() {
+        count++;
+    }
+
+    public int get() {
+        return count;
+    }
+
+    public void reset() {
+        count = 0;
+    }
+
+    public String name() {
+        return "counter";
+    }
+}